@@ -0,0 +1,160 @@
+// emcy.rs - Emergency (EMCY) protocol helpers
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Base COB-ID of the emergency messages a node sends (0x080 + node ID)
+pub const EMCY_COB_ID_BASE: u16 = 0x080;
+
+/// Decoded emergency message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmcyMessage {
+    /// Emergency error code (CiA 301 table 21, or a device profile's codes)
+    pub error_code: u16,
+    /// Error register (0x1001) at the time of the emergency
+    pub error_register: u8,
+    /// Manufacturer-specific error field
+    pub manufacturer_data: [u8; 5],
+}
+
+impl EmcyMessage {
+    /// Error code 0x0000: the node left the error state
+    pub fn is_reset(&self) -> bool {
+        self.error_code == 0x0000
+    }
+
+    /// Description of the error code from its CiA 301 class
+    pub fn description(&self) -> &'static str {
+        error_code_description(self.error_code)
+    }
+}
+
+impl fmt::Display for EmcyMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:04X} {} (error register 0x{:02X})", self.error_code, self.description(), self.error_register)
+    }
+}
+
+/// Parse the data of an emergency frame
+///
+/// Emergency frames carry 8 bytes; shorter frames are accepted with the missing bytes
+/// as zero, as long as the error code and error register are present.
+pub fn parse_emcy_frame(data: &[u8]) -> Option<EmcyMessage> {
+    if data.len() < 3 {
+        return None;
+    }
+    let mut manufacturer_data = [0u8; 5];
+    let extra = &data[3..data.len().min(8)];
+    manufacturer_data[..extra.len()].copy_from_slice(extra);
+
+    Some(EmcyMessage {
+        error_code: u16::from_le_bytes([data[0], data[1]]),
+        error_register: data[2],
+        manufacturer_data,
+    })
+}
+
+/// Description of an emergency error code, from the most specific CiA 301 class it belongs to
+pub fn error_code_description(error_code: u16) -> &'static str {
+    match error_code {
+        0x0000 => "Error reset or no error",
+        0x1000..=0x10FF => "Generic error",
+        0x2000..=0x20FF => "Current",
+        0x2100..=0x21FF => "Current, device input side",
+        0x2200..=0x22FF => "Current inside the device",
+        0x2300..=0x23FF => "Current, device output side",
+        0x3000..=0x30FF => "Voltage",
+        0x3100..=0x31FF => "Mains voltage",
+        0x3200..=0x32FF => "Voltage inside the device",
+        0x3300..=0x33FF => "Output voltage",
+        0x4000..=0x40FF => "Temperature",
+        0x4100..=0x41FF => "Ambient temperature",
+        0x4200..=0x42FF => "Device temperature",
+        0x5000..=0x50FF => "Device hardware",
+        0x6000..=0x60FF => "Device software",
+        0x6100..=0x61FF => "Internal software",
+        0x6200..=0x62FF => "User software",
+        0x6300..=0x63FF => "Data set",
+        0x7000..=0x70FF => "Additional modules",
+        0x8000..=0x80FF => "Monitoring",
+        0x8110 => "CAN overrun (objects lost)",
+        0x8120 => "CAN in error passive mode",
+        0x8130 => "Life guard error or heartbeat error",
+        0x8140 => "Recovered from bus off",
+        0x8150 => "CAN-ID collision",
+        0x8100..=0x81FF => "Communication",
+        0x8210 => "PDO not processed due to length error",
+        0x8220 => "PDO length exceeded",
+        0x8230 => "DAM MPDO not processed, destination object not available",
+        0x8240 => "Unexpected SYNC data length",
+        0x8250 => "RPDO timeout",
+        0x8200..=0x82FF => "Protocol error",
+        0x9000..=0x90FF => "External error",
+        0xF000..=0xF0FF => "Additional functions",
+        0xFF00..=0xFFFF => "Device specific",
+        _ => "Unknown error code",
+    }
+}
+
+/// Names of the bits set in an error register (0x1001)
+pub fn error_register_bits(error_register: u8) -> Vec<&'static str> {
+    const BITS: [&str; 8] = [
+        "Generic",
+        "Current",
+        "Voltage",
+        "Temperature",
+        "Communication",
+        "Device profile specific",
+        "Reserved",
+        "Manufacturer specific",
+    ];
+    BITS.iter()
+        .enumerate()
+        .filter(|(bit, _)| error_register & (1 << bit) != 0)
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_emcy_frame() {
+        let message = parse_emcy_frame(&[0x10, 0x23, 0x03, 0x01, 0x02, 0x03, 0x04, 0x05]).unwrap();
+        assert_eq!(message.error_code, 0x2310);
+        assert_eq!(message.error_register, 0x03);
+        assert_eq!(message.manufacturer_data, [0x01, 0x02, 0x03, 0x04, 0x05]);
+        assert!(!message.is_reset());
+        assert_eq!(message.to_string(), "0x2310 Current, device output side (error register 0x03)");
+
+        // Short frames are padded, bytes beyond 8 are ignored
+        let short = parse_emcy_frame(&[0x00, 0x00, 0x00, 0xAA]).unwrap();
+        assert!(short.is_reset());
+        assert_eq!(short.manufacturer_data, [0xAA, 0x00, 0x00, 0x00, 0x00]);
+        let long = parse_emcy_frame(&[0x30, 0x81, 0x11, 1, 2, 3, 4, 5, 6]).unwrap();
+        assert_eq!(long.manufacturer_data, [1, 2, 3, 4, 5]);
+
+        assert_eq!(parse_emcy_frame(&[0x10, 0x23]), None);
+        assert_eq!(parse_emcy_frame(&[]), None);
+    }
+
+    #[test]
+    fn test_error_code_description() {
+        assert_eq!(error_code_description(0x0000), "Error reset or no error");
+        assert_eq!(error_code_description(0x4210), "Device temperature");
+        // Specific codes take precedence over their class
+        assert_eq!(error_code_description(0x8130), "Life guard error or heartbeat error");
+        assert_eq!(error_code_description(0x8101), "Communication");
+        assert_eq!(error_code_description(0x8250), "RPDO timeout");
+        assert_eq!(error_code_description(0x82FF), "Protocol error");
+        assert_eq!(error_code_description(0xFF42), "Device specific");
+        assert_eq!(error_code_description(0xA000), "Unknown error code");
+    }
+
+    #[test]
+    fn test_error_register_bits() {
+        assert!(error_register_bits(0x00).is_empty());
+        assert_eq!(error_register_bits(0x01), ["Generic"]);
+        assert_eq!(error_register_bits(0x91), ["Generic", "Communication", "Manufacturer specific"]);
+    }
+}
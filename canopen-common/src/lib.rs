@@ -5,10 +5,18 @@
 //!
 //! This library provides:
 //! - SDO (Service Data Object) protocol encoding/decoding
+//! - NMT (Network Management) command frames and heartbeat states
+//! - SYNC frames
+//! - Emergency (EMCY) message decoding
+//! - Layer Setting Services (LSS) requests and responses
 //! - Common data types and error handling
 //! - Frame parsing utilities
 
 pub mod sdo;
+pub mod nmt;
+pub mod sync;
+pub mod emcy;
+pub mod lss;
 
 // Re-export commonly used types for convenience
 pub use sdo::{
@@ -17,3 +25,7 @@ pub use sdo::{
     parse_sdo_response, parse_sdo_write_response, parse_payload,
     get_abort_code_description, SdoCommand
 };
+pub use nmt::{NmtCommand, NmtState, create_nmt_frame, NMT_BROADCAST_NODE_ID, HEARTBEAT_COB_ID_BASE};
+pub use sync::{create_sync_frame, SYNC_COB_ID};
+pub use emcy::{EmcyMessage, parse_emcy_frame, error_code_description, error_register_bits, EMCY_COB_ID_BASE};
+pub use lss::{LssAddress, LssCommand, LssResponse, create_lss_frames, parse_lss_response, LSS_BITRATES, LSS_MASTER_COB_ID, LSS_SLAVE_COB_ID};
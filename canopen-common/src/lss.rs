@@ -0,0 +1,245 @@
+// lss.rs - Layer Setting Services (LSS, CiA 305) protocol helpers
+use socketcan::{CanFrame, StandardId};
+use socketcan::EmbeddedFrame as Frame;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// COB-ID of the requests from the LSS master
+pub const LSS_MASTER_COB_ID: u16 = 0x7E5;
+
+/// COB-ID of the responses from the LSS slaves
+pub const LSS_SLAVE_COB_ID: u16 = 0x7E4;
+
+/// Bitrates of the CiA 301 bit timing table, by table index
+pub const LSS_BITRATES: [(u32, u8); 8] = [
+    (1_000_000, 0),
+    (800_000, 1),
+    (500_000, 2),
+    (250_000, 3),
+    (125_000, 4),
+    (50_000, 6),
+    (20_000, 7),
+    (10_000, 8),
+];
+
+/// LSS address of a node: the four entries of its identity object (0x1018)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LssAddress {
+    pub vendor_id: u32,
+    pub product_code: u32,
+    pub revision: u32,
+    pub serial_number: u32,
+}
+
+/// Requests of the LSS master
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LssCommand {
+    /// Switch all LSS slaves to configuration (`true`) or waiting state
+    SwitchStateGlobal { configuration: bool },
+    /// Switch the slave with this address to configuration state
+    SwitchStateSelective(LssAddress),
+    /// Pending node ID of the slave in configuration state (255 = unconfigured)
+    ConfigureNodeId(u8),
+    /// Pending bitrate, from the CiA 301 table
+    ConfigureBitTiming(u32),
+    /// Switch every slave in configuration state to its pending bitrate
+    ActivateBitTiming { switch_delay_ms: u16 },
+    /// Save the pending node ID and bitrate to non-volatile memory
+    StoreConfiguration,
+    InquireNodeId,
+    /// Ask slaves without a node ID to answer
+    IdentifyNonConfigured,
+}
+
+impl LssCommand {
+    /// Command specifier of the single request frame, or of the last one
+    fn command_specifier(&self) -> u8 {
+        match self {
+            Self::SwitchStateGlobal { .. } => 0x04,
+            Self::SwitchStateSelective(_) => 0x43,
+            Self::ConfigureNodeId(_) => 0x11,
+            Self::ConfigureBitTiming(_) => 0x13,
+            Self::ActivateBitTiming { .. } => 0x15,
+            Self::StoreConfiguration => 0x17,
+            Self::InquireNodeId => 0x5E,
+            Self::IdentifyNonConfigured => 0x4C,
+        }
+    }
+
+    /// Command specifier the slave answers with; `None` for unconfirmed commands
+    pub fn response_specifier(&self) -> Option<u8> {
+        match self {
+            Self::SwitchStateGlobal { .. } | Self::ActivateBitTiming { .. } => None,
+            Self::SwitchStateSelective(_) => Some(0x44),
+            Self::IdentifyNonConfigured => Some(0x50),
+            _ => Some(self.command_specifier()),
+        }
+    }
+}
+
+impl fmt::Display for LssCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SwitchStateGlobal { configuration: true } => write!(f, "Switch all to configuration"),
+            Self::SwitchStateGlobal { configuration: false } => write!(f, "Switch all to waiting"),
+            Self::SwitchStateSelective(address) => write!(
+                f, "Switch selective {:08X}:{:08X}:{:08X}:{:08X}",
+                address.vendor_id, address.product_code, address.revision, address.serial_number
+            ),
+            Self::ConfigureNodeId(node_id) => write!(f, "Configure node ID {}", node_id),
+            Self::ConfigureBitTiming(bitrate) => write!(f, "Configure bitrate {} kbit/s", bitrate / 1000),
+            Self::ActivateBitTiming { switch_delay_ms } => write!(f, "Activate bitrate (delay {} ms)", switch_delay_ms),
+            Self::StoreConfiguration => write!(f, "Store configuration"),
+            Self::InquireNodeId => write!(f, "Inquire node ID"),
+            Self::IdentifyNonConfigured => write!(f, "Identify unconfigured nodes"),
+        }
+    }
+}
+
+/// Answer of a slave to a confirmed command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LssResponse {
+    Confirmed,
+    NodeId(u8),
+}
+
+fn lss_frame(command_specifier: u8, payload: &[u8]) -> Option<CanFrame> {
+    let mut data = [0u8; 8];
+    data[0] = command_specifier;
+    data[1..1 + payload.len()].copy_from_slice(payload);
+    CanFrame::new(StandardId::new(LSS_MASTER_COB_ID)?, &data)
+}
+
+/// Create the request frames of a command
+///
+/// Selective switching takes one frame per address entry; everything else is a single
+/// frame. Returns `None` for a node ID outside 1-127 (or 255) and bitrates not in the table.
+pub fn create_lss_frames(command: &LssCommand) -> Option<Vec<CanFrame>> {
+    let cs = command.command_specifier();
+    let frame = match *command {
+        LssCommand::SwitchStateGlobal { configuration } => lss_frame(cs, &[configuration as u8])?,
+        LssCommand::SwitchStateSelective(address) => {
+            let entries = [address.vendor_id, address.product_code, address.revision, address.serial_number];
+            return (0x40..=0x43u8).zip(entries)
+                .map(|(cs, value)| lss_frame(cs, &value.to_le_bytes()))
+                .collect();
+        }
+        LssCommand::ConfigureNodeId(node_id) => {
+            if !(1..=127).contains(&node_id) && node_id != 0xFF {
+                return None;
+            }
+            lss_frame(cs, &[node_id])?
+        }
+        LssCommand::ConfigureBitTiming(bitrate) => {
+            let index = LSS_BITRATES.iter().find(|(rate, _)| *rate == bitrate)?.1;
+            lss_frame(cs, &[0, index])?
+        }
+        LssCommand::ActivateBitTiming { switch_delay_ms } => lss_frame(cs, &switch_delay_ms.to_le_bytes())?,
+        _ => lss_frame(cs, &[])?,
+    };
+    Some(vec![frame])
+}
+
+/// Decode a slave frame (0x7E4) as the answer to `command`
+///
+/// Returns `None` if the frame answers something else, and an error for the error codes
+/// of the configuration commands.
+pub fn parse_lss_response(command: &LssCommand, data: &[u8]) -> Option<Result<LssResponse, String>> {
+    if data.first() != command.response_specifier().as_ref() {
+        return None;
+    }
+    let code = data.get(1).copied().unwrap_or(0);
+    Some(match command {
+        LssCommand::InquireNodeId => Ok(LssResponse::NodeId(code)),
+        LssCommand::ConfigureNodeId(_) | LssCommand::ConfigureBitTiming(_) | LssCommand::StoreConfiguration if code != 0 => {
+            Err(lss_error_description(command, code, data.get(2).copied().unwrap_or(0)))
+        }
+        _ => Ok(LssResponse::Confirmed),
+    })
+}
+
+fn lss_error_description(command: &LssCommand, code: u8, specific: u8) -> String {
+    match (command, code) {
+        (LssCommand::ConfigureNodeId(_), 1) => "Node ID out of range".to_string(),
+        (LssCommand::ConfigureBitTiming(_), 1) => "Bitrate not supported".to_string(),
+        (LssCommand::StoreConfiguration, 1) => "Storing not supported".to_string(),
+        (LssCommand::StoreConfiguration, 2) => "Storage media access error".to_string(),
+        (_, 0xFF) => format!("Implementation specific error 0x{:02X}", specific),
+        _ => format!("Error code {}", code),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(frames: &[CanFrame]) -> Vec<Vec<u8>> {
+        frames.iter().map(|frame| frame.data().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_create_lss_frames() {
+        let frames = create_lss_frames(&LssCommand::SwitchStateGlobal { configuration: true }).unwrap();
+        assert_eq!(frames[0].id(), StandardId::new(LSS_MASTER_COB_ID).unwrap().into());
+        assert_eq!(data(&frames), [[0x04, 1, 0, 0, 0, 0, 0, 0]]);
+        let frames = create_lss_frames(&LssCommand::SwitchStateGlobal { configuration: false }).unwrap();
+        assert_eq!(data(&frames), [[0x04, 0, 0, 0, 0, 0, 0, 0]]);
+
+        let address = LssAddress { vendor_id: 0x0000_0123, product_code: 0x0A0B_0C0D, revision: 2, serial_number: 0xFFFF_FFFE };
+        let frames = create_lss_frames(&LssCommand::SwitchStateSelective(address)).unwrap();
+        assert_eq!(data(&frames), [
+            [0x40, 0x23, 0x01, 0x00, 0x00, 0, 0, 0],
+            [0x41, 0x0D, 0x0C, 0x0B, 0x0A, 0, 0, 0],
+            [0x42, 0x02, 0x00, 0x00, 0x00, 0, 0, 0],
+            [0x43, 0xFE, 0xFF, 0xFF, 0xFF, 0, 0, 0],
+        ]);
+
+        assert_eq!(data(&create_lss_frames(&LssCommand::ConfigureNodeId(5)).unwrap()), [[0x11, 5, 0, 0, 0, 0, 0, 0]]);
+        assert_eq!(data(&create_lss_frames(&LssCommand::ConfigureNodeId(0xFF)).unwrap()), [[0x11, 0xFF, 0, 0, 0, 0, 0, 0]]);
+        assert!(create_lss_frames(&LssCommand::ConfigureNodeId(0)).is_none());
+        assert!(create_lss_frames(&LssCommand::ConfigureNodeId(128)).is_none());
+
+        let delay = create_lss_frames(&LssCommand::ActivateBitTiming { switch_delay_ms: 0x0102 }).unwrap();
+        assert_eq!(data(&delay), [[0x15, 0x02, 0x01, 0, 0, 0, 0, 0]]);
+        assert_eq!(data(&create_lss_frames(&LssCommand::StoreConfiguration).unwrap()), [[0x17, 0, 0, 0, 0, 0, 0, 0]]);
+        assert_eq!(data(&create_lss_frames(&LssCommand::InquireNodeId).unwrap()), [[0x5E, 0, 0, 0, 0, 0, 0, 0]]);
+        assert_eq!(data(&create_lss_frames(&LssCommand::IdentifyNonConfigured).unwrap()), [[0x4C, 0, 0, 0, 0, 0, 0, 0]]);
+    }
+
+    #[test]
+    fn test_bit_timing_table() {
+        // CiA 305 table selector 0; index 5 is reserved
+        let table = [(1_000_000, 0), (800_000, 1), (500_000, 2), (250_000, 3), (125_000, 4), (50_000, 6), (20_000, 7), (10_000, 8)];
+        for (bitrate, index) in table {
+            let frames = create_lss_frames(&LssCommand::ConfigureBitTiming(bitrate)).unwrap();
+            assert_eq!(data(&frames), [[0x13, 0, index, 0, 0, 0, 0, 0]]);
+        }
+        assert!(create_lss_frames(&LssCommand::ConfigureBitTiming(100_000)).is_none());
+    }
+
+    #[test]
+    fn test_parse_lss_response() {
+        let address = LssAddress::default();
+        assert_eq!(parse_lss_response(&LssCommand::SwitchStateSelective(address), &[0x44]), Some(Ok(LssResponse::Confirmed)));
+        assert_eq!(parse_lss_response(&LssCommand::IdentifyNonConfigured, &[0x50]), Some(Ok(LssResponse::Confirmed)));
+        assert_eq!(parse_lss_response(&LssCommand::InquireNodeId, &[0x5E, 0x0C]), Some(Ok(LssResponse::NodeId(12))));
+        assert_eq!(parse_lss_response(&LssCommand::ConfigureNodeId(5), &[0x11, 0]), Some(Ok(LssResponse::Confirmed)));
+
+        assert_eq!(parse_lss_response(&LssCommand::ConfigureNodeId(5), &[0x11, 1]), Some(Err("Node ID out of range".to_string())));
+        assert_eq!(parse_lss_response(&LssCommand::ConfigureBitTiming(500_000), &[0x13, 1]), Some(Err("Bitrate not supported".to_string())));
+        assert_eq!(parse_lss_response(&LssCommand::StoreConfiguration, &[0x17, 1]), Some(Err("Storing not supported".to_string())));
+        assert_eq!(parse_lss_response(&LssCommand::StoreConfiguration, &[0x17, 2]), Some(Err("Storage media access error".to_string())));
+        assert_eq!(
+            parse_lss_response(&LssCommand::StoreConfiguration, &[0x17, 0xFF, 0x42]),
+            Some(Err("Implementation specific error 0x42".to_string()))
+        );
+        assert_eq!(parse_lss_response(&LssCommand::ConfigureNodeId(5), &[0x11, 7]), Some(Err("Error code 7".to_string())));
+
+        // Answers to other commands, and unconfirmed commands, are not matched
+        assert_eq!(parse_lss_response(&LssCommand::ConfigureNodeId(5), &[0x13, 0]), None);
+        assert_eq!(parse_lss_response(&LssCommand::InquireNodeId, &[]), None);
+        assert_eq!(parse_lss_response(&LssCommand::SwitchStateGlobal { configuration: true }, &[0x04]), None);
+        assert_eq!(parse_lss_response(&LssCommand::ActivateBitTiming { switch_delay_ms: 100 }, &[0x15]), None);
+    }
+}
@@ -0,0 +1,129 @@
+// nmt.rs - Network Management (NMT) protocol helpers
+use socketcan::{CanFrame, StandardId};
+use socketcan::EmbeddedFrame as Frame;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// COB-ID used by the NMT master for node control commands
+pub const NMT_COB_ID: u16 = 0x000;
+
+/// Node ID that addresses every node on the bus
+pub const NMT_BROADCAST_NODE_ID: u8 = 0;
+
+/// Base COB-ID of the heartbeat and boot-up frames a node sends (0x700 + node ID)
+pub const HEARTBEAT_COB_ID_BASE: u16 = 0x700;
+
+/// NMT state a node reports in its heartbeat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum NmtState {
+    /// Boot-up message, sent once after initialization
+    BootUp = 0x00,
+    Stopped = 0x04,
+    Operational = 0x05,
+    PreOperational = 0x7F,
+}
+
+impl NmtState {
+    /// Decode the state byte of a heartbeat frame (the toggle bit of node guarding is ignored)
+    pub fn from_heartbeat(data: &[u8]) -> Option<Self> {
+        match data.first()? & 0x7F {
+            0x00 => Some(Self::BootUp),
+            0x04 => Some(Self::Stopped),
+            0x05 => Some(Self::Operational),
+            0x7F => Some(Self::PreOperational),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for NmtState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BootUp => write!(f, "Boot-up"),
+            Self::Stopped => write!(f, "Stopped"),
+            Self::Operational => write!(f, "Operational"),
+            Self::PreOperational => write!(f, "Pre-Operational"),
+        }
+    }
+}
+
+/// NMT node control command specifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum NmtCommand {
+    /// Switch the node to Operational (PDOs enabled)
+    Start = 0x01,
+    /// Switch the node to Stopped
+    Stop = 0x02,
+    /// Switch the node to Pre-Operational (SDO only)
+    EnterPreOperational = 0x80,
+    /// Reset the application and communication parameters
+    ResetNode = 0x81,
+    /// Reset the communication parameters only
+    ResetCommunication = 0x82,
+}
+
+impl fmt::Display for NmtCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Start => write!(f, "Start"),
+            Self::Stop => write!(f, "Stop"),
+            Self::EnterPreOperational => write!(f, "Enter Pre-Operational"),
+            Self::ResetNode => write!(f, "Reset Node"),
+            Self::ResetCommunication => write!(f, "Reset Communication"),
+        }
+    }
+}
+
+/// Create an NMT node control frame
+///
+/// A `node_id` of 0 addresses all nodes on the bus. Returns `None` for node IDs
+/// outside the valid CANopen range (1-127).
+pub fn create_nmt_frame(node_id: u8, command: NmtCommand) -> Option<CanFrame> {
+    if node_id > 127 {
+        return None;
+    }
+
+    let id = StandardId::new(NMT_COB_ID)?;
+    CanFrame::new(id, &[command as u8, node_id])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_nmt_frame() {
+        let commands = [
+            (NmtCommand::Start, 0x01),
+            (NmtCommand::Stop, 0x02),
+            (NmtCommand::EnterPreOperational, 0x80),
+            (NmtCommand::ResetNode, 0x81),
+            (NmtCommand::ResetCommunication, 0x82),
+        ];
+        for (command, specifier) in commands {
+            let frame = create_nmt_frame(5, command).unwrap();
+            assert_eq!(frame.id(), StandardId::new(NMT_COB_ID).unwrap().into());
+            assert_eq!(frame.data(), [specifier, 5]);
+        }
+
+        let broadcast = create_nmt_frame(NMT_BROADCAST_NODE_ID, NmtCommand::ResetNode).unwrap();
+        assert_eq!(broadcast.data(), [0x81, 0x00]);
+        assert!(create_nmt_frame(127, NmtCommand::Start).is_some());
+        assert!(create_nmt_frame(128, NmtCommand::Start).is_none());
+    }
+
+    #[test]
+    fn test_nmt_state_from_heartbeat() {
+        assert_eq!(NmtState::from_heartbeat(&[0x00]), Some(NmtState::BootUp));
+        assert_eq!(NmtState::from_heartbeat(&[0x04]), Some(NmtState::Stopped));
+        assert_eq!(NmtState::from_heartbeat(&[0x05]), Some(NmtState::Operational));
+        assert_eq!(NmtState::from_heartbeat(&[0x7F]), Some(NmtState::PreOperational));
+        // Node guarding responses carry a toggle bit in bit 7
+        assert_eq!(NmtState::from_heartbeat(&[0x85]), Some(NmtState::Operational));
+        assert_eq!(NmtState::from_heartbeat(&[0xFF]), Some(NmtState::PreOperational));
+        assert_eq!(NmtState::from_heartbeat(&[0x01]), None);
+        assert_eq!(NmtState::from_heartbeat(&[]), None);
+    }
+}
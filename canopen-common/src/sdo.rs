@@ -1,4 +1,5 @@
 // sdo.rs - Updated for the new connection architecture
+use serde::{Deserialize, Serialize};
 use socketcan::{CanFrame, StandardId};
 use socketcan::EmbeddedFrame as Frame;
 use std::error::Error;
@@ -32,7 +33,7 @@ impl SdoCommand {
 }
 
 /// SDO Data Types
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SdoDataType {
     UInt8,
     UInt16,
@@ -46,17 +47,21 @@ pub enum SdoDataType {
 }
 
 impl SdoDataType {
+    /// Map an EDS `DataType` code (CiA 306) to the type used for transfers
+    ///
+    /// BOOLEAN is transferred as one byte and maps to `UInt8`.
     pub fn from_eds_type(eds_type: &str) -> Option<Self> {
-        match eds_type {
-            "0x0001" | "1" => Some(Self::UInt8),
-            "0x0002" | "2" => Some(Self::Int8),
-            "0x0003" | "3" => Some(Self::UInt16),
-            "0x0004" | "4" => Some(Self::Int16),
-            "0x0005" | "5" => Some(Self::UInt32),
-            "0x0006" | "6" => Some(Self::Int32),
-            "0x0008" | "8" => Some(Self::Real32),
-            "0x0009" | "9" => Some(Self::VisibleString),
-            "0x000A" | "10" => Some(Self::OctetString),
+        match eds_type.to_ascii_uppercase().as_str() {
+            "0X0001" | "1" => Some(Self::UInt8), // BOOLEAN
+            "0X0002" | "2" => Some(Self::Int8),
+            "0X0003" | "3" => Some(Self::Int16),
+            "0X0004" | "4" => Some(Self::Int32),
+            "0X0005" | "5" => Some(Self::UInt8),
+            "0X0006" | "6" => Some(Self::UInt16),
+            "0X0007" | "7" => Some(Self::UInt32),
+            "0X0008" | "8" => Some(Self::Real32),
+            "0X0009" | "9" => Some(Self::VisibleString),
+            "0X000A" | "10" => Some(Self::OctetString),
             _ => None,
         }
     }
@@ -122,6 +127,18 @@ pub struct SdoResponse {
     pub raw_data: Vec<u8>,
 }
 
+impl SdoResponse {
+    /// Data bytes of the (expedited) response, without command byte, index and padding
+    pub fn payload(&self) -> &[u8] {
+        let Some(&command) = self.raw_data.first() else {
+            return &[];
+        };
+        // Without the size bit all four data bytes are significant
+        let unused = if command & 0x01 != 0 { ((command & 0x0C) >> 2) as usize } else { 0 };
+        self.raw_data.get(4..8 - unused).unwrap_or(&[])
+    }
+}
+
 /// Custom error type for SDO operations
 #[derive(Debug)]
 pub enum SdoError {
@@ -273,7 +290,7 @@ pub fn parse_sdo_response(frame: CanFrame, request: &SdoRequest) -> Result<SdoRe
 pub fn parse_payload(payload: &[u8], data_type: &SdoDataType) -> Result<SdoResponseData, SdoError> {
     match data_type {
         SdoDataType::UInt8 => {
-            if payload.len() >= 1 {
+            if !payload.is_empty() {
                 Ok(SdoResponseData::UInt8(payload[0]))
             } else {
                 Err(SdoError::ParseError("Insufficient data for UInt8".to_string()))
@@ -296,7 +313,7 @@ pub fn parse_payload(payload: &[u8], data_type: &SdoDataType) -> Result<SdoRespo
             }
         }
         SdoDataType::Int8 => {
-            if payload.len() >= 1 {
+            if !payload.is_empty() {
                 Ok(SdoResponseData::Int8(payload[0] as i8))
             } else {
                 Err(SdoError::ParseError("Insufficient data for Int8".to_string()))
@@ -411,3 +428,33 @@ pub fn get_abort_code_description(code: u32) -> String {
         _ => format!("Unknown abort code: 0x{:08X}", code),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_eds_type() {
+        // CiA 306 data type codes 0x0001 to 0x000A
+        let table = [
+            (1, SdoDataType::UInt8), // BOOLEAN
+            (2, SdoDataType::Int8),
+            (3, SdoDataType::Int16),
+            (4, SdoDataType::Int32),
+            (5, SdoDataType::UInt8),
+            (6, SdoDataType::UInt16),
+            (7, SdoDataType::UInt32),
+            (8, SdoDataType::Real32),
+            (9, SdoDataType::VisibleString),
+            (10, SdoDataType::OctetString),
+        ];
+        for (code, data_type) in table {
+            assert_eq!(SdoDataType::from_eds_type(&format!("0x{:04X}", code)), Some(data_type.clone()));
+            assert_eq!(SdoDataType::from_eds_type(&format!("0x{:04x}", code)), Some(data_type.clone()));
+            assert_eq!(SdoDataType::from_eds_type(&code.to_string()), Some(data_type));
+        }
+        assert_eq!(SdoDataType::from_eds_type("0x000B"), None);
+        assert_eq!(SdoDataType::from_eds_type("0x0000"), None);
+        assert_eq!(SdoDataType::from_eds_type(""), None);
+    }
+}
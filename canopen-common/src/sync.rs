@@ -0,0 +1,18 @@
+// sync.rs - SYNC protocol helpers
+use socketcan::{CanFrame, StandardId};
+use socketcan::EmbeddedFrame as Frame;
+
+/// Default COB-ID of the SYNC object (0x1005)
+pub const SYNC_COB_ID: u16 = 0x080;
+
+/// Create a SYNC frame
+///
+/// Without a counter the frame has no data. With one (used when the synchronous counter
+/// overflow value 0x1019 is 2-240) it carries the counter as its single byte.
+pub fn create_sync_frame(counter: Option<u8>) -> Option<CanFrame> {
+    let id = StandardId::new(SYNC_COB_ID)?;
+    match counter {
+        Some(counter) => CanFrame::new(id, &[counter]),
+        None => CanFrame::new(id, &[]),
+    }
+}
@@ -7,7 +7,7 @@ fn main() {
 
     // Get git information if available
     let git_hash = Command::new("git")
-        .args(&["rev-parse", "--short", "HEAD"])
+        .args(["rev-parse", "--short", "HEAD"])
         .output()
         .ok()
         .and_then(|output| String::from_utf8(output.stdout).ok())
@@ -15,7 +15,7 @@ fn main() {
         .unwrap_or_else(|| "unknown".to_string());
 
     let git_branch = Command::new("git")
-        .args(&["rev-parse", "--abbrev-ref", "HEAD"])
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
         .output()
         .ok()
         .and_then(|output| String::from_utf8(output.stdout).ok())
@@ -24,7 +24,7 @@ fn main() {
 
     // Check if git repo is dirty
     let git_dirty = Command::new("git")
-        .args(&["diff", "--quiet"])
+        .args(["diff", "--quiet"])
         .status()
         .map(|status| !status.success())
         .unwrap_or(false);
@@ -0,0 +1,578 @@
+// backup.rs - Backup of the writable parameters of a node and restoring them
+//
+// A backup is a snapshot of the rw objects only, saved in the same CSV format. On
+// restore the application and profile objects are written first and the communication
+// parameters (0x1000 - 0x1FFF) last, so a changed node ID or bitrate only takes effect
+// at the end. CiA 301 only accepts a new PDO mapping while the PDO is invalid and its
+// mapping count is 0, so each PDO is disabled (bit 31 of its COB-ID), its mapping
+// cleared, the entries and the count written, and the COB-ID restored last.
+//
+// Cloning to another node restores a fresh backup of the current node to a different
+// node ID. COB-IDs that follow the predefined connection set ($NODEID + base) are moved
+// to the target node, anything else is copied as is.
+//
+// Store (0x1010) and restore defaults (0x1011) write the "save" / "load" signature to
+// the selected parameter group, so changes survive a power cycle or are undone.
+use eframe::egui::{self, Color32};
+use std::collections::BTreeSet;
+
+use crate::communication::SdoAddress;
+use crate::snapshot::{self, SnapshotEntry};
+
+/// First index of the device profile and manufacturer specific area
+const APPLICATION_AREA_START: u16 = 0x2000;
+
+/// Result of writing one object during a restore
+#[derive(Debug, Clone)]
+pub struct RestoreOutcome {
+    pub index: u16,
+    pub sub_index: u8,
+    pub name: String,
+    /// `Err` holds the error including the abort code
+    pub result: Result<(), String>,
+}
+
+/// Entries that can be written back, in the order to write them
+///
+/// Objects that could not be read when the backup was taken are skipped.
+pub fn restore_order(entries: &[SnapshotEntry]) -> Vec<SnapshotEntry> {
+    let writable: Vec<&SnapshotEntry> = entries.iter()
+        .filter(|entry| entry.value.is_ok() && !entry.raw.is_empty())
+        .collect();
+
+    // Single objects, and the whole write sequence of each PDO at its communication index
+    let mut groups: Vec<((bool, u16, u8), Vec<SnapshotEntry>)> = writable.iter()
+        .filter(|entry| pdo_communication_index(entry.index).is_none())
+        .map(|entry| ((entry.index < APPLICATION_AREA_START, entry.index, entry.sub_index), vec![(*entry).clone()]))
+        .collect();
+    let pdos: BTreeSet<u16> = writable.iter().filter_map(|entry| pdo_communication_index(entry.index)).collect();
+    groups.extend(pdos.into_iter().map(|communication| ((true, communication, 0), pdo_sequence(communication, &writable))));
+
+    groups.sort_by_key(|(key, _)| *key);
+    groups.into_iter().flat_map(|(_, entries)| entries).collect()
+}
+
+/// Communication parameter index of the RPDO (0x1400 / 0x1600) or TPDO (0x1800 / 0x1A00)
+/// an object belongs to
+fn pdo_communication_index(index: u16) -> Option<u16> {
+    match index {
+        0x1400..=0x15FF | 0x1800..=0x19FF => Some(index),
+        0x1600..=0x17FF | 0x1A00..=0x1BFF => Some(index - 0x200),
+        _ => None,
+    }
+}
+
+/// Disable the PDO, set its communication parameters, clear the mapping, write the
+/// mapped objects and their count, then write the COB-ID from the backup
+fn pdo_sequence(communication: u16, entries: &[&SnapshotEntry]) -> Vec<SnapshotEntry> {
+    let mapping = communication + 0x200;
+    let find = |index: u16, sub_index: u8| entries.iter().find(|entry| entry.index == index && entry.sub_index == sub_index).map(|entry| (*entry).clone());
+    let sorted = |index: u16, first_sub: u8| {
+        let mut found: Vec<SnapshotEntry> = entries.iter()
+            .filter(|entry| entry.index == index && entry.sub_index >= first_sub)
+            .map(|entry| (*entry).clone())
+            .collect();
+        found.sort_by_key(|entry| entry.sub_index);
+        found
+    };
+    let cob_id = find(communication, 1);
+    let mapping_count = find(mapping, 0);
+    let mapped = sorted(mapping, 1);
+    let parameters: Vec<SnapshotEntry> = sorted(communication, 0).into_iter().filter(|entry| entry.sub_index != 1).collect();
+
+    let mut sequence = Vec::new();
+    let disabled = cob_id.as_ref().and_then(|cob_id| {
+        let bytes = <[u8; 4]>::try_from(cob_id.raw.as_slice()).ok()?;
+        let invalid = u32::from_le_bytes(bytes) | PDO_INVALID;
+        Some(SnapshotEntry { raw: invalid.to_le_bytes().to_vec(), value: Ok(invalid.to_string()), ..cob_id.clone() })
+    });
+    sequence.extend(disabled.clone());
+    sequence.extend(parameters);
+    if let Some(count) = &mapping_count {
+        let cleared = vec![0; count.raw.len()];
+        sequence.push(SnapshotEntry { raw: cleared, value: Ok("0".to_string()), ..count.clone() });
+    }
+    sequence.extend(mapped);
+    sequence.extend(mapping_count);
+    // A PDO that was invalid in the backup stays disabled
+    if let Some(cob_id) = cob_id.filter(|cob_id| disabled.as_ref().is_none_or(|disabled| disabled.raw != cob_id.raw)) {
+        sequence.push(cob_id);
+    }
+    sequence
+}
+
+/// Bit 31 of a PDO COB-ID: the PDO doesn't exist (is invalid)
+const PDO_INVALID: u32 = 0x8000_0000;
+
+/// Function codes of the predefined connection set: EMCY, the four TPDO/RPDO pairs and SDO
+const PREDEFINED_COB_BASES: [u32; 11] = [0x080, 0x180, 0x200, 0x280, 0x300, 0x380, 0x400, 0x480, 0x500, 0x580, 0x600];
+
+/// Objects holding a COB-ID of the node itself: EMCY, server SDO and PDO communication parameters
+fn is_own_cob_id(index: u16, sub_index: u8) -> bool {
+    match index {
+        0x1014 => sub_index == 0,
+        0x1200..=0x127F => sub_index == 1 || sub_index == 2,
+        0x1400..=0x15FF | 0x1800..=0x19FF => sub_index == 1,
+        _ => false,
+    }
+}
+
+/// Move COB-IDs derived from `source`'s node ID to `target`
+///
+/// Flag bits (valid, RTR, frame) are kept; COB-IDs that were configured freely are not touched.
+pub fn retarget(entries: &[SnapshotEntry], source: u8, target: u8) -> Vec<SnapshotEntry> {
+    entries.iter()
+        .cloned()
+        .map(|mut entry| {
+            if !is_own_cob_id(entry.index, entry.sub_index) {
+                return entry;
+            }
+            let Ok(bytes) = <[u8; 4]>::try_from(entry.raw.as_slice()) else {
+                return entry;
+            };
+
+            let cob_id = u32::from_le_bytes(bytes);
+            let base = (cob_id & 0x7FF).wrapping_sub(source as u32);
+            if PREDEFINED_COB_BASES.contains(&base) {
+                let moved = (cob_id & !0x7FF) | (base + target as u32);
+                entry.raw = moved.to_le_bytes().to_vec();
+                entry.value = Ok(moved.to_string());
+            }
+            entry
+        })
+        .collect()
+}
+
+/// Entries of `source`'s backup to write to `target`, with the COB-IDs moved and the PDOs
+/// rewritten in the same disable, clear, write, re-enable order as a restore
+pub fn clone_order(entries: &[SnapshotEntry], source: u8, target: u8) -> Vec<SnapshotEntry> {
+    restore_order(&retarget(entries, source, target))
+}
+
+/// Store parameters object and the signature that triggers it ("save" as little endian u32)
+const STORE_PARAMETERS: u16 = 0x1010;
+const SAVE_SIGNATURE: [u8; 4] = *b"save";
+/// Restore default parameters object and its signature ("load")
+const RESTORE_DEFAULTS: u16 = 0x1011;
+const LOAD_SIGNATURE: [u8; 4] = *b"load";
+
+/// Sub-indices of 0x1010 / 0x1011 for the parameter groups defined by CiA 301
+const PARAMETER_GROUPS: [(u8, &str); 3] = [
+    (1, "All parameters"),
+    (2, "Communication"),
+    (3, "Application"),
+];
+
+/// What the backup window asks the communication thread to do
+pub enum BackupAction {
+    Backup,
+    Restore(Vec<SnapshotEntry>),
+    /// Read the current node's parameters to clone them to another node
+    Clone,
+    /// Write a store / restore signature
+    WriteSignature {
+        address: SdoAddress,
+        data: Vec<u8>,
+    },
+}
+
+/// File loaded for restoring, waiting for confirmation
+struct PendingRestore {
+    file_name: String,
+    entries: Vec<SnapshotEntry>,
+}
+
+pub struct ParameterBackup {
+    pub open: bool,
+    /// A backup was requested and its snapshot is being read
+    backup_running: bool,
+    /// Target node ID of a clone; its snapshot is being read while `backup_running`
+    clone_target: Option<u8>,
+    clone_target_input: u8,
+    pending: Option<PendingRestore>,
+    restore_progress: Option<(usize, usize)>,
+    outcomes: Vec<RestoreOutcome>,
+    error: Option<String>,
+    /// Sub-index of 0x1010 / 0x1011 to write
+    parameter_group: u8,
+    /// Last signature write, `None` as result while it is pending
+    signature_status: Option<(SdoAddress, Option<Result<(), String>>)>,
+}
+
+impl ParameterBackup {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            backup_running: false,
+            clone_target: None,
+            clone_target_input: 1,
+            pending: None,
+            restore_progress: None,
+            outcomes: Vec::new(),
+            error: None,
+            parameter_group: 1,
+            signature_status: None,
+        }
+    }
+
+    pub fn backup_running(&self) -> bool {
+        self.backup_running
+    }
+
+    /// Save the snapshot read for a backup, or prepare it for the clone target
+    ///
+    /// Returns the target node ID and the entries to write when a clone was started.
+    pub fn finish_backup(&mut self, node_id: u8, entries: &[SnapshotEntry]) -> Option<(u8, Vec<SnapshotEntry>)> {
+        self.backup_running = false;
+
+        if let Some(target) = self.clone_target.take() {
+            let entries = clone_order(entries, node_id, target);
+            println!("Cloning {} parameters from node {} to node {}", entries.len(), node_id, target);
+            self.restore_progress = Some((0, entries.len()));
+            self.outcomes.clear();
+            return Some((target, entries));
+        }
+
+        let file_name = format!("backup_node{}_{}.csv", node_id, chrono::Local::now().format("%Y-%m-%d_%H-%M-%S"));
+        if let Some(path) = rfd::FileDialog::new().set_file_name(&file_name).save_file() {
+            match snapshot::save(&path, entries) {
+                Ok(()) => println!("✓ Saved backup of {} parameters to {:?}", entries.len(), path),
+                Err(e) => self.error = Some(format!("Failed to save backup: {}", e)),
+            }
+        }
+        None
+    }
+
+    pub fn cancel_backup(&mut self) {
+        self.backup_running = false;
+        self.clone_target = None;
+        self.restore_progress = None;
+        if matches!(self.signature_status, Some((_, None))) {
+            self.signature_status = None;
+        }
+    }
+
+    pub fn restore_progress(&mut self, done: usize, total: usize) {
+        self.restore_progress = Some((done, total));
+    }
+
+    pub fn finish_restore(&mut self, outcomes: Vec<RestoreOutcome>) {
+        self.restore_progress = None;
+        self.outcomes = outcomes;
+    }
+
+    /// Record the result of a signature write; returns `false` if the write wasn't one of ours
+    pub fn signature_written(&mut self, address: &SdoAddress, result: &Result<(), String>) -> bool {
+        match &mut self.signature_status {
+            Some((pending, status @ None)) if pending == address => {
+                *status = Some(result.clone());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Draw the window; returns the action the user started
+    pub fn show(&mut self, ctx: &egui::Context, connected: bool, node_id: u8) -> Option<BackupAction> {
+        let mut action = None;
+        let mut open = self.open;
+        let busy = self.backup_running || self.restore_progress.is_some();
+
+        egui::Window::new("Parameter Backup")
+            .open(&mut open)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(connected && !busy, egui::Button::new("💾 Backup"))
+                        .on_hover_text("Read all writable objects of the EDS and save them to a file")
+                        .clicked()
+                    {
+                        self.backup_running = true;
+                        self.error = None;
+                        action = Some(BackupAction::Backup);
+                    }
+                    if ui.add_enabled(!busy, egui::Button::new("📂 Load for restore...")).clicked() {
+                        self.load_for_restore();
+                    }
+                    if self.backup_running {
+                        ui.spinner();
+                        ui.label("Reading parameters...");
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Clone to node:");
+                    ui.add(egui::DragValue::new(&mut self.clone_target_input).range(1..=127));
+                    let target = self.clone_target_input;
+                    if ui.add_enabled(connected && !busy && target != node_id, egui::Button::new(format!("⚠ Clone {} → {}", node_id, target)))
+                        .on_hover_text("Read the writable objects of this node and write them to the target node")
+                        .clicked()
+                    {
+                        self.backup_running = true;
+                        self.clone_target = Some(target);
+                        self.error = None;
+                        action = Some(BackupAction::Clone);
+                    }
+                });
+
+                ui.separator();
+                self.draw_store_restore(ui, connected, &mut action);
+
+                let mut discard = false;
+                if let Some(pending) = &self.pending {
+                    ui.separator();
+                    ui.label(format!("{}: {} parameters can be restored", pending.file_name, pending.entries.len()));
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(connected && !busy, egui::Button::new(format!("⚠ Write to node {}", node_id))).clicked() {
+                            action = Some(BackupAction::Restore(pending.entries.clone()));
+                            self.restore_progress = Some((0, pending.entries.len()));
+                            self.outcomes.clear();
+                        }
+                        if ui.button("Discard").clicked() {
+                            discard = true;
+                        }
+                    });
+                }
+                if discard {
+                    self.pending = None;
+                }
+
+                if let Some((done, total)) = self.restore_progress {
+                    let fraction = if total > 0 { done as f32 / total as f32 } else { 0.0 };
+                    ui.add(egui::ProgressBar::new(fraction).text(format!("Restoring {}/{}", done, total)));
+                }
+
+                if let Some(err) = &self.error {
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+                }
+
+                if !self.outcomes.is_empty() {
+                    ui.separator();
+                    self.draw_outcomes(ui);
+                }
+            });
+
+        if matches!(action, Some(BackupAction::Restore(_))) {
+            self.pending = None;
+        }
+        self.open = open;
+        action
+    }
+
+    fn draw_store_restore(&mut self, ui: &mut egui::Ui, connected: bool, action: &mut Option<BackupAction>) {
+        ui.horizontal(|ui| {
+            ui.label("Non-volatile memory:");
+            let selected = PARAMETER_GROUPS.iter()
+                .find(|(sub_index, _)| *sub_index == self.parameter_group)
+                .map_or("", |(_, label)| *label);
+            egui::ComboBox::from_id_salt("parameter_group")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for (sub_index, label) in PARAMETER_GROUPS {
+                        ui.selectable_value(&mut self.parameter_group, sub_index, label);
+                    }
+                });
+
+            let pending = matches!(self.signature_status, Some((_, None)));
+            let mut signature = None;
+            if ui.add_enabled(connected && !pending, egui::Button::new("💾 Store"))
+                .on_hover_text("Write \"save\" to 0x1010 so the current values survive a power cycle")
+                .clicked()
+            {
+                signature = Some((STORE_PARAMETERS, SAVE_SIGNATURE));
+            }
+            if ui.add_enabled(connected && !pending, egui::Button::new("⚠ Restore defaults"))
+                .on_hover_text("Write \"load\" to 0x1011; the factory defaults are active after the next reset")
+                .clicked()
+            {
+                signature = Some((RESTORE_DEFAULTS, LOAD_SIGNATURE));
+            }
+
+            if let Some((index, data)) = signature {
+                let address = SdoAddress { index, sub_index: self.parameter_group };
+                self.signature_status = Some((address.clone(), None));
+                *action = Some(BackupAction::WriteSignature { address, data: data.to_vec() });
+            }
+
+            match &self.signature_status {
+                Some((_, None)) => {
+                    ui.spinner();
+                }
+                Some((address, Some(Ok(())))) => {
+                    ui.colored_label(Color32::from_rgb(0, 200, 0), format!("✓ {:04X}:{:02X} written", address.index, address.sub_index));
+                }
+                Some((address, Some(Err(err)))) => {
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {:04X}:{:02X}: {}", address.index, address.sub_index, err));
+                }
+                None => {}
+            }
+        });
+    }
+
+    fn load_for_restore(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("Backup", &["csv"]).pick_file() else {
+            return;
+        };
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+        match snapshot::load(&path) {
+            Ok(entries) => {
+                self.error = None;
+                self.pending = Some(PendingRestore { file_name, entries: restore_order(&entries) });
+            }
+            Err(e) => self.error = Some(format!("Failed to load {}: {}", file_name, e)),
+        }
+    }
+
+    fn draw_outcomes(&self, ui: &mut egui::Ui) {
+        let failed = self.outcomes.iter().filter(|outcome| outcome.result.is_err()).count();
+        ui.label(format!("{} written, {} failed", self.outcomes.len() - failed, failed));
+
+        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            egui::Grid::new("restore_outcome_grid")
+                .num_columns(3)
+                .spacing([10.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    for outcome in &self.outcomes {
+                        ui.label(format!("{:04X}:{:02X}", outcome.index, outcome.sub_index));
+                        ui.label(&outcome.name);
+                        match &outcome.result {
+                            Ok(()) => ui.colored_label(Color32::from_rgb(0, 200, 0), "✓"),
+                            Err(err) => ui.colored_label(Color32::from_rgb(255, 100, 100), err),
+                        };
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use canopen_common::SdoDataType;
+
+    fn entry(index: u16, sub_index: u8, value: Result<&str, &str>) -> SnapshotEntry {
+        SnapshotEntry {
+            index,
+            sub_index,
+            name: String::new(),
+            data_type: SdoDataType::UInt8,
+            raw: if value.is_ok() { vec![1] } else { Vec::new() },
+            value: value.map(str::to_string).map_err(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_restore_order() {
+        let entries = vec![
+            entry(0x1017, 0, Ok("100")),
+            entry(0x1A00, 0, Ok("2")),
+            entry(0x1A00, 1, Ok("0x60000108")),
+            entry(0x2000, 1, Ok("5")),
+            entry(0x2001, 0, Err("SDO abort 0x06010001: Attempt to read a write only object")),
+            entry(0x6000, 0, Ok("1")),
+        ];
+
+        let order: Vec<(u16, u8)> = restore_order(&entries).iter()
+            .map(|entry| (entry.index, entry.sub_index))
+            .collect();
+        assert_eq!(order, vec![(0x2000, 1), (0x6000, 0), (0x1017, 0), (0x1A00, 0), (0x1A00, 1), (0x1A00, 0)]);
+    }
+
+    fn u32_entry(index: u16, sub_index: u8, value: u32) -> SnapshotEntry {
+        SnapshotEntry {
+            index,
+            sub_index,
+            name: String::new(),
+            data_type: SdoDataType::UInt32,
+            raw: value.to_le_bytes().to_vec(),
+            value: Ok(value.to_string()),
+        }
+    }
+
+    fn writes(entries: &[SnapshotEntry]) -> Vec<(u16, u8, Vec<u8>)> {
+        entries.iter().map(|entry| (entry.index, entry.sub_index, entry.raw.clone())).collect()
+    }
+
+    #[test]
+    fn test_pdo_restore_sequence() {
+        let entries = vec![
+            u32_entry(0x1A00, 2, 0x6064_0020),
+            entry(0x1A00, 0, Ok("2")),
+            u32_entry(0x1800, 1, 0x185),
+            entry(0x1800, 2, Ok("254")),
+            u32_entry(0x1A00, 1, 0x6041_0010),
+        ];
+
+        assert_eq!(writes(&restore_order(&entries)), vec![
+            (0x1800, 1, 0x8000_0185u32.to_le_bytes().to_vec()),
+            (0x1800, 2, vec![1]),
+            (0x1A00, 0, vec![0]),
+            (0x1A00, 1, 0x6041_0010u32.to_le_bytes().to_vec()),
+            (0x1A00, 2, 0x6064_0020u32.to_le_bytes().to_vec()),
+            (0x1A00, 0, vec![1]),
+            (0x1800, 1, 0x185u32.to_le_bytes().to_vec()),
+        ]);
+    }
+
+    #[test]
+    fn test_disabled_pdo_stays_disabled() {
+        let entries = vec![u32_entry(0x1401, 1, 0x8000_0305), entry(0x1601, 0, Ok("1"))];
+        assert_eq!(writes(&restore_order(&entries)), vec![
+            (0x1401, 1, 0x8000_0305u32.to_le_bytes().to_vec()),
+            (0x1601, 0, vec![0]),
+            (0x1601, 0, vec![1]),
+        ]);
+    }
+
+    #[test]
+    fn test_clone_pdo_sequence() {
+        let entries = vec![
+            u32_entry(0x1A00, 1, 0x6041_0010),
+            entry(0x1A00, 0, Ok("1")),
+            u32_entry(0x1800, 1, 0x185),
+        ];
+
+        // The PDO of the target node is disabled under its own COB-ID and re-enabled last
+        assert_eq!(writes(&clone_order(&entries, 5, 10)), vec![
+            (0x1800, 1, 0x8000_018Au32.to_le_bytes().to_vec()),
+            (0x1A00, 0, vec![0]),
+            (0x1A00, 1, 0x6041_0010u32.to_le_bytes().to_vec()),
+            (0x1A00, 0, vec![1]),
+            (0x1800, 1, 0x18Au32.to_le_bytes().to_vec()),
+        ]);
+    }
+
+    #[test]
+    fn test_signatures() {
+        // CiA 301 defines the signatures as these u32 values sent little endian
+        assert_eq!(u32::from_le_bytes(SAVE_SIGNATURE), 0x6576_6173);
+        assert_eq!(u32::from_le_bytes(LOAD_SIGNATURE), 0x6461_6F6C);
+    }
+
+    #[test]
+    fn test_retarget_cob_ids() {
+        let cob_entry = |index: u16, sub_index: u8, cob_id: u32| SnapshotEntry {
+            index,
+            sub_index,
+            name: String::new(),
+            data_type: SdoDataType::UInt32,
+            raw: cob_id.to_le_bytes().to_vec(),
+            value: Ok(cob_id.to_string()),
+        };
+        let entries = vec![
+            cob_entry(0x1014, 0, 0x85),
+            cob_entry(0x1800, 1, 0x4000_0185),
+            cob_entry(0x1801, 1, 0x8000_0285),
+            cob_entry(0x1802, 1, 0x1F0),
+            cob_entry(0x2000, 1, 0x185),
+        ];
+
+        let cob_ids: Vec<u32> = retarget(&entries, 5, 10).iter()
+            .map(|entry| u32::from_le_bytes(entry.raw.as_slice().try_into().unwrap()))
+            .collect();
+        assert_eq!(cob_ids, vec![0x8A, 0x4000_018A, 0x8000_028A, 0x1F0, 0x185]);
+    }
+}
@@ -0,0 +1,126 @@
+// bar_chart.rs - Latest values of related channels side by side
+//
+// Cell voltages, temperatures of several sensors or the entries of any array object are
+// compared with each other rather than over time. A bar chart shows the latest value of
+// each of its series as one bar; it is created for all sub-indices of an object from the
+// SDO list and takes further series dragged onto it. Its series aren't plotted on their own.
+use eframe::egui::{self, Color32};
+use egui_plot::{Bar, BarChart, Plot};
+use serde::{Deserialize, Serialize};
+
+use crate::overlay::PlotSeries;
+
+const BAR_COLOR: Color32 = Color32::from_rgb(100, 149, 237);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BarChartSettings {
+    pub title: String,
+    pub series: Vec<PlotSeries>,
+}
+
+impl BarChartSettings {
+    pub fn new(title: &str, series: Vec<PlotSeries>) -> Self {
+        Self { title: title.to_string(), series }
+    }
+
+    pub fn contains(&self, series: &PlotSeries) -> bool {
+        self.series.contains(series)
+    }
+
+    pub fn add(&mut self, series: PlotSeries) {
+        if !self.contains(&series) {
+            self.series.push(series);
+        }
+    }
+}
+
+/// One bar: the series name and its latest value, if recorded yet
+pub struct BarValue {
+    pub name: String,
+    pub value: Option<f64>,
+}
+
+pub enum BarChartAction {
+    /// A series was dragged onto the chart
+    Dropped(PlotSeries),
+    /// Take the bar of a series out, plotting it on its own again
+    RemoveSeries(usize),
+    /// Remove the chart, plotting its series on their own again
+    Remove,
+}
+
+/// Name under the bar at `position` of the x axis; grid marks between bars stay empty
+pub fn axis_label(bars: &[BarValue], position: f64) -> String {
+    if position.fract() != 0.0 || position < 0.0 {
+        return String::new();
+    }
+    bars.get(position as usize).map_or_else(String::new, |bar| bar.name.clone())
+}
+
+pub fn show(ui: &mut egui::Ui, id: usize, settings: &BarChartSettings, bars: &[BarValue], value_label: &str) -> Option<BarChartAction> {
+    let mut action = None;
+
+    let (_, dropped) = ui.dnd_drop_zone::<PlotSeries, _>(egui::Frame::group(ui.style()), |ui| {
+        ui.horizontal_wrapped(|ui| {
+            ui.label(&settings.title);
+            ui.separator();
+            for (index, bar) in bars.iter().enumerate() {
+                if ui.small_button(format!("✖ {}", bar.name)).on_hover_text("Back to its own plot").clicked() {
+                    action = Some(BarChartAction::RemoveSeries(index));
+                }
+            }
+        });
+        ui.separator();
+
+        let chart = BarChart::new(
+            bars.iter()
+                .enumerate()
+                .filter_map(|(index, bar)| Some(Bar::new(index as f64, bar.value?).name(&bar.name).width(0.7).fill(BAR_COLOR)))
+                .collect(),
+        )
+        .color(BAR_COLOR);
+        Plot::new(format!("bar_chart_{}", id))
+            .height(250.0)
+            .width(ui.available_width())
+            .allow_scroll(false)
+            .allow_drag(false)
+            .y_axis_label(value_label)
+            .x_axis_formatter(|mark, _range| axis_label(bars, mark.value))
+            .show(ui, |plot_ui| plot_ui.bar_chart(chart));
+
+        if ui.button("🗑 Remove chart").on_hover_text("Plot its series on their own again").clicked() {
+            action = Some(BarChartAction::Remove);
+        }
+    });
+
+    if let Some(dropped) = dropped {
+        action = Some(BarChartAction::Dropped((*dropped).clone()));
+    }
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::communication::SdoAddress;
+
+    #[test]
+    fn test_bar_chart_series_and_labels() {
+        let cell = |sub_index| PlotSeries::Sdo(SdoAddress { index: 0x2100, sub_index });
+        let mut chart = BarChartSettings::new("Cell voltages", vec![cell(1), cell(2)]);
+        chart.add(cell(2));
+        chart.add(cell(3));
+        assert_eq!(chart.series, vec![cell(1), cell(2), cell(3)]);
+        assert!(chart.contains(&cell(3)));
+
+        let bars = [
+            BarValue { name: "Cell 1".to_string(), value: Some(3.31) },
+            BarValue { name: "Cell 2".to_string(), value: None },
+        ];
+        assert_eq!(axis_label(&bars, 0.0), "Cell 1");
+        assert_eq!(axis_label(&bars, 1.0), "Cell 2");
+        assert_eq!(axis_label(&bars, 0.5), "");
+        assert_eq!(axis_label(&bars, 2.0), "");
+        assert_eq!(axis_label(&bars, -1.0), "");
+    }
+}
@@ -0,0 +1,210 @@
+// black_box.rs - Ring of recent events in memory, written to disk when a trigger fires
+//
+// Logging everything for days to catch one rare fault fills the disk with data nobody
+// reads. In black-box mode only the last seconds of events are kept in memory; a
+// heartbeat alarm, an emergency or "Trigger now" starts a dump, which waits for the
+// seconds after the trigger and then writes the window around it to a log file of its own.
+use chrono::{DateTime, Local, TimeDelta};
+use eframe::egui;
+use std::collections::VecDeque;
+
+use crate::config::BlackBoxConfig;
+use crate::logging::LogEvent;
+
+#[derive(Debug, Clone)]
+pub struct Trigger {
+    pub at: DateTime<Local>,
+    pub reason: String,
+}
+
+/// Events around a trigger, oldest first
+pub struct Dump {
+    pub trigger: Trigger,
+    pub events: Vec<(DateTime<Local>, LogEvent)>,
+}
+
+pub struct BlackBox {
+    ring: VecDeque<(DateTime<Local>, LogEvent)>,
+    config: BlackBoxConfig,
+    pending: Option<Trigger>,
+}
+
+fn seconds(value: f64) -> TimeDelta {
+    TimeDelta::milliseconds((value * 1000.0) as i64)
+}
+
+impl BlackBox {
+    pub fn new(config: BlackBoxConfig) -> Self {
+        Self { ring: VecDeque::new(), config, pending: None }
+    }
+
+    pub fn configure(&mut self, config: BlackBoxConfig) {
+        self.config = config;
+        self.trim();
+    }
+
+    pub fn record(&mut self, at: DateTime<Local>, event: LogEvent) {
+        self.ring.push_back((at, event));
+        self.trim();
+    }
+
+    /// Start a dump, unless one is already waiting; the window then covers this trigger too
+    pub fn trigger(&mut self, at: DateTime<Local>, reason: String) -> bool {
+        if self.pending.is_some() {
+            return false;
+        }
+        self.pending = Some(Trigger { at, reason });
+        true
+    }
+
+    pub fn pending(&self) -> Option<&Trigger> {
+        self.pending.as_ref()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    /// The events around the pending trigger once the time after it has passed
+    pub fn take_due(&mut self, now: DateTime<Local>) -> Option<Dump> {
+        let trigger = self.pending.as_ref()?;
+        if now < trigger.at + seconds(self.config.post_seconds) {
+            return None;
+        }
+        let trigger = self.pending.take()?;
+        let (start, end) = (trigger.at - seconds(self.config.pre_seconds), trigger.at + seconds(self.config.post_seconds));
+        let events = self.ring.iter()
+            .filter(|(at, _)| (start..=end).contains(at))
+            .cloned()
+            .collect();
+        self.trim();
+        Some(Dump { trigger, events })
+    }
+
+    /// Drop what no dump can need any more: older than the time before the newest event,
+    /// or before a pending trigger's window
+    fn trim(&mut self) {
+        let Some(&(newest, _)) = self.ring.back() else {
+            return;
+        };
+        let mut keep_from = newest - seconds(self.config.pre_seconds);
+        if let Some(trigger) = &self.pending {
+            keep_from = keep_from.min(trigger.at - seconds(self.config.pre_seconds));
+        }
+        while self.ring.front().is_some_and(|(at, _)| *at < keep_from) || self.ring.len() > self.config.max_events {
+            self.ring.pop_front();
+        }
+    }
+}
+
+pub enum BlackBoxAction {
+    /// The settings changed
+    Changed,
+    Trigger,
+}
+
+/// "Black box" menu next to the logging controls; `status` describes the recording
+pub fn menu(ui: &mut egui::Ui, config: &mut BlackBoxConfig, status: Option<String>) -> Option<BlackBoxAction> {
+    let mut action = None;
+    ui.menu_button("⬛ Black box", |ui| {
+        if ui.checkbox(&mut config.enabled, "Keep recent events in memory, write them on a trigger")
+            .on_hover_text("Dumps go to the log folder as canopen_blackbox_<time> files, whether or not logging is enabled")
+            .changed()
+        {
+            action = Some(BlackBoxAction::Changed);
+        }
+        ui.horizontal(|ui| {
+            ui.label("Seconds before:");
+            if ui.add(egui::DragValue::new(&mut config.pre_seconds).range(1.0..=3600.0).speed(1.0)).changed() {
+                action = Some(BlackBoxAction::Changed);
+            }
+            ui.label("after:");
+            if ui.add(egui::DragValue::new(&mut config.post_seconds).range(0.0..=3600.0).speed(1.0)).changed() {
+                action = Some(BlackBoxAction::Changed);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Triggers:");
+            if ui.checkbox(&mut config.trigger_on_alarm, "Heartbeat alarm").changed()
+                | ui.checkbox(&mut config.trigger_on_emergency, "EMCY").changed()
+            {
+                action = Some(BlackBoxAction::Changed);
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.add_enabled(status.is_some(), egui::Button::new("⏺ Trigger now")).clicked() {
+                action = Some(BlackBoxAction::Trigger);
+                ui.close_menu();
+            }
+            if let Some(status) = &status {
+                ui.weak(status);
+            }
+        });
+    });
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn config() -> BlackBoxConfig {
+        BlackBoxConfig { enabled: true, pre_seconds: 2.0, post_seconds: 1.0, max_events: 100, ..BlackBoxConfig::default() }
+    }
+
+    /// Times in tenths of a second after a fixed start
+    fn at(tenths: i64) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, 14, 9, 5, 7).unwrap() + TimeDelta::milliseconds(tenths * 100)
+    }
+
+    fn record(black_box: &mut BlackBox, tenths: std::ops::Range<i64>) {
+        for tenths in tenths {
+            black_box.record(at(tenths), LogEvent::Marker(tenths.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_only_the_pre_trigger_window_is_kept() {
+        let mut black_box = BlackBox::new(config());
+        record(&mut black_box, 0..50);
+        assert_eq!(black_box.len(), 21);
+    }
+
+    #[test]
+    fn test_trigger_while_pending_is_ignored() {
+        let mut black_box = BlackBox::new(config());
+        record(&mut black_box, 0..50);
+        assert!(black_box.trigger(at(49), "Manual".to_string()));
+        assert!(!black_box.trigger(at(49), "Again".to_string()));
+        assert_eq!(black_box.pending().map(|trigger| trigger.reason.as_str()), Some("Manual"));
+    }
+
+    #[test]
+    fn test_dump_spans_the_window_around_the_trigger() {
+        let mut black_box = BlackBox::new(config());
+        record(&mut black_box, 0..50);
+        black_box.trigger(at(49), "Manual".to_string());
+        for tenths in 50..80 {
+            black_box.record(at(tenths), LogEvent::Marker(tenths.to_string()));
+            if tenths < 59 {
+                assert!(black_box.take_due(at(tenths)).is_none());
+            }
+        }
+
+        let dump = black_box.take_due(at(80)).unwrap();
+        assert_eq!(dump.trigger.reason, "Manual");
+        let first = dump.events.first().map(|(at, _)| *at);
+        let last = dump.events.last().map(|(at, _)| *at);
+        assert_eq!((first, last, dump.events.len()), (Some(at(29)), Some(at(59)), 31));
+        assert!(black_box.pending().is_none());
+        assert_eq!(black_box.len(), 21);
+    }
+
+    #[test]
+    fn test_event_limit() {
+        let mut black_box = BlackBox::new(BlackBoxConfig { max_events: 5, ..config() });
+        record(&mut black_box, 0..10);
+        assert_eq!(black_box.len(), 5);
+    }
+}
@@ -0,0 +1,239 @@
+// blf.rs - Vector binary logging format (BLF) writer for raw CAN frames
+//
+// The decoded logs only keep what the viewer understood; a BLF recording keeps every frame
+// on the bus, so a capture can be replayed and analysed in CANoe/CANalyzer and the other
+// Vector tools. Frames are collected into log containers, written uncompressed, and the
+// file header with the object count and the time span is rewritten with every container,
+// so the file on disk stays readable up to the last flush.
+use chrono::{DateTime, Datelike, Local, Timelike};
+use std::fs::File;
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::trace::TraceEntry;
+
+const FILE_HEADER_SIZE: usize = 144;
+const OBJECT_HEADER_SIZE: usize = 16;
+const OBJECT_HEADER_V1_SIZE: usize = 16;
+const CONTAINER_HEADER_SIZE: usize = 16;
+/// Uncompressed data per log container
+const MAX_CONTAINER_SIZE: usize = 128 * 1024;
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+const CAN_MESSAGE: u32 = 1;
+const LOG_CONTAINER: u32 = 10;
+const CAN_MSG_EXT: u32 = 0x8000_0000;
+/// Message flags: sent rather than received, remote request
+const TX_FLAG: u8 = 0x01;
+const REMOTE_FLAG: u8 = 0x80;
+/// Object timestamps in nanoseconds
+const TIME_ONE_NANS: u32 = 2;
+/// Channel the frames are logged on; BLF numbers channels from one
+const CHANNEL: u16 = 1;
+
+pub struct BlfWriter {
+    file: File,
+    start: DateTime<Local>,
+    last: DateTime<Local>,
+    objects: u32,
+    /// Size of the file with every container uncompressed
+    uncompressed: u64,
+    buffer: Vec<u8>,
+    last_flush: Instant,
+}
+
+/// Windows SYSTEMTIME fields: year, month, weekday, day, hour, minute, second, milliseconds
+fn system_time(at: DateTime<Local>) -> [u16; 8] {
+    [
+        at.year() as u16,
+        at.month() as u16,
+        at.weekday().num_days_from_sunday() as u16,
+        at.day() as u16,
+        at.hour() as u16,
+        at.minute() as u16,
+        at.second() as u16,
+        (at.nanosecond() / 1_000_000).min(999) as u16,
+    ]
+}
+
+impl BlfWriter {
+    pub fn create(path: &Path, start: DateTime<Local>) -> io::Result<Self> {
+        let mut writer = Self {
+            file: File::create(path)?,
+            start,
+            last: start,
+            objects: 0,
+            uncompressed: FILE_HEADER_SIZE as u64,
+            buffer: Vec::new(),
+            last_flush: Instant::now(),
+        };
+        writer.write_header()?;
+        Ok(writer)
+    }
+
+    pub fn write(&mut self, entry: &TraceEntry) -> io::Result<()> {
+        let mut id = entry.id;
+        if entry.extended {
+            id |= CAN_MSG_EXT;
+        }
+        let mut data = [0; 8];
+        let length = entry.data.len().min(8);
+        data[..length].copy_from_slice(&entry.data[..length]);
+        let nanoseconds = (entry.timestamp - self.start).num_nanoseconds().unwrap_or_default().max(0) as u64;
+
+        let object_size = OBJECT_HEADER_SIZE + OBJECT_HEADER_V1_SIZE + 16;
+        self.buffer.extend_from_slice(b"LOBJ");
+        self.buffer.extend_from_slice(&(OBJECT_HEADER_SIZE as u16 + OBJECT_HEADER_V1_SIZE as u16).to_le_bytes());
+        self.buffer.extend_from_slice(&1u16.to_le_bytes());
+        self.buffer.extend_from_slice(&(object_size as u32).to_le_bytes());
+        self.buffer.extend_from_slice(&CAN_MESSAGE.to_le_bytes());
+        self.buffer.extend_from_slice(&TIME_ONE_NANS.to_le_bytes());
+        // Client index and object version
+        self.buffer.extend_from_slice(&[0; 4]);
+        self.buffer.extend_from_slice(&nanoseconds.to_le_bytes());
+        self.buffer.extend_from_slice(&CHANNEL.to_le_bytes());
+        let mut flags = 0;
+        if entry.transmitted {
+            flags |= TX_FLAG;
+        }
+        if entry.remote {
+            flags |= REMOTE_FLAG;
+        }
+        self.buffer.push(flags);
+        self.buffer.push(entry.dlc as u8);
+        self.buffer.extend_from_slice(&id.to_le_bytes());
+        self.buffer.extend_from_slice(&data);
+
+        self.objects += 1;
+        self.last = self.last.max(entry.timestamp);
+        if self.buffer.len() >= MAX_CONTAINER_SIZE || self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Write the collected frames as a log container and update the file header
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.last_flush = Instant::now();
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let data = std::mem::take(&mut self.buffer);
+        let object_size = OBJECT_HEADER_SIZE + CONTAINER_HEADER_SIZE + data.len();
+        let mut container = Vec::with_capacity(object_size + 3);
+        container.extend_from_slice(b"LOBJ");
+        container.extend_from_slice(&(OBJECT_HEADER_SIZE as u16).to_le_bytes());
+        container.extend_from_slice(&1u16.to_le_bytes());
+        container.extend_from_slice(&(object_size as u32).to_le_bytes());
+        container.extend_from_slice(&LOG_CONTAINER.to_le_bytes());
+        // Not compressed
+        container.extend_from_slice(&0u16.to_le_bytes());
+        container.extend_from_slice(&[0; 6]);
+        container.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        container.extend_from_slice(&[0; 4]);
+        container.extend_from_slice(&data);
+        container.resize(object_size.next_multiple_of(4), 0);
+
+        self.file.seek(SeekFrom::End(0))?;
+        self.file.write_all(&container)?;
+        self.uncompressed += object_size as u64;
+        self.write_header()
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let file_size = self.file.seek(SeekFrom::End(0))?.max(FILE_HEADER_SIZE as u64);
+        let mut header = Vec::with_capacity(FILE_HEADER_SIZE);
+        header.extend_from_slice(b"LOGG");
+        header.extend_from_slice(&(FILE_HEADER_SIZE as u32).to_le_bytes());
+        // Application ID and version, then the BLF version 2.6.8.1
+        header.extend_from_slice(&[0, 0, 0, 0, 2, 6, 8, 1]);
+        header.extend_from_slice(&file_size.to_le_bytes());
+        header.extend_from_slice(&self.uncompressed.to_le_bytes());
+        header.extend_from_slice(&self.objects.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        for field in system_time(self.start).into_iter().chain(system_time(self.last)) {
+            header.extend_from_slice(&field.to_le_bytes());
+        }
+        header.resize(FILE_HEADER_SIZE, 0);
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(&header)?;
+        self.file.flush()
+    }
+}
+
+impl Drop for BlfWriter {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            eprintln!("Failed to write BLF log: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+    use chrono::TimeZone;
+
+    /// Offset of the first frame, behind the file header and the container header
+    const FIRST_FRAME: usize = 176;
+
+    /// A standard data frame, then an extended remote frame 1.5 ms later
+    fn written() -> Vec<u8> {
+        let directory = TestDir::new("blf");
+        let path = directory.join("frames.blf");
+        let start = Local.with_ymd_and_hms(2026, 3, 14, 9, 5, 7).unwrap();
+        let mut writer = BlfWriter::create(&path, start).unwrap();
+        writer.write(&TraceEntry { timestamp: start, id: 0x181, extended: false, remote: false, dlc: 2, data: vec![0x11, 0x22], transmitted: false }).unwrap();
+        let later = start + chrono::TimeDelta::microseconds(1500);
+        writer.write(&TraceEntry { timestamp: later, id: 0x1234_5678, extended: true, remote: true, dlc: 0, data: Vec::new(), transmitted: true }).unwrap();
+        drop(writer);
+        std::fs::read(&path).unwrap()
+    }
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn u64_at(bytes: &[u8], offset: usize) -> u64 {
+        u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_file_header() {
+        let bytes = written();
+        assert_eq!(&bytes[..4], b"LOGG");
+        assert_eq!(u32_at(&bytes, 4), 144);
+        assert_eq!(u64_at(&bytes, 16), bytes.len() as u64);
+        assert_eq!(u32_at(&bytes, 32), 2);
+        // Start time is a Saturday
+        assert_eq!(&bytes[40..48], [0xEA, 0x07, 3, 0, 6, 0, 14, 0]);
+    }
+
+    #[test]
+    fn test_frames_in_one_container() {
+        let bytes = written();
+        assert_eq!(&bytes[144..148], b"LOBJ");
+        assert_eq!(u32_at(&bytes, 144 + 12), LOG_CONTAINER);
+        assert_eq!(u32_at(&bytes, 144 + 24), 96);
+        assert_eq!(u32_at(&bytes, FIRST_FRAME + 12), CAN_MESSAGE);
+        assert_eq!(u32_at(&bytes, FIRST_FRAME + 48 + 12), CAN_MESSAGE);
+    }
+
+    #[test]
+    fn test_standard_data_frame() {
+        let bytes = written();
+        assert_eq!(u64_at(&bytes, FIRST_FRAME + 24), 0);
+        assert_eq!(&bytes[FIRST_FRAME + 32..FIRST_FRAME + 42], [1, 0, 0, 2, 0x81, 0x01, 0, 0, 0x11, 0x22]);
+    }
+
+    #[test]
+    fn test_extended_remote_frame() {
+        let bytes = written();
+        let frame = FIRST_FRAME + 48;
+        assert_eq!(u64_at(&bytes, frame + 24), 1_500_000);
+        assert_eq!(bytes[frame + 34], TX_FLAG | REMOTE_FLAG);
+        assert_eq!(u32_at(&bytes, frame + 36), 0x9234_5678);
+    }
+}
@@ -1,22 +1,56 @@
 // connect.rs
-use socketcan::{CanSocket, Socket, CanFrame, EmbeddedFrame};
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use socketcan::{CanSocket, Socket, SocketOptions, CanFilter, CanFrame, EmbeddedFrame};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::io;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::io::unix::AsyncFd;
 use tokio::sync::{mpsc, oneshot};
 use tokio::task::JoinHandle;
 use std::error::Error;
 use std::fmt;
 
+use crate::queue::{self, QueueItem};
+use super::gs_usb::{self, GsUsbTransport};
+use super::heartbeat::{HeartbeatMonitor, NodeNmtState};
+use super::replay::ReplayTransport;
+use super::simulation::SimulationTransport;
+use super::stats::{BusStats, BusStatsCollector, InterfaceCounters, NodeCommMetrics, SdoTimings};
+use super::tcp::{TcpProtocol, TcpTransport};
+use super::timestamp::{self, ReceivedFrame, TimestampSource};
 use canopen_common::{SdoRequest, SdoResponse, SdoError, SdoWriteRequest,
-                     parse_sdo_response, parse_sdo_write_response};
+                     parse_sdo_response, parse_sdo_write_response,
+                     NmtCommand, create_nmt_frame, create_sync_frame, HEARTBEAT_COB_ID_BASE,
+                     LssCommand, LssResponse, create_lss_frames, parse_lss_response, LSS_SLAVE_COB_ID};
+
+/// How long LSS slaves get to answer a confirmed command
+const LSS_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Frames buffered per raw-frame subscriber before the oldest are dropped
+const RAW_FRAME_QUEUE_CAPACITY: usize = 1024;
+
+/// Frames buffered between the socket reader and the connection manager
+const READER_QUEUE_CAPACITY: usize = 256;
+
+/// How often a lost interface is checked for reappearance
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often bus statistics and SDO metrics are published to event subscribers
+const STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Filter mask matching all 11 identifier bits of a standard (non-extended) frame
+const STANDARD_ID_FILTER_MASK: u32 = 0x8000_07FF; // CAN_EFF_FLAG | CAN_SFF_MASK
 
 #[derive(Debug)]
 pub enum CANopenError {
     SocketError(String),
-    #[allow(dead_code)]  // Reserved for future use
     NodeNotConnected(u8),
     RequestFailed(String),
+    /// The SDO transfer failed: timeout, abort or an unusable response
+    Sdo(SdoError),
 }
 
 impl fmt::Display for CANopenError {
@@ -25,6 +59,7 @@ impl fmt::Display for CANopenError {
             Self::SocketError(msg) => write!(f, "Socket error: {}", msg),
             Self::NodeNotConnected(node_id) => write!(f, "Node {} not connected", node_id),
             Self::RequestFailed(msg) => write!(f, "Request failed: {}", msg),
+            Self::Sdo(error) => write!(f, "Request failed: {}", error),
         }
     }
 }
@@ -33,26 +68,360 @@ impl Error for CANopenError {}
 
 impl From<SdoError> for CANopenError {
     fn from(error: SdoError) -> Self {
-        Self::RequestFailed(error.to_string())
+        Self::Sdo(error)
+    }
+}
+
+/// Boxed future returned by `CanTransport` methods (keeps the trait object-safe)
+pub type TransportFuture<'a, T> = Pin<Box<dyn Future<Output = io::Result<T>> + Send + 'a>>;
+
+/// A CAN bus backend the connection manager reads frames from and writes frames to
+pub trait CanTransport: Send + Sync {
+    /// Wait for the next received frame
+    fn recv(&self) -> TransportFuture<'_, ReceivedFrame>;
+
+    /// Transmit a frame
+    fn send<'a>(&'a self, frame: &'a CanFrame) -> TransportFuture<'a, ()>;
+
+    /// Only deliver standard frames with these COB-IDs, or everything for `None`
+    /// (backends that can't filter deliver everything)
+    fn set_rx_filter(&self, _cob_ids: Option<&[u16]>) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Driver frame/byte totals, which also count frames dropped by the receive filter
+    fn interface_counters(&self) -> Option<InterfaceCounters> {
+        None
+    }
+}
+
+/// Which backend to open and where to find it
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransportConfig {
+    /// Local SocketCAN interface, e.g. "can0"
+    SocketCan { interface: String },
+    /// Remote bus over TCP, e.g. "socketcand://host:29536/can0" or "cannelloni://host:20000"
+    Tcp {
+        address: String,
+        protocol: TcpProtocol,
+        channel: String,
+    },
+    /// candleLight/gs_usb adapter via libusb, e.g. "gs_usb://0?bitrate=500000"
+    GsUsb { device_index: usize, bitrate: u32 },
+    /// In-process mock nodes, e.g. "sim://" (nodes created on demand) or "sim://4,5"
+    Simulation { node_ids: Vec<u8> },
+    /// Recorded capture played back with its original timing, e.g. "replay:///tmp/candump.log?loop"
+    /// or sped up, e.g. "replay:///tmp/capture.pcap?speed=10"
+    Replay { path: PathBuf, looped: bool, speed: f64 },
+}
+
+impl TransportConfig {
+    /// Parse an interface name as entered in the UI or stored in the config file
+    pub fn from_interface(name: &str) -> Result<Self, CANopenError> {
+        let Some((scheme, rest)) = name.split_once("://") else {
+            return Ok(Self::SocketCan { interface: name.to_string() });
+        };
+
+        if scheme == "gs_usb" {
+            return Self::parse_gs_usb(rest);
+        }
+        if scheme == "sim" {
+            return Self::parse_simulation(rest);
+        }
+        if scheme == "replay" {
+            return Self::parse_replay(rest);
+        }
+
+        let protocol = match scheme {
+            "socketcand" => TcpProtocol::Socketcand,
+            "cannelloni" => TcpProtocol::Cannelloni,
+            _ => return Err(CANopenError::RequestFailed(format!("Unknown transport '{}'", scheme))),
+        };
+
+        let (host, channel) = rest.split_once('/').unwrap_or((rest, ""));
+        if protocol == TcpProtocol::Socketcand && channel.is_empty() {
+            return Err(CANopenError::RequestFailed(
+                "socketcand needs a remote interface, e.g. socketcand://host:29536/can0".to_string()
+            ));
+        }
+
+        let address = if host.contains(':') {
+            host.to_string()
+        } else {
+            format!("{}:{}", host, protocol.default_port())
+        };
+
+        Ok(Self::Tcp { address, protocol, channel: channel.to_string() })
+    }
+
+    /// "<index>[?bitrate=<bit/s>]"
+    fn parse_gs_usb(rest: &str) -> Result<Self, CANopenError> {
+        let invalid = || CANopenError::RequestFailed(format!("Invalid gs_usb interface 'gs_usb://{}'", rest));
+
+        let (index, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let device_index = if index.is_empty() { 0 } else { index.parse().map_err(|_| invalid())? };
+        let bitrate = match query.strip_prefix("bitrate=") {
+            Some(bitrate) => bitrate.parse().map_err(|_| invalid())?,
+            None if query.is_empty() => gs_usb::DEFAULT_BITRATE,
+            None => return Err(invalid()),
+        };
+
+        Ok(Self::GsUsb { device_index, bitrate })
+    }
+
+    /// Comma-separated node IDs, or nothing for on-demand nodes
+    fn parse_simulation(rest: &str) -> Result<Self, CANopenError> {
+        let node_ids = rest.split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(|id| id.parse::<u8>().ok().filter(|id| (1..=127).contains(id)))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(|| CANopenError::RequestFailed(format!("Invalid simulation node IDs '{}'", rest)))?;
+
+        Ok(Self::Simulation { node_ids })
+    }
+
+    /// Capture path followed by "?loop", "?speed=10" or both joined with '&'
+    fn parse_replay(rest: &str) -> Result<Self, CANopenError> {
+        let (path, options) = rest.split_once('?').unwrap_or((rest, ""));
+        let mut looped = false;
+        let mut speed = 1.0;
+        for option in options.split('&').filter(|option| !option.is_empty()) {
+            match option.split_once('=') {
+                None if option == "loop" => looped = true,
+                Some(("speed", value)) => {
+                    speed = value.parse::<f64>().ok().filter(|speed| *speed > 0.0)
+                        .ok_or_else(|| CANopenError::RequestFailed(format!("Invalid replay speed '{}'", value)))?;
+                }
+                _ => return Err(CANopenError::RequestFailed(format!("Unknown replay option '{}'", option))),
+            }
+        }
+        Ok(Self::Replay { path: PathBuf::from(path), looped, speed })
+    }
+
+    /// Bitrate the transport sets up itself, if any
+    pub fn bitrate(&self) -> Option<u32> {
+        match self {
+            Self::GsUsb { bitrate, .. } => Some(*bitrate),
+            _ => None,
+        }
+    }
+
+    /// Whether it's worth trying to (re)open the transport now
+    fn is_available(&self) -> bool {
+        match self {
+            Self::SocketCan { interface } => interface_is_up(interface),
+            Self::Tcp { .. } => true, // Only a connection attempt can tell
+            Self::GsUsb { device_index, .. } => gs_usb::list_devices().len() > *device_index,
+            Self::Simulation { .. } => true,
+            Self::Replay { path, .. } => path.is_file(),
+        }
+    }
+
+    async fn open(&self) -> Result<Arc<dyn CanTransport>, CANopenError> {
+        match self {
+            Self::SocketCan { interface } => Ok(Arc::new(SocketCanTransport::open(interface)?)),
+            Self::Tcp { address, protocol, channel } => {
+                let transport = TcpTransport::connect(address, *protocol, channel)
+                    .await
+                    .map_err(|e| CANopenError::SocketError(format!("{}: {}", self, e)))?;
+                Ok(Arc::new(transport))
+            }
+            Self::GsUsb { device_index, bitrate } => {
+                let transport = GsUsbTransport::open(*device_index, *bitrate)
+                    .map_err(|e| CANopenError::SocketError(format!("{}: {}", self, e)))?;
+                Ok(Arc::new(transport))
+            }
+            Self::Simulation { node_ids } => Ok(Arc::new(SimulationTransport::open(node_ids))),
+            Self::Replay { path, looped, speed } => {
+                let transport = ReplayTransport::open(path, *looped, *speed)
+                    .map_err(|e| CANopenError::SocketError(format!("{}: {}", self, e)))?;
+                Ok(Arc::new(transport))
+            }
+        }
+    }
+}
+
+impl fmt::Display for TransportConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SocketCan { interface } => write!(f, "{}", interface),
+            Self::Tcp { address, protocol, channel } if channel.is_empty() => {
+                write!(f, "{}://{}", protocol.scheme(), address)
+            }
+            Self::Tcp { address, protocol, channel } => {
+                write!(f, "{}://{}/{}", protocol.scheme(), address, channel)
+            }
+            Self::GsUsb { device_index, bitrate } => {
+                write!(f, "gs_usb://{}?bitrate={}", device_index, bitrate)
+            }
+            Self::Simulation { node_ids } => {
+                let ids: Vec<String> = node_ids.iter().map(|id| id.to_string()).collect();
+                write!(f, "sim://{}", ids.join(","))
+            }
+            Self::Replay { path, looped, speed } => {
+                let mut options = Vec::new();
+                if *looped {
+                    options.push("loop".to_string());
+                }
+                if *speed != 1.0 {
+                    options.push(format!("speed={}", speed));
+                }
+                if options.is_empty() {
+                    write!(f, "replay://{}", path.display())
+                } else {
+                    write!(f, "replay://{}?{}", path.display(), options.join("&"))
+                }
+            }
+        }
+    }
+}
+
+/// Local SocketCAN interface
+pub struct SocketCanTransport {
+    interface: String,
+    socket: AsyncFd<CanSocket>,
+}
+
+impl SocketCanTransport {
+    /// Open a non-blocking CAN socket with receive timestamping enabled
+    fn open(interface: &str) -> Result<Self, CANopenError> {
+        let socket = CanSocket::open(interface)
+            .map_err(|e| CANopenError::SocketError(e.to_string()))?;
+
+        // Set non-blocking mode for the socket
+        socket.set_nonblocking(true)
+            .map_err(|e| CANopenError::SocketError(e.to_string()))?;
+
+        // Kernel/hardware receive timestamps; without them frames are stamped on read
+        if let Err(e) = timestamp::enable_rx_timestamping(&socket) {
+            eprintln!("RX timestamping unavailable, using local time: {}", e);
+        }
+
+        // Error frames only feed the bus statistics
+        if let Err(e) = socket.set_error_filter_accept_all() {
+            eprintln!("Failed to enable CAN error frames: {}", e);
+        }
+
+        let socket = AsyncFd::new(socket)
+            .map_err(|e| CANopenError::SocketError(format!("Failed to register CAN socket: {}", e)))?;
+
+        Ok(Self { interface: interface.to_string(), socket })
+    }
+}
+
+impl CanTransport for SocketCanTransport {
+    fn recv(&self) -> TransportFuture<'_, ReceivedFrame> {
+        Box::pin(async move {
+            // Woken by the runtime when the socket is readable
+            loop {
+                let mut guard = self.socket.readable().await?;
+
+                match guard.try_io(|inner| timestamp::read_frame_with_timestamp(inner.get_ref())) {
+                    Ok(result) => return result,
+                    Err(_would_block) => continue, // Readiness cleared, wait for the next event
+                }
+            }
+        })
+    }
+
+    fn send<'a>(&'a self, frame: &'a CanFrame) -> TransportFuture<'a, ()> {
+        Box::pin(async move {
+            // Wait for the socket to become writable if the TX queue is full
+            loop {
+                let mut guard = self.socket.writable().await?;
+
+                match guard.try_io(|inner| inner.get_ref().write_frame(frame)) {
+                    Ok(result) => return result,
+                    Err(_would_block) => continue,
+                }
+            }
+        })
+    }
+
+    fn set_rx_filter(&self, cob_ids: Option<&[u16]>) -> io::Result<()> {
+        let Some(cob_ids) = cob_ids else {
+            return self.socket.get_ref().set_filter_accept_all();
+        };
+
+        let filters: Vec<CanFilter> = cob_ids.iter()
+            .map(|&cob_id| CanFilter::new(cob_id as u32, STANDARD_ID_FILTER_MASK))
+            .collect();
+
+        self.socket.get_ref().set_filters(&filters)
+    }
+
+    fn interface_counters(&self) -> Option<InterfaceCounters> {
+        let read = |name: &str| {
+            std::fs::read_to_string(format!("/sys/class/net/{}/statistics/{}", self.interface, name))
+                .ok()?
+                .trim()
+                .parse::<u64>()
+                .ok()
+        };
+
+        Some(InterfaceCounters {
+            rx_frames: read("rx_packets")?,
+            tx_frames: read("tx_packets")?,
+            rx_bytes: read("rx_bytes")?,
+            tx_bytes: read("tx_bytes")?,
+        })
     }
 }
 
+/// Connection-level events reported to subscribers
+#[derive(Debug, Clone)]
+pub enum ConnectionEvent {
+    /// The CAN interface went down or disappeared
+    InterfaceLost(String),
+    /// The interface is back and the transport has been reopened
+    InterfaceRestored,
+    /// Periodic bus statistics
+    BusStats(BusStats),
+    /// Periodic SDO queue and round-trip metrics, one entry per node
+    CommMetrics(Vec<NodeCommMetrics>),
+    /// A node's NMT state changed according to its heartbeats
+    NmtState { node_id: u8, state: NodeNmtState },
+}
+
+/// What the transport reader task passes to the connection manager
+enum ReaderEvent {
+    Frame(ReceivedFrame),
+    /// An error frame, or a frame the backend couldn't decode
+    ErrorFrame,
+    ReadError,
+    InterfaceLost(io::Error),
+}
+
 /// Internal message types for the connection manager
 #[derive(Debug)]
 enum ConnectionMessage {
     SdoRequest {
         node_id: u8,
         request: SdoRequest,
+        priority: SdoPriority,
         response_tx: oneshot::Sender<Result<SdoResponse, SdoError>>,
     },
     #[allow(dead_code)]  // Reserved for future SDO write feature
     SdoWriteRequest {
         node_id: u8,
         request: SdoWriteRequest,
+        priority: SdoPriority,
         response_tx: oneshot::Sender<Result<(), SdoError>>,
     },
     AddNode {
         node_id: u8,
+        timeout: Option<Duration>,
+        response_tx: oneshot::Sender<Result<(), CANopenError>>,
+    },
+    SetNodeTimeout {
+        node_id: u8,
+        timeout: Duration,
+        response_tx: oneshot::Sender<Result<(), CANopenError>>,
+    },
+    SetHeartbeatConsumerTime {
+        node_id: u8,
+        consumer_time: Option<Duration>,
         response_tx: oneshot::Sender<Result<(), CANopenError>>,
     },
     #[allow(dead_code)]  // Reserved for future cleanup functionality
@@ -61,8 +430,35 @@ enum ConnectionMessage {
         response_tx: oneshot::Sender<Result<(), CANopenError>>,
     },
     SubscribeRawFrames {
-        response_tx: oneshot::Sender<mpsc::UnboundedReceiver<CanFrame>>,
+        cob_ids: Option<HashSet<u16>>,
+        response_tx: oneshot::Sender<queue::Receiver<ReceivedFrame>>,
+    },
+    SubscribeEvents {
+        response_tx: oneshot::Sender<mpsc::UnboundedReceiver<ConnectionEvent>>,
+    },
+    SubscribeTransmittedFrames {
+        response_tx: oneshot::Sender<queue::Receiver<ReceivedFrame>>,
     },
+    SendFrame {
+        frame: CanFrame,
+        response_tx: oneshot::Sender<Result<(), CANopenError>>,
+    },
+}
+
+/// A raw frame subscription and the standard COB-IDs it asked for (`None` = every frame)
+struct RawFrameSubscriber {
+    cob_ids: Option<HashSet<u16>>,
+    tx: queue::Sender<ReceivedFrame>,
+}
+
+impl RawFrameSubscriber {
+    fn wants(&self, frame: &CanFrame) -> bool {
+        match (&self.cob_ids, frame.id()) {
+            (None, _) => true,
+            (Some(cob_ids), socketcan::Id::Standard(id)) => cob_ids.contains(&id.as_raw()),
+            (Some(_), socketcan::Id::Extended(_)) => false,
+        }
+    }
 }
 
 /// Represents the type of SDO operation
@@ -77,28 +473,113 @@ enum SdoOperation {
     },
 }
 
+/// Scheduling priority of an SDO request (higher runs first)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SdoPriority {
+    /// Periodic subscription reads
+    Polling,
+    /// Connection health checks
+    HealthCheck,
+    /// User-triggered reads and writes
+    Interactive,
+}
+
+impl SdoOperation {
+    /// Deliver a final error to the caller
+    fn fail(self, error: SdoError) {
+        match self {
+            SdoOperation::Read { response_tx, .. } => {
+                let _ = response_tx.send(Err(error));
+            }
+            SdoOperation::Write { response_tx, .. } => {
+                let _ = response_tx.send(Err(error));
+            }
+        }
+    }
+}
+
+/// When and how often a failed SDO request is sent again before the error is reported
+#[derive(Debug, Clone, PartialEq)]
+pub struct SdoRetryPolicy {
+    /// Additional attempts after the first one (0 disables retries)
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for every further attempt
+    pub retry_delay: Duration,
+    /// Abort codes that are retried as well; timeouts are always retried
+    pub retry_abort_codes: Vec<u32>,
+}
+
+impl Default for SdoRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            retry_delay: Duration::from_millis(50),
+            retry_abort_codes: Vec::new(),
+        }
+    }
+}
+
+impl SdoRetryPolicy {
+    /// Backoff before the next attempt, or `None` if the error should be reported
+    fn retry_delay(&self, error: &SdoError, retries_done: u32) -> Option<Duration> {
+        if retries_done >= self.max_retries {
+            return None;
+        }
+
+        let retryable = match error {
+            SdoError::Timeout => true,
+            SdoError::AbortTransfer { code, .. } => self.retry_abort_codes.contains(code),
+            _ => false,
+        };
+
+        retryable.then(|| self.retry_delay.saturating_mul(1u32 << retries_done.min(16)))
+    }
+}
+
 /// Represents a pending SDO request (read or write)
 struct PendingSdoRequest {
     operation: SdoOperation,
+    priority: SdoPriority,
+    // Queued time, reset when the request is sent so waiting doesn't eat into the timeout
     timestamp: std::time::Instant,
+    // Retries already made for this request
+    retries: u32,
+    // Earliest time a retry may be sent (None for fresh requests)
+    not_before: Option<std::time::Instant>,
+}
+
+impl PendingSdoRequest {
+    fn new(operation: SdoOperation, priority: SdoPriority) -> Self {
+        Self {
+            operation,
+            priority,
+            timestamp: std::time::Instant::now(),
+            retries: 0,
+            not_before: None,
+        }
+    }
 }
 
 /// Per-node state management
 struct NodeState {
-    // Queue of pending SDO requests (FIFO)
+    // Queue of pending SDO requests (FIFO within each priority)
     pending_requests: std::collections::VecDeque<PendingSdoRequest>,
     // Currently active request (if any)
     active_request: Option<PendingSdoRequest>,
     // Node-specific timeout
     timeout: Duration,
+    retry_policy: SdoRetryPolicy,
+    timings: SdoTimings,
 }
 
 impl NodeState {
-    fn new(_node_id: u8, timeout: Duration) -> Self {
+    fn new(_node_id: u8, timeout: Duration, retry_policy: SdoRetryPolicy) -> Self {
         Self {
             pending_requests: std::collections::VecDeque::new(),
             active_request: None,
             timeout,
+            retry_policy,
+            timings: SdoTimings::new(),
         }
     }
 
@@ -108,7 +589,23 @@ impl NodeState {
 
     fn start_next_request(&mut self) -> Option<&PendingSdoRequest> {
         if self.active_request.is_none() {
-            self.active_request = self.pending_requests.pop_front();
+            // Oldest request of the highest priority among those not waiting out a retry delay
+            let now = std::time::Instant::now();
+            let ready = |request: &PendingSdoRequest| request.not_before.is_none_or(|at| at <= now);
+            let next = self.pending_requests.iter()
+                .filter(|request| ready(request))
+                .map(|request| request.priority)
+                .max()
+                .and_then(|priority| {
+                    self.pending_requests.iter()
+                        .position(|request| request.priority == priority && ready(request))
+                })
+                .and_then(|position| self.pending_requests.remove(position));
+
+            self.active_request = next.map(|mut request| {
+                request.timestamp = std::time::Instant::now();
+                request
+            });
         }
         self.active_request.as_ref()
     }
@@ -117,9 +614,28 @@ impl NodeState {
         self.active_request.take()
     }
 
+    /// Re-queue a failed request if the retry policy allows it, otherwise hand it back
+    fn retry_or_fail(&mut self, mut request: PendingSdoRequest, error: SdoError) {
+        match self.retry_policy.retry_delay(&error, request.retries) {
+            Some(delay) => {
+                request.retries += 1;
+                request.not_before = Some(std::time::Instant::now() + delay);
+                // Front of the queue so it keeps its place among requests of the same priority
+                self.pending_requests.push_front(request);
+            }
+            None => request.operation.fail(error),
+        }
+    }
+
+    /// Requests waiting to be sent plus the one in flight
+    fn queue_length(&self) -> usize {
+        self.pending_requests.len() + self.active_request.is_some() as usize
+    }
+
     fn check_timeout(&mut self) -> Option<PendingSdoRequest> {
         if let Some(ref active) = self.active_request {
             if active.timestamp.elapsed() > self.timeout {
+                self.timings.record_timeout();
                 return self.complete_active_request();
             }
         }
@@ -127,6 +643,12 @@ impl NodeState {
     }
 }
 
+impl QueueItem for ReceivedFrame {
+    fn is_sample(&self) -> bool {
+        true
+    }
+}
+
 /// Main CANopen connection handle
 pub struct CANopenConnection {
     command_tx: mpsc::UnboundedSender<ConnectionMessage>,
@@ -143,21 +665,22 @@ impl Clone for CANopenConnection {
 }
 
 impl CANopenConnection {
-    /// Create a new CANopen connection on the specified interface
-    pub async fn new(interface: &str, default_timeout: Duration) -> Result<Self, CANopenError> {
-        let socket = CanSocket::open(interface)
-            .map_err(|e| CANopenError::SocketError(e.to_string()))?;
-
-        // Set non-blocking mode for the socket
-        socket.set_nonblocking(true)
-            .map_err(|e| CANopenError::SocketError(e.to_string()))?;
+    /// Create a new CANopen connection over the given transport
+    pub async fn new(
+        transport_config: TransportConfig,
+        default_timeout: Duration,
+        retry_policy: SdoRetryPolicy,
+    ) -> Result<Self, CANopenError> {
+        let transport = transport_config.open().await?;
 
         let (command_tx, command_rx) = mpsc::unbounded_channel();
 
         let background_task = tokio::spawn(connection_manager_task(
-            socket,
+            transport_config,
+            transport,
             command_rx,
             default_timeout,
+            retry_policy,
         ));
 
         Ok(Self {
@@ -167,11 +690,18 @@ impl CANopenConnection {
     }
 
     /// Add a node to the connection (enables communication with this node)
-    pub async fn add_node(&self, node_id: u8) -> Result<CANopenNodeHandle, CANopenError> {
+    ///
+    /// `timeout` overrides the connection's default SDO timeout for this node. Adding a node
+    /// again returns another handle; requests already queued for it are kept.
+    pub async fn add_node(
+        &self,
+        node_id: u8,
+        timeout: Option<Duration>,
+    ) -> Result<CANopenNodeHandle, CANopenError> {
         let (response_tx, response_rx) = oneshot::channel();
 
         self.command_tx
-            .send(ConnectionMessage::AddNode { node_id, response_tx })
+            .send(ConnectionMessage::AddNode { node_id, timeout, response_tx })
             .map_err(|_| CANopenError::RequestFailed("Connection manager died".to_string()))?;
 
         response_rx
@@ -184,18 +714,200 @@ impl CANopenConnection {
         })
     }
 
+    /// Change the SDO timeout of an already added node (applies to requests started afterwards)
+    pub async fn set_node_timeout(&self, node_id: u8, timeout: Duration) -> Result<(), CANopenError> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.command_tx
+            .send(ConnectionMessage::SetNodeTimeout { node_id, timeout, response_tx })
+            .map_err(|_| CANopenError::RequestFailed("Connection manager died".to_string()))?;
+
+        response_rx
+            .await
+            .map_err(|_| CANopenError::RequestFailed("Failed to get response".to_string()))?
+    }
+
+    /// Set the longest expected gap between a node's heartbeats (`None` = a few measured periods)
+    pub async fn set_heartbeat_consumer_time(&self, node_id: u8, consumer_time: Option<Duration>) -> Result<(), CANopenError> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.command_tx
+            .send(ConnectionMessage::SetHeartbeatConsumerTime { node_id, consumer_time, response_tx })
+            .map_err(|_| CANopenError::RequestFailed("Connection manager died".to_string()))?;
+
+        response_rx
+            .await
+            .map_err(|_| CANopenError::RequestFailed("Failed to get response".to_string()))?
+    }
+
     /// Subscribe to raw CAN frames (for TPDO reception)
-    pub async fn subscribe_raw_frames(&self) -> Result<mpsc::UnboundedReceiver<CanFrame>, CANopenError> {
+    ///
+    /// With `cob_ids` only standard frames with those identifiers are delivered, and
+    /// they are added to the kernel receive filter for as long as the receiver lives.
+    /// `None` subscribes to every frame on the bus.
+    pub async fn subscribe_raw_frames(
+        &self,
+        cob_ids: Option<HashSet<u16>>,
+    ) -> Result<queue::Receiver<ReceivedFrame>, CANopenError> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.command_tx
+            .send(ConnectionMessage::SubscribeRawFrames { cob_ids, response_tx })
+            .map_err(|_| CANopenError::RequestFailed("Connection manager died".to_string()))?;
+
+        response_rx
+            .await
+            .map_err(|_| CANopenError::RequestFailed("Failed to get response".to_string()))
+    }
+
+    /// Subscribe to every frame the connection transmits, stamped with the time it was sent
+    pub async fn subscribe_transmitted_frames(&self) -> Result<queue::Receiver<ReceivedFrame>, CANopenError> {
         let (response_tx, response_rx) = oneshot::channel();
 
         self.command_tx
-            .send(ConnectionMessage::SubscribeRawFrames { response_tx })
+            .send(ConnectionMessage::SubscribeTransmittedFrames { response_tx })
             .map_err(|_| CANopenError::RequestFailed("Connection manager died".to_string()))?;
 
         response_rx
             .await
             .map_err(|_| CANopenError::RequestFailed("Failed to get response".to_string()))
     }
+
+    /// Subscribe to interface lost/restored notifications and bus statistics
+    pub async fn subscribe_events(&self) -> Result<mpsc::UnboundedReceiver<ConnectionEvent>, CANopenError> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.command_tx
+            .send(ConnectionMessage::SubscribeEvents { response_tx })
+            .map_err(|_| CANopenError::RequestFailed("Connection manager died".to_string()))?;
+
+        response_rx
+            .await
+            .map_err(|_| CANopenError::RequestFailed("Failed to get response".to_string()))
+    }
+
+    /// Send an NMT node control command (node_id 0 addresses all nodes)
+    pub async fn send_nmt(&self, node_id: u8, command: NmtCommand) -> Result<(), CANopenError> {
+        let frame = create_nmt_frame(node_id, command)
+            .ok_or_else(|| CANopenError::RequestFailed(format!("Invalid NMT target node {}", node_id)))?;
+
+        send_frame(&self.command_tx, frame).await
+    }
+
+    /// Send an LSS command and wait for the answer of confirmed ones
+    ///
+    /// Returns `None` for unconfirmed commands and when no slave answered in time.
+    pub async fn send_lss(&self, command: LssCommand) -> Result<Option<LssResponse>, CANopenError> {
+        let frames = create_lss_frames(&command)
+            .ok_or_else(|| CANopenError::RequestFailed(format!("Invalid LSS command: {}", command)))?;
+
+        // Subscribe before sending so a fast answer isn't missed
+        let responses = match command.response_specifier() {
+            Some(_) => Some(self.subscribe_raw_frames(Some(HashSet::from([LSS_SLAVE_COB_ID]))).await?),
+            None => None,
+        };
+        for frame in frames {
+            send_frame(&self.command_tx, frame).await?;
+        }
+        let Some(responses) = responses else {
+            return Ok(None);
+        };
+
+        let answer = tokio::time::timeout(LSS_TIMEOUT, async {
+            while let Some(received) = responses.recv().await {
+                if let Some(result) = parse_lss_response(&command, received.frame.data()) {
+                    return Some(result);
+                }
+            }
+            None
+        }).await;
+        match answer {
+            Ok(Some(result)) => result.map(Some).map_err(CANopenError::RequestFailed),
+            Ok(None) | Err(_) => Ok(None),
+        }
+    }
+
+    /// Send a SYNC frame, with the synchronous counter if one is given
+    pub async fn send_sync(&self, counter: Option<u8>) -> Result<(), CANopenError> {
+        let frame = create_sync_frame(counter)
+            .ok_or_else(|| CANopenError::RequestFailed("Invalid SYNC frame".to_string()))?;
+
+        send_frame(&self.command_tx, frame).await
+    }
+
+    /// Transmit an arbitrary frame as is
+    pub async fn send_raw_frame(&self, frame: CanFrame) -> Result<(), CANopenError> {
+        send_frame(&self.command_tx, frame).await
+    }
+}
+
+/// Whether the interface exists and is administratively up
+fn interface_is_up(interface: &str) -> bool {
+    std::fs::read_to_string(format!("/sys/class/net/{}/flags", interface))
+        .ok()
+        .and_then(|flags| u32::from_str_radix(flags.trim().trim_start_matches("0x"), 16).ok())
+        .is_some_and(|flags| flags & libc::IFF_UP as u32 != 0)
+}
+
+/// Read errors meaning the interface went down, was removed or the remote end hung up
+fn is_interface_lost(error: &io::Error) -> bool {
+    matches!(error.raw_os_error(), Some(libc::ENETDOWN) | Some(libc::ENODEV) | Some(libc::ENXIO))
+        || matches!(
+            error.kind(),
+            io::ErrorKind::UnexpectedEof
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::BrokenPipe
+        )
+}
+
+/// Spawn the CAN frame reader task for a transport
+fn spawn_reader(transport: Arc<dyn CanTransport>, frame_tx: mpsc::Sender<ReaderEvent>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            match transport.recv().await {
+                Ok(frame) => {
+                    if frame_tx.send(ReaderEvent::Frame(frame)).await.is_err() {
+                        break; // Channel closed
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                    if frame_tx.send(ReaderEvent::ErrorFrame).await.is_err() {
+                        break;
+                    }
+                }
+                Err(e) if is_interface_lost(&e) => {
+                    eprintln!("CAN interface lost: {}", e);
+                    let _ = frame_tx.send(ReaderEvent::InterfaceLost(e)).await;
+                    break;
+                }
+                Err(e) => {
+                    // Read error - back off instead of spinning on it
+                    eprintln!("CAN read error: {}", e);
+                    if frame_tx.send(ReaderEvent::ReadError).await.is_err() {
+                        break;
+                    }
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            }
+        }
+    })
+}
+
+/// Queue a raw CAN frame for transmission by the connection manager
+async fn send_frame(
+    command_tx: &mpsc::UnboundedSender<ConnectionMessage>,
+    frame: CanFrame,
+) -> Result<(), CANopenError> {
+    let (response_tx, response_rx) = oneshot::channel();
+
+    command_tx
+        .send(ConnectionMessage::SendFrame { frame, response_tx })
+        .map_err(|_| CANopenError::RequestFailed("Connection manager died".to_string()))?;
+
+    response_rx
+        .await
+        .map_err(|_| CANopenError::RequestFailed("Failed to get response".to_string()))?
 }
 
 /// TPDO Mapping Entry - defines one object to map into a TPDO
@@ -227,14 +939,32 @@ pub struct CANopenNodeHandle {
 }
 
 impl CANopenNodeHandle {
-    /// Send an SDO read request to this node
+    /// Send an NMT node control command to this node
+    pub async fn send_nmt(&self, command: NmtCommand) -> Result<(), CANopenError> {
+        let frame = create_nmt_frame(self.node_id, command)
+            .ok_or_else(|| CANopenError::RequestFailed(format!("Invalid NMT target node {}", self.node_id)))?;
+
+        send_frame(&self.command_tx, frame).await
+    }
+
+    /// Send an interactive SDO read request to this node
     pub async fn sdo_read(&self, request: SdoRequest) -> Result<SdoResponse, CANopenError> {
+        self.sdo_read_with_priority(request, SdoPriority::Interactive).await
+    }
+
+    /// Send an SDO read request to this node with an explicit scheduling priority
+    pub async fn sdo_read_with_priority(
+        &self,
+        request: SdoRequest,
+        priority: SdoPriority,
+    ) -> Result<SdoResponse, CANopenError> {
         let (response_tx, response_rx) = oneshot::channel();
 
         self.command_tx
             .send(ConnectionMessage::SdoRequest {
                 node_id: self.node_id,
                 request,
+                priority,
                 response_tx,
             })
             .map_err(|_| CANopenError::RequestFailed("Connection manager died".to_string()))?;
@@ -246,7 +976,6 @@ impl CANopenNodeHandle {
     }
 
     /// Send an SDO write request to this node
-    #[allow(dead_code)]  // Reserved for future SDO write feature
     pub async fn sdo_write(&self, request: SdoWriteRequest) -> Result<(), CANopenError> {
         let (response_tx, response_rx) = oneshot::channel();
 
@@ -254,6 +983,7 @@ impl CANopenNodeHandle {
             .send(ConnectionMessage::SdoWriteRequest {
                 node_id: self.node_id,
                 request,
+                priority: SdoPriority::Interactive,
                 response_tx,
             })
             .map_err(|_| CANopenError::RequestFailed("Connection manager died".to_string()))?;
@@ -376,6 +1106,24 @@ impl CANopenNodeHandle {
         Ok(())
     }
 
+    /// Transmit a PDO frame (e.g. an RPDO for this node) with up to 8 data bytes
+    pub async fn send_pdo(&self, cob_id: u16, data: &[u8]) -> Result<(), CANopenError> {
+        let frame = socketcan::StandardId::new(cob_id)
+            .and_then(|id| CanFrame::new(id, data))
+            .ok_or_else(|| CANopenError::RequestFailed(format!("Invalid PDO frame: COB-ID {:#X}, {} bytes", cob_id, data.len())))?;
+
+        send_frame(&self.command_tx, frame).await
+    }
+
+    /// Send a remote request (RTR frame) for a PDO of `dlc` data bytes
+    pub async fn request_pdo(&self, cob_id: u16, dlc: usize) -> Result<(), CANopenError> {
+        let frame = socketcan::StandardId::new(cob_id)
+            .and_then(|id| CanFrame::new_remote(id, dlc))
+            .ok_or_else(|| CANopenError::RequestFailed(format!("Invalid remote request: COB-ID {:#X}, DLC {}", cob_id, dlc)))?;
+
+        send_frame(&self.command_tx, frame).await
+    }
+
     /// Get the node ID for this handle
     pub fn node_id(&self) -> u8 {
         self.node_id
@@ -383,43 +1131,39 @@ impl CANopenNodeHandle {
 
     // Future methods:
     // pub async fn configure_rpdo(&self, config: RpdoConfig) -> Result<(), CANopenError>
-    // pub async fn send_nmt_command(&self, command: NmtCommand) -> Result<(), CANopenError>
 }
 
 /// Background task that manages all CANopen communication
 async fn connection_manager_task(
-    socket: CanSocket,
+    transport_config: TransportConfig,
+    mut transport: Arc<dyn CanTransport>,
     mut command_rx: mpsc::UnboundedReceiver<ConnectionMessage>,
     default_timeout: Duration,
+    retry_policy: SdoRetryPolicy,
 ) {
     let mut nodes: HashMap<u8, NodeState> = HashMap::new();
-    let socket = Arc::new(Mutex::new(socket));
-    let mut raw_frame_subscribers: Vec<mpsc::UnboundedSender<CanFrame>> = Vec::new();
-
-    // Spawn the CAN frame reader task
-    let socket_clone = socket.clone();
-    let (frame_tx, mut frame_rx) = mpsc::unbounded_channel::<CanFrame>();
-
-    tokio::spawn(async move {
-        loop {
-            let frame = {
-                let socket = socket_clone.lock().unwrap();
-                socket.read_frame()
-            };
-
-            match frame {
-                Ok(frame) => {
-                    if frame_tx.send(frame).is_err() {
-                        break; // Channel closed
-                    }
-                }
-                Err(_) => {
-                    // No frame available or error, sleep briefly
-                    tokio::time::sleep(Duration::from_millis(1)).await;
-                }
-            }
-        }
-    });
+    // Heartbeats of every node on the bus, not just the registered ones
+    let mut heartbeats: HashMap<u8, HeartbeatMonitor> = HashMap::new();
+    let mut raw_frame_subscribers: Vec<RawFrameSubscriber> = Vec::new();
+    let mut transmitted_subscribers: Vec<queue::Sender<ReceivedFrame>> = Vec::new();
+    let mut event_subscribers: Vec<mpsc::UnboundedSender<ConnectionEvent>> = Vec::new();
+    let mut stats = BusStatsCollector::new();
+    let mut stats_timer = tokio::time::interval(STATS_INTERVAL);
+
+    // Nothing is registered yet, so start with an empty (drop-all) filter set
+    let _ = apply_rx_filters(transport.as_ref(), &nodes, &raw_frame_subscribers);
+
+    // Bounded so a stalled manager pushes back into the kernel socket buffer
+    let (frame_tx, mut frame_rx) = mpsc::channel::<ReaderEvent>(READER_QUEUE_CAPACITY);
+    let mut reader = spawn_reader(transport.clone(), frame_tx.clone());
+
+    // Set while the interface is down; the transport is reopened once it comes back
+    let mut interface_lost = false;
+    let mut reconnect_timer = tokio::time::interval(RECONNECT_INTERVAL);
+    // Kept across iterations: a sleep created inside `select!` restarts with every frame
+    // and would never fire on a busy bus
+    let mut timeout_timer = tokio::time::interval(Duration::from_millis(10));
+    timeout_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
 
     // Main event loop
     loop {
@@ -427,28 +1171,56 @@ async fn connection_manager_task(
             // Handle commands from the API
             command = command_rx.recv() => {
                 match command {
-                    Some(ConnectionMessage::AddNode { node_id, response_tx }) => {
-                        nodes.insert(node_id, NodeState::new(node_id, default_timeout));
+                    Some(ConnectionMessage::AddNode { node_id, timeout, response_tx }) => {
+                        // Adding a node again keeps its queued and active requests
+                        match nodes.entry(node_id) {
+                            std::collections::hash_map::Entry::Occupied(mut entry) => {
+                                if let Some(timeout) = timeout {
+                                    entry.get_mut().timeout = timeout;
+                                }
+                            }
+                            std::collections::hash_map::Entry::Vacant(entry) => {
+                                entry.insert(NodeState::new(node_id, timeout.unwrap_or(default_timeout), retry_policy.clone()));
+                            }
+                        }
+                        let _ = apply_rx_filters(transport.as_ref(), &nodes, &raw_frame_subscribers);
+                        let _ = response_tx.send(Ok(()));
+                    }
+
+                    Some(ConnectionMessage::SetNodeTimeout { node_id, timeout, response_tx }) => {
+                        let result = match nodes.get_mut(&node_id) {
+                            Some(node_state) => {
+                                node_state.timeout = timeout;
+                                Ok(())
+                            }
+                            None => Err(CANopenError::NodeNotConnected(node_id)),
+                        };
+                        let _ = response_tx.send(result);
+                    }
+
+                    Some(ConnectionMessage::SetHeartbeatConsumerTime { node_id, consumer_time, response_tx }) => {
+                        heartbeats.entry(node_id).or_insert_with(HeartbeatMonitor::new).set_consumer_time(consumer_time);
                         let _ = response_tx.send(Ok(()));
                     }
 
                     Some(ConnectionMessage::RemoveNode { node_id, response_tx }) => {
                         nodes.remove(&node_id);
+                        let _ = apply_rx_filters(transport.as_ref(), &nodes, &raw_frame_subscribers);
                         let _ = response_tx.send(Ok(()));
                     }
 
-                    Some(ConnectionMessage::SdoRequest { node_id, request, response_tx }) => {
+                    Some(ConnectionMessage::SdoRequest { node_id, request, priority, response_tx }) => {
                         if let Some(node_state) = nodes.get_mut(&node_id) {
-                            let pending_request = PendingSdoRequest {
-                                operation: SdoOperation::Read { request, response_tx },
-                                timestamp: std::time::Instant::now(),
-                            };
+                            let pending_request = PendingSdoRequest::new(
+                                SdoOperation::Read { request, response_tx },
+                                priority,
+                            );
 
                             node_state.queue_request(pending_request);
 
                             // Try to start the request immediately if no active request
                             if let Some(active_request) = node_state.start_next_request() {
-                                send_sdo_operation(&socket, &active_request.operation).await;
+                                send_sdo_operation(transport.as_ref(), &active_request.operation, &mut stats, &mut transmitted_subscribers).await;
                             }
                         } else {
                             let _ = response_tx.send(Err(SdoError::InvalidResponse(
@@ -457,18 +1229,18 @@ async fn connection_manager_task(
                         }
                     }
 
-                    Some(ConnectionMessage::SdoWriteRequest { node_id, request, response_tx }) => {
+                    Some(ConnectionMessage::SdoWriteRequest { node_id, request, priority, response_tx }) => {
                         if let Some(node_state) = nodes.get_mut(&node_id) {
-                            let pending_request = PendingSdoRequest {
-                                operation: SdoOperation::Write { request, response_tx },
-                                timestamp: std::time::Instant::now(),
-                            };
+                            let pending_request = PendingSdoRequest::new(
+                                SdoOperation::Write { request, response_tx },
+                                priority,
+                            );
 
                             node_state.queue_request(pending_request);
 
                             // Try to start the request immediately if no active request
                             if let Some(active_request) = node_state.start_next_request() {
-                                send_sdo_operation(&socket, &active_request.operation).await;
+                                send_sdo_operation(transport.as_ref(), &active_request.operation, &mut stats, &mut transmitted_subscribers).await;
                             }
                         } else {
                             let _ = response_tx.send(Err(SdoError::InvalidResponse(
@@ -477,31 +1249,124 @@ async fn connection_manager_task(
                         }
                     }
 
-                    Some(ConnectionMessage::SubscribeRawFrames { response_tx }) => {
-                        let (tx, rx) = mpsc::unbounded_channel();
-                        raw_frame_subscribers.push(tx);
+                    Some(ConnectionMessage::SubscribeRawFrames { cob_ids, response_tx }) => {
+                        let (tx, rx) = queue::channel(RAW_FRAME_QUEUE_CAPACITY);
+                        raw_frame_subscribers.push(RawFrameSubscriber { cob_ids, tx });
+                        let _ = apply_rx_filters(transport.as_ref(), &nodes, &raw_frame_subscribers);
+                        let _ = response_tx.send(rx);
+                    }
+
+                    Some(ConnectionMessage::SubscribeEvents { response_tx }) => {
+                        let (event_tx, event_rx) = mpsc::unbounded_channel();
+                        event_subscribers.push(event_tx);
+                        let _ = response_tx.send(event_rx);
+                    }
+
+                    Some(ConnectionMessage::SubscribeTransmittedFrames { response_tx }) => {
+                        let (tx, rx) = queue::channel(RAW_FRAME_QUEUE_CAPACITY);
+                        transmitted_subscribers.push(tx);
                         let _ = response_tx.send(rx);
                     }
 
+                    Some(ConnectionMessage::SendFrame { frame, response_tx }) => {
+                        let result = send_frame_counted(transport.as_ref(), &frame, &mut stats, &mut transmitted_subscribers).await
+                            .map_err(|e| CANopenError::SocketError(e.to_string()));
+                        let _ = response_tx.send(result);
+                    }
+
                     None => break, // Channel closed
                 }
             }
 
             // Handle incoming CAN frames
-            frame = frame_rx.recv() => {
-                if let Some(frame) = frame {
-                    // Broadcast frame to raw frame subscribers (for TPDO listeners)
-                    raw_frame_subscribers.retain(|subscriber| {
-                        subscriber.send(frame.clone()).is_ok()
+            event = frame_rx.recv() => {
+                match event {
+                    Some(ReaderEvent::Frame(frame)) => {
+                        stats.record_frame(&frame.frame);
+
+                        // Hand the frame to the subscribers that asked for it (e.g. TPDO listeners)
+                        let subscriber_count = raw_frame_subscribers.len();
+                        raw_frame_subscribers.retain(|subscriber| {
+                            !subscriber.wants(&frame.frame) || subscriber.tx.send(frame.clone()).is_ok()
+                        });
+                        if raw_frame_subscribers.len() != subscriber_count {
+                            let _ = apply_rx_filters(transport.as_ref(), &nodes, &raw_frame_subscribers);
+                        }
+
+                        if let Some((node_id, state)) = receive_heartbeat(&mut heartbeats, &frame.frame) {
+                            let event = ConnectionEvent::NmtState { node_id, state };
+                            event_subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+                        }
+
+                        // Handle SDO responses
+                        handle_can_frame(&mut nodes, frame.frame).await;
+                    }
+                    Some(ReaderEvent::ErrorFrame) => stats.record_error_frame(),
+                    Some(ReaderEvent::ReadError) => stats.record_rx_error(),
+                    Some(ReaderEvent::InterfaceLost(e)) => {
+                        interface_lost = true;
+                        let event = ConnectionEvent::InterfaceLost(format!("{}: {}", transport_config, e));
+                        event_subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+                    }
+                    None => {}
+                }
+            }
+
+            // Reopen the transport once the interface is back; nodes, filters and
+            // subscribers are kept, so existing subscriptions resume by themselves
+            _ = reconnect_timer.tick(), if interface_lost => {
+                if transport_config.is_available() {
+                    match transport_config.open().await {
+                        Ok(new_transport) => {
+                            transport = new_transport;
+                            let _ = apply_rx_filters(transport.as_ref(), &nodes, &raw_frame_subscribers);
+                            reader = spawn_reader(transport.clone(), frame_tx.clone());
+                            interface_lost = false;
+                            println!("CAN interface {} restored", transport_config);
+                            event_subscribers.retain(|subscriber| {
+                                subscriber.send(ConnectionEvent::InterfaceRestored).is_ok()
+                            });
+                        }
+                        Err(e) => eprintln!("Failed to reopen {}: {}", transport_config, e),
+                    }
+                }
+            }
+
+            // Publish bus statistics
+            _ = stats_timer.tick() => {
+                // Receivers of frames that never arrive are only noticed here
+                let subscriber_count = raw_frame_subscribers.len();
+                raw_frame_subscribers.retain(|subscriber| !subscriber.tx.is_closed());
+                if raw_frame_subscribers.len() != subscriber_count {
+                    let _ = apply_rx_filters(transport.as_ref(), &nodes, &raw_frame_subscribers);
+                }
+
+                let report = stats.report(transport.interface_counters());
+                let mut metrics: Vec<NodeCommMetrics> = nodes.iter_mut()
+                    .map(|(&node_id, node_state)| {
+                        let queue_length = node_state.queue_length();
+                        node_state.timings.report(node_id, queue_length)
+                    })
+                    .collect();
+                metrics.sort_by_key(|node| node.node_id);
+
+                let now = std::time::Instant::now();
+                let silent_nodes = heartbeats.iter_mut()
+                    .filter_map(|(&node_id, heartbeat)| {
+                        heartbeat.check(now).map(|state| ConnectionEvent::NmtState { node_id, state })
                     });
+                let events: Vec<ConnectionEvent> = [ConnectionEvent::BusStats(report), ConnectionEvent::CommMetrics(metrics)]
+                    .into_iter()
+                    .chain(silent_nodes)
+                    .collect();
 
-                    // Handle SDO responses
-                    handle_can_frame(&mut nodes, frame).await;
+                for event in events {
+                    event_subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
                 }
             }
 
             // Check for timeouts periodically
-            _ = tokio::time::sleep(Duration::from_millis(10)) => {
+            _ = timeout_timer.tick() => {
                 check_timeouts(&mut nodes).await;
             }
         }
@@ -510,14 +1375,72 @@ async fn connection_manager_task(
         for node_state in nodes.values_mut() {
             if node_state.active_request.is_none() {
                 if let Some(active_request) = node_state.start_next_request() {
-                    send_sdo_operation(&socket, &active_request.operation).await;
+                    send_sdo_operation(transport.as_ref(), &active_request.operation, &mut stats, &mut transmitted_subscribers).await;
                 }
             }
         }
     }
+
+    // The reader holds the transport open until it is stopped
+    reader.abort();
+}
+
+/// Rebuild the kernel receive filters from the registered nodes and raw frame subscriptions
+///
+/// Each node contributes its SDO response (0x580 + id) identifier and each filtered
+/// subscriber its COB-IDs, so frames for other devices on a busy bus never reach
+/// userspace. Heartbeats (0x700 + id) of all nodes pass, to see every node on the
+/// network. An unfiltered subscriber opens the filter.
+fn apply_rx_filters(
+    transport: &dyn CanTransport,
+    nodes: &HashMap<u8, NodeState>,
+    subscribers: &[RawFrameSubscriber],
+) -> Result<(), CANopenError> {
+    let result = if subscribers.iter().any(|subscriber| subscriber.cob_ids.is_none()) {
+        transport.set_rx_filter(None)
+    } else {
+        let mut cob_ids: Vec<u16> = nodes.keys()
+            .map(|&node_id| 0x580 + node_id as u16)
+            .chain((1..=127).map(|node_id| HEARTBEAT_COB_ID_BASE + node_id))
+            .chain(subscribers.iter().flat_map(|subscriber| subscriber.cob_ids.iter().flatten().copied()))
+            .collect();
+        cob_ids.sort_unstable();
+        cob_ids.dedup();
+        transport.set_rx_filter(Some(&cob_ids))
+    };
+
+    result.map_err(|e| {
+        eprintln!("Failed to apply CAN receive filters: {}", e);
+        CANopenError::SocketError(e.to_string())
+    })
+}
+
+/// Transmit a frame, count it (or the failure) in the bus statistics and hand it to the
+/// subscribers of transmitted frames
+async fn send_frame_counted(
+    transport: &dyn CanTransport,
+    frame: &CanFrame,
+    stats: &mut BusStatsCollector,
+    transmitted_subscribers: &mut Vec<queue::Sender<ReceivedFrame>>,
+) -> io::Result<()> {
+    let result = transport.send(frame).await;
+    match result {
+        Ok(()) => {
+            stats.record_frame(frame);
+            let sent = ReceivedFrame { frame: *frame, timestamp: SystemTime::now(), source: TimestampSource::Local, hardware_time: None };
+            transmitted_subscribers.retain(|subscriber| subscriber.send(sent.clone()).is_ok());
+        }
+        Err(_) => stats.record_tx_error(),
+    }
+    result
 }
 
-async fn send_sdo_operation(socket: &Arc<Mutex<CanSocket>>, operation: &SdoOperation) {
+async fn send_sdo_operation(
+    transport: &dyn CanTransport,
+    operation: &SdoOperation,
+    stats: &mut BusStatsCollector,
+    transmitted_subscribers: &mut Vec<queue::Sender<ReceivedFrame>>,
+) {
     use canopen_common::{create_sdo_request_frame, create_sdo_write_frame};
 
     let frame_result = match operation {
@@ -530,9 +1453,23 @@ async fn send_sdo_operation(socket: &Arc<Mutex<CanSocket>>, operation: &SdoOpera
     };
 
     if let Ok(frame) = frame_result {
-        let socket = socket.lock().unwrap();
-        let _ = socket.write_frame(&frame);
+        let _ = send_frame_counted(transport, &frame, stats, transmitted_subscribers).await;
+    }
+}
+
+/// Returns the node's new NMT state if the frame was a heartbeat that changed it
+fn receive_heartbeat(heartbeats: &mut HashMap<u8, HeartbeatMonitor>, frame: &CanFrame) -> Option<(u8, NodeNmtState)> {
+    let socketcan::Id::Standard(id) = frame.id() else {
+        return None;
+    };
+    let node_id = id.as_raw().checked_sub(HEARTBEAT_COB_ID_BASE).filter(|id| (1..=127).contains(id))? as u8;
+    if frame.is_remote_frame() {
+        return None;
     }
+    heartbeats.entry(node_id)
+        .or_insert_with(HeartbeatMonitor::new)
+        .receive(frame.data(), std::time::Instant::now())
+        .map(|state| (node_id, state))
 }
 
 async fn handle_can_frame(nodes: &mut HashMap<u8, NodeState>, frame: CanFrame) {
@@ -542,20 +1479,43 @@ async fn handle_can_frame(nodes: &mut HashMap<u8, NodeState>, frame: CanFrame) {
         socketcan::Id::Extended(_) => return, // We don't handle extended IDs for SDO
     };
 
-    if frame_id >= 0x580 && frame_id <= 0x5FF {
+    if (0x580..=0x5FF).contains(&frame_id) {
         let node_id = (frame_id - 0x580) as u8;
 
         if let Some(node_state) = nodes.get_mut(&node_id) {
             if let Some(completed_request) = node_state.complete_active_request() {
+                let PendingSdoRequest { operation, priority, timestamp, retries, not_before } = completed_request;
+                node_state.timings.record_response(timestamp.elapsed());
+
                 // Parse the response based on operation type
-                match completed_request.operation {
+                match operation {
                     SdoOperation::Read { request, response_tx } => {
-                        let response = parse_sdo_response(frame, &request);
-                        let _ = response_tx.send(response);
+                        match parse_sdo_response(frame, &request) {
+                            Err(error) => {
+                                let retry = PendingSdoRequest {
+                                    operation: SdoOperation::Read { request, response_tx },
+                                    priority, timestamp, retries, not_before,
+                                };
+                                node_state.retry_or_fail(retry, error);
+                            }
+                            response => {
+                                let _ = response_tx.send(response);
+                            }
+                        }
                     }
                     SdoOperation::Write { request, response_tx } => {
-                        let response = parse_sdo_write_response(frame, &request);
-                        let _ = response_tx.send(response);
+                        match parse_sdo_write_response(frame, &request) {
+                            Err(error) => {
+                                let retry = PendingSdoRequest {
+                                    operation: SdoOperation::Write { request, response_tx },
+                                    priority, timestamp, retries, not_before,
+                                };
+                                node_state.retry_or_fail(retry, error);
+                            }
+                            response => {
+                                let _ = response_tx.send(response);
+                            }
+                        }
                     }
                 }
             }
@@ -568,15 +1528,7 @@ async fn handle_can_frame(nodes: &mut HashMap<u8, NodeState>, frame: CanFrame) {
 async fn check_timeouts(nodes: &mut HashMap<u8, NodeState>) {
     for node_state in nodes.values_mut() {
         if let Some(timed_out_request) = node_state.check_timeout() {
-            // Send timeout error based on operation type
-            match timed_out_request.operation {
-                SdoOperation::Read { response_tx, .. } => {
-                    let _ = response_tx.send(Err(SdoError::Timeout));
-                }
-                SdoOperation::Write { response_tx, .. } => {
-                    let _ = response_tx.send(Err(SdoError::Timeout));
-                }
-            }
+            node_state.retry_or_fail(timed_out_request, SdoError::Timeout);
         }
     }
 }
@@ -585,6 +1537,179 @@ async fn check_timeouts(nodes: &mut HashMap<u8, NodeState>) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use canopen_common::SdoDataType;
+
+    fn pending_read(index: u16, priority: SdoPriority) -> PendingSdoRequest {
+        let (response_tx, _response_rx) = oneshot::channel();
+        PendingSdoRequest::new(
+            SdoOperation::Read {
+                request: SdoRequest { node_id: 1, index, subindex: 0, expected_type: SdoDataType::UInt8 },
+                response_tx,
+            },
+            priority,
+        )
+    }
+
+    fn active_index(node_state: &mut NodeState) -> Option<u16> {
+        match node_state.start_next_request()?.operation {
+            SdoOperation::Read { ref request, .. } => Some(request.index),
+            SdoOperation::Write { ref request, .. } => Some(request.index),
+        }
+    }
+
+    #[test]
+    fn test_interactive_request_jumps_polling_queue() {
+        let mut node_state = NodeState::new(1, Duration::from_millis(100), SdoRetryPolicy::default());
+        node_state.queue_request(pending_read(0x2000, SdoPriority::Polling));
+        node_state.queue_request(pending_read(0x2001, SdoPriority::Polling));
+        node_state.queue_request(pending_read(0x1000, SdoPriority::HealthCheck));
+        node_state.queue_request(pending_read(0x3000, SdoPriority::Interactive));
+
+        let mut order = Vec::new();
+        while let Some(index) = active_index(&mut node_state) {
+            order.push(index);
+            node_state.complete_active_request();
+        }
+
+        assert_eq!(order, vec![0x3000, 0x1000, 0x2000, 0x2001]);
+    }
+
+    #[test]
+    fn test_retry_policy_backoff_and_abort_codes() {
+        let policy = SdoRetryPolicy {
+            max_retries: 2,
+            retry_delay: Duration::from_millis(10),
+            retry_abort_codes: vec![0x0800_0022],
+        };
+        let abort = |code| SdoError::AbortTransfer { code, info: String::new() };
+
+        assert_eq!(policy.retry_delay(&SdoError::Timeout, 0), Some(Duration::from_millis(10)));
+        assert_eq!(policy.retry_delay(&SdoError::Timeout, 1), Some(Duration::from_millis(20)));
+        assert_eq!(policy.retry_delay(&SdoError::Timeout, 2), None);
+        assert_eq!(policy.retry_delay(&abort(0x0800_0022), 0), Some(Duration::from_millis(10)));
+        assert_eq!(policy.retry_delay(&abort(0x0602_0000), 0), None);
+    }
+
+    #[test]
+    fn test_timed_out_request_is_retried_after_delay() {
+        let policy = SdoRetryPolicy {
+            max_retries: 1,
+            retry_delay: Duration::from_secs(60),
+            retry_abort_codes: Vec::new(),
+        };
+        let mut node_state = NodeState::new(1, Duration::from_millis(100), policy);
+        node_state.queue_request(pending_read(0x2000, SdoPriority::Polling));
+        node_state.queue_request(pending_read(0x2001, SdoPriority::Polling));
+
+        assert_eq!(active_index(&mut node_state), Some(0x2000));
+        let timed_out = node_state.complete_active_request().unwrap();
+        node_state.retry_or_fail(timed_out, SdoError::Timeout);
+
+        // The retry waits out its delay, so the next request goes first
+        assert_eq!(active_index(&mut node_state), Some(0x2001));
+        node_state.complete_active_request();
+        assert_eq!(active_index(&mut node_state), None);
+        assert_eq!(node_state.pending_requests.len(), 1);
+    }
+
+    #[test]
+    fn test_filtered_raw_frame_subscriber() {
+        let (tx, _rx) = queue::channel(1);
+        let subscriber = RawFrameSubscriber { cob_ids: Some(HashSet::from([0x184])), tx };
+        let frame = |id| CanFrame::new(socketcan::StandardId::new(id).unwrap(), &[0]).unwrap();
+
+        assert!(subscriber.wants(&frame(0x184)));
+        assert!(!subscriber.wants(&frame(0x284)));
+
+        let (tx, rx) = queue::channel(1);
+        let subscriber = RawFrameSubscriber { cob_ids: None, tx };
+        assert!(subscriber.wants(&frame(0x284)));
+        drop(rx);
+        assert!(subscriber.tx.is_closed());
+    }
+    /// Receives frames back to back and never answers an SDO request
+    struct FloodTransport;
+
+    impl CanTransport for FloodTransport {
+        fn recv(&self) -> TransportFuture<'_, ReceivedFrame> {
+            Box::pin(async {
+                tokio::task::yield_now().await;
+                let frame = CanFrame::new(socketcan::StandardId::new(0x181).unwrap(), &[0; 8]).unwrap();
+                Ok(ReceivedFrame { frame, timestamp: SystemTime::now(), source: TimestampSource::Local, hardware_time: None })
+            })
+        }
+
+        fn send<'a>(&'a self, _frame: &'a CanFrame) -> TransportFuture<'a, ()> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    async fn flooded_read(policy: SdoRetryPolicy) -> Option<Result<SdoResponse, SdoError>> {
+        let (command_tx, command_rx) = mpsc::unbounded_channel();
+        let transport_config = TransportConfig::SocketCan { interface: "flood".to_string() };
+        let manager = tokio::spawn(connection_manager_task(
+            transport_config, Arc::new(FloodTransport), command_rx, Duration::from_millis(30), policy,
+        ));
+        let (response_tx, response_rx) = oneshot::channel();
+        command_tx.send(ConnectionMessage::AddNode { node_id: 1, timeout: None, response_tx }).unwrap();
+        response_rx.await.unwrap().unwrap();
+
+        let (response_tx, response_rx) = oneshot::channel();
+        command_tx.send(ConnectionMessage::SdoRequest {
+            node_id: 1,
+            request: SdoRequest { node_id: 1, index: 0x1000, subindex: 0, expected_type: SdoDataType::UInt32 },
+            priority: SdoPriority::Interactive,
+            response_tx,
+        }).unwrap();
+        let result = tokio::time::timeout(Duration::from_secs(2), response_rx).await.ok().and_then(Result::ok);
+        manager.abort();
+        result
+    }
+
+    #[tokio::test]
+    async fn test_adding_a_node_again_keeps_its_requests() {
+        let policy = SdoRetryPolicy { max_retries: 0, ..SdoRetryPolicy::default() };
+        let (command_tx, command_rx) = mpsc::unbounded_channel();
+        let transport_config = TransportConfig::SocketCan { interface: "flood".to_string() };
+        let manager = tokio::spawn(connection_manager_task(
+            transport_config, Arc::new(FloodTransport), command_rx, Duration::from_millis(30), policy,
+        ));
+        let add_node = || {
+            let (response_tx, response_rx) = oneshot::channel();
+            command_tx.send(ConnectionMessage::AddNode { node_id: 1, timeout: None, response_tx }).unwrap();
+            response_rx
+        };
+        add_node().await.unwrap().unwrap();
+
+        let (response_tx, response_rx) = oneshot::channel();
+        command_tx.send(ConnectionMessage::SdoRequest {
+            node_id: 1,
+            request: SdoRequest { node_id: 1, index: 0x1000, subindex: 0, expected_type: SdoDataType::UInt32 },
+            priority: SdoPriority::Interactive,
+            response_tx,
+        }).unwrap();
+        add_node().await.unwrap().unwrap();
+
+        // The request still gets its answer (a timeout) instead of being dropped
+        let result = tokio::time::timeout(Duration::from_secs(2), response_rx).await.unwrap();
+        manager.abort();
+        assert!(matches!(result, Ok(Err(SdoError::Timeout))));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_fires_on_busy_bus() {
+        let policy = SdoRetryPolicy { max_retries: 0, ..SdoRetryPolicy::default() };
+        assert!(matches!(flooded_read(policy).await, Some(Err(SdoError::Timeout))));
+    }
+
+    #[tokio::test]
+    async fn test_retry_runs_on_busy_bus() {
+        // Three 30 ms attempts with 10 ms between them still time out well within the limit
+        let policy = SdoRetryPolicy { max_retries: 2, retry_delay: Duration::from_millis(10), retry_abort_codes: Vec::new() };
+        let started = std::time::Instant::now();
+        assert!(matches!(flooded_read(policy).await, Some(Err(SdoError::Timeout))));
+        assert!(started.elapsed() >= Duration::from_millis(90));
+    }
 
     #[tokio::test]
     async fn test_multiple_concurrent_requests() {
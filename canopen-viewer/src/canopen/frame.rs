@@ -0,0 +1,60 @@
+// frame.rs - Conversions between CanFrame and the Linux can_id word
+//
+// Backends that don't go through the socketcan crate (recvmsg, TCP, USB) describe
+// frames as a 32-bit identifier carrying the EFF/RTR/ERR flags plus the data bytes.
+use socketcan::{CanFrame, EmbeddedFrame, ExtendedId, Id, StandardId};
+
+/// Build a frame from a flagged identifier and its data (`dlc` is used for remote frames)
+///
+/// Returns `None` for error frames and invalid identifiers.
+pub fn from_raw_parts(can_id: u32, dlc: usize, data: &[u8]) -> Option<CanFrame> {
+    if can_id & libc::CAN_ERR_FLAG != 0 {
+        return None;
+    }
+
+    let id: Id = if can_id & libc::CAN_EFF_FLAG != 0 {
+        ExtendedId::new(can_id & libc::CAN_EFF_MASK)?.into()
+    } else {
+        StandardId::new((can_id & libc::CAN_SFF_MASK) as u16)?.into()
+    };
+
+    if can_id & libc::CAN_RTR_FLAG != 0 {
+        CanFrame::new_remote(id, dlc.min(8))
+    } else {
+        CanFrame::new(id, &data[..data.len().min(8)])
+    }
+}
+
+/// Identifier of a frame with the EFF/RTR flags set as in `struct can_frame`
+pub fn raw_can_id(frame: &CanFrame) -> u32 {
+    let id = match frame.id() {
+        Id::Standard(id) => id.as_raw() as u32,
+        Id::Extended(id) => id.as_raw() | libc::CAN_EFF_FLAG,
+    };
+
+    if frame.is_remote_frame() {
+        id | libc::CAN_RTR_FLAG
+    } else {
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_parts_round_trip() {
+        let frame = from_raw_parts(0x181, 2, &[0x12, 0x34]).unwrap();
+        assert_eq!(frame.id(), Id::Standard(StandardId::new(0x181).unwrap()));
+        assert_eq!(frame.data(), &[0x12, 0x34]);
+        assert_eq!(raw_can_id(&frame), 0x181);
+
+        let extended = 0x1234_5678 | libc::CAN_EFF_FLAG;
+        let frame = from_raw_parts(extended, 0, &[]).unwrap();
+        assert_eq!(frame.id(), Id::Extended(ExtendedId::new(0x1234_5678).unwrap()));
+        assert_eq!(raw_can_id(&frame), extended);
+
+        assert!(from_raw_parts(0x080 | libc::CAN_ERR_FLAG, 8, &[0; 8]).is_none());
+    }
+}
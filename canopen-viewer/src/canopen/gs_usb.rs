@@ -0,0 +1,347 @@
+// gs_usb.rs - candleLight / gs_usb USB adapters driven directly through libusb
+//
+// Works without the Linux gs_usb kernel driver, so the same adapter can be used on
+// macOS and Windows. Protocol: vendor control requests configure bit timing and
+// mode, bulk endpoints carry `gs_host_frame` structs (little endian).
+use rusb::{DeviceHandle, Direction, GlobalContext, Recipient, RequestType, UsbContext};
+use socketcan::{CanFrame, EmbeddedFrame};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+
+use super::connect::{CanTransport, TransportFuture};
+use super::frame;
+use super::timestamp::{ReceivedFrame, TimestampSource};
+
+/// (vendor, product) IDs of adapters speaking the gs_usb protocol
+const GS_USB_DEVICES: &[(u16, u16)] = &[
+    (0x1d50, 0x606f), // candleLight / CANable
+    (0x1209, 0x2323), // candleLight (pid.codes)
+    (0x1cd2, 0x606f), // CES CANext FD
+    (0x16d0, 0x10b8), // ABE CANdebugger FD
+];
+
+const GS_USB_BREQ_HOST_FORMAT: u8 = 0;
+const GS_USB_BREQ_BITTIMING: u8 = 1;
+const GS_USB_BREQ_MODE: u8 = 2;
+const GS_USB_BREQ_BT_CONST: u8 = 4;
+
+const GS_CAN_MODE_RESET: u32 = 0;
+const GS_CAN_MODE_START: u32 = 1;
+const GS_CAN_MODE_HW_TIMESTAMP: u32 = 1 << 4;
+const GS_CAN_FEATURE_HW_TIMESTAMP: u32 = 1 << 4;
+
+const ENDPOINT_IN: u8 = 0x81;
+const ENDPOINT_OUT: u8 = 0x02;
+const INTERFACE: u8 = 0;
+const CHANNEL: u8 = 0;
+
+/// echo_id of frames received from the bus (anything else is a TX echo)
+const RX_ECHO_ID: u32 = 0xFFFF_FFFF;
+
+/// gs_host_frame without / with the trailing hardware timestamp
+const HOST_FRAME_SIZE: usize = 20;
+const HOST_FRAME_SIZE_TIMESTAMP: usize = 24;
+
+const CONTROL_TIMEOUT: Duration = Duration::from_millis(500);
+/// Bulk reads wake up this often to notice shutdown
+const READ_POLL_TIMEOUT: Duration = Duration::from_millis(100);
+/// Sample point used when deriving bit timing from the bitrate
+const SAMPLE_POINT_PERMILLE: u32 = 875;
+
+/// Bitrate used when the interface string doesn't name one
+pub const DEFAULT_BITRATE: u32 = 500_000;
+
+/// Bit timing limits reported by the device (GS_USB_BREQ_BT_CONST)
+#[derive(Debug, Clone, Copy)]
+struct BitTimingConst {
+    feature: u32,
+    fclk_can: u32,
+    tseg1_min: u32,
+    tseg1_max: u32,
+    tseg2_min: u32,
+    tseg2_max: u32,
+    sjw_max: u32,
+    brp_min: u32,
+    brp_max: u32,
+    brp_inc: u32,
+}
+
+/// Bit timing sent to the device (GS_USB_BREQ_BITTIMING)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BitTiming {
+    prop_seg: u32,
+    phase_seg1: u32,
+    phase_seg2: u32,
+    sjw: u32,
+    brp: u32,
+}
+
+/// gs_usb adapter opened through libusb
+pub struct GsUsbTransport {
+    handle: Arc<DeviceHandle<GlobalContext>>,
+    frame_rx: tokio::sync::Mutex<mpsc::Receiver<io::Result<ReceivedFrame>>>,
+    running: Arc<AtomicBool>,
+}
+
+/// Adapters currently plugged in, in enumeration order (the index used in "gs_usb://<index>")
+pub fn list_devices() -> Vec<String> {
+    let Ok(devices) = rusb::devices() else {
+        return Vec::new();
+    };
+
+    devices.iter()
+        .filter(is_gs_usb)
+        .map(|device| format!("bus {} address {}", device.bus_number(), device.address()))
+        .collect()
+}
+
+fn is_gs_usb<T: UsbContext>(device: &rusb::Device<T>) -> bool {
+    device.device_descriptor()
+        .map(|descriptor| GS_USB_DEVICES.contains(&(descriptor.vendor_id(), descriptor.product_id())))
+        .unwrap_or(false)
+}
+
+impl GsUsbTransport {
+    /// Open the `index`-th gs_usb adapter and start it at `bitrate`
+    pub fn open(index: usize, bitrate: u32) -> io::Result<Self> {
+        let device = rusb::devices()
+            .map_err(usb_error)?
+            .iter()
+            .filter(is_gs_usb)
+            .nth(index)
+            .ok_or_else(|| io::Error::from_raw_os_error(libc::ENODEV))?;
+
+        let handle = device.open().map_err(usb_error)?;
+        // Detaching the kernel driver is only supported (and needed) on Linux
+        let _ = handle.set_auto_detach_kernel_driver(true);
+        handle.claim_interface(INTERFACE).map_err(usb_error)?;
+
+        write_control(&handle, GS_USB_BREQ_HOST_FORMAT, &0x0000_beef_u32.to_le_bytes())?;
+
+        let bt_const = read_bt_const(&handle)?;
+        let timing = calculate_bit_timing(&bt_const, bitrate).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("Bitrate {} not supported by the adapter", bitrate))
+        })?;
+
+        // Stop the channel before reconfiguring it (it may still run from a previous session)
+        write_control(&handle, GS_USB_BREQ_MODE, &encode_u32s(&[GS_CAN_MODE_RESET, 0]))?;
+        write_control(&handle, GS_USB_BREQ_BITTIMING, &encode_u32s(&[
+            timing.prop_seg, timing.phase_seg1, timing.phase_seg2, timing.sjw, timing.brp,
+        ]))?;
+
+        let hw_timestamps = bt_const.feature & GS_CAN_FEATURE_HW_TIMESTAMP != 0;
+        let flags = if hw_timestamps { GS_CAN_MODE_HW_TIMESTAMP } else { 0 };
+        write_control(&handle, GS_USB_BREQ_MODE, &encode_u32s(&[GS_CAN_MODE_START, flags]))?;
+
+        let frame_size = if hw_timestamps { HOST_FRAME_SIZE_TIMESTAMP } else { HOST_FRAME_SIZE };
+        let handle = Arc::new(handle);
+        let running = Arc::new(AtomicBool::new(true));
+        let (frame_tx, frame_rx) = mpsc::channel(256);
+
+        // libusb transfers block, so frames are read on a dedicated thread
+        let reader_handle = handle.clone();
+        let reader_running = running.clone();
+        std::thread::spawn(move || read_loop(reader_handle, reader_running, frame_size, frame_tx));
+
+        Ok(Self {
+            handle,
+            frame_rx: tokio::sync::Mutex::new(frame_rx),
+            running,
+        })
+    }
+}
+
+impl CanTransport for GsUsbTransport {
+    fn recv(&self) -> TransportFuture<'_, ReceivedFrame> {
+        Box::pin(async move {
+            self.frame_rx.lock().await.recv().await
+                .unwrap_or_else(|| Err(io::Error::from_raw_os_error(libc::ENODEV)))
+        })
+    }
+
+    fn send<'a>(&'a self, frame: &'a CanFrame) -> TransportFuture<'a, ()> {
+        let handle = self.handle.clone();
+        // TX frames never carry the timestamp field
+        let mut buffer = vec![0u8; HOST_FRAME_SIZE];
+        buffer[0..4].copy_from_slice(&0u32.to_le_bytes()); // echo_id
+        buffer[4..8].copy_from_slice(&frame::raw_can_id(frame).to_le_bytes());
+        buffer[8] = frame.dlc() as u8;
+        buffer[9] = CHANNEL;
+        buffer[12..12 + frame.data().len()].copy_from_slice(frame.data());
+
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || {
+                handle.write_bulk(ENDPOINT_OUT, &buffer, CONTROL_TIMEOUT).map(|_| ()).map_err(usb_error)
+            })
+            .await
+            .map_err(io::Error::other)?
+        })
+    }
+}
+
+impl Drop for GsUsbTransport {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+        let _ = write_control(&self.handle, GS_USB_BREQ_MODE, &encode_u32s(&[GS_CAN_MODE_RESET, 0]));
+        let _ = self.handle.release_interface(INTERFACE);
+    }
+}
+
+fn read_loop(
+    handle: Arc<DeviceHandle<GlobalContext>>,
+    running: Arc<AtomicBool>,
+    frame_size: usize,
+    frame_tx: mpsc::Sender<io::Result<ReceivedFrame>>,
+) {
+    let mut buffer = vec![0u8; frame_size];
+    // Device time (µs, wrapping) of the first frame and the wall clock at that moment
+    let mut clock: Option<(u64, SystemTime, u32)> = None;
+
+    while running.load(Ordering::Acquire) {
+        match handle.read_bulk(ENDPOINT_IN, &mut buffer, READ_POLL_TIMEOUT) {
+            Ok(len) if len >= HOST_FRAME_SIZE => {
+                let echo_id = u32::from_le_bytes(buffer[0..4].try_into().unwrap());
+                if echo_id != RX_ECHO_ID {
+                    continue; // Our own transmission echoed back
+                }
+
+                let can_id = u32::from_le_bytes(buffer[4..8].try_into().unwrap());
+                let dlc = (buffer[8] as usize).min(8);
+                let Some(frame) = frame::from_raw_parts(can_id, dlc, &buffer[12..12 + dlc]) else {
+                    continue; // Error frame
+                };
+
+                let (timestamp, source) = if len >= HOST_FRAME_SIZE_TIMESTAMP {
+                    let device_us = u32::from_le_bytes(buffer[20..24].try_into().unwrap());
+                    (device_time_to_system_time(&mut clock, device_us), TimestampSource::Hardware)
+                } else {
+                    (SystemTime::now(), TimestampSource::Local)
+                };
+
+                if frame_tx.blocking_send(Ok(ReceivedFrame { frame, timestamp, source, hardware_time: None })).is_err() {
+                    break; // Transport dropped
+                }
+            }
+            Ok(_) | Err(rusb::Error::Timeout) => continue,
+            Err(e) => {
+                let _ = frame_tx.blocking_send(Err(usb_error(e)));
+                break;
+            }
+        }
+    }
+}
+
+/// Map the adapter's wrapping 32-bit µs counter onto wall-clock time
+fn device_time_to_system_time(clock: &mut Option<(u64, SystemTime, u32)>, device_us: u32) -> SystemTime {
+    let (elapsed_us, start, last) = clock.get_or_insert((0, SystemTime::now(), device_us));
+
+    *elapsed_us += device_us.wrapping_sub(*last) as u64;
+    *last = device_us;
+    *start + Duration::from_micros(*elapsed_us)
+}
+
+/// Pick prescaler and segments for `bitrate` within the device limits
+fn calculate_bit_timing(limits: &BitTimingConst, bitrate: u32) -> Option<BitTiming> {
+    let brp_inc = limits.brp_inc.max(1);
+    let mut brp = limits.brp_min.max(1);
+
+    while brp <= limits.brp_max {
+        let divisor = brp * bitrate;
+        if limits.fclk_can.is_multiple_of(divisor) {
+            // Time quanta per bit: sync segment + tseg1 + tseg2
+            let tq = limits.fclk_can / divisor;
+            let tseg2 = (tq * (1000 - SAMPLE_POINT_PERMILLE) / 1000).clamp(limits.tseg2_min, limits.tseg2_max);
+            let tseg1 = tq.saturating_sub(1 + tseg2);
+
+            if (limits.tseg1_min..=limits.tseg1_max).contains(&tseg1) && tq <= 25 {
+                return Some(BitTiming {
+                    prop_seg: 1,
+                    phase_seg1: tseg1 - 1,
+                    phase_seg2: tseg2,
+                    sjw: tseg2.min(limits.sjw_max).max(1),
+                    brp,
+                });
+            }
+        }
+        brp += brp_inc;
+    }
+
+    None
+}
+
+fn read_bt_const(handle: &DeviceHandle<GlobalContext>) -> io::Result<BitTimingConst> {
+    let mut buffer = [0u8; 40];
+    let request_type = rusb::request_type(Direction::In, RequestType::Vendor, Recipient::Interface);
+    let len = handle
+        .read_control(request_type, GS_USB_BREQ_BT_CONST, CHANNEL as u16, INTERFACE as u16, &mut buffer, CONTROL_TIMEOUT)
+        .map_err(usb_error)?;
+    if len < buffer.len() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Short BT_CONST reply"));
+    }
+
+    let field = |i: usize| u32::from_le_bytes(buffer[i * 4..i * 4 + 4].try_into().unwrap());
+    Ok(BitTimingConst {
+        feature: field(0),
+        fclk_can: field(1),
+        tseg1_min: field(2),
+        tseg1_max: field(3),
+        tseg2_min: field(4),
+        tseg2_max: field(5),
+        sjw_max: field(6),
+        brp_min: field(7),
+        brp_max: field(8),
+        brp_inc: field(9),
+    })
+}
+
+fn write_control(handle: &DeviceHandle<GlobalContext>, request: u8, data: &[u8]) -> io::Result<()> {
+    let request_type = rusb::request_type(Direction::Out, RequestType::Vendor, Recipient::Interface);
+    handle
+        .write_control(request_type, request, CHANNEL as u16, INTERFACE as u16, data, CONTROL_TIMEOUT)
+        .map(|_| ())
+        .map_err(usb_error)
+}
+
+fn encode_u32s(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+/// Unplugged adapters map to ENODEV so the connection treats them as a lost interface
+fn usb_error(error: rusb::Error) -> io::Error {
+    match error {
+        rusb::Error::NoDevice => io::Error::from_raw_os_error(libc::ENODEV),
+        rusb::Error::Access => io::Error::new(io::ErrorKind::PermissionDenied, error),
+        other => io::Error::other(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bit_timing_for_candlelight_clock() {
+        // STM32F0 candleLight: 48 MHz CAN clock
+        let limits = BitTimingConst {
+            feature: 0,
+            fclk_can: 48_000_000,
+            tseg1_min: 1,
+            tseg1_max: 16,
+            tseg2_min: 1,
+            tseg2_max: 8,
+            sjw_max: 4,
+            brp_min: 1,
+            brp_max: 1024,
+            brp_inc: 1,
+        };
+
+        let timing = calculate_bit_timing(&limits, 500_000).unwrap();
+        let tq = 1 + timing.prop_seg + timing.phase_seg1 + timing.phase_seg2;
+        assert_eq!(limits.fclk_can / (timing.brp * tq), 500_000);
+        // Sample point close to 87.5%
+        assert_eq!((1 + timing.prop_seg + timing.phase_seg1) * 1000 / tq, 875);
+    }
+}
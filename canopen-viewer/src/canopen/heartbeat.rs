@@ -0,0 +1,152 @@
+// heartbeat.rs - NMT state of a node from its heartbeat frames
+//
+// Every heartbeat (0x700 + node ID) carries the node's NMT state. A node is reported as
+// silent once no heartbeat arrived within the consumer time, or without one a few of
+// the periods measured between frames, so an unplugged node isn't shown in its last state.
+use canopen_common::NmtState;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Heartbeats missed before a node counts as silent
+const SILENT_CYCLES: u32 = 3;
+
+/// Heartbeat period assumed until two heartbeats were seen
+const DEFAULT_PERIOD: Duration = Duration::from_secs(1);
+
+/// State changes this soon after an NMT command to the node are expected
+pub const COMMAND_GRACE: Duration = Duration::from_secs(5);
+
+/// What the viewer knows about a node's NMT state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeNmtState {
+    /// No heartbeat received since connecting (heartbeat producer disabled?)
+    NoHeartbeat,
+    Active(NmtState),
+    /// Heartbeats stopped arriving
+    Silent,
+}
+
+impl fmt::Display for NodeNmtState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoHeartbeat => write!(f, "No heartbeat"),
+            Self::Active(state) => write!(f, "{}", state),
+            Self::Silent => write!(f, "Silent"),
+        }
+    }
+}
+
+/// Alarm text for a change of a node's state; `commanded` if an NMT command was just sent to it
+pub fn alarm(previous: Option<NodeNmtState>, state: NodeNmtState, commanded: bool) -> Option<String> {
+    match (previous, state) {
+        (Some(NodeNmtState::Silent), NodeNmtState::Silent) => None,
+        (_, NodeNmtState::Silent) => Some("heartbeat lost".to_string()),
+        // Leaving boot-up is the node's own start-up
+        (Some(NodeNmtState::Active(NmtState::BootUp)), _) => None,
+        (Some(NodeNmtState::Active(_)), NodeNmtState::Active(NmtState::BootUp)) if !commanded => {
+            Some("rebooted unexpectedly".to_string())
+        }
+        (Some(NodeNmtState::Active(before)), NodeNmtState::Active(after)) if before != after && !commanded => {
+            Some(format!("changed from {} to {} unexpectedly", before, after))
+        }
+        _ => None,
+    }
+}
+
+pub struct HeartbeatMonitor {
+    state: NodeNmtState,
+    last: Option<Instant>,
+    period: Option<Duration>,
+    /// Longest expected gap between heartbeats, set by the user
+    consumer_time: Option<Duration>,
+}
+
+impl HeartbeatMonitor {
+    pub fn new() -> Self {
+        Self { state: NodeNmtState::NoHeartbeat, last: None, period: None, consumer_time: None }
+    }
+
+    /// Time without heartbeats after which the node is silent; `None` derives it from the period
+    pub fn set_consumer_time(&mut self, consumer_time: Option<Duration>) {
+        self.consumer_time = consumer_time;
+    }
+
+    /// Take in a heartbeat frame; returns the new state if it changed
+    pub fn receive(&mut self, data: &[u8], at: Instant) -> Option<NodeNmtState> {
+        let state = NmtState::from_heartbeat(data)?;
+        // The boot-up message comes before the periodic heartbeats, not at their rate
+        if state != NmtState::BootUp {
+            if let Some(last) = self.last {
+                self.period = Some(at.saturating_duration_since(last));
+            }
+            self.last = Some(at);
+        }
+        self.set(NodeNmtState::Active(state))
+    }
+
+    /// Mark the node silent once its heartbeats stayed away; returns the new state if it changed
+    pub fn check(&mut self, now: Instant) -> Option<NodeNmtState> {
+        let last = self.last?;
+        let timeout = self.consumer_time
+            .unwrap_or_else(|| self.period.unwrap_or(DEFAULT_PERIOD).max(Duration::from_millis(100)) * SILENT_CYCLES);
+        if now.saturating_duration_since(last) > timeout {
+            self.set(NodeNmtState::Silent)
+        } else {
+            None
+        }
+    }
+
+    fn set(&mut self, state: NodeNmtState) -> Option<NodeNmtState> {
+        (self.state != state).then(|| {
+            self.state = state;
+            state
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat_states() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut monitor = HeartbeatMonitor::new();
+
+        assert_eq!(monitor.check(at(5000)), None);
+        assert_eq!(monitor.receive(&[0x00], at(0)), Some(NodeNmtState::Active(NmtState::BootUp)));
+        assert_eq!(monitor.receive(&[0x7F], at(10)), Some(NodeNmtState::Active(NmtState::PreOperational)));
+        assert_eq!(monitor.receive(&[0x7F], at(510)), None);
+        // Toggle bit of node guarding replies is ignored
+        assert_eq!(monitor.receive(&[0x85], at(1010)), Some(NodeNmtState::Active(NmtState::Operational)));
+        assert_eq!(monitor.receive(&[0x42], at(1020)), None);
+
+        // 500 ms heartbeat: silent after three missed
+        assert_eq!(monitor.check(at(2400)), None);
+        assert_eq!(monitor.check(at(2600)), Some(NodeNmtState::Silent));
+        assert_eq!(monitor.check(at(3000)), None);
+        assert_eq!(monitor.receive(&[0x05], at(3100)), Some(NodeNmtState::Active(NmtState::Operational)));
+
+        // The consumer time replaces the measured periods
+        monitor.set_consumer_time(Some(Duration::from_millis(700)));
+        assert_eq!(monitor.check(at(3700)), None);
+        assert_eq!(monitor.check(at(3900)), Some(NodeNmtState::Silent));
+    }
+
+    #[test]
+    fn test_heartbeat_alarms() {
+        let active = NodeNmtState::Active;
+        assert_eq!(alarm(Some(active(NmtState::Operational)), NodeNmtState::Silent, false), Some("heartbeat lost".to_string()));
+        assert_eq!(alarm(Some(NodeNmtState::Silent), NodeNmtState::Silent, false), None);
+        assert_eq!(
+            alarm(Some(active(NmtState::Operational)), active(NmtState::PreOperational), false),
+            Some("changed from Operational to Pre-Operational unexpectedly".to_string())
+        );
+        assert_eq!(alarm(Some(active(NmtState::Operational)), active(NmtState::PreOperational), true), None);
+        assert!(alarm(Some(active(NmtState::Operational)), active(NmtState::BootUp), false).is_some());
+        assert_eq!(alarm(Some(active(NmtState::BootUp)), active(NmtState::PreOperational), false), None);
+        assert_eq!(alarm(None, active(NmtState::Operational), false), None);
+        assert_eq!(alarm(Some(NodeNmtState::Silent), active(NmtState::Operational), false), None);
+    }
+}
@@ -1,11 +1,23 @@
 // Connection management is still local to the viewer
 pub mod connect;
+pub mod frame;
+pub mod gs_usb;
+pub mod heartbeat;
+pub mod netlink;
+pub mod pcap;
+pub mod replay;
+pub mod simulation;
+pub mod stats;
+pub mod tcp;
+pub mod timestamp;
 
 // SDO protocol is now in the common library
 // Re-export from canopen-common for backwards compatibility
 pub use canopen_common::{
-    SdoRequest, SdoDataType
+    SdoRequest, SdoWriteRequest, SdoDataType, NmtCommand
 };
 
-pub use connect::{CANopenConnection, CANopenNodeHandle};
+pub use connect::{
+    CANopenConnection, CANopenError, CANopenNodeHandle, ConnectionEvent, SdoPriority, SdoRetryPolicy, TransportConfig
+};
 
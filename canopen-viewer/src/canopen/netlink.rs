@@ -0,0 +1,306 @@
+// netlink.rs - CAN controller state and bus-off recovery over rtnetlink
+//
+// The kernel reports a CAN controller's state, error counters and error statistics as
+// link attributes (what `ip -details -statistics link show can0` prints), and restarts
+// a bus-off controller on an RTM_NEWLINK request with IFLA_CAN_RESTART. Messages are
+// built and parsed by hand on a raw NETLINK_ROUTE socket. Restarting needs CAP_NET_ADMIN.
+use std::ffi::CString;
+use std::fmt;
+use std::io;
+use std::mem;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+/// CAN attributes inside IFLA_INFO_DATA (linux/can/netlink.h)
+const IFLA_CAN_BITTIMING: u16 = 1;
+const IFLA_CAN_STATE: u16 = 4;
+const IFLA_CAN_RESTART_MS: u16 = 6;
+const IFLA_CAN_RESTART: u16 = 7;
+const IFLA_CAN_BERR_COUNTER: u16 = 8;
+
+/// Attribute type bits, without the nested and byte order flags
+const NLA_TYPE_MASK: u16 = 0x3FFF;
+
+const NLMSG_HEADER_LEN: usize = 16;
+const IFINFOMSG_LEN: usize = 16;
+
+/// `enum can_state`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControllerState {
+    ErrorActive,
+    ErrorWarning,
+    ErrorPassive,
+    BusOff,
+    Stopped,
+    Sleeping,
+    Unknown(u32),
+}
+
+impl ControllerState {
+    fn from_raw(value: u32) -> Self {
+        match value {
+            0 => Self::ErrorActive,
+            1 => Self::ErrorWarning,
+            2 => Self::ErrorPassive,
+            3 => Self::BusOff,
+            4 => Self::Stopped,
+            5 => Self::Sleeping,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl fmt::Display for ControllerState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ErrorActive => write!(f, "Error-active"),
+            Self::ErrorWarning => write!(f, "Error-warning"),
+            Self::ErrorPassive => write!(f, "Error-passive"),
+            Self::BusOff => write!(f, "Bus-off"),
+            Self::Stopped => write!(f, "Stopped"),
+            Self::Sleeping => write!(f, "Sleeping"),
+            Self::Unknown(value) => write!(f, "Unknown ({})", value),
+        }
+    }
+}
+
+/// `struct can_device_stats`: how often the controller went through each error state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DeviceStats {
+    pub bus_errors: u32,
+    pub error_warning: u32,
+    pub error_passive: u32,
+    pub bus_off: u32,
+    pub arbitration_lost: u32,
+    pub restarts: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControllerStatus {
+    pub state: ControllerState,
+    /// Transmit and receive error counters, if the driver reports them
+    pub error_counters: Option<(u16, u16)>,
+    pub bitrate: Option<u32>,
+    /// Automatic bus-off recovery delay, 0 when restarting is left to the user
+    pub restart_ms: Option<u32>,
+    pub device_stats: Option<DeviceStats>,
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_ne_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_ne_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+/// Iterate the `(type, payload)` pairs of a run of netlink attributes
+fn attributes(bytes: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    let mut at = 0;
+    std::iter::from_fn(move || {
+        let length = u16_at(bytes, at)? as usize;
+        let kind = u16_at(bytes, at + 2)? & NLA_TYPE_MASK;
+        let payload = bytes.get(at + 4..at + length.max(4))?;
+        at += length.max(4).div_ceil(4) * 4;
+        Some((kind, payload))
+    })
+}
+
+fn attribute(kind: u16, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = ((4 + payload.len()) as u16).to_ne_bytes().to_vec();
+    bytes.extend(kind.to_ne_bytes());
+    bytes.extend(payload);
+    bytes.resize(bytes.len().div_ceil(4) * 4, 0);
+    bytes
+}
+
+/// Controller status from the payload of an RTM_NEWLINK message (`ifinfomsg` and attributes)
+fn parse_link(payload: &[u8]) -> io::Result<ControllerStatus> {
+    let link_info = attributes(payload.get(IFINFOMSG_LEN..).unwrap_or(&[]))
+        .find(|&(kind, _)| kind == libc::IFLA_LINKINFO)
+        .map(|(_, info)| info);
+    let mut kind = None;
+    let mut data = None;
+    let mut xstats = None;
+    for (info_kind, value) in attributes(link_info.unwrap_or(&[])) {
+        match info_kind {
+            libc::IFLA_INFO_KIND => kind = Some(String::from_utf8_lossy(value).trim_end_matches('\0').to_string()),
+            libc::IFLA_INFO_DATA => data = Some(value),
+            libc::IFLA_INFO_XSTATS => xstats = Some(value),
+            _ => {}
+        }
+    }
+
+    match kind.as_deref() {
+        Some("can") => {}
+        Some(other) => {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, format!("not a CAN controller ({} interface)", other)));
+        }
+        None => return Err(io::Error::new(io::ErrorKind::Unsupported, "not a CAN controller")),
+    }
+
+    let mut state = None;
+    let mut error_counters = None;
+    let mut bitrate = None;
+    let mut restart_ms = None;
+    for (can_kind, value) in attributes(data.unwrap_or(&[])) {
+        match can_kind {
+            IFLA_CAN_STATE => state = u32_at(value, 0).map(ControllerState::from_raw),
+            IFLA_CAN_BERR_COUNTER => error_counters = u16_at(value, 0).zip(u16_at(value, 2)),
+            IFLA_CAN_BITTIMING => bitrate = u32_at(value, 0).filter(|&bitrate| bitrate > 0),
+            IFLA_CAN_RESTART_MS => restart_ms = u32_at(value, 0),
+            _ => {}
+        }
+    }
+
+    let device_stats = xstats.and_then(|value| {
+        Some(DeviceStats {
+            bus_errors: u32_at(value, 0)?,
+            error_warning: u32_at(value, 4)?,
+            error_passive: u32_at(value, 8)?,
+            bus_off: u32_at(value, 12)?,
+            arbitration_lost: u32_at(value, 16)?,
+            restarts: u32_at(value, 20)?,
+        })
+    });
+
+    Ok(ControllerStatus {
+        state: state.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no controller state reported"))?,
+        error_counters,
+        bitrate,
+        restart_ms,
+        device_stats,
+    })
+}
+
+fn interface_index(interface: &str) -> io::Result<i32> {
+    let name = CString::new(interface).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid interface name"))?;
+    match unsafe { libc::if_nametoindex(name.as_ptr()) } {
+        0 => Err(io::Error::new(io::ErrorKind::NotFound, format!("no interface {}", interface))),
+        index => Ok(index as i32),
+    }
+}
+
+/// Send one request for `interface` and return the payload of the answer
+///
+/// An error or acknowledgement answer is turned into `Err`/an empty payload.
+fn request(interface: &str, message_type: u16, flags: u16, attributes: &[u8]) -> io::Result<Vec<u8>> {
+    let index = interface_index(interface)?;
+
+    let mut message = ((NLMSG_HEADER_LEN + IFINFOMSG_LEN + attributes.len()) as u32).to_ne_bytes().to_vec();
+    message.extend(message_type.to_ne_bytes());
+    message.extend((libc::NLM_F_REQUEST as u16 | flags).to_ne_bytes());
+    message.extend(1u32.to_ne_bytes()); // sequence number
+    message.extend(0u32.to_ne_bytes()); // port id, filled in by the kernel
+    message.extend([libc::AF_UNSPEC as u8, 0, 0, 0]); // family, padding, device type
+    message.extend(index.to_ne_bytes());
+    message.extend([0; 8]); // flags and change mask
+    message.extend(attributes);
+
+    let fd = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_RAW | libc::SOCK_CLOEXEC, libc::NETLINK_ROUTE) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    let timeout = libc::timeval { tv_sec: 1, tv_usec: 0 };
+    let mut kernel: libc::sockaddr_nl = unsafe { mem::zeroed() };
+    kernel.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+    let sent = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_RCVTIMEO,
+            &timeout as *const libc::timeval as *const libc::c_void,
+            mem::size_of::<libc::timeval>() as libc::socklen_t,
+        );
+        libc::sendto(
+            socket.as_raw_fd(),
+            message.as_ptr() as *const libc::c_void,
+            message.len(),
+            0,
+            &kernel as *const libc::sockaddr_nl as *const libc::sockaddr,
+            mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+        )
+    };
+    if sent < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut buffer = vec![0u8; 32 * 1024];
+    let received = unsafe { libc::recv(socket.as_raw_fd(), buffer.as_mut_ptr() as *mut libc::c_void, buffer.len(), 0) };
+    if received < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    buffer.truncate(received as usize);
+
+    let length = u32_at(&buffer, 0).unwrap_or(0) as usize;
+    let payload = buffer.get(NLMSG_HEADER_LEN..length.min(buffer.len())).unwrap_or(&[]);
+    match u16_at(&buffer, 4).map(i32::from) {
+        Some(libc::NLMSG_ERROR) => match u32_at(payload, 0).map(|code| code as i32) {
+            Some(0) => Ok(Vec::new()),
+            Some(code) => Err(io::Error::from_raw_os_error(-code)),
+            None => Err(io::Error::new(io::ErrorKind::InvalidData, "truncated netlink error")),
+        },
+        _ => Ok(payload.to_vec()),
+    }
+}
+
+/// Current state, error counters and statistics of a SocketCAN controller
+pub fn controller_status(interface: &str) -> io::Result<ControllerStatus> {
+    let payload = request(interface, libc::RTM_GETLINK, 0, &[])?;
+    parse_link(&payload)
+}
+
+/// Restart a bus-off controller, like `ip link set can0 type can restart`
+pub fn restart(interface: &str) -> io::Result<()> {
+    let data = attribute(IFLA_CAN_RESTART, &1u32.to_ne_bytes());
+    let mut info = attribute(libc::IFLA_INFO_KIND, b"can\0");
+    info.extend(attribute(libc::IFLA_INFO_DATA, &data));
+    let link_info = attribute(libc::IFLA_LINKINFO, &info);
+
+    request(interface, libc::RTM_NEWLINK, libc::NLM_F_ACK as u16, &link_info)
+        .map(|_| ())
+        .map_err(|err| match err.raw_os_error() {
+            Some(libc::EPERM) => io::Error::new(err.kind(), "restarting the interface needs CAP_NET_ADMIN (run as root)"),
+            Some(libc::EBUSY) => io::Error::new(err.kind(), "the controller isn't bus-off, or recovers automatically (restart-ms)"),
+            _ => err,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_link_attributes() {
+        let mut bittiming = 500_000u32.to_ne_bytes().to_vec();
+        bittiming.extend([0; 28]);
+        let berr: Vec<u8> = [96u16, 3].iter().flat_map(|value| value.to_ne_bytes()).collect();
+        let xstats: Vec<u8> = [12u32, 4, 2, 1, 0, 1].iter().flat_map(|value| value.to_ne_bytes()).collect();
+
+        let mut data = attribute(IFLA_CAN_BITTIMING, &bittiming);
+        data.extend(attribute(IFLA_CAN_STATE, &2u32.to_ne_bytes()));
+        data.extend(attribute(IFLA_CAN_RESTART_MS, &100u32.to_ne_bytes()));
+        data.extend(attribute(IFLA_CAN_BERR_COUNTER, &berr));
+        let mut info = attribute(libc::IFLA_INFO_KIND, b"can\0");
+        info.extend(attribute(libc::IFLA_INFO_DATA | 0x8000, &data));
+        info.extend(attribute(libc::IFLA_INFO_XSTATS, &xstats));
+
+        let mut payload = vec![0; IFINFOMSG_LEN];
+        payload.extend(attribute(libc::IFLA_MTU, &16u32.to_ne_bytes()));
+        payload.extend(attribute(libc::IFLA_LINKINFO, &info));
+
+        let status = parse_link(&payload).unwrap();
+        assert_eq!(status.state, ControllerState::ErrorPassive);
+        assert_eq!(status.error_counters, Some((96, 3)));
+        assert_eq!(status.bitrate, Some(500_000));
+        assert_eq!(status.restart_ms, Some(100));
+        assert_eq!(status.device_stats.unwrap().bus_off, 1);
+        assert_eq!(status.device_stats.unwrap().bus_errors, 12);
+
+        // Virtual CAN has no controller
+        let mut payload = vec![0; IFINFOMSG_LEN];
+        payload.extend(attribute(libc::IFLA_LINKINFO, &attribute(libc::IFLA_INFO_KIND, b"vcan\0")));
+        assert_eq!(parse_link(&payload).unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+}
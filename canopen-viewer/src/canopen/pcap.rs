@@ -0,0 +1,265 @@
+// pcap.rs - CAN frames from pcap and pcapng captures
+//
+// tcpdump and Wireshark capture SocketCAN interfaces with the LINKTYPE_CAN_SOCKETCAN
+// (227) link type: each packet is a `struct can_frame` with the identifier in network
+// byte order. Packets of other link types and CAN FD frames are skipped.
+use socketcan::CanFrame;
+
+use super::frame;
+
+/// Link type of SocketCAN captures
+const LINKTYPE_CAN_SOCKETCAN: u32 = 227;
+
+/// Size of the SocketCAN pseudo header before the data bytes
+const SOCKETCAN_HEADER_LEN: usize = 8;
+
+/// pcapng block types
+const SECTION_HEADER_BLOCK: u32 = 0x0A0D_0D0A;
+const INTERFACE_DESCRIPTION_BLOCK: u32 = 1;
+const ENHANCED_PACKET_BLOCK: u32 = 6;
+
+/// pcapng interface option with the timestamp resolution
+const IF_TSRESOL: u16 = 9;
+
+#[derive(Debug, Clone, Copy)]
+enum ByteOrder {
+    Little,
+    Big,
+}
+
+impl ByteOrder {
+    fn u16(self, bytes: &[u8], at: usize) -> Option<u16> {
+        let bytes: [u8; 2] = bytes.get(at..at + 2)?.try_into().ok()?;
+        Some(match self {
+            Self::Little => u16::from_le_bytes(bytes),
+            Self::Big => u16::from_be_bytes(bytes),
+        })
+    }
+
+    fn u32(self, bytes: &[u8], at: usize) -> Option<u32> {
+        let bytes: [u8; 4] = bytes.get(at..at + 4)?.try_into().ok()?;
+        Some(match self {
+            Self::Little => u32::from_le_bytes(bytes),
+            Self::Big => u32::from_be_bytes(bytes),
+        })
+    }
+}
+
+/// Whether the file starts like a pcap or pcapng capture
+pub fn is_pcap(bytes: &[u8]) -> bool {
+    let Some(magic) = ByteOrder::Little.u32(bytes, 0) else {
+        return false;
+    };
+    matches!(magic, 0xA1B2_C3D4 | 0xD4C3_B2A1 | 0xA1B2_3C4D | 0x4D3C_B2A1 | SECTION_HEADER_BLOCK)
+}
+
+/// Frames of a pcap or pcapng capture with their time in seconds
+///
+/// A truncated file yields the frames before the damage.
+pub fn parse(bytes: &[u8]) -> Vec<(f64, CanFrame)> {
+    if ByteOrder::Little.u32(bytes, 0) == Some(SECTION_HEADER_BLOCK) {
+        parse_pcapng(bytes)
+    } else {
+        parse_pcap(bytes)
+    }
+}
+
+/// Frame from the SocketCAN pseudo header and data
+fn socketcan_frame(packet: &[u8]) -> Option<CanFrame> {
+    let can_id = ByteOrder::Big.u32(packet, 0)?;
+    let len = *packet.get(4)? as usize;
+    if len > 8 {
+        return None; // CAN FD
+    }
+    let data = packet.get(SOCKETCAN_HEADER_LEN..SOCKETCAN_HEADER_LEN + len).unwrap_or(&[]);
+    if can_id & libc::CAN_RTR_FLAG == 0 && data.len() != len {
+        return None;
+    }
+    frame::from_raw_parts(can_id, len, data)
+}
+
+fn parse_pcap(bytes: &[u8]) -> Vec<(f64, CanFrame)> {
+    let mut frames = Vec::new();
+    let (order, nanoseconds) = match ByteOrder::Little.u32(bytes, 0) {
+        Some(0xA1B2_C3D4) => (ByteOrder::Little, false),
+        Some(0xD4C3_B2A1) => (ByteOrder::Big, false),
+        Some(0xA1B2_3C4D) => (ByteOrder::Little, true),
+        Some(0x4D3C_B2A1) => (ByteOrder::Big, true),
+        _ => return frames,
+    };
+    if order.u32(bytes, 20) != Some(LINKTYPE_CAN_SOCKETCAN) {
+        return frames;
+    }
+    let fraction = if nanoseconds { 1e-9 } else { 1e-6 };
+
+    let mut at = 24;
+    while let (Some(seconds), Some(sub), Some(length)) = (order.u32(bytes, at), order.u32(bytes, at + 4), order.u32(bytes, at + 8)) {
+        let Some(packet) = bytes.get(at + 16..at + 16 + length as usize) else {
+            break;
+        };
+        if let Some(frame) = socketcan_frame(packet) {
+            frames.push((seconds as f64 + sub as f64 * fraction, frame));
+        }
+        at += 16 + length as usize;
+    }
+    frames
+}
+
+/// Timestamp units per second of an interface description block
+fn ticks_per_second(order: ByteOrder, block: &[u8]) -> f64 {
+    // Options start after link type, reserved and snap length
+    let mut at = 16;
+    while let (Some(code), Some(length)) = (order.u16(block, at), order.u16(block, at + 2)) {
+        if code == 0 {
+            break;
+        }
+        if code == IF_TSRESOL {
+            if let Some(&resolution) = block.get(at + 4) {
+                let exponent = (resolution & 0x7F) as i32;
+                return if resolution & 0x80 == 0 { 10f64.powi(exponent) } else { 2f64.powi(exponent) };
+            }
+        }
+        at += 4 + (length as usize).div_ceil(4) * 4;
+    }
+    1e6
+}
+
+fn parse_pcapng(bytes: &[u8]) -> Vec<(f64, CanFrame)> {
+    let mut frames = Vec::new();
+    let mut order = ByteOrder::Little;
+    // Link type and timestamp units per second of the interfaces of the current section
+    let mut interfaces: Vec<(u32, f64)> = Vec::new();
+
+    let mut at = 0;
+    while let Some(block_type) = order.u32(bytes, at) {
+        // The block type reads the same in both byte orders
+        if block_type == SECTION_HEADER_BLOCK {
+            order = match bytes.get(at + 8..at + 12) {
+                Some([0x4D, 0x3C, 0x2B, 0x1A]) => ByteOrder::Little,
+                Some([0x1A, 0x2B, 0x3C, 0x4D]) => ByteOrder::Big,
+                _ => break,
+            };
+            interfaces.clear();
+        }
+        let Some(length) = order.u32(bytes, at + 4).map(|length| length as usize).filter(|&length| length >= 12) else {
+            break;
+        };
+        let Some(block) = bytes.get(at..at + length) else {
+            break;
+        };
+
+        match order.u32(block, 0) {
+            Some(INTERFACE_DESCRIPTION_BLOCK) => {
+                let link_type = order.u16(block, 8).unwrap_or(0) as u32;
+                interfaces.push((link_type, ticks_per_second(order, block)));
+            }
+            Some(ENHANCED_PACKET_BLOCK) => {
+                let interface = order.u32(block, 8).and_then(|id| interfaces.get(id as usize));
+                if let (Some(&(LINKTYPE_CAN_SOCKETCAN, resolution)), Some(high), Some(low), Some(captured)) =
+                    (interface, order.u32(block, 12), order.u32(block, 16), order.u32(block, 20))
+                {
+                    let packet = block.get(28..28 + captured as usize);
+                    if let Some(frame) = packet.and_then(socketcan_frame) {
+                        let ticks = ((high as u64) << 32) | low as u64;
+                        frames.push((ticks as f64 / resolution, frame));
+                    }
+                }
+            }
+            _ => {}
+        }
+        at += length;
+    }
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use socketcan::{EmbeddedFrame, ExtendedId, Id, StandardId};
+
+    fn packet(can_id: u32, data: &[u8]) -> Vec<u8> {
+        let mut packet = can_id.to_be_bytes().to_vec();
+        packet.extend([data.len() as u8, 0, 0, 0]);
+        packet.extend(data);
+        packet
+    }
+
+    /// Classic pcap, microseconds, little endian: a standard and an extended frame
+    fn classic_pcap() -> Vec<u8> {
+        let mut pcap = Vec::new();
+        for value in [0xA1B2_C3D4u32, 0x0004_0002, 0, 0, 0xFFFF, LINKTYPE_CAN_SOCKETCAN] {
+            pcap.extend(value.to_le_bytes());
+        }
+        for (seconds, micros, packet) in [
+            (1_700_000_000u32, 250_000u32, packet(0x181, &[0x11, 0x22])),
+            (1_700_000_000, 500_000, packet(0x1234_5678 | libc::CAN_EFF_FLAG, &[0xAA])),
+        ] {
+            for value in [seconds, micros, packet.len() as u32, packet.len() as u32] {
+                pcap.extend(value.to_le_bytes());
+            }
+            pcap.extend(packet);
+        }
+        pcap
+    }
+
+    fn block(block_type: u32, body: Vec<u8>) -> Vec<u8> {
+        let length = (12 + body.len().div_ceil(4) * 4) as u32;
+        let mut block = block_type.to_le_bytes().to_vec();
+        block.extend(length.to_le_bytes());
+        block.extend(&body);
+        block.resize(length as usize - 4, 0);
+        block.extend(length.to_le_bytes());
+        block
+    }
+
+    /// pcapng with a millisecond interface and one enhanced packet block holding a remote frame
+    fn pcapng() -> Vec<u8> {
+        let mut section = 0x1A2B_3C4Du32.to_le_bytes().to_vec();
+        section.extend([1, 0, 0, 0]);
+        section.extend(u64::MAX.to_le_bytes());
+        let mut interface = (LINKTYPE_CAN_SOCKETCAN as u16).to_le_bytes().to_vec();
+        interface.extend([0, 0, 0, 0, 0, 0]);
+        interface.extend([9, 0, 1, 0, 3, 0, 0, 0, 0, 0, 0, 0]);
+        let rtr = packet(0x701 | libc::CAN_RTR_FLAG, &[]);
+        let mut packet_body = 0u32.to_le_bytes().to_vec();
+        for value in [0u32, 1500, rtr.len() as u32, rtr.len() as u32] {
+            packet_body.extend(value.to_le_bytes());
+        }
+        packet_body.extend(rtr);
+
+        let mut pcapng = block(SECTION_HEADER_BLOCK, section);
+        pcapng.extend(block(INTERFACE_DESCRIPTION_BLOCK, interface));
+        pcapng.extend(block(ENHANCED_PACKET_BLOCK, packet_body));
+        pcapng
+    }
+
+    #[test]
+    fn test_parse_classic_pcap() {
+        let pcap = classic_pcap();
+        assert!(is_pcap(&pcap));
+        let frames = parse(&pcap);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].0, 1_700_000_000.25);
+        assert_eq!(frames[0].1.id(), Id::Standard(StandardId::new(0x181).unwrap()));
+        assert_eq!(frames[0].1.data(), &[0x11, 0x22]);
+        assert_eq!(frames[1].0, 1_700_000_000.5);
+        assert_eq!(frames[1].1.id(), Id::Extended(ExtendedId::new(0x1234_5678).unwrap()));
+    }
+
+    #[test]
+    fn test_parse_pcapng() {
+        let pcapng = pcapng();
+        assert!(is_pcap(&pcapng));
+        let frames = parse(&pcapng);
+        assert_eq!(frames.len(), 1);
+        // Timestamps in milliseconds of the interface
+        assert_eq!(frames[0].0, 1.5);
+        assert!(frames[0].1.is_remote_frame());
+    }
+
+    #[test]
+    fn test_text_captures_are_not_pcap() {
+        assert!(!is_pcap(b"(1700000000.250000) can0 181#11"));
+        assert!(!is_pcap(b""));
+    }
+}
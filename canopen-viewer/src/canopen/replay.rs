@@ -0,0 +1,350 @@
+// replay.rs - Play a recorded CAN capture back as if it were a live bus
+//
+// Understands candump log files ("(1700000000.123456) can0 181#1122"), candump
+// screen output recorded with -ta ("(1700000000.123456)  can0  181   [2]  11 22")
+// and plain CSV lines "<seconds>,<hex COB-ID>,<hex data>". Lines that match none of
+// these (headers, comments, CAN FD frames) are skipped. pcap and pcapng files of
+// SocketCAN captures are read as well. Frames keep their recorded timestamps when the
+// playback is sped up, so plots of a long capture show its real timeline.
+//
+// Nothing on a replayed bus can answer SDO requests, so they are aborted right away
+// instead of timing out; TPDO decoding then falls back to the EDS mapping.
+use socketcan::{CanFrame, EmbeddedFrame, Id, StandardId};
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use super::connect::{CanTransport, TransportFuture};
+use super::frame;
+use super::pcap;
+use super::timestamp::{ReceivedFrame, TimestampSource};
+
+/// Lead-in before the first frame so TPDO listeners are subscribed when playback starts
+const START_DELAY: Duration = Duration::from_secs(1);
+
+/// Captures with earlier timestamps use relative time and are anchored at playback start
+const MIN_ABSOLUTE_TIMESTAMP: f64 = 1_000_000_000.0;
+
+/// Shortest pause between the last frame of a looped pass and the first of the next, in seconds
+const MIN_LOOP_GAP: f64 = 0.001;
+
+/// Abort code sent in reply to SDO requests (general error)
+const SDO_ABORT_GENERAL_ERROR: u32 = 0x0800_0000;
+
+/// One frame of a capture and its time in seconds
+#[derive(Debug, Clone)]
+struct LoggedFrame {
+    time: f64,
+    frame: CanFrame,
+}
+
+/// Recorded capture played back with its original timing
+pub struct ReplayTransport {
+    frame_tx: mpsc::UnboundedSender<ReceivedFrame>,
+    frame_rx: Mutex<mpsc::UnboundedReceiver<ReceivedFrame>>,
+    playback_task: JoinHandle<()>,
+}
+
+impl ReplayTransport {
+    /// Load a capture and start playing it `speed` times faster than recorded
+    /// (`looped` restarts it when the end is reached)
+    pub fn open(path: &Path, looped: bool, speed: f64) -> io::Result<Self> {
+        let frames = load_frames(&std::fs::read(path)?);
+        if frames.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "No CAN frames found in capture"));
+        }
+
+        println!("Replaying {} frames from {:?}", frames.len(), path);
+
+        let (frame_tx, frame_rx) = mpsc::unbounded_channel();
+        let playback_task = tokio::spawn(playback_task(frames, looped, speed, frame_tx.clone()));
+
+        Ok(Self {
+            frame_tx,
+            frame_rx: Mutex::new(frame_rx),
+            playback_task,
+        })
+    }
+}
+
+impl Drop for ReplayTransport {
+    fn drop(&mut self) {
+        self.playback_task.abort();
+    }
+}
+
+impl CanTransport for ReplayTransport {
+    fn recv(&self) -> TransportFuture<'_, ReceivedFrame> {
+        Box::pin(async move {
+            self.frame_rx.lock().await.recv().await
+                .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Replay stopped"))
+        })
+    }
+
+    fn send<'a>(&'a self, frame: &'a CanFrame) -> TransportFuture<'a, ()> {
+        Box::pin(async move {
+            if let Some(abort) = sdo_abort_for(frame) {
+                let _ = self.frame_tx.send(ReceivedFrame {
+                    frame: abort,
+                    timestamp: SystemTime::now(),
+                    source: TimestampSource::Local,
+                    hardware_time: None,
+                });
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Frames of a capture in time order; frames without a usable time are dropped
+fn load_frames(contents: &[u8]) -> Vec<LoggedFrame> {
+    let mut frames: Vec<LoggedFrame> = if pcap::is_pcap(contents) {
+        pcap::parse(contents).into_iter().map(|(time, frame)| LoggedFrame { time, frame }).collect()
+    } else {
+        String::from_utf8_lossy(contents).lines().filter_map(parse_line).collect()
+    };
+    frames.retain(|logged| logged.time.is_finite());
+    // Merged or hand-edited captures aren't always in order; the sort is stable for equal times
+    frames.sort_by(|a, b| a.time.total_cmp(&b.time));
+    frames
+}
+
+/// Feed the frames to the receiver, sleeping out the gaps between them
+async fn playback_task(frames: Vec<LoggedFrame>, looped: bool, speed: f64, frame_tx: mpsc::UnboundedSender<ReceivedFrame>) {
+    let first = frames[0].time;
+    let duration = frames[frames.len() - 1].time - first;
+    // A capture without any time between its frames can't be paced, so it isn't repeated
+    if looped && duration <= 0.0 {
+        println!("Capture spans no time, playing it once");
+    }
+    let looped = looped && duration > 0.0;
+    // Next pass starts a mean frame interval after the last frame
+    let pass_length = duration + (duration / (frames.len() - 1).max(1) as f64).max(MIN_LOOP_GAP);
+    let time_base = Some(first)
+        .filter(|first| *first >= MIN_ABSOLUTE_TIMESTAMP)
+        .and_then(|first| Duration::try_from_secs_f64(first).ok())
+        .map_or_else(|| SystemTime::now() + START_DELAY, |since_epoch| UNIX_EPOCH + since_epoch);
+
+    let start = Instant::now() + START_DELAY;
+    let mut pass = 0u32;
+
+    loop {
+        // Later passes continue the timeline instead of jumping back
+        let pass_offset = pass as f64 * pass_length;
+
+        for logged in &frames {
+            let Ok(offset) = Duration::try_from_secs_f64(logged.time - first + pass_offset) else {
+                continue;
+            };
+            tokio::time::sleep_until(start + offset.div_f64(speed)).await;
+
+            let received = ReceivedFrame {
+                frame: logged.frame,
+                timestamp: time_base + offset,
+                source: TimestampSource::Recorded,
+                hardware_time: None,
+            };
+            if frame_tx.send(received).is_err() {
+                return;
+            }
+        }
+
+        if !looped {
+            println!("Replay finished");
+            return;
+        }
+        pass += 1;
+    }
+}
+
+/// Parse one line of a capture in any of the supported formats
+fn parse_line(line: &str) -> Option<LoggedFrame> {
+    let line = line.trim();
+
+    if let Some(rest) = line.strip_prefix('(') {
+        let (time, rest) = rest.split_once(')')?;
+        let time = time.trim().parse().ok().filter(|time: &f64| time.is_finite())?;
+
+        let mut parts = rest.split_whitespace();
+        let _interface = parts.next()?;
+        let frame_text = parts.next()?;
+        let frame = if frame_text.contains('#') {
+            parse_candump_log_frame(frame_text)?
+        } else {
+            parse_candump_screen_frame(frame_text, parts.collect())?
+        };
+        return Some(LoggedFrame { time, frame });
+    }
+
+    let mut fields = line.split(',').map(str::trim);
+    let time = fields.next()?.parse().ok().filter(|time: &f64| time.is_finite())?;
+    let can_id = parse_can_id(fields.next()?)?;
+    let data = parse_hex(fields.next().unwrap_or(""))?;
+    let frame = frame::from_raw_parts(can_id, data.len(), &data)?;
+    Some(LoggedFrame { time, frame })
+}
+
+/// "181#1122", "181#R", "181#R2" (CAN FD "181##..." is not supported)
+fn parse_candump_log_frame(text: &str) -> Option<CanFrame> {
+    let (id, data) = text.split_once('#')?;
+    let can_id = parse_can_id(id)?;
+
+    if let Some(dlc) = data.strip_prefix('R') {
+        let dlc = if dlc.is_empty() { 0 } else { dlc.parse().ok()? };
+        return frame::from_raw_parts(can_id | libc::CAN_RTR_FLAG, dlc, &[]);
+    }
+
+    let data = parse_hex(data)?;
+    frame::from_raw_parts(can_id, data.len(), &data)
+}
+
+/// "181", ["[2]", "11", "22"] or ["[2]", "remote", "request"]
+fn parse_candump_screen_frame(id: &str, rest: Vec<&str>) -> Option<CanFrame> {
+    let can_id = parse_can_id(id)?;
+    let (dlc, bytes) = rest.split_first()?;
+    let dlc: usize = dlc.strip_prefix('[')?.strip_suffix(']')?.parse().ok()?;
+
+    if bytes.first() == Some(&"remote") {
+        return frame::from_raw_parts(can_id | libc::CAN_RTR_FLAG, dlc, &[]);
+    }
+
+    let data = parse_hex(&bytes.concat())?;
+    frame::from_raw_parts(can_id, data.len(), &data)
+}
+
+/// Hex identifier; eight digits mark an extended frame as in candump
+fn parse_can_id(text: &str) -> Option<u32> {
+    let text = text.trim_start_matches("0x");
+    let id = u32::from_str_radix(text, 16).ok()?;
+    Some(if text.len() > 3 { id | libc::CAN_EFF_FLAG } else { id })
+}
+
+fn parse_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.is_ascii() || !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len() / 2)
+        .map(|i| u8::from_str_radix(&text[i * 2..i * 2 + 2], 16).ok())
+        .collect()
+}
+
+/// Abort frame answering an SDO request, `None` for any other frame
+fn sdo_abort_for(request: &CanFrame) -> Option<CanFrame> {
+    let Id::Standard(id) = request.id() else {
+        return None;
+    };
+    let node_id = id.as_raw().checked_sub(0x600).filter(|node_id| (1..=127).contains(node_id))?;
+
+    let data = request.data();
+    if data.len() < 4 {
+        return None;
+    }
+
+    let mut response = [0u8; 8];
+    response[0] = 0x80; // Abort transfer
+    response[1..4].copy_from_slice(&data[1..4]); // Index and subindex
+    response[4..8].copy_from_slice(&SDO_ABORT_GENERAL_ERROR.to_le_bytes());
+    CanFrame::new(StandardId::new(0x580 + node_id)?, &response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use socketcan::ExtendedId;
+
+    #[test]
+    fn test_parse_capture_formats() {
+        let logged = parse_line("(1700000000.250000) can0 181#11223344").unwrap();
+        assert_eq!(logged.time, 1_700_000_000.25);
+        assert_eq!(logged.frame.id(), Id::Standard(StandardId::new(0x181).unwrap()));
+        assert_eq!(logged.frame.data(), &[0x11, 0x22, 0x33, 0x44]);
+
+        let logged = parse_line("(1700000000.500000)  can0  12345678   [2]  AA BB").unwrap();
+        assert_eq!(logged.frame.id(), Id::Extended(ExtendedId::new(0x1234_5678).unwrap()));
+        assert_eq!(logged.frame.data(), &[0xAA, 0xBB]);
+
+        let logged = parse_line("(0.001) vcan0 701#R").unwrap();
+        assert!(logged.frame.is_remote_frame());
+
+        let logged = parse_line("12.5,281,0102").unwrap();
+        assert_eq!(logged.time, 12.5);
+        assert_eq!(logged.frame.data(), &[0x01, 0x02]);
+
+        assert!(parse_line("Timestamp,COB-ID,Data").is_none());
+        assert!(parse_line("(1700000000.000000) can0 181##0112233").is_none());
+    }
+
+    #[test]
+    fn test_non_finite_times_are_rejected() {
+        assert!(parse_line("(nan) can0 181#11").is_none());
+        assert!(parse_line("(inf) can0 181#11").is_none());
+        assert!(parse_line("-inf,181,11").is_none());
+        assert!(parse_line("NaN,181,11").is_none());
+    }
+
+    #[test]
+    fn test_out_of_order_capture_is_sorted() {
+        let capture = "2.0,182,02\n(nan) can0 181#FF\n0.5,181,01\n3.0,183,03\n0.5,184,04\n";
+        let frames = load_frames(capture.as_bytes());
+        let order: Vec<(f64, u8)> = frames.iter().map(|logged| (logged.time, logged.frame.data()[0])).collect();
+        assert_eq!(order, [(0.5, 0x01), (0.5, 0x04), (2.0, 0x02), (3.0, 0x03)]);
+    }
+
+    #[tokio::test]
+    async fn test_out_of_order_capture_plays_back() {
+        let frames = load_frames("1.0,182,02\n0.0,181,01\n0.5,183,03\n".as_bytes());
+        let (frame_tx, mut frame_rx) = mpsc::unbounded_channel();
+        playback_task(frames, false, 1000.0, frame_tx).await;
+
+        let mut received = Vec::new();
+        while let Ok(frame) = frame_rx.try_recv() {
+            received.push(frame);
+        }
+        let data: Vec<u8> = received.iter().map(|received| received.frame.data()[0]).collect();
+        assert_eq!(data, [0x01, 0x03, 0x02]);
+        assert!(received.windows(2).all(|pair| pair[0].timestamp <= pair[1].timestamp));
+    }
+
+    #[tokio::test]
+    async fn test_capture_spanning_no_time_is_played_once() {
+        let frames = load_frames("5.0,181,01\n5.0,182,02\n".as_bytes());
+        let (frame_tx, mut frame_rx) = mpsc::unbounded_channel();
+        playback_task(frames, true, 1000.0, frame_tx).await;
+
+        let mut count = 0;
+        while frame_rx.try_recv().is_ok() {
+            count += 1;
+        }
+        assert_eq!(count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_looped_passes_are_a_frame_interval_apart() {
+        let frames = load_frames("0.0,181,01\n0.5,182,02\n1.0,183,03\n".as_bytes());
+        let (frame_tx, mut frame_rx) = mpsc::unbounded_channel();
+        let playback = tokio::spawn(playback_task(frames, true, 1000.0, frame_tx));
+
+        let mut timestamps = Vec::new();
+        for _ in 0..4 {
+            timestamps.push(frame_rx.recv().await.unwrap().timestamp);
+        }
+        playback.abort();
+        // The second pass starts at 1.5 s, half a second after the last frame at 1.0 s
+        assert_eq!(timestamps[3].duration_since(timestamps[2]).unwrap(), Duration::from_millis(500));
+        assert_eq!(timestamps[3].duration_since(timestamps[0]).unwrap(), Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn test_sdo_requests_are_aborted() {
+        let request = CanFrame::new(StandardId::new(0x604).unwrap(), &[0x40, 0x00, 0x18, 0x01, 0, 0, 0, 0]).unwrap();
+        let abort = sdo_abort_for(&request).unwrap();
+        assert_eq!(abort.id(), Id::Standard(StandardId::new(0x584).unwrap()));
+        assert_eq!(abort.data(), &[0x80, 0x00, 0x18, 0x01, 0x00, 0x00, 0x00, 0x08]);
+
+        let tpdo = CanFrame::new(StandardId::new(0x184).unwrap(), &[0x00]).unwrap();
+        assert!(sdo_abort_for(&tpdo).is_none());
+    }
+}
@@ -0,0 +1,150 @@
+// simulation.rs - In-process simulated CAN bus (no hardware required)
+//
+// Runs the mock-canopen-node logic inside the viewer: transmitted frames are handed to
+// the simulated nodes, and their responses plus the periodic TPDO1 come back as
+// received frames. With "sim://" a node is created the first time an SDO request
+// addresses it; "sim://4,5" starts exactly those nodes.
+use mock_canopen_node::{MockNode, TPDO_INTERVAL};
+use socketcan::{CanFrame, EmbeddedFrame, Id};
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use super::connect::{CanTransport, TransportFuture};
+use super::timestamp::{ReceivedFrame, TimestampSource};
+
+/// Interface name of the on-demand simulation as listed in the interface selector
+pub const INTERFACE: &str = "sim://";
+
+/// COB-ID range of SDO requests (client to server)
+const SDO_REQUEST_BASE: u16 = 0x600;
+
+type Nodes = Arc<Mutex<BTreeMap<u8, MockNode>>>;
+
+/// Simulated bus with one or more mock nodes
+pub struct SimulationTransport {
+    nodes: Nodes,
+    /// Create nodes when they are first addressed
+    on_demand: bool,
+    frame_tx: mpsc::UnboundedSender<CanFrame>,
+    frame_rx: tokio::sync::Mutex<mpsc::UnboundedReceiver<CanFrame>>,
+    tpdo_task: JoinHandle<()>,
+}
+
+impl SimulationTransport {
+    /// Start the simulation; an empty `node_ids` creates nodes on demand
+    pub fn open(node_ids: &[u8]) -> Self {
+        let nodes: BTreeMap<u8, MockNode> = node_ids.iter()
+            .map(|&node_id| (node_id, new_node(node_id)))
+            .collect();
+        let nodes = Arc::new(Mutex::new(nodes));
+        let (frame_tx, frame_rx) = mpsc::unbounded_channel();
+
+        let tpdo_task = tokio::spawn(tpdo_task(nodes.clone(), frame_tx.clone()));
+
+        Self {
+            nodes,
+            on_demand: node_ids.is_empty(),
+            frame_tx,
+            frame_rx: tokio::sync::Mutex::new(frame_rx),
+            tpdo_task,
+        }
+    }
+}
+
+impl Drop for SimulationTransport {
+    fn drop(&mut self) {
+        self.tpdo_task.abort();
+    }
+}
+
+impl CanTransport for SimulationTransport {
+    fn recv(&self) -> TransportFuture<'_, ReceivedFrame> {
+        Box::pin(async move {
+            let frame = self.frame_rx.lock().await.recv().await
+                .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Simulation stopped"))?;
+            Ok(ReceivedFrame { frame, timestamp: SystemTime::now(), source: TimestampSource::Local, hardware_time: None })
+        })
+    }
+
+    fn send<'a>(&'a self, frame: &'a CanFrame) -> TransportFuture<'a, ()> {
+        Box::pin(async move {
+            let mut nodes = self.nodes.lock().unwrap();
+
+            if self.on_demand {
+                if let Some(node_id) = sdo_request_node_id(frame) {
+                    nodes.entry(node_id).or_insert_with(|| new_node(node_id));
+                }
+            }
+
+            for node in nodes.values_mut() {
+                if let Some(response) = node.handle_frame(frame) {
+                    let _ = self.frame_tx.send(response);
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+fn new_node(node_id: u8) -> MockNode {
+    let mut node = MockNode::new(node_id);
+    node.set_verbose(false); // Keep the viewer's stdout readable
+    node
+}
+
+/// Node addressed by an SDO request frame
+fn sdo_request_node_id(frame: &CanFrame) -> Option<u8> {
+    let Id::Standard(id) = frame.id() else {
+        return None;
+    };
+    let node_id = id.as_raw().checked_sub(SDO_REQUEST_BASE)?;
+    (1..=127).contains(&node_id).then_some(node_id as u8)
+}
+
+/// Broadcast TPDO1 of every simulated node at the mock node's rate
+async fn tpdo_task(nodes: Nodes, frame_tx: mpsc::UnboundedSender<CanFrame>) {
+    let mut interval = tokio::time::interval(TPDO_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let frames: Vec<CanFrame> = nodes.lock().unwrap()
+            .values()
+            .filter_map(MockNode::tpdo1_frame)
+            .collect();
+        for frame in frames {
+            if frame_tx.send(frame).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::connect::{CANopenConnection, SdoRetryPolicy, TransportConfig};
+    use canopen_common::{SdoDataType, SdoRequest, SdoResponseData};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_sdo_read_from_simulated_node() {
+        let config = TransportConfig::from_interface(super::INTERFACE).unwrap();
+        let connection = CANopenConnection::new(config, Duration::from_millis(500), SdoRetryPolicy::default())
+            .await
+            .unwrap();
+        let node = connection.add_node(4, None).await.unwrap();
+
+        let response = node.sdo_read(SdoRequest {
+            node_id: 4,
+            index: 0x1000,
+            subindex: 0,
+            expected_type: SdoDataType::UInt32,
+        }).await.unwrap();
+
+        assert!(matches!(response.data, SdoResponseData::UInt32(0x191)));
+    }
+}
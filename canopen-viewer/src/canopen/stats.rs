@@ -0,0 +1,267 @@
+// stats.rs - Bus and SDO statistics collected by the connection manager
+//
+// Rates are computed per reporting window. With receive filters active the viewer only
+// sees frames for the monitored node, so transports that can read the driver's own
+// counters (SocketCAN via sysfs) use those for the bus-wide frame and byte rates.
+use socketcan::{CanFrame, EmbeddedFrame};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use super::frame;
+
+/// Bits of a standard data frame besides its data: SOF, ID, control, CRC, ACK, EOF and intermission
+const STANDARD_FRAME_OVERHEAD_BITS: u64 = 47;
+
+/// Same for an extended (29-bit) frame
+const EXTENDED_FRAME_OVERHEAD_BITS: u64 = 67;
+
+/// Frame and byte totals kept by the interface driver, including frames the receive filters drop
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InterfaceCounters {
+    pub rx_frames: u64,
+    pub tx_frames: u64,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+/// One report of bus activity
+#[derive(Debug, Clone, Default)]
+pub struct BusStats {
+    pub frames_per_sec: f64,
+    pub bytes_per_sec: f64,
+    /// Estimated bits on the wire per second (bit stuffing not included)
+    pub bits_per_sec: f64,
+    /// Bus load for the configured bitrate, filled in by the receiver
+    pub bus_load_percent: Option<f64>,
+    /// Rates include frames dropped by the receive filters
+    pub whole_bus: bool,
+    /// Frames seen by the viewer per identifier (EFF flag set for extended IDs) since connecting
+    pub cob_id_counts: BTreeMap<u32, u64>,
+    /// Error frames received since connecting
+    pub error_frames: u64,
+    /// Failed reads since connecting
+    pub rx_errors: u64,
+    /// Failed transmissions since connecting
+    pub tx_errors: u64,
+}
+
+impl BusStats {
+    /// Fill in `bus_load_percent` for a bus running at `bitrate` bit/s
+    pub fn estimate_bus_load(&mut self, bitrate: u32) {
+        if bitrate > 0 {
+            self.bus_load_percent = Some((self.bits_per_sec / bitrate as f64 * 100.0).min(100.0));
+        }
+    }
+}
+
+/// Counts frames between reports
+pub struct BusStatsCollector {
+    window_start: Instant,
+    frames: u64,
+    bytes: u64,
+    bits: u64,
+    cob_id_counts: BTreeMap<u32, u64>,
+    error_frames: u64,
+    rx_errors: u64,
+    tx_errors: u64,
+    last_counters: Option<InterfaceCounters>,
+}
+
+impl BusStatsCollector {
+    pub fn new() -> Self {
+        Self {
+            window_start: Instant::now(),
+            frames: 0,
+            bytes: 0,
+            bits: 0,
+            cob_id_counts: BTreeMap::new(),
+            error_frames: 0,
+            rx_errors: 0,
+            tx_errors: 0,
+            last_counters: None,
+        }
+    }
+
+    /// Count a frame received or transmitted by the viewer
+    pub fn record_frame(&mut self, frame: &CanFrame) {
+        self.frames += 1;
+        self.bytes += frame.data().len() as u64;
+        self.bits += frame_bits(frame);
+
+        let id = frame::raw_can_id(frame) & !libc::CAN_RTR_FLAG;
+        *self.cob_id_counts.entry(id).or_insert(0) += 1;
+    }
+
+    pub fn record_error_frame(&mut self) {
+        self.error_frames += 1;
+    }
+
+    pub fn record_rx_error(&mut self) {
+        self.rx_errors += 1;
+    }
+
+    pub fn record_tx_error(&mut self) {
+        self.tx_errors += 1;
+    }
+
+    /// Report the current window and start a new one
+    ///
+    /// `counters` are the driver totals if the transport provides them.
+    pub fn report(&mut self, counters: Option<InterfaceCounters>) -> BusStats {
+        let elapsed = self.window_start.elapsed().as_secs_f64().max(f64::EPSILON);
+
+        let bus_totals = match (self.last_counters, counters) {
+            (Some(last), Some(now)) => {
+                let frames = (now.rx_frames + now.tx_frames).saturating_sub(last.rx_frames + last.tx_frames);
+                let bytes = (now.rx_bytes + now.tx_bytes).saturating_sub(last.rx_bytes + last.tx_bytes);
+                Some((frames, bytes, frames * STANDARD_FRAME_OVERHEAD_BITS + bytes * 8))
+            }
+            _ => None,
+        };
+        let (frames, bytes, bits) = bus_totals.unwrap_or((self.frames, self.bytes, self.bits));
+
+        let stats = BusStats {
+            frames_per_sec: frames as f64 / elapsed,
+            bytes_per_sec: bytes as f64 / elapsed,
+            bits_per_sec: bits as f64 / elapsed,
+            bus_load_percent: None,
+            whole_bus: bus_totals.is_some(),
+            cob_id_counts: self.cob_id_counts.clone(),
+            error_frames: self.error_frames,
+            rx_errors: self.rx_errors,
+            tx_errors: self.tx_errors,
+        };
+
+        self.window_start = Instant::now();
+        self.frames = 0;
+        self.bytes = 0;
+        self.bits = 0;
+        self.last_counters = counters;
+        stats
+    }
+}
+
+/// SDO traffic of one node during a reporting window
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeCommMetrics {
+    pub node_id: u8,
+    /// Requests waiting to be sent, including the one in flight
+    pub queue_length: usize,
+    pub responses_per_sec: f64,
+    pub average_round_trip: Option<Duration>,
+    pub max_round_trip: Option<Duration>,
+    /// Share of attempts that timed out (0.0 - 1.0)
+    pub timeout_rate: f64,
+}
+
+/// Round-trip times and timeouts of one node between reports
+pub struct SdoTimings {
+    window_start: Instant,
+    responses: u64,
+    timeouts: u64,
+    total_round_trip: Duration,
+    max_round_trip: Duration,
+}
+
+impl SdoTimings {
+    pub fn new() -> Self {
+        Self {
+            window_start: Instant::now(),
+            responses: 0,
+            timeouts: 0,
+            total_round_trip: Duration::ZERO,
+            max_round_trip: Duration::ZERO,
+        }
+    }
+
+    /// A response arrived `round_trip` after the request was sent
+    pub fn record_response(&mut self, round_trip: Duration) {
+        self.responses += 1;
+        self.total_round_trip += round_trip;
+        self.max_round_trip = self.max_round_trip.max(round_trip);
+    }
+
+    pub fn record_timeout(&mut self) {
+        self.timeouts += 1;
+    }
+
+    /// Report the current window and start a new one
+    pub fn report(&mut self, node_id: u8, queue_length: usize) -> NodeCommMetrics {
+        let elapsed = self.window_start.elapsed().as_secs_f64().max(f64::EPSILON);
+        let attempts = self.responses + self.timeouts;
+
+        let metrics = NodeCommMetrics {
+            node_id,
+            queue_length,
+            responses_per_sec: self.responses as f64 / elapsed,
+            average_round_trip: (self.responses > 0).then(|| self.total_round_trip / self.responses as u32),
+            max_round_trip: (self.responses > 0).then_some(self.max_round_trip),
+            timeout_rate: if attempts > 0 { self.timeouts as f64 / attempts as f64 } else { 0.0 },
+        };
+
+        *self = Self::new();
+        metrics
+    }
+}
+
+/// Nominal length of a frame on the wire
+fn frame_bits(frame: &CanFrame) -> u64 {
+    let overhead = if frame.is_extended() {
+        EXTENDED_FRAME_OVERHEAD_BITS
+    } else {
+        STANDARD_FRAME_OVERHEAD_BITS
+    };
+    overhead + frame.data().len() as u64 * 8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use socketcan::StandardId;
+
+    #[test]
+    fn test_report_counts_and_bus_load() {
+        let mut collector = BusStatsCollector::new();
+        let tpdo = CanFrame::new(StandardId::new(0x184).unwrap(), &[0; 8]).unwrap();
+        collector.record_frame(&tpdo);
+        collector.record_frame(&tpdo);
+        collector.record_tx_error();
+
+        let mut stats = collector.report(None);
+        assert!(!stats.whole_bus);
+        assert_eq!(stats.cob_id_counts.get(&0x184), Some(&2));
+        assert_eq!(stats.tx_errors, 1);
+
+        stats.bits_per_sec = 125_000.0;
+        stats.estimate_bus_load(500_000);
+        assert_eq!(stats.bus_load_percent, Some(25.0));
+
+        // Driver counters take over from the second report on
+        let counters = InterfaceCounters { rx_frames: 10, tx_frames: 0, rx_bytes: 80, tx_bytes: 0 };
+        collector.report(Some(counters));
+        let later = InterfaceCounters { rx_frames: 20, rx_bytes: 160, ..counters };
+        let stats = collector.report(Some(later));
+        assert!(stats.whole_bus);
+        assert_eq!(stats.cob_id_counts.get(&0x184), Some(&2));
+    }
+
+    #[test]
+    fn test_sdo_timings_report() {
+        let mut timings = SdoTimings::new();
+        timings.record_response(Duration::from_millis(2));
+        timings.record_response(Duration::from_millis(4));
+        timings.record_timeout();
+        timings.record_timeout();
+
+        let metrics = timings.report(4, 3);
+        assert_eq!(metrics.queue_length, 3);
+        assert_eq!(metrics.average_round_trip, Some(Duration::from_millis(3)));
+        assert_eq!(metrics.max_round_trip, Some(Duration::from_millis(4)));
+        assert_eq!(metrics.timeout_rate, 0.5);
+
+        // A new window starts empty
+        let metrics = timings.report(4, 0);
+        assert_eq!(metrics.average_round_trip, None);
+        assert_eq!(metrics.timeout_rate, 0.0);
+    }
+}
@@ -0,0 +1,299 @@
+// tcp.rs - Remote CAN bus over TCP (socketcand and cannelloni)
+//
+// socketcand speaks an ASCII protocol ("< frame 181 1700000000.000123 1122 >") and
+// timestamps frames on the remote host. cannelloni's TCP mode exchanges a
+// "CANNELLONIv1" greeting and then binary frames: big-endian can_id, length, data.
+use socketcan::{CanFrame, EmbeddedFrame};
+use std::io;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+use super::connect::{CanTransport, TransportFuture};
+use super::frame;
+use super::timestamp::{ReceivedFrame, TimestampSource};
+
+/// Give up on an unreachable host after this long
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+const CANNELLONI_GREETING: &[u8] = b"CANNELLONIv1";
+
+/// Flag in the cannelloni length byte marking a CAN FD frame (followed by a flags byte)
+const CANNELLONI_CANFD_FLAG: u8 = 0x80;
+
+/// Wire protocol spoken by the remote end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpProtocol {
+    Socketcand,
+    Cannelloni,
+}
+
+impl TcpProtocol {
+    /// Port used when the address doesn't name one
+    pub fn default_port(&self) -> u16 {
+        match self {
+            Self::Socketcand => 29536,
+            Self::Cannelloni => 20000,
+        }
+    }
+
+    pub fn scheme(&self) -> &'static str {
+        match self {
+            Self::Socketcand => "socketcand",
+            Self::Cannelloni => "cannelloni",
+        }
+    }
+}
+
+/// CAN bus reached through a TCP connection
+pub struct TcpTransport {
+    protocol: TcpProtocol,
+    reader: Mutex<BufReader<OwnedReadHalf>>,
+    writer: Mutex<OwnedWriteHalf>,
+}
+
+impl TcpTransport {
+    /// Connect and perform the protocol handshake (`channel` is the remote interface for socketcand)
+    pub async fn connect(address: &str, protocol: TcpProtocol, channel: &str) -> io::Result<Self> {
+        let stream = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(address))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "Connection timed out"))??;
+        stream.set_nodelay(true)?;
+
+        let (read_half, write_half) = stream.into_split();
+        let transport = Self {
+            protocol,
+            reader: Mutex::new(BufReader::new(read_half)),
+            writer: Mutex::new(write_half),
+        };
+
+        match protocol {
+            TcpProtocol::Socketcand => transport.socketcand_handshake(channel).await?,
+            TcpProtocol::Cannelloni => transport.cannelloni_handshake().await?,
+        }
+
+        Ok(transport)
+    }
+
+    async fn socketcand_handshake(&self, channel: &str) -> io::Result<()> {
+        expect_message(&self.read_message().await?, "hi")?;
+
+        self.write_all(format!("< open {} >", channel).as_bytes()).await?;
+        expect_message(&self.read_message().await?, "ok")?;
+
+        self.write_all(b"< rawmode >").await?;
+        expect_message(&self.read_message().await?, "ok")
+    }
+
+    async fn cannelloni_handshake(&self) -> io::Result<()> {
+        self.write_all(CANNELLONI_GREETING).await?;
+
+        let mut greeting = [0u8; CANNELLONI_GREETING.len()];
+        self.reader.lock().await.read_exact(&mut greeting).await?;
+        if greeting != CANNELLONI_GREETING {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a cannelloni server"));
+        }
+        Ok(())
+    }
+
+    /// Read one "< ... >" socketcand message and return its contents
+    async fn read_message(&self) -> io::Result<String> {
+        let mut buffer = Vec::new();
+        let mut reader = self.reader.lock().await;
+
+        reader.read_until(b'>', &mut buffer).await?;
+        // A message cut off by the end of the stream is not parsed
+        if buffer.last() != Some(&b'>') {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Connection closed"));
+        }
+
+        let text = String::from_utf8_lossy(&buffer);
+        let start = text.find('<')
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Malformed socketcand message"))?;
+        Ok(text[start + 1..text.len() - 1].trim().to_string())
+    }
+
+    async fn write_all(&self, bytes: &[u8]) -> io::Result<()> {
+        self.writer.lock().await.write_all(bytes).await
+    }
+
+    async fn recv_socketcand(&self) -> io::Result<ReceivedFrame> {
+        loop {
+            let message = self.read_message().await?;
+            if let Some(received) = parse_socketcand_frame(&message)? {
+                return Ok(received);
+            }
+            // Anything else (echo, ok, ...) is not a frame
+        }
+    }
+
+    async fn recv_cannelloni(&self) -> io::Result<ReceivedFrame> {
+        let mut reader = self.reader.lock().await;
+
+        let can_id = reader.read_u32().await?;
+        let mut len = reader.read_u8().await?;
+        if len & CANNELLONI_CANFD_FLAG != 0 {
+            let _flags = reader.read_u8().await?;
+            len &= !CANNELLONI_CANFD_FLAG;
+        }
+
+        let len = len as usize;
+        let mut data = vec![0u8; if can_id & libc::CAN_RTR_FLAG != 0 { 0 } else { len }];
+        reader.read_exact(&mut data).await?;
+
+        let frame = frame::from_raw_parts(can_id, len, &data)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Unsupported CAN frame"))?;
+        Ok(ReceivedFrame { frame, timestamp: SystemTime::now(), source: TimestampSource::Local, hardware_time: None })
+    }
+}
+
+impl CanTransport for TcpTransport {
+    fn recv(&self) -> TransportFuture<'_, ReceivedFrame> {
+        Box::pin(async move {
+            match self.protocol {
+                TcpProtocol::Socketcand => self.recv_socketcand().await,
+                TcpProtocol::Cannelloni => self.recv_cannelloni().await,
+            }
+        })
+    }
+
+    fn send<'a>(&'a self, frame: &'a CanFrame) -> TransportFuture<'a, ()> {
+        Box::pin(async move {
+            let bytes = match self.protocol {
+                TcpProtocol::Socketcand => encode_socketcand_frame(frame).into_bytes(),
+                TcpProtocol::Cannelloni => encode_cannelloni_frame(frame),
+            };
+            self.write_all(&bytes).await
+        })
+    }
+}
+
+fn expect_message(message: &str, expected: &str) -> io::Result<()> {
+    if message == expected {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("socketcand: expected '{}', got '{}'", expected, message)))
+    }
+}
+
+/// Parse "frame <id> <sec.usec> <hex data>"; `Ok(None)` for other messages
+fn parse_socketcand_frame(message: &str) -> io::Result<Option<ReceivedFrame>> {
+    let mut parts = message.split_whitespace();
+    if parts.next() != Some("frame") {
+        return Ok(None);
+    }
+
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("Malformed socketcand frame: {}", message));
+
+    let id_text = parts.next().ok_or_else(invalid)?;
+    let mut can_id = u32::from_str_radix(id_text, 16).map_err(|_| invalid())?;
+    if id_text.len() > 3 {
+        can_id |= libc::CAN_EFF_FLAG; // socketcand prints extended IDs with 8 digits
+    }
+
+    let timestamp = parts.next()
+        .and_then(|time| time.parse::<f64>().ok())
+        .filter(|secs| *secs > 0.0)
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .and_then(|since_epoch| UNIX_EPOCH.checked_add(since_epoch));
+
+    let hex = parts.next().unwrap_or("");
+    if !hex.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    let data = hex.as_bytes()
+        .chunks(2)
+        .map(|pair| std::str::from_utf8(pair).ok().and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(invalid)?;
+
+    let frame = frame::from_raw_parts(can_id, data.len(), &data).ok_or_else(invalid)?;
+    Ok(Some(match timestamp {
+        Some(timestamp) => ReceivedFrame { frame, timestamp, source: TimestampSource::Kernel, hardware_time: None },
+        None => ReceivedFrame { frame, timestamp: SystemTime::now(), source: TimestampSource::Local, hardware_time: None },
+    }))
+}
+
+fn encode_socketcand_frame(frame: &CanFrame) -> String {
+    let can_id = frame::raw_can_id(frame);
+    let id = if can_id & libc::CAN_EFF_FLAG != 0 {
+        format!("{:08X}", can_id & libc::CAN_EFF_MASK)
+    } else {
+        format!("{:03X}", can_id & libc::CAN_SFF_MASK)
+    };
+
+    let mut message = format!("< send {} {}", id, frame.data().len());
+    for byte in frame.data() {
+        message.push_str(&format!(" {:02X}", byte));
+    }
+    message.push_str(" >");
+    message
+}
+
+fn encode_cannelloni_frame(frame: &CanFrame) -> Vec<u8> {
+    let can_id = frame::raw_can_id(frame);
+    let mut bytes = can_id.to_be_bytes().to_vec();
+
+    if frame.is_remote_frame() {
+        bytes.push(frame.dlc() as u8);
+    } else {
+        bytes.push(frame.data().len() as u8);
+        bytes.extend_from_slice(frame.data());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use socketcan::{Id, StandardId};
+
+    #[test]
+    fn test_socketcand_frame_parsing_and_encoding() {
+        let received = parse_socketcand_frame("frame 181 1700000000.000500 1122").unwrap().unwrap();
+        assert_eq!(received.frame.id(), Id::Standard(StandardId::new(0x181).unwrap()));
+        assert_eq!(received.frame.data(), &[0x11, 0x22]);
+        assert_eq!(received.source, TimestampSource::Kernel);
+
+        assert!(parse_socketcand_frame("ok").unwrap().is_none());
+        for message in ["frame 181 0.0 112", "frame 181 0.0 1G", "frame 181 0.0 aéa"] {
+            assert!(parse_socketcand_frame(message).is_err(), "{}", message);
+        }
+        // Unusable times fall back to the local clock instead of failing the reader
+        for time in ["inf", "NaN", "1e300", "-1"] {
+            let received = parse_socketcand_frame(&format!("frame 181 {} 1122", time)).unwrap().unwrap();
+            assert_eq!(received.source, TimestampSource::Local);
+        }
+        assert_eq!(encode_socketcand_frame(&received.frame), "< send 181 2 11 22 >");
+    }
+
+    #[tokio::test]
+    async fn test_socketcand_handshake_and_receive() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0u8; 64];
+            stream.write_all(b"< hi >").await.unwrap();
+            let n = stream.read(&mut buffer).await.unwrap();
+            assert_eq!(&buffer[..n], b"< open vcan0 >");
+            stream.write_all(b"< ok >").await.unwrap();
+            let n = stream.read(&mut buffer).await.unwrap();
+            assert_eq!(&buffer[..n], b"< rawmode >");
+            // The connection closes in the middle of the next message
+            stream.write_all(b"< ok >< frame 701 0.0 05 ><").await.unwrap();
+        });
+
+        let transport = TcpTransport::connect(&address, TcpProtocol::Socketcand, "vcan0").await.unwrap();
+        let received = transport.recv().await.unwrap();
+        assert_eq!(received.frame.id(), Id::Standard(StandardId::new(0x701).unwrap()));
+        assert_eq!(received.frame.data(), &[0x05]);
+        assert_eq!(received.source, TimestampSource::Local);
+        server.await.unwrap();
+        let error = transport.recv().await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
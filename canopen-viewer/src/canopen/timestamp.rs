@@ -0,0 +1,219 @@
+// timestamp.rs - Kernel/hardware receive timestamps for SocketCAN frames
+//
+// `read_frame()` from the socketcan crate only returns the frame itself, so frames
+// are read with `recvmsg()` here to get at the SO_TIMESTAMPING control message.
+// The raw hardware timestamp counts on the controller's own clock, not CLOCK_REALTIME,
+// so it is kept apart from the receive time and only used for intervals between frames.
+use socketcan::{CanFrame, CanSocket};
+use std::io;
+use std::mem;
+use std::os::fd::AsRawFd;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Where a receive timestamp came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampSource {
+    /// Taken by the CAN controller
+    Hardware,
+    /// Taken by the kernel when the frame arrived
+    Kernel,
+    /// Taken in user space after the read (timestamping unavailable)
+    Local,
+    /// Read from a replayed capture
+    Recorded,
+}
+
+/// A received CAN frame with its receive timestamp
+#[derive(Debug, Clone)]
+pub struct ReceivedFrame {
+    pub frame: CanFrame,
+    pub timestamp: SystemTime,
+    pub source: TimestampSource,
+    /// Controller clock reading of a raw hardware timestamp; not wall time
+    pub hardware_time: Option<Duration>,
+}
+
+/// Ask the kernel to attach hardware (if the driver supports it) and software RX timestamps
+pub fn enable_rx_timestamping(socket: &CanSocket) -> io::Result<()> {
+    let flags: libc::c_uint = libc::SOF_TIMESTAMPING_RX_HARDWARE
+        | libc::SOF_TIMESTAMPING_RAW_HARDWARE
+        | libc::SOF_TIMESTAMPING_RX_SOFTWARE
+        | libc::SOF_TIMESTAMPING_SOFTWARE;
+
+    let result = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_TIMESTAMPING,
+            &flags as *const libc::c_uint as *const libc::c_void,
+            mem::size_of::<libc::c_uint>() as libc::socklen_t,
+        )
+    };
+
+    if result < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Read one frame together with its receive timestamp
+///
+/// Error frames are reported as `InvalidData`; the connection only subscribes to
+/// data and remote frames.
+pub fn read_frame_with_timestamp(socket: &CanSocket) -> io::Result<ReceivedFrame> {
+    let mut raw: libc::can_frame = unsafe { mem::zeroed() };
+    // Room for SCM_TIMESTAMPING (three timespecs) plus headers, 8-byte aligned
+    let mut control = [0u64; 16];
+
+    let mut iov = libc::iovec {
+        iov_base: &mut raw as *mut libc::can_frame as *mut libc::c_void,
+        iov_len: mem::size_of::<libc::can_frame>(),
+    };
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+    msg.msg_controllen = mem::size_of_val(&control) as _;
+
+    let received = unsafe { libc::recvmsg(socket.as_raw_fd(), &mut msg, 0) };
+    if received < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if (received as usize) < mem::size_of::<libc::can_frame>() {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Short CAN frame read"));
+    }
+
+    // The kernel sets msg_controllen to the length of the control messages it wrote
+    let control_len = (msg.msg_controllen as usize).min(mem::size_of_val(&control));
+    let control_bytes = unsafe { std::slice::from_raw_parts(control.as_ptr() as *const u8, control_len) };
+    let (kernel_time, hardware_time) = rx_timestamps(control_bytes);
+    let (timestamp, source) = match kernel_time {
+        Some(timestamp) => (timestamp, TimestampSource::Kernel),
+        None => (SystemTime::now(), TimestampSource::Local),
+    };
+
+    let len = (raw.can_dlc as usize).min(raw.data.len());
+    let frame = super::frame::from_raw_parts(raw.can_id, len, &raw.data[..len])
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Unsupported CAN frame"))?;
+
+    Ok(ReceivedFrame { frame, timestamp, source, hardware_time })
+}
+
+/// Software (kernel, wall clock) and raw hardware (controller clock) timestamps from the
+/// control messages received with a frame
+///
+/// `control` is the filled part of the `recvmsg()` control buffer. The messages are walked
+/// the way `CMSG_FIRSTHDR`/`CMSG_NXTHDR` do; a truncated buffer ends the walk.
+fn rx_timestamps(control: &[u8]) -> (Option<SystemTime>, Option<Duration>) {
+    let header_len = cmsg_align(mem::size_of::<libc::cmsghdr>());
+    let mut offset = 0;
+
+    while offset + mem::size_of::<libc::cmsghdr>() <= control.len() {
+        // SAFETY: the bounds check above covers the header, read_unaligned needs no alignment
+        let header = unsafe { (control.as_ptr().add(offset) as *const libc::cmsghdr).read_unaligned() };
+        #[allow(clippy::unnecessary_cast)] // socklen_t rather than size_t on musl
+        let cmsg_len = header.cmsg_len as usize;
+        if cmsg_len < header_len || offset + cmsg_len > control.len() {
+            break;
+        }
+
+        let data = &control[offset + header_len..offset + cmsg_len];
+        if header.cmsg_level == libc::SOL_SOCKET
+            && header.cmsg_type == libc::SCM_TIMESTAMPING
+            && data.len() >= mem::size_of::<[libc::timespec; 3]>()
+        {
+            // [0] software, [1] deprecated, [2] raw hardware
+            // SAFETY: the length check above covers the three timespecs
+            let stamps = unsafe { (data.as_ptr() as *const [libc::timespec; 3]).read_unaligned() };
+
+            let kernel = timespec_to_duration(&stamps[0]).map(|since_epoch| UNIX_EPOCH + since_epoch);
+            return (kernel, timespec_to_duration(&stamps[2]));
+        }
+        offset += cmsg_align(cmsg_len);
+    }
+
+    (None, None)
+}
+
+/// `CMSG_ALIGN`: control messages start at multiples of the size of `size_t`
+fn cmsg_align(len: usize) -> usize {
+    let align = mem::size_of::<usize>();
+    len.div_ceil(align) * align
+}
+
+fn timespec_to_duration(ts: &libc::timespec) -> Option<Duration> {
+    if ts.tv_sec <= 0 && ts.tv_nsec <= 0 {
+        return None; // Not filled in
+    }
+    Some(Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A control message as the kernel lays it out: header, data, padding
+    fn cmsg(level: libc::c_int, cmsg_type: libc::c_int, data: &[u8]) -> Vec<u8> {
+        let header_len = cmsg_align(mem::size_of::<libc::cmsghdr>());
+        let mut header: libc::cmsghdr = unsafe { mem::zeroed() };
+        header.cmsg_len = (header_len + data.len()) as _;
+        header.cmsg_level = level;
+        header.cmsg_type = cmsg_type;
+
+        let mut bytes = vec![0u8; cmsg_align(header_len + data.len())];
+        let header_bytes = unsafe {
+            std::slice::from_raw_parts(&header as *const libc::cmsghdr as *const u8, mem::size_of::<libc::cmsghdr>())
+        };
+        bytes[..header_bytes.len()].copy_from_slice(header_bytes);
+        bytes[header_len..header_len + data.len()].copy_from_slice(data);
+        bytes
+    }
+
+    fn timestamping(software: (i64, i64), hardware: (i64, i64)) -> Vec<u8> {
+        let mut stamps: [libc::timespec; 3] = unsafe { mem::zeroed() };
+        stamps[0].tv_sec = software.0 as _;
+        stamps[0].tv_nsec = software.1 as _;
+        stamps[2].tv_sec = hardware.0 as _;
+        stamps[2].tv_nsec = hardware.1 as _;
+        let data = unsafe {
+            std::slice::from_raw_parts(&stamps as *const [libc::timespec; 3] as *const u8, mem::size_of_val(&stamps))
+        };
+        cmsg(libc::SOL_SOCKET, libc::SCM_TIMESTAMPING, data)
+    }
+
+    #[test]
+    fn test_hardware_timestamp_kept_apart() {
+        // Controller clock, counting from when the adapter was powered up
+        let control = timestamping((1_700_000_000, 500), (42, 250_000));
+        assert_eq!(
+            rx_timestamps(&control),
+            (Some(UNIX_EPOCH + Duration::new(1_700_000_000, 500)), Some(Duration::new(42, 250_000)))
+        );
+        assert_eq!(rx_timestamps(&timestamping((0, 0), (42, 0))), (None, Some(Duration::from_secs(42))));
+    }
+
+    #[test]
+    fn test_software_timestamp_without_hardware() {
+        let control = timestamping((1_700_000_000, 500), (0, 0));
+        assert_eq!(rx_timestamps(&control), (Some(UNIX_EPOCH + Duration::new(1_700_000_000, 500)), None));
+        assert_eq!(rx_timestamps(&timestamping((0, 0), (0, 0))), (None, None));
+    }
+
+    #[test]
+    fn test_other_control_messages_are_skipped() {
+        // SO_RXQ_OVFL reports the dropped frame count ahead of the timestamps
+        let mut control = cmsg(libc::SOL_SOCKET, libc::SO_RXQ_OVFL, &3u32.to_ne_bytes());
+        control.extend(timestamping((1_700_000_000, 0), (0, 0)));
+        assert!(rx_timestamps(&control).0.is_some());
+    }
+
+    #[test]
+    fn test_truncated_control_buffer() {
+        assert_eq!(rx_timestamps(&[]), (None, None));
+        let control = timestamping((1_700_000_000, 0), (0, 0));
+        for len in [4, mem::size_of::<libc::cmsghdr>(), control.len() - 8] {
+            assert_eq!(rx_timestamps(&control[..len]), (None, None));
+        }
+    }
+}
@@ -0,0 +1,93 @@
+// capture.rs - Where plot screenshots and exports are saved
+//
+// During a test run a save dialog for every screenshot or CSV export interrupts the
+// measurement. With auto-save on, captures go straight into the capture directory; each
+// file is named after its plot with the time of the capture appended, so nothing is
+// overwritten. Without it the dialog suggests the same name.
+use chrono::{DateTime, Local};
+use eframe::egui;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::AppConfig;
+
+/// `file_name` with characters unsafe in file names replaced and the time appended
+pub fn timestamped_name(file_name: &str, now: DateTime<Local>) -> String {
+    // Plot titles may contain dots and slashes, so only a short final word counts as extension
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension))
+            if extension.len() <= 4
+                && extension.starts_with(|c: char| c.is_ascii_alphabetic())
+                && extension.chars().all(|c| c.is_ascii_alphanumeric()) => (stem, Some(extension)),
+        _ => (file_name, None),
+    };
+    let stem: String = stem.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let timestamp = now.format("%Y%m%d_%H%M%S_%3f");
+    match extension {
+        Some(extension) => format!("{}_{}.{}", stem, timestamp, extension),
+        None => format!("{}_{}", stem, timestamp),
+    }
+}
+
+/// Path of an auto-saved capture, creating the directory when needed
+pub fn auto_save_path(directory: &Path, file_name: &str, now: DateTime<Local>) -> io::Result<PathBuf> {
+    fs::create_dir_all(directory)?;
+    Ok(directory.join(timestamped_name(file_name, now)))
+}
+
+/// "Captures" menu above the plots; returns whether the settings changed
+pub fn menu(ui: &mut egui::Ui, config: &mut AppConfig) -> bool {
+    let mut changed = false;
+    ui.menu_button("📁 Captures", |ui| {
+        changed |= ui.checkbox(&mut config.auto_save_captures, "Save without asking")
+            .on_hover_text("Screenshots and exports go to the capture folder with timestamped names")
+            .changed();
+
+        let directory = config.get_capture_directory();
+        ui.label(format!("Folder: {}", directory.as_ref().map_or_else(|| "-".to_string(), |dir| dir.display().to_string())));
+        ui.horizontal(|ui| {
+            if ui.button("Choose folder...").clicked() {
+                if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                    config.capture_directory = Some(folder.display().to_string());
+                    changed = true;
+                }
+                ui.close_menu();
+            }
+            if ui.add_enabled(config.capture_directory.is_some(), egui::Button::new("Use default")).clicked() {
+                config.capture_directory = None;
+                changed = true;
+            }
+            if let Some(directory) = directory.filter(|dir| dir.is_dir()) {
+                if ui.button("Open folder").clicked() {
+                    let _ = open::that(directory);
+                    ui.close_menu();
+                }
+            }
+        });
+    });
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_capture_names() {
+        let now = Local.with_ymd_and_hms(2026, 3, 14, 9, 5, 7).unwrap();
+        assert_eq!(timestamped_name("SDO - Voltage (0x6401:1).png", now), "SDO_-_Voltage__0x6401_1__20260314_090507_000.png");
+        assert_eq!(timestamped_name("plot_data_6401_01.csv", now), "plot_data_6401_01_20260314_090507_000.csv");
+        assert_eq!(timestamped_name("capture", now), "capture_20260314_090507_000");
+        assert_eq!(timestamped_name("TPDO 1 - In/Out 1.5", now), "TPDO_1_-_In_Out_1_5_20260314_090507_000");
+
+        let directory = TestDir::new("capture");
+        let path = auto_save_path(&directory.join("nested"), "a.svg", now).unwrap();
+        assert!(path.parent().unwrap().is_dir());
+        assert_eq!(path.file_name().unwrap(), "a_20260314_090507_000.svg");
+    }
+}
@@ -0,0 +1,96 @@
+// channel_stats.rs - Running statistics of a plotted channel
+//
+// Min, max, mean and standard deviation are accumulated over every sample since the
+// subscription started or the statistics were last reset, not just the samples still in
+// the plot buffer. The mean and variance are updated incrementally (Welford), so long
+// recordings cost nothing extra. They are kept in raw units and scaled for display.
+use crate::scaling::Scaling;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChannelStats {
+    pub count: u64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Sum of squared differences from the mean
+    m2: f64,
+}
+
+impl ChannelStats {
+    pub fn record(&mut self, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Standard deviation of the recorded samples (population)
+    pub fn deviation(&self) -> f64 {
+        if self.count == 0 { 0.0 } else { (self.m2 / self.count as f64).sqrt() }
+    }
+
+    /// The statistics of the scaled values; a negative factor swaps min and max
+    pub fn scaled(&self, scaling: Option<&Scaling>) -> Self {
+        let Some(scaling) = scaling else {
+            return self.clone();
+        };
+        let (min, max) = (scaling.apply(self.min), scaling.apply(self.max));
+        Self {
+            count: self.count,
+            min: min.min(max),
+            max: min.max(max),
+            mean: scaling.apply(self.mean),
+            m2: self.m2 * scaling.factor * scaling.factor,
+        }
+    }
+
+    /// Label and value of each statistic, for the line under a plot and CSV exports
+    pub fn rows(&self, rate: f64) -> Vec<(&'static str, String)> {
+        if self.count == 0 {
+            return vec![("Samples", "0".to_string())];
+        }
+        vec![
+            ("Samples", self.count.to_string()),
+            ("Min", format!("{:.4}", self.min)),
+            ("Max", format!("{:.4}", self.max)),
+            ("Mean", format!("{:.4}", self.mean)),
+            ("Std dev", format!("{:.4}", self.deviation())),
+            ("Rate (Hz)", format!("{:.1}", rate)),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_stats() {
+        let mut stats = ChannelStats::default();
+        for value in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            stats.record(value);
+        }
+        assert_eq!((stats.count, stats.min, stats.max, stats.mean), (8, 2.0, 9.0, 5.0));
+        assert!((stats.deviation() - 2.0).abs() < 1e-12);
+        assert_eq!(stats.rows(10.0)[4], ("Std dev", "2.0000".to_string()));
+
+        let scaling = Scaling { factor: -0.5, offset: 1.0, unit: "V".to_string() };
+        let scaled = stats.scaled(Some(&scaling));
+        assert_eq!((scaled.min, scaled.max, scaled.mean), (-3.5, 0.0, -1.5));
+        assert!((scaled.deviation() - 1.0).abs() < 1e-12);
+
+        stats.reset();
+        assert_eq!(stats.rows(0.0), vec![("Samples", "0".to_string())]);
+    }
+}
@@ -0,0 +1,151 @@
+// channel_style.rs - Color, width and dash of a plotted channel
+//
+// Colors derived from the object address could repeat or come out almost black on the
+// dark theme. Each channel is instead given the palette color used least so far the first
+// time it is plotted, and the user can pick another color, a line width and a dash style
+// under its plot. The styles are kept in the config file, so a channel looks the same in
+// every plot and session.
+use eframe::egui::{self, Color32};
+use egui_plot::{Line, LineStyle};
+use serde::{Deserialize, Serialize};
+
+/// Colors distinct from each other and readable on dark and light backgrounds
+pub const PALETTE: [[u8; 3]; 10] = [
+    [100, 149, 237],
+    [255, 165, 0],
+    [60, 179, 113],
+    [220, 80, 80],
+    [186, 120, 220],
+    [64, 200, 200],
+    [230, 200, 60],
+    [240, 128, 190],
+    [160, 200, 90],
+    [170, 140, 110],
+];
+
+const DEFAULT_WIDTH: f32 = 1.5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dash {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl Dash {
+    const ALL: [Dash; 3] = [Dash::Solid, Dash::Dashed, Dash::Dotted];
+
+    fn label(self) -> &'static str {
+        match self {
+            Dash::Solid => "Solid",
+            Dash::Dashed => "Dashed",
+            Dash::Dotted => "Dotted",
+        }
+    }
+
+    fn line_style(self) -> LineStyle {
+        match self {
+            Dash::Solid => LineStyle::Solid,
+            Dash::Dashed => LineStyle::dashed_loose(),
+            Dash::Dotted => LineStyle::dotted_dense(),
+        }
+    }
+}
+
+fn default_width() -> f32 {
+    DEFAULT_WIDTH
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChannelStyle {
+    pub color: [u8; 3],
+    #[serde(default = "default_width")]
+    pub width: f32,
+    #[serde(default)]
+    pub dash: Dash,
+}
+
+impl Default for ChannelStyle {
+    fn default() -> Self {
+        Self::new(PALETTE[0])
+    }
+}
+
+impl ChannelStyle {
+    pub fn new(color: [u8; 3]) -> Self {
+        Self { color, width: DEFAULT_WIDTH, dash: Dash::Solid }
+    }
+
+    pub fn color32(&self) -> Color32 {
+        let [r, g, b] = self.color;
+        Color32::from_rgb(r, g, b)
+    }
+
+    pub fn apply<'a>(&self, line: Line<'a>) -> Line<'a> {
+        line.color(self.color32()).width(self.width).style(self.dash.line_style())
+    }
+}
+
+/// The palette color fewest channels use, the earlier one on a tie
+pub fn next_color(used: impl Iterator<Item = [u8; 3]>) -> [u8; 3] {
+    let mut counts = [0usize; PALETTE.len()];
+    for color in used {
+        if let Some(index) = PALETTE.iter().position(|entry| *entry == color) {
+            counts[index] += 1;
+        }
+    }
+    let least = counts.iter().copied().min().unwrap_or(0);
+    PALETTE[counts.iter().position(|count| *count == least).unwrap_or(0)]
+}
+
+/// "Style" menu under a plot; returns whether the style changed
+pub fn menu(ui: &mut egui::Ui, style: &mut ChannelStyle) -> bool {
+    let mut changed = false;
+    ui.menu_button("🎨 Style", |ui| {
+        ui.horizontal_wrapped(|ui| {
+            for color in PALETTE {
+                let [r, g, b] = color;
+                let swatch = egui::Button::new("   ").fill(Color32::from_rgb(r, g, b)).selected(style.color == color);
+                if ui.add(swatch).clicked() {
+                    style.color = color;
+                    changed = true;
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Custom:");
+            changed |= ui.color_edit_button_srgb(&mut style.color).changed();
+        });
+        ui.horizontal(|ui| {
+            ui.label("Width:");
+            changed |= ui.add(egui::DragValue::new(&mut style.width).range(0.5..=6.0).speed(0.1)).changed();
+        });
+        ui.horizontal(|ui| {
+            for dash in Dash::ALL {
+                changed |= ui.radio_value(&mut style.dash, dash, dash.label()).changed();
+            }
+        });
+    });
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_assignment_and_round_trip() {
+        assert_eq!(next_color(std::iter::empty()), PALETTE[0]);
+        assert_eq!(next_color([PALETTE[0], PALETTE[2]].into_iter()), PALETTE[1]);
+        // Custom colors don't take a palette slot; once all are used the palette starts over
+        assert_eq!(next_color([[1, 2, 3]].into_iter()), PALETTE[0]);
+        assert_eq!(next_color(PALETTE.into_iter().chain([PALETTE[0]])), PALETTE[1]);
+
+        let style: ChannelStyle = toml::from_str("color = [10, 20, 30]\n").unwrap();
+        assert_eq!(style, ChannelStyle { color: [10, 20, 30], width: 1.5, dash: Dash::Solid });
+        let dashed = ChannelStyle { dash: Dash::Dashed, ..style };
+        assert_eq!(toml::from_str::<ChannelStyle>(&toml::to_string(&dashed).unwrap()).unwrap(), dashed);
+    }
+}
@@ -1,21 +1,71 @@
-use std::sync::mpsc::{Receiver, Sender};
+use std::sync::mpsc::Receiver;
 use std::path::PathBuf;
 use configparser::ini::Ini;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{hash_map::Entry, BTreeMap, HashMap, HashSet};
 use tokio::task::JoinHandle;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use chrono::{DateTime, Local};
-use socketcan::EmbeddedFrame;
+use serde::{Deserialize, Serialize};
+use socketcan::{CanFrame, EmbeddedFrame};
+use canopen_common::{create_sdo_request_frame, create_sdo_write_frame, parse_emcy_frame, EMCY_COB_ID_BASE, LssCommand, LssResponse};
+use crate::queue::{QueueItem, Sender};
+use crate::backup::RestoreOutcome;
+use crate::macros::MacroOp;
+use crate::gateway;
+use crate::object_walk;
+use crate::rpdo::RpdoConfig;
+use crate::scanner::{self, Probe, ScanHit, ScanRange};
+use crate::startup::{self, StartupPlan, StartupStep};
+use crate::device_check::{self, CheckOutcome, DeviceCheck, DeviceCheckPlan};
+use crate::stimulus::{self, StimulusConfig};
+use crate::snapshot::SnapshotEntry;
+use crate::trace::TraceEntry;
+use crate::scaling::Scaling;
+use crate::value_description;
+use crate::canopen::heartbeat::NodeNmtState;
+use crate::canopen::netlink::{self, ControllerStatus};
+use crate::emcy_monitor::EmergencyRecord;
+use crate::canopen::stats::{BusStats, NodeCommMetrics};
+use crate::canopen::timestamp::{ReceivedFrame, TimestampSource};
 use crate::canopen::{
-    CANopenConnection, CANopenNodeHandle,
-    SdoRequest, SdoDataType
+    CANopenConnection, CANopenError, CANopenNodeHandle, ConnectionEvent, SdoPriority, SdoRetryPolicy, TransportConfig,
+    SdoRequest, SdoWriteRequest, SdoDataType, NmtCommand
 };
 
 
+/// Connection parameters taken from `AppConfig`
+#[derive(Debug, Clone)]
+pub struct ConnectionSettings {
+    /// Default SDO timeout
+    pub sdo_timeout: Duration,
+    /// SDO timeout override for the connected node
+    pub node_sdo_timeout: Option<Duration>,
+    pub retry_policy: SdoRetryPolicy,
+    /// Nominal bitrate for the bus load estimate
+    pub bus_bitrate: u32,
+    /// Data types chosen by the user for TPDO fields, by mapped object
+    pub tpdo_type_overrides: HashMap<SdoAddress, SdoDataType>,
+    /// Longest expected gap between the node's heartbeats (`None` = a few measured periods)
+    pub heartbeat_consumer_time: Option<Duration>,
+}
+
 #[derive(Debug, Clone)]
 pub struct SdoSubObject {
     pub name: String,
     pub data_type: String,
+    /// EDS access type ("ro" or "rw")
+    pub access_type: String,
+    /// EDS `LowLimit` / `HighLimit` as written in the file
+    pub low_limit: Option<String>,
+    pub high_limit: Option<String>,
+    /// EDS `DefaultValue` as written in the file, may contain $NODEID
+    pub default_value: Option<String>,
+    /// Names of enumerated values from the EDS `ValueDescription` section
+    pub value_descriptions: BTreeMap<i64, String>,
+    /// Engineering-unit conversion from the EDS `Factor` / `Offset` / `Unit` keys
+    pub scaling: Option<Scaling>,
 }
 
 #[derive(Debug, Clone)]
@@ -24,14 +74,30 @@ pub struct SdoObject {
     pub sub_objects: BTreeMap<u8, SdoSubObject>,
 }
 
-#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct SdoAddress {
     pub index: u16,
     pub sub_index: u8,
 }
 
+impl SdoAddress {
+    /// Key used for per-object settings in the config file, named like EDS sections ("6060sub0")
+    pub fn eds_key(&self) -> String {
+        format!("{:04X}sub{}", self.index, self.sub_index)
+    }
+
+    pub fn from_eds_key(key: &str) -> Option<Self> {
+        let key = key.to_ascii_lowercase();
+        let (index, sub_index) = key.split_once("sub")?;
+        Some(Self {
+            index: u16::from_str_radix(index, 16).ok()?,
+            sub_index: sub_index.parse().ok()?,
+        })
+    }
+}
+
 /// Represents a single object mapped into a TPDO
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TpdoMappedObject {
     pub index: u16,
     pub sub_index: u8,
@@ -40,11 +106,48 @@ pub struct TpdoMappedObject {
     pub name: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TpdoConfig {
     pub tpdo_number: u8,
     pub cob_id: u16,
     pub mapped_objects: Vec<TpdoMappedObject>,
+    /// Transmission type (sub-index 2 of the communication parameter), if known
+    #[serde(default)]
+    pub transmission_type: Option<u8>,
+    /// The node answers a remote request on the COB-ID (bit 30 of the COB-ID entry clear)
+    #[serde(default)]
+    pub rtr_allowed: bool,
+    /// Event timer in ms (sub-index 5), 0 = disabled
+    #[serde(default)]
+    pub event_timer_ms: Option<u16>,
+}
+
+impl TpdoConfig {
+    /// Transmission types 0xFC / 0xFD only send on a remote request
+    pub fn rtr_only(&self) -> bool {
+        matches!(self.transmission_type, Some(0xFC) | Some(0xFD))
+    }
+
+    /// Number of data bytes of the PDO frame
+    pub fn data_length(&self) -> usize {
+        let bits: usize = self.mapped_objects.iter().map(|obj| obj.bit_length as usize).sum();
+        bits.div_ceil(8).min(8)
+    }
+}
+
+/// One request/response pair issued from the SDO console
+#[derive(Debug, Clone)]
+pub struct RawSdoExchange {
+    pub timestamp: DateTime<Local>,
+    pub index: u16,
+    pub sub_index: u8,
+    pub write: bool,
+    /// Data bytes of the request frame, empty if it could not be built
+    pub request: Vec<u8>,
+    /// Data bytes of the node's response, `None` if it didn't answer
+    pub response: Option<Vec<u8>>,
+    /// Decoded value of a read ("OK" for a write), or the error including the abort code
+    pub result: Result<String, String>,
 }
 
 /// Parsed TPDO data received from CAN bus
@@ -52,41 +155,293 @@ pub struct TpdoConfig {
 pub struct TpdoData {
     pub tpdo_number: u8,
     pub timestamp: DateTime<Local>,
+    pub timestamp_source: TimestampSource,
+    /// Controller clock reading, for the intervals between frames only
+    pub hardware_time: Option<Duration>,
     pub values: Vec<(String, String)>, // (object_name, parsed_value)
+    /// Data bytes of the frame
+    pub raw: Vec<u8>,
 }
 
 #[derive(Debug)]
 pub enum Command {
     Connect,
     FetchSdos,
+    /// Switch to another EDS file (or re-read the current one) and send the new SDO list
+    LoadEds(Option<PathBuf>),
     Subscribe {
         address: SdoAddress,
         interval_ms: u64,
         data_type: SdoDataType,
     },
     Unsubscribe(SdoAddress),
+    /// Stop polling a subscription without dropping it (`paused: false` polls again)
+    PauseSubscription {
+        address: SdoAddress,
+        paused: bool,
+    },
+    /// Expedited SDO download of an already encoded value
+    WriteSdo {
+        address: SdoAddress,
+        data: Vec<u8>,
+    },
     DiscoverTpdos,
+    /// Read the PDO configuration again and restart running listeners with the new layout
+    RediscoverTpdos,
+    /// Read every object of the EDS once (only the rw ones for a backup) and report the values
+    TakeSnapshot {
+        writable_only: bool,
+    },
+    CancelSnapshot,
+    /// Write backed up parameters to a node (the connected one or a clone target) in the given order
+    RestoreParameters {
+        node_id: u8,
+        entries: Vec<SnapshotEntry>,
+    },
     StartTpdoListener(TpdoConfig),
+    /// Decode TPDO fields with these types; takes effect with the next TPDO discovery
+    SetTpdoTypeOverrides(HashMap<SdoAddress, SdoDataType>),
+    /// Send a remote request (RTR) for a TPDO and report the frame the node answers with
+    RequestTpdo(TpdoConfig),
+    /// One-shot SDO read (`data` is `None`) or write from the console, by address only
+    RawSdo {
+        index: u16,
+        sub_index: u8,
+        data_type: SdoDataType,
+        data: Option<Vec<u8>>,
+    },
     StopTpdoListener(u8),
+    Nmt {
+        node_id: u8, // 0 = all nodes
+        command: NmtCommand,
+    },
+    /// LSS request to the unconfigured nodes; confirmed ones wait briefly for the answer
+    Lss(LssCommand),
+    /// Execute macro steps on the connected node, stopping at the first failure
+    RunMacro(Vec<MacroOp>),
+    StopMacro,
+    /// Check, configure and start the connected node like an NMT master
+    RunStartup(StartupPlan),
+    /// Run the device sanity checks on the node
+    RunDeviceCheck(DeviceCheckPlan),
+    /// Write a value pattern to one object periodically until stopped or a write fails
+    StartStimulus(StimulusConfig),
+    StopStimulus,
+    /// Produce SYNC frames every `period_ms`; a counter overflow of 2-240 adds the counter byte
+    StartSync {
+        period_ms: u64,
+        counter_overflow: u8,
+    },
+    StopSync,
+    /// Serve CiA 309-3 ASCII requests on this address ("127.0.0.1:60000")
+    StartGateway(String),
+    StopGateway,
+    /// Forward every received frame to the trace view
+    StartTrace,
+    StopTrace,
+    /// Transmit an arbitrary frame from the frame sender
+    SendFrame(CanFrame),
+    /// Transmit a frame periodically, replacing a running transmission
+    StartFrame {
+        frame: CanFrame,
+        interval_ms: u64,
+    },
+    StopFrame,
+    /// Read the state and error counters of the SocketCAN controller
+    QueryControllerState,
+    /// Restart the SocketCAN controller to recover from bus-off
+    RestartController,
+    /// Read the RPDO layout (0x1400 / 0x1600) of the node
+    DiscoverRpdos,
+    /// Transmit one RPDO frame
+    SendRpdo {
+        rpdo_number: u8,
+        cob_id: u16,
+        data: Vec<u8>,
+    },
+    /// Transmit an RPDO frame periodically, replacing a running transmission of the same RPDO
+    StartRpdo {
+        rpdo_number: u8,
+        cob_id: u16,
+        data: Vec<u8>,
+        interval_ms: u64,
+    },
+    StopRpdo(u8),
+    /// Read every address of a range to find objects missing from the EDS
+    ScanObjects(ScanRange),
+    CancelScan,
+    /// Stop reading the object list of a device without EDS
+    CancelObjectWalk,
+    /// Tear down the connection and all tasks; the communication thread exits afterwards
+    Disconnect,
+    SetSdoTimeout {
+        node_id: u8,
+        timeout: Duration,
+    },
+    SetHeartbeatConsumerTime {
+        node_id: u8,
+        consumer_time: Option<Duration>,
+    },
 }
 
 #[derive(Debug)]
 pub enum Update {
     ConnectionStatus(bool),
     ConnectionFailed(String),
+    /// The CAN interface went down; the connection keeps trying to reopen it
+    InterfaceLost(String),
+    /// The CAN interface is back and subscriptions resume
+    InterfaceRestored,
+    /// Emergency message from any node on the bus
+    Emergency(EmergencyRecord),
+    /// NMT state of a node from its heartbeats
+    NmtState {
+        node_id: u8,
+        state: NodeNmtState,
+    },
     SdoList(BTreeMap<u16, SdoObject>),
+    /// Indices read so far while building the object list of a device without EDS
+    ObjectWalkProgress {
+        done: usize,
+        total: usize,
+    },
     SdoData {
         address: SdoAddress,
         value: String,
+        /// Data bytes of the response
+        raw: Vec<u8>,
     },
     SdoReadError {
         address: SdoAddress,
         error: String,
     },
-    TpdoData(TpdoData),
+    /// Outcome of a `Command::WriteSdo`, with the abort reason on failure
+    SdoWriteResult {
+        address: SdoAddress,
+        data: Vec<u8>,
+        result: Result<(), String>,
+    },
+    RawSdo(RawSdoExchange),
+    SnapshotProgress {
+        done: usize,
+        total: usize,
+    },
+    SnapshotComplete(Vec<SnapshotEntry>),
+    RestoreProgress {
+        done: usize,
+        total: usize,
+    },
+    RestoreComplete(Vec<RestoreOutcome>),
+    MacroStepResult {
+        step: usize,
+        result: Result<(), String>,
+    },
+    /// The macro completed, failed or was stopped
+    MacroFinished,
+    StartupStep {
+        step: StartupStep,
+        result: Result<String, String>,
+    },
+    /// The startup sequence completed or stopped at a failed step
+    StartupFinished,
+    DeviceCheck {
+        check: DeviceCheck,
+        outcome: CheckOutcome,
+    },
+    DeviceCheckFinished,
+    StimulusWritten {
+        value: f64,
+        result: Result<(), String>,
+    },
+    StimulusStopped,
+    ScanProgress {
+        done: usize,
+        total: usize,
+    },
+    /// An address of the scanned range answered
+    ScanHit(ScanHit),
+    /// The scan went through the whole range or was cancelled
+    ScanFinished,
+    /// TPDO samples collected over one batching window
+    TpdoBatch(Vec<TpdoData>),
     TpdosDiscovered(Vec<TpdoConfig>),
+    /// Answer to a TPDO remote request
+    TpdoRequested {
+        tpdo_number: u8,
+        result: Result<TpdoData, String>,
+    },
+    /// The ASCII gateway couldn't listen or stopped accepting clients
+    GatewayStopped(String),
+    /// Number of connected gateway clients
+    GatewayClients(usize),
+    /// Frames received over one batching window, for the trace view
+    TraceFrames(Vec<TraceEntry>),
+    /// SYNC production ended because a frame couldn't be sent
+    SyncStopped(String),
+    /// Outcome of an NMT command (node ID 0 = broadcast)
+    NmtSent {
+        node_id: u8,
+        command: NmtCommand,
+        result: Result<(), String>,
+    },
+    /// Outcome of an LSS command; `None` when no node answered (or none was expected)
+    LssResult {
+        command: LssCommand,
+        result: Result<Option<LssResponse>, String>,
+    },
+    RpdosDiscovered(Vec<RpdoConfig>),
+    RpdoSent {
+        rpdo_number: u8,
+        result: Result<(), String>,
+    },
+    FrameSent(Result<(), String>),
+    /// The cyclic frame transmission ended because a frame couldn't be sent
+    FrameStopped(String),
+    ControllerState(Result<ControllerStatus, String>),
+    ControllerRestarted(Result<(), String>),
+    /// A cyclic RPDO transmission ended because a frame couldn't be sent
+    RpdoStopped {
+        rpdo_number: u8,
+        error: String,
+    },
+    /// Frame rates, bus load and error counters, published once per second
+    BusStats(BusStats),
+    /// SDO queue length, round-trip time and timeout rate per node, published once per second
+    CommMetrics(Vec<NodeCommMetrics>),
+    /// Samples discarded because a queue between threads was full
+    Overflow {
+        dropped: u64,
+    },
 }
 
+impl QueueItem for Update {
+    fn is_sample(&self) -> bool {
+        matches!(self, Update::SdoData { .. } | Update::TpdoBatch(_) | Update::TraceFrames(_))
+    }
+
+    fn overflow(dropped: u64) -> Option<Self> {
+        Some(Update::Overflow { dropped })
+    }
+}
+
+/// Updates buffered for the UI before the oldest samples are dropped
+pub const UPDATE_QUEUE_CAPACITY: usize = 4096;
+
+/// Longest a TPDO sample waits before its batch is sent to the UI
+const TPDO_BATCH_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Batch size that is sent right away without waiting for the interval
+const TPDO_BATCH_MAX_SAMPLES: usize = 50;
+
+/// Longest a traced frame waits before its batch is sent to the UI
+const TRACE_BATCH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Trace batch size that is sent right away without waiting for the interval
+const TRACE_BATCH_MAX_FRAMES: usize = 200;
+
+/// How long the node has to answer a TPDO remote request
+const TPDO_REQUEST_TIMEOUT: Duration = Duration::from_millis(500);
+
 async fn sdo_polling_task(
     address: SdoAddress,
     interval_ms: u64,
@@ -106,12 +461,13 @@ async fn sdo_polling_task(
             expected_type: data_type.clone(),
         };
 
-        match node_handle.sdo_read(request).await {
+        match node_handle.sdo_read_with_priority(request, SdoPriority::Polling).await {
             Ok(sdo_response) => {
                 let value_string = sdo_response.data.to_string();
                 let _ = update_tx.send(Update::SdoData {
                     address: address.clone(),
                     value: value_string,
+                    raw: sdo_response.payload().to_vec(),
                 });
             },
             Err(err) => {
@@ -125,9 +481,12 @@ async fn sdo_polling_task(
 }
 
 /// Health check task that periodically reads Device Type (0x1000:00) to verify node is alive
+///
+/// Skipped while the node's heartbeats arrive; they tell the same without loading the bus.
 async fn health_check_task(
     update_tx: Sender<Update>,
     node_handle: CANopenNodeHandle,
+    heartbeat_supervised: Arc<AtomicBool>,
 ) {
     let mut interval = tokio::time::interval(Duration::from_secs(2));
     let mut consecutive_failures = 0;
@@ -135,6 +494,10 @@ async fn health_check_task(
 
     loop {
         interval.tick().await;
+        if heartbeat_supervised.load(Ordering::Relaxed) {
+            consecutive_failures = 0;
+            continue;
+        }
 
         // Read mandatory Device Type object (0x1000:00)
         let request = SdoRequest {
@@ -144,7 +507,7 @@ async fn health_check_task(
             expected_type: SdoDataType::UInt32,
         };
 
-        match node_handle.sdo_read(request).await {
+        match node_handle.sdo_read_with_priority(request, SdoPriority::HealthCheck).await {
             Ok(_) => {
                 consecutive_failures = 0;
                 let _ = update_tx.send(Update::ConnectionStatus(true));
@@ -163,6 +526,39 @@ async fn health_check_task(
     }
 }
 
+/// Forward interface events and bus statistics from the connection to the UI
+async fn connection_event_task(
+    update_tx: Sender<Update>,
+    mut event_rx: tokio::sync::mpsc::UnboundedReceiver<ConnectionEvent>,
+    bitrate: u32,
+    node_id: u8,
+    heartbeat_supervised: Arc<AtomicBool>,
+) {
+    while let Some(event) = event_rx.recv().await {
+        let update = match event {
+            ConnectionEvent::InterfaceLost(reason) => Update::InterfaceLost(reason),
+            ConnectionEvent::InterfaceRestored => Update::InterfaceRestored,
+            ConnectionEvent::BusStats(mut stats) => {
+                stats.estimate_bus_load(bitrate);
+                Update::BusStats(stats)
+            }
+            ConnectionEvent::CommMetrics(metrics) => Update::CommMetrics(metrics),
+            ConnectionEvent::NmtState { node_id: from, state } => {
+                // Heartbeats of other nodes only go to the network overview
+                if from == node_id {
+                    let alive = matches!(state, NodeNmtState::Active(_));
+                    heartbeat_supervised.store(alive, Ordering::Relaxed);
+                    let _ = update_tx.send(Update::ConnectionStatus(alive));
+                }
+                Update::NmtState { node_id: from, state }
+            }
+        };
+        if update_tx.send(update).is_err() {
+            break;
+        }
+    }
+}
+
 /// Parse a TPDO CAN frame according to the mapping configuration
 fn parse_tpdo_frame(data: &[u8], config: &TpdoConfig) -> Vec<(String, String)> {
     let mut results = Vec::new();
@@ -177,144 +573,844 @@ fn parse_tpdo_frame(data: &[u8], config: &TpdoConfig) -> Vec<(String, String)> {
     results
 }
 
-/// TPDO listener task that receives raw CAN frames and parses them
-async fn tpdo_listener_task(
-    config: TpdoConfig,
-    mut can_frame_rx: tokio::sync::mpsc::UnboundedReceiver<socketcan::CanFrame>,
+/// Perform one console SDO transfer and report it with the frames exchanged
+async fn raw_sdo_task(
+    node_handle: CANopenNodeHandle,
+    response_rx: crate::queue::Receiver<ReceivedFrame>,
+    index: u16,
+    sub_index: u8,
+    data_type: SdoDataType,
+    data: Option<Vec<u8>>,
     update_tx: Sender<Update>,
 ) {
-    println!("TPDO listener started for TPDO {} on COB-ID {:#X}", config.tpdo_number, config.cob_id);
+    let node_id = node_handle.node_id();
+    let write = data.is_some();
+
+    let (request_frame, result) = match data {
+        Some(data) => {
+            let request = SdoWriteRequest { node_id, index, subindex: sub_index, data };
+            match create_sdo_write_frame(&request) {
+                Ok(frame) => {
+                    let result = node_handle.sdo_write(request).await
+                        .map(|()| "OK".to_string())
+                        .map_err(|err| err.to_string());
+                    (Some(frame), result)
+                }
+                Err(err) => (None, Err(err.to_string())),
+            }
+        }
+        None => {
+            let request = SdoRequest { node_id, index, subindex: sub_index, expected_type: data_type };
+            match create_sdo_request_frame(&request) {
+                Ok(frame) => {
+                    let result = node_handle.sdo_read(request).await
+                        .map(|response| response.data.to_string())
+                        .map_err(|err| err.to_string());
+                    (Some(frame), result)
+                }
+                Err(err) => (None, Err(err.to_string())),
+            }
+        }
+    };
+
+    // The manager hands a frame to subscribers before it completes the request
+    let mut response = None;
+    while let Some(received) = response_rx.try_recv() {
+        let bytes = received.frame.data();
+        if bytes.len() >= 4 && u16::from_le_bytes([bytes[1], bytes[2]]) == index && bytes[3] == sub_index {
+            response = Some(bytes.to_vec());
+        }
+    }
 
-    while let Some(frame) = can_frame_rx.recv().await {
-        // Check if this frame matches our TPDO COB-ID
-        let frame_id = match frame.id() {
-            socketcan::Id::Standard(std_id) => std_id.as_raw(),
-            socketcan::Id::Extended(_) => continue, // Skip extended IDs
+    let _ = update_tx.send(Update::RawSdo(RawSdoExchange {
+        timestamp: Local::now(),
+        index,
+        sub_index,
+        write,
+        request: request_frame.map(|frame| frame.data().to_vec()).unwrap_or_default(),
+        response,
+        result,
+    }));
+}
+
+/// Read the objects one after another, reporting progress after each
+async fn snapshot_task(
+    node_handle: CANopenNodeHandle,
+    objects: Vec<(SdoAddress, String, SdoDataType)>,
+    update_tx: Sender<Update>,
+) {
+    let total = objects.len();
+    let mut entries = Vec::with_capacity(total);
+
+    for (done, (address, name, data_type)) in objects.into_iter().enumerate() {
+        let result = node_handle.sdo_read(SdoRequest {
+            node_id: node_handle.node_id(),
+            index: address.index,
+            subindex: address.sub_index,
+            expected_type: data_type.clone(),
+        }).await;
+
+        let (raw, value) = match result {
+            Ok(response) => (response.payload().to_vec(), Ok(response.data.to_string())),
+            Err(err) => (Vec::new(), Err(err.to_string())),
         };
+        entries.push(SnapshotEntry {
+            index: address.index,
+            sub_index: address.sub_index,
+            name,
+            data_type,
+            raw,
+            value,
+        });
+
+        let _ = update_tx.send(Update::SnapshotProgress { done: done + 1, total });
+    }
 
-        if frame_id == config.cob_id {
-            let values = parse_tpdo_frame(frame.data(), &config);
+    println!("Snapshot complete");
+    let _ = update_tx.send(Update::SnapshotComplete(entries));
+}
 
-            let tpdo_data = TpdoData {
-                tpdo_number: config.tpdo_number,
-                timestamp: Local::now(),
-                values,
-            };
+/// Write the entries one after another, reporting progress after each
+async fn restore_task(node_handle: CANopenNodeHandle, entries: Vec<SnapshotEntry>, update_tx: Sender<Update>) {
+    let total = entries.len();
+    let mut outcomes = Vec::with_capacity(total);
+
+    for (done, entry) in entries.into_iter().enumerate() {
+        let result = node_handle.sdo_write(SdoWriteRequest {
+            node_id: node_handle.node_id(),
+            index: entry.index,
+            subindex: entry.sub_index,
+            data: entry.raw,
+        }).await.map_err(|err| err.to_string());
+
+        outcomes.push(RestoreOutcome {
+            index: entry.index,
+            sub_index: entry.sub_index,
+            name: entry.name,
+            result,
+        });
+        let _ = update_tx.send(Update::RestoreProgress { done: done + 1, total });
+    }
+
+    let failed = outcomes.iter().filter(|outcome| outcome.result.is_err()).count();
+    println!("Restore complete: {} written, {} failed", total - failed, failed);
+    let _ = update_tx.send(Update::RestoreComplete(outcomes));
+}
 
-            let _ = update_tx.send(Update::TpdoData(tpdo_data));
+/// Read the expected identity fields and compare them
+async fn check_identity(node_handle: &CANopenNodeHandle, plan: &StartupPlan) -> Result<String, String> {
+    let mut actual = Vec::new();
+    for (sub_index, name, _) in plan.identity.fields() {
+        let response = node_handle.sdo_read(SdoRequest {
+            node_id: node_handle.node_id(),
+            index: startup::IDENTITY_INDEX,
+            subindex: sub_index,
+            expected_type: SdoDataType::UInt32,
+        }).await.map_err(|err| format!("Failed to read {}: {}", name, err))?;
+        if let canopen_common::SdoResponseData::UInt32(value) = response.data {
+            actual.push((sub_index, value));
         }
     }
+    startup::check_identity(&plan.identity, &actual)
+}
 
-    println!("TPDO listener stopped for TPDO {}", config.tpdo_number);
+/// Raw bytes of one object, or the error message of the failed read
+async fn read_bytes(node_handle: &CANopenNodeHandle, index: u16, sub_index: u8) -> Result<Vec<u8>, String> {
+    node_handle.sdo_read(SdoRequest {
+        node_id: node_handle.node_id(),
+        index,
+        subindex: sub_index,
+        expected_type: SdoDataType::OctetString,
+    }).await
+        .map(|response| response.payload().to_vec())
+        .map_err(|err| err.to_string())
 }
 
-fn merge_tpdo_configs(
-    device_configs: Vec<TpdoConfig>,
-    eds_configs: Vec<TpdoConfig>,
-    object_dictionary: &BTreeMap<u16, SdoObject>,
-) -> Vec<TpdoConfig> {
-    let mut merged = Vec::new();
+fn le_u32(data: &[u8]) -> Option<u32> {
+    let mut bytes = [0u8; 4];
+    bytes[..data.len().min(4)].copy_from_slice(&data[..data.len().min(4)]);
+    (!data.is_empty()).then(|| u32::from_le_bytes(bytes))
+}
 
-    for mut device_tpdo in device_configs {
-        let eds_tpdo = eds_configs.iter()
-            .find(|eds| eds.tpdo_number == device_tpdo.tpdo_number);
+/// Watch the node's heartbeats for three producer periods
+async fn check_heartbeat(conn: &CANopenConnection, node_handle: &CANopenNodeHandle) -> CheckOutcome {
+    let producer_ms = match read_bytes(node_handle, startup::HEARTBEAT_PRODUCER_INDEX, 0).await {
+        Ok(data) => le_u32(&data).unwrap_or(0) as u16,
+        Err(err) => return CheckOutcome::Fail(format!("Failed to read producer heartbeat time: {}", err)),
+    };
+    if producer_ms == 0 {
+        return device_check::heartbeat_outcome(0, 0, None);
+    }
+    let cob_id = 0x700 + node_handle.node_id() as u16;
+    let frame_rx = match conn.subscribe_raw_frames(Some(HashSet::from([cob_id]))).await {
+        Ok(frame_rx) => frame_rx,
+        Err(err) => return CheckOutcome::Fail(err.to_string()),
+    };
+
+    let deadline = tokio::time::Instant::now() + Duration::from_millis(producer_ms as u64 * 3);
+    let mut timestamps = Vec::new();
+    while timestamps.len() < 2 {
+        match tokio::time::timeout_at(deadline, frame_rx.recv()).await {
+            // Boot-up messages aren't periodic heartbeats
+            Ok(Some(received)) if received.frame.data().first().is_some_and(|&state| state != 0) => {
+                timestamps.push(received.timestamp);
+            }
+            Ok(Some(_)) => {}
+            Ok(None) | Err(_) => break,
+        }
+    }
+    let interval = match timestamps.as_slice() {
+        [first, second] => second.duration_since(*first).ok(),
+        _ => None,
+    };
+    device_check::heartbeat_outcome(producer_ms, timestamps.len(), interval)
+}
 
-        for mapped_obj in &mut device_tpdo.mapped_objects {
-            if let Some(eds) = eds_tpdo {
-                if let Some(eds_obj) = eds.mapped_objects.iter()
-                    .find(|obj| obj.index == mapped_obj.index && obj.sub_index == mapped_obj.sub_index)
-                {
-                    mapped_obj.name = eds_obj.name.clone();
-                    mapped_obj.data_type = eds_obj.data_type.clone();
-                    continue;
+/// Run every device check in order, reporting each as it finishes
+async fn device_check_task(conn: CANopenConnection, node_handle: CANopenNodeHandle, plan: DeviceCheckPlan, update_tx: Sender<Update>) {
+    for check in DeviceCheck::ALL {
+        let outcome = match check {
+            DeviceCheck::MandatoryObjects => {
+                let mut reads = Vec::new();
+                for (index, sub_index, name) in device_check::MANDATORY_OBJECTS {
+                    reads.push((index, sub_index, name, read_bytes(&node_handle, index, sub_index).await));
                 }
+                device_check::mandatory_outcome(&reads)
             }
-
-            if let Some(obj) = object_dictionary.get(&mapped_obj.index) {
-                if let Some(sub_obj) = obj.sub_objects.get(&mapped_obj.sub_index) {
-                    mapped_obj.name = sub_obj.name.clone();
-                    if let Some(dt) = SdoDataType::from_eds_type(&sub_obj.data_type) {
-                        mapped_obj.data_type = dt;
+            DeviceCheck::Heartbeat => check_heartbeat(&conn, &node_handle).await,
+            DeviceCheck::SdoAborts => {
+                let missing_object = read_bytes(&node_handle, plan.absent_index, 0).await;
+                let missing_sub_index = read_bytes(&node_handle, startup::IDENTITY_INDEX, device_check::ABSENT_SUB_INDEX).await;
+                device_check::abort_outcome(plan.absent_index, &missing_object, &missing_sub_index)
+            }
+            DeviceCheck::Identity => {
+                let mut identity = Vec::new();
+                for (sub_index, _, _) in plan.identity.fields() {
+                    if let Some(value) = read_bytes(&node_handle, startup::IDENTITY_INDEX, sub_index).await.ok().and_then(|data| le_u32(&data)) {
+                        identity.push((sub_index, value));
                     }
                 }
+                let device_type = read_bytes(&node_handle, 0x1000, 0).await.ok().and_then(|data| le_u32(&data));
+                device_check::identity_outcome(&plan, &identity, device_type)
             }
+        };
+        let _ = update_tx.send(Update::DeviceCheck { check, outcome });
+    }
+
+    println!("Device check of node {} finished", node_handle.node_id());
+    let _ = update_tx.send(Update::DeviceCheckFinished);
+}
+
+/// Write the DCF values; any failed write fails the step
+async fn write_dcf(node_handle: &CANopenNodeHandle, entries: &[SnapshotEntry]) -> Result<String, String> {
+    if entries.is_empty() {
+        return Ok("no DCF assigned, skipped".to_string());
+    }
+    let mut failed = Vec::new();
+    for entry in entries {
+        let result = node_handle.sdo_write(SdoWriteRequest {
+            node_id: node_handle.node_id(),
+            index: entry.index,
+            subindex: entry.sub_index,
+            data: entry.raw.clone(),
+        }).await;
+        if let Err(err) = result {
+            failed.push(format!("{:04X}:{:02X} {}", entry.index, entry.sub_index, err));
         }
+    }
+    if failed.is_empty() {
+        Ok(format!("{} values written", entries.len()))
+    } else {
+        Err(format!("{} of {} writes failed: {}", failed.len(), entries.len(), failed.join("; ")))
+    }
+}
 
-        println!("Merged TPDO {} (from device, enriched with EDS)", device_tpdo.tpdo_number);
-        merged.push(device_tpdo);
+/// Make the node produce heartbeats within the consumer time
+async fn set_heartbeat_producer(node_handle: &CANopenNodeHandle, consumer_time: Option<Duration>) -> Result<String, String> {
+    let Some(consumer_time) = consumer_time else {
+        return Ok("no consumer time configured, skipped".to_string());
+    };
+    let response = node_handle.sdo_read(SdoRequest {
+        node_id: node_handle.node_id(),
+        index: startup::HEARTBEAT_PRODUCER_INDEX,
+        subindex: 0,
+        expected_type: SdoDataType::UInt16,
+    }).await.map_err(|err| format!("Failed to read producer heartbeat time: {}", err))?;
+    let current_ms = match response.data {
+        canopen_common::SdoResponseData::UInt16(value) => value,
+        _ => 0,
+    };
+    let Some(producer_ms) = startup::producer_time(current_ms, consumer_time) else {
+        return Ok(format!("producer heartbeat time {} ms kept", current_ms));
+    };
+    node_handle.sdo_write(SdoWriteRequest {
+        node_id: node_handle.node_id(),
+        index: startup::HEARTBEAT_PRODUCER_INDEX,
+        subindex: 0,
+        data: producer_ms.to_le_bytes().to_vec(),
+    }).await.map_err(|err| format!("Failed to write producer heartbeat time: {}", err))?;
+    Ok(format!("producer heartbeat time set to {} ms", producer_ms))
+}
+
+/// Run the startup steps in order, stopping at the first failure
+async fn startup_task(node_handle: CANopenNodeHandle, plan: StartupPlan, update_tx: Sender<Update>) {
+    for step in StartupStep::ALL {
+        let result = match step {
+            StartupStep::Identity => check_identity(&node_handle, &plan).await,
+            StartupStep::Configuration => write_dcf(&node_handle, &plan.dcf_entries).await,
+            StartupStep::Heartbeat => set_heartbeat_producer(&node_handle, plan.consumer_time).await,
+            StartupStep::Start => node_handle.send_nmt(NmtCommand::Start).await
+                .map(|()| "node switched to Operational".to_string())
+                .map_err(|err| err.to_string()),
+        };
+        let failed = result.is_err();
+        let _ = update_tx.send(Update::StartupStep { step, result });
+        if failed {
+            break;
+        }
     }
+    println!("Startup sequence finished");
+    let _ = update_tx.send(Update::StartupFinished);
+}
 
-    for eds_tpdo in eds_configs {
-        if !merged.iter().any(|m| m.tpdo_number == eds_tpdo.tpdo_number) {
-            println!("Adding TPDO {} from EDS (not found on device)", eds_tpdo.tpdo_number);
-            merged.push(eds_tpdo);
+/// Execute the macro steps in order, stopping at the first failure
+async fn macro_task(node_handle: CANopenNodeHandle, ops: Vec<MacroOp>, update_tx: Sender<Update>) {
+    for (step, op) in ops.into_iter().enumerate() {
+        let result = match op {
+            MacroOp::SdoWrite { index, sub_index, data } => node_handle.sdo_write(SdoWriteRequest {
+                node_id: node_handle.node_id(),
+                index,
+                subindex: sub_index,
+                data,
+            }).await,
+            MacroOp::Delay(duration) => {
+                tokio::time::sleep(duration).await;
+                Ok(())
+            }
+            MacroOp::Nmt(command) => node_handle.send_nmt(command).await,
+        }.map_err(|err| err.to_string());
+
+        let failed = result.is_err();
+        let _ = update_tx.send(Update::MacroStepResult { step, result });
+        if failed {
+            break;
         }
     }
+    let _ = update_tx.send(Update::MacroFinished);
+}
 
-    merged
+/// Write the waveform's current value every interval
+async fn stimulus_task(node_handle: CANopenNodeHandle, config: StimulusConfig, update_tx: Sender<Update>) {
+    let start = tokio::time::Instant::now();
+    let mut ticker = tokio::time::interval(Duration::from_millis(config.interval_ms));
+    // A slow write delays the next one instead of sending a burst to catch up
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+        let value = config.waveform.value_at(start.elapsed().as_secs_f64());
+        let result = match stimulus::encode_value(value, &config.data_type) {
+            Ok(data) => node_handle.sdo_write(SdoWriteRequest {
+                node_id: node_handle.node_id(),
+                index: config.address.index,
+                subindex: config.address.sub_index,
+                data,
+            }).await.map_err(|err| err.to_string()),
+            Err(err) => Err(err),
+        };
+
+        let failed = result.is_err();
+        let _ = update_tx.send(Update::StimulusWritten { value, result });
+        if failed {
+            break;
+        }
+    }
+    let _ = update_tx.send(Update::StimulusStopped);
 }
 
-fn parse_tpdos_from_eds(eds_file: &PathBuf, object_dictionary: &BTreeMap<u16, SdoObject>) -> Vec<TpdoConfig> {
-    let mut tpdo_configs = Vec::new();
-    let mut eds_parser = Ini::new();
+/// Act as SYNC producer until stopped or sending fails
+async fn sync_task(conn: CANopenConnection, period_ms: u64, counter_overflow: u8, update_tx: Sender<Update>) {
+    let mut ticker = tokio::time::interval(Duration::from_millis(period_ms));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // The counter runs from 1 to the overflow value, then starts over
+    let mut counter = 1u8;
 
-    if eds_parser.load(eds_file).is_err() {
-        println!("Failed to load EDS file for TPDO parsing");
-        return tpdo_configs;
+    loop {
+        ticker.tick().await;
+        let frame_counter = (counter_overflow >= 2).then_some(counter);
+        if let Err(err) = conn.send_sync(frame_counter).await {
+            let _ = update_tx.send(Update::SyncStopped(err.to_string()));
+            break;
+        }
+        counter = if counter >= counter_overflow { 1 } else { counter + 1 };
     }
+}
 
-    for tpdo_num in 1..=4u8 {
-        let comm_param_index = 0x1800 + (tpdo_num - 1) as u16;
-        let mapping_param_index = 0x1A00 + (tpdo_num - 1) as u16;
+/// Send an RPDO frame every interval until stopped or sending fails
+async fn rpdo_task(node_handle: CANopenNodeHandle, rpdo_number: u8, cob_id: u16, data: Vec<u8>, interval_ms: u64, update_tx: Sender<Update>) {
+    let mut ticker = tokio::time::interval(Duration::from_millis(interval_ms));
+    // A device timing out on missing RPDOs wants a steady rate, not a burst after a stall
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
 
-        let comm_section = format!("{:04X}sub1", comm_param_index);
-        let cob_id = match eds_parser.get(&comm_section, "DefaultValue") {
-            Some(value_str) => {
-                // Parse hex value (format: "0x184" or "$NODEID+0x180")
-                // For "$NODEID+0x180", we ignore $NODEID (treat as 0) and parse the hex part
-                let to_parse = if value_str.contains("$NODEID+") {
-                    value_str.split("+").nth(1).unwrap_or("0")
-                } else if value_str.contains("+") {
-                    // Handle "NODEID+0x180" without $ (some EDS formats)
-                    value_str.split("+").nth(1).unwrap_or("0")
-                } else {
-                    value_str.as_str()
-                };
+    loop {
+        ticker.tick().await;
+        if let Err(err) = node_handle.send_pdo(cob_id, &data).await {
+            let _ = update_tx.send(Update::RpdoStopped { rpdo_number, error: err.to_string() });
+            break;
+        }
+    }
+}
 
-                if let Ok(val) = if to_parse.starts_with("0x") || to_parse.starts_with("0X") {
-                    u32::from_str_radix(&to_parse[2..], 16)
-                } else {
-                    to_parse.parse::<u32>()
-                } {
-                    // Check valid bit (bit 31)
-                    if val & 0x80000000 != 0 {
-                        println!("EDS: TPDO {} is disabled (invalid bit set)", tpdo_num);
-                        continue;
-                    }
-                    (val & 0x7FF) as u16
-                } else {
-                    println!("EDS: Failed to parse COB-ID '{}' for TPDO {}", to_parse, tpdo_num);
-                    continue;
-                }
-            }
-            None => {
-                println!("EDS: No COB-ID found for TPDO {}", tpdo_num);
-                continue;
-            }
-        };
+/// Send a frame every interval until stopped or sending fails
+async fn frame_task(conn: CANopenConnection, frame: CanFrame, interval_ms: u64, update_tx: Sender<Update>) {
+    let mut ticker = tokio::time::interval(Duration::from_millis(interval_ms));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
 
-        let mapping_section_0 = format!("{:04X}sub0", mapping_param_index);
-        let num_mapped = match eds_parser.get(&mapping_section_0, "DefaultValue") {
-            Some(value_str) => value_str.parse::<u8>().unwrap_or(0),
-            None => {
-                println!("EDS: No mapping count found for TPDO {}", tpdo_num);
-                continue;
-            }
-        };
+    loop {
+        ticker.tick().await;
+        if let Err(err) = conn.send_raw_frame(frame).await {
+            let _ = update_tx.send(Update::FrameStopped(err.to_string()));
+            break;
+        }
+    }
+}
 
-        if num_mapped == 0 {
-            println!("EDS: TPDO {} has no mapped objects", tpdo_num);
-            continue;
+/// Probe the range index by index, reporting each responding address
+async fn scan_task(node_handle: CANopenNodeHandle, range: ScanRange, update_tx: Sender<Update>) {
+    let total = range.len();
+    let subs_per_index = range.sub_indices().count();
+    let mut done = 0;
+    let mut found = 0;
+
+    for index in range.indices() {
+        for (probed, sub_index) in range.sub_indices().enumerate() {
+            // Bulk reads, so the user's own requests go first
+            let result = node_handle.sdo_read_with_priority(SdoRequest {
+                node_id: node_handle.node_id(),
+                index,
+                subindex: sub_index,
+                expected_type: SdoDataType::OctetString,
+            }, SdoPriority::Polling).await
+                .map(|response| response.payload().to_vec())
+                .map_err(|err| err.to_string());
+
+            done += 1;
+            match scanner::probe(result) {
+                Probe::Found(data) => {
+                    found += 1;
+                    let _ = update_tx.send(Update::ScanHit(ScanHit { address: SdoAddress { index, sub_index }, data }));
+                }
+                Probe::Missing => {}
+                Probe::ObjectMissing => {
+                    done += subs_per_index - probed - 1;
+                    let _ = update_tx.send(Update::ScanProgress { done, total });
+                    break;
+                }
+            }
+            let _ = update_tx.send(Update::ScanProgress { done, total });
+        }
+    }
+
+    println!("Scan of {:04X}..{:04X} complete: {} objects found", range.first_index, range.last_index, found);
+    let _ = update_tx.send(Update::ScanFinished);
+}
+
+/// Read one address for the object walk; bulk reads, so live reads go first
+async fn probe_address(node_handle: &CANopenNodeHandle, index: u16, sub_index: u8) -> Probe {
+    let result = node_handle.sdo_read_with_priority(SdoRequest {
+        node_id: node_handle.node_id(),
+        index,
+        subindex: sub_index,
+        expected_type: SdoDataType::OctetString,
+    }, SdoPriority::Polling).await
+        .map(|response| response.payload().to_vec())
+        .map_err(|err| err.to_string());
+    scanner::probe(result)
+}
+
+/// Build the object list of a device without EDS by reading every index of the walk ranges
+///
+/// Sub-index 0 tells whether an index exists. A one-byte sub-index 0 followed by an
+/// existing sub-index 1 is a record or array, whose entries up to the value of
+/// sub-index 0 are read as well.
+async fn walk_object_dictionary(node_handle: CANopenNodeHandle, update_tx: Sender<Update>) -> Result<BTreeMap<u16, SdoObject>, String> {
+    // The mandatory device type; without it the node isn't answering at all
+    if let Probe::Missing | Probe::ObjectMissing = probe_address(&node_handle, 0x1000, 0).await {
+        return Err("Node doesn't answer the device type (0x1000) read".to_string());
+    }
+
+    let total: usize = object_walk::WALK_RANGES.iter().map(|range| range.clone().count()).sum();
+    let mut objects = BTreeMap::new();
+    let size = |data: &Result<Vec<u8>, String>| data.as_ref().map_or(0, Vec::len);
+
+    for (done, index) in object_walk::WALK_RANGES.into_iter().flatten().enumerate() {
+        if done % 64 == 0 {
+            let _ = update_tx.send(Update::ObjectWalkProgress { done, total });
+        }
+        let Probe::Found(sub0) = probe_address(&node_handle, index, 0).await else {
+            continue;
+        };
+
+        let mut sub_objects = BTreeMap::new();
+        let highest = match &sub0 {
+            Ok(data) if data.len() == 1 => Some(data[0]),
+            _ => None,
+        };
+        if let Some(highest) = highest.filter(|highest| *highest >= 1) {
+            for sub_index in 1..=highest {
+                match probe_address(&node_handle, index, sub_index).await {
+                    Probe::Found(data) => {
+                        sub_objects.insert(sub_index, object_walk::sub_object(index, sub_index, size(&data), true));
+                    }
+                    // Sub-index 1 missing: sub-index 0 was a plain value
+                    Probe::Missing | Probe::ObjectMissing if sub_index == 1 => break,
+                    Probe::Missing | Probe::ObjectMissing => {}
+                }
+            }
+        }
+        let has_sub_entries = !sub_objects.is_empty();
+        sub_objects.insert(0, object_walk::sub_object(index, 0, size(&sub0), has_sub_entries));
+        objects.insert(index, SdoObject { name: object_walk::object_name(index), sub_objects });
+    }
+
+    let _ = update_tx.send(Update::ObjectWalkProgress { done: total, total });
+    println!("Object walk complete: {} objects found", objects.len());
+    Ok(objects)
+}
+
+/// Walk the object dictionary in the background; the result is left in `walked` for the
+/// command thread, which needs it for PDO discovery, and sent to the UI
+async fn object_walk_task(
+    node_handle: CANopenNodeHandle,
+    update_tx: Sender<Update>,
+    walked: Arc<Mutex<Option<BTreeMap<u16, SdoObject>>>>,
+) {
+    match walk_object_dictionary(node_handle, update_tx.clone()).await {
+        Ok(objects) => {
+            if let Ok(mut walked) = walked.lock() {
+                *walked = Some(objects.clone());
+            }
+            let _ = update_tx.send(Update::SdoList(objects));
+        }
+        Err(err) => {
+            let _ = update_tx.send(Update::ConnectionFailed(format!("Failed to read the object list: {}", err)));
+            let _ = update_tx.send(Update::SdoList(BTreeMap::new()));
+        }
+    }
+}
+
+/// Decode the emergency messages of all nodes
+async fn emcy_task(frame_rx: crate::queue::Receiver<ReceivedFrame>, update_tx: Sender<Update>) {
+    while let Some(received) = frame_rx.recv().await {
+        let frame = &received.frame;
+        let socketcan::Id::Standard(id) = frame.id() else {
+            continue;
+        };
+        if frame.is_remote_frame() {
+            continue;
+        }
+        let Some(node_id) = id.as_raw().checked_sub(EMCY_COB_ID_BASE).and_then(|node_id| u8::try_from(node_id).ok()) else {
+            continue;
+        };
+        match parse_emcy_frame(frame.data()) {
+            Some(message) => {
+                println!("EMCY from node {}: {}", node_id, message);
+                let record = EmergencyRecord {
+                    timestamp: DateTime::<Local>::from(received.timestamp),
+                    node_id,
+                    message,
+                };
+                if update_tx.send(Update::Emergency(record)).is_err() {
+                    break;
+                }
+            }
+            None => println!("Ignoring malformed EMCY frame from node {}", node_id),
+        }
+    }
+}
+
+/// TPDO listener task that receives raw CAN frames and parses them
+async fn tpdo_listener_task(
+    config: TpdoConfig,
+    can_frame_rx: crate::queue::Receiver<ReceivedFrame>,
+    update_tx: Sender<Update>,
+) {
+    println!("TPDO listener started for TPDO {} on COB-ID {:#X}", config.tpdo_number, config.cob_id);
+
+    // Samples are sent in batches so fast PDOs don't produce one update per frame
+    let mut batch: Vec<TpdoData> = Vec::new();
+    let mut flush_at: Option<tokio::time::Instant> = None;
+
+    loop {
+        let received = match flush_at {
+            Some(deadline) => tokio::select! {
+                received = can_frame_rx.recv() => received,
+                _ = tokio::time::sleep_until(deadline) => {
+                    let _ = update_tx.send(Update::TpdoBatch(std::mem::take(&mut batch)));
+                    flush_at = None;
+                    continue;
+                }
+            },
+            None => can_frame_rx.recv().await,
+        };
+        let Some(received) = received else {
+            break;
+        };
+
+        let dropped = can_frame_rx.take_dropped();
+        if dropped > 0 {
+            let _ = update_tx.send(Update::Overflow { dropped });
+        }
+
+        // Check if this frame matches our TPDO COB-ID; remote requests carry no data
+        let frame = &received.frame;
+        if frame.is_remote_frame() {
+            continue;
+        }
+        let frame_id = match frame.id() {
+            socketcan::Id::Standard(std_id) => std_id.as_raw(),
+            socketcan::Id::Extended(_) => continue, // Skip extended IDs
+        };
+
+        if frame_id == config.cob_id {
+            let values = parse_tpdo_frame(frame.data(), &config);
+
+            batch.push(TpdoData {
+                tpdo_number: config.tpdo_number,
+                // Receive time from the kernel (or controller), not when the update was built
+                timestamp: DateTime::<Local>::from(received.timestamp),
+                timestamp_source: received.source,
+                hardware_time: received.hardware_time,
+                values,
+                raw: frame.data().to_vec(),
+            });
+
+            if batch.len() >= TPDO_BATCH_MAX_SAMPLES {
+                let _ = update_tx.send(Update::TpdoBatch(std::mem::take(&mut batch)));
+                flush_at = None;
+            } else if flush_at.is_none() {
+                flush_at = Some(tokio::time::Instant::now() + TPDO_BATCH_INTERVAL);
+            }
+        }
+    }
+
+    if !batch.is_empty() {
+        let _ = update_tx.send(Update::TpdoBatch(batch));
+    }
+    println!("TPDO listener stopped for TPDO {}", config.tpdo_number);
+}
+
+/// Forward every received and transmitted frame to the trace view and the logs in batches
+async fn trace_task(
+    frame_rx: crate::queue::Receiver<ReceivedFrame>,
+    transmitted_rx: crate::queue::Receiver<ReceivedFrame>,
+    update_tx: Sender<Update>,
+) {
+    let mut batch: Vec<TraceEntry> = Vec::new();
+    let mut flush_at: Option<tokio::time::Instant> = None;
+
+    loop {
+        let entry = match flush_at {
+            Some(deadline) => tokio::select! {
+                received = frame_rx.recv() => received.map(|received| TraceEntry::from_received(&received)),
+                sent = transmitted_rx.recv() => sent.map(|sent| TraceEntry::from_transmitted(&sent)),
+                _ = tokio::time::sleep_until(deadline) => {
+                    if update_tx.send(Update::TraceFrames(std::mem::take(&mut batch))).is_err() {
+                        break;
+                    }
+                    flush_at = None;
+                    continue;
+                }
+            },
+            None => tokio::select! {
+                received = frame_rx.recv() => received.map(|received| TraceEntry::from_received(&received)),
+                sent = transmitted_rx.recv() => sent.map(|sent| TraceEntry::from_transmitted(&sent)),
+            },
+        };
+        let Some(entry) = entry else {
+            break;
+        };
+
+        let dropped = frame_rx.take_dropped() + transmitted_rx.take_dropped();
+        if dropped > 0 {
+            let _ = update_tx.send(Update::Overflow { dropped });
+        }
+
+        batch.push(entry);
+        if batch.len() >= TRACE_BATCH_MAX_FRAMES {
+            let _ = update_tx.send(Update::TraceFrames(std::mem::take(&mut batch)));
+            flush_at = None;
+        } else if flush_at.is_none() {
+            flush_at = Some(tokio::time::Instant::now() + TRACE_BATCH_INTERVAL);
+        }
+    }
+}
+
+/// Request a TPDO by RTR and decode the first data frame that comes back
+async fn tpdo_request_task(
+    node_handle: CANopenNodeHandle,
+    config: TpdoConfig,
+    frame_rx: crate::queue::Receiver<ReceivedFrame>,
+    update_tx: Sender<Update>,
+) {
+    let result = async {
+        node_handle.request_pdo(config.cob_id, config.data_length()).await.map_err(|err| err.to_string())?;
+
+        let response = async {
+            while let Some(received) = frame_rx.recv().await {
+                if !received.frame.is_remote_frame() {
+                    return Some(received);
+                }
+            }
+            None
+        };
+        match tokio::time::timeout(TPDO_REQUEST_TIMEOUT, response).await {
+            Ok(Some(received)) => Ok(TpdoData {
+                tpdo_number: config.tpdo_number,
+                timestamp: DateTime::<Local>::from(received.timestamp),
+                timestamp_source: received.source,
+                hardware_time: received.hardware_time,
+                values: parse_tpdo_frame(received.frame.data(), &config),
+                raw: received.frame.data().to_vec(),
+            }),
+            Ok(None) => Err("Connection closed".to_string()),
+            Err(_) => Err(format!("No response within {} ms", TPDO_REQUEST_TIMEOUT.as_millis())),
+        }
+    }.await;
+
+    let _ = update_tx.send(Update::TpdoRequested { tpdo_number: config.tpdo_number, result });
+}
+
+/// Types a TPDO field of `bit_length` bits can be decoded as
+pub fn decodable_types(bit_length: u8) -> &'static [SdoDataType] {
+    match bit_length {
+        8 => &[SdoDataType::UInt8, SdoDataType::Int8],
+        16 => &[SdoDataType::UInt16, SdoDataType::Int16],
+        32 => &[SdoDataType::UInt32, SdoDataType::Int32, SdoDataType::Real32],
+        _ => &[],
+    }
+}
+
+/// Replace the EDS or inferred type of TPDO fields with the one chosen by the user
+///
+/// Overrides that don't fit the field's bit length are ignored.
+pub fn apply_type_overrides(tpdos: &mut [TpdoConfig], overrides: &HashMap<SdoAddress, SdoDataType>) {
+    for obj in tpdos.iter_mut().flat_map(|tpdo| tpdo.mapped_objects.iter_mut()) {
+        let address = SdoAddress { index: obj.index, sub_index: obj.sub_index };
+        if let Some(data_type) = overrides.get(&address).filter(|dt| decodable_types(obj.bit_length).contains(dt)) {
+            obj.data_type = data_type.clone();
+        }
+    }
+}
+
+fn merge_tpdo_configs(
+    device_configs: Vec<TpdoConfig>,
+    eds_configs: Vec<TpdoConfig>,
+    object_dictionary: &BTreeMap<u16, SdoObject>,
+) -> Vec<TpdoConfig> {
+    let mut merged = Vec::new();
+
+    for mut device_tpdo in device_configs {
+        let eds_tpdo = eds_configs.iter()
+            .find(|eds| eds.tpdo_number == device_tpdo.tpdo_number);
+
+        for mapped_obj in &mut device_tpdo.mapped_objects {
+            if let Some(eds) = eds_tpdo {
+                if let Some(eds_obj) = eds.mapped_objects.iter()
+                    .find(|obj| obj.index == mapped_obj.index && obj.sub_index == mapped_obj.sub_index)
+                {
+                    mapped_obj.name = eds_obj.name.clone();
+                    mapped_obj.data_type = eds_obj.data_type.clone();
+                    continue;
+                }
+            }
+
+            if let Some(obj) = object_dictionary.get(&mapped_obj.index) {
+                if let Some(sub_obj) = obj.sub_objects.get(&mapped_obj.sub_index) {
+                    mapped_obj.name = sub_obj.name.clone();
+                    if let Some(dt) = SdoDataType::from_eds_type(&sub_obj.data_type) {
+                        mapped_obj.data_type = dt;
+                    }
+                }
+            }
+        }
+
+        println!("Merged TPDO {} (from device, enriched with EDS)", device_tpdo.tpdo_number);
+        merged.push(device_tpdo);
+    }
+
+    for eds_tpdo in eds_configs {
+        if !merged.iter().any(|m| m.tpdo_number == eds_tpdo.tpdo_number) {
+            println!("Adding TPDO {} from EDS (not found on device)", eds_tpdo.tpdo_number);
+            merged.push(eds_tpdo);
+        }
+    }
+
+    merged
+}
+
+fn parse_tpdos_from_eds(eds_file: &PathBuf, object_dictionary: &BTreeMap<u16, SdoObject>) -> Vec<TpdoConfig> {
+    let mut tpdo_configs = Vec::new();
+    let mut eds_parser = Ini::new();
+
+    if eds_parser.load(eds_file).is_err() {
+        println!("Failed to load EDS file for TPDO parsing");
+        return tpdo_configs;
+    }
+
+    for tpdo_num in 1..=4u8 {
+        let comm_param_index = 0x1800 + (tpdo_num - 1) as u16;
+        let mapping_param_index = 0x1A00 + (tpdo_num - 1) as u16;
+
+        let comm_section = format!("{:04X}sub1", comm_param_index);
+        let (cob_id, rtr_allowed) = match eds_parser.get(&comm_section, "DefaultValue") {
+            Some(value_str) => {
+                // Parse hex value (format: "0x184" or "$NODEID+0x180")
+                // For "$NODEID+0x180", we ignore $NODEID (treat as 0) and parse the hex part
+                let to_parse = if value_str.contains("$NODEID+") {
+                    value_str.split("+").nth(1).unwrap_or("0")
+                } else if value_str.contains("+") {
+                    // Handle "NODEID+0x180" without $ (some EDS formats)
+                    value_str.split("+").nth(1).unwrap_or("0")
+                } else {
+                    value_str.as_str()
+                };
+
+                if let Ok(val) = if to_parse.starts_with("0x") || to_parse.starts_with("0X") {
+                    u32::from_str_radix(&to_parse[2..], 16)
+                } else {
+                    to_parse.parse::<u32>()
+                } {
+                    // Check valid bit (bit 31)
+                    if val & 0x80000000 != 0 {
+                        println!("EDS: TPDO {} is disabled (invalid bit set)", tpdo_num);
+                        continue;
+                    }
+                    // Bit 30 = RTR bit (0 = remote requests allowed)
+                    ((val & 0x7FF) as u16, val & 0x40000000 == 0)
+                } else {
+                    println!("EDS: Failed to parse COB-ID '{}' for TPDO {}", to_parse, tpdo_num);
+                    continue;
+                }
+            }
+            None => {
+                println!("EDS: No COB-ID found for TPDO {}", tpdo_num);
+                continue;
+            }
+        };
+
+        let mapping_section_0 = format!("{:04X}sub0", mapping_param_index);
+        let num_mapped = match eds_parser.get(&mapping_section_0, "DefaultValue") {
+            Some(value_str) => value_str.parse::<u8>().unwrap_or(0),
+            None => {
+                println!("EDS: No mapping count found for TPDO {}", tpdo_num);
+                continue;
+            }
+        };
+
+        if num_mapped == 0 {
+            println!("EDS: TPDO {} has no mapped objects", tpdo_num);
+            continue;
         }
 
         let mut mapped_objects = Vec::new();
@@ -323,8 +1419,8 @@ fn parse_tpdos_from_eds(eds_file: &PathBuf, object_dictionary: &BTreeMap<u16, Sd
             let mapping_value = match eds_parser.get(&mapping_section, "DefaultValue") {
                 Some(value_str) => {
                     // Parse hex value
-                    if let Ok(val) = if value_str.starts_with("0x") {
-                        u32::from_str_radix(&value_str[2..], 16)
+                    if let Ok(val) = if let Some(hex) = value_str.strip_prefix("0x") {
+                        u32::from_str_radix(hex, 16)
                     } else {
                         value_str.parse::<u32>()
                     } {
@@ -346,14 +1442,14 @@ fn parse_tpdos_from_eds(eds_file: &PathBuf, object_dictionary: &BTreeMap<u16, Sd
 
             let (name, data_type) = if let Some(obj) = object_dictionary.get(&obj_index) {
                 if let Some(sub_obj) = obj.sub_objects.get(&obj_subindex) {
-                    let dt = SdoDataType::from_eds_type(&sub_obj.data_type).unwrap_or_else(|| {
+                    let dt = SdoDataType::from_eds_type(&sub_obj.data_type).unwrap_or(
                         match bit_length {
                             8 => SdoDataType::UInt8,
                             16 => SdoDataType::UInt16,
                             32 => SdoDataType::UInt32,
                             _ => SdoDataType::UInt32,
                         }
-                    });
+                    );
                     (sub_obj.name.clone(), dt)
                 } else {
                     (format!("0x{:04X}:{:02X}", obj_index, obj_subindex),
@@ -385,10 +1481,19 @@ fn parse_tpdos_from_eds(eds_file: &PathBuf, object_dictionary: &BTreeMap<u16, Sd
             println!("EDS: Found TPDO {} with COB-ID 0x{:03X} and {} mapped objects",
                      tpdo_num, cob_id, mapped_objects.len());
 
+            let comm_value = |sub: u8| eds_parser.get(&format!("{:04X}sub{}", comm_param_index, sub), "DefaultValue")
+                .and_then(|value_str| match value_str.strip_prefix("0x").or_else(|| value_str.strip_prefix("0X")) {
+                    Some(hex) => u16::from_str_radix(hex, 16).ok(),
+                    None => value_str.parse().ok(),
+                });
+
             tpdo_configs.push(TpdoConfig {
                 tpdo_number: tpdo_num,
                 cob_id,
                 mapped_objects,
+                transmission_type: comm_value(2).and_then(|value| u8::try_from(value).ok()),
+                rtr_allowed,
+                event_timer_ms: comm_value(5),
             });
         }
     }
@@ -402,131 +1507,227 @@ async fn discover_tpdos_from_device(node_handle: &CANopenNodeHandle) -> Vec<Tpdo
 
     // Try to read TPDO 1-4 (standard CANopen supports 4 TPDOs)
     for tpdo_num in 1..=4u8 {
-        let comm_param_index = 0x1800 + (tpdo_num - 1) as u16;
-        let mapping_param_index = 0x1A00 + (tpdo_num - 1) as u16;
+        let Some((cob_id_entry, mut mapped_objects)) = read_pdo_layout(node_handle, "TPDO", tpdo_num, 0x1800, 0x1A00).await else {
+            continue;
+        };
+        let cob_id = (cob_id_entry & 0x7FF) as u16; // Extract 11-bit COB-ID
+        mapped_objects.retain(|obj| {
+            let supported = matches!(obj.bit_length, 8 | 16 | 32);
+            if !supported {
+                println!("TPDO {} mapping {} has unsupported bit length: {}", tpdo_num, obj.name, obj.bit_length);
+            }
+            supported
+        });
+        if mapped_objects.is_empty() {
+            continue;
+        }
+        println!("Discovered TPDO {} with COB-ID 0x{:03X} and {} mapped objects",
+                 tpdo_num, cob_id, mapped_objects.len());
 
-        // Read COB-ID from communication parameters (subindex 1)
-        let cob_id_request = SdoRequest {
+        let transmission_type_request = SdoRequest {
             node_id: node_handle.node_id(),
-            index: comm_param_index,
-            subindex: 1,
-            expected_type: SdoDataType::UInt32,
+            index: 0x1800 + (tpdo_num - 1) as u16,
+            subindex: 2,
+            expected_type: SdoDataType::UInt8,
         };
-
-        let cob_id = match node_handle.sdo_read(cob_id_request).await {
-            Ok(response) => {
-                if let canopen_common::SdoResponseData::UInt32(value) = response.data {
-                    // Bit 31 = valid bit (0 = valid, 1 = invalid)
-                    if value & 0x80000000 != 0 {
-                        println!("TPDO {} is disabled (invalid bit set)", tpdo_num);
-                        continue; // TPDO is disabled
-                    }
-                    (value & 0x7FF) as u16 // Extract 11-bit COB-ID
-                } else {
-                    println!("TPDO {} COB-ID has unexpected type", tpdo_num);
-                    continue;
-                }
-            }
+        let transmission_type = match node_handle.sdo_read(transmission_type_request).await {
+            Ok(response) => match response.data {
+                canopen_common::SdoResponseData::UInt8(value) => Some(value),
+                _ => None,
+            },
             Err(err) => {
-                println!("Failed to read TPDO {} COB-ID: {}", tpdo_num, err);
-                continue;
+                println!("Failed to read TPDO {} transmission type: {}", tpdo_num, err);
+                None
             }
         };
 
-        // Read number of mapped objects (subindex 0)
-        let num_mapped_request = SdoRequest {
+        let event_timer_request = SdoRequest {
             node_id: node_handle.node_id(),
-            index: mapping_param_index,
-            subindex: 0,
-            expected_type: SdoDataType::UInt8,
+            index: 0x1800 + (tpdo_num - 1) as u16,
+            subindex: 5,
+            expected_type: SdoDataType::UInt16,
+        };
+        let event_timer_ms = match node_handle.sdo_read(event_timer_request).await {
+            Ok(response) => match response.data {
+                canopen_common::SdoResponseData::UInt16(value) => Some(value),
+                _ => None,
+            },
+            Err(err) => {
+                println!("Failed to read TPDO {} event timer: {}", tpdo_num, err);
+                None
+            }
         };
 
-        let num_mapped = match node_handle.sdo_read(num_mapped_request).await {
-            Ok(response) => {
-                if let canopen_common::SdoResponseData::UInt8(count) = response.data {
-                    count
-                } else {
-                    println!("TPDO {} mapping count has unexpected type", tpdo_num);
-                    continue;
-                }
-            }
-            Err(err) => {
-                println!("Failed to read TPDO {} mapping count: {}", tpdo_num, err);
-                continue;
-            }
-        };
+        tpdo_configs.push(TpdoConfig {
+            tpdo_number: tpdo_num,
+            cob_id,
+            mapped_objects,
+            transmission_type,
+            // Bit 30 = RTR bit (0 = remote requests allowed)
+            rtr_allowed: cob_id_entry & 0x40000000 == 0,
+            event_timer_ms,
+        });
+    }
 
-        if num_mapped == 0 {
-            println!("TPDO {} has no mapped objects", tpdo_num);
+    tpdo_configs
+}
+
+/// Discover RPDO configurations (0x1400 / 0x1600) from the device, named from the EDS
+async fn discover_rpdos_from_device(
+    node_handle: &CANopenNodeHandle,
+    object_dictionary: &BTreeMap<u16, SdoObject>,
+) -> Vec<RpdoConfig> {
+    let mut rpdo_configs = Vec::new();
+
+    for rpdo_num in 1..=4u8 {
+        let Some((cob_id_entry, mut mapped_objects)) = read_pdo_layout(node_handle, "RPDO", rpdo_num, 0x1400, 0x1600).await else {
             continue;
+        };
+        let cob_id = (cob_id_entry & 0x7FF) as u16;
+        for mapped_obj in &mut mapped_objects {
+            let sub_obj = object_dictionary.get(&mapped_obj.index)
+                .and_then(|obj| obj.sub_objects.get(&mapped_obj.sub_index));
+            if let Some(sub_obj) = sub_obj {
+                mapped_obj.name = sub_obj.name.clone();
+                if let Some(dt) = SdoDataType::from_eds_type(&sub_obj.data_type) {
+                    mapped_obj.data_type = dt;
+                }
+            }
         }
+        println!("Discovered RPDO {} with COB-ID 0x{:03X} and {} mapped objects",
+                 rpdo_num, cob_id, mapped_objects.len());
+
+        rpdo_configs.push(RpdoConfig {
+            rpdo_number: rpdo_num,
+            cob_id,
+            mapped_objects,
+        });
+    }
 
-        // Read each mapped object
-        let mut mapped_objects = Vec::new();
-        for sub in 1..=num_mapped {
-            let mapping_request = SdoRequest {
-                node_id: node_handle.node_id(),
-                index: mapping_param_index,
-                subindex: sub,
-                expected_type: SdoDataType::UInt32,
-            };
+    rpdo_configs
+}
 
-            let mapping_value = match node_handle.sdo_read(mapping_request).await {
-                Ok(response) => {
-                    if let canopen_common::SdoResponseData::UInt32(value) = response.data {
-                        value
-                    } else {
-                        println!("TPDO {} mapping {} has unexpected type", tpdo_num, sub);
-                        continue;
-                    }
-                }
-                Err(err) => {
-                    println!("Failed to read TPDO {} mapping {}: {}", tpdo_num, sub, err);
-                    continue;
+/// Read the COB-ID entry and mapping of one PDO; `None` if it is disabled, empty or unreadable
+///
+/// `comm_base` / `mapping_base` are the parameter indices of PDO 1 (0x1800 / 0x1A00 for
+/// TPDOs, 0x1400 / 0x1600 for RPDOs).
+async fn read_pdo_layout(
+    node_handle: &CANopenNodeHandle,
+    kind: &str,
+    pdo_num: u8,
+    comm_base: u16,
+    mapping_base: u16,
+) -> Option<(u32, Vec<TpdoMappedObject>)> {
+    let comm_param_index = comm_base + (pdo_num - 1) as u16;
+    let mapping_param_index = mapping_base + (pdo_num - 1) as u16;
+
+    // Read COB-ID from communication parameters (subindex 1)
+    let cob_id_request = SdoRequest {
+        node_id: node_handle.node_id(),
+        index: comm_param_index,
+        subindex: 1,
+        expected_type: SdoDataType::UInt32,
+    };
+
+    let cob_id_entry = match node_handle.sdo_read(cob_id_request).await {
+        Ok(response) => {
+            if let canopen_common::SdoResponseData::UInt32(value) = response.data {
+                // Bit 31 = valid bit (0 = valid, 1 = invalid)
+                if value & 0x80000000 != 0 {
+                    println!("{} {} is disabled (invalid bit set)", kind, pdo_num);
+                    return None;
                 }
-            };
+                value
+            } else {
+                println!("{} {} COB-ID has unexpected type", kind, pdo_num);
+                return None;
+            }
+        }
+        Err(err) => {
+            println!("Failed to read {} {} COB-ID: {}", kind, pdo_num, err);
+            return None;
+        }
+    };
+
+    // Read number of mapped objects (subindex 0)
+    let num_mapped_request = SdoRequest {
+        node_id: node_handle.node_id(),
+        index: mapping_param_index,
+        subindex: 0,
+        expected_type: SdoDataType::UInt8,
+    };
+
+    let num_mapped = match node_handle.sdo_read(num_mapped_request).await {
+        Ok(response) => {
+            if let canopen_common::SdoResponseData::UInt8(count) = response.data {
+                count
+            } else {
+                println!("{} {} mapping count has unexpected type", kind, pdo_num);
+                return None;
+            }
+        }
+        Err(err) => {
+            println!("Failed to read {} {} mapping count: {}", kind, pdo_num, err);
+            return None;
+        }
+    };
 
-            // Parse mapping value: bits 31-16 = index, bits 15-8 = subindex, bits 7-0 = bit length
-            let obj_index = ((mapping_value >> 16) & 0xFFFF) as u16;
-            let obj_subindex = ((mapping_value >> 8) & 0xFF) as u8;
-            let bit_length = (mapping_value & 0xFF) as u8;
+    if num_mapped == 0 {
+        println!("{} {} has no mapped objects", kind, pdo_num);
+        return None;
+    }
 
-            // For now, use a generic name - this will be enriched from EDS later
-            let name = format!("0x{:04X}:{:02X}", obj_index, obj_subindex);
+    // Read each mapped object
+    let mut mapped_objects = Vec::new();
+    for sub in 1..=num_mapped {
+        let mapping_request = SdoRequest {
+            node_id: node_handle.node_id(),
+            index: mapping_param_index,
+            subindex: sub,
+            expected_type: SdoDataType::UInt32,
+        };
 
-            // Infer data type from bit length (will be refined with EDS data)
-            let data_type = match bit_length {
-                8 => SdoDataType::UInt8,
-                16 => SdoDataType::UInt16,
-                32 => SdoDataType::UInt32,
-                _ => {
-                    println!("TPDO {} mapping {} has unsupported bit length: {}", tpdo_num, sub, bit_length);
-                    continue;
+        let mapping_value = match node_handle.sdo_read(mapping_request).await {
+            Ok(response) => {
+                if let canopen_common::SdoResponseData::UInt32(value) = response.data {
+                    value
+                } else {
+                    println!("{} {} mapping {} has unexpected type", kind, pdo_num, sub);
+                    return None;
                 }
-            };
+            }
+            Err(err) => {
+                // Without every entry the offsets of the others are unknown
+                println!("Failed to read {} {} mapping {}: {}", kind, pdo_num, sub, err);
+                return None;
+            }
+        };
 
-            mapped_objects.push(TpdoMappedObject {
-                index: obj_index,
-                sub_index: obj_subindex,
-                bit_length,
-                data_type,
-                name,
-            });
-        }
+        // Parse mapping value: bits 31-16 = index, bits 15-8 = subindex, bits 7-0 = bit length
+        let obj_index = ((mapping_value >> 16) & 0xFFFF) as u16;
+        let obj_subindex = ((mapping_value >> 8) & 0xFF) as u8;
+        let bit_length = (mapping_value & 0xFF) as u8;
 
-        if !mapped_objects.is_empty() {
-            println!("Discovered TPDO {} with COB-ID 0x{:03X} and {} mapped objects",
-                     tpdo_num, cob_id, mapped_objects.len());
+        // For now, use a generic name - this will be enriched from EDS later
+        let name = format!("0x{:04X}:{:02X}", obj_index, obj_subindex);
 
-            tpdo_configs.push(TpdoConfig {
-                tpdo_number: tpdo_num,
-                cob_id,
-                mapped_objects,
-            });
-        }
+        // Infer data type from bit length (will be refined with EDS data)
+        let data_type = match bit_length {
+            0..=8 => SdoDataType::UInt8,
+            9..=16 => SdoDataType::UInt16,
+            _ => SdoDataType::UInt32,
+        };
+
+        mapped_objects.push(TpdoMappedObject {
+            index: obj_index,
+            sub_index: obj_subindex,
+            bit_length,
+            data_type,
+            name,
+        });
     }
 
-    tpdo_configs
+    (!mapped_objects.is_empty()).then_some((cob_id_entry, mapped_objects))
 }
 
 /// Extract a value from a byte array at a specific bit offset
@@ -611,37 +1812,130 @@ fn extract_value_from_bytes(data: &[u8], bit_offset: usize, bit_length: u8, data
     }
 }
 
+/// Read the TPDO layout from the device and complete it with names and types from the EDS
+fn discover_tpdos(
+    rt: &tokio::runtime::Runtime,
+    node_handle: Option<&CANopenNodeHandle>,
+    eds_file: Option<&PathBuf>,
+    object_dictionary: &BTreeMap<u16, SdoObject>,
+    type_overrides: &HashMap<SdoAddress, SdoDataType>,
+) -> Vec<TpdoConfig> {
+    println!("Starting TPDO discovery...");
+
+    let device_tpdos = if let Some(handle) = node_handle {
+        rt.block_on(discover_tpdos_from_device(handle))
+    } else {
+        println!("Cannot discover TPDOs from device: not connected");
+        Vec::new()
+    };
+
+    let eds_tpdos = if let Some(path) = eds_file {
+        parse_tpdos_from_eds(path, object_dictionary)
+    } else {
+        println!("No EDS file available for TPDO parsing");
+        Vec::new()
+    };
+
+    let mut merged_tpdos = merge_tpdo_configs(device_tpdos, eds_tpdos, object_dictionary);
+    apply_type_overrides(&mut merged_tpdos, type_overrides);
+
+    println!("TPDO discovery complete - found {} TPDOs", merged_tpdos.len());
+    merged_tpdos
+}
+
+/// Spawn a listener decoding the frames of one TPDO
+fn start_tpdo_listener(
+    rt: &tokio::runtime::Runtime,
+    conn: &CANopenConnection,
+    config: TpdoConfig,
+    update_tx: &Sender<Update>,
+) -> Result<JoinHandle<()>, CANopenError> {
+    // Only this TPDO's frames; the manager adds the COB-ID to the receive filter
+    let frame_rx = rt.block_on(conn.subscribe_raw_frames(Some(HashSet::from([config.cob_id]))))?;
+    Ok(rt.spawn(tpdo_listener_task(config, frame_rx, update_tx.clone())))
+}
+
+/// Name of the SocketCAN interface behind the configured interface, which only
+/// SocketCAN has a controller to query
+fn socketcan_interface(can_interface: &str) -> Result<String, String> {
+    match TransportConfig::from_interface(can_interface) {
+        Ok(TransportConfig::SocketCan { interface }) => Ok(interface),
+        Ok(_) => Err("Controller state is only available for SocketCAN interfaces".to_string()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
 pub fn communication_thread_main(
     command_rx: Receiver<Command>,
     update_tx: Sender<Update>,
     can_interface: String,
     node_id: u8,
-    eds_file: Option<PathBuf>,
+    mut eds_file: Option<PathBuf>,
+    settings: ConnectionSettings,
 ) {
     let rt = tokio::runtime::Runtime::new().unwrap();
     let mut subscription_handles: HashMap<SdoAddress, JoinHandle<()>> = HashMap::new();
+    // Interval and type of every subscription, to restart its polling after a pause
+    let mut subscription_params: HashMap<SdoAddress, (u64, SdoDataType)> = HashMap::new();
     let mut tpdo_handles: HashMap<u8, JoinHandle<()>> = HashMap::new();
-    let mut _health_check_handle: Option<JoinHandle<()>> = None;
+    let mut health_check_handle: Option<JoinHandle<()>> = None;
+    let mut emcy_handle: Option<JoinHandle<()>> = None;
+    let mut event_handle: Option<JoinHandle<()>> = None;
+    let mut snapshot_handle: Option<JoinHandle<()>> = None;
+    let mut restore_handle: Option<JoinHandle<()>> = None;
+    let mut macro_handle: Option<JoinHandle<()>> = None;
+    let mut startup_handle: Option<JoinHandle<()>> = None;
+    let mut device_check_handle: Option<JoinHandle<()>> = None;
+    let mut stimulus_handle: Option<JoinHandle<()>> = None;
+    let mut rpdo_handles: HashMap<u8, JoinHandle<()>> = HashMap::new();
+    let mut tpdo_type_overrides = settings.tpdo_type_overrides.clone();
+    let mut sync_handle: Option<JoinHandle<()>> = None;
+    let mut gateway_handle: Option<JoinHandle<()>> = None;
+    let mut trace_handle: Option<JoinHandle<()>> = None;
+    let mut frame_handle: Option<JoinHandle<()>> = None;
+    let mut scan_handle: Option<JoinHandle<()>> = None;
+    let mut object_walk_handle: Option<JoinHandle<()>> = None;
+    let walked_objects: Arc<Mutex<Option<BTreeMap<u16, SdoObject>>>> = Arc::new(Mutex::new(None));
     let mut connection_handle: Option<CANopenConnection> = None;
     let mut node_handle: Option<CANopenNodeHandle> = None;
     let mut object_dictionary: BTreeMap<u16, SdoObject> = BTreeMap::new();
 
 
     for command in command_rx {
+        // Object list of a finished background walk
+        if let Some(objects) = walked_objects.lock().ok().and_then(|mut walked| walked.take()) {
+            object_dictionary = objects;
+        }
         match command {
             Command::Connect => {
                 match rt.block_on(async {
-                    let conn = CANopenConnection::new(&can_interface, Duration::from_millis(1000)).await?;
-                    let handle = conn.add_node(node_id).await?;
-                    Ok::<(CANopenConnection, CANopenNodeHandle), Box<dyn std::error::Error>>((conn, handle))
+                    let transport_config = TransportConfig::from_interface(&can_interface)?;
+                    let bitrate = transport_config.bitrate().unwrap_or(settings.bus_bitrate);
+                    let conn = CANopenConnection::new(
+                        transport_config,
+                        settings.sdo_timeout,
+                        settings.retry_policy.clone(),
+                    ).await?;
+                    let handle = conn.add_node(node_id, settings.node_sdo_timeout).await?;
+                    conn.set_heartbeat_consumer_time(node_id, settings.heartbeat_consumer_time).await?;
+                    let events = conn.subscribe_events().await?;
+                    // Emergencies of every node, not just the monitored one
+                    let emcy_cob_ids = (1..=127u16).map(|id| EMCY_COB_ID_BASE + id).collect();
+                    let emcy_rx = conn.subscribe_raw_frames(Some(emcy_cob_ids)).await?;
+                    Ok::<_, Box<dyn std::error::Error>>((conn, handle, events, emcy_rx, bitrate))
                 }){
-                    Ok((conn, handle)) => {
+                    Ok((conn, handle, events, emcy_rx, bitrate)) => {
                         connection_handle = Some(conn);
                         node_handle = Some(handle.clone());
 
+                        emcy_handle = Some(rt.spawn(emcy_task(emcy_rx, update_tx.clone())));
+
+                        let heartbeat_supervised = Arc::new(AtomicBool::new(false));
+                        event_handle = Some(rt.spawn(connection_event_task(update_tx.clone(), events, bitrate, node_id, heartbeat_supervised.clone())));
+
                         let update_tx_clone = update_tx.clone();
-                        let health_handle = rt.spawn(health_check_task(update_tx_clone, handle));
-                        _health_check_handle = Some(health_handle);
+                        let health_handle = rt.spawn(health_check_task(update_tx_clone, handle, heartbeat_supervised));
+                        health_check_handle = Some(health_handle);
 
                         println!("Connection established, health check started");
                     },
@@ -661,31 +1955,73 @@ pub fn communication_thread_main(
                             let _ = update_tx.send(Update::SdoList(BTreeMap::new()));
                         }
                     }
+                } else if let Some(ref handle) = node_handle {
+                    // No EDS: ask the device which objects it has, without holding up other commands
+                    println!("No EDS file, reading the object list from node {}", handle.node_id());
+                    if let Some(previous) = object_walk_handle.take() {
+                        previous.abort();
+                    }
+                    object_walk_handle = Some(rt.spawn(object_walk_task(handle.clone(), update_tx.clone(), walked_objects.clone())));
                 } else {
                     let _ = update_tx.send(Update::SdoList(BTreeMap::new()));
                 }
             },
-            Command::DiscoverTpdos => {
-                println!("Starting TPDO discovery...");
-
-                let device_tpdos = if let Some(ref handle) = node_handle {
-                    rt.block_on(discover_tpdos_from_device(handle))
-                } else {
-                    println!("Cannot discover TPDOs from device: not connected");
-                    Vec::new()
-                };
-
-                let eds_tpdos = if let Some(ref path) = eds_file {
-                    parse_tpdos_from_eds(path, &object_dictionary)
-                } else {
-                    println!("No EDS file available for TPDO parsing");
-                    Vec::new()
+            Command::LoadEds(path) => {
+                // The EDS replaces whatever a running walk would find
+                if let Some(handle) = object_walk_handle.take() {
+                    handle.abort();
+                }
+                let objects = match path.as_ref() {
+                    Some(file) => search_for_readable_sdo(file.clone()),
+                    None => Ok(BTreeMap::new()),
                 };
+                match objects {
+                    Ok(objects) => {
+                        println!("Loaded EDS {:?} with {} objects", path, objects.len());
+                        eds_file = path;
+                        object_dictionary = objects.clone();
+                        let _ = update_tx.send(Update::SdoList(objects));
+                    },
+                    Err(err) => {
+                        // Keep working with the previous EDS
+                        let _ = update_tx.send(Update::ConnectionFailed(
+                            format!("Failed to load EDS {:?}: {}", path.unwrap_or_default(), err)
+                        ));
+                    }
+                }
+            },
+            Command::DiscoverTpdos => {
+                let tpdos = discover_tpdos(&rt, node_handle.as_ref(), eds_file.as_ref(), &object_dictionary, &tpdo_type_overrides);
+                let _ = update_tx.send(Update::TpdosDiscovered(tpdos));
+            },
+            Command::RediscoverTpdos => {
+                let tpdos = discover_tpdos(&rt, node_handle.as_ref(), eds_file.as_ref(), &object_dictionary, &tpdo_type_overrides);
+
+                // Running listeners decode with the old mapping; restart them with the new one
+                let running: Vec<u8> = tpdo_handles.keys().copied().collect();
+                for tpdo_num in running {
+                    if let Some(handle) = tpdo_handles.remove(&tpdo_num) {
+                        handle.abort();
+                    }
+                    let Some(config) = tpdos.iter().find(|c| c.tpdo_number == tpdo_num) else {
+                        println!("TPDO {} is no longer configured, listener stopped", tpdo_num);
+                        continue;
+                    };
+                    if let Some(ref conn) = connection_handle {
+                        match start_tpdo_listener(&rt, conn, config.clone(), &update_tx) {
+                            Ok(handle) => {
+                                tpdo_handles.insert(tpdo_num, handle);
+                            }
+                            Err(err) => {
+                                let _ = update_tx.send(Update::ConnectionFailed(
+                                    format!("Failed to subscribe to CAN frames: {}", err)
+                                ));
+                            }
+                        }
+                    }
+                }
 
-                let merged_tpdos = merge_tpdo_configs(device_tpdos, eds_tpdos, &object_dictionary);
-
-                println!("TPDO discovery complete - found {} TPDOs", merged_tpdos.len());
-                let _ = update_tx.send(Update::TpdosDiscovered(merged_tpdos));
+                let _ = update_tx.send(Update::TpdosDiscovered(tpdos));
             },
             Command::Subscribe { address, interval_ms, data_type } => {
                 if let Some(ref handle) = node_handle {
@@ -699,9 +2035,10 @@ pub fn communication_thread_main(
                         interval_ms,
                         update_tx_clone,
                         handle_clone,
-                        data_type,
+                        data_type.clone(),
                     ));
 
+                    subscription_params.insert(address.clone(), (interval_ms, data_type));
                     subscription_handles.insert(address, subscription_handle);
                 } else {
                     let _ = update_tx.send(Update::ConnectionFailed(
@@ -714,16 +2051,152 @@ pub fn communication_thread_main(
                 if let Some(subscription_handle) = subscription_handles.remove(&address) {
                     subscription_handle.abort();
                 }
+                subscription_params.remove(&address);
+            },
+            Command::PauseSubscription { address, paused } => {
+                if paused {
+                    println!("Pausing subscription {:?}", &address);
+                    if let Some(subscription_handle) = subscription_handles.remove(&address) {
+                        subscription_handle.abort();
+                    }
+                } else if let Some(ref handle) = node_handle {
+                    let params = subscription_params.get(&address);
+                    // Nothing to do if it's already polling
+                    if let (Entry::Vacant(slot), Some((interval_ms, data_type))) = (subscription_handles.entry(address.clone()), params) {
+                        println!("Resuming subscription {:?}", &address);
+                        slot.insert(rt.spawn(sdo_polling_task(
+                            address,
+                            *interval_ms,
+                            update_tx.clone(),
+                            handle.clone(),
+                            data_type.clone(),
+                        )));
+                    }
+                } else {
+                    let _ = update_tx.send(Update::ConnectionFailed(
+                        "Not connected to CANopen network".to_string()
+                    ));
+                }
+            },
+            Command::WriteSdo { address, data } => {
+                if let Some(ref handle) = node_handle {
+                    println!("Writing {:02X?} to {:#06X}:{:02X}", data, address.index, address.sub_index);
+
+                    let handle_clone = handle.clone();
+                    let update_tx_clone = update_tx.clone();
+                    rt.spawn(async move {
+                        let result = handle_clone.sdo_write(SdoWriteRequest {
+                            node_id: handle_clone.node_id(),
+                            index: address.index,
+                            subindex: address.sub_index,
+                            data: data.clone(),
+                        }).await.map_err(|err| err.to_string());
+                        let _ = update_tx_clone.send(Update::SdoWriteResult { address, data, result });
+                    });
+                } else {
+                    let _ = update_tx.send(Update::ConnectionFailed(
+                        "Not connected to CANopen network".to_string()
+                    ));
+                }
+            },
+            Command::RawSdo { index, sub_index, data_type, data } => {
+                if let (Some(conn), Some(handle)) = (&connection_handle, &node_handle) {
+                    // Capture the node's SDO responses to show the raw bytes
+                    let response_cob_id = 0x580 + handle.node_id() as u16;
+                    match rt.block_on(conn.subscribe_raw_frames(Some(HashSet::from([response_cob_id])))) {
+                        Ok(response_rx) => {
+                            rt.spawn(raw_sdo_task(
+                                handle.clone(),
+                                response_rx,
+                                index,
+                                sub_index,
+                                data_type,
+                                data,
+                                update_tx.clone(),
+                            ));
+                        }
+                        Err(err) => {
+                            let _ = update_tx.send(Update::ConnectionFailed(
+                                format!("Failed to subscribe to CAN frames: {}", err)
+                            ));
+                        }
+                    }
+                } else {
+                    let _ = update_tx.send(Update::ConnectionFailed(
+                        "Not connected to CANopen network".to_string()
+                    ));
+                }
+            },
+            Command::TakeSnapshot { writable_only } => {
+                if let Some(ref handle) = node_handle {
+                    let objects: Vec<(SdoAddress, String, SdoDataType)> = object_dictionary.iter()
+                        .flat_map(|(index, object)| object.sub_objects.iter()
+                            .filter(move |(_, sub_object)| !writable_only || sub_object.access_type == "rw")
+                            .map(move |(sub_index, sub_object)| {
+                            // Unknown types are kept as raw bytes
+                            let data_type = SdoDataType::from_eds_type(&sub_object.data_type)
+                                .unwrap_or(SdoDataType::OctetString);
+                            (SdoAddress { index: *index, sub_index: *sub_index }, sub_object.name.clone(), data_type)
+                            }))
+                        .collect();
+
+                    println!("Taking snapshot of {} objects", objects.len());
+                    if let Some(previous) = snapshot_handle.take() {
+                        previous.abort();
+                    }
+                    snapshot_handle = Some(rt.spawn(snapshot_task(handle.clone(), objects, update_tx.clone())));
+                } else {
+                    let _ = update_tx.send(Update::ConnectionFailed(
+                        "Not connected to CANopen network".to_string()
+                    ));
+                }
+            },
+            Command::CancelSnapshot => {
+                if let Some(handle) = snapshot_handle.take() {
+                    println!("Snapshot cancelled");
+                    handle.abort();
+                }
+            },
+            Command::RestoreParameters { node_id: target, entries } => {
+                if let (Some(handle), Some(conn)) = (&node_handle, &connection_handle) {
+                    // A clone target is added to the connection next to the monitored node
+                    let target_handle = if target == handle.node_id() {
+                        Ok(handle.clone())
+                    } else {
+                        rt.block_on(conn.add_node(target, settings.node_sdo_timeout))
+                    };
+
+                    match target_handle {
+                        Ok(target_handle) => {
+                            println!("Restoring {} parameters to node {}", entries.len(), target);
+                            if let Some(previous) = restore_handle.take() {
+                                previous.abort();
+                            }
+                            restore_handle = Some(rt.spawn(restore_task(target_handle, entries, update_tx.clone())));
+                        }
+                        Err(err) => {
+                            let _ = update_tx.send(Update::RestoreComplete(entries.into_iter().map(|entry| RestoreOutcome {
+                                index: entry.index,
+                                sub_index: entry.sub_index,
+                                name: entry.name,
+                                result: Err(format!("Failed to add node {}: {}", target, err)),
+                            }).collect()));
+                        }
+                    }
+                } else {
+                    let _ = update_tx.send(Update::ConnectionFailed(
+                        "Not connected to CANopen network".to_string()
+                    ));
+                }
             },
             Command::StartTpdoListener(config) => {
                 if let Some(ref conn) = connection_handle {
                     let tpdo_num = config.tpdo_number;
-                    println!("Starting TPDO listener for TPDO {} on COB-ID {:#X}", tpdo_num, config.cob_id);
+                    let cob_id = config.cob_id;
+                    println!("Starting TPDO listener for TPDO {} on COB-ID {:#X}", tpdo_num, cob_id);
 
-                    match rt.block_on(conn.subscribe_raw_frames()) {
-                        Ok(frame_rx) => {
-                            let update_tx_clone = update_tx.clone();
-                            let tpdo_handle = rt.spawn(tpdo_listener_task(config, frame_rx, update_tx_clone));
+                    match start_tpdo_listener(&rt, conn, config, &update_tx) {
+                        Ok(tpdo_handle) => {
                             tpdo_handles.insert(tpdo_num, tpdo_handle);
                         }
                         Err(err) => {
@@ -738,12 +2211,431 @@ pub fn communication_thread_main(
                     ));
                 }
             },
+            Command::SetTpdoTypeOverrides(overrides) => {
+                tpdo_type_overrides = overrides;
+            },
+            Command::RequestTpdo(config) => {
+                let tpdo_number = config.tpdo_number;
+                if let (Some(conn), Some(handle)) = (&connection_handle, &node_handle) {
+                    println!("Requesting TPDO {} by RTR on COB-ID {:#X}", tpdo_number, config.cob_id);
+                    // Subscribe first so the answer can't arrive before anyone listens
+                    match rt.block_on(conn.subscribe_raw_frames(Some(HashSet::from([config.cob_id])))) {
+                        Ok(frame_rx) => {
+                            rt.spawn(tpdo_request_task(handle.clone(), config, frame_rx, update_tx.clone()));
+                        }
+                        Err(err) => {
+                            let _ = update_tx.send(Update::TpdoRequested {
+                                tpdo_number,
+                                result: Err(format!("Failed to subscribe to CAN frames: {}", err)),
+                            });
+                        }
+                    }
+                } else {
+                    let _ = update_tx.send(Update::TpdoRequested {
+                        tpdo_number,
+                        result: Err("Not connected to CANopen network".to_string()),
+                    });
+                }
+            },
             Command::StopTpdoListener(tpdo_num) => {
                 println!("Stopping TPDO listener for TPDO {}", tpdo_num);
+                // Dropping the listener's receiver releases its receive filter entry
                 if let Some(handle) = tpdo_handles.remove(&tpdo_num) {
                     handle.abort();
                 }
             },
+            Command::Nmt { node_id, command } => {
+                if let Some(ref conn) = connection_handle {
+                    println!("Sending NMT {} to node {}", command, node_id);
+
+                    let result = rt.block_on(conn.send_nmt(node_id, command)).map_err(|err| err.to_string());
+                    let _ = update_tx.send(Update::NmtSent { node_id, command, result });
+                } else {
+                    let _ = update_tx.send(Update::ConnectionFailed(
+                        "Not connected to CANopen network".to_string()
+                    ));
+                }
+            },
+            Command::Lss(command) => {
+                if let Some(ref conn) = connection_handle {
+                    println!("Sending LSS: {}", command);
+
+                    let result = rt.block_on(conn.send_lss(command)).map_err(|err| err.to_string());
+                    let _ = update_tx.send(Update::LssResult { command, result });
+                } else {
+                    let _ = update_tx.send(Update::ConnectionFailed(
+                        "Not connected to CANopen network".to_string()
+                    ));
+                }
+            },
+            Command::RunMacro(ops) => {
+                if let Some(ref handle) = node_handle {
+                    println!("Running macro with {} steps on node {}", ops.len(), handle.node_id());
+                    if let Some(previous) = macro_handle.take() {
+                        previous.abort();
+                    }
+                    macro_handle = Some(rt.spawn(macro_task(handle.clone(), ops, update_tx.clone())));
+                } else {
+                    let _ = update_tx.send(Update::MacroFinished);
+                    let _ = update_tx.send(Update::ConnectionFailed(
+                        "Not connected to CANopen network".to_string()
+                    ));
+                }
+            },
+            Command::StopMacro => {
+                if let Some(handle) = macro_handle.take() {
+                    handle.abort();
+                    println!("Macro stopped");
+                }
+                let _ = update_tx.send(Update::MacroFinished);
+            },
+            Command::RunStartup(plan) => {
+                if let Some(ref handle) = node_handle {
+                    println!("Running startup sequence on node {}", handle.node_id());
+                    if let Some(previous) = startup_handle.take() {
+                        previous.abort();
+                    }
+                    startup_handle = Some(rt.spawn(startup_task(handle.clone(), plan, update_tx.clone())));
+                } else {
+                    let _ = update_tx.send(Update::StartupFinished);
+                    let _ = update_tx.send(Update::ConnectionFailed(
+                        "Not connected to CANopen network".to_string()
+                    ));
+                }
+            },
+            Command::RunDeviceCheck(plan) => {
+                if let (Some(conn), Some(handle)) = (&connection_handle, &node_handle) {
+                    println!("Running device check on node {}", handle.node_id());
+                    let conn = {
+                        let _guard = rt.enter();
+                        conn.clone()
+                    };
+                    if let Some(previous) = device_check_handle.take() {
+                        previous.abort();
+                    }
+                    device_check_handle = Some(rt.spawn(device_check_task(conn, handle.clone(), plan, update_tx.clone())));
+                } else {
+                    let _ = update_tx.send(Update::DeviceCheckFinished);
+                    let _ = update_tx.send(Update::ConnectionFailed(
+                        "Not connected to CANopen network".to_string()
+                    ));
+                }
+            },
+            Command::StartStimulus(config) => {
+                if let Some(ref handle) = node_handle {
+                    println!("Starting stimulus on {:04X}:{:02X} every {} ms",
+                        config.address.index, config.address.sub_index, config.interval_ms);
+                    if let Some(previous) = stimulus_handle.take() {
+                        previous.abort();
+                    }
+                    stimulus_handle = Some(rt.spawn(stimulus_task(handle.clone(), config, update_tx.clone())));
+                } else {
+                    let _ = update_tx.send(Update::StimulusStopped);
+                    let _ = update_tx.send(Update::ConnectionFailed(
+                        "Not connected to CANopen network".to_string()
+                    ));
+                }
+            },
+            Command::StopStimulus => {
+                if let Some(handle) = stimulus_handle.take() {
+                    handle.abort();
+                    println!("Stimulus stopped");
+                }
+                let _ = update_tx.send(Update::StimulusStopped);
+            },
+            Command::StartSync { period_ms, counter_overflow } => {
+                if let Some(ref conn) = connection_handle {
+                    println!("Producing SYNC every {} ms", period_ms);
+                    if let Some(previous) = sync_handle.take() {
+                        previous.abort();
+                    }
+                    // Cloning the connection needs the runtime
+                    let conn = {
+                        let _guard = rt.enter();
+                        conn.clone()
+                    };
+                    sync_handle = Some(rt.spawn(sync_task(conn, period_ms, counter_overflow, update_tx.clone())));
+                } else {
+                    let _ = update_tx.send(Update::SyncStopped("Not connected to CANopen network".to_string()));
+                }
+            },
+            Command::StopSync => {
+                if let Some(handle) = sync_handle.take() {
+                    handle.abort();
+                    println!("SYNC production stopped");
+                }
+            },
+            Command::StartGateway(address) => {
+                if let Some(previous) = gateway_handle.take() {
+                    previous.abort();
+                }
+                match (&connection_handle, &node_handle) {
+                    (Some(conn), Some(handle)) => match rt.block_on(tokio::net::TcpListener::bind(&address)) {
+                        Ok(listener) => {
+                            println!("ASCII gateway listening on {}", address);
+                            let conn = {
+                                let _guard = rt.enter();
+                                conn.clone()
+                            };
+                            gateway_handle = Some(rt.spawn(gateway::serve(listener, conn, handle.clone(), update_tx.clone())));
+                        }
+                        Err(err) => {
+                            let _ = update_tx.send(Update::GatewayStopped(format!("Can't listen on {}: {}", address, err)));
+                        }
+                    },
+                    _ => {
+                        let _ = update_tx.send(Update::GatewayStopped("Not connected to CANopen network".to_string()));
+                    }
+                }
+            },
+            Command::StopGateway => {
+                if let Some(handle) = gateway_handle.take() {
+                    handle.abort();
+                    println!("ASCII gateway stopped");
+                }
+            },
+            Command::StartTrace => {
+                if let Some(previous) = trace_handle.take() {
+                    previous.abort();
+                }
+                match &connection_handle {
+                    Some(conn) => {
+                        let subscribed = rt.block_on(async {
+                            Ok::<_, CANopenError>((conn.subscribe_raw_frames(None).await?, conn.subscribe_transmitted_frames().await?))
+                        });
+                        match subscribed {
+                            Ok((frame_rx, transmitted_rx)) => {
+                                println!("CAN trace started");
+                                trace_handle = Some(rt.spawn(trace_task(frame_rx, transmitted_rx, update_tx.clone())));
+                            }
+                            Err(err) => eprintln!("Failed to start CAN trace: {}", err),
+                        }
+                    }
+                    None => println!("Cannot start CAN trace: not connected"),
+                }
+            },
+            Command::StopTrace => {
+                if let Some(handle) = trace_handle.take() {
+                    handle.abort();
+                    println!("CAN trace stopped");
+                }
+            },
+            Command::DiscoverRpdos => {
+                let rpdos = match node_handle {
+                    Some(ref handle) => rt.block_on(discover_rpdos_from_device(handle, &object_dictionary)),
+                    None => {
+                        println!("Cannot discover RPDOs from device: not connected");
+                        Vec::new()
+                    }
+                };
+                println!("RPDO discovery complete - found {} RPDOs", rpdos.len());
+                let _ = update_tx.send(Update::RpdosDiscovered(rpdos));
+            },
+            Command::SendRpdo { rpdo_number, cob_id, data } => {
+                if let Some(ref handle) = node_handle {
+                    let handle_clone = handle.clone();
+                    let update_tx_clone = update_tx.clone();
+                    rt.spawn(async move {
+                        let result = handle_clone.send_pdo(cob_id, &data).await.map_err(|err| err.to_string());
+                        let _ = update_tx_clone.send(Update::RpdoSent { rpdo_number, result });
+                    });
+                } else {
+                    let _ = update_tx.send(Update::ConnectionFailed(
+                        "Not connected to CANopen network".to_string()
+                    ));
+                }
+            },
+            Command::StartRpdo { rpdo_number, cob_id, data, interval_ms } => {
+                if let Some(ref handle) = node_handle {
+                    println!("Sending RPDO {} on COB-ID {:#X} every {} ms", rpdo_number, cob_id, interval_ms);
+                    let task = rt.spawn(rpdo_task(handle.clone(), rpdo_number, cob_id, data, interval_ms, update_tx.clone()));
+                    if let Some(previous) = rpdo_handles.insert(rpdo_number, task) {
+                        previous.abort();
+                    }
+                } else {
+                    let _ = update_tx.send(Update::RpdoStopped {
+                        rpdo_number,
+                        error: "Not connected to CANopen network".to_string(),
+                    });
+                }
+            },
+            Command::StopRpdo(rpdo_number) => {
+                if let Some(handle) = rpdo_handles.remove(&rpdo_number) {
+                    handle.abort();
+                    println!("RPDO {} transmission stopped", rpdo_number);
+                }
+            },
+            Command::SendFrame(frame) => {
+                if let Some(conn) = &connection_handle {
+                    let conn = {
+                        let _guard = rt.enter();
+                        conn.clone()
+                    };
+                    let update_tx_clone = update_tx.clone();
+                    rt.spawn(async move {
+                        let result = conn.send_raw_frame(frame).await.map_err(|err| err.to_string());
+                        let _ = update_tx_clone.send(Update::FrameSent(result));
+                    });
+                } else {
+                    let _ = update_tx.send(Update::ConnectionFailed(
+                        "Not connected to CANopen network".to_string()
+                    ));
+                }
+            },
+            Command::StartFrame { frame, interval_ms } => {
+                if let Some(previous) = frame_handle.take() {
+                    previous.abort();
+                }
+                if let Some(conn) = &connection_handle {
+                    println!("Sending {:?} every {} ms", frame, interval_ms);
+                    let conn = {
+                        let _guard = rt.enter();
+                        conn.clone()
+                    };
+                    frame_handle = Some(rt.spawn(frame_task(conn, frame, interval_ms, update_tx.clone())));
+                } else {
+                    let _ = update_tx.send(Update::FrameStopped("Not connected to CANopen network".to_string()));
+                }
+            },
+            Command::StopFrame => {
+                if let Some(handle) = frame_handle.take() {
+                    handle.abort();
+                    println!("Cyclic frame transmission stopped");
+                }
+            },
+            Command::QueryControllerState => {
+                let status = socketcan_interface(&can_interface)
+                    .and_then(|interface| netlink::controller_status(&interface).map_err(|err| err.to_string()));
+                let _ = update_tx.send(Update::ControllerState(status));
+            },
+            Command::RestartController => {
+                let result = socketcan_interface(&can_interface)
+                    .and_then(|interface| netlink::restart(&interface).map_err(|err| err.to_string()));
+                match &result {
+                    Ok(()) => println!("Restarted CAN controller of {}", can_interface),
+                    Err(err) => eprintln!("Failed to restart {}: {}", can_interface, err),
+                }
+                let _ = update_tx.send(Update::ControllerRestarted(result));
+            },
+            Command::ScanObjects(range) => {
+                if let Some(ref handle) = node_handle {
+                    println!("Scanning {} addresses from {:04X}:{:02X} to {:04X}:{:02X}",
+                        range.len(), range.first_index, range.first_sub, range.last_index, range.last_sub);
+                    if let Some(previous) = scan_handle.take() {
+                        previous.abort();
+                    }
+                    scan_handle = Some(rt.spawn(scan_task(handle.clone(), range, update_tx.clone())));
+                } else {
+                    let _ = update_tx.send(Update::ScanFinished);
+                    let _ = update_tx.send(Update::ConnectionFailed(
+                        "Not connected to CANopen network".to_string()
+                    ));
+                }
+            },
+            Command::CancelScan => {
+                if let Some(handle) = scan_handle.take() {
+                    handle.abort();
+                    println!("Scan cancelled");
+                }
+                let _ = update_tx.send(Update::ScanFinished);
+            },
+            Command::CancelObjectWalk => {
+                if let Some(handle) = object_walk_handle.take() {
+                    handle.abort();
+                    println!("Object walk cancelled");
+                    let _ = update_tx.send(Update::SdoList(BTreeMap::new()));
+                }
+            },
+            Command::Disconnect => {
+                println!("Disconnecting from {}", can_interface);
+
+                for (_, handle) in subscription_handles.drain() {
+                    handle.abort();
+                }
+                subscription_params.clear();
+                for (_, handle) in rpdo_handles.drain() {
+                    handle.abort();
+                }
+                if let Some(handle) = sync_handle.take() {
+                    handle.abort();
+                }
+                if let Some(handle) = gateway_handle.take() {
+                    handle.abort();
+                }
+                if let Some(handle) = trace_handle.take() {
+                    handle.abort();
+                }
+                if let Some(handle) = frame_handle.take() {
+                    handle.abort();
+                }
+                for (_, handle) in tpdo_handles.drain() {
+                    handle.abort();
+                }
+                if let Some(handle) = snapshot_handle.take() {
+                    handle.abort();
+                }
+                if let Some(handle) = restore_handle.take() {
+                    handle.abort();
+                }
+                if let Some(handle) = macro_handle.take() {
+                    handle.abort();
+                }
+                if let Some(handle) = startup_handle.take() {
+                    handle.abort();
+                }
+                if let Some(handle) = device_check_handle.take() {
+                    handle.abort();
+                }
+                if let Some(handle) = stimulus_handle.take() {
+                    handle.abort();
+                }
+                if let Some(handle) = scan_handle.take() {
+                    handle.abort();
+                }
+                if let Some(handle) = object_walk_handle.take() {
+                    handle.abort();
+                }
+                if let Some(handle) = health_check_handle.take() {
+                    handle.abort();
+                }
+                if let Some(handle) = event_handle.take() {
+                    handle.abort();
+                }
+                if let Some(handle) = emcy_handle.take() {
+                    handle.abort();
+                }
+
+                // Dropping the last handles closes the connection manager and its socket
+                drop(node_handle.take());
+                drop(connection_handle.take());
+                break;
+            },
+            Command::SetHeartbeatConsumerTime { node_id, consumer_time } => {
+                if let Some(ref conn) = connection_handle {
+                    if let Err(err) = rt.block_on(conn.set_heartbeat_consumer_time(node_id, consumer_time)) {
+                        let _ = update_tx.send(Update::ConnectionFailed(
+                            format!("Failed to set heartbeat consumer time: {}", err)
+                        ));
+                    }
+                } else {
+                    let _ = update_tx.send(Update::ConnectionFailed(
+                        "Not connected to CANopen network".to_string()
+                    ));
+                }
+            },
+            Command::SetSdoTimeout { node_id, timeout } => {
+                if let Some(ref conn) = connection_handle {
+                    if let Err(err) = rt.block_on(conn.set_node_timeout(node_id, timeout)) {
+                        let _ = update_tx.send(Update::ConnectionFailed(
+                            format!("Failed to set SDO timeout: {}", err)
+                        ));
+                    }
+                } else {
+                    let _ = update_tx.send(Update::ConnectionFailed(
+                        "Not connected to CANopen network".to_string()
+                    ));
+                }
+            },
         }
     }
 }
@@ -775,6 +2667,11 @@ pub fn search_for_readable_sdo(eds_file: PathBuf) -> Result<BTreeMap<u16, SdoObj
                                     .unwrap_or("")
                                     .to_string();
 
+                                let optional = |key: &str| properties.get(key)
+                                    .and_then(|opt| opt.as_ref())
+                                    .filter(|s| !s.trim().is_empty())
+                                    .cloned();
+
                                 let parent_object = objects.entry(index).or_insert_with(|| {
                                     let object_name = eds_sections.get(index_str)
                                         .and_then(|props| props.get("parametername"))
@@ -788,7 +2685,20 @@ pub fn search_for_readable_sdo(eds_file: PathBuf) -> Result<BTreeMap<u16, SdoObj
                                     }
                                 });
 
-                                let sub_object = SdoSubObject { name: sub_name, data_type };
+                                let sub_object = SdoSubObject {
+                                    name: sub_name,
+                                    data_type,
+                                    access_type: access.clone(),
+                                    low_limit: optional("lowlimit"),
+                                    high_limit: optional("highlimit"),
+                                    default_value: optional("defaultvalue"),
+                                    value_descriptions: eds_value_descriptions(&eds_sections, index_str, sub_index),
+                                    scaling: Scaling::from_eds(
+                                        optional("factor").as_deref(),
+                                        optional("offset").as_deref(),
+                                        optional("unit").as_deref(),
+                                    ),
+                                };
                                 parent_object.sub_objects.insert(sub_index, sub_object);
                             }
                         }
@@ -799,4 +2709,137 @@ pub fn search_for_readable_sdo(eds_file: PathBuf) -> Result<BTreeMap<u16, SdoObj
         return Ok(objects);
     }
     Err("Failed to parse EDS file".to_string())
-}
\ No newline at end of file
+}
+
+/// `[2000sub1ValueDescription]`, or `[2000ValueDescription]` for sub-index 0
+fn eds_value_descriptions(
+    eds_sections: &HashMap<String, HashMap<String, Option<String>>>,
+    index_str: &str,
+    sub_index: u8,
+) -> BTreeMap<i64, String> {
+    let section = eds_sections.get(&format!("{}sub{}valuedescription", index_str, sub_index))
+        .or_else(|| (sub_index == 0).then(|| eds_sections.get(&format!("{}valuedescription", index_str))).flatten());
+    section.map(value_description::parse_eds_section).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use socketcan::StandardId;
+    use std::time::SystemTime;
+
+    #[test]
+    fn test_tpdo_type_overrides() {
+        let object = |sub_index, bit_length| TpdoMappedObject {
+            index: 0x6000,
+            sub_index,
+            bit_length,
+            data_type: SdoDataType::UInt32,
+            name: String::new(),
+        };
+        let mut tpdos = vec![TpdoConfig {
+            tpdo_number: 1,
+            cob_id: 0x181,
+            mapped_objects: vec![object(1, 32), object(2, 32), object(3, 16)],
+            transmission_type: None,
+            rtr_allowed: false,
+            event_timer_ms: None,
+        }];
+        let overrides = HashMap::from([
+            (SdoAddress { index: 0x6000, sub_index: 1 }, SdoDataType::Real32),
+            // Doesn't fit 16 bits
+            (SdoAddress { index: 0x6000, sub_index: 3 }, SdoDataType::Real32),
+        ]);
+
+        apply_type_overrides(&mut tpdos, &overrides);
+        let types: Vec<_> = tpdos[0].mapped_objects.iter().map(|obj| obj.data_type.clone()).collect();
+        assert_eq!(types, vec![SdoDataType::Real32, SdoDataType::UInt32, SdoDataType::UInt32]);
+    }
+
+    #[test]
+    fn test_tpdo_frame_length_and_rtr_only() {
+        let object = |bit_length| TpdoMappedObject {
+            index: 0x6000,
+            sub_index: 1,
+            bit_length,
+            data_type: SdoDataType::UInt8,
+            name: String::new(),
+        };
+        let mut config = TpdoConfig {
+            tpdo_number: 1,
+            cob_id: 0x181,
+            mapped_objects: vec![object(16), object(1), object(1)],
+            transmission_type: Some(0xFE),
+            rtr_allowed: true,
+            event_timer_ms: Some(100),
+        };
+        assert_eq!(config.data_length(), 3);
+        assert!(!config.rtr_only());
+
+        config.transmission_type = Some(0xFD);
+        assert!(config.rtr_only());
+    }
+
+    #[tokio::test]
+    async fn test_tpdo_listener_batches_samples() {
+        let config = TpdoConfig {
+            tpdo_number: 1,
+            cob_id: 0x184,
+            mapped_objects: vec![TpdoMappedObject {
+                index: 0x6000,
+                sub_index: 1,
+                bit_length: 8,
+                data_type: SdoDataType::UInt8,
+                name: "Input".to_string(),
+            }],
+            transmission_type: None,
+            rtr_allowed: false,
+            event_timer_ms: None,
+        };
+        let (frame_tx, frame_rx) = crate::queue::channel(64);
+        let (update_tx, update_rx) = crate::queue::channel(64);
+
+        for value in 0..3u8 {
+            let frame = CanFrame::new(StandardId::new(0x184).unwrap(), &[value]).unwrap();
+            let _ = frame_tx.send(ReceivedFrame { frame, timestamp: SystemTime::now(), source: TimestampSource::Local, hardware_time: None });
+        }
+        drop(frame_tx);
+
+        tpdo_listener_task(config, frame_rx, update_tx).await;
+
+        match update_rx.try_recv() {
+            Some(Update::TpdoBatch(samples)) => {
+                assert_eq!(samples.len(), 3);
+                assert_eq!(samples[2].values[0].1, "2");
+                assert_eq!(samples[2].raw, [2]);
+            }
+            other => panic!("expected one batch, got {:?}", other),
+        }
+        assert!(update_rx.try_recv().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_raw_sdo_read_reports_frames() {
+        let config = TransportConfig::from_interface(crate::canopen::simulation::INTERFACE).unwrap();
+        let conn = CANopenConnection::new(config, Duration::from_millis(500), SdoRetryPolicy::default())
+            .await
+            .unwrap();
+        let handle = conn.add_node(4, None).await.unwrap();
+        let response_rx = conn.subscribe_raw_frames(Some(HashSet::from([0x584]))).await.unwrap();
+        let transmitted_rx = conn.subscribe_transmitted_frames().await.unwrap();
+        let (update_tx, update_rx) = crate::queue::channel(64);
+
+        raw_sdo_task(handle, response_rx, 0x1000, 0, SdoDataType::UInt32, None, update_tx).await;
+
+        match update_rx.try_recv() {
+            Some(Update::RawSdo(exchange)) => {
+                assert_eq!(exchange.request, vec![0x40, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00]);
+                assert_eq!(exchange.response.as_deref().map(|bytes| bytes[0]), Some(0x43));
+                assert_eq!(exchange.result, Ok("401".to_string()));
+            }
+            other => panic!("expected a console exchange, got {:?}", other),
+        }
+        let sent = transmitted_rx.try_recv().expect("the request is reported as transmitted");
+        assert_eq!(sent.frame.data(), [0x40, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    }
+}
@@ -1,14 +1,255 @@
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::time::Duration;
+
+use crate::canopen::{SdoDataType, SdoRetryPolicy};
+use crate::channel_style::{self, ChannelStyle};
+use crate::communication::{SdoAddress, TpdoConfig};
+use crate::dashboard::Dashboard;
+use crate::derived::DerivedChannel;
+use crate::gateway::DEFAULT_GATEWAY_PORT;
+use crate::log_rotation::LogCompression;
+use crate::logging::LogFormat;
+use crate::macros::Macro;
+use crate::overlay::PlotSeries;
+use crate::presets::SubscriptionPreset;
+use crate::scaling::Scaling;
+use crate::startup::ExpectedIdentity;
+
+/// Quick-start entries kept on the first wizard screen
+const MAX_RECENT_SETUPS: usize = 8;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]  // Keep loading config files written before a field existed
 pub struct AppConfig {
     pub can_interface: String,
     pub node_id: u8,
     pub eds_file_path: Option<String>,
     pub enable_logging: bool,
+    pub log_format: LogFormat,
+    /// Record every raw CAN frame to a BLF file next to the log
+    pub log_raw_frames: bool,
+    /// Log every received and transmitted frame (COB-ID, DLC, data) as an event in the log
+    pub log_frame_events: bool,
+    /// When the CSV and JSON Lines logs start a new file, and what happens to the old ones
+    pub log_rotation: LogRotationConfig,
+    /// Recent events kept in memory and written to the log folder when a trigger fires
+    pub black_box: BlackBoxConfig,
     pub log_directory: Option<String>,
+    /// Save plot screenshots and exports to the capture directory instead of asking for a file
+    pub auto_save_captures: bool,
+    pub capture_directory: Option<String>,
+    /// Default SDO timeout for all nodes
+    pub sdo_timeout_ms: u64,
+    /// Per-node SDO timeout overrides, keyed by node ID (TOML keys must be strings)
+    pub node_sdo_timeout_ms: BTreeMap<String, u64>,
+    /// Heartbeat consumer time per node ID; without one a node is silent after three heartbeat periods
+    pub heartbeat_consumer_ms: BTreeMap<String, u64>,
+    pub sdo_retry: SdoRetryConfig,
+    /// History kept per plotted channel unless a subscription sets its own
+    pub plot_buffer: PlotBufferConfig,
+    /// Where the complete sample history of each subscription is kept
+    pub history: HistoryConfig,
+    /// Plot time axes show the local time of day instead of seconds since connecting
+    pub plot_wall_clock: bool,
+    /// Nominal bus bitrate used to estimate bus load (gs_usb interfaces use their own)
+    pub bus_bitrate: u32,
+    /// Bus load in percent above which the status bar warns
+    pub bus_load_warning_percent: f64,
+    /// Period of the SYNC frames produced by the viewer
+    pub sync_period_ms: u64,
+    /// Synchronous counter overflow value of produced SYNCs (0 = no counter byte)
+    pub sync_counter_overflow: u8,
+    /// TCP port of the CiA 309-3 ASCII gateway
+    pub gateway_port: u16,
+    /// Accept gateway clients from this computer only
+    pub gateway_local_only: bool,
+    /// Last used interface / node / EDS combinations, most recent first
+    pub recent_setups: Vec<RecentSetup>,
+    /// EDS file assigned to each node ID, preselected in the connection wizard
+    pub node_eds_files: BTreeMap<String, String>,
+    /// Rediscover TPDOs and restart subscriptions when the monitored node sends a boot-up
+    pub reconfigure_on_boot: bool,
+    /// DCF written to a node after its boot-up and by the startup sequence, keyed by node ID
+    pub boot_dcf_files: BTreeMap<String, String>,
+    /// Run the startup sequence (identity check, DCF, heartbeat, NMT Start) on connect and boot-up
+    pub manager_mode: bool,
+    /// Identity checked by the startup sequence, keyed by node ID
+    pub expected_identities: BTreeMap<String, ExpectedIdentity>,
+    /// Last discovered TPDO layout per node, reused on reconnect until rediscovered
+    pub tpdo_layouts: BTreeMap<String, Vec<TpdoConfig>>,
+    /// Stale timeouts set by the user, by node ID and TPDO number
+    pub tpdo_timeouts_ms: BTreeMap<String, BTreeMap<String, u64>>,
+    /// Names of enumerated values keyed like EDS sections ("6060sub0"), overriding the EDS
+    pub value_descriptions: BTreeMap<String, BTreeMap<String, String>>,
+    /// Engineering-unit scaling per object ("6060sub0"), overriding the EDS
+    pub scaling: BTreeMap<String, Scaling>,
+    /// Line style of each plotted channel ("6060sub0", "tpdo<n>:<field>" for TPDO fields or
+    /// "derived:<name>")
+    pub channel_styles: BTreeMap<String, ChannelStyle>,
+    /// Data type TPDO fields are decoded as, per mapped object ("6060sub0"), overriding the EDS
+    pub tpdo_data_types: BTreeMap<String, SdoDataType>,
+    /// Starred objects ("6060sub0") per EDS file name, or "node<id>" without an EDS
+    pub favorites: BTreeMap<String, Vec<String>>,
+    /// Sequences of SDO writes, delays and NMT commands defined in the macro window
+    pub macros: Vec<Macro>,
+    /// Named groups of SDO and TPDO subscriptions
+    pub presets: Vec<SubscriptionPreset>,
+    /// Channels computed from others, in the order they were defined
+    pub derived_channels: Vec<DerivedChannel>,
+    /// Plot layouts shown as tabs above the plots, with their subscriptions
+    pub dashboards: Vec<Dashboard>,
+    /// Index of the dashboard shown, restored on the next start
+    pub active_dashboard: usize,
+}
+
+/// SDO retry settings (`[sdo_retry]` table in the config file)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SdoRetryConfig {
+    /// Additional attempts after the first one (0 disables retries)
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for every further attempt
+    pub retry_delay_ms: u64,
+    /// Abort codes retried in addition to timeouts (empty = retry on timeout only)
+    pub retry_abort_codes: Vec<u32>,
+}
+
+impl Default for SdoRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            retry_delay_ms: 50,
+            // 0x08000022: data cannot be transferred because of the present device state
+            retry_abort_codes: vec![0x0800_0022],
+        }
+    }
+}
+
+impl SdoRetryConfig {
+    /// Retry policy for the connection manager
+    pub fn policy(&self) -> SdoRetryPolicy {
+        SdoRetryPolicy {
+            max_retries: self.max_retries,
+            retry_delay: Duration::from_millis(self.retry_delay_ms),
+            retry_abort_codes: self.retry_abort_codes.clone(),
+        }
+    }
+}
+
+/// How much history a plot keeps (`[plot_buffer]` table in the config file)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlotBufferConfig {
+    pub max_samples: usize,
+    /// Seconds kept before the latest sample (0 = no time limit)
+    pub window_seconds: f64,
+}
+
+impl Default for PlotBufferConfig {
+    fn default() -> Self {
+        Self {
+            max_samples: 500,
+            window_seconds: 0.0,
+        }
+    }
+}
+
+impl PlotBufferConfig {
+    /// Drop the oldest `[time, value]` points beyond the sample count or time window
+    pub fn trim(&self, plot_data: &mut VecDeque<[f64; 2]>) {
+        while plot_data.len() > self.max_samples.max(1) {
+            plot_data.pop_front();
+        }
+        if self.window_seconds > 0.0 {
+            if let Some(latest) = plot_data.back().map(|point| point[0]) {
+                while plot_data.front().is_some_and(|point| latest - point[0] > self.window_seconds) {
+                    plot_data.pop_front();
+                }
+            }
+        }
+    }
+}
+
+/// Complete sample history of each subscription (`[history]` table in the config file)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Write older samples to a temporary file instead of keeping them all in memory
+    pub spill_to_disk: bool,
+    /// Samples per subscription kept in memory before older ones go to disk
+    pub memory_samples: usize,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            spill_to_disk: true,
+            memory_samples: 100_000,
+        }
+    }
+}
+
+/// Rotation of the CSV and JSON Lines logs (`[log_rotation]` table in the config file)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogRotationConfig {
+    /// Start a new file once the current one reaches this size; 0 = no limit
+    pub max_size_mb: u64,
+    /// Start a new file after this many minutes; 0 = no limit
+    pub max_minutes: u64,
+    /// Compression of finished files
+    pub compression: LogCompression,
+    /// Logs kept in the log folder besides the current one, oldest deleted first; 0 = all
+    pub keep_files: usize,
+}
+
+impl LogRotationConfig {
+    pub fn max_size(&self) -> Option<u64> {
+        (self.max_size_mb > 0).then_some(self.max_size_mb * 1024 * 1024)
+    }
+
+    pub fn max_age(&self) -> Option<Duration> {
+        (self.max_minutes > 0).then(|| Duration::from_secs(self.max_minutes * 60))
+    }
+}
+
+/// Black-box recording (`[black_box]` table in the config file)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlackBoxConfig {
+    pub enabled: bool,
+    /// Seconds of events before a trigger that are written
+    pub pre_seconds: f64,
+    /// Seconds of events after a trigger that are written
+    pub post_seconds: f64,
+    /// Events kept in memory at most, whatever their age
+    pub max_events: usize,
+    pub trigger_on_alarm: bool,
+    pub trigger_on_emergency: bool,
+}
+
+impl Default for BlackBoxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pre_seconds: 30.0,
+            post_seconds: 10.0,
+            max_events: 500_000,
+            trigger_on_alarm: true,
+            trigger_on_emergency: true,
+        }
+    }
+}
+
+/// One connection set up through the wizard (`[[recent_setups]]` in the config file)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentSetup {
+    pub can_interface: String,
+    pub node_id: u8,
+    pub eds_file_path: Option<String>,
 }
 
 impl Default for AppConfig {
@@ -18,7 +259,45 @@ impl Default for AppConfig {
             node_id: 1,
             eds_file_path: None,
             enable_logging: true,
+            log_format: LogFormat::Csv,
+            log_raw_frames: false,
+            log_frame_events: false,
+            log_rotation: LogRotationConfig::default(),
+            black_box: BlackBoxConfig::default(),
             log_directory: None,
+            auto_save_captures: false,
+            capture_directory: None,
+            sdo_timeout_ms: 1000,
+            node_sdo_timeout_ms: BTreeMap::new(),
+            heartbeat_consumer_ms: BTreeMap::new(),
+            sdo_retry: SdoRetryConfig::default(),
+            plot_buffer: PlotBufferConfig::default(),
+            history: HistoryConfig::default(),
+            plot_wall_clock: false,
+            bus_bitrate: 500_000,
+            bus_load_warning_percent: 70.0,
+            sync_period_ms: 100,
+            sync_counter_overflow: 0,
+            gateway_port: DEFAULT_GATEWAY_PORT,
+            gateway_local_only: true,
+            recent_setups: Vec::new(),
+            node_eds_files: BTreeMap::new(),
+            reconfigure_on_boot: true,
+            boot_dcf_files: BTreeMap::new(),
+            manager_mode: false,
+            expected_identities: BTreeMap::new(),
+            tpdo_layouts: BTreeMap::new(),
+            tpdo_timeouts_ms: BTreeMap::new(),
+            value_descriptions: BTreeMap::new(),
+            scaling: BTreeMap::new(),
+            channel_styles: BTreeMap::new(),
+            tpdo_data_types: BTreeMap::new(),
+            favorites: BTreeMap::new(),
+            macros: Vec::new(),
+            presets: Vec::new(),
+            derived_channels: Vec::new(),
+            dashboards: vec![Dashboard::new("Main")],
+            active_dashboard: 0,
         }
     }
 }
@@ -77,6 +356,199 @@ impl AppConfig {
         }
     }
 
+    /// Default SDO timeout
+    pub fn sdo_timeout(&self) -> Duration {
+        Duration::from_millis(self.sdo_timeout_ms)
+    }
+
+    /// SDO timeout override for a node, if one is configured
+    pub fn node_sdo_timeout(&self, node_id: u8) -> Option<Duration> {
+        self.node_sdo_timeout_ms
+            .get(&node_id.to_string())
+            .map(|&ms| Duration::from_millis(ms))
+    }
+
+    /// Set (or with `None`, clear) the SDO timeout override for a node
+    pub fn set_node_sdo_timeout(&mut self, node_id: u8, timeout_ms: Option<u64>) {
+        match timeout_ms {
+            Some(ms) => self.node_sdo_timeout_ms.insert(node_id.to_string(), ms),
+            None => self.node_sdo_timeout_ms.remove(&node_id.to_string()),
+        };
+    }
+
+    /// Longest expected gap between a node's heartbeats, if configured
+    pub fn heartbeat_consumer_time(&self, node_id: u8) -> Option<Duration> {
+        self.heartbeat_consumer_ms
+            .get(&node_id.to_string())
+            .map(|&ms| Duration::from_millis(ms))
+    }
+
+    /// Set (or with `None`, clear) the heartbeat consumer time of a node
+    pub fn set_heartbeat_consumer_time(&mut self, node_id: u8, consumer_ms: Option<u64>) {
+        match consumer_ms {
+            Some(ms) => self.heartbeat_consumer_ms.insert(node_id.to_string(), ms),
+            None => self.heartbeat_consumer_ms.remove(&node_id.to_string()),
+        };
+    }
+
+    /// Move a setup to the top of the recent list, dropping the oldest beyond the limit
+    pub fn add_recent_setup(&mut self, setup: RecentSetup) {
+        self.recent_setups.retain(|recent| *recent != setup);
+        self.recent_setups.insert(0, setup);
+        self.recent_setups.truncate(MAX_RECENT_SETUPS);
+    }
+
+    /// EDS file assigned to a node
+    pub fn node_eds_file(&self, node_id: u8) -> Option<PathBuf> {
+        self.node_eds_files.get(&node_id.to_string()).map(PathBuf::from)
+    }
+
+    /// Assign (or with `None`, unassign) the EDS file of a node
+    pub fn set_node_eds_file(&mut self, node_id: u8, path: Option<&Path>) {
+        match path {
+            Some(path) => self.node_eds_files.insert(node_id.to_string(), path.display().to_string()),
+            None => self.node_eds_files.remove(&node_id.to_string()),
+        };
+    }
+
+    /// DCF replayed to a node after it booted
+    pub fn boot_dcf_file(&self, node_id: u8) -> Option<PathBuf> {
+        self.boot_dcf_files.get(&node_id.to_string()).map(PathBuf::from)
+    }
+
+    /// Assign (or with `None`, unassign) the DCF replayed after boot-up
+    pub fn set_boot_dcf_file(&mut self, node_id: u8, path: Option<&Path>) {
+        match path {
+            Some(path) => self.boot_dcf_files.insert(node_id.to_string(), path.display().to_string()),
+            None => self.boot_dcf_files.remove(&node_id.to_string()),
+        };
+    }
+
+    /// Identity the startup sequence expects from a node
+    pub fn expected_identity(&self, node_id: u8) -> ExpectedIdentity {
+        self.expected_identities.get(&node_id.to_string()).cloned().unwrap_or_default()
+    }
+
+    /// Set the expected identity of a node; one without fields is removed
+    pub fn set_expected_identity(&mut self, node_id: u8, identity: ExpectedIdentity) {
+        if identity == ExpectedIdentity::default() {
+            self.expected_identities.remove(&node_id.to_string());
+        } else {
+            self.expected_identities.insert(node_id.to_string(), identity);
+        }
+    }
+
+    /// Cached TPDO layout of a node
+    pub fn tpdo_layout(&self, node_id: u8) -> Option<&Vec<TpdoConfig>> {
+        self.tpdo_layouts.get(&node_id.to_string())
+    }
+
+    pub fn set_tpdo_layout(&mut self, node_id: u8, tpdos: Vec<TpdoConfig>) {
+        self.tpdo_layouts.insert(node_id.to_string(), tpdos);
+    }
+
+    /// Time without frames after which a TPDO is shown as stale, if set by the user
+    pub fn tpdo_timeout(&self, node_id: u8, tpdo_number: u8) -> Option<Duration> {
+        self.tpdo_timeouts_ms.get(&node_id.to_string())
+            .and_then(|timeouts| timeouts.get(&tpdo_number.to_string()))
+            .map(|ms| Duration::from_millis(*ms))
+    }
+
+    /// Set or clear (`None`, derived from the event timer again) the stale timeout of a TPDO
+    pub fn set_tpdo_timeout(&mut self, node_id: u8, tpdo_number: u8, timeout_ms: Option<u64>) {
+        let timeouts = self.tpdo_timeouts_ms.entry(node_id.to_string()).or_default();
+        match timeout_ms {
+            Some(ms) => timeouts.insert(tpdo_number.to_string(), ms),
+            None => timeouts.remove(&tpdo_number.to_string()),
+        };
+        if timeouts.is_empty() {
+            self.tpdo_timeouts_ms.remove(&node_id.to_string());
+        }
+    }
+
+    /// Scaling set by the user for an object
+    pub fn scaling(&self, address: &SdoAddress) -> Option<&Scaling> {
+        self.scaling.get(&address.eds_key())
+    }
+
+    /// Set or clear (`None`, back to the EDS default) the scaling of an object
+    pub fn set_scaling(&mut self, address: &SdoAddress, scaling: Option<Scaling>) {
+        match scaling {
+            Some(scaling) => self.scaling.insert(address.eds_key(), scaling),
+            None => self.scaling.remove(&address.eds_key()),
+        };
+    }
+
+    pub fn channel_style(&self, series: &PlotSeries) -> Option<ChannelStyle> {
+        self.channel_styles.get(&series.key()).copied()
+    }
+
+    pub fn set_channel_style(&mut self, series: &PlotSeries, style: ChannelStyle) {
+        self.channel_styles.insert(series.key(), style);
+    }
+
+    /// Give a channel plotted for the first time the palette color used least; returns
+    /// false when it already has a style
+    pub fn assign_channel_style(&mut self, series: &PlotSeries) -> bool {
+        if self.channel_styles.contains_key(&series.key()) {
+            return false;
+        }
+        let color = channel_style::next_color(self.channel_styles.values().map(|style| style.color));
+        self.set_channel_style(series, ChannelStyle::new(color));
+        true
+    }
+
+    /// TPDO field types set by the user
+    pub fn tpdo_type_overrides(&self) -> HashMap<SdoAddress, SdoDataType> {
+        self.tpdo_data_types.iter()
+            .filter_map(|(key, data_type)| Some((SdoAddress::from_eds_key(key)?, data_type.clone())))
+            .collect()
+    }
+
+    /// Set or clear (`None`, back to the EDS / inferred type) the type of a TPDO field
+    pub fn set_tpdo_data_type(&mut self, address: &SdoAddress, data_type: Option<SdoDataType>) {
+        match data_type {
+            Some(data_type) => self.tpdo_data_types.insert(address.eds_key(), data_type),
+            None => self.tpdo_data_types.remove(&address.eds_key()),
+        };
+    }
+
+    /// Starred objects of a device, in the order they were added
+    pub fn favorites(&self, device: &str) -> Vec<SdoAddress> {
+        self.favorites.get(device)
+            .map(|keys| keys.iter().filter_map(|key| SdoAddress::from_eds_key(key)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Star an object, or unstar it if it already is
+    pub fn toggle_favorite(&mut self, device: &str, address: &SdoAddress) {
+        let keys = self.favorites.entry(device.to_string()).or_default();
+        let key = address.eds_key();
+        match keys.iter().position(|existing| SdoAddress::from_eds_key(existing).as_ref() == Some(address)) {
+            Some(position) => {
+                keys.remove(position);
+            }
+            None => keys.push(key),
+        }
+        if keys.is_empty() {
+            self.favorites.remove(device);
+        }
+    }
+
+    /// The dashboard shown; `dashboard_mut` makes sure there is one
+    pub fn dashboard(&self) -> &Dashboard {
+        &self.dashboards[self.active_dashboard.min(self.dashboards.len().saturating_sub(1))]
+    }
+
+    /// The dashboard shown; a config without dashboards gets an empty one
+    pub fn dashboard_mut(&mut self) -> &mut Dashboard {
+        if self.dashboards.is_empty() {
+            self.dashboards.push(Dashboard::new("Main"));
+        }
+        self.active_dashboard = self.active_dashboard.min(self.dashboards.len() - 1);
+        &mut self.dashboards[self.active_dashboard]
+    }
+
     /// Get the default log directory path
     pub fn default_log_directory() -> Option<PathBuf> {
         directories::ProjectDirs::from("com", "canopen", "canopen-viewer")
@@ -94,4 +566,75 @@ impl AppConfig {
             Self::default_log_directory()
         }
     }
+
+    /// Directory auto-saved captures go to, using the default if not set
+    pub fn get_capture_directory(&self) -> Option<PathBuf> {
+        match &self.capture_directory {
+            Some(dir) => Some(PathBuf::from(dir)),
+            None => directories::ProjectDirs::from("com", "canopen", "canopen-viewer")
+                .map(|proj_dirs| proj_dirs.data_local_dir().join("captures")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canopen::SdoDataType;
+    use crate::communication::TpdoMappedObject;
+
+    #[test]
+    fn test_tpdo_layout_round_trip() {
+        let mut config = AppConfig::default();
+        config.set_tpdo_layout(4, vec![TpdoConfig {
+            tpdo_number: 1,
+            cob_id: 0x184,
+            mapped_objects: vec![TpdoMappedObject {
+                index: 0x6000,
+                sub_index: 1,
+                bit_length: 16,
+                data_type: SdoDataType::UInt16,
+                name: "Digital inputs".to_string(),
+            }],
+            transmission_type: Some(0xFE),
+            rtr_allowed: true,
+            event_timer_ms: Some(100),
+        }]);
+
+        let loaded: AppConfig = toml::from_str(&toml::to_string_pretty(&config).unwrap()).unwrap();
+        let layout = loaded.tpdo_layout(4).unwrap();
+        assert_eq!(layout[0].cob_id, 0x184);
+        assert!(matches!(layout[0].mapped_objects[0].data_type, SdoDataType::UInt16));
+        assert!(loaded.tpdo_layout(5).is_none());
+    }
+
+    #[test]
+    fn test_recent_setups() {
+        let setup = |node_id| RecentSetup {
+            can_interface: "can0".to_string(),
+            node_id,
+            eds_file_path: Some("drive.eds".to_string()),
+        };
+        let mut config = AppConfig::default();
+        for node_id in 1..=10 {
+            config.add_recent_setup(setup(node_id));
+        }
+        config.add_recent_setup(setup(5));
+
+        let node_ids: Vec<u8> = config.recent_setups.iter().map(|recent| recent.node_id).collect();
+        assert_eq!(node_ids, vec![5, 10, 9, 8, 7, 6, 4, 3]);
+    }
+
+    #[test]
+    fn test_plot_buffer_trim() {
+        let mut plot_data: VecDeque<[f64; 2]> = (0..10).map(|i| [i as f64 * 0.5, i as f64]).collect();
+        PlotBufferConfig { max_samples: 8, window_seconds: 0.0 }.trim(&mut plot_data);
+        assert_eq!(plot_data.front(), Some(&[1.0, 2.0]));
+        PlotBufferConfig { max_samples: 8, window_seconds: 1.5 }.trim(&mut plot_data);
+        assert_eq!(plot_data.len(), 4);
+        assert_eq!(plot_data.front(), Some(&[3.0, 6.0]));
+
+        let config: AppConfig = toml::from_str("[plot_buffer]\nwindow_seconds = 60.0\n").unwrap();
+        assert_eq!(config.plot_buffer, PlotBufferConfig { max_samples: 500, window_seconds: 60.0 });
+    }
 }
@@ -0,0 +1,218 @@
+// controller_panel.rs - State and error counters of the SocketCAN controller
+//
+// When frames stop arriving the first question is whether the controller itself went
+// error-passive or bus-off. The window polls the interface over netlink once a second
+// while it's open and offers the restart that a bus-off controller without automatic
+// recovery (restart-ms 0) needs before it sends or receives again.
+use eframe::egui::{self, Color32, RichText};
+use std::time::{Duration, Instant};
+
+use crate::canopen::netlink::{ControllerState, ControllerStatus};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Transmit error count at which a controller goes bus-off
+const BUS_OFF_THRESHOLD: u16 = 256;
+
+fn state_color(state: ControllerState) -> Color32 {
+    match state {
+        ControllerState::ErrorActive => Color32::from_rgb(0, 200, 0),
+        ControllerState::ErrorWarning | ControllerState::ErrorPassive => Color32::from_rgb(255, 165, 0),
+        ControllerState::BusOff => Color32::from_rgb(255, 100, 100),
+        _ => Color32::GRAY,
+    }
+}
+
+pub struct ControllerPanel {
+    pub open: bool,
+    status: Option<Result<ControllerStatus, String>>,
+    last_query: Option<Instant>,
+    restart_status: Option<Result<String, String>>,
+}
+
+impl ControllerPanel {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            status: None,
+            last_query: None,
+            restart_status: None,
+        }
+    }
+
+    /// Whether to ask for the controller state now; true once per poll interval while open
+    pub fn poll_due(&mut self) -> bool {
+        if !self.open || self.last_query.is_some_and(|last| last.elapsed() < POLL_INTERVAL) {
+            return false;
+        }
+        self.last_query = Some(Instant::now());
+        true
+    }
+
+    pub fn update(&mut self, status: Result<ControllerStatus, String>) {
+        self.status = Some(status);
+    }
+
+    pub fn restarted(&mut self, result: Result<(), String>) {
+        self.restart_status = Some(result.map(|()| "Interface restarted".to_string()));
+        // Show the state after the restart right away
+        self.last_query = None;
+    }
+
+    pub fn reset(&mut self) {
+        self.status = None;
+        self.last_query = None;
+        self.restart_status = None;
+    }
+
+    /// Returns true when the user asked to restart the interface
+    pub fn show(&mut self, ctx: &egui::Context, connected: bool, interface: &str) -> bool {
+        let mut restart = false;
+
+        egui::Window::new(format!("CAN Controller - {}", interface))
+            .id(egui::Id::new("controller_panel"))
+            .open(&mut self.open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if !connected {
+                    ui.label("Not connected.");
+                    return;
+                }
+                let status = match &self.status {
+                    None => {
+                        ui.spinner();
+                        return;
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+                        return;
+                    }
+                    Some(Ok(status)) => status,
+                };
+
+                ui.label(RichText::new(status.state.to_string()).heading().color(state_color(status.state)));
+                egui::Grid::new("controller_state_grid").num_columns(2).striped(true).show(ui, |ui| {
+                    if let Some((tx_errors, rx_errors)) = status.error_counters {
+                        ui.label("TX error counter:");
+                        ui.add(
+                            egui::ProgressBar::new(tx_errors as f32 / BUS_OFF_THRESHOLD as f32)
+                                .text(tx_errors.to_string())
+                                .desired_width(160.0),
+                        );
+                        ui.end_row();
+                        ui.label("RX error counter:");
+                        ui.add(
+                            egui::ProgressBar::new((rx_errors as f32 / 128.0).min(1.0))
+                                .text(rx_errors.to_string())
+                                .desired_width(160.0),
+                        );
+                        ui.end_row();
+                    } else {
+                        ui.label("Error counters:");
+                        ui.weak("not reported by the driver");
+                        ui.end_row();
+                    }
+
+                    if let Some(bitrate) = status.bitrate {
+                        ui.label("Bitrate:");
+                        ui.label(format!("{} kbit/s", bitrate / 1000));
+                        ui.end_row();
+                    }
+                    if let Some(restart_ms) = status.restart_ms {
+                        ui.label("Bus-off recovery:");
+                        if restart_ms == 0 {
+                            ui.label("manual");
+                        } else {
+                            ui.label(format!("automatic after {} ms", restart_ms));
+                        }
+                        ui.end_row();
+                    }
+
+                    if let Some(stats) = status.device_stats {
+                        for (label, count) in [
+                            ("Bus errors:", stats.bus_errors),
+                            ("Error-warning:", stats.error_warning),
+                            ("Error-passive:", stats.error_passive),
+                            ("Bus-off:", stats.bus_off),
+                            ("Arbitration lost:", stats.arbitration_lost),
+                            ("Restarts:", stats.restarts),
+                        ] {
+                            ui.label(label);
+                            ui.monospace(count.to_string());
+                            ui.end_row();
+                        }
+                    }
+                });
+                ui.separator();
+
+                let button = egui::Button::new("🔁 Restart interface");
+                let button = if status.state == ControllerState::BusOff {
+                    button.fill(Color32::from_rgb(120, 40, 40))
+                } else {
+                    button
+                };
+                if ui.add(button).on_hover_text("Recover from bus-off (needs CAP_NET_ADMIN)").clicked() {
+                    self.restart_status = None;
+                    restart = true;
+                }
+                match &self.restart_status {
+                    Some(Ok(message)) => {
+                        ui.colored_label(Color32::from_rgb(0, 200, 0), message);
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+                    }
+                    None => {}
+                }
+            });
+
+        restart
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polls_only_while_open() {
+        let mut panel = ControllerPanel::new();
+        assert!(!panel.poll_due());
+        panel.open = true;
+        assert!(panel.poll_due());
+        // Once per interval
+        assert!(!panel.poll_due());
+    }
+
+    #[test]
+    fn test_restart_polls_again_right_away() {
+        let mut panel = ControllerPanel { open: true, ..ControllerPanel::new() };
+        assert!(panel.poll_due());
+        panel.restarted(Ok(()));
+        assert_eq!(panel.restart_status, Some(Ok("Interface restarted".to_string())));
+        assert!(panel.poll_due());
+
+        panel.restarted(Err("Operation not permitted".to_string()));
+        assert_eq!(panel.restart_status, Some(Err("Operation not permitted".to_string())));
+    }
+
+    #[test]
+    fn test_reset_forgets_the_controller() {
+        let mut panel = ControllerPanel { open: true, ..ControllerPanel::new() };
+        panel.update(Err("No such device".to_string()));
+        panel.restarted(Ok(()));
+        assert!(panel.poll_due());
+        panel.reset();
+        assert!(panel.status.is_none());
+        assert!(panel.restart_status.is_none());
+        assert!(panel.poll_due());
+    }
+
+    #[test]
+    fn test_state_colors() {
+        assert_eq!(state_color(ControllerState::ErrorActive), Color32::from_rgb(0, 200, 0));
+        assert_eq!(state_color(ControllerState::ErrorWarning), state_color(ControllerState::ErrorPassive));
+        assert_eq!(state_color(ControllerState::BusOff), Color32::from_rgb(255, 100, 100));
+        assert_eq!(state_color(ControllerState::Unknown(9)), Color32::GRAY);
+    }
+}
@@ -0,0 +1,243 @@
+// dashboard.rs - Named plot layouts kept across sessions
+//
+// Each dashboard is a tab above the plots with its own plotted signals and overlay plots.
+// It remembers the subscriptions behind its plots (SDO objects with interval and type,
+// TPDO fields), so switching to it, or connecting with it active, subscribes whatever is
+// missing. Subscriptions of other dashboards keep recording in the background. The
+// dashboards and the active one are kept in the config file, along with the widgets and
+// the Y axis settings of each plot.
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::bar_chart::BarChartSettings;
+use crate::communication::SdoAddress;
+use crate::gauge::GaugeSettings;
+use crate::histogram::HistogramSettings;
+use crate::indicator::IndicatorSettings;
+use crate::overlay::{OverlayPlot, PlotSeries, TpdoFieldId};
+use crate::presets::PresetSdo;
+use crate::xy_plot::XyPlotSettings;
+use crate::y_axis::YAxisSettings;
+
+/// Y axis settings of the plot of a single series; overlays keep their own
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlotYAxis {
+    pub series: PlotSeries,
+    pub axis: YAxisSettings,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dashboard {
+    pub name: String,
+    #[serde(default)]
+    pub sdo: Vec<PresetSdo>,
+    #[serde(default)]
+    pub tpdo_fields: Vec<TpdoFieldId>,
+    /// Names of the derived channels plotted
+    #[serde(default)]
+    pub derived: Vec<String>,
+    #[serde(default)]
+    pub overlays: Vec<OverlayPlot<PlotSeries>>,
+    /// Number of the last overlay created, for the next one's title
+    #[serde(default)]
+    pub overlays_created: usize,
+    /// Series shown as a gauge instead of a plot
+    #[serde(default)]
+    pub gauges: Vec<GaugeSettings>,
+    /// LEDs, several per series when they show different bits
+    #[serde(default)]
+    pub indicators: Vec<IndicatorSettings>,
+    #[serde(default)]
+    pub bar_charts: Vec<BarChartSettings>,
+    #[serde(default)]
+    pub xy_plots: Vec<XyPlotSettings>,
+    #[serde(default)]
+    pub histograms: Vec<HistogramSettings>,
+    /// Plots whose Y axis differs from the default
+    #[serde(default)]
+    pub y_axes: Vec<PlotYAxis>,
+}
+
+impl Dashboard {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), sdo: Vec::new(), tpdo_fields: Vec::new(), derived: Vec::new(), overlays: Vec::new(), overlays_created: 0, gauges: Vec::new(), indicators: Vec::new(), bar_charts: Vec::new(), xy_plots: Vec::new(), histograms: Vec::new(), y_axes: Vec::new() }
+    }
+
+    pub fn shows(&self, series: &PlotSeries) -> bool {
+        match series {
+            PlotSeries::Sdo(address) => self.sdo.iter().any(|entry| entry.address() == *address),
+            PlotSeries::Tpdo(field_id) => self.tpdo_fields.contains(field_id),
+            PlotSeries::Derived(name) => self.derived.contains(name),
+        }
+    }
+
+    /// Add or update a polled object
+    pub fn add_sdo(&mut self, entry: PresetSdo) {
+        match self.sdo.iter_mut().find(|existing| existing.address() == entry.address()) {
+            Some(existing) => *existing = entry,
+            None => self.sdo.push(entry),
+        }
+    }
+
+    pub fn add_tpdo_field(&mut self, field_id: TpdoFieldId) {
+        if !self.tpdo_fields.contains(&field_id) {
+            self.tpdo_fields.push(field_id);
+        }
+    }
+
+    pub fn add_derived(&mut self, name: &str) {
+        if !self.derived.iter().any(|existing| existing == name) {
+            self.derived.push(name.to_string());
+        }
+    }
+
+    /// Drop a series whose subscription the user stopped, from its plots as well
+    pub fn remove(&mut self, series: &PlotSeries) {
+        match series {
+            PlotSeries::Sdo(address) => self.sdo.retain(|entry| entry.address() != *address),
+            PlotSeries::Tpdo(field_id) => self.tpdo_fields.retain(|existing| existing != field_id),
+            PlotSeries::Derived(name) => self.derived.retain(|existing| existing != name),
+        }
+        for overlay in &mut self.overlays {
+            overlay.series.retain(|existing| existing != series);
+        }
+        self.overlays.retain(|overlay| !overlay.series.is_empty());
+        self.gauges.retain(|gauge| gauge.series != *series);
+        self.indicators.retain(|indicator| indicator.series != *series);
+        for chart in &mut self.bar_charts {
+            chart.series.retain(|existing| existing != series);
+        }
+        self.bar_charts.retain(|chart| !chart.series.is_empty());
+        self.xy_plots.retain(|xy_plot| !xy_plot.uses(series));
+        self.histograms.retain(|histogram| histogram.series != *series);
+        self.y_axes.retain(|y_axis| y_axis.series != *series);
+    }
+
+    pub fn y_axis(&self, series: &PlotSeries) -> YAxisSettings {
+        self.y_axes.iter().find(|y_axis| y_axis.series == *series).map(|y_axis| y_axis.axis).unwrap_or_default()
+    }
+
+    /// Keep the Y axis settings of a plot; default ones are not stored
+    pub fn set_y_axis(&mut self, series: PlotSeries, axis: YAxisSettings) {
+        self.y_axes.retain(|y_axis| y_axis.series != series);
+        if axis != YAxisSettings::default() {
+            self.y_axes.push(PlotYAxis { series, axis });
+        }
+    }
+
+    /// Whether a gauge, indicators or a bar chart show the series instead of its plot
+    pub fn plot_replaced(&self, series: &PlotSeries) -> bool {
+        self.gauges.iter().any(|gauge| gauge.series == *series)
+            || self.indicators.iter().any(|indicator| indicator.series == *series)
+            || self.bar_charts.iter().any(|chart| chart.contains(series))
+    }
+
+    /// Drop every field of a stopped TPDO
+    pub fn remove_tpdo(&mut self, tpdo_number: u8) {
+        let fields: Vec<TpdoFieldId> = self.tpdo_fields.iter().filter(|field| field.tpdo_number == tpdo_number).cloned().collect();
+        for field_id in fields {
+            self.remove(&PlotSeries::Tpdo(field_id));
+        }
+    }
+
+    pub fn remove_sdo(&mut self, address: &SdoAddress) {
+        self.remove(&PlotSeries::Sdo(address.clone()));
+    }
+}
+
+pub enum DashboardAction {
+    Switch(usize),
+    Add,
+    Delete(usize),
+    /// A dashboard was renamed
+    Renamed,
+}
+
+/// Tab bar of the dashboards; right-click a tab to rename or delete it
+pub fn tabs(ui: &mut egui::Ui, dashboards: &mut [Dashboard], active: usize) -> Option<DashboardAction> {
+    let mut action = None;
+    let deletable = dashboards.len() > 1;
+
+    ui.horizontal_wrapped(|ui| {
+        for (index, dashboard) in dashboards.iter_mut().enumerate() {
+            let response = ui.selectable_label(index == active, &dashboard.name);
+            if response.clicked() && index != active {
+                action = Some(DashboardAction::Switch(index));
+            }
+            response.context_menu(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    let edit = ui.add(egui::TextEdit::singleline(&mut dashboard.name).desired_width(140.0));
+                    if edit.lost_focus() && !dashboard.name.trim().is_empty() {
+                        action = Some(DashboardAction::Renamed);
+                    }
+                });
+                if ui.add_enabled(deletable, egui::Button::new("🗑 Delete dashboard")).clicked() {
+                    action = Some(DashboardAction::Delete(index));
+                    ui.close_menu();
+                }
+            });
+        }
+        if ui.small_button("➕").on_hover_text("New dashboard").clicked() {
+            action = Some(DashboardAction::Add);
+        }
+    });
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use canopen_common::SdoDataType;
+
+    #[test]
+    fn test_dashboard_round_trip() {
+        let address = SdoAddress { index: 0x6064, sub_index: 0 };
+        let field = TpdoFieldId { tpdo_number: 2, field_name: "Velocity".to_string() };
+        let mut dashboard = Dashboard::new("Drive");
+        dashboard.add_sdo(PresetSdo { index: 0x6064, sub_index: 0, interval_ms: 100, data_type: SdoDataType::Int32 });
+        dashboard.add_sdo(PresetSdo { index: 0x6064, sub_index: 0, interval_ms: 50, data_type: SdoDataType::Int32 });
+        dashboard.add_tpdo_field(field.clone());
+        let mut overlay = OverlayPlot::new(1, PlotSeries::Sdo(address.clone()));
+        overlay.add(PlotSeries::Tpdo(field.clone()));
+        dashboard.overlays.push(overlay);
+        dashboard.overlays_created = 1;
+        dashboard.gauges.push(GaugeSettings::new(PlotSeries::Sdo(address.clone()), &[]));
+        dashboard.indicators.push(IndicatorSettings::new(PlotSeries::Sdo(address.clone())));
+        dashboard.bar_charts.push(BarChartSettings::new("Drive", vec![PlotSeries::Sdo(address.clone())]));
+        dashboard.histograms.push(HistogramSettings::new(PlotSeries::Sdo(address.clone())));
+        dashboard.xy_plots.push(XyPlotSettings::new(PlotSeries::Tpdo(field.clone()), PlotSeries::Sdo(address.clone())));
+        let log_axis = YAxisSettings { log: true, ..Default::default() };
+        dashboard.set_y_axis(PlotSeries::Sdo(address.clone()), log_axis);
+        dashboard.set_y_axis(PlotSeries::Tpdo(field.clone()), log_axis);
+        dashboard.set_y_axis(PlotSeries::Tpdo(field.clone()), YAxisSettings::default());
+        assert_eq!(dashboard.y_axes.len(), 1);
+        assert_eq!(dashboard.y_axis(&PlotSeries::Sdo(address.clone())), log_axis);
+        assert_eq!(dashboard.sdo.len(), 1);
+        assert_eq!(dashboard.sdo[0].interval_ms, 50);
+        assert!(dashboard.shows(&PlotSeries::Tpdo(field.clone())));
+        dashboard.derived.push("Power".to_string());
+        assert!(dashboard.shows(&PlotSeries::Derived("Power".to_string())));
+
+        #[derive(Serialize, Deserialize)]
+        struct Dashboards {
+            dashboards: Vec<Dashboard>,
+        }
+        let text = toml::to_string_pretty(&Dashboards { dashboards: vec![dashboard.clone()] }).unwrap();
+        let loaded: Dashboards = toml::from_str(&text).unwrap();
+        assert_eq!(loaded.dashboards, vec![dashboard.clone()]);
+
+        // Stopping the TPDO takes its fields out of the overlay too
+        dashboard.remove_tpdo(2);
+        assert!(dashboard.xy_plots.is_empty());
+        assert!(dashboard.tpdo_fields.is_empty());
+        assert_eq!(dashboard.overlays[0].series, vec![PlotSeries::Sdo(address.clone())]);
+        dashboard.remove_sdo(&address);
+        assert!(dashboard.overlays.is_empty());
+        assert!(dashboard.gauges.is_empty());
+        assert!(dashboard.indicators.is_empty());
+        assert!(dashboard.bar_charts.is_empty());
+        assert!(dashboard.histograms.is_empty());
+        assert!(dashboard.y_axes.is_empty());
+    }
+}
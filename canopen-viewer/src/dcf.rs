@@ -0,0 +1,221 @@
+// dcf.rs - CiA 306 Device Configuration File of the connected node
+//
+// A DCF is the EDS with the device's actual values added as `ParameterValue` keys and
+// a `[DeviceComissioning]` section holding node-ID and bitrate. The EDS text is copied
+// line by line so comments and vendor sections survive; values that can't be
+// expressed (failed reads, octet strings) are left out.
+//
+// Loading goes the other way: the writable objects with a `ParameterValue` become
+// snapshot entries that can be written to the node like a restored backup.
+use canopen_common::SdoDataType;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::default_report::resolve_default;
+use crate::sdo_write::{integer_size, WriteValue};
+use crate::snapshot::SnapshotEntry;
+
+/// Build the DCF text from the EDS text and a snapshot of the node
+pub fn generate(eds: &str, entries: &[SnapshotEntry], node_id: u8, bitrate: u32) -> String {
+    let newline = if eds.contains("\r\n") { "\r\n" } else { "\n" };
+    let values: HashMap<String, String> = entries.iter()
+        .filter_map(|entry| Some((format!("{:04X}SUB{}", entry.index, entry.sub_index), parameter_value(entry)?)))
+        .collect();
+
+    let mut lines: Vec<String> = Vec::new();
+    // Value still to be written into the current section
+    let mut pending: Option<&String> = None;
+    let mut in_commissioning = false;
+
+    for line in eds.lines() {
+        let trimmed = line.trim();
+        if let Some(section) = trimmed.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            flush(&mut lines, pending.take());
+            let section = section.trim().to_ascii_uppercase();
+            // Replaced by the node's own section below
+            in_commissioning = section == "DEVICECOMISSIONING";
+            if in_commissioning {
+                continue;
+            }
+            pending = values.get(&section);
+        } else if in_commissioning
+            || (pending.is_some() && trimmed.to_ascii_lowercase().starts_with("parametervalue"))
+        {
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    flush(&mut lines, pending);
+
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    lines.extend([
+        String::new(),
+        "[DeviceComissioning]".to_string(),
+        format!("NodeID=0x{:02X}", node_id),
+        "NodeName=".to_string(),
+        format!("Baudrate={}", bitrate / 1000),
+        "NetNumber=0".to_string(),
+        "NetworkName=".to_string(),
+        "CANopenManager=0".to_string(),
+        "LSS_SerialNumber=0".to_string(),
+        String::new(),
+    ]);
+    lines.join(newline)
+}
+
+/// Insert `ParameterValue` after the last key of the section that just ended
+fn flush(lines: &mut Vec<String>, value: Option<&String>) {
+    let Some(value) = value else {
+        return;
+    };
+    let position = lines.iter().rposition(|line| !line.trim().is_empty()).map_or(0, |i| i + 1);
+    lines.insert(position, format!("ParameterValue={}", value));
+}
+
+/// Value as written in a DCF; integers in hex like the EDS defaults
+fn parameter_value(entry: &SnapshotEntry) -> Option<String> {
+    let value = entry.value.as_ref().ok()?;
+    match entry.data_type {
+        SdoDataType::OctetString => None,
+        SdoDataType::VisibleString | SdoDataType::Real32 => Some(value.clone()),
+        _ => {
+            let number: i64 = value.parse().ok()?;
+            let bits = integer_size(&entry.data_type).unwrap_or(4) * 8;
+            Some(format!("0x{:X}", (number as u64) & ((1u64 << bits) - 1)))
+        }
+    }
+}
+
+/// Write the DCF of the node, based on its EDS file
+pub fn save(eds_path: &Path, path: &Path, entries: &[SnapshotEntry], node_id: u8, bitrate: u32) -> Result<(), Box<dyn Error>> {
+    let eds = String::from_utf8_lossy(&fs::read(eds_path)?).to_string();
+    fs::write(path, generate(&eds, entries, node_id, bitrate))?;
+    Ok(())
+}
+
+/// Object address of an EDS section name ("1017sub0", or "1017" for sub-index 0)
+fn section_address(section: &str) -> Option<(u16, u8)> {
+    let section = section.to_ascii_uppercase();
+    let (index, sub_index) = match section.split_once("SUB") {
+        Some((index, sub_index)) => (index, u8::from_str_radix(sub_index, 16).ok()?),
+        None => (section.as_str(), 0),
+    };
+    if index.len() != 4 {
+        return None;
+    }
+    Some((u16::from_str_radix(index, 16).ok()?, sub_index))
+}
+
+/// Entry for a DCF object, if it is writable and its value can be encoded
+fn dcf_entry(index: u16, sub_index: u8, keys: &HashMap<String, String>, node_id: u8) -> Option<SnapshotEntry> {
+    let value = keys.get("parametervalue")?.trim();
+    let access = keys.get("accesstype").map_or("rw".to_string(), |access| access.to_ascii_lowercase());
+    if value.is_empty() || !access.contains('w') {
+        return None;
+    }
+    let data_type = SdoDataType::from_eds_type(keys.get("datatype")?.trim())?;
+    let write_value = match data_type {
+        SdoDataType::OctetString => return None,
+        SdoDataType::VisibleString | SdoDataType::Real32 => WriteValue::parse(value, &data_type).ok()?,
+        _ => WriteValue::Integer(resolve_default(value, &data_type, node_id)? as i64),
+    };
+    let raw = write_value.encode(&data_type).ok()?;
+    let value = match write_value {
+        WriteValue::Integer(number) => number.to_string(),
+        _ => value.to_string(),
+    };
+    Some(SnapshotEntry {
+        index,
+        sub_index,
+        name: keys.get("parametername").cloned().unwrap_or_default(),
+        data_type,
+        raw,
+        value: Ok(value),
+    })
+}
+
+/// Writable objects of a DCF with their `ParameterValue`, $NODEID resolved for `node_id`
+pub fn parse(dcf: &str, node_id: u8) -> Vec<SnapshotEntry> {
+    let mut entries = Vec::new();
+    let mut section: Option<(u16, u8)> = None;
+    let mut keys: HashMap<String, String> = HashMap::new();
+
+    for line in dcf.lines().map(str::trim).chain(["[]"]) {
+        if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            if let Some((index, sub_index)) = section {
+                entries.extend(dcf_entry(index, sub_index, &keys, node_id));
+            }
+            section = section_address(name.trim());
+            keys.clear();
+        } else if let Some((key, value)) = line.split_once('=') {
+            keys.insert(key.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+    entries
+}
+
+/// Read the values to write to the node from a DCF file
+pub fn load(path: &Path, node_id: u8) -> Result<Vec<SnapshotEntry>, Box<dyn Error>> {
+    let dcf = String::from_utf8_lossy(&fs::read(path)?).to_string();
+    let entries = parse(&dcf, node_id);
+    if entries.is_empty() {
+        return Err("DCF has no writable objects with a ParameterValue".into());
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_dcf() {
+        let eds = "[FileInfo]\r\nFileName=drive.eds\r\n\r\n[1017sub0]\r\nParameterName=Producer heartbeat time\r\nDataType=0x0006\r\nDefaultValue=0\r\nParameterValue=100\r\n\r\n[2000sub1]\r\nDataType=0x0003\r\n\r\n[2001sub0]\r\nDataType=0x000A\r\n\r\n[DeviceComissioning]\r\nNodeID=1\r\n";
+        let entry = |index, sub_index, data_type, raw: Vec<u8>, value: &str| SnapshotEntry {
+            index,
+            sub_index,
+            name: String::new(),
+            data_type,
+            raw,
+            value: Ok(value.to_string()),
+        };
+        let entries = vec![
+            entry(0x1017, 0, SdoDataType::UInt16, vec![0xE8, 0x03], "1000"),
+            entry(0x2000, 1, SdoDataType::Int16, vec![0xFE, 0xFF], "-2"),
+            entry(0x2001, 0, SdoDataType::OctetString, vec![1, 2], "[01, 02]"),
+        ];
+
+        let dcf = generate(eds, &entries, 5, 250_000);
+        assert_eq!(dcf, "[FileInfo]\r\nFileName=drive.eds\r\n\r\n\
+            [1017sub0]\r\nParameterName=Producer heartbeat time\r\nDataType=0x0006\r\nDefaultValue=0\r\nParameterValue=0x3E8\r\n\r\n\
+            [2000sub1]\r\nDataType=0x0003\r\nParameterValue=0xFFFE\r\n\r\n\
+            [2001sub0]\r\nDataType=0x000A\r\n\r\n\
+            [DeviceComissioning]\r\nNodeID=0x05\r\nNodeName=\r\nBaudrate=250\r\nNetNumber=0\r\nNetworkName=\r\nCANopenManager=0\r\nLSS_SerialNumber=0\r\n");
+    }
+
+    #[test]
+    fn test_parse_dcf() {
+        let dcf = "[1000]\r\nDataType=0x0007\r\nAccessType=ro\r\nParameterValue=0x191\r\n\r\n\
+            [1017sub0]\r\nParameterName=Producer heartbeat time\r\nDataType=0x0006\r\nAccessType=rw\r\nParameterValue=0x3E8\r\n\r\n\
+            [1800sub1]\r\nDataType=0x0007\r\nAccessType=rw\r\nDefaultValue=$NODEID+0x180\r\nParameterValue=$NODEID+0x180\r\n\r\n\
+            [2000sub1]\r\nDataType=0x0003\r\nAccessType=rw\r\nParameterValue=0xFFFE\r\n\r\n\
+            [2001sub0]\r\nDataType=0x0003\r\nAccessType=rw\r\n\r\n\
+            [DeviceComissioning]\r\nNodeID=0x05\r\n";
+
+        let entries = parse(dcf, 5);
+        let summary: Vec<(u16, u8, Vec<u8>, String)> = entries.into_iter()
+            .map(|entry| (entry.index, entry.sub_index, entry.raw, entry.value.unwrap()))
+            .collect();
+        assert_eq!(summary, vec![
+            (0x1017, 0, vec![0xE8, 0x03], "1000".to_string()),
+            (0x1800, 1, vec![0x85, 0x01, 0x00, 0x00], "389".to_string()),
+            (0x2000, 1, vec![0xFE, 0xFF], "-2".to_string()),
+        ]);
+        assert_eq!(section_address("1A00sub1F"), Some((0x1A00, 0x1F)));
+        assert_eq!(section_address("FileInfo"), None);
+    }
+}
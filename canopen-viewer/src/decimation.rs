@@ -0,0 +1,93 @@
+// decimation.rs - Min/max envelope of long series for drawing
+//
+// A plot is a few hundred pixels wide, so drawing tens of thousands of samples each frame
+// only costs time. The samples are split into one bucket per pixel column and each bucket
+// contributes its minimum and maximum in time order, which keeps spikes and the envelope
+// of noisy signals exactly as they would look with every point drawn. Once the user zooms
+// or pans, only the samples in view are decimated, so details come back when zooming in.
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// Index and point of the minimum and of the maximum of a bucket
+type Extremes = ((usize, [f64; 2]), (usize, [f64; 2]));
+
+/// At most about two points per bucket of `points`, which are ordered by time
+pub fn min_max<'a>(points: impl ExactSizeIterator<Item = &'a [f64; 2]>, buckets: usize) -> Vec<[f64; 2]> {
+    let len = points.len();
+    if buckets == 0 || len <= 2 * buckets {
+        return points.copied().collect();
+    }
+
+    let bucket_size = len.div_ceil(buckets);
+    let mut decimated = Vec::with_capacity(2 * buckets + 1);
+    let mut extremes: Option<Extremes> = None;
+    let mut last = None;
+
+    let flush = |decimated: &mut Vec<[f64; 2]>, extremes: Option<Extremes>| {
+        if let Some(((min_index, min), (max_index, max))) = extremes {
+            match min_index.cmp(&max_index) {
+                std::cmp::Ordering::Less => decimated.extend([min, max]),
+                std::cmp::Ordering::Greater => decimated.extend([max, min]),
+                std::cmp::Ordering::Equal => decimated.push(min),
+            }
+        }
+    };
+
+    for (index, point) in points.enumerate() {
+        if index % bucket_size == 0 {
+            flush(&mut decimated, extremes.take());
+        }
+        extremes = Some(match extremes {
+            None => ((index, *point), (index, *point)),
+            Some((min, max)) => (
+                if point[1] < min.1[1] { (index, *point) } else { min },
+                if point[1] > max.1[1] { (index, *point) } else { max },
+            ),
+        });
+        last = Some(*point);
+    }
+    flush(&mut decimated, extremes);
+
+    // The line ends at the latest sample
+    if let Some(last) = last {
+        if decimated.last() != Some(&last) {
+            decimated.push(last);
+        }
+    }
+    decimated
+}
+
+/// Indices of the samples between `min_x` and `max_x`, with one more on either side so the
+/// line runs to the edges of the plot
+pub fn visible_range(points: &VecDeque<[f64; 2]>, min_x: f64, max_x: f64) -> Range<usize> {
+    let start = points.partition_point(|point| point[0] < min_x).saturating_sub(1);
+    let end = (points.partition_point(|point| point[0] <= max_x) + 1).min(points.len());
+    start..end.max(start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_max_decimation() {
+        let short = [[0.0, 1.0], [1.0, 2.0]];
+        assert_eq!(min_max(short.iter(), 10), short.to_vec());
+
+        // A spike in the middle of a flat signal survives
+        let mut points: Vec<[f64; 2]> = (0..100).map(|i| [i as f64, 0.0]).collect();
+        points[42][1] = 5.0;
+        points[43][1] = -3.0;
+        let decimated = min_max(points.iter(), 10);
+        assert!(decimated.len() <= 21);
+        assert!(decimated.contains(&[42.0, 5.0]));
+        assert!(decimated.contains(&[43.0, -3.0]));
+        assert_eq!(decimated.last(), Some(&[99.0, 0.0]));
+        assert!(decimated.windows(2).all(|pair| pair[0][0] < pair[1][0]));
+
+        let points: VecDeque<[f64; 2]> = points.into_iter().collect();
+        assert_eq!(visible_range(&points, 10.5, 20.0), 10..22);
+        assert_eq!(visible_range(&points, -5.0, 200.0), 0..100);
+        assert_eq!(visible_range(&points, 150.0, 200.0), 99..100);
+    }
+}
@@ -0,0 +1,299 @@
+// default_report.rs - Objects whose device value differs from the EDS DefaultValue
+//
+// Lets a commissioning engineer check that a device is still at factory configuration.
+// The values come from a live snapshot; numbers are compared numerically (so "0x0A"
+// matches 10) and "$NODEID+0x180" defaults are resolved for the connected node.
+use canopen_common::SdoDataType;
+use eframe::egui::{self, Color32};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+
+use crate::communication::SdoObject;
+use crate::sdo_write::{integer_size, parse_eds_number};
+use crate::snapshot::SnapshotEntry;
+
+/// An object not at its default value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Deviation {
+    #[serde(rename = "Index", serialize_with = "hex_index")]
+    pub index: u16,
+    #[serde(rename = "SubIndex", serialize_with = "hex_sub_index")]
+    pub sub_index: u8,
+    #[serde(rename = "Name")]
+    pub name: String,
+    /// As written in the EDS
+    #[serde(rename = "Default")]
+    pub default: String,
+    #[serde(rename = "Actual")]
+    pub actual: String,
+}
+
+fn hex_index<S: serde::Serializer>(index: &u16, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("0x{:04X}", index))
+}
+
+fn hex_sub_index<S: serde::Serializer>(sub_index: &u8, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("0x{:02X}", sub_index))
+}
+
+/// Outcome of comparing a snapshot with the EDS defaults
+#[derive(Debug, Default)]
+pub struct DefaultComparison {
+    pub deviations: Vec<Deviation>,
+    pub matching: usize,
+    /// Objects that couldn't be read
+    pub unreadable: usize,
+    /// Objects without a default, or one that can't be compared (e.g. octet strings)
+    pub not_compared: usize,
+}
+
+/// Compare every snapshot entry with its object's `DefaultValue`
+pub fn compare(objects: &BTreeMap<u16, SdoObject>, entries: &[SnapshotEntry], node_id: u8) -> DefaultComparison {
+    let mut comparison = DefaultComparison::default();
+
+    for entry in entries {
+        let default = objects.get(&entry.index)
+            .and_then(|object| object.sub_objects.get(&entry.sub_index))
+            .and_then(|sub_object| sub_object.default_value.as_deref());
+        let Ok(actual) = &entry.value else {
+            comparison.unreadable += 1;
+            continue;
+        };
+        match default.and_then(|default| is_default(default, actual, &entry.data_type, node_id).map(|same| (default, same))) {
+            Some((_, true)) => comparison.matching += 1,
+            Some((default, false)) => comparison.deviations.push(Deviation {
+                index: entry.index,
+                sub_index: entry.sub_index,
+                name: entry.name.clone(),
+                default: default.trim().to_string(),
+                actual: actual.clone(),
+            }),
+            None => comparison.not_compared += 1,
+        }
+    }
+    comparison
+}
+
+/// Whether a read value equals the EDS default, `None` if the two can't be compared
+fn is_default(default: &str, actual: &str, data_type: &SdoDataType, node_id: u8) -> Option<bool> {
+    match data_type {
+        SdoDataType::VisibleString => Some(default.trim() == actual.trim()),
+        SdoDataType::OctetString => None,
+        SdoDataType::Real32 => {
+            let (default, actual) = (resolve_default(default, data_type, node_id)?, actual.parse::<f64>().ok()?);
+            // The device value went through an f32
+            Some((default - actual).abs() <= 1e-6 * default.abs().max(1.0))
+        }
+        _ => {
+            let default = resolve_default(default, data_type, node_id)?;
+            let actual = actual.parse::<f64>().ok()?;
+            // Defaults like "$NODEID+0x80000180" may not fit the object's size
+            let bits = integer_size(data_type).unwrap_or(4) * 8;
+            let mask = (1u64 << bits) - 1;
+            Some((default as i64 as u64) & mask == (actual as i64 as u64) & mask)
+        }
+    }
+}
+
+/// Numeric default, with $NODEID terms replaced by the node ID
+pub fn resolve_default(default: &str, data_type: &SdoDataType, node_id: u8) -> Option<f64> {
+    if !default.to_ascii_uppercase().contains("$NODEID") {
+        return parse_eds_number(default, data_type);
+    }
+    default.split('+')
+        .map(|term| match term.trim().to_ascii_uppercase().as_str() {
+            "$NODEID" => Some(node_id as f64),
+            _ => parse_eds_number(term, data_type),
+        })
+        .sum()
+}
+
+/// Write the deviations to a CSV file
+pub fn save(path: &Path, deviations: &[Deviation]) -> Result<(), Box<dyn Error>> {
+    let mut writer = csv::Writer::from_path(path)?;
+    for deviation in deviations {
+        writer.serialize(deviation)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub struct DefaultReport {
+    pub open: bool,
+    /// A live snapshot was requested for the report
+    waiting_for_live: bool,
+    /// Time of the snapshot and its comparison
+    report: Option<(String, DefaultComparison)>,
+    error: Option<String>,
+}
+
+impl DefaultReport {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            waiting_for_live: false,
+            report: None,
+            error: None,
+        }
+    }
+
+    pub fn waiting_for_live(&self) -> bool {
+        self.waiting_for_live
+    }
+
+    pub fn set_live(&mut self, objects: &BTreeMap<u16, SdoObject>, entries: &[SnapshotEntry], node_id: u8) {
+        self.waiting_for_live = false;
+        let label = chrono::Local::now().format("%H:%M:%S").to_string();
+        self.report = Some((label, compare(objects, entries, node_id)));
+    }
+
+    pub fn cancel_live(&mut self) {
+        self.waiting_for_live = false;
+    }
+
+    /// Draw the window; returns `true` when a live snapshot should be taken
+    pub fn show(&mut self, ctx: &egui::Context, live_available: bool, node_id: u8) -> bool {
+        let mut take_live = false;
+        let mut open = self.open;
+
+        egui::Window::new("Factory Defaults")
+            .open(&mut open)
+            .default_width(600.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if self.waiting_for_live {
+                        ui.spinner();
+                        ui.label("Reading device values...");
+                    } else if ui.add_enabled(live_available, egui::Button::new("📷 Read device"))
+                        .on_hover_text("Read every object and compare it with the EDS DefaultValue")
+                        .clicked()
+                    {
+                        self.waiting_for_live = true;
+                        take_live = true;
+                    }
+
+                    let has_deviations = self.report.as_ref().is_some_and(|(_, report)| !report.deviations.is_empty());
+                    if ui.add_enabled(has_deviations, egui::Button::new("💾 Export CSV")).clicked() {
+                        self.export(node_id);
+                    }
+                });
+
+                if let Some(err) = &self.error {
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+                }
+                ui.separator();
+                self.draw_report(ui);
+            });
+
+        self.open = open;
+        take_live
+    }
+
+    fn draw_report(&self, ui: &mut egui::Ui) {
+        let Some((label, report)) = &self.report else {
+            ui.label("Read the device to compare its values with the EDS defaults.");
+            return;
+        };
+
+        ui.label(format!(
+            "Read at {}: {} at default, {} deviating, {} unreadable, {} without comparable default",
+            label, report.matching, report.deviations.len(), report.unreadable, report.not_compared
+        ));
+        if report.deviations.is_empty() {
+            ui.colored_label(Color32::from_rgb(0, 200, 0), "✓ All compared objects are at their default value");
+            return;
+        }
+
+        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            egui::Grid::new("default_deviations")
+                .num_columns(4)
+                .spacing([10.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Address");
+                    ui.label("Name");
+                    ui.label("Default");
+                    ui.label("Actual");
+                    ui.end_row();
+
+                    for deviation in &report.deviations {
+                        ui.label(format!("{:04X}:{:02X}", deviation.index, deviation.sub_index));
+                        ui.label(&deviation.name);
+                        ui.label(&deviation.default);
+                        ui.label(&deviation.actual);
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+
+    fn export(&mut self, node_id: u8) {
+        let Some((_, report)) = &self.report else {
+            return;
+        };
+        let file_name = format!("defaults_node{}_{}.csv", node_id, chrono::Local::now().format("%Y-%m-%d_%H-%M-%S"));
+        if let Some(path) = rfd::FileDialog::new().set_file_name(&file_name).save_file() {
+            match save(&path, &report.deviations) {
+                Ok(()) => println!("✓ Saved {} deviations from the defaults to {:?}", report.deviations.len(), path),
+                Err(e) => self.error = Some(format!("Failed to save report: {}", e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::communication::SdoSubObject;
+
+    #[test]
+    fn test_compare_with_defaults() {
+        let sub_object = |data_type: &str, default: Option<&str>| SdoSubObject {
+            name: String::new(),
+            data_type: data_type.to_string(),
+            access_type: "rw".to_string(),
+            low_limit: None,
+            high_limit: None,
+            default_value: default.map(str::to_string),
+            value_descriptions: BTreeMap::new(),
+            scaling: None,
+        };
+        let objects = BTreeMap::from([
+            (0x1017, SdoObject { name: String::new(), sub_objects: BTreeMap::from([(0, sub_object("0x0006", Some("0x03E8")))]) }),
+            (0x1800, SdoObject { name: String::new(), sub_objects: BTreeMap::from([(1, sub_object("0x0007", Some("$NODEID+0x180")))]) }),
+            (0x2000, SdoObject { name: String::new(), sub_objects: BTreeMap::from([(0, sub_object("0x0003", Some("0xFFFF")))]) }),
+            (0x2001, SdoObject { name: String::new(), sub_objects: BTreeMap::from([(0, sub_object("0x0008", Some("1.5")))]) }),
+            (0x2002, SdoObject { name: String::new(), sub_objects: BTreeMap::from([(0, sub_object("0x0006", None))]) }),
+        ]);
+        let entry = |index, sub_index, data_type, value: Result<&str, &str>| SnapshotEntry {
+            index,
+            sub_index,
+            name: format!("{:04X}", index),
+            data_type,
+            raw: Vec::new(),
+            value: value.map(str::to_string).map_err(str::to_string),
+        };
+        let entries = vec![
+            entry(0x1017, 0, SdoDataType::UInt16, Ok("1000")),
+            entry(0x1800, 1, SdoDataType::UInt32, Ok("389")),
+            entry(0x2000, 0, SdoDataType::Int16, Ok("-1")),
+            entry(0x2001, 0, SdoDataType::Real32, Ok("2")),
+            entry(0x2002, 0, SdoDataType::UInt16, Ok("7")),
+            entry(0x2003, 0, SdoDataType::UInt16, Err("SDO request timeout")),
+        ];
+
+        let report = compare(&objects, &entries, 5);
+        assert_eq!(report.matching, 3);
+        assert_eq!(report.unreadable, 1);
+        assert_eq!(report.not_compared, 1);
+        assert_eq!(report.deviations, vec![Deviation {
+            index: 0x2001,
+            sub_index: 0,
+            name: "2001".to_string(),
+            default: "1.5".to_string(),
+            actual: "2".to_string(),
+        }]);
+    }
+}
@@ -0,0 +1,511 @@
+// derived.rs - Virtual channels computed from recorded ones
+//
+// Power from voltage and current, a smoothed speed or the rate of change of a temperature
+// is what the engineer wants to see, not the raw objects. A derived channel is an
+// expression over other channels, referenced by name or config key in braces:
+// `{Voltage} * {Current}`, `avg({Speed}, 20)`, `rate({Temperature})`. It is evaluated with
+// the latest value of each channel whenever one of them gets a sample, and is then plotted,
+// logged and exported like a subscription. The definitions are kept in the config file.
+use eframe::egui::{self, Color32};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Largest window of a moving average
+const MAX_AVERAGE_WINDOW: usize = 10_000;
+
+/// A derived channel as stored in the config file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DerivedChannel {
+    pub name: String,
+    pub expression: String,
+    #[serde(default)]
+    pub unit: String,
+}
+
+impl DerivedChannel {
+    /// Y axis label, like `Scaling::value_label`
+    pub fn value_label(&self) -> String {
+        if self.unit.is_empty() { "Value".to_string() } else { format!("Value [{}]", self.unit) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+impl Operator {
+    fn apply(self, left: f64, right: f64) -> f64 {
+        match self {
+            Operator::Add => left + right,
+            Operator::Subtract => left - right,
+            Operator::Multiply => left * right,
+            Operator::Divide => left / right,
+            Operator::Power => left.powf(right),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Number(f64),
+    /// Index into the referenced channels
+    Channel(usize),
+    Negate(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+    Abs(Box<Node>),
+    Sqrt(Box<Node>),
+    Min(Box<Node>, Box<Node>),
+    Max(Box<Node>, Box<Node>),
+    /// Mean of the last `window` values of the argument
+    Average { argument: Box<Node>, window: usize, values: VecDeque<f64> },
+    /// Change of the argument per second since the previous evaluation
+    Rate { argument: Box<Node>, previous: Option<(f64, f64)> },
+}
+
+impl Node {
+    /// NaN while there is no value, e.g. for the first evaluation of a rate
+    fn evaluate(&mut self, time: f64, values: &[f64]) -> f64 {
+        match self {
+            Node::Number(value) => *value,
+            Node::Channel(index) => values.get(*index).copied().unwrap_or(f64::NAN),
+            Node::Negate(argument) => -argument.evaluate(time, values),
+            Node::Binary(operator, left, right) => operator.apply(left.evaluate(time, values), right.evaluate(time, values)),
+            Node::Abs(argument) => argument.evaluate(time, values).abs(),
+            Node::Sqrt(argument) => argument.evaluate(time, values).sqrt(),
+            Node::Min(left, right) => left.evaluate(time, values).min(right.evaluate(time, values)),
+            Node::Max(left, right) => left.evaluate(time, values).max(right.evaluate(time, values)),
+            Node::Average { argument, window, values: recent } => {
+                let value = argument.evaluate(time, values);
+                if value.is_finite() {
+                    recent.push_back(value);
+                    if recent.len() > *window {
+                        recent.pop_front();
+                    }
+                }
+                if recent.is_empty() { f64::NAN } else { recent.iter().sum::<f64>() / recent.len() as f64 }
+            }
+            Node::Rate { argument, previous } => {
+                let value = argument.evaluate(time, values);
+                let rate = match *previous {
+                    Some((previous_time, previous_value)) if time > previous_time => (value - previous_value) / (time - previous_time),
+                    _ => f64::NAN,
+                };
+                if value.is_finite() {
+                    *previous = Some((time, value));
+                }
+                rate
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        match self {
+            Node::Number(_) | Node::Channel(_) => {}
+            Node::Negate(argument) | Node::Abs(argument) | Node::Sqrt(argument) => argument.reset(),
+            Node::Binary(_, left, right) | Node::Min(left, right) | Node::Max(left, right) => {
+                left.reset();
+                right.reset();
+            }
+            Node::Average { argument, values, .. } => {
+                values.clear();
+                argument.reset();
+            }
+            Node::Rate { argument, previous } => {
+                *previous = None;
+                argument.reset();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Channel(String),
+    Name(String),
+    Operator(char),
+    Open,
+    Close,
+    Comma,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '0'..='9' | '.' => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek() {
+                    // An exponent sign belongs to the number
+                    let exponent_sign = (c == '-' || c == '+') && number.ends_with(['e', 'E']);
+                    if !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || exponent_sign) {
+                        break;
+                    }
+                    number.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Number(number.parse().map_err(|_| format!("Invalid number '{}'", number))?));
+            }
+            '{' => {
+                chars.next();
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(format!("Missing '}}' after '{{{}'", name)),
+                    }
+                }
+                if name.trim().is_empty() {
+                    return Err("Empty channel reference '{}'".to_string());
+                }
+                tokens.push(Token::Channel(name.trim().to_string()));
+            }
+            c if c.is_alphabetic() => {
+                let mut name = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !c.is_alphanumeric() && c != '_' {
+                        break;
+                    }
+                    name.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Name(name.to_lowercase()));
+            }
+            '+' | '-' | '*' | '/' | '^' => {
+                tokens.push(Token::Operator(c));
+                chars.next();
+            }
+            '(' => {
+                tokens.push(Token::Open);
+                chars.next();
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                chars.next();
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                chars.next();
+            }
+            _ => return Err(format!("Unexpected '{}'", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the tokens: sums of products of powers of unary terms
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    channels: Vec<String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token, what: &str) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            _ => Err(format!("Expected {}", what)),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Node, String> {
+        let mut node = self.product()?;
+        while let Some(Token::Operator(c @ ('+' | '-'))) = self.peek().cloned() {
+            self.position += 1;
+            let operator = if c == '+' { Operator::Add } else { Operator::Subtract };
+            node = Node::Binary(operator, Box::new(node), Box::new(self.product()?));
+        }
+        Ok(node)
+    }
+
+    fn product(&mut self) -> Result<Node, String> {
+        let mut node = self.power()?;
+        while let Some(Token::Operator(c @ ('*' | '/'))) = self.peek().cloned() {
+            self.position += 1;
+            let operator = if c == '*' { Operator::Multiply } else { Operator::Divide };
+            node = Node::Binary(operator, Box::new(node), Box::new(self.power()?));
+        }
+        Ok(node)
+    }
+
+    fn power(&mut self) -> Result<Node, String> {
+        let base = self.unary()?;
+        if self.peek() == Some(&Token::Operator('^')) {
+            self.position += 1;
+            // Right associative: 2^3^2 is 2^(3^2)
+            return Ok(Node::Binary(Operator::Power, Box::new(base), Box::new(self.power()?)));
+        }
+        Ok(base)
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        match self.next() {
+            Some(Token::Operator('-')) => Ok(Node::Negate(Box::new(self.unary()?))),
+            Some(Token::Operator('+')) => self.unary(),
+            Some(Token::Number(value)) => Ok(Node::Number(value)),
+            Some(Token::Channel(name)) => {
+                let index = match self.channels.iter().position(|channel| *channel == name) {
+                    Some(index) => index,
+                    None => {
+                        self.channels.push(name);
+                        self.channels.len() - 1
+                    }
+                };
+                Ok(Node::Channel(index))
+            }
+            Some(Token::Open) => {
+                let node = self.sum()?;
+                self.expect(Token::Close, "')'")?;
+                Ok(node)
+            }
+            Some(Token::Name(name)) => self.function(&name),
+            Some(token) => Err(format!("Unexpected {:?}", token)),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+
+    fn function(&mut self, name: &str) -> Result<Node, String> {
+        if name == "pi" {
+            return Ok(Node::Number(std::f64::consts::PI));
+        }
+        self.expect(Token::Open, &format!("'(' after '{}'", name))?;
+        let first = Box::new(self.sum()?);
+        let node = match name {
+            "abs" => Node::Abs(first),
+            "sqrt" => Node::Sqrt(first),
+            "rate" => Node::Rate { argument: first, previous: None },
+            "min" | "max" => {
+                self.expect(Token::Comma, &format!("two arguments to '{}'", name))?;
+                let second = Box::new(self.sum()?);
+                if name == "min" { Node::Min(first, second) } else { Node::Max(first, second) }
+            }
+            "avg" => {
+                self.expect(Token::Comma, "a window size: avg(x, n)")?;
+                let window = match self.next() {
+                    Some(Token::Number(window)) if window >= 1.0 && window.fract() == 0.0 && window as usize <= MAX_AVERAGE_WINDOW => window as usize,
+                    _ => return Err(format!("The window of avg() must be a whole number from 1 to {}", MAX_AVERAGE_WINDOW)),
+                };
+                Node::Average { argument: first, window, values: VecDeque::new() }
+            }
+            _ => return Err(format!("Unknown function '{}'", name)),
+        };
+        self.expect(Token::Close, &format!("')' after the arguments of '{}'", name))?;
+        Ok(node)
+    }
+}
+
+/// A parsed expression with the state of its moving averages and rates
+#[derive(Debug, Clone)]
+pub struct Expression {
+    root: Node,
+    /// Referenced channels in order of first use
+    channels: Vec<String>,
+}
+
+impl Expression {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser { tokens: tokenize(text)?, position: 0, channels: Vec::new() };
+        let root = parser.sum()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("Unexpected {:?}", token));
+        }
+        if parser.channels.is_empty() {
+            return Err("The expression uses no channel".to_string());
+        }
+        Ok(Self { root, channels: parser.channels })
+    }
+
+    pub fn channels(&self) -> &[String] {
+        &self.channels
+    }
+
+    /// Value at `time` (seconds) from the latest value of each channel, in the order of
+    /// `channels()`; None when the result isn't a finite number, e.g. for the first rate
+    pub fn evaluate(&mut self, time: f64, values: &[f64]) -> Option<f64> {
+        Some(self.root.evaluate(time, values)).filter(|value| value.is_finite())
+    }
+
+    /// Forget the samples of moving averages and rates
+    pub fn reset(&mut self) {
+        self.root.reset();
+    }
+}
+
+pub enum DerivedAction {
+    /// Add a channel, or replace the one named `previous`
+    Save { previous: Option<String>, channel: DerivedChannel },
+    Remove(String),
+    /// Show the channel on the active dashboard
+    Plot(String),
+}
+
+/// Window listing the derived channels with an editor for one of them
+pub struct DerivedPanel {
+    pub open: bool,
+    draft: DerivedChannel,
+    /// Name of the channel being edited; None adds a new one
+    editing: Option<String>,
+}
+
+impl DerivedPanel {
+    pub fn new() -> Self {
+        Self { open: false, draft: DerivedChannel::default(), editing: None }
+    }
+
+    /// `channels` are the names of the recorded channels an expression can use
+    pub fn show(&mut self, ctx: &egui::Context, definitions: &[DerivedChannel], channels: &[String]) -> Option<DerivedAction> {
+        let mut action = None;
+        let mut open = self.open;
+        egui::Window::new("Derived Channels")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if definitions.is_empty() {
+                    ui.weak("No derived channels yet");
+                }
+                egui::Grid::new("derived_channels_grid").num_columns(3).striped(true).show(ui, |ui| {
+                    for definition in definitions {
+                        ui.strong(&definition.name);
+                        let unit = if definition.unit.is_empty() { String::new() } else { format!(" [{}]", definition.unit) };
+                        ui.monospace(format!("{}{}", definition.expression, unit));
+                        ui.horizontal(|ui| {
+                            if ui.small_button("📈").on_hover_text("Plot on this dashboard").clicked() {
+                                action = Some(DerivedAction::Plot(definition.name.clone()));
+                            }
+                            if ui.small_button("✏").on_hover_text("Edit").clicked() {
+                                self.draft = definition.clone();
+                                self.editing = Some(definition.name.clone());
+                            }
+                            if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                                action = Some(DerivedAction::Remove(definition.name.clone()));
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+
+                ui.strong(if self.editing.is_some() { "Edit channel" } else { "New channel" });
+                egui::Grid::new("derived_channel_editor").num_columns(2).show(ui, |ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut self.draft.name);
+                    ui.end_row();
+                    ui.label("Unit:");
+                    ui.text_edit_singleline(&mut self.draft.unit);
+                    ui.end_row();
+                    ui.label("Expression:");
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut self.draft.expression).code_editor().desired_width(260.0).hint_text("{Voltage} * {Current}"));
+                        ui.menu_button("➕ Channel", |ui| {
+                            if channels.is_empty() {
+                                ui.weak("Nothing recorded yet");
+                            }
+                            for channel in channels {
+                                if ui.button(channel).clicked() {
+                                    self.draft.expression.push_str(&format!("{{{}}}", channel));
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    });
+                    ui.end_row();
+                });
+                ui.weak("Operators + - * / ^ and parentheses; functions abs(x), sqrt(x), min(a, b), max(a, b), avg(x, n) over the last n values and rate(x) per second.");
+
+                let problem = self.problem(definitions);
+                ui.horizontal(|ui| {
+                    let label = if self.editing.is_some() { "💾 Save" } else { "➕ Add" };
+                    if ui.add_enabled(problem.is_none(), egui::Button::new(label)).clicked() {
+                        let channel = DerivedChannel {
+                            name: self.draft.name.trim().to_string(),
+                            expression: self.draft.expression.trim().to_string(),
+                            unit: self.draft.unit.trim().to_string(),
+                        };
+                        action = Some(DerivedAction::Save { previous: self.editing.take(), channel });
+                        self.draft = DerivedChannel::default();
+                    }
+                    if self.editing.is_some() && ui.button("Cancel").clicked() {
+                        self.editing = None;
+                        self.draft = DerivedChannel::default();
+                    }
+                });
+                let started = !self.draft.name.trim().is_empty() || !self.draft.expression.trim().is_empty();
+                if let Some(problem) = problem.filter(|_| started) {
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", problem));
+                }
+            });
+        self.open = open;
+        action
+    }
+
+    /// Why the draft can't be saved
+    fn problem(&self, definitions: &[DerivedChannel]) -> Option<String> {
+        let name = self.draft.name.trim();
+        if name.is_empty() {
+            return Some("Enter a name".to_string());
+        }
+        if name.contains(['{', '}']) {
+            return Some("The name can't contain braces".to_string());
+        }
+        let taken = definitions.iter().any(|definition| definition.name == name && self.editing.as_deref() != Some(name));
+        if taken {
+            return Some(format!("There already is a channel named '{}'", name));
+        }
+        Expression::parse(&self.draft.expression).err()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derived_expressions() {
+        let mut power = Expression::parse("{Voltage} * {Current} / 1000").unwrap();
+        assert_eq!(power.channels(), ["Voltage", "Current"]);
+        assert_eq!(power.evaluate(0.0, &[24.0, 500.0]), Some(12.0));
+
+        let mut precedence = Expression::parse("-{a} + 2 * 3 ^ 2 - (1 - 4)").unwrap();
+        assert_eq!(precedence.evaluate(0.0, &[1.0]), Some(20.0));
+        let mut functions = Expression::parse("max(abs({a}), sqrt(16)) + min({a}, 1.5e1)").unwrap();
+        assert_eq!(functions.evaluate(0.0, &[-5.0]), Some(0.0));
+
+        // Moving average over the last two values, rate per second from the second sample on
+        let mut average = Expression::parse("avg({Speed}, 2)").unwrap();
+        assert_eq!(average.evaluate(0.0, &[1.0]), Some(1.0));
+        assert_eq!(average.evaluate(1.0, &[3.0]), Some(2.0));
+        assert_eq!(average.evaluate(2.0, &[7.0]), Some(5.0));
+        let mut rate = Expression::parse("rate({T})").unwrap();
+        assert_eq!(rate.evaluate(0.0, &[10.0]), None);
+        assert_eq!(rate.evaluate(0.5, &[12.0]), Some(4.0));
+        rate.reset();
+        assert_eq!(rate.evaluate(1.0, &[20.0]), None);
+        assert_eq!(Expression::parse("{a} / 0").unwrap().evaluate(0.0, &[1.0]), None);
+
+        assert!(Expression::parse("2 * 3").is_err());
+        assert!(Expression::parse("{a} +").is_err());
+        assert!(Expression::parse("avg({a}, 0.5)").is_err());
+        assert!(Expression::parse("foo({a})").is_err());
+        assert!(Expression::parse("({a}").is_err());
+    }
+}
@@ -0,0 +1,385 @@
+// device_check.rs - Quick CiA 301 sanity checks before a device ships
+//
+// Not a conformance test tool, but the handful of things that most often go wrong:
+// the mandatory objects can be read, the node produces heartbeats, reads of objects and
+// sub-indices that don't exist abort with the right codes, and the identity object
+// matches the EDS. Each check passes, fails or is skipped, and the result can be saved
+// as a text report.
+use chrono::Local;
+use eframe::egui::{self, Color32};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::communication::SdoObject;
+use crate::scanner::{self, OBJECT_DOES_NOT_EXIST, SUB_INDEX_DOES_NOT_EXIST};
+use crate::sdo_write::parse_eds_number;
+use crate::startup::{self, ExpectedIdentity};
+use canopen_common::SdoDataType;
+
+/// Objects every CANopen device must have: device type, error register, vendor ID
+pub const MANDATORY_OBJECTS: [(u16, u8, &str); 3] = [
+    (0x1000, 0, "Device type"),
+    (0x1001, 0, "Error register"),
+    (0x1018, 1, "Vendor ID"),
+];
+
+/// Sub-index of the identity object beyond any device's last one
+pub const ABSENT_SUB_INDEX: u8 = 0x7F;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceCheck {
+    MandatoryObjects,
+    Heartbeat,
+    SdoAborts,
+    Identity,
+}
+
+impl DeviceCheck {
+    pub const ALL: [DeviceCheck; 4] = [Self::MandatoryObjects, Self::Heartbeat, Self::SdoAborts, Self::Identity];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::MandatoryObjects => "Mandatory objects",
+            Self::Heartbeat => "Heartbeat production",
+            Self::SdoAborts => "SDO aborts",
+            Self::Identity => "EDS/identity consistency",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckOutcome {
+    Pass(String),
+    Fail(String),
+    Skipped(String),
+}
+
+impl CheckOutcome {
+    pub fn verdict(&self) -> &'static str {
+        match self {
+            Self::Pass(_) => "PASS",
+            Self::Fail(_) => "FAIL",
+            Self::Skipped(_) => "SKIP",
+        }
+    }
+
+    pub fn detail(&self) -> &str {
+        match self {
+            Self::Pass(detail) | Self::Fail(detail) | Self::Skipped(detail) => detail,
+        }
+    }
+}
+
+/// What the checks compare the device with, taken from the EDS
+#[derive(Debug, Clone, Default)]
+pub struct DeviceCheckPlan {
+    pub identity: ExpectedIdentity,
+    pub device_type: Option<u32>,
+    /// A manufacturer index the EDS doesn't list, to provoke "object does not exist"
+    pub absent_index: u16,
+}
+
+impl DeviceCheckPlan {
+    pub fn new(eds_path: Option<&Path>, objects: Option<&BTreeMap<u16, SdoObject>>) -> Self {
+        let identity = eds_path.and_then(|path| ExpectedIdentity::from_eds(path).ok()).unwrap_or_default();
+        let device_type = objects
+            .and_then(|objects| objects.get(&0x1000))
+            .and_then(|object| object.sub_objects.get(&0))
+            .and_then(|sub_object| sub_object.default_value.as_deref())
+            .and_then(|value| parse_eds_number(value, &SdoDataType::UInt32))
+            .map(|value| value as u32);
+        let absent_index = (0x2000..=0x5FFF)
+            .rev()
+            .find(|index| !objects.is_some_and(|objects| objects.contains_key(index)))
+            .unwrap_or(0x5FFF);
+        Self { identity, device_type, absent_index }
+    }
+}
+
+/// Index, sub-index and name of an object with the outcome of reading it
+pub type ObjectRead<'a> = (u16, u8, &'a str, Result<Vec<u8>, String>);
+
+/// Every mandatory object has to be readable
+pub fn mandatory_outcome(reads: &[ObjectRead]) -> CheckOutcome {
+    let failed: Vec<String> = reads.iter()
+        .filter_map(|(index, sub_index, name, result)| {
+            result.as_ref().err().map(|err| format!("{} ({:04X}:{:02X}): {}", name, index, sub_index, err))
+        })
+        .collect();
+    if failed.is_empty() {
+        CheckOutcome::Pass(format!("{} objects readable", reads.len()))
+    } else {
+        CheckOutcome::Fail(failed.join("; "))
+    }
+}
+
+/// Heartbeats seen within three producer periods, with the interval between the first two
+pub fn heartbeat_outcome(producer_ms: u16, heartbeats: usize, interval: Option<Duration>) -> CheckOutcome {
+    if producer_ms == 0 {
+        return CheckOutcome::Fail("producer heartbeat time (0x1017) is 0, no heartbeats produced".to_string());
+    }
+    match (heartbeats, interval) {
+        (0, _) => CheckOutcome::Fail(format!("no heartbeat within {} ms, 0x1017 is {} ms", producer_ms as u32 * 3, producer_ms)),
+        (_, Some(interval)) => {
+            let measured = interval.as_millis();
+            let expected = producer_ms as u128;
+            // Generous margin for bus and scheduling jitter
+            if measured.abs_diff(expected) * 4 > expected {
+                CheckOutcome::Fail(format!("heartbeats {} ms apart, 0x1017 is {} ms", measured, producer_ms))
+            } else {
+                CheckOutcome::Pass(format!("heartbeats {} ms apart, 0x1017 is {} ms", measured, producer_ms))
+            }
+        }
+        (_, None) => CheckOutcome::Pass(format!("heartbeat received, 0x1017 is {} ms", producer_ms)),
+    }
+}
+
+/// The read of `what` should have aborted with `expected`
+fn expect_abort(what: &str, result: &Result<Vec<u8>, String>, expected: u32) -> Option<String> {
+    match result {
+        Ok(_) => Some(format!("{} was read successfully", what)),
+        Err(err) => match scanner::abort_code(err) {
+            Some(code) if code == expected => None,
+            Some(code) => Some(format!("{} aborted with 0x{:08X}, expected 0x{:08X}", what, code, expected)),
+            None => Some(format!("{} didn't abort: {}", what, err)),
+        },
+    }
+}
+
+/// Reads of a missing object and a missing sub-index must abort with the matching codes
+pub fn abort_outcome(
+    absent_index: u16,
+    missing_object: &Result<Vec<u8>, String>,
+    missing_sub_index: &Result<Vec<u8>, String>,
+) -> CheckOutcome {
+    let problems: Vec<String> = [
+        expect_abort(&format!("missing object {:04X}:00", absent_index), missing_object, OBJECT_DOES_NOT_EXIST),
+        expect_abort(&format!("missing sub-index 1018:{:02X}", ABSENT_SUB_INDEX), missing_sub_index, SUB_INDEX_DOES_NOT_EXIST),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if problems.is_empty() {
+        CheckOutcome::Pass("missing object and sub-index abort with 0x06020000 and 0x06090011".to_string())
+    } else {
+        CheckOutcome::Fail(problems.join("; "))
+    }
+}
+
+/// Identity fields and device type read from the device against the EDS
+pub fn identity_outcome(plan: &DeviceCheckPlan, identity: &[(u8, u32)], device_type: Option<u32>) -> CheckOutcome {
+    let expected = ExpectedIdentity { serial_number: None, ..plan.identity.clone() };
+    if expected.fields().is_empty() && plan.device_type.is_none() {
+        return CheckOutcome::Skipped("the EDS has no identity or device type to compare".to_string());
+    }
+
+    let mut problems = Vec::new();
+    let mut matched = Vec::new();
+    match startup::check_identity(&expected, identity) {
+        Ok(_) if expected.fields().is_empty() => {}
+        Ok(message) => matched.push(message),
+        Err(mismatches) => problems.push(mismatches),
+    }
+    if let Some(expected_type) = plan.device_type {
+        match device_type {
+            Some(actual) if actual == expected_type => matched.push("device type matches".to_string()),
+            Some(actual) => problems.push(format!("Device type is 0x{:08X}, EDS says 0x{:08X}", actual, expected_type)),
+            None => problems.push("Device type not read".to_string()),
+        }
+    }
+    if problems.is_empty() {
+        CheckOutcome::Pass(matched.join(", "))
+    } else {
+        CheckOutcome::Fail(problems.join("; "))
+    }
+}
+
+/// Plain-text report of a finished check
+pub fn format_report(node_id: u8, results: &[(DeviceCheck, CheckOutcome)]) -> String {
+    let mut text = format!("Device check of node {}\n{}\n\n", node_id, Local::now().format("%Y-%m-%d %H:%M:%S"));
+    for (check, outcome) in results {
+        let _ = writeln!(text, "[{}] {}: {}", outcome.verdict(), check.label(), outcome.detail());
+    }
+    let failed = results.iter().filter(|(_, outcome)| matches!(outcome, CheckOutcome::Fail(_))).count();
+    let _ = writeln!(text, "\n{}", if failed == 0 { "Result: PASS".to_string() } else { format!("Result: FAIL ({} checks failed)", failed) });
+    text
+}
+
+pub struct DeviceCheckPanel {
+    pub open: bool,
+    running: bool,
+    results: Vec<(DeviceCheck, CheckOutcome)>,
+    error: Option<String>,
+}
+
+impl DeviceCheckPanel {
+    pub fn new() -> Self {
+        Self { open: false, running: false, results: Vec::new(), error: None }
+    }
+
+    pub fn start(&mut self) {
+        self.running = true;
+        self.results.clear();
+        self.error = None;
+    }
+
+    pub fn check_finished(&mut self, check: DeviceCheck, outcome: CheckOutcome) {
+        self.results.push((check, outcome));
+    }
+
+    pub fn finished(&mut self) {
+        self.running = false;
+    }
+
+    /// Outcomes of the checks run last
+    pub fn results(&self) -> &[(DeviceCheck, CheckOutcome)] {
+        &self.results
+    }
+
+    /// Returns true when the user started the check
+    pub fn show(&mut self, ctx: &egui::Context, connected: bool, node_id: u8) -> bool {
+        let mut run = false;
+        let mut open = self.open;
+
+        egui::Window::new(format!("Device Check - Node {}", node_id))
+            .id(egui::Id::new("device_check"))
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("device_check_grid").num_columns(3).striped(true).show(ui, |ui| {
+                    for check in DeviceCheck::ALL {
+                        ui.label(check.label());
+                        match self.results.iter().find(|(done, _)| *done == check).map(|(_, outcome)| outcome) {
+                            Some(CheckOutcome::Pass(detail)) => {
+                                ui.colored_label(Color32::from_rgb(0, 200, 0), "✔ Pass");
+                                ui.label(detail);
+                            }
+                            Some(CheckOutcome::Fail(detail)) => {
+                                ui.colored_label(Color32::from_rgb(255, 100, 100), "✖ Fail");
+                                ui.label(detail);
+                            }
+                            Some(CheckOutcome::Skipped(detail)) => {
+                                ui.weak("Skipped");
+                                ui.weak(detail);
+                            }
+                            None if self.running => {
+                                ui.spinner();
+                                ui.label("");
+                            }
+                            None => {
+                                ui.label("");
+                                ui.label("");
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(connected && !self.running, egui::Button::new("▶ Run check")).clicked() {
+                        run = true;
+                    }
+                    let complete = !self.running && self.results.len() == DeviceCheck::ALL.len();
+                    if ui.add_enabled(complete, egui::Button::new("💾 Save report")).clicked() {
+                        self.save_report(node_id);
+                    }
+                    if complete {
+                        if self.results.iter().any(|(_, outcome)| matches!(outcome, CheckOutcome::Fail(_))) {
+                            ui.colored_label(Color32::from_rgb(255, 100, 100), "Device check failed");
+                        } else {
+                            ui.colored_label(Color32::from_rgb(0, 200, 0), "Device check passed");
+                        }
+                    }
+                });
+                if let Some(error) = &self.error {
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", error));
+                }
+            });
+
+        self.open = open;
+        run
+    }
+
+    fn save_report(&mut self, node_id: u8) {
+        let file_name = format!("device_check_node{}_{}.txt", node_id, Local::now().format("%Y-%m-%d_%H-%M-%S"));
+        if let Some(path) = rfd::FileDialog::new().set_file_name(&file_name).save_file() {
+            match std::fs::write(&path, format_report(node_id, &self.results)) {
+                Ok(()) => {
+                    println!("✓ Saved device check report to {:?}", path);
+                    self.error = None;
+                }
+                Err(e) => self.error = Some(format!("Failed to save report: {}", e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mandatory_objects_outcome() {
+        let reads = [
+            (0x1000, 0, "Device type", Ok(vec![0x91, 0x01, 0x0F, 0x00])),
+            (0x1001, 0, "Error register", Err("Timeout".to_string())),
+        ];
+        assert!(matches!(mandatory_outcome(&reads), CheckOutcome::Fail(detail) if detail.starts_with("Error register (1001:00)")));
+    }
+
+    #[test]
+    fn test_heartbeat_outcome() {
+        assert!(matches!(heartbeat_outcome(0, 0, None), CheckOutcome::Fail(_)));
+        assert!(matches!(heartbeat_outcome(100, 0, None), CheckOutcome::Fail(_)));
+        assert!(matches!(heartbeat_outcome(100, 2, Some(Duration::from_millis(104))), CheckOutcome::Pass(_)));
+        assert!(matches!(heartbeat_outcome(100, 2, Some(Duration::from_millis(500))), CheckOutcome::Fail(_)));
+    }
+
+    #[test]
+    fn test_abort_outcome() {
+        let missing_object = Err("Request failed: SDO abort 0x06020000: Object does not exist".to_string());
+        let missing_sub = Err("Request failed: SDO abort 0x06090011: Sub-index does not exist".to_string());
+        assert!(matches!(abort_outcome(0x5FFF, &missing_object, &missing_sub), CheckOutcome::Pass(_)));
+        let wrong_code = Err("Request failed: SDO abort 0x08000000: General error".to_string());
+        match abort_outcome(0x5FFF, &Ok(vec![0]), &wrong_code) {
+            CheckOutcome::Fail(detail) => {
+                assert!(detail.contains("5FFF:00 was read successfully"));
+                assert!(detail.contains("aborted with 0x08000000, expected 0x06090011"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_identity_outcome() {
+        let plan = DeviceCheckPlan {
+            identity: ExpectedIdentity { vendor_id: Some(0x1234), product_code: Some(7), revision: None, serial_number: None },
+            device_type: Some(0x000F_0191),
+            absent_index: 0x5FFF,
+        };
+        assert!(matches!(identity_outcome(&plan, &[(1, 0x1234), (2, 7)], Some(0x000F_0191)), CheckOutcome::Pass(_)));
+        assert!(matches!(identity_outcome(&plan, &[(1, 0x1234), (2, 8)], Some(0x000F_0191)), CheckOutcome::Fail(_)));
+        assert!(matches!(identity_outcome(&DeviceCheckPlan::default(), &[], None), CheckOutcome::Skipped(_)));
+    }
+
+    #[test]
+    fn test_format_report() {
+        let report = format_report(5, &[
+            (DeviceCheck::MandatoryObjects, CheckOutcome::Pass("3 objects readable".to_string())),
+            (DeviceCheck::Identity, CheckOutcome::Skipped("no EDS".to_string())),
+        ]);
+        assert!(report.contains("[PASS] Mandatory objects: 3 objects readable\n[SKIP] EDS/identity consistency: no EDS\n"));
+        assert!(report.ends_with("Result: PASS\n"));
+    }
+
+    #[test]
+    fn test_absent_index_avoids_eds_objects() {
+        let mut objects = BTreeMap::new();
+        objects.insert(0x5FFF, SdoObject { name: "Vendor specific".to_string(), sub_objects: BTreeMap::new() });
+        assert_eq!(DeviceCheckPlan::new(None, Some(&objects)).absent_index, 0x5FFE);
+        assert_eq!(DeviceCheckPlan::new(None, None).absent_index, 0x5FFF);
+    }
+}
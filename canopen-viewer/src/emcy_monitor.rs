@@ -0,0 +1,224 @@
+// emcy_monitor.rs - List of the emergency messages received from the bus
+//
+// Emergencies (0x080 + node ID) are decoded by the communication thread and kept here in
+// the order they arrived. New ones count as unacknowledged until the user has seen them,
+// which the top panel shows, and the list can be exported to CSV for a service report.
+use canopen_common::{error_register_bits, EmcyMessage};
+use chrono::{DateTime, Local};
+use eframe::egui::{self, Color32};
+use serde::Serialize;
+use std::error::Error;
+use std::path::Path;
+
+/// Emergencies kept before the oldest are dropped
+const MAX_EMERGENCIES: usize = 1000;
+
+/// An emergency message and where it came from
+#[derive(Debug, Clone)]
+pub struct EmergencyRecord {
+    pub timestamp: DateTime<Local>,
+    pub node_id: u8,
+    pub message: EmcyMessage,
+}
+
+#[derive(Debug, Serialize)]
+struct CsvRow {
+    #[serde(rename = "Timestamp")]
+    timestamp: String,
+    #[serde(rename = "Node")]
+    node_id: u8,
+    #[serde(rename = "ErrorCode")]
+    error_code: String,
+    #[serde(rename = "Description")]
+    description: &'static str,
+    #[serde(rename = "ErrorRegister")]
+    error_register: String,
+    #[serde(rename = "RegisterBits")]
+    register_bits: String,
+    #[serde(rename = "ManufacturerData")]
+    manufacturer_data: String,
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" ")
+}
+
+/// Write the emergencies to a CSV file
+pub fn save(path: &Path, records: &[EmergencyRecord]) -> Result<(), Box<dyn Error>> {
+    let mut writer = csv::Writer::from_path(path)?;
+    for record in records {
+        writer.serialize(CsvRow {
+            timestamp: record.timestamp.format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+            node_id: record.node_id,
+            error_code: format!("0x{:04X}", record.message.error_code),
+            description: record.message.description(),
+            error_register: format!("0x{:02X}", record.message.error_register),
+            register_bits: error_register_bits(record.message.error_register).join(", "),
+            manufacturer_data: hex_bytes(&record.message.manufacturer_data),
+        })?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub struct EmcyMonitor {
+    pub open: bool,
+    records: Vec<EmergencyRecord>,
+    /// Records from this position on haven't been acknowledged
+    acknowledged: usize,
+    error: Option<String>,
+}
+
+impl EmcyMonitor {
+    pub fn new() -> Self {
+        Self { open: false, records: Vec::new(), acknowledged: 0, error: None }
+    }
+
+    pub fn record(&mut self, record: EmergencyRecord) {
+        self.records.push(record);
+        if self.records.len() > MAX_EMERGENCIES {
+            self.records.remove(0);
+            self.acknowledged = self.acknowledged.saturating_sub(1);
+        }
+    }
+
+    /// Emergencies received since the last acknowledgement
+    pub fn unacknowledged(&self) -> usize {
+        self.records.len() - self.acknowledged
+    }
+
+    /// Nodes that sent emergencies, in ascending order
+    pub fn nodes(&self) -> Vec<u8> {
+        let mut nodes: Vec<u8> = self.records.iter().map(|record| record.node_id).collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+        nodes
+    }
+
+    /// Unacknowledged emergencies of a node, and its latest one unless the node reset it
+    pub fn node_summary(&self, node_id: u8) -> (usize, Option<&EmcyMessage>) {
+        let unacknowledged = self.records[self.acknowledged..].iter()
+            .filter(|record| record.node_id == node_id && !record.message.is_reset())
+            .count();
+        let active = self.records.iter()
+            .rev()
+            .find(|record| record.node_id == node_id)
+            .map(|record| &record.message)
+            .filter(|message| !message.is_reset());
+        (unacknowledged, active)
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+
+        egui::Window::new("Emergency Messages")
+            .open(&mut open)
+            .default_width(720.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} emergencies, {} unacknowledged", self.records.len(), self.unacknowledged()));
+                    if ui.add_enabled(self.unacknowledged() > 0, egui::Button::new("✔ Acknowledge all")).clicked() {
+                        self.acknowledged = self.records.len();
+                    }
+                    if ui.add_enabled(!self.records.is_empty(), egui::Button::new("🗑 Clear")).clicked() {
+                        self.records.clear();
+                        self.acknowledged = 0;
+                    }
+                    if ui.add_enabled(!self.records.is_empty(), egui::Button::new("💾 Export CSV")).clicked() {
+                        self.export();
+                    }
+                });
+                if let Some(error) = &self.error {
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", error));
+                }
+                ui.separator();
+
+                if self.records.is_empty() {
+                    ui.label("No emergency messages received.");
+                    return;
+                }
+
+                egui::ScrollArea::vertical().stick_to_bottom(true).show(ui, |ui| {
+                    egui::Grid::new("emcy_grid")
+                        .num_columns(6)
+                        .spacing([10.0, 4.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("Time");
+                            ui.strong("Node");
+                            ui.strong("Code");
+                            ui.strong("Description");
+                            ui.strong("Error register");
+                            ui.strong("Manufacturer data");
+                            ui.end_row();
+
+                            for (i, record) in self.records.iter().enumerate() {
+                                let message = &record.message;
+                                let color = if message.is_reset() {
+                                    Color32::from_rgb(0, 200, 0)
+                                } else if i >= self.acknowledged {
+                                    Color32::from_rgb(255, 100, 100)
+                                } else {
+                                    ui.visuals().text_color()
+                                };
+                                ui.label(record.timestamp.format("%H:%M:%S%.3f").to_string());
+                                ui.label(record.node_id.to_string());
+                                ui.colored_label(color, format!("0x{:04X}", message.error_code));
+                                ui.colored_label(color, message.description());
+                                let bits = error_register_bits(message.error_register);
+                                let register = ui.monospace(format!("0x{:02X}", message.error_register));
+                                if !bits.is_empty() {
+                                    register.on_hover_text(bits.join(", "));
+                                }
+                                ui.monospace(hex_bytes(&message.manufacturer_data));
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+
+        self.open = open;
+    }
+
+    fn export(&mut self) {
+        let file_name = format!("emergencies_{}.csv", Local::now().format("%Y-%m-%d_%H-%M-%S"));
+        if let Some(path) = rfd::FileDialog::new().set_file_name(&file_name).save_file() {
+            match save(&path, &self.records) {
+                Ok(()) => {
+                    println!("✓ Saved {} emergencies to {:?}", self.records.len(), path);
+                    self.error = None;
+                }
+                Err(e) => self.error = Some(format!("Failed to save emergencies: {}", e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+    use canopen_common::parse_emcy_frame;
+
+    #[test]
+    fn test_emergency_csv_export() {
+        let message = parse_emcy_frame(&[0x10, 0x82, 0x11, 0xAA, 0xBB]).unwrap();
+        assert_eq!(message.error_code, 0x8210);
+        assert_eq!(message.manufacturer_data, [0xAA, 0xBB, 0, 0, 0]);
+        assert_eq!(error_register_bits(message.error_register), vec!["Generic", "Communication"]);
+        assert!(parse_emcy_frame(&[0x00, 0x00]).is_none());
+
+        let mut monitor = EmcyMonitor::new();
+        monitor.record(EmergencyRecord { timestamp: Local::now(), node_id: 5, message });
+        assert_eq!(monitor.unacknowledged(), 1);
+
+        let directory = TestDir::new("emcy");
+        let path = directory.join("emcy.csv");
+        save(&path, &monitor.records).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], "Timestamp,Node,ErrorCode,Description,ErrorRegister,RegisterBits,ManufacturerData");
+        assert!(lines[1].ends_with(",5,0x8210,PDO not processed due to length error,0x11,\"Generic, Communication\",AA BB 00 00 00"));
+    }
+}
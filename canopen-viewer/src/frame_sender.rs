@@ -0,0 +1,236 @@
+// frame_sender.rs - Composing and sending arbitrary CAN frames
+//
+// An expert tool for quick low-level experiments, like cansend: any standard or extended
+// identifier, data or remote frame, sent once or cyclically. Nothing is checked against
+// CANopen, so a frame can upset the nodes on the bus as much as one from any other tool.
+use eframe::egui::{self, Color32};
+use socketcan::{CanFrame, EmbeddedFrame};
+
+use crate::canopen::frame;
+
+/// Build a frame from the identifier (hex) and data bytes (hex, spaces allowed) as typed
+///
+/// `dlc` is only used for remote frames; data frames take it from the data.
+pub fn parse_frame(id: &str, extended: bool, remote: bool, dlc: usize, data: &str) -> Result<CanFrame, String> {
+    let id = id.trim();
+    let id = id.strip_prefix("0x").or_else(|| id.strip_prefix("0X")).unwrap_or(id);
+    let id = u32::from_str_radix(id, 16).map_err(|_| format!("Invalid identifier '{}'", id))?;
+    let max_id = if extended { libc::CAN_EFF_MASK } else { libc::CAN_SFF_MASK };
+    if id > max_id {
+        return Err(format!("Identifier 0x{:X} is above 0x{:X}", id, max_id));
+    }
+
+    let can_id = if extended { id | libc::CAN_EFF_FLAG } else { id };
+    if remote {
+        return frame::from_raw_parts(can_id | libc::CAN_RTR_FLAG, dlc.min(8), &[])
+            .ok_or_else(|| "Invalid frame".to_string());
+    }
+
+    let digits: String = data.chars().filter(|c| !c.is_whitespace()).collect();
+    if !digits.is_ascii() || !digits.len().is_multiple_of(2) {
+        return Err("Data must be whole bytes in hex".to_string());
+    }
+    let bytes = (0..digits.len() / 2)
+        .map(|i| u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| format!("Invalid data '{}'", data.trim()))?;
+    if bytes.len() > 8 {
+        return Err(format!("{} data bytes, at most 8 fit a frame", bytes.len()));
+    }
+    frame::from_raw_parts(can_id, bytes.len(), &bytes).ok_or_else(|| "Invalid frame".to_string())
+}
+
+fn describe(frame: &CanFrame) -> String {
+    let id = match frame.id() {
+        socketcan::Id::Standard(id) => format!("{:03X}", id.as_raw()),
+        socketcan::Id::Extended(id) => format!("{:08X}", id.as_raw()),
+    };
+    if frame.is_remote_frame() {
+        format!("{}#R{}", id, frame.dlc())
+    } else {
+        format!("{}#{}", id, frame.data().iter().map(|byte| format!("{:02X}", byte)).collect::<String>())
+    }
+}
+
+pub enum FrameSenderAction {
+    Send(CanFrame),
+    /// Send the frame every `interval_ms`, replacing a running transmission
+    Start { frame: CanFrame, interval_ms: u64 },
+    Stop,
+}
+
+pub struct FrameSender {
+    pub open: bool,
+    id: String,
+    extended: bool,
+    remote: bool,
+    dlc: usize,
+    data: String,
+    interval_ms: u64,
+    /// Frame of the running cyclic transmission
+    cyclic: Option<CanFrame>,
+    sent: usize,
+    status: Option<Result<String, String>>,
+}
+
+impl FrameSender {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            id: "000".to_string(),
+            extended: false,
+            remote: false,
+            dlc: 0,
+            data: String::new(),
+            interval_ms: 100,
+            cyclic: None,
+            sent: 0,
+            status: None,
+        }
+    }
+
+    pub fn transmitted(&mut self, result: Result<(), String>) {
+        match result {
+            Ok(()) => {
+                self.sent += 1;
+                self.status = Some(Ok(format!("{} frames sent", self.sent)));
+            }
+            Err(err) => self.status = Some(Err(err)),
+        }
+    }
+
+    /// The cyclic transmission ended because a frame couldn't be sent
+    pub fn stopped(&mut self, error: String) {
+        self.cyclic = None;
+        self.status = Some(Err(error));
+    }
+
+    /// Forget the running transmission, e.g. after disconnecting
+    pub fn reset(&mut self) {
+        self.cyclic = None;
+        self.status = None;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, connected: bool) -> Option<FrameSenderAction> {
+        let mut action = None;
+        let mut open = self.open;
+
+        egui::Window::new("Send CAN Frame")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.colored_label(Color32::from_rgb(255, 165, 0), "Frames go to the bus unchecked.");
+                egui::Grid::new("frame_sender_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Identifier (hex):");
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut self.id).desired_width(90.0).font(egui::TextStyle::Monospace));
+                        ui.checkbox(&mut self.extended, "Extended (29 bit)");
+                    });
+                    ui.end_row();
+
+                    ui.label("Type:");
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut self.remote, false, "Data");
+                        ui.radio_value(&mut self.remote, true, "Remote request");
+                    });
+                    ui.end_row();
+
+                    if self.remote {
+                        ui.label("DLC:");
+                        ui.add(egui::DragValue::new(&mut self.dlc).range(0..=8));
+                    } else {
+                        ui.label("Data (hex):");
+                        ui.add(egui::TextEdit::singleline(&mut self.data).hint_text("11 22 33").desired_width(200.0).font(egui::TextStyle::Monospace));
+                    }
+                    ui.end_row();
+                });
+
+                let frame = parse_frame(&self.id, self.extended, self.remote, self.dlc, &self.data);
+                match &frame {
+                    Ok(frame) => {
+                        ui.monospace(describe(frame));
+                    }
+                    Err(err) => {
+                        ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+                    }
+                }
+                ui.separator();
+
+                let frame = frame.ok();
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(connected && frame.is_some(), egui::Button::new("📤 Send once")).clicked() {
+                        action = frame.map(FrameSenderAction::Send);
+                    }
+                    ui.separator();
+
+                    if self.cyclic.is_some() {
+                        ui.spinner();
+                        ui.label(format!("Sending every {} ms", self.interval_ms));
+                        if ui.button("⏹ Stop").clicked() {
+                            self.cyclic = None;
+                            action = Some(FrameSenderAction::Stop);
+                        }
+                    } else {
+                        ui.add(egui::DragValue::new(&mut self.interval_ms).range(1..=60_000).suffix(" ms"));
+                        if ui.add_enabled(connected && frame.is_some(), egui::Button::new("▶ Send cyclic")).clicked() {
+                            if let Some(frame) = frame {
+                                self.cyclic = Some(frame);
+                                self.status = None;
+                                action = Some(FrameSenderAction::Start { frame, interval_ms: self.interval_ms });
+                            }
+                        }
+                    }
+                });
+
+                // Edits while sending cyclically take effect right away
+                if let (Some(sending), Some(frame)) = (self.cyclic, frame) {
+                    if describe(&sending) != describe(&frame) {
+                        self.cyclic = Some(frame);
+                        action = Some(FrameSenderAction::Start { frame, interval_ms: self.interval_ms });
+                    }
+                }
+
+                match &self.status {
+                    Some(Ok(message)) => {
+                        ui.colored_label(Color32::from_rgb(0, 200, 0), message);
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+                    }
+                    None => {}
+                }
+            });
+
+        // Don't keep sending without the window that stops it
+        if !open && self.cyclic.take().is_some() {
+            action = Some(FrameSenderAction::Stop);
+        }
+        self.open = open;
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_frame() {
+        let frame = parse_frame("0x604", false, false, 0, "40 00 10 00").unwrap();
+        assert_eq!(describe(&frame), "604#40001000");
+        assert_eq!(frame.dlc(), 4);
+
+        let frame = parse_frame("18FF1234", true, false, 0, "aabb").unwrap();
+        assert_eq!(describe(&frame), "18FF1234#AABB");
+
+        let frame = parse_frame("704", false, true, 1, "ignored").unwrap();
+        assert!(frame.is_remote_frame());
+        assert_eq!(describe(&frame), "704#R1");
+
+        assert!(parse_frame("800", false, false, 0, "").is_err());
+        assert!(parse_frame("800", true, false, 0, "").is_ok());
+        assert!(parse_frame("181", false, false, 0, "1").is_err());
+        assert!(parse_frame("181", false, false, 0, "00 11 22 33 44 55 66 77 88").is_err());
+        assert!(parse_frame("xyz", false, false, 0, "").is_err());
+    }
+}
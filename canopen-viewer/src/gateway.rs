@@ -0,0 +1,336 @@
+// gateway.rs - CiA 309-3 ASCII gateway server
+//
+// Other tools and scripts reach the bus through the viewer by sending one request per
+// line over TCP, e.g. "[1] 4 read 0x2000 1 u32", and get "[1] 1234" back. Requests
+// name an optional network (only 1) and node; without a node the one set with
+// "set node", or the node the viewer monitors, is used. SDO, NMT and node selection
+// are supported; anything else is answered with error 100.
+use canopen_common::{NmtCommand, SdoDataType, SdoError, SdoRequest, SdoResponseData, SdoWriteRequest};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio::task::JoinSet;
+
+use crate::canopen::{CANopenConnection, CANopenError, CANopenNodeHandle};
+use crate::communication::Update;
+use crate::queue::Sender;
+use crate::sdo_write::WriteValue;
+
+/// Port of the gateway unless configured otherwise
+pub const DEFAULT_GATEWAY_PORT: u16 = 60000;
+
+/// The only network the gateway serves
+const NETWORK: u8 = 1;
+
+/// CiA 309-3 error responses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GatewayError {
+    NotSupported,
+    Syntax,
+    /// Request failed for a reason other than an abort or timeout
+    NotProcessed,
+    Timeout,
+    UnsupportedNet,
+    UnsupportedNode,
+    SdoAbort(u32),
+}
+
+impl fmt::Display for GatewayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotSupported => write!(f, "ERROR:100"),
+            Self::Syntax => write!(f, "ERROR:101"),
+            Self::NotProcessed => write!(f, "ERROR:102"),
+            Self::Timeout => write!(f, "ERROR:103"),
+            Self::UnsupportedNet => write!(f, "ERROR:106"),
+            Self::UnsupportedNode => write!(f, "ERROR:107"),
+            Self::SdoAbort(code) => write!(f, "ERROR:0x{:08X}", code),
+        }
+    }
+}
+
+impl From<CANopenError> for GatewayError {
+    fn from(error: CANopenError) -> Self {
+        match error {
+            CANopenError::Sdo(SdoError::AbortTransfer { code, .. }) => Self::SdoAbort(code),
+            CANopenError::Sdo(SdoError::Timeout) => Self::Timeout,
+            _ => Self::NotProcessed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GatewayRequest {
+    Read { node_id: u8, index: u16, sub_index: u8, data_type: SdoDataType },
+    Write { node_id: u8, index: u16, sub_index: u8, data: Vec<u8> },
+    /// Node 0 addresses all nodes
+    Nmt { node_id: u8, command: NmtCommand },
+    /// Default node of the client's later requests
+    SetNode(u8),
+}
+
+fn parse_number(token: &str) -> Option<u32> {
+    match token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => token.parse().ok(),
+    }
+}
+
+/// Data type of a CiA 309 type name; 64-bit and time types aren't supported by the viewer
+fn parse_data_type(token: &str) -> Result<SdoDataType, GatewayError> {
+    match token {
+        "b" | "u8" => Ok(SdoDataType::UInt8),
+        "u16" => Ok(SdoDataType::UInt16),
+        "u32" => Ok(SdoDataType::UInt32),
+        "i8" => Ok(SdoDataType::Int8),
+        "i16" => Ok(SdoDataType::Int16),
+        "i32" => Ok(SdoDataType::Int32),
+        "r32" => Ok(SdoDataType::Real32),
+        "vs" => Ok(SdoDataType::VisibleString),
+        "os" => Ok(SdoDataType::OctetString),
+        "i64" | "u64" | "r64" | "t" | "td" | "us" | "d" => Err(GatewayError::NotSupported),
+        _ => Err(GatewayError::Syntax),
+    }
+}
+
+fn node_id(value: u32, broadcast_allowed: bool) -> Result<u8, GatewayError> {
+    match value {
+        1..=127 => Ok(value as u8),
+        0 if broadcast_allowed => Ok(0),
+        _ => Err(GatewayError::UnsupportedNode),
+    }
+}
+
+/// Split a request line into its sequence number and the request
+///
+/// The sequence is returned even for invalid requests so the error can be matched to
+/// the request; it is empty if the line doesn't start with one.
+pub fn parse_request(line: &str, default_node: u8) -> (String, Result<GatewayRequest, GatewayError>) {
+    let line = line.trim();
+    let Some((sequence, rest)) = line.strip_prefix('[').and_then(|rest| rest.split_once(']')) else {
+        return (String::new(), Err(GatewayError::Syntax));
+    };
+    let sequence = sequence.trim().to_string();
+    (sequence, parse_command(rest, default_node))
+}
+
+fn parse_command(text: &str, default_node: u8) -> Result<GatewayRequest, GatewayError> {
+    let mut tokens: Vec<&str> = text.split_whitespace().collect();
+
+    // Up to two leading numbers: [[net] node]
+    let numbers = tokens.iter().take(2).take_while(|token| parse_number(token).is_some()).count();
+    let addressing: Vec<u32> = tokens.drain(..numbers).filter_map(parse_number).collect();
+    let (net, node) = match addressing[..] {
+        [net, node] => (net, Some(node)),
+        [node] => (NETWORK as u32, Some(node)),
+        _ => (NETWORK as u32, None),
+    };
+    if net != NETWORK as u32 {
+        return Err(GatewayError::UnsupportedNet);
+    }
+    let node = node.unwrap_or(default_node as u32);
+
+    let lowercase: Vec<String> = tokens.iter().map(|token| token.to_ascii_lowercase()).collect();
+    let words: Vec<&str> = lowercase.iter().map(String::as_str).collect();
+    let object = |index: &str, sub_index: &str| -> Result<(u16, u8), GatewayError> {
+        let index = parse_number(index).and_then(|index| u16::try_from(index).ok()).ok_or(GatewayError::Syntax)?;
+        let sub_index = parse_number(sub_index).and_then(|sub| u8::try_from(sub).ok()).ok_or(GatewayError::Syntax)?;
+        Ok((index, sub_index))
+    };
+
+    match words[..] {
+        ["read" | "r", index, sub_index, data_type] => {
+            let (index, sub_index) = object(index, sub_index)?;
+            Ok(GatewayRequest::Read { node_id: node_id(node, false)?, index, sub_index, data_type: parse_data_type(data_type)? })
+        }
+        ["write" | "w", index, sub_index, data_type, _, ..] => {
+            let (index, sub_index) = object(index, sub_index)?;
+            let data_type = parse_data_type(data_type)?;
+            // Strings may contain spaces and come in quotes; octet strings are hex bytes
+            let value = tokens[4..].join(" ");
+            let value = WriteValue::parse(value.trim_matches('"'), &data_type).map_err(|_| GatewayError::Syntax)?;
+            // Out of range, or longer than an expedited transfer
+            let data = value.encode(&data_type).map_err(|_| GatewayError::NotProcessed)?;
+            Ok(GatewayRequest::Write { node_id: node_id(node, false)?, index, sub_index, data })
+        }
+        ["start"] => Ok(GatewayRequest::Nmt { node_id: node_id(node, true)?, command: NmtCommand::Start }),
+        ["stop"] => Ok(GatewayRequest::Nmt { node_id: node_id(node, true)?, command: NmtCommand::Stop }),
+        ["preop" | "preoperational"] => {
+            Ok(GatewayRequest::Nmt { node_id: node_id(node, true)?, command: NmtCommand::EnterPreOperational })
+        }
+        ["reset", "node"] => Ok(GatewayRequest::Nmt { node_id: node_id(node, true)?, command: NmtCommand::ResetNode }),
+        ["reset", "comm" | "communication"] => {
+            Ok(GatewayRequest::Nmt { node_id: node_id(node, true)?, command: NmtCommand::ResetCommunication })
+        }
+        ["set", "node", value] => {
+            let value = parse_number(value).ok_or(GatewayError::Syntax)?;
+            Ok(GatewayRequest::SetNode(node_id(value, false)?))
+        }
+        ["read" | "r" | "write" | "w" | "set" | "reset", ..] => Err(GatewayError::Syntax),
+        _ => Err(GatewayError::NotSupported),
+    }
+}
+
+/// Value of a read response as the gateway prints it
+fn format_value(data: &SdoResponseData) -> String {
+    match data {
+        SdoResponseData::Bytes(bytes) => bytes.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" "),
+        SdoResponseData::String(text) => format!("\"{}\"", text),
+        other => other.to_string(),
+    }
+}
+
+/// Response line to a request
+pub fn format_response(sequence: &str, result: &Result<String, GatewayError>) -> String {
+    match result {
+        Ok(value) => format!("[{}] {}", sequence, value),
+        Err(error) => format!("[{}] {}", sequence, error),
+    }
+}
+
+/// Handles of the nodes addressed so far, starting with the monitored one
+type NodeHandles = Arc<Mutex<HashMap<u8, CANopenNodeHandle>>>;
+
+async fn node_handle(nodes: &NodeHandles, conn: &CANopenConnection, node_id: u8) -> Result<CANopenNodeHandle, GatewayError> {
+    let mut nodes = nodes.lock().await;
+    if let Some(handle) = nodes.get(&node_id) {
+        return Ok(handle.clone());
+    }
+    let handle = conn.add_node(node_id, None).await?;
+    nodes.insert(node_id, handle.clone());
+    Ok(handle)
+}
+
+async fn execute(request: GatewayRequest, nodes: &NodeHandles, conn: &CANopenConnection) -> Result<String, GatewayError> {
+    match request {
+        GatewayRequest::Read { node_id, index, sub_index, data_type } => {
+            let handle = node_handle(nodes, conn, node_id).await?;
+            let response = handle.sdo_read(SdoRequest { node_id, index, subindex: sub_index, expected_type: data_type }).await?;
+            Ok(format_value(&response.data))
+        }
+        GatewayRequest::Write { node_id, index, sub_index, data } => {
+            let handle = node_handle(nodes, conn, node_id).await?;
+            handle.sdo_write(SdoWriteRequest { node_id, index, subindex: sub_index, data }).await?;
+            Ok("OK".to_string())
+        }
+        GatewayRequest::Nmt { node_id, command } => {
+            conn.send_nmt(node_id, command).await?;
+            Ok("OK".to_string())
+        }
+        GatewayRequest::SetNode(_) => Ok("OK".to_string()),
+    }
+}
+
+async fn handle_client(stream: TcpStream, nodes: NodeHandles, conn: CANopenConnection, mut default_node: u8) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (sequence, request) = parse_request(&line, default_node);
+        let result = match request {
+            Ok(GatewayRequest::SetNode(node_id)) => {
+                default_node = node_id;
+                Ok("OK".to_string())
+            }
+            Ok(request) => execute(request, &nodes, &conn).await,
+            Err(error) => Err(error),
+        };
+        writer.write_all(format_response(&sequence, &result).as_bytes()).await?;
+        writer.write_all(b"\r\n").await?;
+    }
+    Ok(())
+}
+
+/// Accept gateway clients until the task is aborted; their sessions end with it
+pub async fn serve(listener: TcpListener, conn: CANopenConnection, monitored: CANopenNodeHandle, update_tx: Sender<Update>) {
+    let default_node = monitored.node_id();
+    let nodes: NodeHandles = Arc::new(Mutex::new(HashMap::from([(default_node, monitored)])));
+    let mut clients = JoinSet::new();
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, peer)) => {
+                    println!("Gateway client connected from {}", peer);
+                    clients.spawn(handle_client(stream, nodes.clone(), conn.clone(), default_node));
+                }
+                Err(err) => {
+                    let _ = update_tx.send(Update::GatewayStopped(err.to_string()));
+                    return;
+                }
+            },
+            Some(_) = clients.join_next() => {
+                println!("Gateway client disconnected");
+            }
+        }
+        let _ = update_tx.send(Update::GatewayClients(clients.len()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_read_requests() {
+        let (sequence, request) = parse_request("[1] 4 read 0x2000 1 u32", 5);
+        assert_eq!(sequence, "1");
+        assert_eq!(request, Ok(GatewayRequest::Read { node_id: 4, index: 0x2000, sub_index: 1, data_type: SdoDataType::UInt32 }));
+        // Network and node, or neither
+        assert_eq!(parse_request("[2] 1 4 r 0x1017 0 u16", 5).1, parse_request("[2] 4 r 0x1017 0 u16", 5).1);
+        assert!(matches!(parse_request("[3] read 0x1017 0 u16", 5).1, Ok(GatewayRequest::Read { node_id: 5, .. })));
+    }
+
+    #[test]
+    fn test_parse_write_requests() {
+        assert_eq!(
+            parse_request("[5] 4 write 0x6040 0 i16 -2", 5).1,
+            Ok(GatewayRequest::Write { node_id: 4, index: 0x6040, sub_index: 0, data: vec![0xFE, 0xFF] })
+        );
+        assert!(matches!(
+            parse_request("[6] write 0x2001 0 vs \"a b\"", 5).1,
+            Ok(GatewayRequest::Write { ref data, .. }) if data == b"a b"
+        ));
+        assert_eq!(parse_request("[6] write 0x2001 0 u8 300", 5).1, Err(GatewayError::NotProcessed));
+    }
+
+    #[test]
+    fn test_parse_nmt_and_node_requests() {
+        assert_eq!(parse_request("[7] 0 reset comm", 5).1, Ok(GatewayRequest::Nmt { node_id: 0, command: NmtCommand::ResetCommunication }));
+        assert_eq!(parse_request("[11] set node 9", 5).1, Ok(GatewayRequest::SetNode(9)));
+    }
+
+    #[test]
+    fn test_rejected_requests() {
+        assert_eq!(parse_request("[4] 2 4 start", 5).1, Err(GatewayError::UnsupportedNet));
+        assert_eq!(parse_request("[8] 0 read 0x1000 0 u32", 5).1, Err(GatewayError::UnsupportedNode));
+        assert_eq!(parse_request("[9] read 0x1000 0 u64", 5).1, Err(GatewayError::NotSupported));
+        assert_eq!(parse_request("[10] read 0x1000", 5).1, Err(GatewayError::Syntax));
+        assert_eq!(parse_request("read 0x1000 0 u32", 5), (String::new(), Err(GatewayError::Syntax)));
+    }
+
+    #[test]
+    fn test_format_response() {
+        assert_eq!(format_response("1", &Ok("1234".to_string())), "[1] 1234");
+        assert_eq!(format_response("2", &Err(GatewayError::SdoAbort(0x0602_0000))), "[2] ERROR:0x06020000");
+        assert_eq!(format_response("3", &Err(GatewayError::Timeout)), "[3] ERROR:103");
+    }
+
+    #[test]
+    fn test_errors_map_by_variant() {
+        let abort = CANopenError::from(SdoError::AbortTransfer { code: 0x0602_0000, info: "Object does not exist".to_string() });
+        assert_eq!(GatewayError::from(abort), GatewayError::SdoAbort(0x0602_0000));
+        assert_eq!(GatewayError::from(CANopenError::from(SdoError::Timeout)), GatewayError::Timeout);
+        // The text of other errors is not searched for abort codes or timeouts
+        let socket = CANopenError::SocketError("SDO abort 0x06020000 after timeout".to_string());
+        assert_eq!(GatewayError::from(socket), GatewayError::NotProcessed);
+        let invalid = CANopenError::from(SdoError::InvalidResponse("unexpected toggle".to_string()));
+        assert_eq!(GatewayError::from(invalid), GatewayError::NotProcessed);
+    }
+}
@@ -0,0 +1,192 @@
+// gauge.rs - Dial showing the latest value of a subscription
+//
+// Slowly changing values like a temperature or the bus voltage say little as a line plot
+// but are read at a glance on a dial. A gauge replaces the plot of its series on the
+// dashboard; its range and the warning zones below and above given limits are set from
+// its context menu and kept with the dashboard.
+use eframe::egui::{self, Align2, Color32, FontId, Pos2, Sense, Shape, Stroke};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+use crate::overlay::PlotSeries;
+
+const DIAL_WIDTH: f32 = 180.0;
+const DIAL_HEIGHT: f32 = 150.0;
+/// Angle the scale covers, open at the bottom
+const SWEEP: f32 = 1.5 * PI;
+const WARNING_COLOR: Color32 = Color32::from_rgb(255, 165, 0);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GaugeSettings {
+    pub series: PlotSeries,
+    pub min: f64,
+    pub max: f64,
+    #[serde(default)]
+    pub warn_below: Option<f64>,
+    #[serde(default)]
+    pub warn_above: Option<f64>,
+}
+
+impl GaugeSettings {
+    /// A gauge whose range takes in the values recorded so far, starting from zero
+    pub fn new(series: PlotSeries, points: &[[f64; 2]]) -> Self {
+        let low = points.iter().map(|point| point[1]).fold(f64::INFINITY, f64::min);
+        let high = points.iter().map(|point| point[1]).fold(f64::NEG_INFINITY, f64::max);
+        let (min, max) = if points.is_empty() {
+            (0.0, 100.0)
+        } else {
+            let min = if low >= 0.0 { 0.0 } else { (low * 1.2).floor() };
+            let max = if high <= 0.0 { 0.0 } else { (high * 1.2).ceil() };
+            (min, if max > min { max } else { min + 1.0 })
+        };
+        Self { series, min, max, warn_below: None, warn_above: None }
+    }
+
+    /// Position of `value` on the scale, clamped to its ends
+    pub fn fraction(&self, value: f64) -> f32 {
+        if self.max <= self.min {
+            return 0.0;
+        }
+        ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0) as f32
+    }
+
+    pub fn warning(&self, value: f64) -> bool {
+        self.warn_below.is_some_and(|limit| value < limit) || self.warn_above.is_some_and(|limit| value > limit)
+    }
+
+    /// Value ranges drawn as warning zones on the scale
+    fn zones(&self) -> Vec<(f64, f64)> {
+        let mut zones = Vec::new();
+        if let Some(limit) = self.warn_below {
+            zones.push((self.min, limit));
+        }
+        if let Some(limit) = self.warn_above {
+            zones.push((limit, self.max));
+        }
+        zones
+    }
+}
+
+pub enum GaugeAction {
+    /// Range or warning limits were edited
+    Changed,
+    /// Show the series as a plot again
+    Remove,
+}
+
+fn point_at(center: Pos2, radius: f32, fraction: f32) -> Pos2 {
+    let angle = 1.25 * PI - SWEEP * fraction;
+    center + radius * egui::vec2(angle.cos(), -angle.sin())
+}
+
+fn arc(center: Pos2, radius: f32, from: f32, to: f32, stroke: Stroke) -> Shape {
+    let steps = ((to - from) * 48.0).ceil().max(1.0) as usize;
+    let points = (0..=steps)
+        .map(|step| point_at(center, radius, from + (to - from) * step as f32 / steps as f32))
+        .collect();
+    Shape::line(points, stroke)
+}
+
+/// Checkbox and value of an optional warning limit; true when edited
+fn optional_limit(ui: &mut egui::Ui, label: &str, limit: &mut Option<f64>, default: f64) -> bool {
+    let mut enabled = limit.is_some();
+    let mut value = limit.unwrap_or(default);
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        changed |= ui.checkbox(&mut enabled, label).changed();
+        changed |= ui.add_enabled(enabled, egui::DragValue::new(&mut value).speed(0.1)).changed();
+    });
+    *limit = enabled.then_some(value);
+    changed
+}
+
+/// Draw one gauge with its title; right-click it for the settings
+pub fn show(ui: &mut egui::Ui, settings: &mut GaugeSettings, title: &str, value: Option<f64>, unit: &str) -> Option<GaugeAction> {
+    let mut action = None;
+
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.set_width(DIAL_WIDTH);
+        ui.vertical_centered(|ui| {
+            ui.label(title);
+        });
+
+        let (rect, response) = ui.allocate_exact_size(egui::vec2(DIAL_WIDTH, DIAL_HEIGHT), Sense::click());
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        let center = rect.center() + egui::vec2(0.0, 10.0);
+        let radius = DIAL_HEIGHT * 0.45;
+
+        painter.add(arc(center, radius, 0.0, 1.0, Stroke::new(10.0, visuals.widgets.inactive.bg_fill)));
+        for (from, to) in settings.zones() {
+            let (from, to) = (settings.fraction(from), settings.fraction(to));
+            if to > from {
+                painter.add(arc(center, radius, from, to, Stroke::new(10.0, WARNING_COLOR)));
+            }
+        }
+        let small = FontId::proportional(10.0);
+        painter.text(point_at(center, radius, 0.0) + egui::vec2(0.0, 14.0), Align2::CENTER_CENTER, format!("{}", settings.min), small.clone(), visuals.weak_text_color());
+        painter.text(point_at(center, radius, 1.0) + egui::vec2(0.0, 14.0), Align2::CENTER_CENTER, format!("{}", settings.max), small, visuals.weak_text_color());
+
+        let text = match value {
+            Some(value) => {
+                let color = if settings.warning(value) { WARNING_COLOR } else { visuals.strong_text_color() };
+                painter.line_segment([center, point_at(center, radius - 14.0, settings.fraction(value))], Stroke::new(3.0, color));
+                painter.circle_filled(center, 5.0, color);
+                format!("{:.2} {}", value, unit)
+            }
+            None => "—".to_string(),
+        };
+        painter.text(center + egui::vec2(0.0, radius * 0.55), Align2::CENTER_CENTER, text.trim_end(), FontId::proportional(18.0), visuals.text_color());
+
+        response.on_hover_text("Right-click to set the range and warning zones").context_menu(|ui| {
+            let mut changed = false;
+            egui::Grid::new("gauge_settings").num_columns(2).show(ui, |ui| {
+                ui.label("Min:");
+                changed |= ui.add(egui::DragValue::new(&mut settings.min).speed(0.1)).changed();
+                ui.end_row();
+                ui.label("Max:");
+                changed |= ui.add(egui::DragValue::new(&mut settings.max).speed(0.1)).changed();
+                ui.end_row();
+            });
+            let (min, max) = (settings.min, settings.max);
+            changed |= optional_limit(ui, "Warn below", &mut settings.warn_below, min);
+            changed |= optional_limit(ui, "Warn above", &mut settings.warn_above, max);
+            if changed {
+                action = Some(GaugeAction::Changed);
+            }
+            ui.separator();
+            if ui.button("📈 Show as plot").clicked() {
+                action = Some(GaugeAction::Remove);
+                ui.close_menu();
+            }
+        });
+    });
+
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::communication::SdoAddress;
+
+    #[test]
+    fn test_gauge_range_and_warnings() {
+        let series = PlotSeries::Sdo(SdoAddress { index: 0x2001, sub_index: 0 });
+        let mut gauge = GaugeSettings::new(series.clone(), &[[0.0, 21.5], [1.0, 48.0]]);
+        assert_eq!((gauge.min, gauge.max), (0.0, 58.0));
+        assert_eq!(GaugeSettings::new(series.clone(), &[]).max, 100.0);
+        assert_eq!(GaugeSettings::new(series.clone(), &[[0.0, -10.0], [1.0, -2.0]]).min, -12.0);
+
+        assert_eq!(gauge.fraction(29.0), 0.5);
+        assert_eq!(gauge.fraction(-5.0), 0.0);
+        assert_eq!(gauge.fraction(80.0), 1.0);
+
+        gauge.warn_above = Some(50.0);
+        assert!(!gauge.warning(48.0));
+        assert!(gauge.warning(51.0));
+        gauge.warn_below = Some(5.0);
+        assert!(gauge.warning(4.0));
+        assert_eq!(gauge.zones(), vec![(0.0, 5.0), (50.0, 58.0)]);
+    }
+}
@@ -0,0 +1,169 @@
+// histogram.rs - Distribution of a channel's recent samples
+//
+// Characterizing the noise of an analog sensor object needs its spread rather than its
+// course over time. The histogram bins the samples of the last few seconds and marks the
+// mean and one standard deviation either side, with the numbers next to the plot.
+use eframe::egui::{self, Color32};
+use egui_plot::{Bar, BarChart, Plot, VLine};
+use serde::{Deserialize, Serialize};
+
+use crate::overlay::PlotSeries;
+
+const BAR_COLOR: Color32 = Color32::from_rgb(100, 149, 237);
+const MEAN_COLOR: Color32 = Color32::from_rgb(255, 165, 0);
+
+fn default_window_seconds() -> f64 {
+    10.0
+}
+
+fn default_bins() -> usize {
+    30
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistogramSettings {
+    pub series: PlotSeries,
+    /// Samples of this many seconds before the latest one are counted
+    #[serde(default = "default_window_seconds")]
+    pub window_seconds: f64,
+    #[serde(default = "default_bins")]
+    pub bins: usize,
+}
+
+impl HistogramSettings {
+    pub fn new(series: PlotSeries) -> Self {
+        Self { series, window_seconds: default_window_seconds(), bins: default_bins() }
+    }
+
+    /// Values of the points within the window before the latest one
+    pub fn window(&self, points: &[[f64; 2]]) -> Vec<f64> {
+        let Some(latest) = points.last().map(|point| point[0]) else {
+            return Vec::new();
+        };
+        points.iter()
+            .filter(|point| point[0] >= latest - self.window_seconds)
+            .map(|point| point[1])
+            .collect()
+    }
+}
+
+/// Mean and standard deviation (population) of the values
+pub fn mean_and_deviation(values: &[f64]) -> Option<(f64, f64)> {
+    if values.is_empty() {
+        return None;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    Some((mean, variance.sqrt()))
+}
+
+/// Centre, width and count of each bin spanning the values; a single bin when they're all equal
+pub fn bins(values: &[f64], bins: usize) -> Vec<(f64, f64, usize)> {
+    let (Some(min), Some(max)) = (
+        values.iter().copied().reduce(f64::min),
+        values.iter().copied().reduce(f64::max),
+    ) else {
+        return Vec::new();
+    };
+    if max == min || bins <= 1 {
+        return vec![(min, 1.0, values.len())];
+    }
+
+    let width = (max - min) / bins as f64;
+    let mut counts = vec![0; bins];
+    for value in values {
+        // The maximum belongs to the last bin
+        let bin = (((value - min) / width) as usize).min(bins - 1);
+        counts[bin] += 1;
+    }
+    counts.into_iter()
+        .enumerate()
+        .map(|(bin, count)| (min + (bin as f64 + 0.5) * width, width, count))
+        .collect()
+}
+
+pub enum HistogramAction {
+    /// Window or bin count changed
+    Changed,
+    Remove,
+}
+
+/// `points` are the recorded samples of the series, `value_label` names its axis
+pub fn show(ui: &mut egui::Ui, id: usize, settings: &mut HistogramSettings, name: &str, value_label: &str, points: &[[f64; 2]]) -> Option<HistogramAction> {
+    let mut action = None;
+    let values = settings.window(points);
+    let statistics = mean_and_deviation(&values);
+
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(format!("Histogram - {}", name));
+            if let Some((mean, deviation)) = statistics {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(format!("n = {}   mean = {:.4}   σ = {:.4}", values.len(), mean, deviation));
+                });
+            }
+        });
+        ui.separator();
+
+        let chart = BarChart::new(
+            bins(&values, settings.bins).into_iter()
+                .map(|(center, width, count)| Bar::new(center, count as f64).width(width).fill(BAR_COLOR))
+                .collect(),
+        )
+        .color(BAR_COLOR);
+        Plot::new(format!("histogram_{}", id))
+            .height(250.0)
+            .width(ui.available_width())
+            .allow_scroll(false)
+            .x_axis_label(value_label)
+            .y_axis_label("Samples")
+            .show(ui, |plot_ui| {
+                plot_ui.bar_chart(chart);
+                if let Some((mean, deviation)) = statistics {
+                    plot_ui.vline(VLine::new(mean).color(MEAN_COLOR).width(2.0).name("Mean"));
+                    for marker in [mean - deviation, mean + deviation] {
+                        plot_ui.vline(VLine::new(marker).color(MEAN_COLOR).style(egui_plot::LineStyle::dashed_loose()).name("±σ"));
+                    }
+                }
+            });
+
+        ui.horizontal(|ui| {
+            ui.label("Window:");
+            let mut changed = ui.add(egui::DragValue::new(&mut settings.window_seconds).range(0.1..=3600.0).suffix(" s")).changed();
+            ui.label("Bins:");
+            changed |= ui.add(egui::DragValue::new(&mut settings.bins).range(2..=200)).changed();
+            if changed {
+                action = Some(HistogramAction::Changed);
+            }
+            if ui.button("🗑 Remove").clicked() {
+                action = Some(HistogramAction::Remove);
+            }
+        });
+    });
+
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::communication::SdoAddress;
+
+    #[test]
+    fn test_histogram_window_bins_and_statistics() {
+        let mut settings = HistogramSettings::new(PlotSeries::Sdo(SdoAddress { index: 0x6401, sub_index: 1 }));
+        settings.window_seconds = 2.0;
+        let points = [[0.0, 100.0], [1.0, 2.0], [2.0, 4.0], [3.0, 4.0], [3.0, 6.0]];
+        let values = settings.window(&points);
+        assert_eq!(values, vec![2.0, 4.0, 4.0, 6.0]);
+
+        let (mean, deviation) = mean_and_deviation(&values).unwrap();
+        assert_eq!(mean, 4.0);
+        assert!((deviation - 2.0_f64.sqrt()).abs() < 1e-12);
+        assert_eq!(mean_and_deviation(&[]), None);
+
+        assert_eq!(bins(&values, 2), vec![(3.0, 2.0, 1), (5.0, 2.0, 3)]);
+        assert_eq!(bins(&[1.5, 1.5], 10), vec![(1.5, 1.0, 2)]);
+        assert!(bins(&[], 10).is_empty());
+    }
+}
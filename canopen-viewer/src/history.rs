@@ -0,0 +1,278 @@
+// history.rs - Complete sample history of a subscription
+//
+// The plot buffer keeps only the latest samples so drawing stays cheap. The history keeps
+// every sample of the run in blocks of BLOCK_SAMPLES. Beyond the configured number of
+// samples in memory, full blocks are written to a temporary file and only their time span
+// and extremes stay in memory. Those summaries are enough to draw hours of data zoomed
+// out; the blocks in view are read back once the user zooms in. Exports read it all.
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::config::HistoryConfig;
+use crate::decimation;
+
+/// Samples per block; a block on disk takes 64 KiB
+const BLOCK_SAMPLES: usize = 4096;
+const SAMPLE_BYTES: usize = 16;
+/// Blocks read back per frame at most; with more in view their summaries are drawn
+const MAX_READ_BLOCKS: usize = 8;
+
+/// Numbers the spill files of this process; sanitized names alone can collide
+static SPILL_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug)]
+struct Block {
+    first: f64,
+    last: f64,
+    /// Samples with the smallest and largest value
+    min: [f64; 2],
+    max: [f64; 2],
+    len: usize,
+    /// The samples while in memory; None once written to the spill file at `offset`
+    samples: Option<Vec<[f64; 2]>>,
+    offset: u64,
+}
+
+impl Block {
+    fn new(samples: Vec<[f64; 2]>) -> Self {
+        let min = samples.iter().copied().reduce(|a, b| if b[1] < a[1] { b } else { a }).unwrap_or_default();
+        let max = samples.iter().copied().reduce(|a, b| if b[1] > a[1] { b } else { a }).unwrap_or_default();
+        Self {
+            first: samples.first().map_or(0.0, |point| point[0]),
+            last: samples.last().map_or(0.0, |point| point[0]),
+            min,
+            max,
+            len: samples.len(),
+            samples: Some(samples),
+            offset: 0,
+        }
+    }
+
+    /// The extremes in time order, standing in for the block when zoomed out
+    fn summary(&self) -> [[f64; 2]; 2] {
+        if self.min[0] <= self.max[0] { [self.min, self.max] } else { [self.max, self.min] }
+    }
+}
+
+#[derive(Debug)]
+pub struct SampleHistory {
+    /// Full blocks, oldest first
+    blocks: Vec<Block>,
+    /// The block being filled
+    current: Vec<[f64; 2]>,
+    /// Spill file, created with the first block written to disk
+    spill_path: Option<PathBuf>,
+    spill: Option<File>,
+    /// A write to the spill file failed, the rest stays in memory; the file is still removed
+    spill_failed: bool,
+    memory_samples: usize,
+    len: usize,
+}
+
+impl SampleHistory {
+    /// `name` identifies the subscription in the spill file name
+    pub fn new(config: &HistoryConfig, name: &str) -> Self {
+        let spill_path = config.spill_to_disk.then(|| {
+            let name: String = name.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+            let number = SPILL_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+            std::env::temp_dir().join("canopen-viewer-history").join(format!("{}_{}_{}.bin", std::process::id(), number, name))
+        });
+        Self { blocks: Vec::new(), current: Vec::new(), spill_path, spill: None, spill_failed: false, memory_samples: config.memory_samples, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn push(&mut self, point: [f64; 2]) {
+        self.current.push(point);
+        self.len += 1;
+        if self.current.len() < BLOCK_SAMPLES {
+            return;
+        }
+        self.blocks.push(Block::new(std::mem::take(&mut self.current)));
+
+        // Oldest blocks first go to disk
+        let mut in_memory: usize = self.blocks.iter().filter(|block| block.samples.is_some()).map(|block| block.len).sum();
+        for index in 0..self.blocks.len() {
+            if in_memory <= self.memory_samples || self.spill_path.is_none() || self.spill_failed {
+                break;
+            }
+            if self.blocks[index].samples.is_none() {
+                continue;
+            }
+            match self.write_block(index) {
+                Ok(()) => in_memory -= self.blocks[index].len,
+                Err(e) => {
+                    eprintln!("Failed to write sample history to disk, keeping it in memory: {}", e);
+                    self.spill_failed = true;
+                }
+            }
+        }
+    }
+
+    fn write_block(&mut self, index: usize) -> io::Result<()> {
+        if self.spill.is_none() {
+            let path = self.spill_path.as_ref().ok_or_else(|| io::Error::other("no spill file"))?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            self.spill = Some(OpenOptions::new().create(true).truncate(true).read(true).write(true).open(path)?);
+        }
+        let file = self.spill.as_mut().ok_or_else(|| io::Error::other("no spill file"))?;
+        let block = &mut self.blocks[index];
+        let Some(samples) = &block.samples else {
+            return Ok(());
+        };
+
+        let mut bytes = Vec::with_capacity(samples.len() * SAMPLE_BYTES);
+        for [time, value] in samples {
+            bytes.extend_from_slice(&time.to_le_bytes());
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        block.offset = file.seek(SeekFrom::End(0))?;
+        file.write_all(&bytes)?;
+        block.samples = None;
+        Ok(())
+    }
+
+    fn read_block(&self, block: &Block) -> io::Result<Vec<[f64; 2]>> {
+        if let Some(samples) = &block.samples {
+            return Ok(samples.clone());
+        }
+        let mut file = self.spill.as_ref().ok_or_else(|| io::Error::other("sample history file missing"))?;
+        let mut bytes = vec![0; block.len * SAMPLE_BYTES];
+        file.seek(SeekFrom::Start(block.offset))?;
+        file.read_exact(&mut bytes)?;
+        Ok(bytes.chunks_exact(SAMPLE_BYTES)
+            .map(|sample| {
+                let time = f64::from_le_bytes(sample[..8].try_into().unwrap_or_default());
+                let value = f64::from_le_bytes(sample[8..].try_into().unwrap_or_default());
+                [time, value]
+            })
+            .collect())
+    }
+
+    /// Every sample since the subscription started or was cleared
+    pub fn all(&self) -> io::Result<Vec<[f64; 2]>> {
+        let mut points = Vec::with_capacity(self.len);
+        for block in &self.blocks {
+            points.extend(self.read_block(block)?);
+        }
+        points.extend_from_slice(&self.current);
+        Ok(points)
+    }
+
+    /// Samples between `min_x` and `max_x` as a min/max envelope of about `buckets` buckets;
+    /// blocks are only read back when a few of them are in view
+    pub fn range(&self, min_x: f64, max_x: f64, buckets: usize) -> io::Result<Vec<[f64; 2]>> {
+        // One block more on either side, so the line runs to the edges of the plot
+        let start = self.blocks.partition_point(|block| block.last < min_x).saturating_sub(1);
+        let end = (self.blocks.partition_point(|block| block.first <= max_x) + 1).min(self.blocks.len());
+        let blocks = &self.blocks[start..end.max(start)];
+
+        let mut points = Vec::new();
+        if blocks.len() > MAX_READ_BLOCKS || 2 * blocks.len() > buckets {
+            points.extend(blocks.iter().flat_map(Block::summary));
+        } else {
+            for block in blocks {
+                points.extend(self.read_block(block)?);
+            }
+        }
+        points.extend_from_slice(&self.current);
+
+        let first = points.partition_point(|point| point[0] < min_x).saturating_sub(1);
+        let last = (points.partition_point(|point| point[0] <= max_x) + 1).min(points.len());
+        Ok(decimation::min_max(points[first..last.max(first)].iter(), buckets))
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.current.clear();
+        self.len = 0;
+        if let Some(file) = &self.spill {
+            let _ = file.set_len(0);
+        }
+    }
+}
+
+impl Drop for SampleHistory {
+    fn drop(&mut self) {
+        if let (Some(_), Some(path)) = (self.spill.take(), &self.spill_path) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_spills_to_disk_and_reads_back() {
+        let config = HistoryConfig { spill_to_disk: true, memory_samples: BLOCK_SAMPLES };
+        let mut history = SampleHistory::new(&config, "test history");
+        let total = 3 * BLOCK_SAMPLES + 10;
+        for i in 0..total {
+            history.push([i as f64, (i % 100) as f64]);
+        }
+        assert_eq!(history.len(), total);
+        assert!(history.blocks[0].samples.is_none());
+        assert!(history.blocks[2].samples.is_some());
+        let path = history.spill_path.clone().unwrap();
+        assert!(path.exists());
+
+        let all = history.all().unwrap();
+        assert_eq!(all.len(), total);
+        assert_eq!(all[5000], [5000.0, 0.0]);
+        assert_eq!(all[total - 1], [(total - 1) as f64, 97.0]);
+
+        // Zoomed in, the block on disk is read back; zoomed out, its summary is used
+        let zoomed = history.range(100.0, 120.0, 100).unwrap();
+        assert_eq!(zoomed.first(), Some(&[99.0, 99.0]));
+        assert_eq!(zoomed.len(), 23);
+        let overview = history.range(0.0, total as f64, 4).unwrap();
+        assert!(overview.len() <= 9);
+        assert!(overview.iter().any(|point| point[1] == 99.0));
+
+        history.clear();
+        assert!(history.all().unwrap().is_empty());
+        drop(history);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_spill_file_removed_after_failed_write() {
+        let config = HistoryConfig { spill_to_disk: true, memory_samples: BLOCK_SAMPLES };
+        let mut history = SampleHistory::new(&config, "failing history");
+        for i in 0..3 * BLOCK_SAMPLES {
+            history.push([i as f64, 0.0]);
+        }
+        let path = history.spill_path.clone().unwrap();
+        assert!(history.blocks[0].samples.is_none());
+
+        // A read-only handle makes the next block write fail
+        history.spill = Some(File::open(&path).unwrap());
+        for i in 3 * BLOCK_SAMPLES..5 * BLOCK_SAMPLES {
+            history.push([i as f64, 0.0]);
+        }
+        assert!(history.spill_failed);
+        assert!(history.blocks[4].samples.is_some());
+        assert_eq!(history.all().unwrap().len(), 5 * BLOCK_SAMPLES);
+        drop(history);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_spill_files_are_unique() {
+        let config = HistoryConfig { spill_to_disk: true, memory_samples: BLOCK_SAMPLES };
+        // Both sanitize to "Motor_speed"
+        let first = SampleHistory::new(&config, "Motor speed");
+        let second = SampleHistory::new(&config, "Motor/speed");
+        let third = SampleHistory::new(&config, "Motor speed");
+        assert_ne!(first.spill_path, second.spill_path);
+        assert_ne!(first.spill_path, third.spill_path);
+    }
+}
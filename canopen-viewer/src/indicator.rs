@@ -0,0 +1,170 @@
+// indicator.rs - LEDs for digital inputs and fault bits
+//
+// A status dashboard mostly asks "is it on": an indicator lights up while its value is
+// non-zero, or while one bit of an integer object is set, so a status word can be split
+// into one LED per bit. Like a gauge, the indicators of a series take the place of its
+// plot; they are set up from their context menu and kept with the dashboard.
+use eframe::egui::{self, Color32, Sense, Stroke};
+use serde::{Deserialize, Serialize};
+
+use crate::overlay::PlotSeries;
+
+const LED_RADIUS: f32 = 7.0;
+const OFF_COLOR: Color32 = Color32::from_rgb(60, 60, 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LedColor {
+    Green,
+    Red,
+    Orange,
+    Blue,
+}
+
+impl LedColor {
+    pub const ALL: [LedColor; 4] = [LedColor::Green, LedColor::Red, LedColor::Orange, LedColor::Blue];
+
+    fn color(self) -> Color32 {
+        match self {
+            LedColor::Green => Color32::from_rgb(0, 200, 0),
+            LedColor::Red => Color32::from_rgb(255, 60, 60),
+            LedColor::Orange => Color32::from_rgb(255, 165, 0),
+            LedColor::Blue => Color32::from_rgb(80, 160, 255),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            LedColor::Green => "Green",
+            LedColor::Red => "Red",
+            LedColor::Orange => "Orange",
+            LedColor::Blue => "Blue",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndicatorSettings {
+    pub series: PlotSeries,
+    /// Bit of the value to show; the whole value when unset
+    #[serde(default)]
+    pub bit: Option<u8>,
+    /// Shown next to the LED; the series name when empty
+    #[serde(default)]
+    pub label: String,
+    pub color: LedColor,
+}
+
+impl IndicatorSettings {
+    pub fn new(series: PlotSeries) -> Self {
+        Self { series, bit: None, label: String::new(), color: LedColor::Green }
+    }
+
+    /// Whether the LED is lit for a raw value
+    pub fn lit(&self, value: f64) -> bool {
+        match self.bit {
+            Some(bit) => (value as i64 >> bit.min(63)) & 1 == 1,
+            None => value != 0.0,
+        }
+    }
+
+    pub fn caption(&self, series_name: &str) -> String {
+        match (self.label.is_empty(), self.bit) {
+            (false, _) => self.label.clone(),
+            (true, Some(bit)) => format!("{} bit {}", series_name, bit),
+            (true, None) => series_name.to_string(),
+        }
+    }
+}
+
+pub enum IndicatorAction {
+    /// Label, bit or colour were edited
+    Changed,
+    /// Another LED for the next bit of the same value
+    AddBit,
+    Remove,
+}
+
+/// Draw one LED with its caption; right-click it for the settings
+pub fn show(ui: &mut egui::Ui, settings: &mut IndicatorSettings, series_name: &str, value: Option<f64>) -> Option<IndicatorAction> {
+    let mut action = None;
+
+    let response = ui.horizontal(|ui| {
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(LED_RADIUS * 2.0, LED_RADIUS * 2.0), Sense::hover());
+        let fill = match value {
+            Some(value) if settings.lit(value) => settings.color.color(),
+            _ => OFF_COLOR,
+        };
+        ui.painter().circle(rect.center(), LED_RADIUS, fill, Stroke::new(1.0, ui.visuals().weak_text_color()));
+        ui.label(settings.caption(series_name));
+    }).response;
+
+    let hover = match value {
+        Some(value) => format!("Value: {}\nRight-click to set up", value),
+        None => "No value yet\nRight-click to set up".to_string(),
+    };
+    response.interact(Sense::click()).on_hover_text(hover).context_menu(|ui| {
+        let mut changed = false;
+        egui::Grid::new("indicator_settings").num_columns(2).show(ui, |ui| {
+            ui.label("Label:");
+            changed |= ui.add(egui::TextEdit::singleline(&mut settings.label).hint_text(series_name).desired_width(140.0)).changed();
+            ui.end_row();
+
+            ui.label("Bit:");
+            ui.horizontal(|ui| {
+                let mut single_bit = settings.bit.is_some();
+                let mut bit = settings.bit.unwrap_or(0);
+                changed |= ui.checkbox(&mut single_bit, "").changed();
+                changed |= ui.add_enabled(single_bit, egui::DragValue::new(&mut bit).range(0..=63)).changed();
+                settings.bit = single_bit.then_some(bit);
+            });
+            ui.end_row();
+
+            ui.label("Colour:");
+            egui::ComboBox::from_id_salt("indicator_color")
+                .selected_text(settings.color.label())
+                .show_ui(ui, |ui| {
+                    for color in LedColor::ALL {
+                        changed |= ui.selectable_value(&mut settings.color, color, color.label()).changed();
+                    }
+                });
+            ui.end_row();
+        });
+        if changed {
+            action = Some(IndicatorAction::Changed);
+        }
+        ui.separator();
+        if ui.button("➕ LED for the next bit").clicked() {
+            action = Some(IndicatorAction::AddBit);
+            ui.close_menu();
+        }
+        if ui.button("🗑 Remove").on_hover_text("The series is plotted again once its last LED is gone").clicked() {
+            action = Some(IndicatorAction::Remove);
+            ui.close_menu();
+        }
+    });
+
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::communication::SdoAddress;
+
+    #[test]
+    fn test_indicator_bits() {
+        let mut led = IndicatorSettings::new(PlotSeries::Sdo(SdoAddress { index: 0x6041, sub_index: 0 }));
+        assert!(!led.lit(0.0));
+        assert!(led.lit(0x0237 as f64));
+        assert_eq!(led.caption("Statusword"), "Statusword");
+
+        led.bit = Some(3);
+        assert!(!led.lit(0x0237 as f64));
+        assert!(led.lit(0x0008 as f64));
+        // Negative integers keep their two's complement bits
+        assert!(led.lit(-1.0));
+        assert_eq!(led.caption("Statusword"), "Statusword bit 3");
+        led.label = "Fault".to_string();
+        assert_eq!(led.caption("Statusword"), "Fault");
+    }
+}
@@ -0,0 +1,210 @@
+// log_import.rs - An earlier CSV / JSON Lines event log read back as a session
+//
+// Reviewing a capture from last week meant opening the CSV in a spreadsheet and plotting
+// it by hand. Imported, the logged SDO, TPDO and derived values become samples of the
+// same series a live session records, and alarms, emergencies, connection changes and
+// markers go back onto the timeline, all at their logged times. The viewer then shows
+// them in its usual plots and statistics, without a bus.
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use std::path::Path;
+
+use crate::communication::SdoAddress;
+use crate::log_viewer;
+use crate::overlay::{PlotSeries, TpdoFieldId};
+use crate::timeline::EventKind;
+
+/// A logged value; SDO values are as logged, i.e. scaled when the object had a scaling
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedSample {
+    pub at: DateTime<Local>,
+    pub series: PlotSeries,
+    pub value: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportedLog {
+    /// Oldest first
+    pub samples: Vec<ImportedSample>,
+    pub events: Vec<(DateTime<Local>, EventKind, String)>,
+}
+
+impl ImportedLog {
+    /// Time of the first sample or event, the epoch of the imported session
+    pub fn start(&self) -> Option<DateTime<Local>> {
+        let first_sample = self.samples.first().map(|sample| sample.at);
+        let first_event = self.events.first().map(|(at, _, _)| *at);
+        first_sample.into_iter().chain(first_event).min()
+    }
+}
+
+/// Read the samples and timeline events of a CSV or JSON Lines log, compressed or not
+pub fn import(path: &Path) -> Result<ImportedLog, String> {
+    let rows = log_viewer::read_log(path)?;
+    let mut log = ImportedLog::default();
+    let mut connected = None;
+    for row in &rows {
+        let Some(at) = parse_timestamp(&row.timestamp) else {
+            continue;
+        };
+        match row.event.as_str() {
+            "SDO_DATA" => {
+                if let Some(address) = parse_address(&row.address) {
+                    log.samples.push(ImportedSample { at, series: PlotSeries::Sdo(address), value: row.value.clone() });
+                }
+            }
+            "TPDO_DATA" => {
+                let Some(tpdo_number) = row.address.strip_prefix("TPDO").and_then(|number| number.parse().ok()) else {
+                    continue;
+                };
+                for (field_name, label) in row.value.split(", ").filter_map(|field| field.split_once('=')) {
+                    if let Some(value) = leading_number(label) {
+                        let series = PlotSeries::Tpdo(TpdoFieldId { tpdo_number, field_name: field_name.to_string() });
+                        log.samples.push(ImportedSample { at, series, value });
+                    }
+                }
+            }
+            "DERIVED_DATA" => {
+                log.samples.push(ImportedSample { at, series: PlotSeries::Derived(row.address.clone()), value: row.value.clone() });
+            }
+            "HEARTBEAT_ALARM" => log.events.push((at, EventKind::Alarm, row.message.clone())),
+            "EMCY" => log.events.push((at, EventKind::Emergency, row.message.clone())),
+            "MARKER" => log.events.push((at, EventKind::Marker, row.message.clone())),
+            "CONNECTION_FAILED" => log.events.push((at, EventKind::Connection, format!("Connection error: {}", row.message))),
+            "INTERFACE_LOST" => log.events.push((at, EventKind::Connection, format!("CAN interface lost ({})", row.message))),
+            "INTERFACE_RESTORED" => log.events.push((at, EventKind::Connection, "CAN interface restored".to_string())),
+            "CONNECTION_STATUS" => {
+                // Logged with every health check, only changes go on the timeline
+                let is_alive = row.value == "Connected";
+                if connected != Some(is_alive) {
+                    connected = Some(is_alive);
+                    log.events.push((at, EventKind::Connection, if is_alive { "Connected" } else { "Connection lost" }.to_string()));
+                }
+            }
+            _ => {}
+        }
+    }
+    log.samples.sort_by_key(|sample| sample.at);
+    log.events.sort_by_key(|(at, _, _)| *at);
+    Ok(log)
+}
+
+/// Log timestamps are local time, "2026-03-14 09:05:07.250"
+fn parse_timestamp(text: &str) -> Option<DateTime<Local>> {
+    let naive = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f").ok()?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// "6041:00"
+fn parse_address(text: &str) -> Option<SdoAddress> {
+    let (index, sub_index) = text.split_once(':')?;
+    Some(SdoAddress {
+        index: u16::from_str_radix(index, 16).ok()?,
+        sub_index: u8::from_str_radix(sub_index, 16).ok()?,
+    })
+}
+
+/// The number a TPDO field label starts with, "25.3 °C" -> "25.3"
+fn leading_number(label: &str) -> Option<String> {
+    let number = label.split_whitespace().next()?;
+    number.parse::<f64>().ok()?;
+    Some(number.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+    use std::fs;
+
+    /// Import a CSV log with these rows below the header
+    fn import_csv(rows: &str) -> ImportedLog {
+        let directory = TestDir::new("log_import");
+        let path = directory.join("canopen_log_20260314_090507.csv");
+        fs::write(&path, format!("Timestamp,Event Type,Address,Value,Message\n{}", rows)).unwrap();
+        import(&path).unwrap()
+    }
+
+    fn at(seconds: u32, millis: i64) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, 14, 9, 5, seconds).unwrap() + chrono::TimeDelta::milliseconds(millis)
+    }
+
+    fn samples(log: &ImportedLog) -> Vec<(PlotSeries, &str)> {
+        log.samples.iter().map(|sample| (sample.series.clone(), sample.value.as_str())).collect()
+    }
+
+    fn events(log: &ImportedLog) -> Vec<(EventKind, &str)> {
+        log.events.iter().map(|(_, kind, text)| (*kind, text.as_str())).collect()
+    }
+
+    #[test]
+    fn test_import_samples() {
+        let log = import_csv("2026-03-14 09:05:07.500,SDO_DATA,6041:00,1079,Statusword\n\
+            2026-03-14 09:05:08.000,TPDO_DATA,TPDO1,\"Temperature=25.3 °C, Status=Running\",\n\
+            2026-03-14 09:05:08.000,DERIVED_DATA,Power,12.5,W\n\
+            2026-03-14 09:05:09.500,SDO_WRITE,6040:00,[0F],\n");
+        assert_eq!(samples(&log), [
+            (PlotSeries::Sdo(SdoAddress { index: 0x6041, sub_index: 0 }), "1079"),
+            (PlotSeries::Tpdo(TpdoFieldId { tpdo_number: 1, field_name: "Temperature".to_string() }), "25.3"),
+            (PlotSeries::Derived("Power".to_string()), "12.5"),
+        ]);
+        assert_eq!(log.samples[0].at, at(7, 500));
+    }
+
+    #[test]
+    fn test_import_timeline_events() {
+        let log = import_csv("2026-03-14 09:05:09.000,EMCY,,,\"Node 3: Overcurrent\"\n\
+            2026-03-14 09:05:09.100,HEARTBEAT_ALARM,,,Node 3 missed its heartbeat\n\
+            2026-03-14 09:05:09.200,MARKER,,,Door opened\n\
+            2026-03-14 09:05:09.300,INTERFACE_LOST,,,can0\n\
+            2026-03-14 09:05:09.400,INTERFACE_RESTORED,,,\n\
+            2026-03-14 09:05:09.500,CONNECTION_FAILED,,,No such device\n");
+        assert_eq!(events(&log), [
+            (EventKind::Emergency, "Node 3: Overcurrent"),
+            (EventKind::Alarm, "Node 3 missed its heartbeat"),
+            (EventKind::Marker, "Door opened"),
+            (EventKind::Connection, "CAN interface lost (can0)"),
+            (EventKind::Connection, "CAN interface restored"),
+            (EventKind::Connection, "Connection error: No such device"),
+        ]);
+    }
+
+    #[test]
+    fn test_only_connection_changes_go_on_the_timeline() {
+        let log = import_csv("2026-03-14 09:05:07.250,CONNECTION_STATUS,,Connected,\n\
+            2026-03-14 09:05:08.250,CONNECTION_STATUS,,Connected,\n\
+            2026-03-14 09:05:09.250,CONNECTION_STATUS,,Disconnected,\n\
+            2026-03-14 09:05:10.250,CONNECTION_STATUS,,Disconnected,\n\
+            2026-03-14 09:05:11.250,CONNECTION_STATUS,,Connected,\n");
+        assert_eq!(events(&log), [
+            (EventKind::Connection, "Connected"),
+            (EventKind::Connection, "Connection lost"),
+            (EventKind::Connection, "Connected"),
+        ]);
+    }
+
+    #[test]
+    fn test_rows_are_sorted_and_start_is_the_first() {
+        let log = import_csv("2026-03-14 09:05:09.000,DERIVED_DATA,Power,2,W\n\
+            2026-03-14 09:05:08.000,DERIVED_DATA,Power,1,W\n\
+            2026-03-14 09:05:07.750,MARKER,,,first\n\
+            not a time,MARKER,,,skipped\n");
+        let values: Vec<&str> = log.samples.iter().map(|sample| sample.value.as_str()).collect();
+        assert_eq!(values, ["1", "2"]);
+        assert_eq!(log.events.len(), 1);
+        assert_eq!(log.start(), Some(at(7, 750)));
+        assert_eq!(ImportedLog::default().start(), None);
+    }
+
+    #[test]
+    fn test_parse_fields() {
+        assert_eq!(parse_timestamp("2026-03-14 09:05:07.250"), Some(at(7, 250)));
+        assert_eq!(parse_timestamp("2026-03-14 09:05:07"), Some(at(7, 0)));
+        assert_eq!(parse_timestamp("14.03.2026 09:05"), None);
+        assert_eq!(parse_address("6041:0A"), Some(SdoAddress { index: 0x6041, sub_index: 0x0A }));
+        assert_eq!(parse_address("TPDO1"), None);
+        assert_eq!(leading_number("25.3 °C"), Some("25.3".to_string()));
+        assert_eq!(leading_number("-4"), Some("-4".to_string()));
+        assert_eq!(leading_number("Running"), None);
+        assert_eq!(leading_number(""), None);
+    }
+}
@@ -0,0 +1,296 @@
+// log_rotation.rs - Size and age limits for the event logs, compression and retention
+//
+// A long test run used to end with one huge CSV file in the log folder. With rotation the
+// logger starts a new file once the current one reaches the size or age limit; the
+// finished file is compressed in the background and the oldest logs are deleted, so the
+// folder holds at most the configured number of them. Compressed files are written under
+// a temporary name first, so a viewer closed mid-compression never leaves a truncated
+// archive in place of the log.
+use eframe::egui;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::LogRotationConfig;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogCompression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl LogCompression {
+    pub const ALL: [LogCompression; 3] = [LogCompression::None, LogCompression::Gzip, LogCompression::Zstd];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogCompression::None => "None",
+            LogCompression::Gzip => "gzip",
+            LogCompression::Zstd => "zstd",
+        }
+    }
+
+    fn extension(self) -> Option<&'static str> {
+        match self {
+            LogCompression::None => None,
+            LogCompression::Gzip => Some("gz"),
+            LogCompression::Zstd => Some("zst"),
+        }
+    }
+}
+
+/// Buffered log file that counts what was written, so it can be rotated by size
+pub struct LogFile {
+    file: BufWriter<File>,
+    written: u64,
+}
+
+impl LogFile {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self { file: BufWriter::new(File::create(path)?), written: 0 })
+    }
+
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+}
+
+impl Write for LogFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// `<stem>.<extension>` in `directory`, numbered when a log of that name already exists
+/// (which happens when a file fills up within a second)
+pub fn unused_path(directory: &Path, stem: &str, extension: &str) -> PathBuf {
+    // A retired log lives on under its name plus the compression suffix
+    let taken = |path: &Path| path.exists() || LogCompression::ALL.iter().filter_map(|compression| compression.extension())
+        .any(|suffix| {
+            let mut name = path.as_os_str().to_owned();
+            name.push(format!(".{}", suffix));
+            Path::new(&name).exists()
+        });
+    let mut path = directory.join(format!("{}.{}", stem, extension));
+    let mut number = 1;
+    while taken(&path) {
+        path = directory.join(format!("{}_{}.{}", stem, number, extension));
+        number += 1;
+    }
+    path
+}
+
+/// Compress a finished log and delete the oldest logs beyond the limit, in the background
+pub fn retire(path: PathBuf, current: Option<PathBuf>, rotation: LogRotationConfig) {
+    std::thread::spawn(move || retire_now(&path, current.as_deref(), &rotation));
+}
+
+fn retire_now(path: &Path, current: Option<&Path>, rotation: &LogRotationConfig) {
+    if let Err(e) = compress(path, rotation.compression) {
+        eprintln!("Failed to compress log {:?}: {}", path, e);
+    }
+    if rotation.keep_files > 0 {
+        if let Err(e) = prune(path, current, rotation.keep_files) {
+            eprintln!("Failed to delete old logs: {}", e);
+        }
+    }
+}
+
+fn compress(path: &Path, compression: LogCompression) -> io::Result<()> {
+    let Some(suffix) = compression.extension() else {
+        return Ok(());
+    };
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", suffix));
+    let compressed = PathBuf::from(name);
+    let partial = compressed.with_extension(format!("{}.part", suffix));
+
+    let mut input = File::open(path)?;
+    let output = BufWriter::new(File::create(&partial)?);
+    let result = match compression {
+        LogCompression::None => Ok(()),
+        LogCompression::Gzip => {
+            let mut encoder = GzEncoder::new(output, flate2::Compression::default());
+            io::copy(&mut input, &mut encoder).and_then(|_| encoder.finish()?.flush())
+        }
+        LogCompression::Zstd => zstd::stream::copy_encode(&mut input, output, 0),
+    };
+    if let Err(e) = result {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    fs::rename(&partial, &compressed)?;
+    fs::remove_file(path)
+}
+
+/// Delete the oldest logs of the same kind as `path` so `keep` remain besides `current`
+fn prune(path: &Path, current: Option<&Path>, keep: usize) -> io::Result<()> {
+    let (Some(directory), Some(extension)) = (path.parent(), path.extension().and_then(|extension| extension.to_str())) else {
+        return Ok(());
+    };
+    let prefix = "canopen_log_";
+    let suffixes: Vec<String> = std::iter::once(format!(".{}", extension))
+        .chain(LogCompression::ALL.iter().filter_map(|compression| compression.extension()).map(|suffix| format!(".{}.{}", extension, suffix)))
+        .collect();
+    let mut logs: Vec<((String, u64), PathBuf)> = fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|log| Some(log.as_path()) != current)
+        .filter_map(|log| {
+            let name = log.file_name()?.to_str()?.strip_prefix(prefix)?;
+            let start = suffixes.iter().find_map(|suffix| name.strip_suffix(suffix.as_str()))?;
+            let (start, number) = log_order(start);
+            Some(((start.to_string(), number), log))
+        })
+        .collect();
+    // Names carry the start time, so they sort oldest first
+    logs.sort();
+    let excess = logs.len().saturating_sub(keep);
+    for (_, log) in &logs[..excess] {
+        fs::remove_file(log)?;
+    }
+    Ok(())
+}
+
+/// Start time and number of a log name without prefix and extension, "20260314_090507_2";
+/// the number is the one `unused_path` adds when several logs start in the same second
+fn log_order(name: &str) -> (&str, u64) {
+    match name.rsplit_once('_') {
+        Some((start, number)) if start.contains('_') => (start, number.parse().unwrap_or(0)),
+        _ => (name, 0),
+    }
+}
+
+/// "Rotation" menu next to the logging controls; returns whether the settings changed
+pub fn menu(ui: &mut egui::Ui, rotation: &mut LogRotationConfig) -> bool {
+    let mut changed = false;
+    ui.menu_button("🔁 Rotation", |ui| {
+        ui.label("Start a new CSV / JSON Lines log file after:");
+        ui.horizontal(|ui| {
+            changed |= ui.add(egui::DragValue::new(&mut rotation.max_size_mb).range(0..=100_000).suffix(" MB")).changed();
+            ui.label("or");
+            changed |= ui.add(egui::DragValue::new(&mut rotation.max_minutes).range(0..=100_000).suffix(" min")).changed();
+        });
+        ui.horizontal(|ui| {
+            ui.label("Compress finished files:");
+            egui::ComboBox::from_id_salt("log_compression")
+                .selected_text(rotation.compression.label())
+                .show_ui(ui, |ui| {
+                    for compression in LogCompression::ALL {
+                        changed |= ui.selectable_value(&mut rotation.compression, compression, compression.label()).changed();
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Keep");
+            changed |= ui.add(egui::DragValue::new(&mut rotation.keep_files).range(0..=10_000).suffix(" files")).changed();
+        });
+        ui.weak("0 = no limit");
+    });
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+    use std::io::Read;
+
+    fn contents() -> String {
+        "Timestamp,Event Type\n".repeat(100)
+    }
+
+    fn names(directory: &TestDir) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(directory.path()).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_unused_path_skips_compressed_logs() {
+        let directory = TestDir::new("rotation_names");
+        assert_eq!(unused_path(directory.path(), "canopen_log_20260314_090000", "csv"), directory.join("canopen_log_20260314_090000.csv"));
+        fs::write(directory.join("canopen_log_20260314_090000.csv.gz"), "").unwrap();
+        assert_eq!(unused_path(directory.path(), "canopen_log_20260314_090000", "csv"), directory.join("canopen_log_20260314_090000_1.csv"));
+        fs::write(directory.join("canopen_log_20260314_090000_1.csv"), "").unwrap();
+        assert_eq!(unused_path(directory.path(), "canopen_log_20260314_090000", "csv"), directory.join("canopen_log_20260314_090000_2.csv"));
+    }
+
+    #[test]
+    fn test_gzip_compression() {
+        let directory = TestDir::new("rotation_gzip");
+        let log = directory.join("canopen_log_20260314_091000.csv");
+        fs::write(&log, contents()).unwrap();
+        retire_now(&log, None, &LogRotationConfig { compression: LogCompression::Gzip, ..LogRotationConfig::default() });
+
+        assert_eq!(names(&directory), ["canopen_log_20260314_091000.csv.gz"]);
+        let mut unpacked = String::new();
+        flate2::read::GzDecoder::new(File::open(directory.join("canopen_log_20260314_091000.csv.gz")).unwrap())
+            .read_to_string(&mut unpacked)
+            .unwrap();
+        assert_eq!(unpacked, contents());
+    }
+
+    #[test]
+    fn test_zstd_compression() {
+        let directory = TestDir::new("rotation_zstd");
+        let log = directory.join("canopen_log_20260314_092000.csv");
+        fs::write(&log, contents()).unwrap();
+        retire_now(&log, None, &LogRotationConfig { compression: LogCompression::Zstd, ..LogRotationConfig::default() });
+
+        assert_eq!(names(&directory), ["canopen_log_20260314_092000.csv.zst"]);
+        let unpacked = zstd::decode_all(File::open(directory.join("canopen_log_20260314_092000.csv.zst")).unwrap()).unwrap();
+        assert_eq!(unpacked, contents().as_bytes());
+    }
+
+    #[test]
+    fn test_oldest_logs_of_the_same_kind_are_pruned() {
+        let directory = TestDir::new("rotation_prune");
+        for stem in ["canopen_log_20260314_090000", "canopen_log_20260314_091000", "canopen_log_20260314_092000"] {
+            fs::write(directory.join(&format!("{}.csv", stem)), contents()).unwrap();
+        }
+        fs::write(directory.join("canopen_log_20260314_080000.mf4"), "").unwrap();
+        let current = directory.join("canopen_log_20260314_093000.csv");
+        fs::write(&current, "").unwrap();
+
+        let rotation = LogRotationConfig { compression: LogCompression::Gzip, keep_files: 2, ..LogRotationConfig::default() };
+        retire_now(&directory.join("canopen_log_20260314_091000.csv"), Some(&current), &rotation);
+        let rotation = LogRotationConfig { compression: LogCompression::Zstd, ..rotation };
+        retire_now(&directory.join("canopen_log_20260314_092000.csv"), Some(&current), &rotation);
+
+        // The MDF4 log and the one being written don't count
+        assert_eq!(names(&directory), [
+            "canopen_log_20260314_080000.mf4",
+            "canopen_log_20260314_091000.csv.gz",
+            "canopen_log_20260314_092000.csv.zst",
+            "canopen_log_20260314_093000.csv",
+        ]);
+    }
+
+    #[test]
+    fn test_logs_of_the_same_second_are_pruned_by_number() {
+        let directory = TestDir::new("rotation_prune_numbered");
+        for number in [2, 3, 10, 11] {
+            fs::write(directory.join(&format!("canopen_log_20260314_090000_{}.csv", number)), "").unwrap();
+        }
+        fs::write(directory.join("canopen_log_20260314_090000.csv"), "").unwrap();
+
+        prune(&directory.join("canopen_log_20260314_090000_11.csv"), None, 2).unwrap();
+        assert_eq!(names(&directory), ["canopen_log_20260314_090000_10.csv", "canopen_log_20260314_090000_11.csv"]);
+        assert_eq!(log_order("20260314_090000_10"), ("20260314_090000", 10));
+        assert_eq!(log_order("20260314_090000"), ("20260314_090000", 0));
+    }
+}
@@ -0,0 +1,448 @@
+// log_viewer.rs - Window that opens the event logs (CSV or JSON Lines) inside the viewer
+//
+// Checking what happened during a run meant opening the log folder and loading the CSV
+// into a spreadsheet. The log viewer reads the current log, or any earlier one from the
+// log folder (rotated logs are decompressed on the fly), into the columns of the CSV log
+// and filters them by event type and address. JSON Lines records are mapped onto the
+// same columns, so both formats read alike.
+use chrono::{DateTime, Local};
+use eframe::egui::{self, Color32};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// One event of a log, in the columns of the CSV log
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogRow {
+    pub timestamp: String,
+    pub event: String,
+    pub address: String,
+    pub value: String,
+    pub message: String,
+}
+
+/// The event logs and black-box dumps in `directory`, newest first
+pub fn log_files(directory: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name().and_then(|name| name.to_str()).is_some_and(|name| {
+            (name.starts_with("canopen_log_") || name.starts_with("canopen_blackbox_")) && log_kind(name).is_some()
+        }))
+        .collect();
+    files.sort_by_key(|path| std::cmp::Reverse(fs::metadata(path).and_then(|metadata| metadata.modified()).ok()));
+    files
+}
+
+/// Whether a log name, compressed or not, is JSON Lines (`Some(true)`) or CSV (`Some(false)`)
+fn log_kind(name: &str) -> Option<bool> {
+    let name = name.strip_suffix(".gz").or_else(|| name.strip_suffix(".zst")).unwrap_or(name);
+    if name.ends_with(".jsonl") {
+        Some(true)
+    } else if name.ends_with(".csv") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Read a CSV or JSON Lines log, gzip or zstd compressed or not
+pub fn read_log(path: &Path) -> Result<Vec<LogRow>, String> {
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    let Some(jsonl) = log_kind(name) else {
+        return Err(format!("{} is not a CSV or JSON Lines log", name));
+    };
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", name, e))?;
+    let reader: Box<dyn Read> = if name.ends_with(".gz") {
+        Box::new(flate2::read::GzDecoder::new(file))
+    } else if name.ends_with(".zst") {
+        Box::new(zstd::Decoder::new(file).map_err(|e| format!("Failed to open {}: {}", name, e))?)
+    } else {
+        Box::new(file)
+    };
+    let result = if jsonl { read_jsonl(reader) } else { read_csv(reader) };
+    result.map_err(|e| format!("Failed to read {}: {}", name, e))
+}
+
+fn read_csv(reader: impl Read) -> io::Result<Vec<LogRow>> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).trim(csv::Trim::All).from_reader(reader);
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let field = |index: usize| record.get(index).unwrap_or_default().to_string();
+        rows.push(LogRow { timestamp: field(0), event: field(1), address: field(2), value: field(3), message: field(4) });
+    }
+    Ok(rows)
+}
+
+fn read_jsonl(reader: impl Read) -> io::Result<Vec<LogRow>> {
+    let mut rows = Vec::new();
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        // The last line of a log that is still being written may be cut off
+        if let Ok(record) = serde_json::from_str::<Value>(&line) {
+            rows.push(json_row(&record));
+        }
+    }
+    Ok(rows)
+}
+
+/// Map a JSON Lines record onto the CSV columns
+fn json_row(record: &Value) -> LogRow {
+    let text = |key: &str| match record.get(key) {
+        Some(Value::String(text)) => Some(text.clone()),
+        Some(Value::Null) | None => None,
+        Some(other) => Some(other.to_string()),
+    };
+    let number = |key: &str| record.get(key).and_then(Value::as_u64);
+    let spaced = |hex: String| hex.as_bytes().chunks(2).map(|pair| String::from_utf8_lossy(pair).into_owned()).collect::<Vec<_>>().join(" ");
+
+    let timestamp = text("timestamp")
+        .map(|timestamp| match DateTime::parse_from_rfc3339(&timestamp) {
+            Ok(at) => at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+            Err(_) => timestamp,
+        })
+        .unwrap_or_default();
+
+    let address = if let (Some(index), Some(sub_index)) = (number("index"), number("sub_index")) {
+        format!("{:04X}:{:02X}", index, sub_index)
+    } else if let Some(tpdo) = number("tpdo_number") {
+        format!("TPDO{}", tpdo)
+    } else if let Some(cob_id) = number("cob_id") {
+        if record.get("extended").and_then(Value::as_bool) == Some(true) { format!("{:08X}", cob_id) } else { format!("{:03X}", cob_id) }
+    } else {
+        text("name").unwrap_or_default()
+    };
+
+    let value = if let Some(Value::Array(fields)) = record.get("fields") {
+        fields.iter()
+            .map(|field| {
+                let name = field.get("name").and_then(Value::as_str).unwrap_or_default();
+                let value = match (field.get("label"), field.get("value")) {
+                    (Some(Value::String(label)), _) | (None, Some(Value::String(label))) => label.clone(),
+                    (_, Some(value)) => value.to_string(),
+                    _ => String::new(),
+                };
+                format!("{}={}", name, value)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    } else if let Some(connected) = record.get("connected").and_then(Value::as_bool) {
+        if connected { "Connected" } else { "Disconnected" }.to_string()
+    } else {
+        text("value").or_else(|| text("raw").map(spaced)).unwrap_or_default()
+    };
+
+    let message = if let Some(direction) = text("direction") {
+        let remote = if record.get("remote").and_then(Value::as_bool) == Some(true) { ", RTR" } else { "" };
+        let direction = if direction == "tx" { "Tx" } else { "Rx" };
+        format!("{}{}, DLC {}", direction, remote, number("dlc").unwrap_or_default())
+    } else {
+        let message = text("message").or_else(|| text("state")).or_else(|| text("error")).or_else(|| text("description")).or_else(|| text("unit"));
+        match (number("node_id"), message) {
+            (Some(node_id), Some(message)) if record.get("state").is_some() || text("event").as_deref() == Some("EMCY") => {
+                format!("Node {}: {}", node_id, message)
+            }
+            (_, message) => message.unwrap_or_default(),
+        }
+    };
+
+    LogRow { timestamp, event: text("event").unwrap_or_default(), address, value, message }
+}
+
+pub struct LogViewer {
+    pub open: bool,
+    path: Option<PathBuf>,
+    rows: Vec<LogRow>,
+    /// Logs in the log folder, refreshed when the list is opened
+    files: Vec<PathBuf>,
+    event_types: BTreeSet<String>,
+    hidden_events: HashSet<String>,
+    address_filter: String,
+    error: Option<String>,
+}
+
+impl LogViewer {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            path: None,
+            rows: Vec::new(),
+            files: Vec::new(),
+            event_types: BTreeSet::new(),
+            hidden_events: HashSet::new(),
+            address_filter: String::new(),
+            error: None,
+        }
+    }
+
+    /// Open the viewer on `path`, e.g. the log being written
+    pub fn show_file(&mut self, path: PathBuf) {
+        self.open = true;
+        self.load(path);
+    }
+
+    fn load(&mut self, path: PathBuf) {
+        match read_log(&path) {
+            Ok(rows) => {
+                self.event_types = rows.iter().map(|row| row.event.clone()).collect();
+                self.rows = rows;
+                self.error = None;
+            }
+            Err(e) => {
+                self.rows.clear();
+                self.event_types.clear();
+                self.error = Some(e);
+            }
+        }
+        self.path = Some(path);
+    }
+
+    fn shows(&self, row: &LogRow) -> bool {
+        let address = self.address_filter.trim();
+        !self.hidden_events.contains(&row.event)
+            && (address.is_empty() || row.address.to_uppercase().contains(&address.to_uppercase()))
+    }
+
+    /// `current` is the log being written, `directory` the log folder
+    pub fn show(&mut self, ctx: &egui::Context, current: Option<PathBuf>, directory: Option<PathBuf>) {
+        let mut open = self.open;
+
+        egui::Window::new("Log Viewer")
+            .open(&mut open)
+            .default_size([820.0, 460.0])
+            .show(ctx, |ui| {
+                let mut load = None;
+
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(current.is_some(), egui::Button::new("📝 Current log")).clicked() {
+                        load = current.clone();
+                    }
+                    let selected = self.path.as_ref()
+                        .and_then(|path| path.file_name())
+                        .map_or_else(|| "Choose a log…".to_string(), |name| name.to_string_lossy().into_owned());
+                    let combo = egui::ComboBox::from_id_salt("log_viewer_file")
+                        .selected_text(selected)
+                        .width(300.0)
+                        .show_ui(ui, |ui| {
+                            if self.files.is_empty() {
+                                ui.weak("No logs in the log folder");
+                            }
+                            for file in &self.files {
+                                let name = file.file_name().unwrap_or_default().to_string_lossy();
+                                if ui.selectable_label(self.path.as_ref() == Some(file), name).clicked() {
+                                    load = Some(file.clone());
+                                }
+                            }
+                        });
+                    if combo.response.clicked() {
+                        self.files = directory.as_deref().map(log_files).unwrap_or_default();
+                    }
+                    if ui.button("📂 Open…").clicked() {
+                        let mut dialog = rfd::FileDialog::new().add_filter("Event log", &["csv", "jsonl", "gz", "zst"]);
+                        if let Some(directory) = &directory {
+                            dialog = dialog.set_directory(directory);
+                        }
+                        load = dialog.pick_file();
+                    }
+                    if ui.add_enabled(self.path.is_some(), egui::Button::new("🔄 Reload")).clicked() {
+                        load = self.path.clone();
+                    }
+                });
+                if let Some(path) = load {
+                    self.load(path);
+                }
+                if let Some(error) = &self.error {
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", error));
+                }
+
+                let visible: Vec<usize> = (0..self.rows.len()).filter(|&i| self.shows(&self.rows[i])).collect();
+                ui.horizontal(|ui| {
+                    ui.menu_button("Event types", |ui| {
+                        ui.horizontal(|ui| {
+                            if ui.button("All").clicked() {
+                                self.hidden_events.clear();
+                            }
+                            if ui.button("None").clicked() {
+                                self.hidden_events = self.event_types.iter().cloned().collect();
+                            }
+                        });
+                        for event in &self.event_types {
+                            let mut shown = !self.hidden_events.contains(event);
+                            if ui.checkbox(&mut shown, event).changed() {
+                                if shown {
+                                    self.hidden_events.remove(event);
+                                } else {
+                                    self.hidden_events.insert(event.clone());
+                                }
+                            }
+                        }
+                    });
+                    ui.label("Address:");
+                    ui.add(egui::TextEdit::singleline(&mut self.address_filter).hint_text("e.g. 6041 or TPDO1").desired_width(120.0));
+                    if visible.len() == self.rows.len() {
+                        ui.label(format!("{} events", self.rows.len()));
+                    } else {
+                        ui.label(format!("{} of {} events shown", visible.len(), self.rows.len()));
+                    }
+                });
+                ui.separator();
+
+                let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                egui::ScrollArea::both()
+                    .auto_shrink([false, false])
+                    .show_rows(ui, row_height, visible.len(), |ui, range| {
+                        for &index in &visible[range] {
+                            let row = &self.rows[index];
+                            ui.horizontal(|ui| {
+                                ui.monospace(format!("{:<23}  {:<18}  {:<10}", row.timestamp, row.event, row.address));
+                                ui.label(&row.value);
+                                if !row.message.is_empty() {
+                                    ui.weak(&row.message);
+                                }
+                            });
+                        }
+                    });
+            });
+
+        self.open = open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::LogEvent;
+    use crate::test_dir::TestDir;
+    use std::io::Write;
+
+    const CSV_LOG: &str = "Timestamp,Event Type,Address,Value,Message\n\
+        2026-03-14 09:05:07.250,SDO_DATA,6041:00,1079,Statusword\n\
+        2026-03-14 09:05:07.500,RAW_FRAME,181,\"[01, 02]\",\"Rx, DLC 2\"\n";
+
+    fn write_gz(path: &Path, text: &str) {
+        let mut encoder = flate2::write::GzEncoder::new(File::create(path).unwrap(), flate2::Compression::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+    }
+
+    fn json_rows(lines: &str) -> Vec<LogRow> {
+        read_jsonl(lines.as_bytes()).unwrap()
+    }
+
+    fn columns(row: &LogRow) -> (&str, &str, &str, &str) {
+        (&row.event, &row.address, &row.value, &row.message)
+    }
+
+    #[test]
+    fn test_log_files_lists_logs_and_dumps() {
+        let directory = TestDir::new("log_viewer_files");
+        fs::write(directory.join("canopen_log_20260314_090507.csv"), CSV_LOG).unwrap();
+        fs::write(directory.join("canopen_blackbox_20260314_090507.jsonl.gz"), "").unwrap();
+        fs::write(directory.join("canopen_log_20260313_120000.csv.zst"), "").unwrap();
+        fs::write(directory.join("canopen_frames_20260314_090507.blf"), "").unwrap();
+        fs::write(directory.join("notes.csv"), "").unwrap();
+
+        let mut names: Vec<String> = log_files(directory.path()).iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, [
+            "canopen_blackbox_20260314_090507.jsonl.gz",
+            "canopen_log_20260313_120000.csv.zst",
+            "canopen_log_20260314_090507.csv",
+        ]);
+    }
+
+    #[test]
+    fn test_read_csv_log() {
+        let directory = TestDir::new("log_viewer_csv");
+        let path = directory.join("canopen_log_20260314_090507.csv");
+        fs::write(&path, CSV_LOG).unwrap();
+
+        let rows = read_log(&path).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].timestamp, "2026-03-14 09:05:07.250");
+        assert_eq!(columns(&rows[0]), ("SDO_DATA", "6041:00", "1079", "Statusword"));
+        assert_eq!(columns(&rows[1]), ("RAW_FRAME", "181", "[01, 02]", "Rx, DLC 2"));
+        assert!(read_log(&directory.join("canopen_log.txt")).is_err());
+    }
+
+    #[test]
+    fn test_written_sdo_record_reads_like_csv() {
+        // The record as the event log writes it, gzip compressed as after rotation
+        let at = Local::now();
+        let record = LogEvent::SdoData {
+            node_id: 1,
+            index: 0x6041,
+            sub_index: 0,
+            data_type: Some(canopen_common::SdoDataType::UInt16),
+            raw: vec![0x37, 0x04],
+            value: "1079".to_string(),
+            description: Some("Statusword".to_string()),
+        }.to_json(at);
+        let directory = TestDir::new("log_viewer_jsonl");
+        let path = directory.join("canopen_blackbox_20260314_090507.jsonl.gz");
+        write_gz(&path, &format!("{}\n", record));
+
+        let rows = read_log(&path).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(columns(&rows[0]), ("SDO_DATA", "6041:00", "1079", "Statusword"));
+        assert_eq!(rows[0].timestamp, at.format("%Y-%m-%d %H:%M:%S%.3f").to_string());
+    }
+
+    #[test]
+    fn test_cut_off_json_line_is_skipped() {
+        let rows = json_rows("{\"event\":\"MARKER\",\"message\":\"start\"}\n\n{\"timestamp\":\"2026-03-14T09:05:07\",\"event\":\"TPDO_DA");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(columns(&rows[0]), ("MARKER", "", "", "start"));
+    }
+
+    #[test]
+    fn test_json_raw_frame_row() {
+        let rows = json_rows(
+            "{\"event\":\"RAW_FRAME\",\"cob_id\":385,\"extended\":false,\"remote\":false,\"dlc\":2,\"raw\":\"0102\",\"direction\":\"rx\"}\n\
+             {\"event\":\"RAW_FRAME\",\"cob_id\":305419896,\"extended\":true,\"remote\":true,\"dlc\":0,\"raw\":\"\",\"direction\":\"tx\"}\n",
+        );
+        assert_eq!(columns(&rows[0]), ("RAW_FRAME", "181", "01 02", "Rx, DLC 2"));
+        assert_eq!(columns(&rows[1]), ("RAW_FRAME", "12345678", "", "Tx, RTR, DLC 0"));
+    }
+
+    #[test]
+    fn test_json_event_rows() {
+        let rows = json_rows(
+            "{\"event\":\"EMCY\",\"node_id\":3,\"message\":\"Overcurrent\"}\n\
+             {\"event\":\"NMT_STATE\",\"node_id\":4,\"state\":\"Operational\"}\n\
+             {\"event\":\"CONNECTION_STATUS\",\"connected\":false}\n\
+             {\"event\":\"TPDO_DATA\",\"tpdo_number\":1,\"fields\":[{\"name\":\"Mode\",\"value\":2,\"label\":\"Running\"},{\"name\":\"Speed\",\"value\":1500}]}\n",
+        );
+        assert_eq!(rows[0].message, "Node 3: Overcurrent");
+        assert_eq!(rows[1].message, "Node 4: Operational");
+        assert_eq!(rows[2].value, "Disconnected");
+        assert_eq!(columns(&rows[3]), ("TPDO_DATA", "TPDO1", "Mode=Running, Speed=1500", ""));
+    }
+
+    #[test]
+    fn test_filter_by_event_and_address() {
+        let mut viewer = LogViewer::new();
+        viewer.rows = read_csv(CSV_LOG.as_bytes()).unwrap();
+        viewer.address_filter = "6041".to_string();
+        assert_eq!(viewer.rows.iter().filter(|row| viewer.shows(row)).count(), 1);
+        viewer.address_filter = " 6041 ".to_string();
+        assert_eq!(viewer.rows.iter().filter(|row| viewer.shows(row)).count(), 1);
+
+        viewer.address_filter.clear();
+        viewer.hidden_events.insert("RAW_FRAME".to_string());
+        assert_eq!(viewer.rows.iter().filter(|row| viewer.shows(row)).count(), 1);
+        viewer.hidden_events.insert("SDO_DATA".to_string());
+        assert_eq!(viewer.rows.iter().filter(|row| viewer.shows(row)).count(), 0);
+    }
+}
@@ -1,33 +1,454 @@
-use std::path::PathBuf;
-use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::io::{self, Write};
 use std::sync::{Arc, Mutex};
-use chrono::Local;
+use std::time::{Duration, Instant};
+use canopen_common::SdoDataType;
+use chrono::{DateTime, Local, SecondsFormat};
 use csv::Writer;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::black_box::{BlackBox, Dump};
+use crate::blf::BlfWriter;
+use crate::config::{BlackBoxConfig, LogRotationConfig};
+use crate::log_rotation::{self, LogFile};
+use crate::mdf4::{MdfChannel, MdfWriter};
+use crate::trace::TraceEntry;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Every event as a row of text
+    #[default]
+    Csv,
+    /// The samples of every channel as an ASAM MDF4 measurement file; other events are not kept
+    Mdf4,
+    /// Every event as a JSON object on its own line, with node ID, data type and raw bytes
+    Jsonl,
+}
+
+impl LogFormat {
+    pub const ALL: [LogFormat; 3] = [LogFormat::Csv, LogFormat::Mdf4, LogFormat::Jsonl];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogFormat::Csv => "CSV",
+            LogFormat::Mdf4 => "MDF4",
+            LogFormat::Jsonl => "JSON Lines",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            LogFormat::Csv => "csv",
+            LogFormat::Mdf4 => "mf4",
+            LogFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// Buffered events are written out at least this often
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A mapped object of a logged TPDO
+#[derive(Debug, Clone)]
+pub struct TpdoField {
+    pub name: String,
+    /// Value as decoded from the frame
+    pub value: String,
+    /// Value as shown, scaled or with the name of an enumerated value
+    pub label: String,
+    pub data_type: Option<SdoDataType>,
+}
 
 #[derive(Debug, Clone)]
 pub enum LogEvent {
     SdoData {
+        node_id: u8,
         index: u16,
         sub_index: u8,
+        data_type: Option<SdoDataType>,
+        /// Data bytes of the response
+        raw: Vec<u8>,
         value: String,
+        /// Name of an enumerated value or unit of a converted one, written to the message column
+        description: Option<String>,
     },
     SdoError {
+        node_id: u8,
         index: u16,
         sub_index: u8,
         error: String,
     },
+    SdoWrite {
+        node_id: u8,
+        index: u16,
+        sub_index: u8,
+        data: Vec<u8>,
+        error: Option<String>,
+    },
     TpdoData {
+        node_id: u8,
         tpdo_number: u8,
-        values: Vec<(String, String)>,
+        /// Data bytes of the frame
+        raw: Vec<u8>,
+        values: Vec<TpdoField>,
+    },
+    /// New value of a derived channel
+    DerivedData {
+        name: String,
+        value: f64,
+        unit: String,
     },
     #[allow(dead_code)]  // Reserved for future use
     ConnectionSuccess,
     ConnectionFailed(String),
     ConnectionStatus(bool),
+    InterfaceLost(String),
+    InterfaceRestored,
+    /// NMT state change seen in a node's heartbeats
+    NmtState {
+        node_id: u8,
+        state: String,
+    },
+    /// Heartbeat loss or unexpected NMT state change
+    HeartbeatAlarm(String),
+    Emergency {
+        node_id: u8,
+        message: String,
+    },
+    /// Text marker set by the user
+    Marker(String),
+    /// A frame received or transmitted on the bus
+    RawFrame(TraceEntry),
+}
+
+impl LogEvent {
+    /// Event type column of the CSV log and `event` of a JSON record
+    fn kind(&self) -> &'static str {
+        match self {
+            LogEvent::SdoData { .. } => "SDO_DATA",
+            LogEvent::SdoError { .. } => "SDO_ERROR",
+            LogEvent::SdoWrite { .. } => "SDO_WRITE",
+            LogEvent::TpdoData { .. } => "TPDO_DATA",
+            LogEvent::DerivedData { .. } => "DERIVED_DATA",
+            LogEvent::ConnectionSuccess => "CONNECTION_SUCCESS",
+            LogEvent::ConnectionFailed(_) => "CONNECTION_FAILED",
+            LogEvent::ConnectionStatus(_) => "CONNECTION_STATUS",
+            LogEvent::InterfaceLost(_) => "INTERFACE_LOST",
+            LogEvent::InterfaceRestored => "INTERFACE_RESTORED",
+            LogEvent::NmtState { .. } => "NMT_STATE",
+            LogEvent::HeartbeatAlarm(_) => "HEARTBEAT_ALARM",
+            LogEvent::Emergency { .. } => "EMCY",
+            LogEvent::Marker(_) => "MARKER",
+            LogEvent::RawFrame(_) => "RAW_FRAME",
+        }
+    }
+
+    /// Address, value and message columns of the CSV log
+    fn csv_fields(self) -> (String, String, String) {
+        match self {
+            LogEvent::SdoData { index, sub_index, value, description, .. } => (
+                format!("{:04X}:{:02X}", index, sub_index),
+                value,
+                description.unwrap_or_default(),
+            ),
+            LogEvent::SdoError { index, sub_index, error, .. } => (
+                format!("{:04X}:{:02X}", index, sub_index),
+                String::new(),
+                error,
+            ),
+            LogEvent::SdoWrite { index, sub_index, data, error, .. } => (
+                format!("{:04X}:{:02X}", index, sub_index),
+                format!("{:02X?}", data),
+                error.unwrap_or_default(),
+            ),
+            LogEvent::TpdoData { tpdo_number, values, .. } => {
+                let fields = values.iter()
+                    .map(|field| format!("{}={}", field.name, field.label))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                (
+                    format!("TPDO{}", tpdo_number),
+                    fields,
+                    String::new(),
+                )
+            },
+            LogEvent::DerivedData { name, value, unit } => (
+                name,
+                value.to_string(),
+                unit,
+            ),
+            LogEvent::ConnectionSuccess => (
+                String::new(),
+                String::new(),
+                "Successfully connected to CANopen node".to_string(),
+            ),
+            LogEvent::ConnectionFailed(err) => (
+                String::new(),
+                String::new(),
+                err,
+            ),
+            LogEvent::ConnectionStatus(is_alive) => (
+                String::new(),
+                if is_alive { "Connected" } else { "Disconnected" }.to_string(),
+                String::new(),
+            ),
+            LogEvent::InterfaceLost(reason) => (
+                String::new(),
+                String::new(),
+                reason,
+            ),
+            LogEvent::InterfaceRestored => (
+                String::new(),
+                String::new(),
+                "CAN interface is back, socket reopened".to_string(),
+            ),
+            LogEvent::NmtState { node_id, state } => (
+                String::new(),
+                String::new(),
+                format!("Node {}: {}", node_id, state),
+            ),
+            LogEvent::Emergency { node_id, message } => (
+                String::new(),
+                String::new(),
+                format!("Node {}: {}", node_id, message),
+            ),
+            LogEvent::HeartbeatAlarm(message) => (
+                String::new(),
+                String::new(),
+                message,
+            ),
+            LogEvent::Marker(text) => (
+                String::new(),
+                String::new(),
+                text,
+            ),
+            LogEvent::RawFrame(entry) => (
+                if entry.extended { format!("{:08X}", entry.id) } else { format!("{:03X}", entry.id) },
+                format!("{:02X?}", entry.data),
+                format!("{}{}, DLC {}", if entry.transmitted { "Tx" } else { "Rx" }, if entry.remote { ", RTR" } else { "" }, entry.dlc),
+            ),
+        }
+    }
+
+    /// One self-contained JSON object; absent fields are left out rather than null
+    pub(crate) fn to_json(&self, timestamp: DateTime<Local>) -> Value {
+        let fields = match self {
+            LogEvent::SdoData { node_id, index, sub_index, data_type, raw, value, description } => json!({
+                "node_id": node_id,
+                "index": index,
+                "sub_index": sub_index,
+                "data_type": data_type,
+                "raw": hex_bytes(raw),
+                "value": json_value(value),
+                "description": description,
+            }),
+            LogEvent::SdoError { node_id, index, sub_index, error } => json!({
+                "node_id": node_id,
+                "index": index,
+                "sub_index": sub_index,
+                "error": error,
+            }),
+            LogEvent::SdoWrite { node_id, index, sub_index, data, error } => json!({
+                "node_id": node_id,
+                "index": index,
+                "sub_index": sub_index,
+                "raw": hex_bytes(data),
+                "error": error,
+            }),
+            LogEvent::TpdoData { node_id, tpdo_number, raw, values } => json!({
+                "node_id": node_id,
+                "tpdo_number": tpdo_number,
+                "raw": hex_bytes(raw),
+                "fields": values.iter().map(|field| json!({
+                    "name": field.name,
+                    "data_type": field.data_type,
+                    "value": json_value(&field.value),
+                    "label": (field.label != field.value).then_some(&field.label),
+                })).map(without_nulls).collect::<Vec<_>>(),
+            }),
+            LogEvent::DerivedData { name, value, unit } => json!({
+                "name": name,
+                "value": value,
+                "unit": (!unit.is_empty()).then_some(unit),
+            }),
+            LogEvent::ConnectionSuccess => json!({ "message": "Successfully connected to CANopen node" }),
+            LogEvent::InterfaceRestored => json!({ "message": "CAN interface is back, socket reopened" }),
+            LogEvent::ConnectionStatus(is_alive) => json!({ "connected": is_alive }),
+            LogEvent::ConnectionFailed(message)
+            | LogEvent::InterfaceLost(message)
+            | LogEvent::HeartbeatAlarm(message)
+            | LogEvent::Marker(message) => json!({ "message": message }),
+            LogEvent::NmtState { node_id, state } => json!({ "node_id": node_id, "state": state }),
+            LogEvent::Emergency { node_id, message } => json!({ "node_id": node_id, "message": message }),
+            LogEvent::RawFrame(entry) => json!({
+                "cob_id": entry.id,
+                "extended": entry.extended,
+                "remote": entry.remote,
+                "dlc": entry.dlc,
+                "raw": hex_bytes(&entry.data),
+                "direction": if entry.transmitted { "tx" } else { "rx" },
+            }),
+        };
+        let mut record = Map::new();
+        record.insert("timestamp".to_string(), json!(timestamp.to_rfc3339_opts(SecondsFormat::Micros, false)));
+        record.insert("event".to_string(), json!(self.kind()));
+        if let Value::Object(fields) = without_nulls(fields) {
+            record.extend(fields);
+        }
+        Value::Object(record)
+    }
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+/// Numbers as JSON numbers, so they can be aggregated without casting; anything else as text
+/// A decoded value as a JSON number where it is one; integers stay exact, also beyond 2^53
+fn json_value(text: &str) -> Value {
+    if let Ok(integer) = text.parse::<i64>() {
+        json!(integer)
+    } else if let Ok(integer) = text.parse::<u64>() {
+        json!(integer)
+    } else {
+        text.parse::<f64>().ok()
+            .and_then(serde_json::Number::from_f64)
+            .map_or_else(|| json!(text), Value::Number)
+    }
+}
+
+fn without_nulls(value: Value) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(fields.into_iter().filter(|(_, value)| !value.is_null()).collect()),
+        value => value,
+    }
+}
+
+enum EventOutput {
+    Csv(Box<Writer<LogFile>>),
+    Jsonl(LogFile),
+}
+
+/// The CSV or JSON Lines log, moving on to a new file at the rotation limits
+struct EventLog {
+    output: EventOutput,
+    format: LogFormat,
+    path: PathBuf,
+    opened: Instant,
+    last_flush: Instant,
+    rotation: LogRotationConfig,
+}
+
+impl EventLog {
+    fn create(path: PathBuf, format: LogFormat, rotation: LogRotationConfig) -> io::Result<Self> {
+        let output = match format {
+            LogFormat::Jsonl => EventOutput::Jsonl(LogFile::create(&path)?),
+            _ => {
+                let mut writer = Writer::from_writer(LogFile::create(&path)?);
+                writer.write_record(["Timestamp", "Event Type", "Address", "Value", "Message"])?;
+                writer.flush()?;
+                EventOutput::Csv(Box::new(writer))
+            }
+        };
+        Ok(Self { output, format, path, opened: Instant::now(), last_flush: Instant::now(), rotation })
+    }
+
+    fn write(&mut self, event: LogEvent, now: DateTime<Local>) -> io::Result<()> {
+        match &mut self.output {
+            EventOutput::Csv(writer) => {
+                let timestamp = now.format("%Y-%m-%d %H:%M:%S%.3f").to_string();
+                let event_type = event.kind();
+                let (address, value, message) = event.csv_fields();
+                writer.write_record([timestamp.as_str(), event_type, &address, &value, &message])?;
+            }
+            EventOutput::Jsonl(file) => {
+                let mut line = event.to_json(now).to_string();
+                line.push('\n');
+                file.write_all(line.as_bytes())?;
+            }
+        }
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush()?;
+        }
+        if self.rotation_due() {
+            self.rotate()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.last_flush = Instant::now();
+        match &mut self.output {
+            EventOutput::Csv(writer) => writer.flush(),
+            EventOutput::Jsonl(file) => file.flush(),
+        }
+    }
+
+    fn written(&self) -> u64 {
+        match &self.output {
+            EventOutput::Csv(writer) => writer.get_ref().written(),
+            EventOutput::Jsonl(file) => file.written(),
+        }
+    }
+
+    fn rotation_due(&self) -> bool {
+        self.rotation.max_size().is_some_and(|limit| self.written() >= limit)
+            || self.rotation.max_age().is_some_and(|limit| self.opened.elapsed() >= limit)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.flush()?;
+        let directory = self.path.parent().unwrap_or(Path::new("."));
+        let path = log_rotation::unused_path(directory, &log_stem(Local::now()), self.format.extension());
+        let next = Self::create(path, self.format, self.rotation)?;
+        let finished = std::mem::replace(self, next).path;
+        println!("✓ Log rotated: {:?}", self.path);
+        log_rotation::retire(finished, Some(self.path.clone()), self.rotation);
+        Ok(())
+    }
+}
+
+fn log_stem(now: DateTime<Local>) -> String {
+    format!("canopen_log_{}", now.format("%Y%m%d_%H%M%S"))
+}
+
+/// Write the events of a black-box dump to their own log, with a marker at the trigger
+fn write_dump(directory: &Path, format: LogFormat, dump: Dump) -> io::Result<PathBuf> {
+    fs::create_dir_all(directory)?;
+    let stem = format!("canopen_blackbox_{}", dump.trigger.at.format("%Y%m%d_%H%M%S"));
+    let path = log_rotation::unused_path(directory, &stem, format.extension());
+    let mut log = EventLog::create(path.clone(), format, LogRotationConfig::default())?;
+    let mut marker = Some(LogEvent::Marker(format!("Black box trigger: {}", dump.trigger.reason)));
+    for (at, event) in dump.events {
+        if at > dump.trigger.at {
+            if let Some(marker) = marker.take() {
+                log.write(marker, dump.trigger.at)?;
+            }
+        }
+        log.write(event, at)?;
+    }
+    if let Some(marker) = marker {
+        log.write(marker, dump.trigger.at)?;
+    }
+    log.flush()?;
+    Ok(path)
 }
 
 pub struct Logger {
-    writer: Arc<Mutex<Option<Writer<File>>>>,
+    events: Arc<Mutex<Option<EventLog>>>,
+    mdf: Arc<Mutex<Option<MdfWriter>>>,
+    blf: Arc<Mutex<Option<BlfWriter>>>,
+    /// Every frame is also logged as a `RawFrame` event
+    frame_events: bool,
+    rotation: LogRotationConfig,
+    black_box: Arc<Mutex<Option<BlackBox>>>,
+    black_box_config: BlackBoxConfig,
+    /// Folder and format of black-box dumps
+    black_box_target: Option<(PathBuf, LogFormat)>,
+    last_dump: Arc<Mutex<Option<PathBuf>>>,
     enabled: bool,
     log_file_path: Option<PathBuf>,
 }
@@ -36,32 +457,49 @@ impl Logger {
     /// Create a new logger (disabled by default)
     pub fn new() -> Self {
         Self {
-            writer: Arc::new(Mutex::new(None)),
+            events: Arc::new(Mutex::new(None)),
+            mdf: Arc::new(Mutex::new(None)),
+            blf: Arc::new(Mutex::new(None)),
+            frame_events: false,
+            rotation: LogRotationConfig::default(),
+            black_box: Arc::new(Mutex::new(None)),
+            black_box_config: BlackBoxConfig::default(),
+            black_box_target: None,
+            last_dump: Arc::new(Mutex::new(None)),
             enabled: false,
             log_file_path: None,
         }
     }
 
-    /// Enable logging and create a new log file
-    pub fn enable(&mut self, log_directory: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    /// Enable logging and create a new log file, and a BLF file with every frame when `raw_frames`
+    pub fn enable(&mut self, log_directory: PathBuf, format: LogFormat, raw_frames: bool) -> Result<(), Box<dyn std::error::Error>> {
         // Create log directory if it doesn't exist
         fs::create_dir_all(&log_directory)?;
 
         // Generate log file name with timestamp
-        let timestamp = Local::now().format("%Y%m%d_%H%M%S");
-        let log_filename = format!("canopen_log_{}.csv", timestamp);
-        let log_path = log_directory.join(log_filename);
+        let now = Local::now();
+        let timestamp = now.format("%Y%m%d_%H%M%S");
+        let log_path = log_rotation::unused_path(&log_directory, &log_stem(now), format.extension());
 
-        // Create CSV writer
-        let file = File::create(&log_path)?;
-        let mut writer = Writer::from_writer(file);
+        self.close_events();
+        *self.mdf.lock().unwrap() = None;
+        match format {
+            LogFormat::Csv | LogFormat::Jsonl => {
+                *self.events.lock().unwrap() = Some(EventLog::create(log_path.clone(), format, self.rotation)?);
+            }
+            LogFormat::Mdf4 => {
+                *self.mdf.lock().unwrap() = Some(MdfWriter::create(&log_path, now)?);
+            }
+        }
 
-        // Write CSV header
-        writer.write_record(&["Timestamp", "Event Type", "Address", "Value", "Message"])?;
-        writer.flush()?;
+        *self.blf.lock().unwrap() = if raw_frames {
+            let blf_path = log_directory.join(format!("canopen_frames_{}.blf", timestamp));
+            Some(BlfWriter::create(&blf_path, now)?)
+        } else {
+            None
+        };
 
-        // Store writer and update state
-        *self.writer.lock().unwrap() = Some(writer);
+        // Update state
         self.enabled = true;
         self.log_file_path = Some(log_path.clone());
 
@@ -71,85 +509,185 @@ impl Logger {
 
     /// Disable logging and close the file
     pub fn disable(&mut self) {
-        *self.writer.lock().unwrap() = None;
+        self.close_events();
+        *self.mdf.lock().unwrap() = None;
+        *self.blf.lock().unwrap() = None;
         self.enabled = false;
         println!("✓ Logging disabled");
     }
 
+    /// Close the CSV or JSON Lines log; it is compressed and counted like a rotated one
+    fn close_events(&mut self) {
+        if let Some(events) = self.events.lock().unwrap().take() {
+            let path = events.path.clone();
+            drop(events);
+            log_rotation::retire(path, None, self.rotation);
+        }
+    }
+
+    /// Size and age limits of the CSV and JSON Lines logs, applied to the current file too
+    pub fn set_rotation(&mut self, rotation: LogRotationConfig) {
+        self.rotation = rotation;
+        if let Some(events) = self.events.lock().unwrap().as_mut() {
+            events.rotation = rotation;
+        }
+    }
+
+    /// Keep recent events in memory for black-box dumps to `directory`, in `format` if that
+    /// is a text log and as CSV otherwise; without a directory nothing is kept
+    pub fn set_black_box(&mut self, config: BlackBoxConfig, directory: Option<PathBuf>, format: LogFormat) {
+        self.black_box_config = config;
+        let mut black_box = self.black_box.lock().unwrap();
+        match directory.filter(|_| config.enabled) {
+            Some(directory) => {
+                let format = if format == LogFormat::Jsonl { LogFormat::Jsonl } else { LogFormat::Csv };
+                self.black_box_target = Some((directory, format));
+                match black_box.as_mut() {
+                    Some(black_box) => black_box.configure(config),
+                    None => *black_box = Some(BlackBox::new(config)),
+                }
+            }
+            None => {
+                self.black_box_target = None;
+                *black_box = None;
+            }
+        }
+    }
+
+    /// Start a black-box dump of the events around now
+    pub fn trigger_black_box(&self, reason: &str) {
+        if let Some(black_box) = self.black_box.lock().unwrap().as_mut() {
+            if black_box.trigger(Local::now(), reason.to_string()) {
+                println!("Black box triggered: {}", reason);
+            }
+        }
+    }
+
+    /// What the black box holds and the last dump, or `None` when it is off
+    pub fn black_box_status(&self) -> Option<String> {
+        let black_box = self.black_box.lock().unwrap();
+        let black_box = black_box.as_ref()?;
+        let mut status = match black_box.pending() {
+            Some(trigger) => format!("Triggered at {} ({}), writing soon", trigger.at.format("%H:%M:%S"), trigger.reason),
+            None => format!("{} events in memory", black_box.len()),
+        };
+        if let Some(name) = self.last_dump.lock().unwrap().as_ref().and_then(|path| path.file_name()) {
+            status.push_str(&format!(", last dump {}", name.to_string_lossy()));
+        }
+        Some(status)
+    }
+
     /// Check if logging is enabled
     pub fn is_enabled(&self) -> bool {
         self.enabled
     }
 
-    /// Get the current log file path
+    /// Get the current log file path; it changes when the log is rotated
     pub fn log_file_path(&self) -> Option<PathBuf> {
-        self.log_file_path.clone()
+        let rotated = self.events.lock().ok().and_then(|events| events.as_ref().map(|events| events.path.clone()));
+        rotated.or_else(|| self.log_file_path.clone())
     }
 
-    /// Log an event
-    pub fn log(&self, event: LogEvent) {
+    /// Log a sample of a channel to the MDF4 file; `describe` gives the channel's name and
+    /// metadata the first time. The CSV log has the samples from the events.
+    pub fn sample(&self, key: &str, at: DateTime<Local>, raw: f64, describe: impl FnOnce() -> MdfChannel) {
         if !self.enabled {
             return;
         }
+        if let Ok(mut mdf_guard) = self.mdf.lock() {
+            if let Some(mdf) = mdf_guard.as_mut() {
+                if let Err(e) = mdf.sample(key, at, raw, describe) {
+                    eprintln!("Failed to write log sample: {}", e);
+                }
+            }
+        }
+    }
 
-        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string();
+    /// Log every received and transmitted frame as an event, next to the decoded values
+    pub fn set_frame_events(&mut self, frame_events: bool) {
+        self.frame_events = frame_events;
+    }
 
-        let (event_type, address, value, message) = match event {
-            LogEvent::SdoData { index, sub_index, value } => (
-                "SDO_DATA".to_string(),
-                format!("{:04X}:{:02X}", index, sub_index),
-                value,
-                String::new(),
-            ),
-            LogEvent::SdoError { index, sub_index, error } => (
-                "SDO_ERROR".to_string(),
-                format!("{:04X}:{:02X}", index, sub_index),
-                String::new(),
-                error,
-            ),
-            LogEvent::TpdoData { tpdo_number, values } => {
-                let fields = values.iter()
-                    .map(|(name, val)| format!("{}={}", name, val))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                (
-                    "TPDO_DATA".to_string(),
-                    format!("TPDO{}", tpdo_number),
-                    fields,
-                    String::new(),
-                )
-            },
-            LogEvent::ConnectionSuccess => (
-                "CONNECTION_SUCCESS".to_string(),
-                String::new(),
-                String::new(),
-                "Successfully connected to CANopen node".to_string(),
-            ),
-            LogEvent::ConnectionFailed(err) => (
-                "CONNECTION_FAILED".to_string(),
-                String::new(),
-                String::new(),
-                err,
-            ),
-            LogEvent::ConnectionStatus(is_alive) => (
-                "CONNECTION_STATUS".to_string(),
-                String::new(),
-                if is_alive { "Connected" } else { "Disconnected" }.to_string(),
-                String::new(),
-            ),
+    /// Whether raw frames are recorded, so the communication thread has to forward them
+    pub fn records_frames(&self) -> bool {
+        let recording = self.enabled || self.black_box.lock().is_ok_and(|black_box| black_box.is_some());
+        (recording && self.frame_events) || (self.enabled && self.blf.lock().is_ok_and(|blf| blf.is_some()))
+    }
+
+    /// Record raw frames to the BLF file and as events
+    pub fn log_frames(&self, entries: &[TraceEntry]) {
+        if self.frame_events {
+            for entry in entries {
+                self.log(LogEvent::RawFrame(entry.clone()));
+            }
+        }
+        if let Ok(mut blf_guard) = self.blf.lock() {
+            if let Some(blf) = blf_guard.as_mut() {
+                for entry in entries {
+                    if let Err(e) = blf.write(entry) {
+                        eprintln!("Failed to write raw frame: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Log an event
+    pub fn log(&self, event: LogEvent) {
+        let now = match &event {
+            LogEvent::RawFrame(entry) => entry.timestamp,
+            _ => Local::now(),
         };
+        if let Ok(mut black_box_guard) = self.black_box.lock() {
+            if let Some(black_box) = black_box_guard.as_mut() {
+                let trigger = match &event {
+                    LogEvent::HeartbeatAlarm(message) if self.black_box_config.trigger_on_alarm => Some(format!("Heartbeat alarm: {}", message)),
+                    LogEvent::Emergency { node_id, message } if self.black_box_config.trigger_on_emergency => Some(format!("EMCY from node {}: {}", node_id, message)),
+                    _ => None,
+                };
+                black_box.record(now, event.clone());
+                if let Some(reason) = trigger {
+                    if black_box.trigger(now, reason.clone()) {
+                        println!("Black box triggered: {}", reason);
+                    }
+                }
+            }
+        }
 
-        // Write to CSV
-        if let Ok(mut writer_guard) = self.writer.lock() {
-            if let Some(writer) = writer_guard.as_mut() {
-                if let Err(e) = writer.write_record(&[&timestamp, &event_type, &address, &value, &message]) {
+        if !self.enabled {
+            return;
+        }
+        if let Ok(mut events_guard) = self.events.lock() {
+            if let Some(events) = events_guard.as_mut() {
+                if let Err(e) = events.write(event, now) {
                     eprintln!("Failed to write log entry: {}", e);
                 }
-                if let Err(e) = writer.flush() {
+            }
+        }
+    }
+
+    /// Write out buffered events that have waited long enough, and black-box dumps that are due
+    pub fn tick(&self) {
+        if let Ok(mut events_guard) = self.events.lock() {
+            if let Some(events) = events_guard.as_mut().filter(|events| events.last_flush.elapsed() >= FLUSH_INTERVAL) {
+                if let Err(e) = events.flush() {
                     eprintln!("Failed to flush log file: {}", e);
                 }
             }
         }
+
+        let dump = self.black_box.lock().ok().and_then(|mut black_box| black_box.as_mut()?.take_due(Local::now()));
+        if let (Some(dump), Some((directory, format))) = (dump, self.black_box_target.clone()) {
+            let last_dump = self.last_dump.clone();
+            std::thread::spawn(move || match write_dump(&directory, format, dump) {
+                Ok(path) => {
+                    println!("✓ Black box written: {:?}", path);
+                    *last_dump.lock().unwrap() = Some(path);
+                }
+                Err(e) => eprintln!("Failed to write black box: {}", e),
+            });
+        }
     }
 }
 
@@ -158,3 +696,80 @@ impl Default for Logger {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_json_record() {
+        let timestamp = Local.with_ymd_and_hms(2026, 3, 14, 9, 5, 7).unwrap();
+        let event = LogEvent::SdoData {
+            node_id: 4,
+            index: 0x6000,
+            sub_index: 1,
+            data_type: Some(SdoDataType::UInt16),
+            raw: vec![0xE8, 0x03],
+            value: "1000".to_string(),
+            description: None,
+        };
+        let record = event.to_json(timestamp);
+        assert_eq!(record["event"], "SDO_DATA");
+        assert_eq!(record["node_id"], 4);
+        assert_eq!(record["index"], 0x6000);
+        assert_eq!(record["data_type"], "UInt16");
+        assert_eq!(record["raw"], "E803");
+        assert_eq!(record["value"], json!(1000));
+        assert!(record.get("description").is_none());
+        assert!(record["timestamp"].as_str().unwrap().starts_with("2026-03-14T09:05:07.000000"));
+
+        let event = LogEvent::TpdoData {
+            node_id: 4,
+            tpdo_number: 1,
+            raw: vec![0x01, 0x02],
+            values: vec![TpdoField { name: "Mode".to_string(), value: "2".to_string(), label: "Running".to_string(), data_type: None }],
+        };
+        let record = event.to_json(timestamp);
+        assert_eq!(record["fields"][0], json!({ "name": "Mode", "value": 2, "label": "Running" }));
+        assert!(!record.to_string().contains('\n'));
+        assert_eq!(record["fields"][0].to_string(), r#"{"label":"Running","name":"Mode","value":2}"#);
+
+        let entry = TraceEntry { timestamp, id: 0x604, extended: false, remote: false, dlc: 2, data: vec![0x40, 0x00], transmitted: true };
+        let record = LogEvent::RawFrame(entry).to_json(timestamp);
+        assert_eq!(record["event"], "RAW_FRAME");
+        assert_eq!(record["cob_id"], 0x604);
+        assert_eq!(record["raw"], "4000");
+        assert_eq!(record["direction"], "tx");
+    }
+
+    #[test]
+    fn test_json_values() {
+        assert_eq!(json_value("1079").to_string(), "1079");
+        assert_eq!(json_value("-32768").to_string(), "-32768");
+        assert_eq!(json_value("18446744073709551615").to_string(), "18446744073709551615");
+        assert_eq!(json_value("9007199254740993").to_string(), "9007199254740993");
+        assert_eq!(json_value("25.5").to_string(), "25.5");
+        assert_eq!(json_value("NaN"), json!("NaN"));
+        assert_eq!(json_value("Running"), json!("Running"));
+    }
+
+    #[test]
+    fn test_black_box_dump() {
+        let directory = TestDir::new("black_box_dump");
+        let at = Local.with_ymd_and_hms(2026, 3, 14, 9, 5, 7).unwrap();
+        let dump = Dump {
+            trigger: crate::black_box::Trigger { at, reason: "Manual".to_string() },
+            events: vec![
+                (at - chrono::TimeDelta::seconds(1), LogEvent::Marker("before".to_string())),
+                (at + chrono::TimeDelta::seconds(1), LogEvent::Marker("after".to_string())),
+            ],
+        };
+        let path = write_dump(directory.path(), LogFormat::Csv, dump).unwrap();
+        assert_eq!(path.file_name().unwrap(), "canopen_blackbox_20260314_090507.csv");
+        let text = fs::read_to_string(&path).unwrap();
+        let messages: Vec<&str> = text.lines().skip(1).filter_map(|line| line.rsplit(',').next()).collect();
+        assert_eq!(messages, ["before", "Black box trigger: Manual", "after"]);
+    }
+}
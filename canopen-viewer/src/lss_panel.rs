@@ -0,0 +1,211 @@
+// lss_panel.rs - LSS commissioning of nodes without a valid node ID
+//
+// A node leaves the factory with node ID 255 (unconfigured) and can only be reached by
+// LSS. The dialog switches it to configuration state, either every node on the bus or
+// one node selected by its identity, assigns node ID and bitrate and stores them. The
+// new node ID takes effect once the node is back in waiting state and resets its
+// communication.
+use canopen_common::{LssAddress, LssCommand, LssResponse, LSS_BITRATES};
+use chrono::Local;
+use eframe::egui::{self, Color32};
+use std::path::Path;
+
+use crate::startup::ExpectedIdentity;
+
+/// Outcomes kept in the dialog
+const MAX_LOG_LINES: usize = 10;
+
+/// Text for the outcome of an LSS command, as shown in the dialog
+pub fn describe(command: &LssCommand, result: &Result<Option<LssResponse>, String>) -> Result<String, String> {
+    match (result, command) {
+        (Err(err), _) => Err(err.clone()),
+        (Ok(Some(LssResponse::NodeId(0xFF))), _) => Ok("node is unconfigured (node ID 255)".to_string()),
+        (Ok(Some(LssResponse::NodeId(node_id))), _) => Ok(format!("node ID is {}", node_id)),
+        (Ok(Some(LssResponse::Confirmed)), LssCommand::IdentifyNonConfigured) => Ok("an unconfigured node answered".to_string()),
+        (Ok(Some(LssResponse::Confirmed)), _) => Ok("confirmed".to_string()),
+        (Ok(None), LssCommand::IdentifyNonConfigured) => Ok("no unconfigured node answered".to_string()),
+        (Ok(None), _) if command.response_specifier().is_some() => Err("no answer, is a node in configuration state?".to_string()),
+        (Ok(None), _) => Ok("sent".to_string()),
+    }
+}
+
+pub struct LssPanel {
+    pub open: bool,
+    /// Address one node by identity instead of switching every node
+    selective: bool,
+    address: LssAddress,
+    node_id: u8,
+    bitrate: u32,
+    switch_delay_ms: u16,
+    log: Vec<(String, Result<String, String>)>,
+}
+
+impl LssPanel {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            selective: false,
+            address: LssAddress::default(),
+            node_id: 1,
+            bitrate: 500_000,
+            switch_delay_ms: 100,
+            log: Vec::new(),
+        }
+    }
+
+    pub fn sent(&mut self, command: LssCommand, result: Result<Option<LssResponse>, String>) {
+        let line = format!("{} {}", Local::now().format("%H:%M:%S"), command);
+        self.log.push((line, describe(&command, &result)));
+        let excess = self.log.len().saturating_sub(MAX_LOG_LINES);
+        self.log.drain(..excess);
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, connected: bool, eds_path: Option<&Path>) -> Option<LssCommand> {
+        let mut action = None;
+        let mut open = self.open;
+
+        egui::Window::new("LSS Commissioning")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(connected, |ui| {
+                    ui.strong("1. Switch to configuration state");
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut self.selective, false, "All nodes");
+                        ui.radio_value(&mut self.selective, true, "By identity");
+                    });
+                    if self.selective {
+                        egui::Grid::new("lss_address_grid").num_columns(2).show(ui, |ui| {
+                            for (name, value) in [
+                                ("Vendor ID", &mut self.address.vendor_id),
+                                ("Product code", &mut self.address.product_code),
+                                ("Revision", &mut self.address.revision),
+                                ("Serial number", &mut self.address.serial_number),
+                            ] {
+                                ui.label(name);
+                                ui.add(egui::DragValue::new(value).hexadecimal(8, false, true));
+                                ui.end_row();
+                            }
+                        });
+                        if ui.add_enabled(eds_path.is_some(), egui::Button::new("📄 Take from EDS"))
+                            .on_hover_text("Vendor, product and revision from the EDS; the serial number stays")
+                            .clicked()
+                        {
+                            if let Some(identity) = eds_path.and_then(|path| ExpectedIdentity::from_eds(path).ok()) {
+                                self.address.vendor_id = identity.vendor_id.unwrap_or(self.address.vendor_id);
+                                self.address.product_code = identity.product_code.unwrap_or(self.address.product_code);
+                                self.address.revision = identity.revision.unwrap_or(self.address.revision);
+                            }
+                        }
+                    } else {
+                        ui.colored_label(Color32::from_rgb(255, 165, 0), "Every LSS node on the bus enters configuration state.");
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("Switch to configuration").clicked() {
+                            action = Some(if self.selective {
+                                LssCommand::SwitchStateSelective(self.address)
+                            } else {
+                                LssCommand::SwitchStateGlobal { configuration: true }
+                            });
+                        }
+                        if ui.button("🔍 Find unconfigured").on_hover_text("Ask nodes without node ID to answer").clicked() {
+                            action = Some(LssCommand::IdentifyNonConfigured);
+                        }
+                    });
+                    ui.separator();
+
+                    ui.strong("2. Configure");
+                    ui.horizontal(|ui| {
+                        ui.label("Node ID:");
+                        ui.add(egui::DragValue::new(&mut self.node_id).range(1..=127));
+                        if ui.button("Assign").clicked() {
+                            action = Some(LssCommand::ConfigureNodeId(self.node_id));
+                        }
+                        if ui.button("Inquire").on_hover_text("Read the node ID of the node in configuration state").clicked() {
+                            action = Some(LssCommand::InquireNodeId);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Bitrate:");
+                        egui::ComboBox::from_id_salt("lss_bitrate")
+                            .selected_text(format!("{} kbit/s", self.bitrate / 1000))
+                            .show_ui(ui, |ui| {
+                                for (bitrate, _) in LSS_BITRATES {
+                                    ui.selectable_value(&mut self.bitrate, bitrate, format!("{} kbit/s", bitrate / 1000));
+                                }
+                            });
+                        if ui.button("Set").clicked() {
+                            action = Some(LssCommand::ConfigureBitTiming(self.bitrate));
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Switch delay:");
+                        ui.add(egui::DragValue::new(&mut self.switch_delay_ms).range(10..=10_000).suffix(" ms"));
+                        if ui.button("Activate bitrate")
+                            .on_hover_text("All nodes in configuration state switch to the new bitrate; the viewer's interface has to follow")
+                            .clicked()
+                        {
+                            action = Some(LssCommand::ActivateBitTiming { switch_delay_ms: self.switch_delay_ms });
+                        }
+                    });
+                    ui.separator();
+
+                    ui.strong("3. Store and leave");
+                    ui.horizontal(|ui| {
+                        if ui.button("💾 Store configuration").clicked() {
+                            action = Some(LssCommand::StoreConfiguration);
+                        }
+                        if ui.button("Switch all to waiting").on_hover_text("Nodes with a new node ID restart their communication with it").clicked() {
+                            action = Some(LssCommand::SwitchStateGlobal { configuration: false });
+                        }
+                    });
+                });
+                if !connected {
+                    ui.weak("Connect to the bus first (any node ID).");
+                }
+
+                if !self.log.is_empty() {
+                    ui.separator();
+                    for (line, outcome) in &self.log {
+                        match outcome {
+                            Ok(message) => ui.label(format!("{}: {}", line, message)),
+                            Err(err) => ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}: {}", line, err)),
+                        };
+                    }
+                }
+            });
+
+        self.open = open;
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use canopen_common::{create_lss_frames, parse_lss_response};
+    use socketcan::EmbeddedFrame;
+
+    #[test]
+    fn test_lss_commissioning() {
+        let frames = create_lss_frames(&LssCommand::ConfigureNodeId(5)).unwrap();
+        assert_eq!(frames[0].data(), &[0x11, 5, 0, 0, 0, 0, 0, 0]);
+        assert!(create_lss_frames(&LssCommand::ConfigureNodeId(128)).is_none());
+        assert_eq!(create_lss_frames(&LssCommand::ConfigureBitTiming(250_000)).unwrap()[0].data(), &[0x13, 0, 3, 0, 0, 0, 0, 0]);
+        let address = LssAddress { vendor_id: 0x1A2, product_code: 2, revision: 3, serial_number: 4 };
+        let frames = create_lss_frames(&LssCommand::SwitchStateSelective(address)).unwrap();
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0].data(), &[0x40, 0xA2, 0x01, 0, 0, 0, 0, 0]);
+
+        let store = LssCommand::StoreConfiguration;
+        let answer = parse_lss_response(&store, &[0x17, 1, 0, 0, 0, 0, 0, 0]).unwrap();
+        assert_eq!(answer, Err("Storing not supported".to_string()));
+        assert_eq!(parse_lss_response(&store, &[0x11, 0, 0, 0, 0, 0, 0, 0]), None);
+        let inquiry = parse_lss_response(&LssCommand::InquireNodeId, &[0x5E, 0xFF, 0, 0, 0, 0, 0, 0]).unwrap();
+        assert_eq!(describe(&LssCommand::InquireNodeId, &inquiry.map(Some)), Ok("node is unconfigured (node ID 255)".to_string()));
+
+        assert_eq!(describe(&store, &Ok(None)), Err("no answer, is a node in configuration state?".to_string()));
+        assert_eq!(describe(&LssCommand::SwitchStateGlobal { configuration: true }, &Ok(None)), Ok("sent".to_string()));
+        assert_eq!(describe(&LssCommand::IdentifyNonConfigured, &Ok(None)), Ok("no unconfigured node answered".to_string()));
+    }
+}
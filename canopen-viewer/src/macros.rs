@@ -0,0 +1,377 @@
+// macros.rs - Named sequences of SDO writes, delays and NMT commands
+//
+// Macros are kept in the config file and run step by step in the communication thread,
+// e.g. "enter pre-op, set PDO mapping, save, reset" with one click. A failed step stops
+// the macro; the steps after it are skipped.
+use canopen_common::{NmtCommand, SdoDataType};
+use eframe::egui::{self, Color32};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::sdo_console::DATA_TYPES;
+use crate::sdo_write::WriteValue;
+
+const NMT_COMMANDS: [NmtCommand; 5] = [
+    NmtCommand::Start,
+    NmtCommand::Stop,
+    NmtCommand::EnterPreOperational,
+    NmtCommand::ResetNode,
+    NmtCommand::ResetCommunication,
+];
+
+/// One step as defined by the user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MacroStep {
+    /// Value as typed, encoded for `data_type` when the macro runs
+    SdoWrite {
+        index: u16,
+        sub_index: u8,
+        data_type: SdoDataType,
+        value: String,
+    },
+    Delay {
+        ms: u64,
+    },
+    Nmt {
+        command: NmtCommand,
+    },
+}
+
+impl MacroStep {
+    /// Encode the step for execution; fails if a write value doesn't fit its type
+    pub fn to_op(&self) -> Result<MacroOp, String> {
+        Ok(match self {
+            Self::SdoWrite { index, sub_index, data_type, value } => MacroOp::SdoWrite {
+                index: *index,
+                sub_index: *sub_index,
+                data: WriteValue::parse(value, data_type)?.encode(data_type)?,
+            },
+            Self::Delay { ms } => MacroOp::Delay(Duration::from_millis(*ms)),
+            Self::Nmt { command } => MacroOp::Nmt(*command),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Macro {
+    pub name: String,
+    pub steps: Vec<MacroStep>,
+}
+
+/// A step ready to be executed by the communication thread
+#[derive(Debug, Clone, PartialEq)]
+pub enum MacroOp {
+    SdoWrite {
+        index: u16,
+        sub_index: u8,
+        data: Vec<u8>,
+    },
+    Delay(Duration),
+    Nmt(NmtCommand),
+}
+
+/// Encode all steps, or report the first one that can't be encoded
+pub fn compile(steps: &[MacroStep]) -> Result<Vec<MacroOp>, String> {
+    steps.iter()
+        .enumerate()
+        .map(|(i, step)| step.to_op().map_err(|err| format!("Step {}: {}", i + 1, err)))
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+enum StepStatus {
+    Running,
+    Done,
+    Failed(String),
+    Skipped,
+}
+
+/// What the macro window asks the communication thread to do
+pub enum MacroAction {
+    Run(Vec<MacroOp>),
+    Stop,
+    /// Macros were changed and should be written to the config file
+    Save,
+}
+
+/// Macro being executed and the status of its steps (`None` = not reached yet)
+struct MacroRun {
+    macro_index: usize,
+    statuses: Vec<Option<StepStatus>>,
+    finished: bool,
+}
+
+pub struct MacroEditor {
+    pub open: bool,
+    selected: usize,
+    run: Option<MacroRun>,
+    error: Option<String>,
+}
+
+impl MacroEditor {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            selected: 0,
+            run: None,
+            error: None,
+        }
+    }
+
+    pub fn running(&self) -> bool {
+        self.run.as_ref().is_some_and(|run| !run.finished)
+    }
+
+    /// Record the result of a step; the next one is running, or skipped after a failure
+    pub fn step_finished(&mut self, step: usize, result: Result<(), String>) {
+        let Some(run) = self.run.as_mut() else {
+            return;
+        };
+        let failed = result.is_err();
+        if let Some(status) = run.statuses.get_mut(step) {
+            *status = Some(match result {
+                Ok(()) => StepStatus::Done,
+                Err(err) => StepStatus::Failed(err),
+            });
+        }
+        if let Some(next) = run.statuses.get_mut(step + 1) {
+            *next = Some(if failed { StepStatus::Skipped } else { StepStatus::Running });
+        }
+    }
+
+    /// The macro ended, completed or stopped; steps not reached are skipped
+    pub fn finished(&mut self) {
+        if let Some(run) = self.run.as_mut() {
+            run.finished = true;
+            for status in &mut run.statuses {
+                if matches!(status, None | Some(StepStatus::Running)) {
+                    *status = Some(StepStatus::Skipped);
+                }
+            }
+        }
+    }
+
+    /// Draw the window; `macros` is edited in place
+    pub fn show(&mut self, ctx: &egui::Context, connected: bool, macros: &mut Vec<Macro>) -> Option<MacroAction> {
+        let mut action = None;
+        let mut open = self.open;
+
+        egui::Window::new("Macros")
+            .open(&mut open)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                let running = self.running();
+                ui.add_enabled_ui(!running, |ui| {
+                    self.draw_selector(ui, macros);
+                });
+
+                let Some(selected) = macros.get_mut(self.selected) else {
+                    ui.label("No macros yet. Click \"➕ New\" to create one.");
+                    return;
+                };
+
+                ui.add_enabled_ui(!running, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Name:");
+                        ui.text_edit_singleline(&mut selected.name);
+                    });
+                });
+                ui.separator();
+
+                let statuses = self.run.as_ref()
+                    .filter(|run| run.macro_index == self.selected)
+                    .map(|run| run.statuses.as_slice());
+                ui.add_enabled_ui(!running, |ui| {
+                    draw_steps(ui, &mut selected.steps, statuses);
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if running {
+                        ui.spinner();
+                        if ui.button("⏹ Stop").clicked() {
+                            action = Some(MacroAction::Stop);
+                        }
+                    } else if ui.add_enabled(connected && !selected.steps.is_empty(), egui::Button::new("▶ Run")).clicked() {
+                        match compile(&selected.steps) {
+                            Ok(ops) => {
+                                self.error = None;
+                                let mut statuses = vec![None; ops.len()];
+                                statuses[0] = Some(StepStatus::Running);
+                                self.run = Some(MacroRun { macro_index: self.selected, statuses, finished: false });
+                                action = Some(MacroAction::Run(ops));
+                            }
+                            Err(err) => self.error = Some(err),
+                        }
+                    }
+                    if ui.add_enabled(!running, egui::Button::new("💾 Save")).clicked() {
+                        action = Some(MacroAction::Save);
+                    }
+                    if let Some(err) = &self.error {
+                        ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+                    }
+                });
+            });
+
+        self.open = open;
+        action
+    }
+
+    fn draw_selector(&mut self, ui: &mut egui::Ui, macros: &mut Vec<Macro>) {
+        ui.horizontal(|ui| {
+            let selected_name = macros.get(self.selected).map_or("—", |m| m.name.as_str());
+            egui::ComboBox::from_id_salt("macro_select")
+                .selected_text(selected_name)
+                .show_ui(ui, |ui| {
+                    for (i, m) in macros.iter().enumerate() {
+                        ui.selectable_value(&mut self.selected, i, &m.name);
+                    }
+                });
+
+            if ui.button("➕ New").clicked() {
+                macros.push(Macro { name: format!("Macro {}", macros.len() + 1), steps: Vec::new() });
+                self.selected = macros.len() - 1;
+                self.run = None;
+            }
+            if !macros.is_empty() && ui.button("🗑 Delete").clicked() {
+                macros.remove(self.selected);
+                self.selected = self.selected.saturating_sub(1);
+                self.run = None;
+            }
+        });
+    }
+}
+
+fn draw_steps(ui: &mut egui::Ui, steps: &mut Vec<MacroStep>, statuses: Option<&[Option<StepStatus>]>) {
+    let mut move_up = None;
+    let mut remove = None;
+
+    egui::Grid::new("macro_steps")
+        .num_columns(4)
+        .spacing([10.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            for (i, step) in steps.iter_mut().enumerate() {
+                ui.label(format!("{}.", i + 1));
+                ui.horizontal(|ui| draw_step(ui, i, step));
+
+                match statuses.and_then(|statuses| statuses.get(i)).cloned().flatten() {
+                    Some(StepStatus::Running) => {
+                        ui.spinner();
+                    }
+                    Some(StepStatus::Done) => {
+                        ui.colored_label(Color32::from_rgb(0, 200, 0), "✓");
+                    }
+                    Some(StepStatus::Failed(err)) => {
+                        ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+                    }
+                    Some(StepStatus::Skipped) => {
+                        ui.weak("skipped");
+                    }
+                    None => {
+                        ui.label("");
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(i > 0, egui::Button::new("⏶").small()).clicked() {
+                        move_up = Some(i);
+                    }
+                    if ui.small_button("🗑").clicked() {
+                        remove = Some(i);
+                    }
+                });
+                ui.end_row();
+            }
+        });
+
+    if let Some(i) = move_up {
+        steps.swap(i - 1, i);
+    }
+    if let Some(i) = remove {
+        steps.remove(i);
+    }
+
+    ui.horizontal(|ui| {
+        if ui.button("+ SDO write").clicked() {
+            steps.push(MacroStep::SdoWrite {
+                index: 0x2000,
+                sub_index: 0,
+                data_type: SdoDataType::UInt8,
+                value: "0".to_string(),
+            });
+        }
+        if ui.button("+ Delay").clicked() {
+            steps.push(MacroStep::Delay { ms: 100 });
+        }
+        if ui.button("+ NMT").clicked() {
+            steps.push(MacroStep::Nmt { command: NmtCommand::EnterPreOperational });
+        }
+    });
+}
+
+fn draw_step(ui: &mut egui::Ui, i: usize, step: &mut MacroStep) {
+    match step {
+        MacroStep::SdoWrite { index, sub_index, data_type, value } => {
+            ui.label("Write");
+            ui.add(egui::DragValue::new(index).hexadecimal(4, false, true).prefix("0x"));
+            ui.label("sub");
+            ui.add(egui::DragValue::new(sub_index));
+            egui::ComboBox::from_id_salt(("macro_step_type", i))
+                .selected_text(format!("{:?}", data_type))
+                .show_ui(ui, |ui| {
+                    for option in DATA_TYPES {
+                        let label = format!("{:?}", option);
+                        ui.selectable_value(data_type, option, label);
+                    }
+                });
+            ui.add(egui::TextEdit::singleline(value).desired_width(90.0));
+        }
+        MacroStep::Delay { ms } => {
+            ui.label("Wait");
+            ui.add(egui::DragValue::new(ms).suffix(" ms"));
+        }
+        MacroStep::Nmt { command } => {
+            ui.label("NMT");
+            egui::ComboBox::from_id_salt(("macro_step_nmt", i))
+                .selected_text(command.to_string())
+                .show_ui(ui, |ui| {
+                    for option in NMT_COMMANDS {
+                        ui.selectable_value(command, option, option.to_string());
+                    }
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_macro_round_trip_and_compile() {
+        let steps = vec![
+            MacroStep::Nmt { command: NmtCommand::EnterPreOperational },
+            MacroStep::SdoWrite { index: 0x1010, sub_index: 1, data_type: SdoDataType::UInt32, value: "0x65766173".to_string() },
+            MacroStep::Delay { ms: 50 },
+        ];
+
+        #[derive(Serialize, Deserialize)]
+        struct Wrapper {
+            macros: Vec<Macro>,
+        }
+        let wrapper = Wrapper { macros: vec![Macro { name: "Save".to_string(), steps: steps.clone() }] };
+        let loaded: Wrapper = toml::from_str(&toml::to_string_pretty(&wrapper).unwrap()).unwrap();
+        assert_eq!(loaded.macros[0].steps, steps);
+
+        assert_eq!(compile(&steps).unwrap(), vec![
+            MacroOp::Nmt(NmtCommand::EnterPreOperational),
+            MacroOp::SdoWrite { index: 0x1010, sub_index: 1, data: b"save".to_vec() },
+            MacroOp::Delay(Duration::from_millis(50)),
+        ]);
+
+        let invalid = [MacroStep::SdoWrite { index: 0x2000, sub_index: 0, data_type: SdoDataType::UInt8, value: "300".to_string() }];
+        assert!(compile(&invalid).unwrap_err().starts_with("Step 1:"));
+    }
+}
@@ -1,7 +1,66 @@
+mod backup;
+mod bar_chart;
+mod black_box;
+mod blf;
+mod capture;
 mod communication;
 mod canopen;
+mod channel_stats;
+mod channel_style;
 mod config;
+mod controller_panel;
+mod dashboard;
+mod dcf;
+mod decimation;
+mod default_report;
+mod derived;
+mod device_check;
+mod emcy_monitor;
+mod frame_sender;
+mod gauge;
+mod histogram;
+mod history;
+mod gateway;
+mod indicator;
+mod log_import;
+mod log_rotation;
+mod log_viewer;
 mod logging;
+mod lss_panel;
+mod macros;
+mod mdf4;
+mod parquet_export;
+mod network_overview;
+mod nmt_panel;
+mod pdo_layout;
+mod pdo_timing;
+mod presets;
+mod object_walk;
+mod overlay;
+mod queue;
+mod radix;
+mod report;
+mod rpdo;
+mod scaling;
+mod scanner;
+mod sdo_console;
+mod sdo_search;
+mod sdo_write;
+mod session_export;
+mod snapshot;
+mod snapshot_compare;
+mod startup;
+mod stimulus;
+mod svg_export;
+#[cfg(test)]
+mod test_dir;
+mod timeline;
+mod trace;
+mod trace_export;
+mod value_description;
+mod watch;
+mod xy_plot;
+mod y_axis;
 
 // Version information embedded at compile time
 const APP_VERSION: &str = env!("APP_VERSION");
@@ -11,20 +70,69 @@ const GIT_DIRTY: &str = env!("GIT_DIRTY");
 const BUILD_TIME: &str = env!("BUILD_TIME");
 
 use std::collections::{BTreeMap, HashMap, VecDeque, HashSet};
-use communication::{Command, Update, SdoAddress, SdoObject, TpdoData};
-use canopen_common::SdoDataType;
-use config::AppConfig;
-use logging::{Logger, LogEvent};
+use communication::{Command, Update, SdoAddress, SdoObject, SdoSubObject, TpdoData};
+use canopen_common::{SdoDataType, NmtCommand, NmtState, NMT_BROADCAST_NODE_ID};
+use canopen::heartbeat::{self, NodeNmtState};
+use canopen::stats::{BusStats, NodeCommMetrics};
+use canopen::timestamp::TimestampSource;
+use canopen::TransportConfig;
+use config::{AppConfig, HistoryConfig, PlotBufferConfig, RecentSetup};
+use black_box::BlackBoxAction;
+use logging::{Logger, LogEvent, LogFormat, TpdoField};
+use mdf4::MdfChannel;
+use sdo_write::{ValueLimits, WriteValue};
+use radix::{Radix, radix_toggle};
+use sdo_console::SdoConsole;
+use sdo_search::SdoQuery;
+use snapshot_compare::SnapshotCompare;
+use default_report::DefaultReport;
+use emcy_monitor::EmcyMonitor;
+use log_viewer::LogViewer;
+use frame_sender::{FrameSender, FrameSenderAction};
+use controller_panel::ControllerPanel;
+use device_check::{DeviceCheckPanel, DeviceCheckPlan};
+use backup::{BackupAction, ParameterBackup};
+use value_description::ValueDescriptions;
+use scaling::Scaling;
+use watch::{RateMeter, WatchRow, WatchTable};
+use macros::{MacroAction, MacroEditor};
+use network_overview::{NetworkOverview, OverviewAction};
+use lss_panel::LssPanel;
+use nmt_panel::NmtPanel;
+use pdo_timing::PdoTiming;
+use presets::{PresetAction, PresetMenu, PresetSdo, SubscriptionPreset};
+use scanner::{ObjectScanner, ScanAction};
+use startup::{StartupAction, StartupPanel, StartupPlan};
+use stimulus::{StimulusAction, StimulusGenerator};
+use rpdo::{RpdoAction, RpdoPanel};
+use trace::TracePanel;
 
 use eframe::{egui, NativeOptions, egui::Color32, egui::ColorImage};
 use std::process::Command as process_command;
+use bar_chart::{BarChartAction, BarChartSettings, BarValue};
+use channel_stats::ChannelStats;
+use dashboard::{Dashboard, DashboardAction};
+use timeline::{EventKind, Timeline};
+use session_export::{Alignment, ExportChannel};
+use report::{Report, ReportChannel, ReportPanel};
+use derived::{DerivedAction, DerivedChannel, DerivedPanel, Expression};
+use svg_export::{SvgPlot, SvgSeries};
+use y_axis::{YAxisSettings, YRange};
+use xy_plot::{XyPlotAction, XyPlotSettings};
+use gauge::{GaugeAction, GaugeSettings};
+use history::SampleHistory;
+use histogram::{HistogramAction, HistogramSettings};
+use indicator::{IndicatorAction, IndicatorSettings};
+use overlay::{DropTarget, OverlayPlot, PlotSeries, TpdoFieldId};
 use std::path::PathBuf;
-use std::sync::mpsc::{Sender, Receiver};
-use egui_plot::{Plot, PlotPoints, Line, Legend};
+use std::sync::mpsc::Sender;
+use egui_plot::{Plot, PlotPoints, Line, Legend, VLine};
 use chrono::{Local, DateTime};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-const PLOT_BUFFER_SIZE: usize = 500;
+/// Polling interval of an object dragged onto the plots before it was subscribed
+const DEFAULT_DROP_INTERVAL_MS: u64 = 100;
 
 enum AppView {
     SelectInterface,
@@ -35,8 +143,15 @@ enum AppView {
 
 #[derive(Debug, Clone, PartialEq)]
 enum SidebarTab {
-    SDO,
-    TPDO,
+    Sdo,
+    Tpdo,
+}
+
+/// Content of the central panel
+#[derive(Debug, Clone, PartialEq)]
+enum CentralView {
+    Plots,
+    Watch,
 }
 
 #[derive(Debug, Clone)]
@@ -46,7 +161,15 @@ pub enum SubscriptionStatus {
     Idle,         // Subscribed but no recent data
 }
 
+/// Progress of the last SDO write issued from the modal
 #[derive(Debug, Clone)]
+enum WriteStatus {
+    Pending,
+    Written,
+    Failed(String),
+}
+
+#[derive(Debug)]
 struct SdoSubscription{
     interval_ms: u64,
     plot_data: VecDeque<[f64; 2]>, // [timestamp_seconds, value]
@@ -55,25 +178,81 @@ struct SdoSubscription{
     last_timestamp: Option<DateTime<Local>>,
     status: SubscriptionStatus,
     paused: bool,
-    start_time: DateTime<Local>, // Reference point for relative timestamps
+    rate: RateMeter,
+    stats: ChannelStats,
+    /// History kept by this plot instead of the configured default
+    buffer: Option<PlotBufferConfig>,
+    /// Every sample since the subscription started or was cleared
+    history: SampleHistory,
 }
 
-// Identifier for a specific field within a TPDO
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct TpdoFieldId {
-    tpdo_number: u8,
-    field_name: String,  // e.g., "Temperature", "Pressure", "Status"
+/// What an overlay plot draws of one series
+struct SeriesData {
+    name: String,
+    /// Scaled points timed from the session start
+    points: Vec<[f64; 2]>,
+    value_label: String,
+    unit: String,
+    /// Latest value before scaling, for bit indicators
+    last_raw: Option<f64>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 struct TpdoFieldSubscription {
     plot_data: VecDeque<[f64; 2]>, // [timestamp_seconds, value]
     last_value: Option<String>,
     last_timestamp: Option<DateTime<Local>>,
-    start_time: DateTime<Local>,
+    rate: RateMeter,
+    stats: ChannelStats,
+    /// History kept by this plot instead of the configured default
+    buffer: Option<PlotBufferConfig>,
+    /// Every sample since the subscription started or was cleared
+    history: SampleHistory,
+}
+
+/// A derived channel with the values computed so far
+#[derive(Debug)]
+struct DerivedSubscription {
+    definition: DerivedChannel,
+    expression: Expression,
+    plot_data: VecDeque<[f64; 2]>, // [timestamp_seconds, value]
+    last_value: Option<f64>,
+    last_timestamp: Option<DateTime<Local>>,
+    rate: RateMeter,
+    stats: ChannelStats,
+    /// History kept by this plot instead of the configured default
+    buffer: Option<PlotBufferConfig>,
+    /// Every value since the channel was defined or cleared
+    history: SampleHistory,
+}
+
+impl DerivedSubscription {
+    fn new(definition: DerivedChannel, history: &HistoryConfig) -> Result<Self, String> {
+        let expression = Expression::parse(&definition.expression)?;
+        let history = SampleHistory::new(history, &PlotSeries::Derived(definition.name.clone()).key());
+        Ok(Self {
+            definition,
+            expression,
+            plot_data: VecDeque::new(),
+            last_value: None,
+            last_timestamp: None,
+            rate: RateMeter::default(),
+            stats: ChannelStats::default(),
+            buffer: None,
+            history,
+        })
+    }
+
+    fn clear(&mut self) {
+        self.plot_data.clear();
+        self.stats.reset();
+        self.history.clear();
+        self.expression.reset();
+    }
 }
 
 struct ScreenshotInfo {
+    /// Name before the capture time is appended
     filename: String,
     rect: egui::Rect,
 }
@@ -82,28 +261,53 @@ struct MyApp {
     current_view: AppView,
     available_can_interfaces: Vec<String>,
     selected_can_interface: Option<String>,
+    remote_interface_str: String,
+    /// Playback speed of captures opened with "Replay capture..."
+    replay_speed: f64,
     selected_node_id: Option<u8>,
     node_id_str : String,
     eds_file_path : Option<PathBuf>,
 
     command_tx: Option<Sender<Command>>,
-    update_rx: Option<Receiver<Update>>,
+    update_rx: Option<queue::Receiver<Update>>,
 
     connection_status: bool,
+    interface_lost: bool,
     connection_requested: bool,
+    dropped_samples: u64,
+    bus_stats: Option<BusStats>,
+    comm_metrics: Vec<NodeCommMetrics>,
+    /// NMT state of each node from its heartbeats
+    nmt_states: HashMap<u8, NodeNmtState>,
+    /// When an NMT command was last sent per node (0 = broadcast), so the state changes it causes aren't alarms
+    nmt_commanded: HashMap<u8, Instant>,
+    /// Unacknowledged heartbeat loss and unexpected state change alarms
+    heartbeat_alarms: Vec<String>,
 
     sdo_requested: bool,
     object_dictionary: Option<BTreeMap<u16, SdoObject>>,
 
     subscriptions: HashMap<SdoAddress, SdoSubscription>,
+    /// Display radix of integer values, per object (also for objects mapped into TPDOs)
+    value_radix: HashMap<SdoAddress, Radix>,
+    /// Names of enumerated values, rebuilt when the object dictionary arrives
+    value_descriptions: ValueDescriptions,
 
     // Managing the state of the pop-up configuration modal
     modal_open_for: Option<SdoAddress>,
     modal_interval_str: String,
+    modal_timeout_str: String,
+    /// Value for the write editor, `None` if the object is read-only
+    modal_write_value: Option<WriteValue>,
+    modal_write_status: Option<WriteStatus>,
+    /// Scaling being edited in the SDO dialog
+    modal_scaling: Scaling,
 
     sdo_search_query: String,
     tpdo_search_query: String,
     sidebar_tab: SidebarTab,
+    central_view: CentralView,
+    watch_table: WatchTable,
 
     error_message: Option<String>,
 
@@ -111,34 +315,113 @@ struct MyApp {
     logger: Logger,
 
     show_about_dialog: bool,
+    sdo_console: SdoConsole,
+    /// Indices read and total while the object list of a device without EDS is built
+    object_walk_progress: Option<(usize, usize)>,
+    /// Objects read and total while a snapshot is running
+    snapshot_progress: Option<(usize, usize)>,
+    snapshot_compare: SnapshotCompare,
+    default_report: DefaultReport,
+    /// The running snapshot is for a DCF export
+    dcf_export_pending: bool,
+    parameter_backup: ParameterBackup,
+    macro_editor: MacroEditor,
+    preset_menu: PresetMenu,
+    stimulus: StimulusGenerator,
+    rpdo_panel: RpdoPanel,
+    frame_sender: FrameSender,
+    controller_panel: ControllerPanel,
+    nmt_panel: NmtPanel,
+    lss_panel: LssPanel,
+    startup_panel: StartupPanel,
+    device_check: DeviceCheckPanel,
+    report_panel: ReportPanel,
+    derived_panel: DerivedPanel,
+    /// Manager mode already ran the startup sequence for this connection
+    startup_requested: bool,
+    emcy_monitor: EmcyMonitor,
+    timeline: Timeline,
+    log_viewer: LogViewer,
+    /// Log shown as a read-only session instead of a connection
+    imported_log: Option<PathBuf>,
+    /// Every plot, marker and export is timed from this moment, set on connecting
+    session_start: DateTime<Local>,
+    /// File the latest auto-saved capture or export went to
+    last_capture: Option<PathBuf>,
+    /// "Follow live" toggles by plot id, applied when the plot is drawn next
+    follow_live_requests: HashMap<String, bool>,
+    network_overview: NetworkOverview,
+    /// The viewer is sending SYNC frames
+    sync_producing: bool,
+    gateway_running: bool,
+    gateway_clients: usize,
+    trace_panel: TracePanel,
+    /// The communication thread forwards frames to the trace view
+    trace_running: bool,
+    object_scanner: ObjectScanner,
 
     tpdo_data: Vec<TpdoData>,
+    /// Answer to the last remote request per TPDO, `None` while waiting for it
+    tpdo_requests: HashMap<u8, Option<Result<TpdoData, String>>>,
+    /// Period and jitter of the active TPDOs since they were started
+    tpdo_timing: HashMap<u8, PdoTiming>,
+    /// When the last frame of each active TPDO arrived, to flag TPDOs that went quiet
+    tpdo_last_seen: HashMap<u8, Instant>,
     tpdo_discovery_requested: bool,
+    /// The boot DCF is being written; TPDOs are rediscovered once it is done
+    boot_replay_pending: bool,
+    /// The TPDO layout was loaded from the config instead of read from the device
+    tpdo_layout_cached: bool,
     discovered_tpdos: Vec<communication::TpdoConfig>,
     active_tpdos: std::collections::HashSet<u8>,
 
     tpdo_field_subscriptions: HashMap<TpdoFieldId, TpdoFieldSubscription>,
+    /// Derived channels of the config file by name
+    derived_channels: BTreeMap<String, DerivedSubscription>,
+    /// The active dashboard's subscriptions were started for this connection
+    dashboard_restored: bool,
 }
 
 
 impl Default for MyApp {
     fn default() -> Self {
-        let config = AppConfig::load();
+        let mut config = AppConfig::load();
+        // Makes sure there is a dashboard to show
+        config.dashboard_mut();
         let mut logger = Logger::new();
+        logger.set_frame_events(config.log_frame_events);
+        logger.set_rotation(config.log_rotation);
+        logger.set_black_box(config.black_box, config.get_log_directory(), config.log_format);
         if config.enable_logging {
             if let Some(log_dir) = config.get_log_directory() {
-                if let Err(e) = logger.enable(log_dir) {
+                if let Err(e) = logger.enable(log_dir, config.log_format, config.log_raw_frames) {
                     eprintln!("Failed to enable logging: {}", e);
                 }
             }
         }
 
+        let derived_channels = config.derived_channels.iter()
+            .filter_map(|definition| match DerivedSubscription::new(definition.clone(), &config.history) {
+                Ok(channel) => Some((definition.name.clone(), channel)),
+                Err(e) => {
+                    eprintln!("Skipping derived channel '{}': {}", definition.name, e);
+                    None
+                }
+            })
+            .collect();
+
         // Pre-populate fields from loaded config
         let selected_can_interface = if config.can_interface.is_empty() {
             None
         } else {
             Some(config.can_interface.clone())
         };
+        // Remote buses are stored as URLs, e.g. "socketcand://host:29536/can0"
+        let remote_interface_str = if config.can_interface.contains("://") {
+            config.can_interface.clone()
+        } else {
+            String::new()
+        };
 
         let (selected_node_id, node_id_str) = if config.node_id > 0 && config.node_id <= 127 {
             (Some(config.node_id), config.node_id.to_string())
@@ -152,6 +435,8 @@ impl Default for MyApp {
             current_view: AppView::SelectInterface,
             available_can_interfaces: get_can_interfaces(),
             selected_can_interface,
+            remote_interface_str,
+            replay_speed: 1.0,
             selected_node_id,
             node_id_str,
             eds_file_path,
@@ -160,19 +445,34 @@ impl Default for MyApp {
             update_rx: None,
 
             connection_status: false,
+            interface_lost: false,
             connection_requested: false,
+            dropped_samples: 0,
+            bus_stats: None,
+            comm_metrics: Vec::new(),
+            nmt_states: HashMap::new(),
+            nmt_commanded: HashMap::new(),
+            heartbeat_alarms: Vec::new(),
 
             sdo_requested: false,
             object_dictionary: None,
 
             subscriptions: HashMap::new(),
+            value_radix: HashMap::new(),
+            value_descriptions: ValueDescriptions::new(),
 
             modal_open_for: None,
             modal_interval_str: String::new(),
+            modal_timeout_str: String::new(),
+            modal_write_value: None,
+            modal_write_status: None,
+            modal_scaling: Scaling::default(),
 
             sdo_search_query: String::new(),
             tpdo_search_query: String::new(),
-            sidebar_tab: SidebarTab::SDO,
+            sidebar_tab: SidebarTab::Sdo,
+            central_view: CentralView::Plots,
+            watch_table: WatchTable::new(),
 
             error_message: None,
 
@@ -180,13 +480,54 @@ impl Default for MyApp {
             logger,
 
             show_about_dialog: false,
+            sdo_console: SdoConsole::new(),
+            object_walk_progress: None,
+            snapshot_progress: None,
+            snapshot_compare: SnapshotCompare::new(),
+            default_report: DefaultReport::new(),
+            dcf_export_pending: false,
+            parameter_backup: ParameterBackup::new(),
+            macro_editor: MacroEditor::new(),
+            preset_menu: PresetMenu::new(),
+            stimulus: StimulusGenerator::new(),
+            rpdo_panel: RpdoPanel::new(),
+            frame_sender: FrameSender::new(),
+            controller_panel: ControllerPanel::new(),
+            nmt_panel: NmtPanel::new(),
+            lss_panel: LssPanel::new(),
+            startup_panel: StartupPanel::new(),
+            device_check: DeviceCheckPanel::new(),
+            report_panel: ReportPanel::new(),
+            derived_panel: DerivedPanel::new(),
+            startup_requested: false,
+            emcy_monitor: EmcyMonitor::new(),
+            timeline: Timeline::new(),
+            log_viewer: LogViewer::new(),
+            imported_log: None,
+            session_start: Local::now(),
+            last_capture: None,
+            follow_live_requests: HashMap::new(),
+            network_overview: NetworkOverview::new(),
+            sync_producing: false,
+            gateway_running: false,
+            gateway_clients: 0,
+            trace_panel: TracePanel::new(),
+            trace_running: false,
+            object_scanner: ObjectScanner::new(),
 
             tpdo_data: Vec::new(),
+            tpdo_requests: HashMap::new(),
+            tpdo_timing: HashMap::new(),
+            tpdo_last_seen: HashMap::new(),
             tpdo_discovery_requested: false,
+            boot_replay_pending: false,
+            tpdo_layout_cached: false,
             discovered_tpdos: Vec::new(),
             active_tpdos: HashSet::new(),
 
             tpdo_field_subscriptions: HashMap::new(),
+            derived_channels,
+            dashboard_restored: false,
         }
     }
 }
@@ -194,51 +535,92 @@ impl Default for MyApp {
 impl eframe::App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
 
-        if let Some(update) = self.update_rx.as_mut().and_then(|rx| rx.try_recv().ok()) {
+        while let Some(update) = self.update_rx.as_ref().and_then(|rx| rx.try_recv()) {
             match update {
                 Update::SdoList(objects) => {
+                    self.object_walk_progress = None;
+                    self.value_descriptions = ValueDescriptions::load(&objects, &self.config.value_descriptions);
                     self.object_dictionary = Some(objects);
                 },
 
-                Update::SdoData { address, value } => {
+                Update::ObjectWalkProgress { done, total } => {
+                    self.object_walk_progress = Some((done, total));
+                }
+
+                Update::SdoData { address, value, raw } => {
+                    let scaling = effective_scaling(&self.config, self.object_dictionary.as_ref(), &address);
+                    let (logged_value, description) = match scaling.as_ref().and_then(|scaling| Some((scaling.convert(&value)?, scaling))) {
+                        Some((converted, scaling)) => (converted, Some(scaling.unit.clone())),
+                        None => (value.clone(), self.value_descriptions.describe(&address, &value).map(str::to_string)),
+                    };
                     self.logger.log(LogEvent::SdoData {
+                        node_id: self.config.node_id,
                         index: address.index,
                         sub_index: address.sub_index,
-                        value: value.clone(),
+                        data_type: self.sdo_data_type(&address),
+                        raw,
+                        value: logged_value,
+                        description,
                     });
+                    if let Ok(raw) = value.parse::<f64>() {
+                        self.log_sample(&PlotSeries::Sdo(address.clone()), Local::now(), raw);
+                    }
 
+                    let mut sampled_at = None;
                     if let Some(subscription) = self.subscriptions.get_mut(&address) {
                         let now = Local::now();
                         subscription.last_value = Some(value.clone());
                         subscription.last_timestamp = Some(now);
+                        subscription.rate.record(now);
                         subscription.status = SubscriptionStatus::Active;
 
                         // Only add to plot data if not paused
                         if !subscription.paused {
                             // Try to parse the incoming string value into a number for plotting.
                             if let Ok(number_value) = value.parse::<f64>() {
-                                if subscription.plot_data.len() >= PLOT_BUFFER_SIZE {
-                                    subscription.plot_data.pop_front();
-                                }
-
                                 // Calculate seconds since start time for X-axis
-                                let elapsed_seconds = (now - subscription.start_time).num_milliseconds() as f64 / 1000.0;
+                                let elapsed_seconds = timeline::offset_seconds(now, self.session_start);
                                 subscription.plot_data.push_back([elapsed_seconds, number_value]);
+                                subscription.history.push([elapsed_seconds, number_value]);
+                                subscription.buffer.unwrap_or(self.config.plot_buffer).trim(&mut subscription.plot_data);
+                                subscription.stats.record(number_value);
+                                sampled_at = Some(now);
                             }
                         }
                     }
+                    if let Some(now) = sampled_at {
+                        self.update_derived(&[PlotSeries::Sdo(address)], now);
+                    }
                 }
                 Update::ConnectionFailed(error) => {
                     self.logger.log(LogEvent::ConnectionFailed(error.clone()));
+                    self.timeline.record(EventKind::Connection, format!("Connection error: {}", error));
                     self.error_message = Some(format!("Connection Error: {}", error));
                     self.connection_status = false;
                 }
                 Update::ConnectionStatus(is_alive) => {
                     self.logger.log(LogEvent::ConnectionStatus(is_alive));
+                    if is_alive != self.connection_status {
+                        self.timeline.record(EventKind::Connection, if is_alive { "Connected" } else { "Connection lost" }.to_string());
+                    }
                     self.connection_status = is_alive;
                 }
+                Update::InterfaceLost(reason) => {
+                    self.logger.log(LogEvent::InterfaceLost(reason.clone()));
+                    self.timeline.record(EventKind::Connection, format!("CAN interface lost ({})", reason));
+                    self.error_message = Some(format!("CAN interface lost ({}), waiting for it to come back...", reason));
+                    self.connection_status = false;
+                    self.interface_lost = true;
+                }
+                Update::InterfaceRestored => {
+                    self.logger.log(LogEvent::InterfaceRestored);
+                    self.timeline.record(EventKind::Connection, "CAN interface restored".to_string());
+                    self.interface_lost = false;
+                    self.error_message = None;
+                }
                 Update::SdoReadError { address, error } => {
                     self.logger.log(LogEvent::SdoError {
+                        node_id: self.config.node_id,
                         index: address.index,
                         sub_index: address.sub_index,
                         error: error.clone(),
@@ -250,54 +632,307 @@ impl eframe::App for MyApp {
 
                     self.error_message = Some(format!("SDO Read Error [{:#06X}:{:02X}]: {}", address.index, address.sub_index, error));
                 }
-                Update::TpdoData(tpdo_data) => {
-                    self.logger.log(LogEvent::TpdoData {
-                        tpdo_number: tpdo_data.tpdo_number,
-                        values: tpdo_data.values.clone(),
+                Update::SdoWriteResult { address, data, result } => {
+                    self.logger.log(LogEvent::SdoWrite {
+                        node_id: self.config.node_id,
+                        index: address.index,
+                        sub_index: address.sub_index,
+                        data,
+                        error: result.as_ref().err().cloned(),
                     });
 
-                    // Store TPDO data (keep last 50 messages)
-                    let now = tpdo_data.timestamp;
-
-                    for (field_name, value_str) in &tpdo_data.values {
-                        let field_id = TpdoFieldId {
+                    if self.modal_open_for.as_ref() == Some(&address) {
+                        self.modal_write_status = Some(match result {
+                            Ok(()) => WriteStatus::Written,
+                            Err(err) => WriteStatus::Failed(err),
+                        });
+                    } else if self.parameter_backup.signature_written(&address, &result) {
+                        // Shown in the backup window
+                    } else if let Err(err) = result {
+                        self.error_message = Some(format!("SDO Write Error [{:#06X}:{:02X}]: {}", address.index, address.sub_index, err));
+                    }
+                }
+                Update::SnapshotProgress { done, total } => {
+                    if self.snapshot_progress.is_some() {
+                        self.snapshot_progress = Some((done, total));
+                    }
+                }
+                Update::SnapshotComplete(entries) => {
+                    self.snapshot_progress = None;
+                    if self.snapshot_compare.waiting_for_live() {
+                        self.snapshot_compare.set_live(entries);
+                    } else if self.default_report.waiting_for_live() {
+                        let objects = self.object_dictionary.clone().unwrap_or_default();
+                        self.default_report.set_live(&objects, &entries, self.config.node_id);
+                    } else if self.dcf_export_pending {
+                        self.dcf_export_pending = false;
+                        self.save_dcf(&entries);
+                    } else if self.parameter_backup.backup_running() {
+                        if let Some((node_id, entries)) = self.parameter_backup.finish_backup(self.config.node_id, &entries) {
+                            if let Some(tx) = &self.command_tx {
+                                let _ = tx.send(Command::RestoreParameters { node_id, entries });
+                            }
+                        }
+                    } else {
+                        self.save_snapshot(&entries);
+                    }
+                }
+                Update::RestoreProgress { done, total } => {
+                    self.parameter_backup.restore_progress(done, total);
+                }
+                Update::RestoreComplete(outcomes) => {
+                    if self.boot_replay_pending {
+                        self.boot_replay_pending = false;
+                        let failed = outcomes.iter().filter(|outcome| outcome.result.is_err()).count();
+                        if failed > 0 {
+                            self.error_message = Some(format!(
+                                "Boot DCF: {} of {} writes failed, see Parameter Backup", failed, outcomes.len()
+                            ));
+                        } else {
+                            println!("✓ Wrote {} boot DCF values", outcomes.len());
+                        }
+                        self.restart_after_boot();
+                    }
+                    self.parameter_backup.finish_restore(outcomes);
+                }
+                Update::MacroStepResult { step, result } => {
+                    // Macros may send NMT commands to the node
+                    self.nmt_commanded.insert(self.config.node_id, Instant::now());
+                    self.macro_editor.step_finished(step, result);
+                }
+                Update::MacroFinished => {
+                    self.macro_editor.finished();
+                }
+                Update::StimulusWritten { value, result } => {
+                    self.stimulus.written(value, result);
+                }
+                Update::StimulusStopped => {
+                    self.stimulus.stopped();
+                }
+                Update::GatewayStopped(error) => {
+                    self.gateway_running = false;
+                    self.gateway_clients = 0;
+                    self.error_message = Some(format!("ASCII gateway stopped: {}", error));
+                }
+                Update::GatewayClients(clients) => {
+                    self.gateway_clients = clients;
+                }
+                Update::TraceFrames(mut entries) => {
+                    self.logger.log_frames(&entries);
+                    // The trace lists what the viewer receives
+                    entries.retain(|entry| !entry.transmitted);
+                    self.trace_panel.push(entries, self.config.node_id, &self.discovered_tpdos);
+                }
+                Update::SyncStopped(error) => {
+                    self.sync_producing = false;
+                    self.error_message = Some(format!("SYNC production stopped: {}", error));
+                }
+                Update::RpdosDiscovered(rpdos) => {
+                    self.rpdo_panel.discovered(rpdos);
+                }
+                Update::NmtSent { node_id, command, result } => {
+                    match &result {
+                        Ok(()) => {
+                            self.nmt_commanded.insert(node_id, Instant::now());
+                        }
+                        Err(err) => {
+                            self.error_message = Some(format!("Failed to send NMT {}: {}", command, err));
+                        }
+                    }
+                    self.nmt_panel.sent(node_id, command, result);
+                }
+                Update::LssResult { command, result } => {
+                    self.lss_panel.sent(command, result);
+                }
+                Update::StartupStep { step, result } => {
+                    // The sequence ends with NMT Start
+                    self.nmt_commanded.insert(self.config.node_id, Instant::now());
+                    match &result {
+                        Ok(message) => println!("Startup: {}: {}", step, message),
+                        Err(err) => eprintln!("Startup: {} failed: {}", step, err),
+                    }
+                    self.startup_panel.step_finished(step, result);
+                }
+                Update::StartupFinished => {
+                    if !self.startup_panel.finished() {
+                        self.error_message = Some("Startup sequence failed, see the Startup Sequence window".to_string());
+                    }
+                    if self.boot_replay_pending {
+                        self.boot_replay_pending = false;
+                        self.restart_after_boot();
+                    }
+                }
+                Update::DeviceCheck { check, outcome } => {
+                    self.device_check.check_finished(check, outcome);
+                }
+                Update::DeviceCheckFinished => {
+                    self.device_check.finished();
+                }
+                Update::RpdoSent { rpdo_number, result } => {
+                    self.rpdo_panel.transmitted(rpdo_number, result);
+                }
+                Update::RpdoStopped { rpdo_number, error } => {
+                    self.rpdo_panel.stopped(rpdo_number, error);
+                }
+                Update::FrameSent(result) => {
+                    self.frame_sender.transmitted(result);
+                }
+                Update::FrameStopped(error) => {
+                    self.frame_sender.stopped(error);
+                }
+                Update::ControllerState(status) => {
+                    self.controller_panel.update(status);
+                }
+                Update::ControllerRestarted(result) => {
+                    self.controller_panel.restarted(result);
+                }
+                Update::ScanProgress { done, total } => {
+                    self.object_scanner.progress(done, total);
+                }
+                Update::ScanHit(hit) => {
+                    self.object_scanner.found(hit);
+                }
+                Update::ScanFinished => {
+                    self.object_scanner.finished();
+                }
+                Update::RawSdo(exchange) => {
+                    self.sdo_console.record(exchange);
+                }
+                Update::TpdoBatch(samples) => {
+                    for tpdo_data in samples {
+                        let mapped_objects = self.discovered_tpdos.iter()
+                            .find(|config| config.tpdo_number == tpdo_data.tpdo_number)
+                            .map(|config| config.mapped_objects.as_slice())
+                            .unwrap_or_default();
+                        self.logger.log(LogEvent::TpdoData {
+                            node_id: self.config.node_id,
                             tpdo_number: tpdo_data.tpdo_number,
-                            field_name: field_name.clone(),
-                        };
+                            raw: tpdo_data.raw.clone(),
+                            values: tpdo_data.values.iter()
+                                .map(|(name, value)| {
+                                    let mapped_object = mapped_objects.iter().find(|obj| &obj.name == name);
+                                    let label = mapped_object
+                                        .map(|obj| SdoAddress { index: obj.index, sub_index: obj.sub_index })
+                                        .map_or_else(|| value.clone(), |address| {
+                                            let scaling = effective_scaling(&self.config, self.object_dictionary.as_ref(), &address);
+                                            display_value(scaling.as_ref(), &self.value_descriptions, Radix::Decimal, &address, value, &SdoDataType::Real32)
+                                        });
+                                    TpdoField {
+                                        name: name.clone(),
+                                        value: value.clone(),
+                                        label,
+                                        data_type: mapped_object.map(|obj| obj.data_type.clone()),
+                                    }
+                                })
+                                .collect(),
+                        });
 
-                        if let Ok(numeric_value) = value_str.parse::<f64>() {
-                            let subscription = self.tpdo_field_subscriptions
-                                .entry(field_id.clone())
-                                .or_insert_with(|| TpdoFieldSubscription {
-                                    plot_data: VecDeque::new(),
-                                    last_value: None,
-                                    last_timestamp: None,
-                                    start_time: now,
-                                });
+                        // Store TPDO data (keep last 50 messages)
+                        let now = tpdo_data.timestamp;
+                        self.tpdo_timing.entry(tpdo_data.tpdo_number).or_default().record(now, tpdo_data.hardware_time);
+                        self.tpdo_last_seen.insert(tpdo_data.tpdo_number, Instant::now());
 
-                            subscription.last_value = Some(value_str.clone());
-                            subscription.last_timestamp = Some(now);
+                        let mut sampled = Vec::new();
+                        for (field_name, value_str) in &tpdo_data.values {
+                            let field_id = TpdoFieldId {
+                                tpdo_number: tpdo_data.tpdo_number,
+                                field_name: field_name.clone(),
+                            };
 
-                            if subscription.plot_data.len() >= PLOT_BUFFER_SIZE {
-                                subscription.plot_data.pop_front();
-                            }
+                            if let Ok(numeric_value) = value_str.parse::<f64>() {
+                                self.log_sample(&PlotSeries::Tpdo(field_id.clone()), now, numeric_value);
+                                if !self.tpdo_field_subscriptions.contains_key(&field_id) {
+                                    self.show_on_dashboard(&PlotSeries::Tpdo(field_id.clone()));
+                                }
+                                let subscription = self.tpdo_field_subscriptions
+                                    .entry(field_id.clone())
+                                    .or_insert_with(|| TpdoFieldSubscription {
+                                        plot_data: VecDeque::new(),
+                                        last_value: None,
+                                        last_timestamp: None,
+                                        rate: RateMeter::default(),
+                                        stats: ChannelStats::default(),
+                                        buffer: None,
+                                        history: SampleHistory::new(&self.config.history, &PlotSeries::Tpdo(field_id.clone()).key()),
+                                    });
+
+                                subscription.last_value = Some(value_str.clone());
+                                subscription.last_timestamp = Some(now);
+                                subscription.rate.record(now);
 
-                            // Calculate seconds since start time for X-axis
-                            let elapsed_seconds = (now - subscription.start_time).num_milliseconds() as f64 / 1000.0;
-                            subscription.plot_data.push_back([elapsed_seconds, numeric_value]);
+                                // Calculate seconds since start time for X-axis
+                                let elapsed_seconds = timeline::offset_seconds(now, self.session_start);
+                                subscription.plot_data.push_back([elapsed_seconds, numeric_value]);
+                                subscription.history.push([elapsed_seconds, numeric_value]);
+                                subscription.buffer.unwrap_or(self.config.plot_buffer).trim(&mut subscription.plot_data);
+                                subscription.stats.record(numeric_value);
+                                sampled.push(PlotSeries::Tpdo(field_id));
+                            }
                         }
-                    }
+                        self.update_derived(&sampled, now);
 
-                    self.tpdo_data.push(tpdo_data);
-                    if self.tpdo_data.len() > 50 {
-                        self.tpdo_data.remove(0);
+                        self.tpdo_data.push(tpdo_data);
+                        if self.tpdo_data.len() > 50 {
+                            self.tpdo_data.remove(0);
+                        }
                     }
                 }
+                Update::TpdoRequested { tpdo_number, result } => {
+                    self.tpdo_requests.insert(tpdo_number, Some(result));
+                }
                 Update::TpdosDiscovered(tpdos) => {
+                    // Listeners for TPDOs that disappeared were stopped by the communication thread
+                    self.active_tpdos.retain(|num| tpdos.iter().any(|c| c.tpdo_number == *num));
+                    self.tpdo_layout_cached = false;
+
+                    if !tpdos.is_empty() {
+                        self.config.set_tpdo_layout(self.config.node_id, tpdos.clone());
+                        if let Err(e) = self.config.save() {
+                            eprintln!("Failed to save TPDO layout: {}", e);
+                        }
+                    }
                     self.discovered_tpdos = tpdos;
+                    self.restore_dashboard();
+                }
+                Update::Overflow { dropped } => {
+                    self.dropped_samples += dropped;
+                }
+                Update::BusStats(stats) => {
+                    self.bus_stats = Some(stats);
+                }
+                Update::CommMetrics(metrics) => {
+                    self.comm_metrics = metrics;
+                }
+                Update::Emergency(record) => {
+                    self.logger.log(LogEvent::Emergency {
+                        node_id: record.node_id,
+                        message: record.message.to_string(),
+                    });
+                    self.timeline.record_at(record.timestamp, EventKind::Emergency, format!("Node {}: {}", record.node_id, record.message));
+                    self.emcy_monitor.record(record);
+                }
+                Update::NmtState { node_id, state } => {
+                    self.logger.log(LogEvent::NmtState { node_id, state: state.to_string() });
+                    let commanded = [node_id, NMT_BROADCAST_NODE_ID].iter()
+                        .filter_map(|id| self.nmt_commanded.get(id))
+                        .any(|sent| sent.elapsed() < heartbeat::COMMAND_GRACE);
+                    let previous = self.nmt_states.insert(node_id, state);
+                    if let Some(alarm) = heartbeat::alarm(previous, state, commanded) {
+                        let message = format!("Node {}: {}", node_id, alarm);
+                        self.logger.log(LogEvent::HeartbeatAlarm(message.clone()));
+                        self.timeline.record(EventKind::Alarm, message.clone());
+                        self.heartbeat_alarms.push(format!("{} {}", Local::now().format("%H:%M:%S"), message));
+                    }
+                    if node_id == self.config.node_id
+                        && state == NodeNmtState::Active(NmtState::BootUp)
+                        && self.config.reconfigure_on_boot
+                    {
+                        self.node_booted();
+                    }
                 }
             }
         }
+        self.logger.tick();
 
         let events = ctx.input(|i| i.events.clone());
         for event in &events {
@@ -325,6 +960,66 @@ impl eframe::App for MyApp {
 }
 
 impl MyApp {
+    /// Close the current connection and go back to the connection wizard
+    fn disconnect(&mut self) {
+        if let Some(tx) = self.command_tx.take() {
+            let _ = tx.send(Command::Disconnect);
+        }
+        self.update_rx = None;
+
+        self.connection_status = false;
+        self.interface_lost = false;
+        self.connection_requested = false;
+        self.startup_requested = false;
+        self.startup_panel.finished();
+        self.device_check.finished();
+        self.dropped_samples = 0;
+        self.bus_stats = None;
+        self.comm_metrics.clear();
+        self.nmt_states.clear();
+        self.nmt_commanded.clear();
+        self.sdo_requested = false;
+        self.object_dictionary = None;
+        self.value_descriptions = ValueDescriptions::new();
+        self.subscriptions.clear();
+        self.modal_open_for = None;
+        self.object_walk_progress = None;
+        self.snapshot_progress = None;
+        self.snapshot_compare.cancel_live();
+        self.default_report.cancel_live();
+        self.dcf_export_pending = false;
+        self.boot_replay_pending = false;
+        self.parameter_backup.cancel_backup();
+        self.macro_editor.finished();
+        self.stimulus.stopped();
+        self.rpdo_panel.reset();
+        self.frame_sender.reset();
+        self.controller_panel.reset();
+        self.sync_producing = false;
+        self.gateway_running = false;
+        self.gateway_clients = 0;
+        self.trace_running = false;
+        self.object_scanner.finished();
+        self.error_message = None;
+        self.tpdo_data.clear();
+        self.tpdo_requests.clear();
+        self.tpdo_timing.clear();
+        self.tpdo_last_seen.clear();
+        self.tpdo_discovery_requested = false;
+        self.tpdo_layout_cached = false;
+        self.discovered_tpdos.clear();
+        self.active_tpdos.clear();
+        self.tpdo_field_subscriptions.clear();
+        for channel in self.derived_channels.values_mut() {
+            channel.clear();
+        }
+        self.dashboard_restored = false;
+        self.imported_log = None;
+
+        self.available_can_interfaces = get_can_interfaces();
+        self.current_view = AppView::SelectInterface;
+    }
+
     /// Draws the UI for selecting the CAN interface using a centered window.
     fn draw_interface_view(&mut self, ui: &mut egui::Ui) {
         egui::Window::new("Interface Selection")
@@ -337,6 +1032,12 @@ impl MyApp {
                 // This layout just centers widgets horizontally.
                 ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
                     ui.set_width(300.0); // Give the panel a fixed width
+                    if !self.config.recent_setups.is_empty() {
+                        self.draw_recent_setups(ui);
+                        ui.add_space(10.0);
+                        ui.separator();
+                    }
+
                     ui.heading("Step 1: Select CAN Interface");
                     ui.add_space(20.0); // Spacers will now work reliably
 
@@ -347,12 +1048,12 @@ impl MyApp {
                             self.available_can_interfaces = get_can_interfaces();
                         }
                     } else {
-                        let selected_text = self.selected_can_interface.as_deref().unwrap_or("Click to select...");
+                        let selected_text = self.selected_can_interface.as_deref().map_or("Click to select...", interface_label);
                         egui::ComboBox::from_label("") // Label can be empty if it's clear from context
                             .selected_text(selected_text)
                             .show_ui(ui, |ui| {
                                 for interface in &self.available_can_interfaces {
-                                    ui.selectable_value(&mut self.selected_can_interface, Some(interface.clone()), interface);
+                                    ui.selectable_value(&mut self.selected_can_interface, Some(interface.clone()), interface_label(interface));
                                 }
                             });
 
@@ -363,10 +1064,102 @@ impl MyApp {
                             self.current_view = AppView::SelectNodeId;
                         }
                     }
+
+                    // Remote bus over TCP (socketcand / cannelloni)
+                    ui.add_space(20.0);
+                    ui.separator();
+                    ui.label("Or connect to a remote CAN bus:");
+                    ui.text_edit_singleline(&mut self.remote_interface_str)
+                        .on_hover_text("socketcand://host:29536/can0, cannelloni://host:20000 or gs_usb://0?bitrate=250000");
+                    let remote = self.remote_interface_str.trim().to_string();
+                    if ui.add_enabled(remote.contains("://"), egui::Button::new("Use remote ➡")).clicked() {
+                        self.selected_can_interface = Some(remote);
+                        self.current_view = AppView::SelectNodeId;
+                    }
+
+                    // Recorded capture played back as a live bus
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        if ui.button("📂 Replay capture...").on_hover_text("candump log, CSV, pcap or pcapng").clicked() {
+                            let file = rfd::FileDialog::new()
+                                .add_filter("CAN capture", &["log", "txt", "csv", "pcap", "pcapng"])
+                                .pick_file();
+
+                            if let Some(path) = file {
+                                let transport = TransportConfig::Replay { path, looped: false, speed: self.replay_speed };
+                                self.selected_can_interface = Some(transport.to_string());
+                                self.current_view = AppView::SelectNodeId;
+                            }
+                        }
+                        egui::ComboBox::from_id_salt("replay_speed")
+                            .selected_text(format!("{}×", self.replay_speed))
+                            .show_ui(ui, |ui| {
+                                for speed in [1.0, 10.0, 100.0] {
+                                    ui.selectable_value(&mut self.replay_speed, speed, format!("{}×", speed));
+                                }
+                            })
+                            .response
+                            .on_hover_text("Playback speed; samples keep their recorded timestamps");
+                    });
+                    if ui.button("📈 Open log...").on_hover_text("Plot an earlier CSV or JSON Lines log without connecting").clicked() {
+                        let mut dialog = rfd::FileDialog::new().add_filter("Event log", &["csv", "jsonl", "gz", "zst"]);
+                        if let Some(log_dir) = self.config.get_log_directory() {
+                            dialog = dialog.set_directory(log_dir);
+                        }
+                        if let Some(path) = dialog.pick_file() {
+                            self.open_log_session(path);
+                        }
+                    }
                 });
             });
     }
 
+    /// One-click entries for setups used before, skipping the wizard
+    fn draw_recent_setups(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Quick Start");
+        ui.add_space(5.0);
+
+        let mut start = None;
+        let mut remove = None;
+        for (i, setup) in self.config.recent_setups.iter().enumerate() {
+            let eds = setup.eds_file_path.as_deref().map(PathBuf::from);
+            let eds_name = eds.as_ref()
+                .and_then(|path| path.file_name())
+                .map_or("no EDS".to_string(), |name| name.to_string_lossy().to_string());
+            let eds_missing = eds.as_ref().is_some_and(|path| !path.exists());
+
+            ui.horizontal(|ui| {
+                let label = format!("▶ {} · node {} · {}", interface_label(&setup.can_interface), setup.node_id, eds_name);
+                let button = ui.add_enabled(!eds_missing, egui::Button::new(label));
+                let button = if eds_missing {
+                    button.on_disabled_hover_text("The EDS file no longer exists")
+                } else {
+                    button.on_hover_text(setup.eds_file_path.as_deref().unwrap_or("Without an EDS file"))
+                };
+                if button.clicked() {
+                    start = Some(setup.clone());
+                }
+                if ui.small_button("✖").on_hover_text("Remove from the list").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+
+        if let Some(i) = remove {
+            self.config.recent_setups.remove(i);
+            if let Err(e) = self.config.save() {
+                eprintln!("Failed to save configuration: {}", e);
+            }
+        }
+        if let Some(setup) = start {
+            self.selected_can_interface = Some(setup.can_interface);
+            self.selected_node_id = Some(setup.node_id);
+            self.node_id_str = setup.node_id.to_string();
+            self.eds_file_path = setup.eds_file_path.map(PathBuf::from);
+            self.start_session();
+        }
+    }
+
     fn draw_node_id_view(&mut self, ui: &mut egui::Ui) {
         egui::Window::new("Node ID Selection")
             .title_bar(false)
@@ -382,7 +1175,7 @@ impl MyApp {
 
                     // Show the previously selected interface for context.
                     if let Some(interface) = &self.selected_can_interface {
-                        ui.label(format!("Interface: {}", interface));
+                        ui.label(format!("Interface: {}", interface_label(interface)));
                     }
                     ui.add_space(10.0);
 
@@ -410,6 +1203,9 @@ impl MyApp {
 
                         let is_start_enabled = self.selected_node_id.is_some();
                         if ui.add_enabled(is_start_enabled, egui::Button::new("Next ➡")).clicked() {
+                            if let Some(path) = self.selected_node_id.and_then(|node_id| self.config.node_eds_file(node_id)) {
+                                self.eds_file_path = Some(path);
+                            }
                             self.current_view = AppView::SelectEDSFile;
                         }
                     });
@@ -457,39 +1253,187 @@ impl MyApp {
                             self.current_view = AppView::SelectNodeId;
                         }
                         if ui.button("🚀Start").clicked() {
-                            // Update and save configuration
-                            self.config.can_interface = self.selected_can_interface.clone().unwrap();
-                            self.config.node_id = self.selected_node_id.unwrap();
-                            self.config.eds_file_path = self.eds_file_path.as_ref().map(|p| p.display().to_string());
+                            self.start_session();
+                        }
+                    });
+                });
+            });
+    }
 
-                            if let Err(e) = self.config.save() {
-                                eprintln!("Failed to save configuration: {}", e);
-                            }
+    /// Save the wizard's choices and start the communication thread
+    fn start_session(&mut self) {
+        // Update and save configuration
+        self.config.can_interface = self.selected_can_interface.clone().unwrap();
+        self.config.node_id = self.selected_node_id.unwrap();
+        self.config.eds_file_path = self.eds_file_path.as_ref().map(|p| p.display().to_string());
+        self.config.set_node_eds_file(self.config.node_id, self.eds_file_path.as_deref());
+        self.config.add_recent_setup(RecentSetup {
+            can_interface: self.config.can_interface.clone(),
+            node_id: self.config.node_id,
+            eds_file_path: self.config.eds_file_path.clone(),
+        });
 
-                            let (command_tx, command_rx) = std::sync::mpsc::channel();
-                            let (update_tx, update_rx) = std::sync::mpsc::channel();
+        if let Err(e) = self.config.save() {
+            eprintln!("Failed to save configuration: {}", e);
+        }
 
-                            self.command_tx = Some(command_tx);
-                            self.update_rx = Some(update_rx);
+        let (command_tx, command_rx) = std::sync::mpsc::channel();
+        let (update_tx, update_rx) = queue::channel(communication::UPDATE_QUEUE_CAPACITY);
+
+        self.command_tx = Some(command_tx);
+        self.update_rx = Some(update_rx);
+        self.session_start = Local::now();
+
+        let can_interface = self.selected_can_interface.clone().unwrap();
+        let node_id = self.selected_node_id.unwrap();
+        let eds_file_path = self.eds_file_path.clone();
+        let settings = communication::ConnectionSettings {
+            sdo_timeout: self.config.sdo_timeout(),
+            node_sdo_timeout: self.config.node_sdo_timeout(node_id),
+            retry_policy: self.config.sdo_retry.policy(),
+            bus_bitrate: self.config.bus_bitrate,
+            tpdo_type_overrides: self.config.tpdo_type_overrides(),
+            heartbeat_consumer_time: self.config.heartbeat_consumer_time(node_id),
+        };
 
-                            let can_interface = self.selected_can_interface.clone().unwrap();
-                            let node_id = self.selected_node_id.unwrap();
-                            let eds_file_path = self.eds_file_path.clone();
+        std::thread::spawn(move || {
+            communication::communication_thread_main(
+                command_rx,
+                update_tx,
+                can_interface,
+                node_id,
+                eds_file_path,
+                settings,
+            );
+        });
+        self.current_view = AppView::Main;
+    }
 
-                            std::thread::spawn(move || {
-                                communication::communication_thread_main(
-                                    command_rx,
-                                    update_tx,
-                                    can_interface,
-                                    node_id,
-                                    eds_file_path,
-                                );
-                            });
-                            self.current_view = AppView::Main;
-                        }
-                    });
-                });
-            });
+    /// Show an earlier log as a read-only session: its values in the plots and statistics,
+    /// named and scaled with the EDS last used, its events on the timeline
+    fn open_log_session(&mut self, path: PathBuf) {
+        let log = match log_import::import(&path) {
+            Ok(log) => log,
+            Err(e) => {
+                self.error_message = Some(e);
+                return;
+            }
+        };
+        let Some(start) = log.start() else {
+            self.error_message = Some(format!("{} has no values or events to show", path.display()));
+            return;
+        };
+        self.disconnect();
+        self.session_start = start;
+
+        let objects = match &self.eds_file_path {
+            Some(eds) => communication::search_for_readable_sdo(eds.clone()).unwrap_or_else(|e| {
+                eprintln!("Failed to load EDS {:?}: {}", eds, e);
+                BTreeMap::new()
+            }),
+            None => BTreeMap::new(),
+        };
+        self.value_descriptions = ValueDescriptions::load(&objects, &self.config.value_descriptions);
+        self.object_dictionary = Some(objects);
+
+        let sample_count = log.samples.len();
+        for sample in log.samples {
+            let now = sample.at;
+            let elapsed_seconds = timeline::offset_seconds(now, start);
+            match &sample.series {
+                PlotSeries::Sdo(address) => {
+                    if !self.subscriptions.contains_key(address) {
+                        let data_type = self.sdo_data_type(address).unwrap_or(SdoDataType::Real32);
+                        self.subscriptions.insert(address.clone(), SdoSubscription {
+                            interval_ms: 0,
+                            plot_data: VecDeque::new(),
+                            data_type,
+                            last_value: None,
+                            last_timestamp: None,
+                            status: SubscriptionStatus::Idle,
+                            paused: false,
+                            rate: RateMeter::default(),
+                            stats: ChannelStats::default(),
+                            buffer: None,
+                            history: SampleHistory::new(&self.config.history, &address.eds_key()),
+                        });
+                        self.show_on_dashboard(&sample.series);
+                    }
+                    // Scaled objects were logged converted; the plots convert the raw value
+                    let value = effective_scaling(&self.config, self.object_dictionary.as_ref(), address)
+                        .and_then(|scaling| scaling.raw(&sample.value))
+                        .unwrap_or(sample.value);
+                    let Some(subscription) = self.subscriptions.get_mut(address) else {
+                        continue;
+                    };
+                    subscription.last_value = Some(value.clone());
+                    subscription.last_timestamp = Some(now);
+                    subscription.rate.record(now);
+                    if let Ok(number_value) = value.parse::<f64>() {
+                        subscription.plot_data.push_back([elapsed_seconds, number_value]);
+                        subscription.history.push([elapsed_seconds, number_value]);
+                        subscription.buffer.unwrap_or(self.config.plot_buffer).trim(&mut subscription.plot_data);
+                        subscription.stats.record(number_value);
+                    }
+                }
+                PlotSeries::Tpdo(field_id) => {
+                    let Ok(numeric_value) = sample.value.parse::<f64>() else {
+                        continue;
+                    };
+                    if !self.tpdo_field_subscriptions.contains_key(field_id) {
+                        self.show_on_dashboard(&sample.series);
+                    }
+                    let subscription = self.tpdo_field_subscriptions
+                        .entry(field_id.clone())
+                        .or_insert_with(|| TpdoFieldSubscription {
+                            plot_data: VecDeque::new(),
+                            last_value: None,
+                            last_timestamp: None,
+                            rate: RateMeter::default(),
+                            stats: ChannelStats::default(),
+                            buffer: None,
+                            history: SampleHistory::new(&self.config.history, &sample.series.key()),
+                        });
+                    subscription.last_value = Some(sample.value.clone());
+                    subscription.last_timestamp = Some(now);
+                    subscription.rate.record(now);
+                    subscription.plot_data.push_back([elapsed_seconds, numeric_value]);
+                    subscription.history.push([elapsed_seconds, numeric_value]);
+                    subscription.buffer.unwrap_or(self.config.plot_buffer).trim(&mut subscription.plot_data);
+                    subscription.stats.record(numeric_value);
+                }
+                PlotSeries::Derived(name) => {
+                    // Only channels still defined; their logged values are shown, not recomputed
+                    let (Some(channel), Ok(value)) = (self.derived_channels.get_mut(name), sample.value.parse::<f64>()) else {
+                        continue;
+                    };
+                    channel.last_value = Some(value);
+                    channel.last_timestamp = Some(now);
+                    channel.rate.record(now);
+                    channel.plot_data.push_back([elapsed_seconds, value]);
+                    channel.history.push([elapsed_seconds, value]);
+                    channel.buffer.unwrap_or(self.config.plot_buffer).trim(&mut channel.plot_data);
+                    channel.stats.record(value);
+                    self.show_on_dashboard(&sample.series);
+                }
+            }
+        }
+        for (at, kind, text) in log.events {
+            self.timeline.record_at(at, kind, text);
+        }
+
+        println!("✓ Opened log {:?} with {} samples", path, sample_count);
+        self.imported_log = Some(path);
+        self.current_view = AppView::Main;
+    }
+
+    /// Reconnect to another node on the same interface, with the EDS remembered for it
+    fn switch_node(&mut self, node_id: u8) {
+        self.disconnect();
+        self.selected_node_id = Some(node_id);
+        self.node_id_str = node_id.to_string();
+        self.eds_file_path = self.config.node_eds_file(node_id);
+        self.start_session();
     }
 
     /// Draws the main application view.
@@ -509,8 +1453,22 @@ impl MyApp {
             }
         }
 
-        if !self.tpdo_discovery_requested && self.connection_status && self.object_dictionary.is_some() {
-            if let Some(tx) = &self.command_tx {
+        if self.config.manager_mode && !self.startup_requested && self.connection_status {
+            self.startup_requested = true;
+            self.run_startup();
+        }
+
+        // Discovery waits for the startup sequence, which may change the PDO configuration
+        if !self.tpdo_discovery_requested && self.connection_status && self.object_dictionary.is_some() && !self.startup_panel.running() {
+            if let Some(tpdos) = self.config.tpdo_layout(self.config.node_id) {
+                // Skip reading the PDO configuration again until the user asks for it
+                println!("Using cached TPDO layout ({} TPDOs)", tpdos.len());
+                self.discovered_tpdos = tpdos.clone();
+                communication::apply_type_overrides(&mut self.discovered_tpdos, &self.config.tpdo_type_overrides());
+                self.tpdo_layout_cached = true;
+                self.tpdo_discovery_requested = true;
+                self.restore_dashboard();
+            } else if let Some(tx) = &self.command_tx {
                 let _ = tx.send(Command::DiscoverTpdos);
                 self.tpdo_discovery_requested = true;
             }
@@ -522,20 +1480,153 @@ impl MyApp {
                 // Connection status indicator
                 let status_color = if self.connection_status {
                     Color32::from_rgb(0, 200, 0) // Green
+                } else if self.imported_log.is_some() {
+                    Color32::GRAY
                 } else {
                     Color32::from_rgb(200, 0, 0) // Red
                 };
-                let status_text = if self.connection_status { "● Connected" } else { "● Disconnected" };
+                let status_text = if self.connection_status {
+                    "● Connected"
+                } else if self.imported_log.is_some() {
+                    "● Log file (read-only)"
+                } else if self.interface_lost {
+                    "● Interface lost (reconnecting)"
+                } else {
+                    "● Disconnected"
+                };
                 ui.colored_label(status_color, status_text);
 
+                if let Some(node_id) = self.selected_node_id {
+                    // Also shown after heartbeat loss marked the node disconnected
+                    if self.connection_status || self.nmt_states.contains_key(&node_id) {
+                        let state = self.nmt_states.get(&node_id).copied().unwrap_or(NodeNmtState::NoHeartbeat);
+                        let (color, hover) = match state {
+                            NodeNmtState::Active(NmtState::Operational) => (Color32::from_rgb(0, 200, 0), "PDOs are exchanged"),
+                            NodeNmtState::Active(NmtState::PreOperational) => (Color32::from_rgb(255, 165, 0), "Only SDOs: the node sends no PDOs until it is started"),
+                            NodeNmtState::Active(NmtState::BootUp) => (Color32::from_rgb(255, 165, 0), "The node just booted and enters Pre-Operational"),
+                            NodeNmtState::Active(NmtState::Stopped) => (Color32::from_rgb(200, 0, 0), "No PDOs or SDOs: the node only answers NMT commands"),
+                            NodeNmtState::Silent => (Color32::from_rgb(200, 0, 0), "Heartbeats stopped arriving"),
+                            NodeNmtState::NoHeartbeat => (Color32::GRAY, "No heartbeat received; the node's heartbeat producer (0x1017) may be off"),
+                        };
+                        ui.colored_label(color, format!("NMT: {}", state)).on_hover_text(hover);
+                    }
+                }
+
                 ui.separator();
 
                 // Show interface and node ID info
-                if let Some(interface) = &self.selected_can_interface {
+                if let Some(path) = &self.imported_log {
+                    ui.label(format!("Log: {}", path.file_name().unwrap_or_default().to_string_lossy()));
+                } else if let Some(interface) = &self.selected_can_interface {
                     ui.label(format!("Interface: {}", interface));
                 }
                 if let Some(node_id) = self.selected_node_id {
                     ui.label(format!("Node ID: {}", node_id));
+
+                    ui.menu_button("NMT", |ui| {
+                        for command in [NmtCommand::Start, NmtCommand::EnterPreOperational, NmtCommand::ResetNode] {
+                            if ui.button(command.to_string()).clicked() {
+                                if let Some(tx) = &self.command_tx {
+                                    let _ = tx.send(Command::Nmt { node_id, command });
+                                }
+                                ui.close_menu();
+                            }
+                        }
+                        ui.separator();
+                        if ui.button("🖧 Control panel...").clicked() {
+                            self.nmt_panel.open = true;
+                            ui.close_menu();
+                        }
+                        if ui.button("🚀 Startup sequence...").clicked() {
+                            self.startup_panel.open = true;
+                            ui.close_menu();
+                        }
+                        if ui.button("🩺 Device check...").clicked() {
+                            self.device_check.open = true;
+                            ui.close_menu();
+                        }
+                        if ui.button("🏷 LSS commissioning...").clicked() {
+                            self.lss_panel.open = true;
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            let configured = self.config.heartbeat_consumer_time(node_id);
+                            let mut consumer_ms = configured.map_or(0, |time| time.as_millis() as u64);
+                            ui.label("Heartbeat consumer time:");
+                            let response = ui.add(egui::DragValue::new(&mut consumer_ms).range(0..=60_000).suffix(" ms"))
+                                .on_hover_text("Alarm when no heartbeat arrives for this long (0 = three heartbeat periods)");
+                            if response.changed() {
+                                let consumer_ms = (consumer_ms > 0).then_some(consumer_ms);
+                                self.config.set_heartbeat_consumer_time(node_id, consumer_ms);
+                                if let Some(tx) = &self.command_tx {
+                                    let consumer_time = consumer_ms.map(Duration::from_millis);
+                                    let _ = tx.send(Command::SetHeartbeatConsumerTime { node_id, consumer_time });
+                                }
+                            }
+                            if response.drag_stopped() || response.lost_focus() {
+                                if let Err(e) = self.config.save() {
+                                    eprintln!("Failed to save configuration: {}", e);
+                                }
+                            }
+                        });
+                        ui.separator();
+                        let mut config_changed = ui.checkbox(&mut self.config.reconfigure_on_boot, "Reconfigure after boot-up")
+                            .on_hover_text("Rediscover TPDOs and restart subscriptions when the node sends a boot-up")
+                            .changed();
+                        ui.add_enabled_ui(self.config.reconfigure_on_boot, |ui| {
+                            ui.horizontal(|ui| {
+                                let boot_dcf = self.config.boot_dcf_file(node_id);
+                                ui.label("Boot DCF:");
+                                ui.label(boot_dcf.as_ref()
+                                    .and_then(|path| path.file_name())
+                                    .map_or("none".to_string(), |name| name.to_string_lossy().to_string()));
+                                if ui.button("📂").on_hover_text("Write this DCF to the node after each boot-up").clicked() {
+                                    if let Some(path) = rfd::FileDialog::new().add_filter("CANopen DCF", &["dcf"]).pick_file() {
+                                        self.config.set_boot_dcf_file(node_id, Some(&path));
+                                        config_changed = true;
+                                    }
+                                }
+                                if boot_dcf.is_some() && ui.button("✖").on_hover_text("Don't write a DCF after boot-up").clicked() {
+                                    self.config.set_boot_dcf_file(node_id, None);
+                                    config_changed = true;
+                                }
+                            });
+                        });
+                        if config_changed {
+                            if let Err(e) = self.config.save() {
+                                eprintln!("Failed to save configuration: {}", e);
+                            }
+                        }
+                    });
+                }
+
+                self.draw_sync_menu(ui);
+        self.draw_gateway_menu(ui);
+
+                let eds_label = self.eds_file_path.as_ref()
+                    .and_then(|path| path.file_name())
+                    .map_or("No EDS".to_string(), |name| name.to_string_lossy().to_string());
+                ui.menu_button(format!("📄 {}", eds_label), |ui| {
+                    if ui.add_enabled(self.eds_file_path.is_some(), egui::Button::new("⟳ Reload"))
+                        .on_hover_text("Read the EDS file again after editing it")
+                        .clicked()
+                    {
+                        self.load_eds(self.eds_file_path.clone());
+                        ui.close_menu();
+                    }
+                    if ui.button("📂 Change...").clicked() {
+                        if let Some(path) = rfd::FileDialog::new().add_filter("CANopen EDS", &["eds"]).pick_file() {
+                            self.load_eds(Some(path));
+                        }
+                        ui.close_menu();
+                    }
+                });
+
+                if self.dropped_samples > 0 {
+                    ui.separator();
+                    ui.colored_label(Color32::from_rgb(255, 165, 0), format!("⚠ {} samples dropped", self.dropped_samples))
+                        .on_hover_text("Data arrived faster than it could be processed; the oldest samples were discarded");
                 }
 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -544,6 +1635,56 @@ impl MyApp {
                         self.show_about_dialog = true;
                     }
 
+                    if ui.button("🛠 SDO Console").clicked() {
+                        self.sdo_console.open = true;
+                    }
+
+                    if ui.button("🔎 Scan").on_hover_text("Find objects missing from the EDS").clicked() {
+                        self.object_scanner.open = true;
+                    }
+
+                    if ui.button("📤 RPDO").on_hover_text("Compose and transmit RPDOs").clicked() {
+                        self.rpdo_panel.open = true;
+                    }
+
+                    if ui.button("✉ Send frame").on_hover_text("Transmit an arbitrary CAN frame").clicked() {
+                        self.frame_sender.open = true;
+                    }
+
+                    if ui.button("🚦 Controller").on_hover_text("CAN controller state and error counters").clicked() {
+                        self.controller_panel.open = true;
+                    }
+
+                    if ui.button("🌐 Network").on_hover_text("All nodes seen on the bus").clicked() {
+                        self.network_overview.open = true;
+                    }
+
+                    if ui.button("📃 Trace").on_hover_text("Raw CAN frames as they arrive").clicked() {
+                        self.trace_panel.open = true;
+                    }
+
+                    let unacknowledged = self.emcy_monitor.unacknowledged();
+                    let emcy_button = if unacknowledged > 0 {
+                        egui::Button::new(egui::RichText::new(format!("🚨 EMCY ({})", unacknowledged)).color(Color32::from_rgb(255, 100, 100)))
+                    } else {
+                        egui::Button::new("🚨 EMCY")
+                    };
+                    if ui.add(emcy_button).on_hover_text("Emergency messages received from the bus").clicked() {
+                        self.emcy_monitor.open = true;
+                    }
+
+                    if ui.button("🕘 Timeline").on_hover_text("Alarms, emergencies, connection events and markers over time").clicked() {
+                        self.timeline.open = true;
+                    }
+
+                    if ui.button("📜 Macros").clicked() {
+                        self.macro_editor.open = true;
+                    }
+
+                    if ui.button("🔌 Change connection").clicked() {
+                        self.disconnect();
+                    }
+
                     ui.separator();
 
                     // Logging controls on the right side
@@ -561,10 +1702,17 @@ impl MyApp {
                         }
                     }
 
-                    if ui.checkbox(&mut self.config.enable_logging, "Enable Logging").changed() {
+                    if ui.button("📖 View log").on_hover_text("Browse the current or an earlier CSV / JSON Lines log").clicked() {
+                        match self.logger.log_file_path() {
+                            Some(log_path) if !self.log_viewer.open => self.log_viewer.show_file(log_path),
+                            _ => self.log_viewer.open = true,
+                        }
+                    }
+
+                    if ui.checkbox(&mut self.config.enable_logging, "Enable Logging").changed() {
                         if self.config.enable_logging {
                             if let Some(log_dir) = self.config.get_log_directory() {
-                                if let Err(e) = self.logger.enable(log_dir) {
+                                if let Err(e) = self.logger.enable(log_dir, self.config.log_format, self.config.log_raw_frames) {
                                     self.error_message = Some(format!("Failed to enable logging: {}", e));
                                     self.config.enable_logging = false;
                                 }
@@ -575,9 +1723,76 @@ impl MyApp {
                         // Save config when logging preference changes
                         let _ = self.config.save();
                     }
+
+                    let (format, raw_frames) = (self.config.log_format, self.config.log_raw_frames);
+                    egui::ComboBox::from_id_salt("log_format")
+                        .selected_text(format.label())
+                        .width(60.0)
+                        .show_ui(ui, |ui| {
+                            for option in LogFormat::ALL {
+                                ui.selectable_value(&mut self.config.log_format, option, option.label());
+                            }
+                        })
+                        .response
+                        .on_hover_text("CSV logs every event as text; MDF4 logs the channel samples for CANape, vSignalyzer and other analysis tools; JSON Lines logs every event as a JSON object for ELK, ClickHouse and the like");
+                    ui.checkbox(&mut self.config.log_raw_frames, "Raw frames (BLF)")
+                        .on_hover_text("Also record every CAN frame on the bus to a Vector BLF file");
+                    if ui.checkbox(&mut self.config.log_frame_events, "Frames in log")
+                        .on_hover_text("Also log every received and transmitted frame (COB-ID, DLC, data) as a RAW_FRAME event next to the decoded values")
+                        .changed()
+                    {
+                        self.logger.set_frame_events(self.config.log_frame_events);
+                        let _ = self.config.save();
+                    }
+                    if log_rotation::menu(ui, &mut self.config.log_rotation) {
+                        self.logger.set_rotation(self.config.log_rotation);
+                        let _ = self.config.save();
+                    }
+                    match black_box::menu(ui, &mut self.config.black_box, self.logger.black_box_status()) {
+                        Some(BlackBoxAction::Changed) => {
+                            self.logger.set_black_box(self.config.black_box, self.config.get_log_directory(), self.config.log_format);
+                            let _ = self.config.save();
+                        }
+                        Some(BlackBoxAction::Trigger) => {
+                            self.logger.trigger_black_box("Manual trigger");
+                            self.timeline.record(EventKind::Marker, "Black box triggered".to_string());
+                        }
+                        None => {}
+                    }
+                    if self.config.log_format != format || self.config.log_raw_frames != raw_frames {
+                        // Black-box dumps follow the log format
+                        self.logger.set_black_box(self.config.black_box, self.config.get_log_directory(), self.config.log_format);
+                        // New files with the chosen settings
+                        if self.logger.is_enabled() {
+                            if let Some(log_dir) = self.config.get_log_directory() {
+                                self.logger.disable();
+                                if let Err(e) = self.logger.enable(log_dir, self.config.log_format, self.config.log_raw_frames) {
+                                    self.error_message = Some(format!("Failed to enable logging: {}", e));
+                                    self.config.enable_logging = false;
+                                }
+                            }
+                        }
+                        let _ = self.config.save();
+                    }
                 });
             });
 
+            // Heartbeat alarms stay until acknowledged
+            if let Some(latest) = self.heartbeat_alarms.last().cloned() {
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let text = match self.heartbeat_alarms.len() {
+                        1 => format!("🔔 {}", latest),
+                        count => format!("🔔 {} (+{} more)", latest, count - 1),
+                    };
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), text)
+                        .on_hover_text(self.heartbeat_alarms.join("\n"));
+                    if ui.button("✔ Acknowledge").clicked() {
+                        self.heartbeat_alarms.clear();
+                    }
+                });
+            }
+
             // Error banner
             if let Some(error_msg) = self.error_message.clone() {
                 ui.separator();
@@ -590,6 +1805,11 @@ impl MyApp {
             }
         });
 
+        // Bus statistics strip along the bottom edge
+        egui::TopBottomPanel::bottom("bus_stats_panel").show_inside(ui, |ui| {
+            self.draw_bus_stats(ui);
+        });
+
         // Bottom panel for subscription management
         egui::TopBottomPanel::bottom("subscription_panel").show_inside(ui, |ui| {
             self.draw_subscription_management(ui);
@@ -600,27 +1820,406 @@ impl MyApp {
             self.draw_sdo_list(ui);
         });
 
-        // The central panel will contain the plots
+        // The central panel shows the plots or the watch table
         egui::CentralPanel::default().show_inside(ui, |ui| {
-            self.draw_plots(ui);
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.central_view, CentralView::Plots, "📈 Plots");
+                ui.selectable_value(&mut self.central_view, CentralView::Watch, "📋 Watch");
+            });
+            match self.central_view {
+                CentralView::Plots => self.draw_plots(ui),
+                CentralView::Watch => self.draw_watch(ui),
+            }
         });
 
         self.draw_subscription_modal(ui);
         self.draw_about_dialog(ui);
+        self.sdo_console.show(ui.ctx(), self.command_tx.as_ref());
+
+        let node_id = self.config.node_id;
+        match self.parameter_backup.show(ui.ctx(), self.connection_status && self.snapshot_progress.is_none(), node_id) {
+            Some(BackupAction::Backup) | Some(BackupAction::Clone) => {
+                if let Some(tx) = &self.command_tx {
+                    let _ = tx.send(Command::TakeSnapshot { writable_only: true });
+                    self.snapshot_progress = Some((0, 0));
+                }
+            }
+            Some(BackupAction::Restore(entries)) => {
+                if let Some(tx) = &self.command_tx {
+                    let _ = tx.send(Command::RestoreParameters { node_id, entries });
+                }
+            }
+            Some(BackupAction::WriteSignature { address, data }) => {
+                if let Some(tx) = &self.command_tx {
+                    let _ = tx.send(Command::WriteSdo { address, data });
+                }
+            }
+            None => {}
+        }
+
+        match self.macro_editor.show(ui.ctx(), self.connection_status, &mut self.config.macros) {
+            Some(MacroAction::Run(ops)) => {
+                if let Some(tx) = &self.command_tx {
+                    let _ = tx.send(Command::RunMacro(ops));
+                }
+            }
+            Some(MacroAction::Stop) => {
+                if let Some(tx) = &self.command_tx {
+                    let _ = tx.send(Command::StopMacro);
+                }
+            }
+            Some(MacroAction::Save) => {
+                if let Err(e) = self.config.save() {
+                    self.error_message = Some(format!("Failed to save macros: {}", e));
+                }
+            }
+            None => {}
+        }
+
+        match self.stimulus.show(ui.ctx(), self.connection_status) {
+            Some(StimulusAction::Start(config)) => {
+                if let Some(tx) = &self.command_tx {
+                    let _ = tx.send(Command::StartStimulus(config));
+                }
+            }
+            Some(StimulusAction::Stop) => {
+                if let Some(tx) = &self.command_tx {
+                    let _ = tx.send(Command::StopStimulus);
+                }
+            }
+            None => {}
+        }
+
+        self.emcy_monitor.show(ui.ctx());
+        self.log_viewer.show(ui.ctx(), self.logger.log_file_path(), self.config.get_log_directory());
+        if let Some(marker) = self.timeline.show(ui.ctx(), self.session_start, self.config.plot_wall_clock) {
+            self.logger.log(LogEvent::Marker(marker));
+        }
+
+        // Frames are only forwarded while the trace window is open or they are recorded
+        self.trace_panel.show(ui.ctx(), self.connection_status);
+        let trace_wanted = (self.trace_panel.open || self.logger.records_frames()) && self.connection_status;
+        if trace_wanted != self.trace_running {
+            if let Some(tx) = &self.command_tx {
+                let _ = tx.send(if trace_wanted { Command::StartTrace } else { Command::StopTrace });
+                self.trace_running = trace_wanted;
+            }
+        }
+
+        let interface = self.selected_can_interface.clone().unwrap_or_default();
+        let restart = self.controller_panel.show(ui.ctx(), self.connection_status, &interface);
+        if let Some(tx) = &self.command_tx {
+            if restart {
+                let _ = tx.send(Command::RestartController);
+            }
+            if self.connection_status && self.controller_panel.poll_due() {
+                let _ = tx.send(Command::QueryControllerState);
+            }
+        }
+
+        let subscription_count = self.subscriptions.len() + self.active_tpdos.len();
+        let nodes = network_overview::summaries(self.config.node_id, subscription_count, &self.nmt_states, &self.emcy_monitor);
+        if let Some(OverviewAction::SwitchTo(node_id)) = self.network_overview.show(ui.ctx(), &nodes) {
+            self.switch_node(node_id);
+            return;
+        }
+
+        if let Some((node_id, command)) = self.nmt_panel.show(ui.ctx(), self.connection_status, self.selected_node_id) {
+            if let Some(tx) = &self.command_tx {
+                let _ = tx.send(Command::Nmt { node_id, command });
+            }
+        }
+
+        // LSS needs the bus only; the node the viewer monitors may not answer while unconfigured
+        let bus_available = self.command_tx.is_some() && !self.interface_lost;
+        if let Some(command) = self.lss_panel.show(ui.ctx(), bus_available, self.eds_file_path.as_deref()) {
+            if let Some(tx) = &self.command_tx {
+                let _ = tx.send(Command::Lss(command));
+            }
+        }
+
+        let node_id = self.config.node_id;
+        let mut identity = self.config.expected_identity(node_id);
+        let dcf_path = self.config.boot_dcf_file(node_id);
+        let startup_action = self.startup_panel.show(
+            ui.ctx(),
+            self.connection_status,
+            &mut self.config.manager_mode,
+            &mut identity,
+            self.eds_file_path.as_deref(),
+            dcf_path.as_deref(),
+        );
+        if identity != self.config.expected_identity(node_id) {
+            self.config.set_expected_identity(node_id, identity);
+        }
+        match startup_action {
+            Some(StartupAction::Run) => {
+                self.run_startup();
+            }
+            Some(StartupAction::Save) => {
+                if let Err(e) = self.config.save() {
+                    eprintln!("Failed to save configuration: {}", e);
+                }
+            }
+            None => {}
+        }
+
+        if self.device_check.show(ui.ctx(), self.connection_status, node_id) {
+            if let Some(tx) = &self.command_tx {
+                let plan = DeviceCheckPlan::new(self.eds_file_path.as_deref(), self.object_dictionary.as_ref());
+                let _ = tx.send(Command::RunDeviceCheck(plan));
+                self.device_check.start();
+            }
+        }
+
+        let report_plots: Vec<String> = if self.report_panel.open {
+            self.report_plots().into_iter().map(|(title, _, _)| title).collect()
+        } else {
+            Vec::new()
+        };
+        if self.report_panel.show(ui.ctx(), &report_plots) {
+            self.generate_report();
+        }
+
+        let channel_names = if self.derived_panel.open { self.recorded_channel_names() } else { Vec::new() };
+        match self.derived_panel.show(ui.ctx(), &self.config.derived_channels, &channel_names) {
+            Some(DerivedAction::Save { previous, channel }) => self.save_derived_channel(previous, channel),
+            Some(DerivedAction::Remove(name)) => self.remove_derived_channel(&name),
+            Some(DerivedAction::Plot(name)) => self.show_on_dashboard(&PlotSeries::Derived(name)),
+            None => {}
+        }
+
+        for action in self.rpdo_panel.show(ui.ctx(), self.connection_status) {
+            let command = match action {
+                RpdoAction::Discover => Command::DiscoverRpdos,
+                RpdoAction::Send { rpdo_number, cob_id, data } => Command::SendRpdo { rpdo_number, cob_id, data },
+                RpdoAction::Start { rpdo_number, cob_id, data, interval_ms } => Command::StartRpdo { rpdo_number, cob_id, data, interval_ms },
+                RpdoAction::Stop(rpdo_number) => Command::StopRpdo(rpdo_number),
+            };
+            if let Some(tx) = &self.command_tx {
+                let _ = tx.send(command);
+            }
+        }
+
+        if let Some(action) = self.frame_sender.show(ui.ctx(), self.connection_status) {
+            let command = match action {
+                FrameSenderAction::Send(frame) => Command::SendFrame(frame),
+                FrameSenderAction::Start { frame, interval_ms } => Command::StartFrame { frame, interval_ms },
+                FrameSenderAction::Stop => Command::StopFrame,
+            };
+            if let Some(tx) = &self.command_tx {
+                let _ = tx.send(command);
+            }
+        }
+
+        match self.object_scanner.show(ui.ctx(), self.connection_status, self.object_dictionary.as_ref()) {
+            Some(ScanAction::Start(range)) => {
+                if let Some(tx) = &self.command_tx {
+                    let _ = tx.send(Command::ScanObjects(range));
+                }
+            }
+            Some(ScanAction::Cancel) => {
+                if let Some(tx) = &self.command_tx {
+                    let _ = tx.send(Command::CancelScan);
+                }
+            }
+            None => {}
+        }
+
+        let live_available = self.connection_status && self.snapshot_progress.is_none();
+        if self.snapshot_compare.show(ui.ctx(), live_available) {
+            if let Some(tx) = &self.command_tx {
+                let _ = tx.send(Command::TakeSnapshot { writable_only: false });
+                self.snapshot_progress = Some((0, 0));
+            }
+        }
+        if self.default_report.show(ui.ctx(), live_available, self.config.node_id) {
+            if let Some(tx) = &self.command_tx {
+                let _ = tx.send(Command::TakeSnapshot { writable_only: false });
+                self.snapshot_progress = Some((0, 0));
+            }
+        }
+    }
+
+    fn draw_watch(&mut self, ui: &mut egui::Ui) {
+        let now = Local::now();
+        let age = |timestamp: Option<DateTime<Local>>| timestamp.map(|at| (now - at).num_milliseconds() as f64 / 1000.0);
+        let mut rows = Vec::new();
+
+        for (address, subscription) in &self.subscriptions {
+            let name = self.object_dictionary.as_ref()
+                .and_then(|dict| dict.get(&address.index))
+                .and_then(|obj| obj.sub_objects.get(&address.sub_index))
+                .map_or_else(|| format!("0x{:04X}:{:02X}", address.index, address.sub_index), |sub_obj| sub_obj.name.clone());
+            let scaling = effective_scaling(&self.config, self.object_dictionary.as_ref(), address);
+            let radix = self.value_radix.get(address).copied().unwrap_or_default();
+            rows.push(WatchRow {
+                name,
+                address: format!("{:04X}:{:02X}", address.index, address.sub_index),
+                series: PlotSeries::Sdo(address.clone()),
+                value: subscription.last_value.as_ref()
+                    .map(|value| watch_value(scaling.as_ref(), &self.value_descriptions, radix, address, value, &subscription.data_type)),
+                unit: scaling.map(|scaling| scaling.unit).unwrap_or_default(),
+                age: age(subscription.last_timestamp),
+                rate: subscription.rate.rate(now),
+            });
+        }
+
+        for (field_id, subscription) in &self.tpdo_field_subscriptions {
+            let mapped_object = self.discovered_tpdos.iter()
+                .find(|config| config.tpdo_number == field_id.tpdo_number)
+                .and_then(|config| config.mapped_objects.iter().find(|obj| obj.name == field_id.field_name));
+            let value = match (&subscription.last_value, mapped_object) {
+                (Some(value), Some(obj)) => {
+                    let address = SdoAddress { index: obj.index, sub_index: obj.sub_index };
+                    let scaling = effective_scaling(&self.config, self.object_dictionary.as_ref(), &address);
+                    let radix = self.value_radix.get(&address).copied().unwrap_or_default();
+                    let unit = scaling.as_ref().map(|scaling| scaling.unit.clone()).unwrap_or_default();
+                    Some((watch_value(scaling.as_ref(), &self.value_descriptions, radix, &address, value, &obj.data_type), unit))
+                }
+                (Some(value), None) => Some((value.clone(), String::new())),
+                (None, _) => None,
+            };
+            let (value, unit) = value.unzip();
+            rows.push(WatchRow {
+                name: field_id.field_name.clone(),
+                address: format!("TPDO{}", field_id.tpdo_number),
+                series: PlotSeries::Tpdo(field_id.clone()),
+                value,
+                unit: unit.unwrap_or_default(),
+                age: age(subscription.last_timestamp),
+                rate: subscription.rate.rate(now),
+            });
+        }
+
+        for (name, channel) in &self.derived_channels {
+            rows.push(WatchRow {
+                name: name.clone(),
+                address: "Derived".to_string(),
+                series: PlotSeries::Derived(name.clone()),
+                value: channel.last_value.map(y_axis::format_value),
+                unit: channel.definition.unit.clone(),
+                age: age(channel.last_timestamp),
+                rate: channel.rate.rate(now),
+            });
+        }
+
+        self.watch_table.show(ui, rows);
+    }
+
+    fn draw_bus_stats(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let Some(stats) = &self.bus_stats else {
+                ui.label("Bus statistics: waiting for data...");
+                return;
+            };
+
+            ui.label(format!("{:.0} frames/s", stats.frames_per_sec));
+            ui.separator();
+            ui.label(format!("{:.0} bytes/s", stats.bytes_per_sec));
+            ui.separator();
+
+            let load_hover = if stats.whole_bus {
+                "Estimated from the interface's frame counters (bit stuffing not included)"
+            } else {
+                "Estimated from the frames the viewer receives and sends; frames removed by the receive filter are not counted"
+            };
+            let threshold = self.config.bus_load_warning_percent;
+            let load_response = match stats.bus_load_percent {
+                Some(load) => {
+                    let (color, text) = if load >= threshold {
+                        (Color32::from_rgb(255, 165, 0), format!("⚠ Bus load {:.1}%", load))
+                    } else {
+                        (Color32::from_rgb(0, 160, 0), format!("Bus load {:.1}%", load))
+                    };
+                    ui.add(egui::ProgressBar::new((load / 100.0) as f32).desired_width(140.0).fill(color).text(text))
+                }
+                None => ui.label("Bus load: n/a"),
+            };
+            let mut threshold_changed = false;
+            load_response
+                .on_hover_text(format!("{}\nWarning above {:.0}% (right-click to change)", load_hover, threshold))
+                .context_menu(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Warn above:");
+                        threshold_changed = ui.add(
+                            egui::DragValue::new(&mut self.config.bus_load_warning_percent).range(1.0..=100.0).suffix(" %")
+                        ).changed();
+                    });
+                });
+            if threshold_changed {
+                if let Err(e) = self.config.save() {
+                    eprintln!("Failed to save config: {}", e);
+                }
+            }
+            ui.separator();
+
+            let errors = stats.error_frames + stats.rx_errors + stats.tx_errors;
+            let error_text = format!("Errors: {}", errors);
+            let error_label = if errors > 0 {
+                ui.colored_label(Color32::from_rgb(255, 165, 0), error_text)
+            } else {
+                ui.label(error_text)
+            };
+            error_label.on_hover_text(format!(
+                "Error frames: {}\nRead errors: {}\nTransmit errors: {}",
+                stats.error_frames, stats.rx_errors, stats.tx_errors
+            ));
+            ui.separator();
+
+            ui.label(format!("{} COB-IDs", stats.cob_id_counts.len())).on_hover_ui(|ui| {
+                egui::Grid::new("cob_id_counts").striped(true).show(ui, |ui| {
+                    for (id, count) in &stats.cob_id_counts {
+                        if id & libc::CAN_EFF_FLAG != 0 {
+                            ui.monospace(format!("0x{:08X}", id & libc::CAN_EFF_MASK));
+                        } else {
+                            ui.monospace(format!("0x{:03X}", id));
+                        }
+                        ui.label(count.to_string());
+                        ui.end_row();
+                    }
+                });
+            });
+
+            // SDO scheduling of the monitored node
+            let node_metrics = self.comm_metrics.iter()
+                .find(|metrics| Some(metrics.node_id) == self.selected_node_id);
+            if let Some(metrics) = node_metrics {
+                ui.separator();
+                let round_trip = match (metrics.average_round_trip, metrics.max_round_trip) {
+                    (Some(average), Some(max)) => format!(
+                        "{:.1} ms avg, {:.1} ms max",
+                        average.as_secs_f64() * 1000.0,
+                        max.as_secs_f64() * 1000.0
+                    ),
+                    _ => "n/a".to_string(),
+                };
+                ui.label(format!(
+                    "SDO: {} queued | {:.0} responses/s | RTT {} | {:.0}% timeouts",
+                    metrics.queue_length,
+                    metrics.responses_per_sec,
+                    round_trip,
+                    metrics.timeout_rate * 100.0
+                )).on_hover_text(
+                    "SDO requests are sent one at a time per node, so polling faster than \
+                     responses/s allows only grows the queue"
+                );
+            }
+        });
     }
 
     fn draw_sdo_list(&mut self, ui: &mut egui::Ui) {
         // Tabs at the top
         ui.horizontal(|ui| {
-            ui.selectable_value(&mut self.sidebar_tab, SidebarTab::SDO, "SDO");
-            ui.selectable_value(&mut self.sidebar_tab, SidebarTab::TPDO, "TPDO");
+            ui.selectable_value(&mut self.sidebar_tab, SidebarTab::Sdo, "SDO");
+            ui.selectable_value(&mut self.sidebar_tab, SidebarTab::Tpdo, "TPDO");
         });
         ui.separator();
 
         // Render content based on selected tab
         match self.sidebar_tab {
-            SidebarTab::SDO => self.draw_sdo_tab_content(ui),
-            SidebarTab::TPDO => self.draw_tpdo_tab_content(ui),
+            SidebarTab::Sdo => self.draw_sdo_tab_content(ui),
+            SidebarTab::Tpdo => self.draw_tpdo_tab_content(ui),
         }
     }
 
@@ -628,40 +2227,196 @@ impl MyApp {
         // Search box
         ui.horizontal(|ui| {
             ui.label("Search:");
-            ui.text_edit_singleline(&mut self.sdo_search_query);
+            ui.text_edit_singleline(&mut self.sdo_search_query)
+                .on_hover_text("Text, /pattern/, index range 0x2000-0x20FF, access:rw, type:uint16");
+        });
+        let query = match SdoQuery::parse(&self.sdo_search_query) {
+            Ok(query) => query,
+            Err(err) => {
+                ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+                SdoQuery::default()
+            }
+        };
+
+        // Snapshot of all readable objects
+        ui.horizontal(|ui| {
+            if let Some((done, total)) = self.snapshot_progress {
+                let fraction = if total > 0 { done as f32 / total as f32 } else { 0.0 };
+                ui.add(egui::ProgressBar::new(fraction)
+                    .text(format!("Snapshot {}/{}", done, total))
+                    .desired_width(150.0));
+                if ui.button("Cancel").clicked() {
+                    if let Some(tx) = &self.command_tx {
+                        let _ = tx.send(Command::CancelSnapshot);
+                    }
+                    self.snapshot_progress = None;
+                    self.snapshot_compare.cancel_live();
+                    self.default_report.cancel_live();
+                    self.dcf_export_pending = false;
+                    self.parameter_backup.cancel_backup();
+                }
+            } else {
+                let enabled = self.connection_status && self.object_dictionary.as_ref().is_some_and(|dict| !dict.is_empty());
+                if ui.add_enabled(enabled, egui::Button::new("📷 Snapshot device"))
+                    .on_hover_text("Read every object in the EDS and save the values to a file")
+                    .clicked()
+                {
+                    if let Some(tx) = &self.command_tx {
+                        let _ = tx.send(Command::TakeSnapshot { writable_only: false });
+                        self.snapshot_progress = Some((0, 0));
+                    }
+                }
+            }
+
+            if ui.button("🔍 Compare").on_hover_text("Show the differences between two snapshots").clicked() {
+                self.snapshot_compare.open = true;
+            }
+            if ui.button("🏭 Defaults").on_hover_text("List the objects that differ from the EDS default values").clicked() {
+                self.default_report.open = true;
+            }
+            let dcf_enabled = self.connection_status && self.snapshot_progress.is_none() && self.eds_file_path.is_some();
+            if ui.add_enabled(dcf_enabled, egui::Button::new("📝 Export DCF"))
+                .on_hover_text("Read the node and save its EDS with the actual values as a DCF file")
+                .clicked()
+            {
+                if let Some(tx) = &self.command_tx {
+                    let _ = tx.send(Command::TakeSnapshot { writable_only: false });
+                    self.snapshot_progress = Some((0, 0));
+                    self.dcf_export_pending = true;
+                }
+            }
+            if ui.button("💾 Backup").on_hover_text("Back up and restore the writable parameters").clicked() {
+                self.parameter_backup.open = true;
+            }
         });
         ui.separator();
 
+        let favorites_key = favorites_key(self.eds_file_path.as_ref(), self.config.node_id);
+        let favorites = self.config.favorites(&favorites_key);
+        let mut open_modal: Option<(SdoAddress, SdoSubObject)> = None;
+        let mut toggle_favorite: Option<SdoAddress> = None;
+        let mut bar_chart_for: Option<u16> = None;
+
         egui::ScrollArea::vertical().show(ui, |ui| {
             if let Some(object_dictionary) = &self.object_dictionary {
-                let query = self.sdo_search_query.to_lowercase();
+                // Star, button and latest value of one sub-object; returns (opened, star clicked)
+                let mut sdo_row = |ui: &mut egui::Ui, address: &SdoAddress, label: String| ui.horizontal(|ui| {
+                    let starred = favorites.contains(address);
+                    let star = ui.small_button(if starred { "★" } else { "☆" })
+                        .on_hover_text(if starred { "Remove from favorites" } else { "Add to favorites" })
+                        .clicked();
+                    // Dragging the object onto the plots subscribes and places it
+                    let response = ui.add(egui::Button::new(label).sense(egui::Sense::click_and_drag()));
+                    response.dnd_set_drag_payload(PlotSeries::Sdo(address.clone()));
+                    let clicked = response.clicked();
+
+                    // Latest value of subscribed objects
+                    if let Some(subscription) = self.subscriptions.get(address) {
+                        if let Some(value) = &subscription.last_value {
+                            let scaling = effective_scaling(&self.config, Some(object_dictionary), address);
+                            let radix = self.value_radix.entry(address.clone()).or_default();
+                            ui.label(display_value(scaling.as_ref(), &self.value_descriptions, *radix, address, value, &subscription.data_type));
+                            radix_toggle(ui, radix, &subscription.data_type);
+                        }
+                    }
+                    (clicked, star)
+                }).inner;
+
+                let favorite_objects: Vec<(SdoAddress, &SdoSubObject)> = favorites.iter()
+                    .filter_map(|address| Some((address.clone(), object_dictionary.get(&address.index)?.sub_objects.get(&address.sub_index)?)))
+                    .collect();
+                if !favorite_objects.is_empty() {
+                    ui.strong("★ Favorites");
+                    for (address, sub_object) in favorite_objects {
+                        let label = format!("{:#06X} sub {}: {}", address.index, address.sub_index, &sub_object.name);
+                        let (clicked, star) = sdo_row(ui, &address, label);
+                        if clicked {
+                            open_modal = Some((address.clone(), sub_object.clone()));
+                        }
+                        if star {
+                            toggle_favorite = Some(address);
+                        }
+                    }
+                    ui.separator();
+                }
+
                 for (index, sdo_object) in object_dictionary {
-                    let object_name_matches = sdo_object.name.to_lowercase().contains(&query);
-                    let index_matches = format!("{:#06X}", index).to_lowercase().contains(&query);
                     let any_sub_object_matches = sdo_object.sub_objects.values()
-                        .any(|sub| sub.name.to_lowercase().contains(&query));
+                        .any(|sub| query.matches(*index, sdo_object, sub));
 
-                    if query.is_empty() || object_name_matches || index_matches || any_sub_object_matches {
-                        ui.collapsing(format!("{:#06X}: {}", index, &sdo_object.name), |ui| {
+                    if query.is_empty() || any_sub_object_matches {
+                        let collapsing = ui.collapsing(format!("{:#06X}: {}", index, &sdo_object.name), |ui| {
                             for (sub_index, sub_object) in &sdo_object.sub_objects {
+                                if !query.matches(*index, sdo_object, sub_object) {
+                                    continue;
+                                }
                                 let address = SdoAddress { index: *index, sub_index: *sub_index };
-                                let button_text = format!("Sub {}: {}", sub_index, &sub_object.name);
-                                if ui.button(button_text).clicked() {
-                                    self.modal_open_for = Some(address.clone());
-                                    if let Some(sub) = self.subscriptions.get(&address) {
-                                        self.modal_interval_str = sub.interval_ms.to_string();
-                                    } else {
-                                        self.modal_interval_str = "100".to_string();
-                                    }
+                                let (clicked, star) = sdo_row(ui, &address, format!("Sub {}: {}", sub_index, &sub_object.name));
+                                if clicked {
+                                    open_modal = Some((address.clone(), sub_object.clone()));
+                                }
+                                if star {
+                                    toggle_favorite = Some(address);
                                 }
                             }
                         });
+                        if sdo_object.sub_objects.len() > 2 {
+                            collapsing.header_response.context_menu(|ui| {
+                                if ui.add_enabled(self.connection_status, egui::Button::new("📊 Bar chart of the sub-indices")).clicked() {
+                                    bar_chart_for = Some(*index);
+                                    ui.close_menu();
+                                }
+                            });
+                        }
+                    }
+                }
+            } else if let Some((done, total)) = self.object_walk_progress {
+                ui.label("No EDS file: reading the object list from the device...");
+                let fraction = if total > 0 { done as f32 / total as f32 } else { 0.0 };
+                ui.add(egui::ProgressBar::new(fraction).text(format!("{}/{} indices", done, total)));
+                if ui.button("⏹ Cancel").on_hover_text("Stop reading; load an EDS file instead").clicked() {
+                    if let Some(tx) = &self.command_tx {
+                        let _ = tx.send(Command::CancelObjectWalk);
                     }
                 }
             } else {
                 ui.label("Fetching SDO list...");
             }
         });
+
+        if let Some((address, sub_object)) = open_modal {
+            self.open_sdo_modal(address, &sub_object);
+        }
+        if let Some(index) = bar_chart_for {
+            self.add_bar_chart(index);
+        }
+        if let Some(address) = toggle_favorite {
+            self.config.toggle_favorite(&favorites_key, &address);
+            if let Err(e) = self.config.save() {
+                eprintln!("Failed to save favorites: {}", e);
+            }
+        }
+    }
+
+    /// Open the subscription / write dialog of an object
+    fn open_sdo_modal(&mut self, address: SdoAddress, sub_object: &SdoSubObject) {
+        if let Some(sub) = self.subscriptions.get(&address) {
+            self.modal_interval_str = sub.interval_ms.to_string();
+        } else {
+            self.modal_interval_str = "100".to_string();
+        }
+        let node_timeout = self.config.node_sdo_timeout(self.config.node_id)
+            .unwrap_or_else(|| self.config.sdo_timeout());
+        self.modal_timeout_str = node_timeout.as_millis().to_string();
+        self.modal_scaling = effective_scaling(&self.config, self.object_dictionary.as_ref(), &address)
+            .unwrap_or_default();
+        self.modal_write_value = (sub_object.access_type == "rw").then(|| {
+            let data_type = SdoDataType::from_eds_type(&sub_object.data_type)
+                .unwrap_or(SdoDataType::Real32);
+            WriteValue::for_eds_type(&sub_object.data_type, &data_type)
+        });
+        self.modal_write_status = None;
+        self.modal_open_for = Some(address);
     }
 
     fn draw_tpdo_tab_content(&mut self, ui: &mut egui::Ui) {
@@ -669,9 +2424,29 @@ impl MyApp {
         ui.horizontal(|ui| {
             ui.label("Search:");
             ui.text_edit_singleline(&mut self.tpdo_search_query);
+
+            let rediscover_enabled = self.connection_status && !self.discovered_tpdos.is_empty();
+            if ui.add_enabled(rediscover_enabled, egui::Button::new("🔄 Rediscover"))
+                .on_hover_text("Read the PDO configuration from the device again")
+                .clicked()
+            {
+                if let Some(tx) = &self.command_tx {
+                    let _ = tx.send(Command::RediscoverTpdos);
+                    self.discovered_tpdos.clear();
+                }
+            }
+            if self.tpdo_layout_cached {
+                ui.weak("(cached layout)");
+            }
         });
         ui.separator();
 
+        // Field whose decoding type was changed: (object, new type or None for automatic)
+        let mut type_change: Option<(SdoAddress, Option<SdoDataType>)> = None;
+        let mut reset_timing: Option<u8> = None;
+        // Stale timeout edited by the user: (TPDO, timeout or None for automatic, save now)
+        let mut timeout_change: Option<(u8, Option<u64>, bool)> = None;
+
         // Scrollable list of TPDOs
         egui::ScrollArea::vertical().show(ui, |ui| {
             if !self.connection_status {
@@ -701,38 +2476,113 @@ impl MyApp {
                                     .rev()
                                     .find(|tpdo| tpdo.tpdo_number == tpdo_num)
                                 {
-                                    ui.label(format!("Last update: {}", latest_tpdo.timestamp.format("%H:%M:%S%.3f")));
+                                    let source = match latest_tpdo.timestamp_source {
+                                        TimestampSource::Hardware => "hardware",
+                                        TimestampSource::Kernel => "kernel",
+                                        TimestampSource::Local => "local",
+                                        TimestampSource::Recorded => "recorded",
+                                    };
+                                    ui.label(format!("Last update: {} ({} time)", latest_tpdo.timestamp.format("%H:%M:%S%.3f"), source));
+                                    if let Some(timing) = self.tpdo_timing.get(&tpdo_num) {
+                                        let missed_color = if timing.missed() > 0 { Color32::from_rgb(255, 165, 0) } else { ui.visuals().text_color() };
+                                        ui.horizontal(|ui| {
+                                            ui.colored_label(missed_color, format!("Timing: {}", timing.summary()));
+                                            if ui.small_button("⟲").on_hover_text("Reset timing statistics").clicked() {
+                                                reset_timing = Some(tpdo_num);
+                                            }
+                                        });
+                                    }
+                                    if let Some((silent, _)) = self.tpdo_stale(tpdo_num) {
+                                        ui.colored_label(
+                                            Color32::from_rgb(255, 165, 0),
+                                            format!("⚠ Stale: no frame for {:.1} s, the values below are the last received", silent.as_secs_f64()),
+                                        );
+                                    }
                                 } else {
                                     ui.label("Status: Active (waiting for data...)");
+                                    if let Some(NodeNmtState::Active(state @ (NmtState::PreOperational | NmtState::Stopped))) = self.nmt_states.get(&self.config.node_id) {
+                                        ui.colored_label(Color32::from_rgb(255, 165, 0), format!("⚠ The node is {}; it sends PDOs only when Operational", state));
+                                    }
                                 }
                             } else {
                                 ui.label("Status: Stopped");
                             }
 
+                            ui.horizontal(|ui| {
+                                let user_timeout = self.config.tpdo_timeout(self.config.node_id, tpdo_num);
+                                let mut timeout_ms = user_timeout.map_or(0, |timeout| timeout.as_millis() as u64);
+                                ui.label("Stale after:");
+                                let response = ui.add(egui::DragValue::new(&mut timeout_ms).range(0..=600_000).suffix(" ms"))
+                                    .on_hover_text("0 = automatic: 3 event timer cycles, or 3 measured periods");
+                                if user_timeout.is_none() {
+                                    match self.tpdo_stale_timeout(tpdo_num) {
+                                        Some(timeout) => ui.weak(format!("(auto, {} ms)", timeout.as_millis())),
+                                        None => ui.weak("(auto, once the period is known)"),
+                                    };
+                                }
+                                if response.changed() {
+                                    timeout_change = Some((tpdo_num, (timeout_ms > 0).then_some(timeout_ms), false));
+                                }
+                                if response.drag_stopped() || response.lost_focus() {
+                                    timeout_change = Some((tpdo_num, (timeout_ms > 0).then_some(timeout_ms), true));
+                                }
+                            });
+
                             ui.separator();
 
                             // Show mapped objects and their current values
                             ui.label(format!("Mapped objects ({}):", config.mapped_objects.len()));
                             ui.add_space(5.0);
+                            pdo_layout::draw_mapping(ui, &config.mapped_objects);
+                            ui.add_space(5.0);
 
-                            // Get the latest TPDO data for this TPDO if active
+                            // Get the latest TPDO data for this TPDO if active, else the last requested one
                             let latest_values = if is_active {
                                 self.tpdo_data.iter()
                                     .rev()
                                     .find(|tpdo| tpdo.tpdo_number == tpdo_num)
                                     .map(|tpdo| &tpdo.values)
                             } else {
-                                None
+                                match self.tpdo_requests.get(&tpdo_num) {
+                                    Some(Some(Ok(response))) => Some(&response.values),
+                                    _ => None,
+                                }
                             };
 
                             for obj in &config.mapped_objects {
                                 ui.horizontal(|ui| {
-                                    ui.label(format!("  • {}:", obj.name));
+                                    ui.add(egui::Label::new(format!("  • {}:", obj.name)).sense(egui::Sense::drag()))
+                                        .on_hover_text("Drag onto the plots")
+                                        .dnd_set_drag_payload(PlotSeries::Tpdo(TpdoFieldId { tpdo_number: tpdo_num, field_name: obj.name.clone() }));
+
+                                    let address = SdoAddress { index: obj.index, sub_index: obj.sub_index };
+                                    let overridden = self.config.tpdo_data_types.contains_key(&address.eds_key());
+                                    let selected = format!("{:?}{}", obj.data_type, if overridden { " *" } else { "" });
+                                    egui::ComboBox::from_id_salt(("tpdo_field_type", tpdo_num, obj.index, obj.sub_index))
+                                        .selected_text(selected)
+                                        .width(80.0)
+                                        .show_ui(ui, |ui| {
+                                            if ui.selectable_label(!overridden, "Auto (EDS)").clicked() && overridden {
+                                                type_change = Some((address.clone(), None));
+                                            }
+                                            for data_type in communication::decodable_types(obj.bit_length) {
+                                                let checked = overridden && obj.data_type == *data_type;
+                                                if ui.selectable_label(checked, format!("{:?}", data_type)).clicked() && !checked {
+                                                    type_change = Some((address.clone(), Some(data_type.clone())));
+                                                }
+                                            }
+                                        })
+                                        .response
+                                        .on_hover_text("Decode this field as another type (* = set by you)");
 
                                     // Show current value if available
                                     if let Some(values) = latest_values {
                                         if let Some((_, value)) = values.iter().find(|(name, _)| name == &obj.name) {
-                                            ui.label(value);
+                                            let address = SdoAddress { index: obj.index, sub_index: obj.sub_index };
+                                            let scaling = effective_scaling(&self.config, self.object_dictionary.as_ref(), &address);
+                                            let radix = self.value_radix.entry(address.clone()).or_default();
+                                            ui.label(display_value(scaling.as_ref(), &self.value_descriptions, *radix, &address, value, &obj.data_type));
+                                            radix_toggle(ui, radix, &obj.data_type);
                                         } else {
                                             ui.label("—");
                                         }
@@ -758,7 +2608,39 @@ impl MyApp {
                                 } else {
                                     ui.label("(Use Active Subscriptions panel below to stop)");
                                 }
+
+                                if config.rtr_allowed {
+                                    let pending = matches!(self.tpdo_requests.get(&tpdo_num), Some(None));
+                                    if ui.add_enabled(!pending, egui::Button::new("📡 Request now"))
+                                        .on_hover_text("Send a remote request (RTR) and show the values the node answers with")
+                                        .clicked()
+                                    {
+                                        if let Some(tx) = &self.command_tx {
+                                            let _ = tx.send(Command::RequestTpdo(config.clone()));
+                                            self.tpdo_requests.insert(tpdo_num, None);
+                                        }
+                                    }
+                                }
                             });
+
+                            if config.rtr_only() && !is_active {
+                                ui.weak("This TPDO is only sent on a remote request.");
+                            }
+                            match self.tpdo_requests.get(&tpdo_num) {
+                                Some(None) => {
+                                    ui.horizontal(|ui| {
+                                        ui.spinner();
+                                        ui.label("Waiting for response...");
+                                    });
+                                }
+                                Some(Some(Ok(response))) => {
+                                    ui.label(format!("Requested at {}", response.timestamp.format("%H:%M:%S%.3f")));
+                                }
+                                Some(Some(Err(err))) => {
+                                    ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ Request failed: {}", err));
+                                }
+                                None => {}
+                            }
                         });
                     }
                 }
@@ -769,196 +2651,1451 @@ impl MyApp {
                 }
             }
         });
+
+        if let Some(tpdo_num) = reset_timing {
+            self.tpdo_timing.remove(&tpdo_num);
+        }
+        if let Some((tpdo_num, timeout_ms, save)) = timeout_change {
+            self.config.set_tpdo_timeout(self.config.node_id, tpdo_num, timeout_ms);
+            if save {
+                if let Err(e) = self.config.save() {
+                    self.error_message = Some(format!("Failed to save TPDO timeout: {}", e));
+                }
+            }
+        }
+        if let Some((address, data_type)) = type_change {
+            self.set_tpdo_data_type(&address, data_type);
+        }
     }
 
-    fn draw_plots(&mut self, ui: &mut egui::Ui) {
-        ui.heading("Plots");
+    /// Time without frames after which a TPDO counts as stale
+    fn tpdo_stale_timeout(&self, tpdo_num: u8) -> Option<Duration> {
+        let event_timer_ms = self.discovered_tpdos.iter()
+            .find(|config| config.tpdo_number == tpdo_num)
+            .and_then(|config| config.event_timer_ms);
+        pdo_timing::stale_timeout(
+            self.config.tpdo_timeout(self.config.node_id, tpdo_num),
+            event_timer_ms,
+            self.tpdo_timing.get(&tpdo_num).and_then(PdoTiming::period),
+        )
+    }
 
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            if self.subscriptions.is_empty() && self.tpdo_field_subscriptions.is_empty() {
-                ui.label("No active subscriptions. Select an SDO to start reading or enable TPDO plotting.");
-            } else {
+    /// How long an active TPDO has been silent and its timeout, if it went stale
+    fn tpdo_stale(&self, tpdo_num: u8) -> Option<(Duration, Duration)> {
+        let silent = self.tpdo_last_seen.get(&tpdo_num)?.elapsed();
+        let timeout = self.tpdo_stale_timeout(tpdo_num)?;
+        (silent > timeout).then_some((silent, timeout))
+    }
+
+    /// Decode a TPDO field as another type; running listeners restart with the new layout
+    fn set_tpdo_data_type(&mut self, address: &SdoAddress, data_type: Option<SdoDataType>) {
+        self.config.set_tpdo_data_type(address, data_type);
+        if let Err(e) = self.config.save() {
+            self.error_message = Some(format!("Failed to save TPDO field type: {}", e));
+        }
+        if let Some(tx) = &self.command_tx {
+            let _ = tx.send(Command::SetTpdoTypeOverrides(self.config.tpdo_type_overrides()));
+            let _ = tx.send(Command::RediscoverTpdos);
+            self.discovered_tpdos.clear();
+        }
+    }
+
+    fn draw_plots(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.heading("Plots");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                // Right to left: the button ends up after the text field
+                ui.horizontal(|ui| {
+                    if let Some(marker) = self.timeline.marker_input(ui) {
+                        self.logger.log(LogEvent::Marker(marker));
+                    }
+                });
+                let recording = !self.subscriptions.is_empty() || !self.tpdo_field_subscriptions.is_empty();
+                ui.add_enabled_ui(recording, |ui| {
+                    ui.menu_button("💾 Export session", |ui| {
+                        ui.label("All subscribed channels in one CSV, one row per sample time:");
+                        for alignment in Alignment::ALL {
+                            if ui.button(alignment.label()).clicked() {
+                                self.export_session(alignment);
+                                ui.close_menu();
+                            }
+                        }
+                        ui.separator();
+                        ui.label("Every sample as a row, for pandas or Polars:");
+                        if ui.button("Parquet (timestamp, channel, value)").clicked() {
+                            self.export_session_parquet();
+                            ui.close_menu();
+                        }
+                    });
+                });
+                if ui.button("🧮 Derived").on_hover_text("Channels computed from the recorded ones, like power from voltage and current").clicked() {
+                    self.derived_panel.open = true;
+                }
+                if ui.button("📄 Report").on_hover_text("PDF test report of the session with the plots, statistics and alarms").clicked() {
+                    self.report_panel.open = true;
+                }
+                if capture::menu(ui, &mut self.config) {
+                    if let Err(e) = self.config.save() {
+                        eprintln!("Failed to save configuration: {}", e);
+                    }
+                }
+                if let Some(path) = &self.last_capture {
+                    ui.weak(format!("Saved {}", path.file_name().unwrap_or_default().to_string_lossy()))
+                        .on_hover_text(path.display().to_string());
+                }
+                if ui.checkbox(&mut self.config.plot_wall_clock, "🕒 Clock time")
+                    .on_hover_text("Label the time axis with the local time of day instead of seconds since connecting")
+                    .changed()
+                {
+                    if let Err(e) = self.config.save() {
+                        eprintln!("Failed to save configuration: {}", e);
+                    }
+                }
+            });
+        });
+        let active = self.config.active_dashboard;
+        match dashboard::tabs(ui, &mut self.config.dashboards, active) {
+            Some(DashboardAction::Switch(index)) => {
+                self.config.active_dashboard = index;
+                self.save_dashboards();
+                self.restore_dashboard_now();
+            }
+            Some(DashboardAction::Add) => {
+                let name = format!("Dashboard {}", self.config.dashboards.len() + 1);
+                self.config.dashboards.push(Dashboard::new(&name));
+                self.config.active_dashboard = self.config.dashboards.len() - 1;
+                self.save_dashboards();
+            }
+            Some(DashboardAction::Delete(index)) => {
+                self.config.dashboards.remove(index);
+                if self.config.active_dashboard > index || self.config.active_dashboard >= self.config.dashboards.len() {
+                    self.config.active_dashboard = self.config.active_dashboard.saturating_sub(1);
+                }
+                self.save_dashboards();
+                self.restore_dashboard_now();
+            }
+            Some(DashboardAction::Renamed) => self.save_dashboards(),
+            None => {}
+        }
+        ui.separator();
+        self.assign_channel_styles();
+
+        let mut drops = Vec::new();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            let dashboard = self.config.dashboard();
+            let shown = self.recorded_series().iter().any(|series| dashboard.shows(series));
+            if !shown {
+                ui.label("Nothing plotted on this dashboard. Select an SDO to start reading, enable TPDO plotting, or drag an object here.");
+            } else {
+
+                let mut overlay_additions = Vec::new();
+                let mut gauge_additions = Vec::new();
+                let mut indicator_additions = Vec::new();
+                let mut xy_additions = Vec::new();
+                let mut histogram_additions = Vec::new();
+                let mut stats_to_reset = Vec::new();
+                let mut buffer_changes = Vec::new();
+                let mut y_axis_changes = Vec::new();
+                let mut style_changes = Vec::new();
+                let mut svg_exports = Vec::new();
+                let mut follow_changes = Vec::new();
+                // Series of this dashboard another one can be plotted against
+                let xy_candidates: Vec<(PlotSeries, String)> = self.recorded_series().into_iter()
+                    .filter(|series| self.config.dashboard().shows(series))
+                    .filter_map(|series| {
+                        let name = self.plot_series(&series)?.name;
+                        Some((series, name))
+                    })
+                    .collect();
+                self.draw_indicators(ui);
+                self.draw_gauges(ui);
+                self.draw_bar_charts(ui);
+                self.draw_xy_plots(ui);
+                self.draw_histograms(ui);
+                self.draw_overlays(ui, &mut drops);
+                let mut follow_requests = std::mem::take(&mut self.follow_live_requests);
+
+                // Draw SDO plots
+                let mut addresses_to_clear = Vec::new();
+                let mut addresses_to_export = Vec::new();
+
+                for (address, subscription) in &self.subscriptions {
+                    let series = PlotSeries::Sdo(address.clone());
+                    let dashboard = self.config.dashboard();
+                    if !dashboard.shows(&series) || dashboard.plot_replaced(&series) || dashboard.overlays.iter().any(|overlay| overlay.contains(&series)) {
+                        continue;
+                    }
+                    // 1. Use a Frame to visually group each plot and its title.
+                    let mut capture_clicked = false;
+                    let mut plot_title = String::new();
+
+                    let (frame_response, dropped) = ui.dnd_drop_zone::<PlotSeries, _>(egui::Frame::group(ui.style()), |ui| {
+                        let plot_id = format!("sdo_plot_{:x}_{}", address.index, address.sub_index);
+
+                        let field_name = self.object_dictionary.as_ref()
+                            .and_then(|dict| dict.get(&address.index))
+                            .and_then(|obj| obj.sub_objects.get(&address.sub_index))
+                            .map(|sub_obj| sub_obj.name.clone())
+                            .unwrap_or_else(|| format!("0x{:04X}:{:02X}", address.index, address.sub_index));
+
+                        plot_title = format!("SDO - {} ({:#06X}:{})", field_name, address.index, address.sub_index);
+                        let scaling = effective_scaling(&self.config, self.object_dictionary.as_ref(), address);
+
+                        // Add a title for the individual plot, with the latest value on the right.
+                        ui.horizontal(|ui| {
+                            ui.label(&plot_title);
+                            if let Some(value) = &subscription.last_value {
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    let radix = self.value_radix.entry(address.clone()).or_default();
+                                    radix_toggle(ui, radix, &subscription.data_type);
+                                    let shown = display_value(scaling.as_ref(), &self.value_descriptions, *radix, address, value, &subscription.data_type);
+                                    ui.label(format!("Last: {}", shown));
+                                });
+                            }
+                        });
+                        ui.separator();
+
+                        let y_axis = self.config.dashboard().y_axis(&series);
+                        let style = self.config.channel_style(&series).unwrap_or_default();
+                        let follow_request = follow_requests.remove(&plot_id);
+                        let plot = Plot::new(&plot_id)
+                            .legend(egui_plot::Legend::default())
+                            .view_aspect(2.0)
+                            .allow_scroll(false)
+                            .height(350.0)
+                            .width(ui.available_width());
+                        let following = plot_axes(follow_live(plot, follow_request), self.session_start, self.config.plot_wall_clock, y_axis)
+                            .y_axis_label(scaling.as_ref().map_or_else(|| "Value".to_string(), Scaling::value_label))
+                            .legend(Legend::default())
+                            .show(ui, |plot_ui| {
+                                let following = hold_view(plot_ui, follow_request);
+                                let points_vec: Vec<[f64; 2]> = y_axis.transform(plot_points(plot_ui, &subscription.plot_data, &subscription.history, scaling.as_ref()));
+
+                                let line = Line::new(PlotPoints::from(points_vec))
+                                    .name(&field_name);  // Use field name in legend (without hex address)
+
+                                plot_ui.line(style.apply(line));
+                                draw_markers(plot_ui, &self.timeline, self.session_start);
+                                following
+                            }).inner;
+                        let stats = subscription.stats.scaled(scaling.as_ref());
+                        if stats_line(ui, &stats, subscription.rate.rate(Local::now())) {
+                            stats_to_reset.push(series.clone());
+                        }
+
+                        ui.horizontal_wrapped(|ui| {
+                            if let Some(follow) = follow_live_toggle(ui, following) {
+                                follow_changes.push((plot_id.clone(), follow));
+                            }
+
+                            if ui.button("📸 Capture Plot").clicked() {
+                                capture_clicked = true;
+                            }
+
+                            if ui.button("🗑 Clear").clicked() {
+                                addresses_to_clear.push(address.clone());
+                            }
+
+                            if ui.button("💾 Export to CSV").clicked() {
+                                addresses_to_export.push(address.clone());
+                            }
+
+                            if ui.button("🖼 Export SVG").on_hover_text("Save the plot as a vector figure for reports").clicked() {
+                                svg_exports.push((plot_title.clone(), vec![series.clone()], y_axis));
+                            }
+
+                            overlay_menu(ui, &self.config.dashboard().overlays, series.clone(), &mut overlay_additions);
+
+                            if ui.button("🕓 Gauge").on_hover_text("Show the latest value on a dial instead").clicked() {
+                                gauge_additions.push(series.clone());
+                            }
+                            if ui.button("💡 LED").on_hover_text("Show the value, or bits of it, as indicator LEDs instead").clicked() {
+                                indicator_additions.push(series.clone());
+                            }
+                            xy_menu(ui, &xy_candidates, series.clone(), &mut xy_additions);
+                            if let Some(change) = buffer_menu(ui, subscription.buffer, self.config.plot_buffer) {
+                                buffer_changes.push((series.clone(), change));
+                            }
+                            let mut axis = y_axis;
+                            if y_axis::menu(ui, &mut axis, (stats.count > 0).then_some((stats.min, stats.max)), scaling.as_ref().map_or("", |scaling| scaling.unit.as_str())) {
+                                y_axis_changes.push((series.clone(), axis));
+                            }
+                            let mut new_style = style;
+                            if channel_style::menu(ui, &mut new_style) {
+                                style_changes.push((series.clone(), new_style));
+                            }
+                            if ui.button("📶 Histogram").on_hover_text("Distribution of the recent samples").clicked() {
+                                histogram_additions.push(series.clone());
+                            }
+                        });
+                    });
+
+                    if let Some(dropped) = dropped {
+                        drops.push(((*dropped).clone(), DropTarget::Plot(series)));
+                    }
+
+                    // Handle capture after we have the frame rect
+                    if capture_clicked {
+                        let info = ScreenshotInfo{
+                            filename: format!("{}.png", plot_title),
+                            rect: frame_response.response.rect,
+                        };
+
+                        let user_data = egui::UserData::new(Arc::new(info));
+                        ui.ctx().send_viewport_cmd(egui::ViewportCommand::Screenshot(user_data));
+                    }
+                }
+
+                for address in addresses_to_clear {
+                    if let Some(subscription) = self.subscriptions.get_mut(&address) {
+                        subscription.plot_data.clear();
+                        subscription.stats.reset();
+                        subscription.history.clear();
+                    }
+                }
+
+                for address in addresses_to_export {
+                    self.export_plot_data_to_csv(&address);
+                }
+
+                // Draw TPDO field plots
+                let mut tpdo_fields_to_clear = Vec::new();
+                let mut tpdo_fields_to_export = Vec::new();
+
+                for (field_id, subscription) in &self.tpdo_field_subscriptions {
+                    let series = PlotSeries::Tpdo(field_id.clone());
+                    let dashboard = self.config.dashboard();
+                    if !dashboard.shows(&series) || dashboard.plot_replaced(&series) || dashboard.overlays.iter().any(|overlay| overlay.contains(&series)) {
+                        continue;
+                    }
+                    let mut capture_clicked = false;
+                    let mut plot_title = String::new();
+
+                    let (frame_response, dropped) = ui.dnd_drop_zone::<PlotSeries, _>(egui::Frame::group(ui.style()), |ui| {
+                        let plot_id = format!("tpdo_plot_{}_{}", field_id.tpdo_number, field_id.field_name);
+                        plot_title = format!("TPDO {} - {}", field_id.tpdo_number, field_id.field_name);
+
+                        let mapped_object = self.discovered_tpdos.iter()
+                            .find(|config| config.tpdo_number == field_id.tpdo_number)
+                            .and_then(|config| config.mapped_objects.iter().find(|obj| obj.name == field_id.field_name));
+                        let scaling = mapped_object.and_then(|obj| {
+                            let address = SdoAddress { index: obj.index, sub_index: obj.sub_index };
+                            effective_scaling(&self.config, self.object_dictionary.as_ref(), &address)
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label(&plot_title);
+                            if let (Some(value), Some(obj)) = (&subscription.last_value, mapped_object) {
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    let address = SdoAddress { index: obj.index, sub_index: obj.sub_index };
+                                    let radix = self.value_radix.entry(address.clone()).or_default();
+                                    radix_toggle(ui, radix, &obj.data_type);
+                                    let shown = display_value(scaling.as_ref(), &self.value_descriptions, *radix, &address, value, &obj.data_type);
+                                    ui.label(format!("Last: {}", shown));
+                                });
+                            }
+                        });
+                        ui.separator();
+
+                        let y_axis = self.config.dashboard().y_axis(&series);
+                        let style = self.config.channel_style(&series).unwrap_or_default();
+                        let follow_request = follow_requests.remove(&plot_id);
+                        let plot = Plot::new(&plot_id)
+                            .legend(egui_plot::Legend::default())
+                            .view_aspect(2.0)
+                            .allow_scroll(false)
+                            .height(350.0)
+                            .width(ui.available_width());
+                        let following = plot_axes(follow_live(plot, follow_request), self.session_start, self.config.plot_wall_clock, y_axis)
+                            .y_axis_label(scaling.as_ref().map_or_else(|| "Value".to_string(), Scaling::value_label))
+                            .legend(Legend::default())
+                            .show(ui, |plot_ui| {
+                                let following = hold_view(plot_ui, follow_request);
+                                let points_vec: Vec<[f64; 2]> = y_axis.transform(plot_points(plot_ui, &subscription.plot_data, &subscription.history, scaling.as_ref()));
+
+                                let line = Line::new(PlotPoints::from(points_vec))
+                                    .name(&plot_title);
+
+                                plot_ui.line(style.apply(line));
+                                draw_markers(plot_ui, &self.timeline, self.session_start);
+                                following
+                            }).inner;
+                        let stats = subscription.stats.scaled(scaling.as_ref());
+                        if stats_line(ui, &stats, subscription.rate.rate(Local::now())) {
+                            stats_to_reset.push(series.clone());
+                        }
+
+                        ui.horizontal_wrapped(|ui| {
+                            if let Some(follow) = follow_live_toggle(ui, following) {
+                                follow_changes.push((plot_id.clone(), follow));
+                            }
+
+                            if ui.button("📸 Capture Plot").clicked() {
+                                capture_clicked = true;
+                            }
+
+                            if ui.button("🗑 Clear").clicked() {
+                                tpdo_fields_to_clear.push(field_id.clone());
+                            }
+
+                            if ui.button("💾 Export to CSV").clicked() {
+                                tpdo_fields_to_export.push(field_id.clone());
+                            }
+
+                            if ui.button("🖼 Export SVG").on_hover_text("Save the plot as a vector figure for reports").clicked() {
+                                svg_exports.push((plot_title.clone(), vec![series.clone()], y_axis));
+                            }
+
+                            overlay_menu(ui, &self.config.dashboard().overlays, series.clone(), &mut overlay_additions);
+
+                            if ui.button("🕓 Gauge").on_hover_text("Show the latest value on a dial instead").clicked() {
+                                gauge_additions.push(series.clone());
+                            }
+                            if ui.button("💡 LED").on_hover_text("Show the value, or bits of it, as indicator LEDs instead").clicked() {
+                                indicator_additions.push(series.clone());
+                            }
+                            xy_menu(ui, &xy_candidates, series.clone(), &mut xy_additions);
+                            if let Some(change) = buffer_menu(ui, subscription.buffer, self.config.plot_buffer) {
+                                buffer_changes.push((series.clone(), change));
+                            }
+                            let mut axis = y_axis;
+                            if y_axis::menu(ui, &mut axis, (stats.count > 0).then_some((stats.min, stats.max)), scaling.as_ref().map_or("", |scaling| scaling.unit.as_str())) {
+                                y_axis_changes.push((series.clone(), axis));
+                            }
+                            let mut new_style = style;
+                            if channel_style::menu(ui, &mut new_style) {
+                                style_changes.push((series.clone(), new_style));
+                            }
+                            if ui.button("📶 Histogram").on_hover_text("Distribution of the recent samples").clicked() {
+                                histogram_additions.push(series.clone());
+                            }
+                        });
+                    });
+
+                    if let Some(dropped) = dropped {
+                        drops.push(((*dropped).clone(), DropTarget::Plot(series)));
+                    }
+
+                    // Handle capture after we have the frame rect
+                    if capture_clicked {
+                        let info = ScreenshotInfo{
+                            filename: format!("{}.png", plot_title),
+                            rect: frame_response.response.rect,
+                        };
+
+                        let user_data = egui::UserData::new(Arc::new(info));
+                        ui.ctx().send_viewport_cmd(egui::ViewportCommand::Screenshot(user_data));
+                    }
+                }
+
+                // Draw derived channel plots
+                let mut derived_to_clear = Vec::new();
+                let mut derived_to_export = Vec::new();
+
+                for (name, channel) in &self.derived_channels {
+                    let series = PlotSeries::Derived(name.clone());
+                    let dashboard = self.config.dashboard();
+                    if !dashboard.shows(&series) || dashboard.plot_replaced(&series) || dashboard.overlays.iter().any(|overlay| overlay.contains(&series)) {
+                        continue;
+                    }
+                    let mut capture_clicked = false;
+                    let plot_title = format!("Derived - {}", name);
+
+                    let (frame_response, dropped) = ui.dnd_drop_zone::<PlotSeries, _>(egui::Frame::group(ui.style()), |ui| {
+                        let plot_id = format!("derived_plot_{}", name);
+
+                        ui.horizontal(|ui| {
+                            ui.label(&plot_title);
+                            ui.weak(&channel.definition.expression);
+                            if let Some(value) = channel.last_value {
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    ui.label(format!("Last: {} {}", y_axis::format_value(value), channel.definition.unit));
+                                });
+                            }
+                        });
+                        ui.separator();
+
+                        let y_axis = self.config.dashboard().y_axis(&series);
+                        let style = self.config.channel_style(&series).unwrap_or_default();
+                        let follow_request = follow_requests.remove(&plot_id);
+                        let plot = Plot::new(&plot_id)
+                            .legend(egui_plot::Legend::default())
+                            .view_aspect(2.0)
+                            .allow_scroll(false)
+                            .height(350.0)
+                            .width(ui.available_width());
+                        let following = plot_axes(follow_live(plot, follow_request), self.session_start, self.config.plot_wall_clock, y_axis)
+                            .y_axis_label(channel.definition.value_label())
+                            .legend(Legend::default())
+                            .show(ui, |plot_ui| {
+                                let following = hold_view(plot_ui, follow_request);
+                                let points_vec: Vec<[f64; 2]> = y_axis.transform(plot_points(plot_ui, &channel.plot_data, &channel.history, None));
+
+                                let line = Line::new(PlotPoints::from(points_vec))
+                                    .name(name);
+
+                                plot_ui.line(style.apply(line));
+                                draw_markers(plot_ui, &self.timeline, self.session_start);
+                                following
+                            }).inner;
+                        if stats_line(ui, &channel.stats, channel.rate.rate(Local::now())) {
+                            stats_to_reset.push(series.clone());
+                        }
+
+                        ui.horizontal_wrapped(|ui| {
+                            if let Some(follow) = follow_live_toggle(ui, following) {
+                                follow_changes.push((plot_id.clone(), follow));
+                            }
+
+                            if ui.button("📸 Capture Plot").clicked() {
+                                capture_clicked = true;
+                            }
+
+                            if ui.button("🗑 Clear").clicked() {
+                                derived_to_clear.push(name.clone());
+                            }
+
+                            if ui.button("💾 Export to CSV").clicked() {
+                                derived_to_export.push(name.clone());
+                            }
+
+                            if ui.button("🖼 Export SVG").on_hover_text("Save the plot as a vector figure for reports").clicked() {
+                                svg_exports.push((plot_title.clone(), vec![series.clone()], y_axis));
+                            }
+
+                            overlay_menu(ui, &self.config.dashboard().overlays, series.clone(), &mut overlay_additions);
+
+                            if ui.button("🕓 Gauge").on_hover_text("Show the latest value on a dial instead").clicked() {
+                                gauge_additions.push(series.clone());
+                            }
+                            if ui.button("💡 LED").on_hover_text("Show the value, or bits of it, as indicator LEDs instead").clicked() {
+                                indicator_additions.push(series.clone());
+                            }
+                            xy_menu(ui, &xy_candidates, series.clone(), &mut xy_additions);
+                            if let Some(change) = buffer_menu(ui, channel.buffer, self.config.plot_buffer) {
+                                buffer_changes.push((series.clone(), change));
+                            }
+                            let mut axis = y_axis;
+                            if y_axis::menu(ui, &mut axis, (channel.stats.count > 0).then_some((channel.stats.min, channel.stats.max)), &channel.definition.unit) {
+                                y_axis_changes.push((series.clone(), axis));
+                            }
+                            let mut new_style = style;
+                            if channel_style::menu(ui, &mut new_style) {
+                                style_changes.push((series.clone(), new_style));
+                            }
+                            if ui.button("📶 Histogram").on_hover_text("Distribution of the recent samples").clicked() {
+                                histogram_additions.push(series.clone());
+                            }
+                        });
+                    });
+
+                    if let Some(dropped) = dropped {
+                        drops.push(((*dropped).clone(), DropTarget::Plot(series)));
+                    }
+
+                    // Handle capture after we have the frame rect
+                    if capture_clicked {
+                        let info = ScreenshotInfo{
+                            filename: format!("{}.png", plot_title),
+                            rect: frame_response.response.rect,
+                        };
+
+                        let user_data = egui::UserData::new(Arc::new(info));
+                        ui.ctx().send_viewport_cmd(egui::ViewportCommand::Screenshot(user_data));
+                    }
+                }
+
+                self.follow_live_requests.extend(follow_changes);
+
+                // Clear derived channel plots
+                for name in derived_to_clear {
+                    if let Some(channel) = self.derived_channels.get_mut(&name) {
+                        channel.clear();
+                    }
+                }
+
+                // Export derived channel plots
+                for name in derived_to_export {
+                    self.export_derived_plot_data_to_csv(&name);
+                }
+
+                // Clear TPDO field plots
+                for field_id in tpdo_fields_to_clear {
+                    if let Some(subscription) = self.tpdo_field_subscriptions.get_mut(&field_id) {
+                        subscription.plot_data.clear();
+                        subscription.stats.reset();
+                        subscription.history.clear();
+                    }
+                }
+
+                // Export TPDO field plots
+                for field_id in tpdo_fields_to_export {
+                    self.export_tpdo_plot_data_to_csv(&field_id);
+                }
+
+                for (series, change) in buffer_changes {
+                    let buffer = match change {
+                        BufferChange::Set(buffer) => buffer,
+                        BufferChange::MakeDefault(buffer) => {
+                            self.config.plot_buffer = buffer;
+                            if let Err(e) = self.config.save() {
+                                eprintln!("Failed to save config: {}", e);
+                            }
+                            None
+                        }
+                    };
+                    let default = self.config.plot_buffer;
+                    let (subscription_buffer, plot_data) = match &series {
+                        PlotSeries::Sdo(address) => match self.subscriptions.get_mut(address) {
+                            Some(subscription) => (&mut subscription.buffer, &mut subscription.plot_data),
+                            None => continue,
+                        },
+                        PlotSeries::Tpdo(field_id) => match self.tpdo_field_subscriptions.get_mut(field_id) {
+                            Some(subscription) => (&mut subscription.buffer, &mut subscription.plot_data),
+                            None => continue,
+                        },
+                        PlotSeries::Derived(name) => match self.derived_channels.get_mut(name) {
+                            Some(channel) => (&mut channel.buffer, &mut channel.plot_data),
+                            None => continue,
+                        },
+                    };
+                    *subscription_buffer = buffer;
+                    buffer.unwrap_or(default).trim(plot_data);
+                }
+
+                for (title, series, axis) in svg_exports {
+                    self.export_plot_svg(&title, &series, axis);
+                }
+
+                for (series, axis) in y_axis_changes {
+                    self.config.dashboard_mut().set_y_axis(series, axis);
+                    self.save_dashboards();
+                }
+
+                for (series, style) in style_changes {
+                    self.config.set_channel_style(&series, style);
+                    if let Err(e) = self.config.save() {
+                        eprintln!("Failed to save config: {}", e);
+                    }
+                }
+
+                for series in stats_to_reset {
+                    match series {
+                        PlotSeries::Sdo(address) => {
+                            if let Some(subscription) = self.subscriptions.get_mut(&address) {
+                                subscription.stats.reset();
+                            }
+                        }
+                        PlotSeries::Tpdo(field_id) => {
+                            if let Some(subscription) = self.tpdo_field_subscriptions.get_mut(&field_id) {
+                                subscription.stats.reset();
+                            }
+                        }
+                        PlotSeries::Derived(name) => {
+                            if let Some(channel) = self.derived_channels.get_mut(&name) {
+                                channel.stats.reset();
+                            }
+                        }
+                    }
+                }
+
+                for series in histogram_additions {
+                    self.config.dashboard_mut().histograms.push(HistogramSettings::new(series));
+                    self.save_dashboards();
+                }
+
+                for (x, y) in xy_additions {
+                    self.config.dashboard_mut().xy_plots.push(XyPlotSettings::new(x, y));
+                    self.save_dashboards();
+                }
+
+                for series in indicator_additions {
+                    self.config.dashboard_mut().indicators.push(IndicatorSettings::new(series));
+                    self.save_dashboards();
+                }
+
+                for series in gauge_additions {
+                    let points = self.plot_series(&series).map(|data| data.points).unwrap_or_default();
+                    self.config.dashboard_mut().gauges.push(GaugeSettings::new(series, &points));
+                    self.save_dashboards();
+                }
+
+                if !overlay_additions.is_empty() {
+                    let dashboard = self.config.dashboard_mut();
+                    for (overlay, series) in overlay_additions {
+                        match overlay.and_then(|overlay| dashboard.overlays.get_mut(overlay)) {
+                            Some(overlay) => overlay.add(series),
+                            None => {
+                                dashboard.overlays_created += 1;
+                                dashboard.overlays.push(OverlayPlot::new(dashboard.overlays_created, series));
+                            }
+                        }
+                    }
+                    self.save_dashboards();
+                }
+            }
+
+            // Room for a new plot while something is dragged
+            if egui::DragAndDrop::has_payload_of_type::<PlotSeries>(ui.ctx()) {
+                let (_, dropped) = ui.dnd_drop_zone::<PlotSeries, _>(egui::Frame::group(ui.style()), |ui| {
+                    ui.set_min_size(egui::vec2(ui.available_width(), 80.0));
+                    ui.centered_and_justified(|ui| ui.label("Drop here for a new plot"));
+                });
+                if let Some(dropped) = dropped {
+                    drops.push(((*dropped).clone(), DropTarget::NewPlot));
+                }
+            }
+        });
+
+        for (series, target) in drops {
+            if self.record_series(&series) {
+                let dashboard = self.config.dashboard_mut();
+                overlay::place(&mut dashboard.overlays, &mut dashboard.overlays_created, series, target);
+                self.save_dashboards();
+            }
+        }
+    }
+
+    /// Subscribe what the active dashboard plots once the TPDOs of this connection are known
+    fn restore_dashboard(&mut self) {
+        if !self.dashboard_restored {
+            self.dashboard_restored = true;
+            self.restore_dashboard_now();
+        }
+    }
+
+    fn restore_dashboard_now(&mut self) {
+        if self.command_tx.is_none() {
+            return;
+        }
+        let dashboard = self.config.dashboard().clone();
+        for entry in &dashboard.sdo {
+            if !self.subscriptions.contains_key(&entry.address()) {
+                self.subscribe(entry.address(), entry.interval_ms, entry.data_type.clone());
+            }
+        }
+        for field_id in dashboard.tpdo_fields {
+            self.record_series(&PlotSeries::Tpdo(field_id));
+        }
+    }
+
+    /// Give every recorded channel without a style its palette color, kept in the config
+    fn assign_channel_styles(&mut self) {
+        let mut assigned = false;
+        for series in &self.recorded_series() {
+            assigned |= self.config.assign_channel_style(series);
+        }
+        if assigned {
+            if let Err(e) = self.config.save() {
+                eprintln!("Failed to save config: {}", e);
+            }
+        }
+    }
+
+    /// Add a recorded series to the active dashboard
+    fn show_on_dashboard(&mut self, series: &PlotSeries) {
+        let dashboard = self.config.dashboard_mut();
+        match series {
+            PlotSeries::Sdo(address) => {
+                let Some(subscription) = self.subscriptions.get(address) else {
+                    return;
+                };
+                let entry = PresetSdo {
+                    index: address.index,
+                    sub_index: address.sub_index,
+                    interval_ms: subscription.interval_ms,
+                    data_type: subscription.data_type.clone(),
+                };
+                if dashboard.sdo.contains(&entry) {
+                    return;
+                }
+                dashboard.add_sdo(entry);
+            }
+            PlotSeries::Tpdo(field_id) => {
+                if dashboard.tpdo_fields.contains(field_id) {
+                    return;
+                }
+                dashboard.add_tpdo_field(field_id.clone());
+            }
+            PlotSeries::Derived(name) => {
+                if !self.derived_channels.contains_key(name) || dashboard.derived.contains(name) {
+                    return;
+                }
+                dashboard.add_derived(name);
+            }
+        }
+        self.save_dashboards();
+    }
+
+    fn save_dashboards(&self) {
+        if let Err(e) = self.config.save() {
+            eprintln!("Failed to save dashboards: {}", e);
+        }
+    }
+
+    /// Start recording a series dropped onto the plots unless it already is
+    fn record_series(&mut self, series: &PlotSeries) -> bool {
+        if self.command_tx.is_none() {
+            return false;
+        }
+        match series {
+            PlotSeries::Sdo(address) => {
+                if !self.subscriptions.contains_key(address) {
+                    let data_type = self.object_dictionary.as_ref()
+                        .and_then(|dict| dict.get(&address.index))
+                        .and_then(|obj| obj.sub_objects.get(&address.sub_index))
+                        .and_then(|sub_obj| SdoDataType::from_eds_type(&sub_obj.data_type))
+                        .unwrap_or(SdoDataType::Real32);
+                    self.subscribe(address.clone(), DEFAULT_DROP_INTERVAL_MS, data_type);
+                }
+            }
+            PlotSeries::Tpdo(field_id) => {
+                if !self.active_tpdos.contains(&field_id.tpdo_number) {
+                    let Some(config) = self.discovered_tpdos.iter().find(|config| config.tpdo_number == field_id.tpdo_number) else {
+                        return false;
+                    };
+                    if let Some(tx) = &self.command_tx {
+                        let _ = tx.send(Command::StartTpdoListener(config.clone()));
+                    }
+                    self.active_tpdos.insert(field_id.tpdo_number);
+                }
+                // Plotted before its first frame arrives
+                self.tpdo_field_subscriptions.entry(field_id.clone()).or_insert_with(|| TpdoFieldSubscription {
+                    plot_data: VecDeque::new(),
+                    last_value: None,
+                    last_timestamp: None,
+                    rate: RateMeter::default(),
+                    stats: ChannelStats::default(),
+                    buffer: None,
+                    history: SampleHistory::new(&self.config.history, &series.key()),
+                });
+            }
+            PlotSeries::Derived(name) => {
+                if !self.derived_channels.contains_key(name) {
+                    return false;
+                }
+            }
+        }
+        self.show_on_dashboard(series);
+        true
+    }
+
+    /// Plot buffer and full history of a recorded series, with raw values
+    fn series_buffers(&self, series: &PlotSeries) -> Option<(&VecDeque<[f64; 2]>, &SampleHistory)> {
+        match series {
+            PlotSeries::Sdo(address) => self.subscriptions.get(address).map(|sub| (&sub.plot_data, &sub.history)),
+            PlotSeries::Tpdo(field_id) => self.tpdo_field_subscriptions.get(field_id).map(|sub| (&sub.plot_data, &sub.history)),
+            PlotSeries::Derived(name) => self.derived_channels.get(name).map(|channel| (&channel.plot_data, &channel.history)),
+        }
+    }
+
+    fn series_scaling(&self, series: &PlotSeries) -> Option<Scaling> {
+        match series {
+            PlotSeries::Sdo(address) => effective_scaling(&self.config, self.object_dictionary.as_ref(), address),
+            PlotSeries::Tpdo(field_id) => self.discovered_tpdos.iter()
+                .find(|config| config.tpdo_number == field_id.tpdo_number)
+                .and_then(|config| config.mapped_objects.iter().find(|obj| obj.name == field_id.field_name))
+                .and_then(|obj| {
+                    let address = SdoAddress { index: obj.index, sub_index: obj.sub_index };
+                    effective_scaling(&self.config, self.object_dictionary.as_ref(), &address)
+                }),
+            // Computed from scaled values already
+            PlotSeries::Derived(_) => None,
+        }
+    }
+
+    /// Like `plot_series`, with every sample recorded instead of the plot buffer, for exports
+    fn series_history(&self, series: &PlotSeries) -> Option<SeriesData> {
+        let (plot_data, history) = self.series_buffers(series)?;
+        let mut data = self.plot_series(series)?;
+        data.points = scaled_points(&full_history(history, plot_data), self.series_scaling(series).as_ref());
+        Some(data)
+    }
+
+    fn plot_series(&self, series: &PlotSeries) -> Option<SeriesData> {
+        let value_label = |scaling: Option<&Scaling>| scaling.map_or_else(|| "Value".to_string(), Scaling::value_label);
+        match series {
+            PlotSeries::Sdo(address) => {
+                let subscription = self.subscriptions.get(address)?;
+                let scaling = self.series_scaling(series);
+                Some(SeriesData {
+                    name: self.series_name(series),
+                    points: scaled_points(&subscription.plot_data, scaling.as_ref()),
+                    value_label: value_label(scaling.as_ref()),
+                    unit: scaling.map_or_else(String::new, |scaling| scaling.unit),
+                    last_raw: subscription.plot_data.back().map(|point| point[1]),
+                })
+            }
+            PlotSeries::Tpdo(field_id) => {
+                let subscription = self.tpdo_field_subscriptions.get(field_id)?;
+                let scaling = self.series_scaling(series);
+                Some(SeriesData {
+                    name: self.series_name(series),
+                    points: scaled_points(&subscription.plot_data, scaling.as_ref()),
+                    value_label: value_label(scaling.as_ref()),
+                    unit: scaling.map_or_else(String::new, |scaling| scaling.unit),
+                    last_raw: subscription.plot_data.back().map(|point| point[1]),
+                })
+            }
+            PlotSeries::Derived(name) => {
+                let channel = self.derived_channels.get(name)?;
+                Some(SeriesData {
+                    name: name.clone(),
+                    points: scaled_points(&channel.plot_data, None),
+                    value_label: channel.definition.value_label(),
+                    unit: channel.definition.unit.clone(),
+                    last_raw: channel.last_value,
+                })
+            }
+        }
+    }
 
-                // Draw SDO plots
-                let mut addresses_to_clear = Vec::new();
-                let mut addresses_to_export = Vec::new();
+    /// Legend name of a series, which derived channels refer to it by
+    fn series_name(&self, series: &PlotSeries) -> String {
+        match series {
+            PlotSeries::Sdo(address) => self.object_dictionary.as_ref()
+                .and_then(|dict| dict.get(&address.index))
+                .and_then(|obj| obj.sub_objects.get(&address.sub_index))
+                .map(|sub_obj| sub_obj.name.clone())
+                .unwrap_or_else(|| format!("0x{:04X}:{:02X}", address.index, address.sub_index)),
+            PlotSeries::Tpdo(field_id) => format!("TPDO {} - {}", field_id.tpdo_number, field_id.field_name),
+            PlotSeries::Derived(name) => name.clone(),
+        }
+    }
 
-                for (address, subscription) in &self.subscriptions {
-                    // 1. Use a Frame to visually group each plot and its title.
-                    let mut capture_clicked = false;
-                    let mut plot_title = String::new();
+    /// Every recorded series, derived channels included
+    fn recorded_series(&self) -> Vec<PlotSeries> {
+        self.subscriptions.keys().cloned().map(PlotSeries::Sdo)
+            .chain(self.tpdo_field_subscriptions.keys().cloned().map(PlotSeries::Tpdo))
+            .chain(self.derived_channels.keys().cloned().map(PlotSeries::Derived))
+            .collect()
+    }
 
-                    let frame_response = egui::Frame::group(ui.style()).show(ui, |ui| {
-                        let plot_id = format!("sdo_plot_{:x}_{}", address.index, address.sub_index);
+    /// Names of the SDO and TPDO channels an expression can refer to
+    fn recorded_channel_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.subscriptions.keys().cloned().map(PlotSeries::Sdo)
+            .chain(self.tpdo_field_subscriptions.keys().cloned().map(PlotSeries::Tpdo))
+            .map(|series| self.series_name(&series))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
 
-                        let field_name = self.object_dictionary.as_ref()
-                            .and_then(|dict| dict.get(&address.index))
-                            .and_then(|obj| obj.sub_objects.get(&address.sub_index))
-                            .map(|sub_obj| sub_obj.name.clone())
-                            .unwrap_or_else(|| format!("0x{:04X}:{:02X}", address.index, address.sub_index));
+    /// SDO or TPDO channel a derived channel refers to by name or config key
+    fn resolve_channel(&self, reference: &str) -> Option<PlotSeries> {
+        self.subscriptions.keys().cloned().map(PlotSeries::Sdo)
+            .chain(self.tpdo_field_subscriptions.keys().cloned().map(PlotSeries::Tpdo))
+            .find(|series| series.key().eq_ignore_ascii_case(reference) || self.series_name(series).eq_ignore_ascii_case(reference))
+    }
 
-                        plot_title = format!("SDO - {} ({:#06X}:{})", field_name, address.index, address.sub_index);
+    /// Latest value of a channel, scaled
+    fn latest_value(&self, series: &PlotSeries) -> Option<f64> {
+        let (plot_data, _) = self.series_buffers(series)?;
+        let raw = plot_data.back()?[1];
+        Some(self.series_scaling(series).map_or(raw, |scaling| scaling.apply(raw)))
+    }
 
-                        // Add a title for the individual plot.
-                        ui.label(&plot_title);
-                        ui.separator();
+    /// Evaluate the derived channels using any of `sources`, which just got a sample
+    fn update_derived(&mut self, sources: &[PlotSeries], now: DateTime<Local>) {
+        if sources.is_empty() {
+            return;
+        }
+        let elapsed_seconds = timeline::offset_seconds(now, self.session_start);
+        let names: Vec<String> = self.derived_channels.keys().cloned().collect();
+        for name in names {
+            let Some(channel) = self.derived_channels.get(&name) else {
+                continue;
+            };
+            let inputs: Vec<Option<PlotSeries>> = channel.expression.channels().iter()
+                .map(|reference| self.resolve_channel(reference))
+                .collect();
+            if !inputs.iter().flatten().any(|series| sources.contains(series)) {
+                continue;
+            }
+            // Waits until every channel it uses has a value
+            let Some(values) = inputs.iter().map(|series| self.latest_value(series.as_ref()?)).collect::<Option<Vec<f64>>>() else {
+                continue;
+            };
+            let default_buffer = self.config.plot_buffer;
+            let Some(channel) = self.derived_channels.get_mut(&name) else {
+                continue;
+            };
+            let Some(value) = channel.expression.evaluate(elapsed_seconds, &values) else {
+                continue;
+            };
+            channel.last_value = Some(value);
+            channel.last_timestamp = Some(now);
+            channel.rate.record(now);
+            channel.plot_data.push_back([elapsed_seconds, value]);
+            channel.history.push([elapsed_seconds, value]);
+            channel.buffer.unwrap_or(default_buffer).trim(&mut channel.plot_data);
+            channel.stats.record(value);
+            let unit = channel.definition.unit.clone();
+            self.log_sample(&PlotSeries::Derived(name.clone()), now, value);
+            self.logger.log(LogEvent::DerivedData { name, value, unit });
+        }
+    }
 
-                        Plot::new(plot_id)
-                            .legend(egui_plot::Legend::default())
-                            .view_aspect(2.0)
-                            .allow_scroll(false)
-                            .height(350.0)
-                            .width(ui.available_width())
-                            .x_axis_label("Time (seconds)")
-                            .y_axis_label("Value")
-                            .legend(Legend::default())
-                            .show(ui, |plot_ui| {
-                                // 2. Generate a unique color for the line based on its address.
-                                let color = Color32::from_rgb(
-                                    (address.index as u8).wrapping_mul(20),
-                                    (address.sub_index as u8).wrapping_mul(40),
-                                    (address.index as u8 ^ address.sub_index as u8).wrapping_mul(30),
-                                );
+    /// Write a sample to the MDF4 log, describing the channel the first time
+    fn log_sample(&self, series: &PlotSeries, at: DateTime<Local>, raw: f64) {
+        self.logger.sample(&series.key(), at, raw, || self.log_channel(series));
+    }
 
-                                let points_vec: Vec<[f64; 2]> = subscription.plot_data.iter().cloned().collect();
+    /// Type an object is read as: the subscription's, else the one from the object dictionary
+    fn sdo_data_type(&self, address: &SdoAddress) -> Option<SdoDataType> {
+        self.subscriptions.get(address).map(|subscription| subscription.data_type.clone())
+            .or_else(|| self.object_dictionary.as_ref()
+                .and_then(|dict| dict.get(&address.index))
+                .and_then(|obj| obj.sub_objects.get(&address.sub_index))
+                .and_then(|sub_obj| SdoDataType::from_eds_type(&sub_obj.data_type)))
+    }
 
-                                let line = Line::new(PlotPoints::from(points_vec))
-                                    .name(&field_name)  // Use field name in legend (without hex address)
-                                    .color(color);
+    /// Name, unit, data type and scaling of a channel for the MDF4 log
+    fn log_channel(&self, series: &PlotSeries) -> MdfChannel {
+        let scaling = self.series_scaling(series);
+        let (data_type, comment) = match series {
+            PlotSeries::Sdo(address) => {
+                (self.sdo_data_type(address), format!("SDO 0x{:04X}:{:02X}", address.index, address.sub_index))
+            }
+            PlotSeries::Tpdo(field_id) => {
+                let mapped_object = self.discovered_tpdos.iter()
+                    .find(|config| config.tpdo_number == field_id.tpdo_number)
+                    .and_then(|config| config.mapped_objects.iter().find(|obj| obj.name == field_id.field_name));
+                let comment = mapped_object.map_or_else(
+                    || format!("TPDO {}", field_id.tpdo_number),
+                    |obj| format!("TPDO {}, object 0x{:04X}:{:02X}", field_id.tpdo_number, obj.index, obj.sub_index),
+                );
+                (mapped_object.map(|obj| obj.data_type.clone()), comment)
+            }
+            PlotSeries::Derived(name) => {
+                let comment = self.derived_channels.get(name).map(|channel| channel.definition.expression.clone()).unwrap_or_default();
+                (None, comment)
+            }
+        };
+        let unit = match series {
+            PlotSeries::Derived(name) => self.derived_channels.get(name).map(|channel| channel.definition.unit.clone()).unwrap_or_default(),
+            _ => scaling.as_ref().map(|scaling| scaling.unit.clone()).unwrap_or_default(),
+        };
+        MdfChannel { name: self.series_name(series), unit, data_type, scaling, comment }
+    }
 
-                                plot_ui.line(line);
-                            });
+    /// Add a derived channel, or replace one edited in the derived channels window
+    fn save_derived_channel(&mut self, previous: Option<String>, channel: DerivedChannel) {
+        let subscription = match DerivedSubscription::new(channel.clone(), &self.config.history) {
+            Ok(subscription) => subscription,
+            Err(e) => {
+                self.error_message = Some(format!("Invalid derived channel: {}", e));
+                return;
+            }
+        };
+        let index = previous.as_ref().and_then(|previous| self.config.derived_channels.iter().position(|existing| existing.name == *previous));
+        match index {
+            Some(index) => {
+                let old = std::mem::replace(&mut self.config.derived_channels[index], channel.clone());
+                self.derived_channels.remove(&old.name);
+                // A renamed channel stays on the dashboards it was plotted on
+                if old.name != channel.name {
+                    let old_series = PlotSeries::Derived(old.name);
+                    for dashboard in &mut self.config.dashboards {
+                        if dashboard.shows(&old_series) {
+                            dashboard.remove(&old_series);
+                            dashboard.add_derived(&channel.name);
+                        }
+                    }
+                }
+                self.derived_channels.insert(channel.name, subscription);
+                self.save_dashboards();
+            }
+            None => {
+                self.config.derived_channels.push(channel.clone());
+                self.derived_channels.insert(channel.name.clone(), subscription);
+                self.show_on_dashboard(&PlotSeries::Derived(channel.name));
+            }
+        }
+    }
 
-                        ui.horizontal(|ui| {
-                            if ui.button("📸 Capture Plot").clicked() {
-                                capture_clicked = true;
-                            }
+    fn remove_derived_channel(&mut self, name: &str) {
+        self.config.derived_channels.retain(|existing| existing.name != name);
+        self.derived_channels.remove(name);
+        let series = PlotSeries::Derived(name.to_string());
+        for dashboard in &mut self.config.dashboards {
+            dashboard.remove(&series);
+        }
+        self.save_dashboards();
+    }
 
-                            if ui.button("🗑 Clear").clicked() {
-                                addresses_to_clear.push(address.clone());
-                            }
+    fn draw_indicators(&mut self, ui: &mut egui::Ui) {
+        let series: Vec<Option<SeriesData>> = self.config.dashboard().indicators.iter()
+            .map(|indicator| self.plot_series(&indicator.series))
+            .collect();
+        if series.iter().all(Option::is_none) {
+            return;
+        }
 
-                            if ui.button("💾 Export to CSV").clicked() {
-                                addresses_to_export.push(address.clone());
+        let mut changed = false;
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.horizontal_wrapped(|ui| {
+                for (index, (indicator, data)) in self.config.dashboard_mut().indicators.iter_mut().zip(series).enumerate() {
+                    let Some(data) = data else {
+                        continue;
+                    };
+                    ui.push_id(index, |ui| {
+                        match indicator::show(ui, indicator, &data.name, data.last_raw) {
+                            Some(IndicatorAction::Changed) => changed = true,
+                            Some(IndicatorAction::AddBit) => {
+                                let next = IndicatorSettings {
+                                    bit: Some(indicator.bit.map_or(0, |bit| (bit + 1).min(63))),
+                                    label: String::new(),
+                                    ..indicator.clone()
+                                };
+                                added.push((index + 1, next));
                             }
-                        });
+                            Some(IndicatorAction::Remove) => removed.push(index),
+                            None => {}
+                        }
                     });
+                    ui.add_space(12.0);
+                }
+            });
+        });
 
-                    // Handle capture after we have the frame rect
-                    if capture_clicked {
-                        let now = Local::now();
-                        let timestamp = now.format("%Y-%m-%d %H:%M:%S");
-                        let info = ScreenshotInfo{
-                            filename: format!("{}_{}.png", plot_title.replace(":", "_"), timestamp),
-                            rect: frame_response.response.rect,
+        let dashboard = self.config.dashboard_mut();
+        for index in removed.iter().rev() {
+            dashboard.indicators.remove(*index);
+        }
+        for (index, indicator) in added.iter().rev() {
+            dashboard.indicators.insert((*index).min(dashboard.indicators.len()), indicator.clone());
+        }
+        if changed || !added.is_empty() || !removed.is_empty() {
+            self.save_dashboards();
+        }
+    }
+
+    /// Subscribe the entries of an array or record object and show them in one bar chart
+    fn add_bar_chart(&mut self, index: u16) {
+        let Some(object) = self.object_dictionary.as_ref().and_then(|dict| dict.get(&index)) else {
+            return;
+        };
+        let title = format!("{} ({:#06X})", object.name, index);
+        // Sub-index 0 holds the number of entries
+        let series: Vec<PlotSeries> = object.sub_objects.keys()
+            .filter(|sub_index| **sub_index != 0)
+            .map(|sub_index| PlotSeries::Sdo(SdoAddress { index, sub_index: *sub_index }))
+            .collect();
+        let series: Vec<PlotSeries> = series.into_iter().filter(|series| self.record_series(series)).collect();
+        if !series.is_empty() {
+            self.config.dashboard_mut().bar_charts.push(BarChartSettings::new(&title, series));
+            self.save_dashboards();
+        }
+    }
+
+    fn draw_bar_charts(&mut self, ui: &mut egui::Ui) {
+        let mut actions = Vec::new();
+        for (chart_index, chart) in self.config.dashboard().bar_charts.iter().enumerate() {
+            let series: Vec<Option<SeriesData>> = chart.series.iter().map(|series| self.plot_series(series)).collect();
+            if series.iter().all(Option::is_none) {
+                continue;
+            }
+            let value_label = series.iter().flatten().next().map_or_else(|| "Value".to_string(), |data| data.value_label.clone());
+            let bars: Vec<BarValue> = chart.series.iter().zip(series)
+                .map(|(plot_series, data)| match data {
+                    Some(data) => BarValue { name: data.name, value: data.points.last().map(|point| point[1]) },
+                    None => {
+                        let name = match plot_series {
+                            PlotSeries::Sdo(address) => format!("{:#06X}:{}", address.index, address.sub_index),
+                            PlotSeries::Tpdo(field_id) => field_id.field_name.clone(),
+                            PlotSeries::Derived(name) => name.clone(),
                         };
+                        BarValue { name, value: None }
+                    }
+                })
+                .collect();
+            if let Some(action) = bar_chart::show(ui, chart_index, chart, &bars, &value_label) {
+                actions.push((chart_index, action));
+            }
+        }
 
-                        let user_data = egui::UserData::new(Arc::new(info));
-                        ui.ctx().send_viewport_cmd(egui::ViewportCommand::Screenshot(user_data));
+        for (chart_index, action) in actions {
+            match action {
+                BarChartAction::Dropped(series) => {
+                    if self.record_series(&series) {
+                        if let Some(chart) = self.config.dashboard_mut().bar_charts.get_mut(chart_index) {
+                            chart.add(series);
+                        }
                     }
                 }
-
-                for address in addresses_to_clear {
-                    if let Some(subscription) = self.subscriptions.get_mut(&address) {
-                        subscription.start_time = Local::now();
-                        subscription.plot_data.clear();
+                BarChartAction::RemoveSeries(index) => {
+                    if let Some(chart) = self.config.dashboard_mut().bar_charts.get_mut(chart_index) {
+                        if index < chart.series.len() {
+                            chart.series.remove(index);
+                        }
                     }
+                    self.config.dashboard_mut().bar_charts.retain(|chart| !chart.series.is_empty());
                 }
-
-                for address in addresses_to_export {
-                    self.export_plot_data_to_csv(&address);
+                BarChartAction::Remove => {
+                    self.config.dashboard_mut().bar_charts.remove(chart_index);
                 }
+            }
+            self.save_dashboards();
+        }
+    }
 
-                // Draw TPDO field plots
-                let mut tpdo_fields_to_clear = Vec::new();
-                let mut tpdo_fields_to_export = Vec::new();
+    fn draw_xy_plots(&mut self, ui: &mut egui::Ui) {
+        let mut actions = Vec::new();
+        let mut xy_plots = self.config.dashboard().xy_plots.clone();
+        for (index, xy_plot) in xy_plots.iter_mut().enumerate() {
+            let (Some(x), Some(y)) = (self.plot_series(&xy_plot.x), self.plot_series(&xy_plot.y)) else {
+                continue;
+            };
+            if let Some(action) = xy_plot::show(
+                ui,
+                index,
+                xy_plot,
+                (&x.name, &x.value_label, &x.points),
+                (&y.name, &y.value_label, &y.points),
+            ) {
+                actions.push((index, action));
+            }
+        }
 
-                for (field_id, subscription) in &self.tpdo_field_subscriptions {
-                    let mut capture_clicked = false;
-                    let mut plot_title = String::new();
+        if actions.is_empty() {
+            return;
+        }
+        for (index, action) in actions.into_iter().rev() {
+            if let XyPlotAction::Remove = action {
+                xy_plots.remove(index);
+            }
+        }
+        self.config.dashboard_mut().xy_plots = xy_plots;
+        self.save_dashboards();
+    }
 
-                    let frame_response = egui::Frame::group(ui.style()).show(ui, |ui| {
-                        let plot_id = format!("tpdo_plot_{}_{}", field_id.tpdo_number, field_id.field_name);
-                        plot_title = format!("TPDO {} - {}", field_id.tpdo_number, field_id.field_name);
+    fn draw_histograms(&mut self, ui: &mut egui::Ui) {
+        let mut actions = Vec::new();
+        let mut histograms = self.config.dashboard().histograms.clone();
+        for (index, histogram) in histograms.iter_mut().enumerate() {
+            let Some(data) = self.plot_series(&histogram.series) else {
+                continue;
+            };
+            if let Some(action) = histogram::show(ui, index, histogram, &data.name, &data.value_label, &data.points) {
+                actions.push((index, action));
+            }
+        }
 
-                        ui.label(&plot_title);
-                        ui.separator();
+        if actions.is_empty() {
+            return;
+        }
+        for (index, action) in actions.into_iter().rev() {
+            if let HistogramAction::Remove = action {
+                histograms.remove(index);
+            }
+        }
+        self.config.dashboard_mut().histograms = histograms;
+        self.save_dashboards();
+    }
 
-                        Plot::new(plot_id)
-                            .legend(egui_plot::Legend::default())
-                            .view_aspect(2.0)
-                            .allow_scroll(false)
-                            .height(350.0)
-                            .width(ui.available_width())
-                            .x_axis_label("Time (seconds)")
-                            .y_axis_label("Value")
-                            .legend(Legend::default())
-                            .show(ui, |plot_ui| {
-                                // Generate a unique color for the line based on TPDO number and field name
-                                let hash = field_id.tpdo_number as u32 * 100 + field_id.field_name.len() as u32;
-                                let color = Color32::from_rgb(
-                                    ((hash * 37) % 256) as u8,
-                                    ((hash * 73) % 256) as u8,
-                                    ((hash * 151) % 256) as u8,
-                                );
+    fn draw_gauges(&mut self, ui: &mut egui::Ui) {
+        let series: Vec<Option<SeriesData>> = self.config.dashboard().gauges.iter()
+            .map(|gauge| self.plot_series(&gauge.series))
+            .collect();
+        if series.iter().all(Option::is_none) {
+            return;
+        }
 
-                                let points_vec: Vec<[f64; 2]> = subscription.plot_data.iter().cloned().collect();
+        let mut changed = false;
+        let mut removed = Vec::new();
+        ui.horizontal_wrapped(|ui| {
+            for (index, (gauge, data)) in self.config.dashboard_mut().gauges.iter_mut().zip(series).enumerate() {
+                // Not recorded until the dashboard's subscriptions are restored
+                let Some(data) = data else {
+                    continue;
+                };
+                let value = data.points.last().map(|point| point[1]);
+                ui.push_id(index, |ui| {
+                    match gauge::show(ui, gauge, &data.name, value, &data.unit) {
+                        Some(GaugeAction::Changed) => changed = true,
+                        Some(GaugeAction::Remove) => removed.push(index),
+                        None => {}
+                    }
+                });
+            }
+        });
 
-                                let line = Line::new(PlotPoints::from(points_vec))
-                                    .name(&plot_title)
-                                    .color(color);
+        let dashboard = self.config.dashboard_mut();
+        for index in removed.iter().rev() {
+            dashboard.gauges.remove(*index);
+        }
+        if changed || !removed.is_empty() {
+            self.save_dashboards();
+        }
+    }
 
-                                plot_ui.line(line);
-                            });
+    fn draw_overlays(&mut self, ui: &mut egui::Ui, drops: &mut Vec<(PlotSeries, DropTarget<PlotSeries>)>) {
+        // Series not recorded yet are left out until their subscription is restored
+        let mut removed_series = Vec::new();
+        let mut series_to_clear = Vec::new();
+        let mut overlays_to_split = Vec::new();
+        let mut y_axis_changes = Vec::new();
+        let mut svg_exports = Vec::new();
+        let mut follow_changes = Vec::new();
+
+        for (overlay_index, overlay) in self.config.dashboard().overlays.iter().enumerate() {
+            let series: Vec<_> = overlay.series.iter()
+                .filter_map(|series| Some((series, self.plot_series(series)?)))
+                .collect();
+            if series.is_empty() {
+                continue;
+            }
+            let mut capture_clicked = false;
 
-                        ui.horizontal(|ui| {
-                            if ui.button("📸 Capture Plot").clicked() {
-                                capture_clicked = true;
-                            }
+            let (frame_response, dropped) = ui.dnd_drop_zone::<PlotSeries, _>(egui::Frame::group(ui.style()), |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(&overlay.title);
+                    ui.separator();
+                    for (plot_series, data) in &series {
+                        if ui.small_button(format!("✖ {}", data.name)).on_hover_text("Back to its own plot").clicked() {
+                            removed_series.push((overlay_index, (*plot_series).clone()));
+                        }
+                    }
+                });
+                ui.separator();
 
-                            if ui.button("🗑 Clear").clicked() {
-                                tpdo_fields_to_clear.push(field_id.clone());
-                            }
+                // One axis label when all series share it, otherwise the legend tells them apart
+                let first_label = &series[0].1.value_label;
+                let y_label = if series.iter().all(|(_, data)| data.value_label == *first_label) { first_label.clone() } else { "Value".to_string() };
+                let plot_id = format!("overlay_plot_{}", overlay_index);
+                let follow_request = self.follow_live_requests.get(&plot_id).copied();
+                let plot = Plot::new(&plot_id)
+                    .legend(Legend::default())
+                    .view_aspect(2.0)
+                    .allow_scroll(false)
+                    .height(350.0)
+                    .width(ui.available_width());
+                let following = plot_axes(follow_live(plot, follow_request), self.session_start, self.config.plot_wall_clock, overlay.y_axis)
+                    .y_axis_label(y_label)
+                    .show(ui, |plot_ui| {
+                        let following = hold_view(plot_ui, follow_request);
+                        for (plot_series, data) in &series {
+                            let points = match self.series_buffers(plot_series) {
+                                Some((plot_data, history)) => plot_points(plot_ui, plot_data, history, self.series_scaling(plot_series).as_ref()),
+                                None => data.points.clone(),
+                            };
+                            let style = self.config.channel_style(plot_series).unwrap_or_default();
+                            plot_ui.line(style.apply(Line::new(PlotPoints::from(overlay.y_axis.transform(points))).name(&data.name)));
+                        }
+                        draw_markers(plot_ui, &self.timeline, self.session_start);
+                        following
+                    }).inner;
 
-                            if ui.button("💾 Export to CSV").clicked() {
-                                tpdo_fields_to_export.push(field_id.clone());
-                            }
-                        });
-                    });
+                ui.horizontal(|ui| {
+                    if let Some(follow) = follow_live_toggle(ui, following) {
+                        follow_changes.push((plot_id.clone(), follow));
+                    }
+                    if ui.button("📸 Capture Plot").clicked() {
+                        capture_clicked = true;
+                    }
+                    if ui.button("🖼 Export SVG").on_hover_text("Save the plot as a vector figure for reports").clicked() {
+                        svg_exports.push((overlay.title.clone(), overlay.series.clone(), overlay.y_axis));
+                    }
+                    if ui.button("🗑 Clear").clicked() {
+                        series_to_clear.extend(overlay.series.iter().cloned());
+                    }
+                    if ui.button("✂ Split").on_hover_text("Show the series in their own plots again").clicked() {
+                        overlays_to_split.push(overlay_index);
+                    }
+                    let mut axis = overlay.y_axis;
+                    let data_range = series.iter()
+                        .filter_map(|(_, data)| y_axis::value_range(&data.points))
+                        .reduce(|(min, max), (other_min, other_max)| (min.min(other_min), max.max(other_max)));
+                    // Converted only when all series share the unit
+                    let unit = if series.iter().all(|(_, data)| data.unit == series[0].1.unit) { series[0].1.unit.as_str() } else { "" };
+                    if y_axis::menu(ui, &mut axis, data_range, unit) {
+                        y_axis_changes.push((overlay_index, axis));
+                    }
+                });
+            });
 
-                    // Handle capture after we have the frame rect
-                    if capture_clicked {
-                        let now = Local::now();
-                        let timestamp = now.format("%Y-%m-%d %H:%M:%S");
-                        let info = ScreenshotInfo{
-                            filename: format!("{}_{}.png", plot_title.replace(":", "_").replace(" - ", "_"), timestamp),
-                            rect: frame_response.response.rect,
-                        };
+            if let Some(dropped) = dropped {
+                drops.push(((*dropped).clone(), DropTarget::Overlay(overlay_index)));
+            }
+            if capture_clicked {
+                let info = ScreenshotInfo {
+                    filename: format!("{}.png", overlay.title),
+                    rect: frame_response.response.rect,
+                };
+                ui.ctx().send_viewport_cmd(egui::ViewportCommand::Screenshot(egui::UserData::new(Arc::new(info))));
+            }
+        }
 
-                        let user_data = egui::UserData::new(Arc::new(info));
-                        ui.ctx().send_viewport_cmd(egui::ViewportCommand::Screenshot(user_data));
+        for (title, series, axis) in svg_exports {
+            self.export_plot_svg(&title, &series, axis);
+        }
+        self.follow_live_requests.retain(|plot_id, _| !plot_id.starts_with("overlay_plot_"));
+        self.follow_live_requests.extend(follow_changes);
+
+        let layout_changed = !removed_series.is_empty() || !overlays_to_split.is_empty() || !y_axis_changes.is_empty();
+        let dashboard = self.config.dashboard_mut();
+        for (overlay_index, axis) in y_axis_changes {
+            if let Some(overlay) = dashboard.overlays.get_mut(overlay_index) {
+                overlay.y_axis = axis;
+            }
+        }
+        for (overlay_index, series) in removed_series {
+            if let Some(overlay) = dashboard.overlays.get_mut(overlay_index) {
+                overlay.series.retain(|existing| *existing != series);
+            }
+        }
+        dashboard.overlays.retain(|overlay| !overlay.series.is_empty());
+        for overlay_index in overlays_to_split.into_iter().rev() {
+            dashboard.overlays.remove(overlay_index);
+        }
+        if layout_changed {
+            self.save_dashboards();
+        }
+        for series in series_to_clear {
+            match series {
+                PlotSeries::Sdo(address) => {
+                    if let Some(subscription) = self.subscriptions.get_mut(&address) {
+                        subscription.plot_data.clear();
+                        subscription.stats.reset();
+                        subscription.history.clear();
                     }
                 }
-
-                // Clear TPDO field plots
-                for field_id in tpdo_fields_to_clear {
+                PlotSeries::Tpdo(field_id) => {
                     if let Some(subscription) = self.tpdo_field_subscriptions.get_mut(&field_id) {
-                        subscription.start_time = Local::now();
                         subscription.plot_data.clear();
+                        subscription.stats.reset();
+                        subscription.history.clear();
                     }
                 }
-
-                // Export TPDO field plots
-                for field_id in tpdo_fields_to_export {
-                    self.export_tpdo_plot_data_to_csv(&field_id);
+                PlotSeries::Derived(name) => {
+                    if let Some(channel) = self.derived_channels.get_mut(&name) {
+                        channel.clear();
+                    }
                 }
             }
-        });
+        }
     }
 
     fn draw_subscription_management(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
             ui.heading("Active Subscriptions");
+            let has_subscriptions = !self.subscriptions.is_empty() || !self.active_tpdos.is_empty();
+            match self.preset_menu.show(ui, &self.config.presets, has_subscriptions) {
+                Some(PresetAction::Save(name)) => self.save_preset(&name),
+                Some(PresetAction::Apply(i)) => self.apply_preset(i),
+                Some(PresetAction::Delete(i)) => {
+                    self.config.presets.remove(i);
+                    if let Err(e) = self.config.save() {
+                        self.error_message = Some(format!("Failed to save presets: {}", e));
+                    }
+                }
+                None => {}
+            }
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 // Stop All button
                 let stop_all_enabled = !self.subscriptions.is_empty() || !self.active_tpdos.is_empty();
@@ -975,8 +4112,20 @@ impl MyApp {
                     }
                     self.subscriptions.clear();
                     self.active_tpdos.clear();
+                    self.tpdo_timing.clear();
+                    self.tpdo_last_seen.clear();
                     // Clear TPDO field subscriptions
                     self.tpdo_field_subscriptions.clear();
+                    let dashboard = self.config.dashboard_mut();
+                    dashboard.sdo.clear();
+                    dashboard.tpdo_fields.clear();
+                    dashboard.overlays.clear();
+                    dashboard.gauges.clear();
+                    dashboard.indicators.clear();
+                    dashboard.bar_charts.clear();
+                    dashboard.xy_plots.clear();
+                    dashboard.histograms.clear();
+                    self.save_dashboards();
                 }
 
                 // Subscription statistics
@@ -1016,9 +4165,13 @@ impl MyApp {
 
                         // Data rows - SDO subscriptions
                         let mut sdo_to_remove = Vec::new();
+                        let mut sdo_to_pause = Vec::new();
                         for (address, subscription) in &self.subscriptions {
                             // Status indicator with color
                             match &subscription.status {
+                                _ if subscription.paused => {
+                                    ui.colored_label(Color32::GRAY, "⏸ SDO");
+                                },
                                 SubscriptionStatus::Active => {
                                     ui.colored_label(Color32::from_rgb(0, 200, 0), "🟢 SDO");
                                 },
@@ -1041,10 +4194,16 @@ impl MyApp {
                             ui.label(format!("{} ms", subscription.interval_ms));
 
                             // Last value (truncate if too long)
+                            let scaling = effective_scaling(&self.config, self.object_dictionary.as_ref(), address);
+                            let radix = self.value_radix.entry(address.clone()).or_default();
                             let value_text = subscription.last_value.as_ref()
-                                .map(|v| if v.len() > 20 { format!("{}...", &v[..17]) } else { v.clone() })
+                                .map(|v| display_value(scaling.as_ref(), &self.value_descriptions, *radix, address, v, &subscription.data_type))
+                                .map(|v| if v.chars().count() > 20 { format!("{}...", v.chars().take(17).collect::<String>()) } else { v })
                                 .unwrap_or_else(|| "—".to_string());
-                            ui.label(value_text);
+                            ui.horizontal(|ui| {
+                                ui.label(value_text);
+                                radix_toggle(ui, radix, &subscription.data_type);
+                            });
 
                             // Last timestamp
                             let timestamp_text = subscription.last_timestamp.as_ref()
@@ -1052,13 +4211,23 @@ impl MyApp {
                                 .unwrap_or_else(|| "—".to_string());
                             ui.label(timestamp_text);
 
-                            // Actions (Stop button)
-                            if ui.button("🛑 Stop").clicked() {
-                                if let Some(tx) = &self.command_tx {
-                                    let _ = tx.send(Command::Unsubscribe(address.clone()));
+                            // Actions (Pause/Resume and Stop buttons)
+                            ui.horizontal(|ui| {
+                                let (label, hover) = if subscription.paused {
+                                    ("▶ Resume", "Poll this object again")
+                                } else {
+                                    ("⏸ Pause", "Stop polling this object but keep its plot")
+                                };
+                                if ui.button(label).on_hover_text(hover).clicked() {
+                                    sdo_to_pause.push((address.clone(), !subscription.paused));
                                 }
-                                sdo_to_remove.push(address.clone());
-                            }
+                                if ui.button("🛑 Stop").clicked() {
+                                    if let Some(tx) = &self.command_tx {
+                                        let _ = tx.send(Command::Unsubscribe(address.clone()));
+                                    }
+                                    sdo_to_remove.push(address.clone());
+                                }
+                            });
                             ui.end_row();
                         }
 
@@ -1066,7 +4235,15 @@ impl MyApp {
                         let mut tpdo_to_remove = Vec::new();
                         for tpdo_num in &self.active_tpdos.clone() {
                             // Status
-                            ui.colored_label(Color32::from_rgb(0, 200, 0), "🟢 TPDO");
+                            match self.tpdo_stale(*tpdo_num) {
+                                Some((silent, timeout)) => {
+                                    ui.colored_label(Color32::from_rgb(255, 165, 0), "⚠ Stale")
+                                        .on_hover_text(format!("No frame for {:.1} s (timeout {} ms)", silent.as_secs_f64(), timeout.as_millis()));
+                                }
+                                None => {
+                                    ui.colored_label(Color32::from_rgb(0, 200, 0), "🟢 TPDO");
+                                }
+                            }
 
                             // Address (TPDO number)
                             ui.label(format!("TPDO {}", tpdo_num));
@@ -1078,8 +4255,21 @@ impl MyApp {
                                 ui.label("—");
                             }
 
-                            // Interval (TPDOs are event-driven, not polled)
-                            ui.label("Event-driven");
+                            // Interval (TPDOs are event-driven, not polled): the measured period
+                            match self.tpdo_timing.get(tpdo_num) {
+                                Some(timing) => {
+                                    let text = format!("≈ {}", timing.period_text());
+                                    let label = if timing.missed() > 0 {
+                                        ui.colored_label(Color32::from_rgb(255, 165, 0), format!("{} ⚠", text))
+                                    } else {
+                                        ui.label(text)
+                                    };
+                                    label.on_hover_text(timing.summary());
+                                }
+                                None => {
+                                    ui.label("Event-driven");
+                                }
+                            }
 
                             // Last value - show summary of latest TPDO data
                             if let Some(latest) = self.tpdo_data.iter().rev().find(|t| t.tpdo_number == *tpdo_num) {
@@ -1113,19 +4303,125 @@ impl MyApp {
                             ui.end_row();
                         }
 
-                        // Remove stopped SDO subscriptions
-                        for address in sdo_to_remove {
-                            self.subscriptions.remove(&address);
-                        }
+                        // Pause or resume polling
+                        for (address, paused) in sdo_to_pause {
+                            if let Some(tx) = &self.command_tx {
+                                let _ = tx.send(Command::PauseSubscription { address: address.clone(), paused });
+                            }
+                            if let Some(subscription) = self.subscriptions.get_mut(&address) {
+                                subscription.paused = paused;
+                                subscription.status = SubscriptionStatus::Idle;
+                            }
+                        }
+
+                        // Remove stopped SDO subscriptions
+                        for address in sdo_to_remove {
+                            self.subscriptions.remove(&address);
+                            self.config.dashboard_mut().remove_sdo(&address);
+                            self.save_dashboards();
+                        }
+
+                        // Remove stopped TPDO subscriptions
+                        for tpdo_num in tpdo_to_remove {
+                            self.active_tpdos.remove(&tpdo_num);
+                            self.tpdo_timing.remove(&tpdo_num);
+                            self.tpdo_last_seen.remove(&tpdo_num);
+                            // Clear field subscriptions for this TPDO
+                            self.tpdo_field_subscriptions.retain(|field_id, _| field_id.tpdo_number != tpdo_num);
+                            self.config.dashboard_mut().remove_tpdo(tpdo_num);
+                            self.save_dashboards();
+                        }
+                    });
+            });
+        }
+    }
+
+    /// Start polling an SDO object and track it in the subscription list
+    fn subscribe(&mut self, address: SdoAddress, interval_ms: u64, data_type: SdoDataType) {
+        if let Some(tx) = &self.command_tx {
+            let _ = tx.send(Command::Subscribe {
+                address: address.clone(),
+                interval_ms,
+                data_type: data_type.clone(),
+            });
+        }
+        self.subscriptions.insert(address.clone(), SdoSubscription {
+            interval_ms,
+            plot_data: VecDeque::new(),
+            data_type,
+            last_value: None,
+            last_timestamp: None,
+            status: SubscriptionStatus::Idle,
+            paused: false,
+            rate: RateMeter::default(),
+            stats: ChannelStats::default(),
+            buffer: None,
+            history: SampleHistory::new(&self.config.history, &address.eds_key()),
+        });
+        self.show_on_dashboard(&PlotSeries::Sdo(address));
+    }
+
+    /// Store the current subscriptions as a preset, replacing one of the same name
+    fn save_preset(&mut self, name: &str) {
+        let sdo = self.subscriptions.iter()
+            .map(|(address, sub)| PresetSdo {
+                index: address.index,
+                sub_index: address.sub_index,
+                interval_ms: sub.interval_ms,
+                data_type: sub.data_type.clone(),
+            })
+            .collect();
+        let preset = SubscriptionPreset::capture(name, sdo, self.active_tpdos.iter().copied().collect());
+        match self.config.presets.iter_mut().find(|existing| existing.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => self.config.presets.push(preset),
+        }
+        match self.config.save() {
+            Ok(()) => self.preset_menu.set_status(Ok(format!("Saved preset '{}'", name))),
+            Err(e) => self.preset_menu.set_status(Err(format!("Failed to save presets: {}", e))),
+        }
+    }
+
+    /// Subscribe to everything in a preset that isn't running yet
+    fn apply_preset(&mut self, i: usize) {
+        let Some(preset) = self.config.presets.get(i).cloned() else {
+            return;
+        };
+        let Some(tx) = self.command_tx.clone() else {
+            self.preset_menu.set_status(Err("Not connected to CANopen network".to_string()));
+            return;
+        };
+
+        let mut started = 0;
+        for entry in &preset.sdo {
+            let address = entry.address();
+            if !self.subscriptions.contains_key(&address) {
+                self.subscribe(address, entry.interval_ms, entry.data_type.clone());
+                started += 1;
+            }
+        }
+
+        let mut missing_tpdos = Vec::new();
+        for tpdo_num in &preset.tpdos {
+            if self.active_tpdos.contains(tpdo_num) {
+                continue;
+            }
+            match self.discovered_tpdos.iter().find(|config| config.tpdo_number == *tpdo_num) {
+                Some(config) => {
+                    let _ = tx.send(Command::StartTpdoListener(config.clone()));
+                    self.active_tpdos.insert(*tpdo_num);
+                    started += 1;
+                }
+                None => missing_tpdos.push(format!("TPDO{}", tpdo_num)),
+            }
+        }
 
-                        // Remove stopped TPDO subscriptions
-                        for tpdo_num in tpdo_to_remove {
-                            self.active_tpdos.remove(&tpdo_num);
-                            // Clear field subscriptions for this TPDO
-                            self.tpdo_field_subscriptions.retain(|field_id, _| field_id.tpdo_number != tpdo_num);
-                        }
-                    });
-            });
+        if missing_tpdos.is_empty() {
+            self.preset_menu.set_status(Ok(format!("Applied '{}': {} subscriptions started", preset.name, started)));
+        } else {
+            self.preset_menu.set_status(Err(format!(
+                "Applied '{}', but {} not discovered on this node",
+                preset.name, missing_tpdos.join(", "))));
         }
     }
 
@@ -1145,6 +4441,8 @@ impl MyApp {
                                 tx.send(Command::Unsubscribe(address.clone())).unwrap();
                             }
                             self.subscriptions.remove(&address);
+                            self.config.dashboard_mut().remove_sdo(&address);
+                            self.save_dashboards();
                             self.modal_open_for = None; // Close the modal
                         }
                     } else {
@@ -1161,28 +4459,63 @@ impl MyApp {
                                     .and_then(|sub_obj| SdoDataType::from_eds_type(&sub_obj.data_type))
                                     .unwrap_or(SdoDataType::Real32);
 
-                                if let Some(tx) = &self.command_tx {
-                                    tx.send(Command::Subscribe {
-                                        address: address.clone(),
-                                        interval_ms,
-                                        data_type: data_type.clone(),
-                                    }).unwrap();
-                                }
-                                let now = Local::now();
-                                self.subscriptions.insert(address.clone(), SdoSubscription {
-                                    interval_ms,
-                                    plot_data: VecDeque::new(),
-                                    data_type,
-                                    last_value: None,
-                                    last_timestamp: None,
-                                    status: SubscriptionStatus::Idle,
-                                    paused: false,
-                                    start_time: now,
-                                });
+                                self.subscribe(address.clone(), interval_ms, data_type);
                                 self.modal_open_for = None; // Close the modal
                             }
                         }
                     }
+
+                    if self.modal_write_value.is_some() {
+                        ui.separator();
+                        self.draw_write_editor(ui, &address);
+                        if ui.button("〰 Stimulus...").on_hover_text("Write a value pattern to this object periodically").clicked() {
+                            let data_type = self.object_dictionary.as_ref()
+                                .and_then(|dict| dict.get(&address.index))
+                                .and_then(|obj| obj.sub_objects.get(&address.sub_index))
+                                .and_then(|sub_obj| SdoDataType::from_eds_type(&sub_obj.data_type))
+                                .unwrap_or(SdoDataType::Real32);
+                            self.stimulus.open_for(&address, data_type);
+                        }
+                    }
+
+                    ui.separator();
+                    self.draw_scaling_editor(ui, &address);
+
+                    ui.separator();
+
+                    // Per-node SDO timeout override (slow devices, e.g. in bootloader mode)
+                    let node_id = self.config.node_id;
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Node {} SDO timeout (ms):", node_id));
+                        ui.text_edit_singleline(&mut self.modal_timeout_str);
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.button("Apply Timeout").clicked() {
+                            if let Ok(timeout_ms) = self.modal_timeout_str.parse::<u64>() {
+                                if let Some(tx) = &self.command_tx {
+                                    let _ = tx.send(Command::SetSdoTimeout {
+                                        node_id,
+                                        timeout: Duration::from_millis(timeout_ms),
+                                    });
+                                }
+                                self.config.set_node_sdo_timeout(node_id, Some(timeout_ms));
+                                if let Err(e) = self.config.save() {
+                                    eprintln!("Failed to save configuration: {}", e);
+                                }
+                            }
+                        }
+                        if self.config.node_sdo_timeout(node_id).is_some() && ui.button("Use Default").clicked() {
+                            let timeout = self.config.sdo_timeout();
+                            if let Some(tx) = &self.command_tx {
+                                let _ = tx.send(Command::SetSdoTimeout { node_id, timeout });
+                            }
+                            self.config.set_node_sdo_timeout(node_id, None);
+                            self.modal_timeout_str = timeout.as_millis().to_string();
+                            if let Err(e) = self.config.save() {
+                                eprintln!("Failed to save configuration: {}", e);
+                            }
+                        }
+                    });
                 });
 
             // If the user closes the window with the 'X' button
@@ -1192,8 +4525,109 @@ impl MyApp {
         }
     }
 
+    /// Input widget matching the object's data type plus a "Write" button
+    fn draw_scaling_editor(&mut self, ui: &mut egui::Ui, address: &SdoAddress) {
+        ui.horizontal(|ui| {
+            ui.label("Scaling:");
+            ui.add(egui::DragValue::new(&mut self.modal_scaling.factor).speed(0.01).prefix("× "));
+            ui.add(egui::DragValue::new(&mut self.modal_scaling.offset).speed(0.1).prefix("+ "));
+            ui.add(egui::TextEdit::singleline(&mut self.modal_scaling.unit).hint_text("unit").desired_width(50.0));
+        });
+        ui.horizontal(|ui| {
+            let mut changed = false;
+            if ui.button("Apply Scaling").clicked() {
+                self.config.set_scaling(address, Some(self.modal_scaling.clone()));
+                changed = true;
+            }
+            if self.config.scaling(address).is_some() && ui.button("Use EDS Default").clicked() {
+                self.config.set_scaling(address, None);
+                self.modal_scaling = effective_scaling(&self.config, self.object_dictionary.as_ref(), address)
+                    .unwrap_or_default();
+                changed = true;
+            }
+            if changed {
+                if let Err(e) = self.config.save() {
+                    eprintln!("Failed to save configuration: {}", e);
+                }
+            }
+        });
+    }
+
+    fn draw_write_editor(&mut self, ui: &mut egui::Ui, address: &SdoAddress) {
+        let sub_object = self.object_dictionary.as_ref()
+            .and_then(|dict| dict.get(&address.index))
+            .and_then(|obj| obj.sub_objects.get(&address.sub_index));
+        let data_type = sub_object
+            .and_then(|sub_obj| SdoDataType::from_eds_type(&sub_obj.data_type))
+            .unwrap_or(SdoDataType::Real32);
+        let limits = sub_object
+            .map(|sub_obj| ValueLimits::from_eds(sub_obj.low_limit.as_deref(), sub_obj.high_limit.as_deref(), &data_type))
+            .unwrap_or_default();
+        let Some(value) = self.modal_write_value.as_mut() else {
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("Write value:");
+            sdo_write::value_input(ui, value, &data_type);
+
+            if !limits.is_empty() {
+                ui.weak(format!("Allowed: {}", limits));
+            }
+
+            let pending = matches!(self.modal_write_status, Some(WriteStatus::Pending));
+            if ui.add_enabled(!pending, egui::Button::new("Write")).clicked() {
+                match limits.check(value).and_then(|()| value.encode(&data_type)) {
+                    Ok(data) => {
+                        if let Some(tx) = &self.command_tx {
+                            let _ = tx.send(Command::WriteSdo { address: address.clone(), data });
+                            self.modal_write_status = Some(WriteStatus::Pending);
+                        }
+                    }
+                    Err(err) => self.modal_write_status = Some(WriteStatus::Failed(err)),
+                }
+            }
+        });
+
+        match &self.modal_write_status {
+            Some(WriteStatus::Pending) => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Writing...");
+                });
+            }
+            Some(WriteStatus::Written) => {
+                ui.colored_label(Color32::from_rgb(0, 200, 0), "✓ Written");
+            }
+            Some(WriteStatus::Failed(err)) => {
+                ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+            }
+            None => {}
+        }
+    }
+
+    /// File for a screenshot or export: in the capture directory when auto-saving, otherwise
+    /// chosen in a save dialog. Both get the capture time appended to `file_name`.
+    fn capture_path(&mut self, file_name: &str) -> Option<PathBuf> {
+        let now = Local::now();
+        if !self.config.auto_save_captures {
+            return rfd::FileDialog::new().set_file_name(capture::timestamped_name(file_name, now)).save_file();
+        }
+        let directory = self.config.get_capture_directory()?;
+        match capture::auto_save_path(&directory, file_name, now) {
+            Ok(path) => {
+                self.last_capture = Some(path.clone());
+                Some(path)
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to create capture directory: {}", e));
+                None
+            }
+        }
+    }
+
     fn save_screenshot(&mut self, image: &Arc<ColorImage>, info: &ScreenshotInfo) {
-        if let Some(path) = rfd::FileDialog::new().set_file_name(&info.filename).save_file() {
+        if let Some(path) = self.capture_path(&info.filename) {
             // Crop the full screenshot to the plot's rectangle
             let region = image.region(&info.rect, None);
 
@@ -1210,20 +4644,234 @@ impl MyApp {
         }
     }
 
+    /// Use another EDS (or re-read the current one) without reconnecting
+    ///
+    /// The file is assigned to the node, and the TPDO names are taken from it again.
+    /// SYNC producer toggle with period and counter settings
+    fn draw_sync_menu(&mut self, ui: &mut egui::Ui) {
+        let label = if self.sync_producing {
+            format!("⏱ SYNC {} ms", self.config.sync_period_ms)
+        } else {
+            "⏱ SYNC".to_string()
+        };
+        ui.menu_button(label, |ui| {
+            let mut changed = false;
+            ui.horizontal(|ui| {
+                ui.label("Period:");
+                changed |= ui.add(egui::DragValue::new(&mut self.config.sync_period_ms).range(1..=10_000).suffix(" ms")).changed();
+            });
+            ui.horizontal(|ui| {
+                ui.label("Counter overflow:");
+                changed |= ui.add(egui::DragValue::new(&mut self.config.sync_counter_overflow).range(0..=240))
+                    .on_hover_text("0 = SYNC without counter, 2-240 = counter wraps at this value (0x1019)")
+                    .changed();
+            });
+
+            let toggled = ui.add_enabled(self.connection_status, egui::Checkbox::new(&mut self.sync_producing, "Produce SYNC"))
+                .on_hover_text("Needed for synchronous TPDOs (transmission type 1-240) when no other SYNC producer is on the bus")
+                .changed();
+
+            let Some(tx) = &self.command_tx else {
+                return;
+            };
+            // New settings restart a running producer
+            if (toggled || changed) && self.sync_producing {
+                let _ = tx.send(Command::StartSync {
+                    period_ms: self.config.sync_period_ms,
+                    counter_overflow: self.config.sync_counter_overflow,
+                });
+            } else if toggled {
+                let _ = tx.send(Command::StopSync);
+            }
+            if toggled {
+                let _ = self.config.save();
+            }
+        });
+    }
+
+    fn draw_gateway_menu(&mut self, ui: &mut egui::Ui) {
+        let label = if self.gateway_running {
+            format!("🔌 Gateway :{} ({} clients)", self.config.gateway_port, self.gateway_clients)
+        } else {
+            "🔌 Gateway".to_string()
+        };
+        ui.menu_button(label, |ui| {
+            ui.add_enabled_ui(!self.gateway_running, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Port:");
+                    ui.add(egui::DragValue::new(&mut self.config.gateway_port).range(1024..=65535));
+                });
+                ui.checkbox(&mut self.config.gateway_local_only, "Local connections only")
+                    .on_hover_text("Listen on 127.0.0.1; otherwise any host on the network can access the bus");
+            });
+
+            let toggled = ui.add_enabled(self.connection_status, egui::Checkbox::new(&mut self.gateway_running, "Run CiA 309-3 ASCII gateway"))
+                .on_hover_text("Requests like \"[1] 4 read 0x2000 1 u32\", one per line")
+                .changed();
+            if !toggled {
+                return;
+            }
+            let Some(tx) = &self.command_tx else {
+                return;
+            };
+            if self.gateway_running {
+                let host = if self.config.gateway_local_only { "127.0.0.1" } else { "0.0.0.0" };
+                let _ = tx.send(Command::StartGateway(format!("{}:{}", host, self.config.gateway_port)));
+            } else {
+                let _ = tx.send(Command::StopGateway);
+                self.gateway_clients = 0;
+            }
+            if let Err(e) = self.config.save() {
+                eprintln!("Failed to save configuration: {}", e);
+            }
+        });
+    }
+
+    fn load_eds(&mut self, path: Option<PathBuf>) {
+        let Some(tx) = &self.command_tx else {
+            return;
+        };
+        let _ = tx.send(Command::LoadEds(path.clone()));
+        if self.tpdo_discovery_requested && self.connection_status {
+            let _ = tx.send(Command::RediscoverTpdos);
+            self.discovered_tpdos.clear();
+        } else {
+            // Discovery runs once the new object list arrives
+            self.tpdo_discovery_requested = false;
+            self.config.tpdo_layouts.remove(&self.config.node_id.to_string());
+        }
+
+        self.config.eds_file_path = path.as_ref().map(|p| p.display().to_string());
+        self.config.set_node_eds_file(self.config.node_id, path.as_deref());
+        if let Err(e) = self.config.save() {
+            eprintln!("Failed to save configuration: {}", e);
+        }
+        self.eds_file_path = path;
+    }
+
+    /// The monitored node rebooted and lost its runtime configuration: write the boot DCF,
+    /// if one is assigned, then read the TPDO layout again and restart the subscriptions
+    fn node_booted(&mut self) {
+        let node_id = self.config.node_id;
+        println!("Node {} booted, reconfiguring", node_id);
+        if self.config.manager_mode {
+            // The startup sequence writes the DCF and starts the node
+            if self.run_startup() {
+                self.boot_replay_pending = true;
+                return;
+            }
+        }
+        if let Some(path) = self.config.boot_dcf_file(node_id) {
+            match dcf::load(&path, node_id) {
+                Ok(entries) => {
+                    if let Some(tx) = &self.command_tx {
+                        let entries = backup::restore_order(&entries);
+                        println!("Writing {} values of boot DCF {:?}", entries.len(), path);
+                        let _ = tx.send(Command::RestoreParameters { node_id, entries });
+                        self.boot_replay_pending = true;
+                        return;
+                    }
+                }
+                Err(e) => {
+                    self.error_message = Some(format!("Failed to load boot DCF {}: {}", path.display(), e));
+                }
+            }
+        }
+        self.restart_after_boot();
+    }
+
+    /// Send the startup sequence to the communication thread; returns whether it was started
+    fn run_startup(&mut self) -> bool {
+        let node_id = self.config.node_id;
+        let dcf_entries = match self.config.boot_dcf_file(node_id) {
+            Some(path) => match dcf::load(&path, node_id) {
+                Ok(entries) => backup::restore_order(&entries),
+                Err(e) => {
+                    self.error_message = Some(format!("Startup sequence not run, failed to load DCF {}: {}", path.display(), e));
+                    return false;
+                }
+            },
+            None => Vec::new(),
+        };
+        let plan = StartupPlan {
+            identity: self.config.expected_identity(node_id),
+            dcf_entries,
+            consumer_time: self.config.heartbeat_consumer_time(node_id),
+        };
+        let Some(tx) = &self.command_tx else {
+            return false;
+        };
+        let _ = tx.send(Command::RunStartup(plan));
+        self.startup_panel.start();
+        true
+    }
+
+    fn restart_after_boot(&mut self) {
+        let Some(tx) = &self.command_tx else {
+            return;
+        };
+        if self.tpdo_discovery_requested {
+            let _ = tx.send(Command::RediscoverTpdos);
+            self.discovered_tpdos.clear();
+        }
+        // Paused subscriptions aren't polling and pick up the node when resumed
+        for (address, subscription) in self.subscriptions.iter_mut().filter(|(_, sub)| !sub.paused) {
+            let _ = tx.send(Command::Unsubscribe(address.clone()));
+            let _ = tx.send(Command::Subscribe {
+                address: address.clone(),
+                interval_ms: subscription.interval_ms,
+                data_type: subscription.data_type.clone(),
+            });
+            subscription.status = SubscriptionStatus::Idle;
+        }
+    }
+
+    fn save_dcf(&mut self, entries: &[snapshot::SnapshotEntry]) {
+        let Some(eds_path) = self.eds_file_path.clone() else {
+            return;
+        };
+        let file_name = format!("node{}_{}.dcf", self.config.node_id, Local::now().format("%Y-%m-%d_%H-%M-%S"));
+        if let Some(path) = rfd::FileDialog::new().add_filter("CANopen DCF", &["dcf"]).set_file_name(&file_name).save_file() {
+            match dcf::save(&eds_path, &path, entries, self.config.node_id, self.config.bus_bitrate) {
+                Ok(()) => println!("✓ Saved DCF of node {} to {:?}", self.config.node_id, path),
+                Err(e) => self.error_message = Some(format!("Failed to save DCF: {}", e)),
+            }
+        }
+    }
+
+    fn save_snapshot(&mut self, entries: &[snapshot::SnapshotEntry]) {
+        let file_name = format!("snapshot_node{}_{}.csv", self.config.node_id, Local::now().format("%Y-%m-%d_%H-%M-%S"));
+        if let Some(path) = rfd::FileDialog::new().set_file_name(&file_name).save_file() {
+            match snapshot::save(&path, entries) {
+                Ok(()) => println!("✓ Saved snapshot of {} objects to {:?}", entries.len(), path),
+                Err(e) => self.error_message = Some(format!("Failed to save snapshot: {}", e)),
+            }
+        }
+    }
+
     fn export_plot_data_to_csv(&mut self, address: &SdoAddress) {
+        if !self.subscriptions.contains_key(address) {
+            return;
+        }
+        let file_name = format!("plot_data_{:04X}_{:02X}.csv", address.index, address.sub_index);
+        let path = self.capture_path(&file_name);
         if let Some(subscription) = self.subscriptions.get(address) {
-            let file_name = format!("plot_data_{:04X}_{:02X}.csv", address.index, address.sub_index);
-            if let Some(path) = rfd::FileDialog::new().set_file_name(&file_name).save_file() {
+            let scaling = effective_scaling(&self.config, self.object_dictionary.as_ref(), address);
+            if let Some(path) = path {
                 match csv::Writer::from_path(path) {
                     Ok(mut writer) => {
                         // Write header
-                        if let Err(e) = writer.write_record(&["Sample No", "Value"]) {
+                        let value_label = scaling.as_ref().map_or_else(|| "Value".to_string(), Scaling::value_label);
+                        if let Err(e) = writer.write_record(["Time (seconds)", value_label.as_str(), "Statistic", "Result", "Marker"]) {
                             eprintln!("Failed to write CSV header: {}", e);
                         }
 
-                        // Write data
-                        for point in &subscription.plot_data {
-                            if let Err(e) = writer.write_record(&[point[0].to_string(), point[1].to_string()]) {
+                        // Write data, with the statistics alongside the first rows and the markers where they were set
+                        let stats = subscription.stats.scaled(scaling.as_ref()).rows(subscription.rate.rate(Local::now()));
+                        let markers = marker_offsets(&self.timeline, self.session_start);
+                        let points = full_history(&subscription.history, &subscription.plot_data);
+                        for record in csv_records(&scaled_points(&points, scaling.as_ref()), &stats, &markers) {
+                            if let Err(e) = writer.write_record(&record) {
                                 eprintln!("Failed to write CSV record: {}", e);
                             }
                         }
@@ -1240,20 +4888,175 @@ impl MyApp {
         }
     }
 
+    /// Write every subscribed channel into one CSV with a shared time column
+    /// Every recorded channel with its history, named with the unit
+    fn session_channels(&self) -> Vec<ExportChannel> {
+        let mut channels: Vec<ExportChannel> = self.recorded_series().into_iter()
+            .filter_map(|series| self.series_history(&series))
+            .map(|data| ExportChannel {
+                name: if data.unit.is_empty() { data.name } else { format!("{} ({})", data.name, data.unit) },
+                points: data.points,
+            })
+            .collect();
+        channels.sort_by(|a, b| a.name.cmp(&b.name));
+        channels
+    }
+
+    fn export_session(&mut self, alignment: Alignment) {
+        let channels = self.session_channels();
+        let Some(path) = self.capture_path("session.csv") else {
+            return;
+        };
+        if let Err(e) = session_export::write(&path, self.session_start, &channels, alignment) {
+            self.error_message = Some(format!("Failed to export session: {}", e));
+        }
+    }
+
+    fn export_session_parquet(&mut self) {
+        let channels = self.session_channels();
+        let Some(path) = self.capture_path("session.parquet") else {
+            return;
+        };
+        if let Err(e) = parquet_export::write(&path, self.session_start, &channels) {
+            self.error_message = Some(format!("Failed to export session: {}", e));
+        }
+    }
+
+    /// Plots of the active dashboard as title, series and Y axis, overlays first
+    fn report_plots(&self) -> Vec<(String, Vec<PlotSeries>, YAxisSettings)> {
+        let dashboard = self.config.dashboard();
+        let overlays = dashboard.overlays.iter()
+            .filter(|overlay| overlay.series.iter().any(|series| self.series_buffers(series).is_some()))
+            .map(|overlay| (overlay.title.clone(), overlay.series.clone(), overlay.y_axis));
+        let single = self.recorded_series().into_iter()
+            .filter(|series| dashboard.shows(series) && !dashboard.plot_replaced(series) && !dashboard.overlays.iter().any(|overlay| overlay.contains(series)))
+            .filter_map(|series| Some((self.plot_series(&series)?.name, vec![series.clone()], dashboard.y_axis(&series))));
+        overlays.chain(single).collect()
+    }
+
+    /// Write the PDF test report with the plots chosen in the report window
+    fn generate_report(&mut self) {
+        let now = Local::now();
+        let mut details = vec![
+            ("Generated".to_string(), now.format("%Y-%m-%d %H:%M:%S").to_string()),
+            ("Session start".to_string(), self.session_start.format("%Y-%m-%d %H:%M:%S").to_string()),
+            ("Duration".to_string(), format!("{:.0} s", timeline::offset_seconds(now, self.session_start))),
+            ("Interface".to_string(), interface_label(&self.config.can_interface).to_string()),
+            ("Node ID".to_string(), self.config.node_id.to_string()),
+            ("EDS file".to_string(), self.eds_file_path.as_ref().map_or_else(|| "-".to_string(), |path| path.display().to_string())),
+        ];
+        if let Some(path) = &self.eds_file_path {
+            details.extend(report::device_info(path));
+        }
+        details.extend(self.device_check.results().iter()
+            .map(|(check, outcome)| (check.label().to_string(), format!("{}: {}", outcome.verdict(), outcome.detail()))));
+
+        let plots = self.report_plots().into_iter()
+            .filter(|(title, _, _)| self.report_panel.includes(title))
+            .filter_map(|(title, series, y_axis)| self.figure(&title, &series, y_axis))
+            .collect();
+        let mut channels: Vec<ReportChannel> = self.subscriptions.iter()
+            .map(|(address, subscription)| (PlotSeries::Sdo(address.clone()), &subscription.stats, subscription.rate.rate(now)))
+            .chain(self.tpdo_field_subscriptions.iter()
+                .map(|(field_id, subscription)| (PlotSeries::Tpdo(field_id.clone()), &subscription.stats, subscription.rate.rate(now))))
+            .chain(self.derived_channels.iter()
+                .map(|(name, channel)| (PlotSeries::Derived(name.clone()), &channel.stats, channel.rate.rate(now))))
+            .filter_map(|(series, stats, rate)| {
+                let data = self.plot_series(&series)?;
+                Some(ReportChannel {
+                    name: if data.unit.is_empty() { data.name } else { format!("{} ({})", data.name, data.unit) },
+                    stats: stats.scaled(self.series_scaling(&series).as_ref()).rows(rate),
+                })
+            })
+            .collect();
+        channels.sort_by(|a, b| a.name.cmp(&b.name));
+        let events = self.timeline.events()
+            .filter(|event| matches!(event.kind, EventKind::Alarm | EventKind::Emergency))
+            .map(|event| [event.at.format("%Y-%m-%d %H:%M:%S").to_string(), event.kind.label().to_string(), event.text.clone()])
+            .collect();
+
+        let report = Report { title: self.report_panel.title.trim().to_string(), details, plots, channels, events };
+        let Some(path) = self.capture_path(&format!("{}.pdf", report.title)) else {
+            return;
+        };
+        let result = report::save(&path, &report).map(|()| path).map_err(|e| format!("Failed to write report: {}", e));
+        self.report_panel.finished(result);
+    }
+
+    /// Draw the series of a plot into an SVG file chosen by the user
+    fn export_plot_svg(&mut self, title: &str, series: &[PlotSeries], y_axis: YAxisSettings) {
+        let Some(plot) = self.figure(title, series, y_axis) else {
+            return;
+        };
+        if let Some(path) = self.capture_path(&format!("{}.svg", title)) {
+            if let Err(e) = svg_export::save(&path, &plot) {
+                eprintln!("Failed to write SVG file: {}", e);
+            }
+        }
+    }
+
+    /// A plot with the full history of its series, laid out for SVG or PDF output
+    fn figure(&self, title: &str, series: &[PlotSeries], y_axis: YAxisSettings) -> Option<SvgPlot> {
+        let data: Vec<(&PlotSeries, SeriesData)> = series.iter()
+            .filter_map(|series| Some((series, self.series_history(series)?)))
+            .collect();
+        let (_, first) = data.first()?;
+        let y_label = if data.iter().all(|(_, series)| series.value_label == first.value_label) { first.value_label.clone() } else { "Value".to_string() };
+
+        let (epoch, wall_clock) = (self.session_start, self.config.plot_wall_clock);
+        let x_format = move |seconds: f64| if wall_clock { timeline::clock_time(epoch, seconds, "%H:%M:%S") } else { y_axis::format_value(seconds) };
+        let y_format = move |position: f64| y_axis::format_value(y_axis.value(position));
+        let y_range = match y_axis.range {
+            YRange::Fixed { min, max } => y_axis.position(min).zip(y_axis.position(max)),
+            YRange::Auto { .. } => None,
+        };
+        Some(SvgPlot {
+            title: title.to_string(),
+            x_label: if wall_clock { "Local time" } else { "Time (seconds)" }.to_string(),
+            y_label,
+            series: data.into_iter()
+                .map(|(series, data)| SvgSeries {
+                    name: data.name,
+                    style: self.config.channel_style(series).unwrap_or_default(),
+                    points: y_axis.transform(data.points),
+                })
+                .collect(),
+            markers: marker_offsets(&self.timeline, self.session_start),
+            y_range,
+            x_format: Box::new(x_format),
+            y_format: Box::new(y_format),
+        })
+    }
+
     fn export_tpdo_plot_data_to_csv(&mut self, field_id: &TpdoFieldId) {
+        if !self.tpdo_field_subscriptions.contains_key(field_id) {
+            return;
+        }
+        let file_name = format!("plot_data_tpdo{}_{}.csv", field_id.tpdo_number, field_id.field_name);
+        let path = self.capture_path(&file_name);
         if let Some(subscription) = self.tpdo_field_subscriptions.get(field_id) {
-            let file_name = format!("plot_data_tpdo{}_{}.csv", field_id.tpdo_number, field_id.field_name);
-            if let Some(path) = rfd::FileDialog::new().set_file_name(&file_name).save_file() {
+            let scaling = self.discovered_tpdos.iter()
+                .find(|config| config.tpdo_number == field_id.tpdo_number)
+                .and_then(|config| config.mapped_objects.iter().find(|obj| obj.name == field_id.field_name))
+                .and_then(|obj| {
+                    let address = SdoAddress { index: obj.index, sub_index: obj.sub_index };
+                    effective_scaling(&self.config, self.object_dictionary.as_ref(), &address)
+                });
+            if let Some(path) = path {
                 match csv::Writer::from_path(path) {
                     Ok(mut writer) => {
                         // Write header
-                        if let Err(e) = writer.write_record(&["Time (seconds)", "Value"]) {
+                        let value_label = scaling.as_ref().map_or_else(|| "Value".to_string(), Scaling::value_label);
+                        if let Err(e) = writer.write_record(["Time (seconds)", value_label.as_str(), "Statistic", "Result", "Marker"]) {
                             eprintln!("Failed to write CSV header: {}", e);
                         }
 
-                        // Write data
-                        for point in &subscription.plot_data {
-                            if let Err(e) = writer.write_record(&[point[0].to_string(), point[1].to_string()]) {
+                        // Write data, with the statistics alongside the first rows and the markers where they were set
+                        let stats = subscription.stats.scaled(scaling.as_ref()).rows(subscription.rate.rate(Local::now()));
+                        let markers = marker_offsets(&self.timeline, self.session_start);
+                        let points = full_history(&subscription.history, &subscription.plot_data);
+                        for record in csv_records(&scaled_points(&points, scaling.as_ref()), &stats, &markers) {
+                            if let Err(e) = writer.write_record(&record) {
                                 eprintln!("Failed to write CSV record: {}", e);
                             }
                         }
@@ -1270,6 +5073,39 @@ impl MyApp {
         }
     }
 
+    fn export_derived_plot_data_to_csv(&mut self, name: &str) {
+        if !self.derived_channels.contains_key(name) {
+            return;
+        }
+        let file_name = format!("plot_data_derived_{}.csv", name);
+        let path = self.capture_path(&file_name);
+        if let (Some(channel), Some(path)) = (self.derived_channels.get(name), path) {
+            match csv::Writer::from_path(path) {
+                Ok(mut writer) => {
+                    if let Err(e) = writer.write_record(["Time (seconds)", channel.definition.value_label().as_str(), "Statistic", "Result", "Marker"]) {
+                        eprintln!("Failed to write CSV header: {}", e);
+                    }
+
+                    let stats = channel.stats.rows(channel.rate.rate(Local::now()));
+                    let markers = marker_offsets(&self.timeline, self.session_start);
+                    let points = full_history(&channel.history, &channel.plot_data);
+                    for record in csv_records(&points, &stats, &markers) {
+                        if let Err(e) = writer.write_record(&record) {
+                            eprintln!("Failed to write CSV record: {}", e);
+                        }
+                    }
+
+                    if let Err(e) = writer.flush() {
+                        eprintln!("Failed to flush CSV file: {}", e);
+                    }
+                },
+                Err(e) => {
+                    eprintln!("Failed to create CSV file: {}", e);
+                }
+            }
+        }
+    }
+
     fn draw_about_dialog(&mut self, ui: &mut egui::Ui) {
         if self.show_about_dialog {
             let mut is_open = true;
@@ -1337,17 +5173,17 @@ impl MyApp {
 
 fn get_can_interfaces() -> Vec<String> {
     let output = match process_command::new("ip").arg("link").arg("show").output() {
-        Ok(output) => output,
+        Ok(output) => output.stdout,
         Err(_) => {
-            // If the command fails (e.g., on Windows), return an empty list.
-            return Vec::new();
+            // If the command fails (e.g., on Windows), only USB adapters can be listed.
+            Vec::new()
         }
     };
 
-    let output_str = String::from_utf8_lossy(&output.stdout);
+    let output_str = String::from_utf8_lossy(&output);
 
     // Parse the output to find lines containing "can"
-    output_str
+    let mut interfaces: Vec<String> = output_str
         .lines()
         .filter_map(|line| {
             if line.contains(": can") {
@@ -1357,6 +5193,269 @@ fn get_can_interfaces() -> Vec<String> {
                 None
             }
         })
+        .collect();
+
+    // candleLight/gs_usb adapters driven through libusb (no kernel driver needed)
+    interfaces.extend((0..canopen::gs_usb::list_devices().len()).map(|index| format!("gs_usb://{}", index)));
+
+    // Built-in mock nodes, always available
+    interfaces.push(canopen::simulation::INTERFACE.to_string());
+    interfaces
+}
+
+/// Name shown in the interface selector
+fn interface_label(interface: &str) -> &str {
+    if interface == canopen::simulation::INTERFACE {
+        "Simulation"
+    } else {
+        interface
+    }
+}
+
+/// Favorites are kept per EDS file, or per node ID for devices without one
+fn favorites_key(eds_file: Option<&PathBuf>, node_id: u8) -> String {
+    eds_file.and_then(|path| path.file_name())
+        .map_or_else(|| format!("node{}", node_id), |name| name.to_string_lossy().to_string())
+}
+
+/// Scaling in effect for an object: the user's from the config, else the EDS default
+fn effective_scaling(config: &AppConfig, objects: Option<&BTreeMap<u16, SdoObject>>, address: &SdoAddress) -> Option<Scaling> {
+    config.scaling(address).cloned()
+        .or_else(|| objects?.get(&address.index)?.sub_objects.get(&address.sub_index)?.scaling.clone())
+        .filter(|scaling| !scaling.is_identity())
+}
+
+/// Value as shown next to an object: converted with unit when scaled, else named or in the chosen radix
+fn display_value(
+    scaling: Option<&Scaling>,
+    descriptions: &ValueDescriptions,
+    radix: Radix,
+    address: &SdoAddress,
+    value: &str,
+    data_type: &SdoDataType,
+) -> String {
+    match scaling.and_then(|scaling| scaling.format(value)) {
+        Some(converted) => converted,
+        None => descriptions.label(address, value, radix.format(value, data_type)),
+    }
+}
+
+/// Value for the watch table: like [`display_value`], but the unit has its own column
+fn watch_value(
+    scaling: Option<&Scaling>,
+    descriptions: &ValueDescriptions,
+    radix: Radix,
+    address: &SdoAddress,
+    value: &str,
+    data_type: &SdoDataType,
+) -> String {
+    match scaling.and_then(|scaling| scaling.convert(value)) {
+        Some(converted) => converted,
+        None => descriptions.label(address, value, radix.format(value, data_type)),
+    }
+}
+
+/// "Overlay" menu of a single plot: add its series to an existing overlay or a new one
+fn overlay_menu(
+    ui: &mut egui::Ui,
+    overlays: &[OverlayPlot<PlotSeries>],
+    series: PlotSeries,
+    additions: &mut Vec<(Option<usize>, PlotSeries)>,
+) {
+    ui.menu_button("📈 Overlay", |ui| {
+        for (index, overlay) in overlays.iter().enumerate() {
+            if ui.button(format!("Add to {}", overlay.title)).clicked() {
+                additions.push((Some(index), series.clone()));
+                ui.close_menu();
+            }
+        }
+        if ui.button("New overlay plot").clicked() {
+            additions.push((None, series.clone()));
+            ui.close_menu();
+        }
+    });
+}
+
+enum BufferChange {
+    /// Own history of the plot, or the default again
+    Set(Option<PlotBufferConfig>),
+    /// The plot's settings become the default of all plots without their own
+    MakeDefault(PlotBufferConfig),
+}
+
+/// "Buffer" menu of a single plot: how many samples or seconds it keeps
+fn buffer_menu(ui: &mut egui::Ui, buffer: Option<PlotBufferConfig>, default: PlotBufferConfig) -> Option<BufferChange> {
+    let mut change = None;
+    ui.menu_button("⏱ Buffer", |ui| {
+        let mut own = buffer.is_some();
+        let mut settings = buffer.unwrap_or(default);
+        if ui.checkbox(&mut own, "Own setting for this plot").changed() {
+            change = Some(BufferChange::Set(own.then_some(settings)));
+        }
+        ui.add_enabled_ui(own, |ui| {
+            egui::Grid::new("plot_buffer_settings").num_columns(2).show(ui, |ui| {
+                ui.label("Samples:");
+                let samples = ui.add(egui::DragValue::new(&mut settings.max_samples).range(10..=1_000_000).speed(10.0));
+                ui.end_row();
+                ui.label("Time window:");
+                let window = ui.add(egui::DragValue::new(&mut settings.window_seconds).range(0.0..=86_400.0).suffix(" s"))
+                    .on_hover_text("0 keeps samples regardless of their age");
+                ui.end_row();
+                if samples.changed() || window.changed() {
+                    change = Some(BufferChange::Set(Some(settings)));
+                }
+            });
+            if ui.button("Use as default for all plots").clicked() {
+                change = Some(BufferChange::MakeDefault(settings));
+                ui.close_menu();
+            }
+        });
+        ui.weak(format!("Default: {} samples{}", default.max_samples,
+            if default.window_seconds > 0.0 { format!(", {} s", default.window_seconds) } else { String::new() }));
+    });
+    change
+}
+
+/// "XY" menu of a single plot: plot its series over another one of the dashboard
+fn xy_menu(
+    ui: &mut egui::Ui,
+    candidates: &[(PlotSeries, String)],
+    series: PlotSeries,
+    additions: &mut Vec<(PlotSeries, PlotSeries)>,
+) {
+    ui.menu_button("⤢ XY", |ui| {
+        let mut any = false;
+        for (other, name) in candidates.iter().filter(|(other, _)| *other != series) {
+            any = true;
+            if ui.button(format!("Over {}", name)).clicked() {
+                additions.push((other.clone(), series.clone()));
+                ui.close_menu();
+            }
+        }
+        if !any {
+            ui.label("No other series on this dashboard");
+        }
+    });
+}
+
+/// Timeline markers as vertical lines in a plot timed from `start`
+fn draw_markers(plot_ui: &mut egui_plot::PlotUi, timeline: &Timeline, start: DateTime<Local>) {
+    for (offset, text) in marker_offsets(timeline, start) {
+        plot_ui.vline(
+            VLine::new(offset)
+                .color(Color32::from_rgb(100, 149, 237))
+                .style(egui_plot::LineStyle::dashed_loose())
+                .name(text),
+        );
+    }
+}
+
+/// Time axis, Y axis settings and hover label of a plot of recorded series
+fn plot_axes(plot: Plot<'_>, epoch: DateTime<Local>, wall_clock: bool, y_axis: YAxisSettings) -> Plot<'_> {
+    y_axis.apply(timeline::time_axis(plot, epoch, wall_clock))
+        .label_formatter(move |name, point| {
+            let time = timeline::time_text(epoch, point.x, wall_clock);
+            let value = y_axis.hover_text(point.y);
+            if name.is_empty() { format!("{}\n{}", time, value) } else { format!("{}\n{}\n{}", name, time, value) }
+        })
+}
+
+/// A plot asked to follow the live samples again starts over with automatic bounds
+fn follow_live(plot: Plot<'_>, request: Option<bool>) -> Plot<'_> {
+    if request == Some(true) { plot.reset() } else { plot }
+}
+
+/// Keeps the current view of a plot asked to stop following; returns whether the plot
+/// follows the live samples, which ends once the user pans or zooms
+fn hold_view(plot_ui: &mut egui_plot::PlotUi, request: Option<bool>) -> bool {
+    if request == Some(false) {
+        plot_ui.set_plot_bounds(plot_ui.plot_bounds());
+        return false;
+    }
+    plot_ui.auto_bounds().x
+}
+
+/// "Follow live" toggle under a plot; returns the new state when clicked
+fn follow_live_toggle(ui: &mut egui::Ui, following: bool) -> Option<bool> {
+    let mut follow = following;
+    let hint = if following { "Hold the current view to scroll back through the history" } else { "Snap back to the newest samples" };
+    let changed = ui.toggle_value(&mut follow, "⏩ Follow live").on_hover_text(hint).changed();
+    if changed {
+        ui.ctx().request_repaint();
+    }
+    changed.then_some(follow)
+}
+
+/// Statistics of a channel under its plot; returns true when the user resets them
+fn stats_line(ui: &mut egui::Ui, stats: &ChannelStats, rate: f64) -> bool {
+    ui.horizontal_wrapped(|ui| {
+        for (label, value) in stats.rows(rate) {
+            ui.weak(format!("{}:", label));
+            ui.monospace(value);
+        }
+        ui.small_button("🔄").on_hover_text("Reset the statistics").clicked()
+    }).inner
+}
+
+/// CSV rows of the plot points with one statistic next to each of the first rows, and each
+/// marker (timed like the points) next to the first sample at or after it
+fn csv_records(points: &[[f64; 2]], stats: &[(&str, String)], markers: &[(f64, String)]) -> Vec<[String; 5]> {
+    let mut marker_texts = vec![Vec::new(); points.len()];
+    for (time, text) in markers {
+        if let Some(row) = marker_texts.get_mut(points.partition_point(|point| point[0] < *time)) {
+            row.push(text.as_str());
+        }
+    }
+    (0..points.len().max(stats.len()))
+        .map(|row| {
+            let (time, value) = points.get(row).map_or((String::new(), String::new()), |point| (point[0].to_string(), point[1].to_string()));
+            let (label, result) = stats.get(row).map_or((String::new(), String::new()), |(label, result)| (label.to_string(), result.clone()));
+            let marker = marker_texts.get(row).map_or_else(String::new, |texts| texts.join("; "));
+            [time, value, label, result, marker]
+        })
+        .collect()
+}
+
+/// Markers set since `start`, timed like the points of a plot starting then
+fn marker_offsets(timeline: &Timeline, start: DateTime<Local>) -> Vec<(f64, String)> {
+    timeline.markers()
+        .filter(|marker| marker.at >= start)
+        .map(|marker| (timeline::offset_seconds(marker.at, start), marker.text.clone()))
+        .collect()
+}
+
+/// Samples to draw in a plot, decimated to its width and scaled; once the user zoomed or
+/// panned only the part in view, from the full history when scrolled back past the buffer
+fn plot_points(plot_ui: &egui_plot::PlotUi, plot_data: &VecDeque<[f64; 2]>, history: &SampleHistory, scaling: Option<&Scaling>) -> Vec<[f64; 2]> {
+    let width = plot_ui.response().rect.width() as usize;
+    let points = if plot_ui.auto_bounds().x {
+        decimation::min_max(plot_data.iter(), width)
+    } else {
+        let bounds = plot_ui.plot_bounds();
+        let (min_x, max_x) = (bounds.min()[0], bounds.max()[0]);
+        let buffered_from = plot_data.front().map_or(f64::INFINITY, |point| point[0]);
+        let from_history = (min_x < buffered_from && history.len() > plot_data.len())
+            .then(|| history.range(min_x, max_x, width))
+            .and_then(|points| points.map_err(|e| eprintln!("Failed to read the sample history: {}", e)).ok());
+        from_history.unwrap_or_else(|| decimation::min_max(plot_data.range(decimation::visible_range(plot_data, min_x, max_x)), width))
+    };
+    points.into_iter()
+        .map(|[time, value]| [time, scaling.map_or(value, |scaling| scaling.apply(value))])
+        .collect()
+}
+
+/// Every recorded sample of a subscription; the plot buffer if the history can't be read
+fn full_history(history: &SampleHistory, plot_data: &VecDeque<[f64; 2]>) -> Vec<[f64; 2]> {
+    history.all().unwrap_or_else(|e| {
+        eprintln!("Failed to read the sample history: {}", e);
+        plot_data.iter().copied().collect()
+    })
+}
+
+/// Plot samples with the scaling applied; the buffers keep the raw values
+fn scaled_points<'a>(plot_data: impl IntoIterator<Item = &'a [f64; 2]>, scaling: Option<&Scaling>) -> Vec<[f64; 2]> {
+    plot_data.into_iter()
+        .map(|&[time, value]| [time, scaling.map_or(value, |scaling| scaling.apply(value))])
         .collect()
 }
 
@@ -0,0 +1,407 @@
+// mdf4.rs - ASAM MDF 4.1 measurement file writer
+//
+// CANape, vSignalyzer and most other automotive analysis tools open MDF4 files directly,
+// with names, units and conversions of every signal, while the CSV log has to be imported
+// and split by hand. Samples go into one unsorted data group: every channel gets its own
+// channel group with a record ID, a time master and its value in the object's own data
+// type, with the engineering-unit scaling as a linear conversion. Channels are added as
+// they first appear, and data is appended in blocks chained by data list blocks, so the
+// file on disk stays readable up to the last flush even if the viewer is killed.
+use canopen_common::SdoDataType;
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::scaling::Scaling;
+
+/// Records collected before they are written as a data block
+const FLUSH_BYTES: usize = 64 * 1024;
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+const BLOCK_HEADER: u64 = 24;
+/// Record IDs are two bytes, so up to 65535 channels
+const RECORD_ID_BYTES: usize = 2;
+const TIME_BYTES: usize = 8;
+
+// Data types of the channel blocks
+const UNSIGNED_LE: u8 = 0;
+const SIGNED_LE: u8 = 2;
+const FLOAT_LE: u8 = 4;
+
+/// Name and metadata of a channel, taken from the EDS where there is one
+#[derive(Debug, Clone, Default)]
+pub struct MdfChannel {
+    pub name: String,
+    pub unit: String,
+    /// Transfer type of the object; None stores the value as a 64-bit float
+    pub data_type: Option<SdoDataType>,
+    /// Scaling from the raw value to the engineering unit
+    pub scaling: Option<Scaling>,
+    pub comment: String,
+}
+
+struct Channel {
+    data_type: Option<SdoDataType>,
+    /// Offset of the channel group block, to update its record count
+    group: u64,
+    records: u64,
+}
+
+pub struct MdfWriter {
+    file: File,
+    start: DateTime<Local>,
+    keys: HashMap<String, usize>,
+    channels: Vec<Channel>,
+    /// Channels whose blocks aren't written yet, starting at index `unwritten`
+    pending_channels: Vec<MdfChannel>,
+    unwritten: usize,
+    pending: Vec<u8>,
+    /// Bytes of record data written so far
+    data_bytes: u64,
+    data_group: u64,
+    last_group: Option<u64>,
+    last_list: Option<u64>,
+    last_flush: Instant,
+}
+
+/// MDF data type and size in bytes of a value; None for types that aren't numbers
+fn encoding(data_type: Option<&SdoDataType>) -> Option<(u8, usize)> {
+    match data_type {
+        None => Some((FLOAT_LE, 8)),
+        Some(SdoDataType::UInt8) => Some((UNSIGNED_LE, 1)),
+        Some(SdoDataType::UInt16) => Some((UNSIGNED_LE, 2)),
+        Some(SdoDataType::UInt32) => Some((UNSIGNED_LE, 4)),
+        Some(SdoDataType::Int8) => Some((SIGNED_LE, 1)),
+        Some(SdoDataType::Int16) => Some((SIGNED_LE, 2)),
+        Some(SdoDataType::Int32) => Some((SIGNED_LE, 4)),
+        Some(SdoDataType::Real32) => Some((FLOAT_LE, 4)),
+        Some(SdoDataType::VisibleString | SdoDataType::OctetString) => None,
+    }
+}
+
+fn encode(value: f64, data_type: Option<&SdoDataType>, bytes: &mut Vec<u8>) {
+    match data_type {
+        None => bytes.extend_from_slice(&value.to_le_bytes()),
+        Some(SdoDataType::UInt8) => bytes.push(value as u8),
+        Some(SdoDataType::UInt16) => bytes.extend_from_slice(&(value as u16).to_le_bytes()),
+        Some(SdoDataType::UInt32) => bytes.extend_from_slice(&(value as u32).to_le_bytes()),
+        Some(SdoDataType::Int8) => bytes.extend_from_slice(&(value as i8).to_le_bytes()),
+        Some(SdoDataType::Int16) => bytes.extend_from_slice(&(value as i16).to_le_bytes()),
+        Some(SdoDataType::Int32) => bytes.extend_from_slice(&(value as i32).to_le_bytes()),
+        Some(SdoDataType::Real32) => bytes.extend_from_slice(&(value as f32).to_le_bytes()),
+        Some(SdoDataType::VisibleString | SdoDataType::OctetString) => {}
+    }
+}
+
+impl MdfWriter {
+    pub fn create(path: &Path, start: DateTime<Local>) -> io::Result<Self> {
+        let mut writer = Self {
+            file: File::create(path)?,
+            start,
+            keys: HashMap::new(),
+            channels: Vec::new(),
+            pending_channels: Vec::new(),
+            unwritten: 0,
+            pending: Vec::new(),
+            data_bytes: 0,
+            data_group: 0,
+            last_group: None,
+            last_list: None,
+            last_flush: Instant::now(),
+        };
+
+        // Identification block
+        let mut id = Vec::with_capacity(64);
+        id.extend_from_slice(b"MDF     4.10    CANopenV");
+        id.extend_from_slice(&[0; 4]);
+        id.extend_from_slice(&410u16.to_le_bytes());
+        id.extend_from_slice(&[0; 34]);
+        writer.file.write_all(&id)?;
+
+        // Header block, linked to the file history and data group written after it
+        let start_ns = start.timestamp_nanos_opt().unwrap_or_default() as u64;
+        let tz_minutes = (start.offset().local_minus_utc() / 60) as i16;
+        let mut header = Vec::new();
+        header.extend_from_slice(&start_ns.to_le_bytes());
+        header.extend_from_slice(&tz_minutes.to_le_bytes());
+        header.extend_from_slice(&0i16.to_le_bytes());
+        // Time zone offset valid
+        header.extend_from_slice(&[2, 0, 0, 0]);
+        header.extend_from_slice(&[0; 16]);
+        let header_offset = writer.write_block(b"##HD", &[0; 6], &header)?;
+
+        let comment = format!(
+            "<FHcomment><TX>Logged by the CANopen Data Viewer</TX><tool_id>{}</tool_id><tool_vendor>CANopen Data Viewer</tool_vendor><tool_version>{}</tool_version></FHcomment>",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+        );
+        let comment = writer.write_text(b"##MD", &comment)?;
+        let mut history = Vec::new();
+        history.extend_from_slice(&start_ns.to_le_bytes());
+        history.extend_from_slice(&tz_minutes.to_le_bytes());
+        history.extend_from_slice(&0i16.to_le_bytes());
+        history.extend_from_slice(&[2, 0, 0, 0]);
+        let history = writer.write_block(b"##FH", &[0, comment], &history)?;
+
+        let mut group = vec![RECORD_ID_BYTES as u8];
+        group.extend_from_slice(&[0; 7]);
+        writer.data_group = writer.write_block(b"##DG", &[0; 4], &group)?;
+
+        // hd_dg_first and hd_fh_first
+        writer.patch(header_offset + BLOCK_HEADER, writer.data_group)?;
+        writer.patch(header_offset + BLOCK_HEADER + 8, history)?;
+        writer.file.flush()?;
+        Ok(writer)
+    }
+
+    /// Add a sample of the channel with config key `key`; `describe` gives its name and
+    /// metadata when it is new. Values of non-numeric types are left out.
+    pub fn sample(&mut self, key: &str, at: DateTime<Local>, raw: f64, describe: impl FnOnce() -> MdfChannel) -> io::Result<()> {
+        let index = match self.keys.get(key) {
+            Some(&index) => index,
+            None => {
+                let index = self.channels.len();
+                let description = describe();
+                self.channels.push(Channel { data_type: description.data_type.clone(), group: 0, records: 0 });
+                self.keys.insert(key.to_string(), index);
+                self.pending_channels.push(description);
+                index
+            }
+        };
+        let channel = &mut self.channels[index];
+        if encoding(channel.data_type.as_ref()).is_none() {
+            return Ok(());
+        }
+        let time = (at - self.start).num_microseconds().unwrap_or_default() as f64 / 1e6;
+        self.pending.extend_from_slice(&(index as u16 + 1).to_le_bytes());
+        self.pending.extend_from_slice(&time.to_le_bytes());
+        encode(raw, channel.data_type.as_ref(), &mut self.pending);
+        channel.records += 1;
+
+        if self.pending.len() >= FLUSH_BYTES || self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Write the new channels and the collected records, leaving a complete file
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.last_flush = Instant::now();
+        for description in std::mem::take(&mut self.pending_channels) {
+            let index = self.unwritten;
+            self.unwritten += 1;
+            self.write_channel_group(index, &description)?;
+        }
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let data = std::mem::take(&mut self.pending);
+        let block = self.write_block(b"##DT", &[], &data)?;
+        let mut list = vec![0; 4];
+        list.extend_from_slice(&1u32.to_le_bytes());
+        list.extend_from_slice(&self.data_bytes.to_le_bytes());
+        let list = self.write_block(b"##DL", &[0, block], &list)?;
+        match self.last_list {
+            // dl_dl_next of the previous list, dg_data of the data group for the first
+            Some(previous) => self.patch(previous + BLOCK_HEADER, list)?,
+            None => self.patch(self.data_group + BLOCK_HEADER + 16, list)?,
+        }
+        self.last_list = Some(list);
+        self.data_bytes += data.len() as u64;
+
+        for index in 0..self.channels.len() {
+            let Channel { group, records, .. } = self.channels[index];
+            if group != 0 {
+                // cg_cycle_count
+                self.patch(group + BLOCK_HEADER + 6 * 8 + 8, records)?;
+            }
+        }
+        self.file.flush()
+    }
+
+    fn write_channel_group(&mut self, index: usize, description: &MdfChannel) -> io::Result<()> {
+        let Some((data_type, bytes)) = encoding(description.data_type.as_ref()) else {
+            return Ok(());
+        };
+
+        let time_name = self.write_text(b"##TX", "time")?;
+        let time_unit = self.write_text(b"##TX", "s")?;
+        let name = self.write_text(b"##TX", &description.name)?;
+        let unit = self.write_text(b"##TX", &description.unit)?;
+        let comment = self.write_text(b"##TX", &description.comment)?;
+        let conversion = match description.scaling.as_ref().filter(|scaling| !scaling.is_identity()) {
+            Some(scaling) => self.write_linear_conversion(scaling)?,
+            None => 0,
+        };
+
+        let value = self.write_channel(&[0, 0, name, 0, conversion, 0, unit, comment], 0, 0, data_type, TIME_BYTES as u32, bytes as u32 * 8)?;
+        let time = self.write_channel(&[value, 0, time_name, 0, 0, 0, time_unit, 0], 2, 1, FLOAT_LE, 0, 64)?;
+
+        let mut group = Vec::new();
+        group.extend_from_slice(&(index as u64 + 1).to_le_bytes());
+        group.extend_from_slice(&self.channels[index].records.to_le_bytes());
+        group.extend_from_slice(&[0; 8]);
+        group.extend_from_slice(&((TIME_BYTES + bytes) as u32).to_le_bytes());
+        group.extend_from_slice(&0u32.to_le_bytes());
+        let group = self.write_block(b"##CG", &[0, time, 0, 0, 0, 0], &group)?;
+        match self.last_group {
+            Some(previous) => self.patch(previous + BLOCK_HEADER, group)?,
+            None => self.patch(self.data_group + BLOCK_HEADER + 8, group)?,
+        }
+        self.last_group = Some(group);
+        self.channels[index].group = group;
+        Ok(())
+    }
+
+    fn write_channel(&mut self, links: &[u64; 8], channel_type: u8, sync_type: u8, data_type: u8, byte_offset: u32, bit_count: u32) -> io::Result<u64> {
+        let mut channel = vec![channel_type, sync_type, data_type, 0];
+        channel.extend_from_slice(&byte_offset.to_le_bytes());
+        channel.extend_from_slice(&bit_count.to_le_bytes());
+        // Flags, invalidation bit, precision, attachments, ranges and limits
+        channel.extend_from_slice(&[0; 12 + 48]);
+        self.write_block(b"##CN", links, &channel)
+    }
+
+    /// Physical value = factor * raw + offset
+    fn write_linear_conversion(&mut self, scaling: &Scaling) -> io::Result<u64> {
+        let unit = self.write_text(b"##TX", &scaling.unit)?;
+        let mut conversion = vec![1, 0, 0, 0, 0, 0];
+        conversion.extend_from_slice(&2u16.to_le_bytes());
+        conversion.extend_from_slice(&[0; 16]);
+        conversion.extend_from_slice(&scaling.offset.to_le_bytes());
+        conversion.extend_from_slice(&scaling.factor.to_le_bytes());
+        self.write_block(b"##CC", &[0, unit, 0, 0], &conversion)
+    }
+
+    /// Text or XML block; no block and a zero link for empty text
+    fn write_text(&mut self, id: &[u8; 4], text: &str) -> io::Result<u64> {
+        if text.is_empty() {
+            return Ok(0);
+        }
+        let mut data = text.as_bytes().to_vec();
+        data.push(0);
+        self.write_block(id, &[], &data)
+    }
+
+    /// Append a block at the end of the file, padded to the next multiple of eight bytes
+    fn write_block(&mut self, id: &[u8; 4], links: &[u64], data: &[u8]) -> io::Result<u64> {
+        let offset = self.file.seek(SeekFrom::End(0))?;
+        let length = (BLOCK_HEADER as usize + 8 * links.len() + data.len()).next_multiple_of(8);
+        let mut block = Vec::with_capacity(length);
+        block.extend_from_slice(id);
+        block.extend_from_slice(&[0; 4]);
+        block.extend_from_slice(&(length as u64).to_le_bytes());
+        block.extend_from_slice(&(links.len() as u64).to_le_bytes());
+        for link in links {
+            block.extend_from_slice(&link.to_le_bytes());
+        }
+        block.extend_from_slice(data);
+        block.resize(length, 0);
+        self.file.write_all(&block)?;
+        Ok(offset)
+    }
+
+    fn patch(&mut self, offset: u64, value: u64) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.write_all(&value.to_le_bytes())
+    }
+}
+
+impl Drop for MdfWriter {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            eprintln!("Failed to write MDF4 log: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    /// A file with two samples of a scaled SDO and one of a derived value in between
+    fn written() -> Vec<u8> {
+        let directory = TestDir::new("mdf4");
+        let path = directory.join("log.mf4");
+        let start = Local::now();
+        let mut writer = MdfWriter::create(&path, start).unwrap();
+        let speed = || MdfChannel {
+            name: "Speed".to_string(),
+            unit: "rpm".to_string(),
+            data_type: Some(SdoDataType::UInt16),
+            scaling: Some(Scaling { factor: 0.5, offset: 0.0, unit: "rpm".to_string() }),
+            comment: "SDO 0x6401:01".to_string(),
+        };
+        writer.sample("6401sub1", start, 100.0, speed).unwrap();
+        writer.sample("derived:Power", start + chrono::TimeDelta::milliseconds(500), 1.5, MdfChannel::default).unwrap();
+        writer.sample("6401sub1", start + chrono::TimeDelta::seconds(1), 300.0, || unreachable!()).unwrap();
+        drop(writer);
+        std::fs::read(&path).unwrap()
+    }
+
+    fn link(bytes: &[u8], offset: u64) -> u64 {
+        u64::from_le_bytes(bytes[offset as usize..offset as usize + 8].try_into().unwrap())
+    }
+
+    fn id(bytes: &[u8], offset: u64) -> &[u8] {
+        &bytes[offset as usize..offset as usize + 4]
+    }
+
+    #[test]
+    fn test_identification_and_header() {
+        let bytes = written();
+        assert_eq!(&bytes[..16], b"MDF     4.10    ");
+        assert_eq!(id(&bytes, 64), b"##HD");
+        assert!(bytes.len().is_multiple_of(8));
+        assert_eq!(id(&bytes, link(&bytes, 64 + 24)), b"##DG");
+    }
+
+    #[test]
+    fn test_one_channel_group_per_series() {
+        let bytes = written();
+        let data_group = link(&bytes, 64 + 24);
+        let first_group = link(&bytes, data_group + 32);
+        assert_eq!(id(&bytes, first_group), b"##CG");
+        // Record ID and number of records of both groups
+        assert_eq!(link(&bytes, first_group + 72), 1);
+        assert_eq!(link(&bytes, first_group + 80), 2);
+        let second_group = link(&bytes, first_group + 24);
+        assert_eq!(link(&bytes, second_group + 72), 2);
+        assert_eq!(link(&bytes, second_group + 80), 1);
+        assert_eq!(link(&bytes, second_group + 24), 0);
+    }
+
+    #[test]
+    fn test_time_master_and_scaled_value_channel() {
+        let bytes = written();
+        let first_group = link(&bytes, link(&bytes, 64 + 24) + 32);
+        let time = link(&bytes, first_group + 32);
+        assert_eq!(&bytes[time as usize + 88..time as usize + 91], [2, 1, FLOAT_LE]);
+        let value = link(&bytes, time + 24);
+        assert_eq!(&bytes[value as usize + 88..value as usize + 91], [0, 0, UNSIGNED_LE]);
+        assert_eq!(id(&bytes, link(&bytes, value + 24 + 32)), b"##CC");
+    }
+
+    #[test]
+    fn test_records_in_one_data_block() {
+        let bytes = written();
+        let list = link(&bytes, link(&bytes, 64 + 24) + 40);
+        assert_eq!(id(&bytes, list), b"##DL");
+        assert_eq!(link(&bytes, list + 24), 0);
+        let data = link(&bytes, list + 32);
+        assert_eq!(id(&bytes, data), b"##DT");
+
+        // Records of 2 + 8 + 2 and 2 + 8 + 8 bytes, raw values before scaling
+        let records = &bytes[data as usize + 24..];
+        assert_eq!(&records[..2], 1u16.to_le_bytes());
+        assert_eq!(&records[10..12], 100u16.to_le_bytes());
+        assert_eq!(&records[12..14], 2u16.to_le_bytes());
+        assert_eq!(f64::from_le_bytes(records[14..22].try_into().unwrap()), 0.5);
+        assert_eq!(f64::from_le_bytes(records[22..30].try_into().unwrap()), 1.5);
+        assert_eq!(&records[40..42], 300u16.to_le_bytes());
+    }
+}
@@ -0,0 +1,182 @@
+// network_overview.rs - Every node seen on the bus at a glance
+//
+// Nodes are detected passively from their heartbeats and emergencies, so devices the
+// viewer isn't connected to show up as well. Each row has the NMT state, heartbeat
+// health and active emergency; the monitored node also its subscription count. A row
+// leads to the node's detail view: the main view, after switching to that node.
+use canopen_common::NmtState;
+use eframe::egui::{self, Color32};
+use std::collections::{BTreeSet, HashMap};
+
+use crate::canopen::heartbeat::NodeNmtState;
+use crate::emcy_monitor::EmcyMonitor;
+
+/// One row of the overview
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeSummary {
+    pub node_id: u8,
+    pub state: NodeNmtState,
+    /// Emergencies received since the last acknowledgement
+    pub emergencies: usize,
+    /// Latest emergency unless the node reported the error as gone
+    pub active_emergency: Option<String>,
+    /// SDO subscriptions plus TPDO listeners; `None` for nodes other than the monitored one
+    pub subscriptions: Option<usize>,
+}
+
+/// Summaries of the monitored node and every node that sent a heartbeat or emergency
+pub fn summaries(
+    monitored: u8,
+    subscriptions: usize,
+    nmt_states: &HashMap<u8, NodeNmtState>,
+    emcy_monitor: &EmcyMonitor,
+) -> Vec<NodeSummary> {
+    let node_ids: BTreeSet<u8> = nmt_states.keys().copied()
+        .chain(emcy_monitor.nodes())
+        .chain([monitored])
+        .collect();
+
+    node_ids.into_iter()
+        .map(|node_id| {
+            let (emergencies, active) = emcy_monitor.node_summary(node_id);
+            NodeSummary {
+                node_id,
+                state: nmt_states.get(&node_id).copied().unwrap_or(NodeNmtState::NoHeartbeat),
+                emergencies,
+                active_emergency: active.map(|message| format!("0x{:04X} {}", message.error_code, message.description())),
+                subscriptions: (node_id == monitored).then_some(subscriptions),
+            }
+        })
+        .collect()
+}
+
+pub enum OverviewAction {
+    /// Reconnect to another node to see its details
+    SwitchTo(u8),
+}
+
+pub struct NetworkOverview {
+    pub open: bool,
+}
+
+impl NetworkOverview {
+    pub fn new() -> Self {
+        Self { open: false }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, nodes: &[NodeSummary]) -> Option<OverviewAction> {
+        let mut action = None;
+        let mut open = self.open;
+        let mut close = false;
+
+        egui::Window::new("Network Overview")
+            .open(&mut open)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                ui.label(format!("{} nodes seen on the bus (from heartbeats and emergencies)", nodes.len()));
+                ui.separator();
+
+                egui::Grid::new("network_overview_grid")
+                    .num_columns(6)
+                    .spacing([12.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Node");
+                        ui.strong("NMT state");
+                        ui.strong("Heartbeat");
+                        ui.strong("Emergency");
+                        ui.strong("Subscriptions");
+                        ui.strong("");
+                        ui.end_row();
+
+                        for node in nodes {
+                            let monitored = node.subscriptions.is_some();
+                            if monitored {
+                                ui.strong(format!("{} (monitored)", node.node_id));
+                            } else {
+                                ui.label(node.node_id.to_string());
+                            }
+
+                            let (state_color, state) = match node.state {
+                                NodeNmtState::Active(NmtState::Operational) => (Color32::from_rgb(0, 200, 0), node.state.to_string()),
+                                NodeNmtState::Active(NmtState::Stopped) => (Color32::from_rgb(200, 0, 0), node.state.to_string()),
+                                NodeNmtState::Active(_) => (Color32::from_rgb(255, 165, 0), node.state.to_string()),
+                                NodeNmtState::Silent | NodeNmtState::NoHeartbeat => (Color32::GRAY, "—".to_string()),
+                            };
+                            ui.colored_label(state_color, state);
+
+                            match node.state {
+                                NodeNmtState::Active(_) => ui.colored_label(Color32::from_rgb(0, 200, 0), "OK"),
+                                NodeNmtState::Silent => ui.colored_label(Color32::from_rgb(200, 0, 0), "Lost"),
+                                NodeNmtState::NoHeartbeat => ui.colored_label(Color32::GRAY, "None"),
+                            };
+
+                            match &node.active_emergency {
+                                Some(emergency) => {
+                                    let text = if node.emergencies > 0 {
+                                        format!("⚠ {} ({} new)", emergency, node.emergencies)
+                                    } else {
+                                        format!("⚠ {}", emergency)
+                                    };
+                                    ui.colored_label(Color32::from_rgb(255, 100, 100), text);
+                                }
+                                None => {
+                                    ui.label("—");
+                                }
+                            }
+
+                            ui.label(node.subscriptions.map_or("—".to_string(), |count| count.to_string()));
+
+                            if monitored {
+                                if ui.button("Details").on_hover_text("Show this node in the main view").clicked() {
+                                    close = true;
+                                }
+                            } else if ui.button("Switch to node").on_hover_text("Reconnect to this node to see its objects and PDOs").clicked() {
+                                action = Some(OverviewAction::SwitchTo(node.node_id));
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        self.open = open && !close && action.is_none();
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emcy_monitor::EmergencyRecord;
+    use canopen_common::parse_emcy_frame;
+    use chrono::Local;
+
+    #[test]
+    fn test_network_summaries() {
+        let mut emcy_monitor = EmcyMonitor::new();
+        let mut emergency = |node_id, data: &[u8]| emcy_monitor.record(EmergencyRecord {
+            timestamp: Local::now(),
+            node_id,
+            message: parse_emcy_frame(data).unwrap(),
+        });
+        emergency(7, &[0x10, 0x42, 0x09, 0, 0, 0, 0, 0]);
+        emergency(9, &[0x10, 0x42, 0x09, 0, 0, 0, 0, 0]);
+        emergency(9, &[0x00, 0x00, 0x00, 0, 0, 0, 0, 0]);
+
+        let nmt_states = HashMap::from([
+            (3, NodeNmtState::Active(NmtState::Operational)),
+            (7, NodeNmtState::Silent),
+        ]);
+        let nodes = summaries(5, 4, &nmt_states, &emcy_monitor);
+
+        assert_eq!(nodes.iter().map(|node| node.node_id).collect::<Vec<_>>(), vec![3, 5, 7, 9]);
+        assert_eq!(nodes[1].state, NodeNmtState::NoHeartbeat);
+        assert_eq!(nodes[1].subscriptions, Some(4));
+        assert_eq!(nodes[0].subscriptions, None);
+        assert_eq!(nodes[2].active_emergency.as_deref(), Some("0x4210 Device temperature"));
+        assert_eq!(nodes[2].emergencies, 1);
+        // Node 9 reset its error
+        assert_eq!(nodes[3].active_emergency, None);
+        assert_eq!(nodes[3].emergencies, 1);
+    }
+}
@@ -0,0 +1,105 @@
+// nmt_panel.rs - NMT node control window
+//
+// One button per NMT command for the selected node, or for every node on the bus with
+// the broadcast option (node ID 0). Commands go through the communication thread like
+// the NMT menu; the panel shows which command was last sent and whether it went out.
+use canopen_common::{NmtCommand, NMT_BROADCAST_NODE_ID};
+use chrono::Local;
+use eframe::egui::{self, Color32};
+
+const COMMANDS: [(NmtCommand, &str); 5] = [
+    (NmtCommand::Start, "Switch to Operational (PDOs enabled)"),
+    (NmtCommand::Stop, "Switch to Stopped (only NMT and heartbeat)"),
+    (NmtCommand::EnterPreOperational, "Switch to Pre-Operational (SDO only)"),
+    (NmtCommand::ResetNode, "Reset the application and communication parameters"),
+    (NmtCommand::ResetCommunication, "Reset the communication parameters only"),
+];
+
+/// Node ID the commands are sent to: the selected node, or 0 for all nodes
+pub fn target(broadcast: bool, node_id: Option<u8>) -> Option<u8> {
+    if broadcast { Some(NMT_BROADCAST_NODE_ID) } else { node_id }
+}
+
+fn target_name(node_id: u8) -> String {
+    if node_id == NMT_BROADCAST_NODE_ID { "all nodes".to_string() } else { format!("node {}", node_id) }
+}
+
+pub struct NmtPanel {
+    pub open: bool,
+    broadcast: bool,
+    status: Option<Result<String, String>>,
+}
+
+impl NmtPanel {
+    pub fn new() -> Self {
+        Self { open: false, broadcast: false, status: None }
+    }
+
+    /// Outcome of a command sent from anywhere in the viewer
+    pub fn sent(&mut self, node_id: u8, command: NmtCommand, result: Result<(), String>) {
+        self.status = Some(match result {
+            Ok(()) => Ok(format!("{} sent to {} at {}", command, target_name(node_id), Local::now().format("%H:%M:%S"))),
+            Err(err) => Err(format!("{} to {} failed: {}", command, target_name(node_id), err)),
+        });
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, connected: bool, node_id: Option<u8>) -> Option<(u8, NmtCommand)> {
+        let mut action = None;
+        let mut open = self.open;
+
+        egui::Window::new("NMT Control")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let target = target(self.broadcast, node_id);
+                ui.horizontal(|ui| {
+                    ui.label("Target:");
+                    match target {
+                        Some(node_id) => ui.strong(target_name(node_id)),
+                        None => ui.weak("no node selected"),
+                    };
+                });
+                ui.checkbox(&mut self.broadcast, "All nodes (broadcast)");
+                if self.broadcast {
+                    ui.colored_label(Color32::from_rgb(255, 165, 0), "Commands address every node on the bus.");
+                }
+                ui.separator();
+
+                ui.horizontal_wrapped(|ui| {
+                    for (command, description) in COMMANDS {
+                        let enabled = connected && target.is_some();
+                        if ui.add_enabled(enabled, egui::Button::new(command.to_string())).on_hover_text(description).clicked() {
+                            action = target.map(|node_id| (node_id, command));
+                        }
+                    }
+                });
+
+                match &self.status {
+                    Some(Ok(message)) => {
+                        ui.colored_label(Color32::from_rgb(0, 200, 0), message);
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+                    }
+                    None => {}
+                }
+            });
+
+        self.open = open;
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nmt_target() {
+        assert_eq!(target(false, Some(5)), Some(5));
+        assert_eq!(target(true, Some(5)), Some(NMT_BROADCAST_NODE_ID));
+        assert_eq!(target(true, None), Some(NMT_BROADCAST_NODE_ID));
+        assert_eq!(target(false, None), None);
+        assert_eq!(target_name(0), "all nodes");
+    }
+}
@@ -0,0 +1,101 @@
+// object_walk.rs - Object list of a device without an EDS
+//
+// Every index of the communication and manufacturer/profile areas is read via SDO;
+// "object does not exist" aborts tell which ones are there. Types are guessed from
+// the response size and names come from CiA 301 for the standard objects. Whether an
+// object is writable isn't known, so all are offered as rw and the device decides.
+use std::ops::RangeInclusive;
+
+use crate::communication::SdoSubObject;
+
+/// Index ranges walked: communication area, manufacturer and device profile areas
+pub const WALK_RANGES: [RangeInclusive<u16>; 2] = [0x1000..=0x1FFF, 0x2000..=0x5FFF];
+
+/// EDS `DataType` code for a read of `size` bytes; other sizes are kept as octet strings
+pub fn eds_type_for_size(size: usize) -> &'static str {
+    match size {
+        1 => "0x0005",
+        2 => "0x0006",
+        4 => "0x0007",
+        _ => "0x000A",
+    }
+}
+
+/// CiA 301 name of a standard object, "Object 0x2000" otherwise
+pub fn object_name(index: u16) -> String {
+    let name = match index {
+        0x1000 => "Device type",
+        0x1001 => "Error register",
+        0x1002 => "Manufacturer status register",
+        0x1003 => "Pre-defined error field",
+        0x1005 => "COB-ID SYNC",
+        0x1006 => "Communication cycle period",
+        0x1007 => "Synchronous window length",
+        0x1008 => "Manufacturer device name",
+        0x1009 => "Manufacturer hardware version",
+        0x100A => "Manufacturer software version",
+        0x100C => "Guard time",
+        0x100D => "Life time factor",
+        0x1010 => "Store parameters",
+        0x1011 => "Restore default parameters",
+        0x1012 => "COB-ID TIME",
+        0x1014 => "COB-ID EMCY",
+        0x1015 => "Inhibit time EMCY",
+        0x1016 => "Consumer heartbeat time",
+        0x1017 => "Producer heartbeat time",
+        0x1018 => "Identity object",
+        0x1019 => "Synchronous counter overflow value",
+        0x1200..=0x127F => "SDO server parameter",
+        0x1280..=0x12FF => "SDO client parameter",
+        0x1400..=0x15FF => "RPDO communication parameter",
+        0x1600..=0x17FF => "RPDO mapping parameter",
+        0x1800..=0x19FF => "TPDO communication parameter",
+        0x1A00..=0x1BFF => "TPDO mapping parameter",
+        _ => return format!("Object 0x{:04X}", index),
+    };
+    name.to_string()
+}
+
+/// Sub-object found on the device
+pub fn sub_object(index: u16, sub_index: u8, size: usize, has_sub_entries: bool) -> SdoSubObject {
+    let name = match (sub_index, has_sub_entries) {
+        (0, true) => "Highest sub-index supported".to_string(),
+        (0, false) => object_name(index),
+        _ => format!("Sub-index {}", sub_index),
+    };
+    SdoSubObject {
+        name,
+        data_type: eds_type_for_size(size).to_string(),
+        access_type: "rw".to_string(),
+        low_limit: None,
+        high_limit: None,
+        default_value: None,
+        value_descriptions: Default::default(),
+        scaling: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use canopen_common::SdoDataType;
+
+    #[test]
+    fn test_walked_sub_objects() {
+        let heartbeat = sub_object(0x1017, 0, 2, false);
+        assert_eq!(heartbeat.name, "Producer heartbeat time");
+        assert_eq!(heartbeat.data_type, "0x0006");
+
+        assert_eq!(sub_object(0x1018, 0, 1, true).name, "Highest sub-index supported");
+        assert_eq!(sub_object(0x1A01, 2, 4, true).data_type, "0x0007");
+        assert_eq!(object_name(0x1A01), "TPDO mapping parameter");
+        assert_eq!(object_name(0x2100), "Object 0x2100");
+        assert_eq!(eds_type_for_size(8), "0x000A");
+
+        // The guessed types read back with the size they were guessed from
+        assert_eq!(SdoDataType::from_eds_type(eds_type_for_size(1)), Some(SdoDataType::UInt8));
+        assert_eq!(SdoDataType::from_eds_type(eds_type_for_size(2)), Some(SdoDataType::UInt16));
+        assert_eq!(SdoDataType::from_eds_type(eds_type_for_size(4)), Some(SdoDataType::UInt32));
+        assert_eq!(SdoDataType::from_eds_type(eds_type_for_size(8)), Some(SdoDataType::OctetString));
+    }
+}
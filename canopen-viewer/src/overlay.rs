@@ -0,0 +1,132 @@
+// overlay.rs - Several subscriptions drawn in one plot
+//
+// Correlating signals needs them on one time axis. An overlay plot takes any mix of SDO
+// and TPDO subscriptions; their series keep being recorded as before and are only drawn
+// together. All subscriptions are timed from the same session epoch, so their points can
+// be drawn as they are.
+use serde::{Deserialize, Serialize};
+
+use crate::communication::SdoAddress;
+use crate::y_axis::YAxisSettings;
+
+// Identifier for a specific field within a TPDO
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TpdoFieldId {
+    pub tpdo_number: u8,
+    pub field_name: String,  // e.g., "Temperature", "Pressure", "Status"
+}
+
+/// A recorded series that can be drawn in an overlay plot, also the payload when one
+/// is dragged onto a plot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlotSeries {
+    Sdo(SdoAddress),
+    Tpdo(TpdoFieldId),
+    /// A derived channel, by name
+    Derived(String),
+}
+
+impl PlotSeries {
+    /// Key of the series in the config file
+    pub fn key(&self) -> String {
+        match self {
+            PlotSeries::Sdo(address) => address.eds_key(),
+            PlotSeries::Tpdo(field_id) => format!("tpdo{}:{}", field_id.tpdo_number, field_id.field_name),
+            PlotSeries::Derived(name) => format!("derived:{}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverlayPlot<S> {
+    pub title: String,
+    pub series: Vec<S>,
+    #[serde(default)]
+    pub y_axis: YAxisSettings,
+}
+
+impl<S: PartialEq> OverlayPlot<S> {
+    pub fn new(number: usize, first: S) -> Self {
+        Self { title: format!("Overlay {}", number), series: vec![first], y_axis: YAxisSettings::default() }
+    }
+
+    pub fn contains(&self, series: &S) -> bool {
+        self.series.contains(series)
+    }
+
+    pub fn add(&mut self, series: S) {
+        if !self.contains(&series) {
+            self.series.push(series);
+        }
+    }
+}
+
+/// Where a dragged series was dropped
+#[derive(Debug, Clone, PartialEq)]
+pub enum DropTarget<S> {
+    /// The plot of a series on its own
+    Plot(S),
+    Overlay(usize),
+    /// The empty area below the plots: the series gets a plot of its own
+    NewPlot,
+}
+
+/// Rearrange the overlays for `series` dropped on `target`; a series is in one plot only
+pub fn place<S: PartialEq>(overlays: &mut Vec<OverlayPlot<S>>, created: &mut usize, series: S, target: DropTarget<S>) {
+    let keep = match &target {
+        DropTarget::Plot(target) if *target == series => return,
+        DropTarget::Plot(target) => overlays.iter().position(|overlay| overlay.contains(target)),
+        DropTarget::Overlay(index) => Some(*index),
+        DropTarget::NewPlot => None,
+    };
+    for (index, overlay) in overlays.iter_mut().enumerate() {
+        if Some(index) != keep {
+            overlay.series.retain(|existing| *existing != series);
+        }
+    }
+
+    match (target, keep.and_then(|index| overlays.get_mut(index))) {
+        (DropTarget::NewPlot, _) => {}
+        (_, Some(overlay)) => overlay.add(series),
+        (DropTarget::Plot(target), None) => {
+            *created += 1;
+            let mut overlay = OverlayPlot::new(*created, target);
+            overlay.add(series);
+            overlays.push(overlay);
+        }
+        (DropTarget::Overlay(_), None) => {}
+    }
+    overlays.retain(|overlay| !overlay.series.is_empty());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlay_series_placement() {
+        let mut overlay = OverlayPlot::new(1, "a");
+        overlay.add("b");
+        overlay.add("a");
+        assert_eq!(overlay.series, vec!["a", "b"]);
+        assert_eq!(overlay.title, "Overlay 1");
+
+        // Dropping one single plot onto another combines them, moving between overlays
+        let mut overlays = Vec::new();
+        let mut created = 0;
+        place(&mut overlays, &mut created, "b", DropTarget::Plot("a"));
+        assert_eq!(overlays[0].series, vec!["a", "b"]);
+        place(&mut overlays, &mut created, "d", DropTarget::Plot("c"));
+        assert_eq!(overlays[1].title, "Overlay 2");
+        place(&mut overlays, &mut created, "b", DropTarget::Overlay(1));
+        assert_eq!(overlays[0].series, vec!["a"]);
+        assert_eq!(overlays[1].series, vec!["c", "d", "b"]);
+        place(&mut overlays, &mut created, "a", DropTarget::Plot("d"));
+        assert_eq!(overlays.len(), 1);
+        assert_eq!(overlays[0].series, vec!["c", "d", "b", "a"]);
+        place(&mut overlays, &mut created, "a", DropTarget::NewPlot);
+        assert_eq!(overlays[0].series, vec!["c", "d", "b"]);
+        place(&mut overlays, &mut created, "c", DropTarget::Plot("c"));
+        assert_eq!(overlays[0].series, vec!["c", "d", "b"]);
+    }
+}
@@ -0,0 +1,306 @@
+// parquet_export.rs - Session data as an Apache Parquet file
+//
+// pandas and Polars read a Parquet file of millions of samples in a fraction of the time
+// they take to parse the same CSV, and keep the column types. The export is in long
+// format, one row per sample with its absolute time, channel name and value, which suits
+// channels sampled at different rates. The file has one row group with plain-encoded,
+// uncompressed pages; the metadata is written with the Thrift compact protocol.
+use chrono::{DateTime, Local};
+use std::io;
+use std::path::Path;
+
+use crate::session_export::ExportChannel;
+
+const MAGIC: &[u8; 4] = b"PAR1";
+/// Values per data page
+const PAGE_ROWS: usize = 64 * 1024;
+
+// Thrift compact protocol types
+const TYPE_TRUE: u8 = 1;
+const TYPE_I32: u8 = 5;
+const TYPE_I64: u8 = 6;
+const TYPE_BINARY: u8 = 8;
+const TYPE_LIST: u8 = 9;
+const TYPE_STRUCT: u8 = 12;
+
+// Parquet physical types and enums
+const INT64: i32 = 2;
+const DOUBLE: i32 = 5;
+const BYTE_ARRAY: i32 = 6;
+const REQUIRED: i32 = 0;
+const CONVERTED_UTF8: i32 = 0;
+const CONVERTED_TIMESTAMP_MICROS: i32 = 10;
+const PLAIN: i32 = 0;
+const RLE: i32 = 3;
+const UNCOMPRESSED: i32 = 0;
+const DATA_PAGE: i32 = 0;
+
+/// Thrift compact protocol encoder
+#[derive(Default)]
+struct Compact {
+    bytes: Vec<u8>,
+    /// Last field ID of each open struct
+    fields: Vec<i16>,
+}
+
+impl Compact {
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.bytes.push(value as u8);
+    }
+
+    fn zigzag(&mut self, value: i64) {
+        self.varint(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    fn field(&mut self, id: i16, kind: u8) {
+        let last = self.fields.last().copied().unwrap_or_default();
+        match id - last {
+            delta @ 1..=15 => self.bytes.push((delta as u8) << 4 | kind),
+            _ => {
+                self.bytes.push(kind);
+                self.zigzag(id as i64);
+            }
+        }
+        if let Some(last) = self.fields.last_mut() {
+            *last = id;
+        }
+    }
+
+    fn begin(&mut self) {
+        self.fields.push(0);
+    }
+
+    fn end(&mut self) {
+        self.bytes.push(0);
+        self.fields.pop();
+    }
+
+    fn struct_field(&mut self, id: i16) {
+        self.field(id, TYPE_STRUCT);
+        self.begin();
+    }
+
+    fn i32(&mut self, id: i16, value: i32) {
+        self.field(id, TYPE_I32);
+        self.zigzag(value as i64);
+    }
+
+    fn i64(&mut self, id: i16, value: i64) {
+        self.field(id, TYPE_I64);
+        self.zigzag(value);
+    }
+
+    fn string(&mut self, id: i16, value: &str) {
+        self.field(id, TYPE_BINARY);
+        self.varint(value.len() as u64);
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    fn list(&mut self, id: i16, kind: u8, len: usize) {
+        self.field(id, TYPE_LIST);
+        if len < 15 {
+            self.bytes.push((len as u8) << 4 | kind);
+        } else {
+            self.bytes.push(0xF0 | kind);
+            self.varint(len as u64);
+        }
+    }
+}
+
+struct Column {
+    name: &'static str,
+    physical_type: i32,
+    /// Plain-encoded values of each page, with the number of values
+    pages: Vec<(usize, Vec<u8>)>,
+}
+
+impl Column {
+    fn new<T>(name: &'static str, physical_type: i32, values: &[T], encode: impl Fn(&T, &mut Vec<u8>)) -> Self {
+        let pages = values.chunks(PAGE_ROWS)
+            .map(|chunk| {
+                let mut bytes = Vec::new();
+                for value in chunk {
+                    encode(value, &mut bytes);
+                }
+                (chunk.len(), bytes)
+            })
+            .collect();
+        Self { name, physical_type, pages }
+    }
+}
+
+/// Column chunk metadata, once its pages are in the file
+struct Written {
+    offset: usize,
+    size: usize,
+    values: usize,
+}
+
+fn schema_element(thrift: &mut Compact, column: &Column) {
+    thrift.begin();
+    thrift.i32(1, column.physical_type);
+    thrift.i32(3, REQUIRED);
+    thrift.string(4, column.name);
+    match column.physical_type {
+        INT64 => {
+            thrift.i32(6, CONVERTED_TIMESTAMP_MICROS);
+            // Logical type TIMESTAMP(isAdjustedToUTC, MICROS)
+            thrift.struct_field(10);
+            thrift.struct_field(8);
+            thrift.field(1, TYPE_TRUE);
+            thrift.struct_field(2);
+            thrift.struct_field(2);
+            thrift.end();
+            thrift.end();
+            thrift.end();
+            thrift.end();
+        }
+        BYTE_ARRAY => {
+            thrift.i32(6, CONVERTED_UTF8);
+            // Logical type STRING
+            thrift.struct_field(10);
+            thrift.struct_field(1);
+            thrift.end();
+            thrift.end();
+        }
+        _ => {}
+    }
+    thrift.end();
+}
+
+/// The whole file: every sample of `channels` by time, timestamps from `epoch`
+pub fn encode(epoch: DateTime<Local>, channels: &[ExportChannel]) -> Vec<u8> {
+    let mut rows: Vec<(f64, usize, f64)> = channels.iter().enumerate()
+        .flat_map(|(index, channel)| channel.points.iter().map(move |point| (point[0], index, point[1])))
+        .collect();
+    rows.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let start = epoch.timestamp_micros();
+    let columns = [
+        Column::new("timestamp", INT64, &rows, |row, bytes| {
+            let micros = start + (row.0 * 1e6).round() as i64;
+            bytes.extend_from_slice(&micros.to_le_bytes());
+        }),
+        Column::new("channel", BYTE_ARRAY, &rows, |row, bytes| {
+            let name = channels[row.1].name.as_bytes();
+            bytes.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(name);
+        }),
+        Column::new("value", DOUBLE, &rows, |row, bytes| bytes.extend_from_slice(&row.2.to_le_bytes())),
+    ];
+
+    let mut file = MAGIC.to_vec();
+    let mut written = Vec::new();
+    for column in &columns {
+        let offset = file.len();
+        for (values, data) in &column.pages {
+            let mut header = Compact::default();
+            header.begin();
+            header.i32(1, DATA_PAGE);
+            header.i32(2, data.len() as i32);
+            header.i32(3, data.len() as i32);
+            header.struct_field(5);
+            header.i32(1, *values as i32);
+            header.i32(2, PLAIN);
+            header.i32(3, RLE);
+            header.i32(4, RLE);
+            header.end();
+            header.end();
+            file.extend_from_slice(&header.bytes);
+            file.extend_from_slice(data);
+        }
+        let values = column.pages.iter().map(|(values, _)| values).sum();
+        written.push(Written { offset, size: file.len() - offset, values });
+    }
+
+    let mut footer = Compact::default();
+    footer.begin();
+    footer.i32(1, 1);
+    footer.list(2, TYPE_STRUCT, columns.len() + 1);
+    footer.begin();
+    footer.string(4, "schema");
+    footer.i32(5, columns.len() as i32);
+    footer.end();
+    for column in &columns {
+        schema_element(&mut footer, column);
+    }
+    footer.i64(3, rows.len() as i64);
+    footer.list(4, TYPE_STRUCT, 1);
+    footer.begin();
+    footer.list(1, TYPE_STRUCT, columns.len());
+    for (column, written) in columns.iter().zip(&written) {
+        footer.begin();
+        footer.i64(2, written.offset as i64);
+        footer.struct_field(3);
+        footer.i32(1, column.physical_type);
+        footer.list(2, TYPE_I32, 1);
+        footer.zigzag(PLAIN as i64);
+        footer.list(3, TYPE_BINARY, 1);
+        footer.varint(column.name.len() as u64);
+        footer.bytes.extend_from_slice(column.name.as_bytes());
+        footer.i32(4, UNCOMPRESSED);
+        footer.i64(5, written.values as i64);
+        footer.i64(6, written.size as i64);
+        footer.i64(7, written.size as i64);
+        footer.i64(9, written.offset as i64);
+        footer.end();
+        footer.end();
+    }
+    footer.i64(2, written.iter().map(|written| written.size as i64).sum());
+    footer.i64(3, rows.len() as i64);
+    footer.end();
+    footer.string(6, concat!("canopen-viewer version ", env!("CARGO_PKG_VERSION")));
+    footer.end();
+
+    file.extend_from_slice(&footer.bytes);
+    file.extend_from_slice(&(footer.bytes.len() as u32).to_le_bytes());
+    file.extend_from_slice(MAGIC);
+    file
+}
+
+pub fn write(path: &Path, epoch: DateTime<Local>, channels: &[ExportChannel]) -> io::Result<()> {
+    std::fs::write(path, encode(epoch, channels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parquet_layout() {
+        let mut thrift = Compact::default();
+        thrift.begin();
+        thrift.i32(1, 1);
+        thrift.i64(20, -2);
+        thrift.string(21, "ab");
+        thrift.end();
+        assert_eq!(thrift.bytes, [0x15, 0x02, 0x06, 0x28, 0x03, 0x18, 0x02, b'a', b'b', 0x00]);
+
+        let epoch = Local.timestamp_micros(1_700_000_000_000_000).unwrap();
+        let channels = [
+            ExportChannel { name: "a".to_string(), points: vec![[0.0, 1.0], [2.0, 3.0]] },
+            ExportChannel { name: "bc".to_string(), points: vec![[1.5, 10.0]] },
+        ];
+        let file = encode(epoch, &channels);
+        assert_eq!(&file[..4], MAGIC);
+        assert_eq!(&file[file.len() - 4..], MAGIC);
+        let footer = u32::from_le_bytes(file[file.len() - 8..file.len() - 4].try_into().unwrap()) as usize;
+        assert!(footer < file.len());
+
+        // Timestamps come first, ordered by time, right after the first page header
+        let header = [0x15, 0x00, 0x15, 0x30, 0x15, 0x30, 0x2C, 0x15, 0x06, 0x15, 0x00, 0x15, 0x06, 0x15, 0x06, 0x00, 0x00];
+        assert_eq!(&file[4..4 + header.len()], header);
+        let timestamps: Vec<i64> = file[4 + header.len()..4 + header.len() + 24].chunks(8)
+            .map(|bytes| i64::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        assert_eq!(timestamps, [1_700_000_000_000_000, 1_700_000_001_500_000, 1_700_000_002_000_000]);
+        let names = &file[4 + header.len() + 24 + header.len()..];
+        assert_eq!(&names[..5], [1, 0, 0, 0, b'a']);
+        assert_eq!(&names[5..11], [2, 0, 0, 0, b'b', b'c']);
+    }
+}
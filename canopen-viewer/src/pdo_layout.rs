@@ -0,0 +1,140 @@
+// pdo_layout.rs - Byte/bit diagram of a PDO mapping
+//
+// Draws the 8 data bytes of a PDO as 64 bit cells with one coloured segment per mapped
+// object, labelled with its name and bit width. Objects are packed from bit 0 in mapping
+// order (LSB first), so gaps, overlong mappings and odd widths stand out at a glance.
+use eframe::egui::{self, Align2, Color32, FontId, Pos2, Rect, Sense, Stroke, StrokeKind};
+
+use crate::communication::TpdoMappedObject;
+
+/// Data bits of a classic CAN frame
+const FRAME_BITS: usize = 64;
+
+const BYTE_ROW_HEIGHT: f32 = 14.0;
+const BIT_ROW_HEIGHT: f32 = 26.0;
+
+const SEGMENT_COLORS: [Color32; 8] = [
+    Color32::from_rgb(100, 149, 237),
+    Color32::from_rgb(102, 187, 106),
+    Color32::from_rgb(255, 183, 77),
+    Color32::from_rgb(186, 104, 200),
+    Color32::from_rgb(77, 182, 172),
+    Color32::from_rgb(240, 98, 146),
+    Color32::from_rgb(161, 136, 127),
+    Color32::from_rgb(144, 164, 174),
+];
+
+/// Bits of the frame taken by one mapped object
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub start_bit: usize,
+    pub bit_length: usize,
+    pub name: String,
+}
+
+impl Segment {
+    pub fn end_bit(&self) -> usize {
+        self.start_bit + self.bit_length
+    }
+}
+
+/// Segments of the mapped objects, packed from bit 0 in mapping order
+pub fn segments(mapped_objects: &[TpdoMappedObject]) -> Vec<Segment> {
+    let mut start_bit = 0;
+    mapped_objects.iter()
+        .map(|object| {
+            let segment = Segment { start_bit, bit_length: object.bit_length as usize, name: object.name.clone() };
+            start_bit = segment.end_bit();
+            segment
+        })
+        .collect()
+}
+
+/// Mapped bits that don't fit in the 8 data bytes
+pub fn overflow_bits(segments: &[Segment]) -> usize {
+    segments.last().map_or(0, |segment| segment.end_bit().saturating_sub(FRAME_BITS))
+}
+
+pub fn draw_mapping(ui: &mut egui::Ui, mapped_objects: &[TpdoMappedObject]) {
+    let segments = segments(mapped_objects);
+    let width = ui.available_width().clamp(320.0, 720.0);
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(width, BYTE_ROW_HEIGHT + BIT_ROW_HEIGHT), Sense::hover());
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    let bit_width = width / FRAME_BITS as f32;
+    let x_at = |bit: usize| rect.left() + bit.min(FRAME_BITS) as f32 * bit_width;
+    let bits_top = rect.top() + BYTE_ROW_HEIGHT;
+
+    for byte in 0..FRAME_BITS / 8 {
+        let center = Pos2::new((x_at(byte * 8) + x_at(byte * 8 + 8)) / 2.0, rect.top() + BYTE_ROW_HEIGHT / 2.0);
+        painter.text(center, Align2::CENTER_CENTER, format!("Byte {}", byte), FontId::proportional(10.0), visuals.weak_text_color());
+    }
+
+    let bits_rect = Rect::from_min_max(Pos2::new(rect.left(), bits_top), rect.max);
+    painter.rect_filled(bits_rect, 0.0, visuals.extreme_bg_color);
+
+    for (i, segment) in segments.iter().enumerate() {
+        if segment.bit_length == 0 || segment.start_bit >= FRAME_BITS {
+            continue;
+        }
+        let segment_rect = Rect::from_min_max(
+            Pos2::new(x_at(segment.start_bit), bits_top),
+            Pos2::new(x_at(segment.end_bit()), rect.bottom()),
+        );
+        painter.rect_filled(segment_rect.shrink(0.5), 2.0, SEGMENT_COLORS[i % SEGMENT_COLORS.len()]);
+
+        // Longest label that fits: name with width, name, width
+        let labels = [format!("{} ({} bit)", segment.name, segment.bit_length), segment.name.clone(), segment.bit_length.to_string()];
+        if let Some(label) = labels.into_iter().find(|label| {
+            painter.layout_no_wrap(label.clone(), FontId::proportional(11.0), Color32::BLACK).size().x + 4.0 <= segment_rect.width()
+        }) {
+            painter.text(segment_rect.center(), Align2::CENTER_CENTER, label, FontId::proportional(11.0), Color32::BLACK);
+        }
+
+        ui.interact(segment_rect, response.id.with(i), Sense::hover()).on_hover_text(format!(
+            "{}\nBits {}..{} ({} bit)",
+            segment.name, segment.start_bit, segment.end_bit() - 1, segment.bit_length
+        ));
+    }
+
+    // Faint bit ticks, stronger byte boundaries
+    for bit in 0..=FRAME_BITS {
+        let (top, stroke) = if bit % 8 == 0 {
+            (bits_top, Stroke::new(1.5, visuals.text_color()))
+        } else {
+            (rect.bottom() - 4.0, Stroke::new(0.5, visuals.weak_text_color()))
+        };
+        painter.line_segment([Pos2::new(x_at(bit), top), Pos2::new(x_at(bit), rect.bottom())], stroke);
+    }
+    painter.rect_stroke(bits_rect, 0.0, Stroke::new(1.0, visuals.text_color()), StrokeKind::Inside);
+
+    let overflow = overflow_bits(&segments);
+    if overflow > 0 {
+        ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ Mapping is {} bits longer than the 8 data bytes", overflow));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use canopen_common::SdoDataType;
+
+    #[test]
+    fn test_mapping_segments() {
+        let object = |name: &str, bit_length| TpdoMappedObject {
+            index: 0x6000,
+            sub_index: 1,
+            bit_length,
+            data_type: SdoDataType::UInt32,
+            name: name.to_string(),
+        };
+
+        let mapping = segments(&[object("Status", 16), object("Flag", 1), object("Position", 32)]);
+        assert_eq!(mapping.iter().map(|s| (s.start_bit, s.end_bit())).collect::<Vec<_>>(), vec![(0, 16), (16, 17), (17, 49)]);
+        assert_eq!(overflow_bits(&mapping), 0);
+
+        let too_long = segments(&[object("A", 32), object("B", 32), object("C", 8)]);
+        assert_eq!(overflow_bits(&too_long), 8);
+        assert_eq!(overflow_bits(&[]), 0);
+    }
+}
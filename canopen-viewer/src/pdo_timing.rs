@@ -0,0 +1,186 @@
+// pdo_timing.rs - Period, jitter and missed cycles of received TPDOs
+//
+// Computed from the frame timestamps (kernel time where available), or from the CAN
+// controller's clock when both frames of an interval carry a hardware timestamp. The
+// nominal period is the median of the recent intervals, so a few missed frames don't
+// shift it; an interval of about n periods counts as n - 1 missed cycles. Spread in the
+// intervals points at EMC or load problems on the device side.
+//
+// A TPDO is stale when nothing arrived for longer than its timeout: set by the user,
+// or a few cycles of its event timer (or measured period) so one late frame isn't flagged.
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Intervals the nominal period is taken from
+const PERIOD_WINDOW: usize = 64;
+
+/// An interval this many nominal periods long counts as a missed cycle
+const MISSED_THRESHOLD: f64 = 1.5;
+
+/// Cycles without a frame before a TPDO is stale when no timeout is set
+const STALE_CYCLES: u32 = 3;
+
+/// Timing statistics of one TPDO since it was started or reset
+#[derive(Debug, Clone, Default)]
+pub struct PdoTiming {
+    last: Option<DateTime<Local>>,
+    /// Controller clock reading of the last frame, if it had one
+    last_hardware: Option<Duration>,
+    recent: VecDeque<f64>,
+    /// Intervals in seconds: count, mean and sum of squared deviations (Welford)
+    count: usize,
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+    missed: u64,
+}
+
+impl PdoTiming {
+    /// Add a frame received at `at`, with the controller clock reading `hardware_time` if any
+    pub fn record(&mut self, at: DateTime<Local>, hardware_time: Option<Duration>) {
+        let last_hardware = std::mem::replace(&mut self.last_hardware, hardware_time);
+        let Some(last) = self.last.replace(at) else {
+            return;
+        };
+        let interval = match (last_hardware, hardware_time) {
+            (Some(last), Some(now)) => now.checked_sub(last).map_or(0.0, |interval| interval.as_secs_f64()),
+            _ => (at - last).num_microseconds().unwrap_or(0) as f64 / 1e6,
+        };
+        if interval <= 0.0 {
+            return;
+        }
+
+        // Judge against the period seen so far, before this interval shifts it
+        if let Some(period) = self.period() {
+            if interval > period * MISSED_THRESHOLD {
+                self.missed += ((interval / period).round() as u64).saturating_sub(1);
+            }
+        }
+
+        self.recent.push_back(interval);
+        if self.recent.len() > PERIOD_WINDOW {
+            self.recent.pop_front();
+        }
+
+        self.count += 1;
+        let delta = interval - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (interval - self.mean);
+        if self.count == 1 {
+            self.min = interval;
+            self.max = interval;
+        } else {
+            self.min = self.min.min(interval);
+            self.max = self.max.max(interval);
+        }
+    }
+
+    /// Nominal period in seconds (median of the recent intervals)
+    pub fn period(&self) -> Option<f64> {
+        if self.recent.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<f64> = self.recent.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        Some(sorted[sorted.len() / 2])
+    }
+
+    /// Shortest and longest interval in seconds
+    pub fn min_max(&self) -> Option<(f64, f64)> {
+        (self.count > 0).then_some((self.min, self.max))
+    }
+
+    /// Standard deviation of the intervals in seconds
+    pub fn std_dev(&self) -> Option<f64> {
+        (self.count > 1).then(|| (self.m2 / (self.count - 1) as f64).sqrt())
+    }
+
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+
+    pub fn frames(&self) -> usize {
+        if self.last.is_some() { self.count + 1 } else { 0 }
+    }
+
+    /// "10.00 ms" style summary for tables
+    pub fn period_text(&self) -> String {
+        self.period().map_or("—".to_string(), |period| format!("{:.2} ms", period * 1000.0))
+    }
+
+    /// Full statistics, one line
+    pub fn summary(&self) -> String {
+        let (Some(period), Some((min, max))) = (self.period(), self.min_max()) else {
+            return "Waiting for frames...".to_string();
+        };
+        let std_dev = self.std_dev().map_or("—".to_string(), |sd| format!("{:.3} ms", sd * 1000.0));
+        format!(
+            "Period {:.2} ms | min {:.2} / max {:.2} ms | σ {} | missed {} of {} frames",
+            period * 1000.0, min * 1000.0, max * 1000.0, std_dev, self.missed, self.frames()
+        )
+    }
+}
+
+/// Time without frames after which a TPDO is stale; `None` if nothing tells its rate
+///
+/// A user timeout wins, then the event timer (0x18xx sub-index 5), then the measured period.
+pub fn stale_timeout(user: Option<Duration>, event_timer_ms: Option<u16>, measured_period: Option<f64>) -> Option<Duration> {
+    user.or_else(|| event_timer_ms.filter(|ms| *ms > 0).map(|ms| Duration::from_millis(ms as u64) * STALE_CYCLES))
+        .or_else(|| measured_period.map(|period| Duration::from_secs_f64(period) * STALE_CYCLES))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    #[test]
+    fn test_period_jitter_and_missed_cycles() {
+        let start = Local::now();
+        let mut timing = PdoTiming::default();
+        let mut at = |ms: i64| timing.record(start + TimeDelta::milliseconds(ms), None);
+
+        // 10 ms period with one frame 1 ms late and two frames lost before 80
+        for ms in [0, 10, 20, 31, 40, 50, 80, 90] {
+            at(ms);
+        }
+
+        assert_eq!(timing.frames(), 8);
+        assert!((timing.period().unwrap() - 0.010).abs() < 1e-9);
+        let (min, max) = timing.min_max().unwrap();
+        assert!((min - 0.009).abs() < 1e-9);
+        assert!((max - 0.030).abs() < 1e-9);
+        assert_eq!(timing.missed(), 2);
+        assert!(timing.std_dev().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_hardware_clock_intervals() {
+        let start = Local::now();
+        let mut timing = PdoTiming::default();
+        // Receive times spread by scheduling, the controller clock is exact
+        timing.record(start, Some(Duration::from_secs(42)));
+        timing.record(start + TimeDelta::milliseconds(13), Some(Duration::from_millis(42_010)));
+        timing.record(start + TimeDelta::milliseconds(19), Some(Duration::from_millis(42_020)));
+        assert_eq!(timing.min_max(), Some((0.010, 0.010)));
+
+        // Without a hardware stamp on both frames the receive times are used
+        timing.record(start + TimeDelta::milliseconds(30), None);
+        timing.record(start + TimeDelta::milliseconds(40), Some(Duration::from_millis(42_040)));
+        assert_eq!(timing.frames(), 5);
+        let (min, max) = timing.min_max().unwrap();
+        assert!((min - 0.010).abs() < 1e-9 && (max - 0.011).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_stale_timeout() {
+        let ms = Duration::from_millis;
+        assert_eq!(stale_timeout(Some(ms(500)), Some(100), Some(0.1)), Some(ms(500)));
+        assert_eq!(stale_timeout(None, Some(100), Some(0.5)), Some(ms(300)));
+        // Event timer 0 = disabled
+        assert_eq!(stale_timeout(None, Some(0), Some(0.010)), Some(ms(30)));
+        assert_eq!(stale_timeout(None, None, None), None);
+    }
+}
@@ -0,0 +1,137 @@
+// presets.rs - Saved groups of subscriptions
+//
+// A preset records the polled SDO objects (with interval and type) and the TPDOs being
+// listened to, so a monitoring setup can be brought back with one click in a later
+// session or on another node of the same type. Presets are kept in the config file.
+use canopen_common::SdoDataType;
+use eframe::egui::{self, Color32};
+use serde::{Deserialize, Serialize};
+
+use crate::communication::SdoAddress;
+
+/// A polled SDO object of a preset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PresetSdo {
+    pub index: u16,
+    pub sub_index: u8,
+    pub interval_ms: u64,
+    pub data_type: SdoDataType,
+}
+
+impl PresetSdo {
+    pub fn address(&self) -> SdoAddress {
+        SdoAddress { index: self.index, sub_index: self.sub_index }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubscriptionPreset {
+    pub name: String,
+    #[serde(default)]
+    pub sdo: Vec<PresetSdo>,
+    /// Numbers of the TPDOs listened to
+    #[serde(default)]
+    pub tpdos: Vec<u8>,
+}
+
+impl SubscriptionPreset {
+    /// Preset of the given subscriptions, sorted so saved files diff cleanly
+    pub fn capture(name: &str, mut sdo: Vec<PresetSdo>, mut tpdos: Vec<u8>) -> Self {
+        sdo.sort_by_key(|entry| (entry.index, entry.sub_index));
+        tpdos.sort_unstable();
+        Self { name: name.trim().to_string(), sdo, tpdos }
+    }
+
+    fn summary(&self) -> String {
+        format!("{} SDO, {} TPDO", self.sdo.len(), self.tpdos.len())
+    }
+}
+
+pub enum PresetAction {
+    /// Save the current subscriptions under this name, replacing a preset of the same name
+    Save(String),
+    Apply(usize),
+    Delete(usize),
+}
+
+/// "Presets" menu of the Active Subscriptions panel
+pub struct PresetMenu {
+    name: String,
+    /// Outcome of the last apply, e.g. TPDOs that weren't discovered
+    status: Option<Result<String, String>>,
+}
+
+impl PresetMenu {
+    pub fn new() -> Self {
+        Self { name: String::new(), status: None }
+    }
+
+    pub fn set_status(&mut self, status: Result<String, String>) {
+        self.status = Some(status);
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, presets: &[SubscriptionPreset], has_subscriptions: bool) -> Option<PresetAction> {
+        let mut action = None;
+
+        ui.menu_button("📑 Presets", |ui| {
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.name).hint_text("Preset name").desired_width(140.0));
+                let can_save = has_subscriptions && !self.name.trim().is_empty();
+                if ui.add_enabled(can_save, egui::Button::new("💾 Save current")).clicked() {
+                    action = Some(PresetAction::Save(self.name.trim().to_string()));
+                    self.name.clear();
+                }
+            });
+            ui.separator();
+
+            if presets.is_empty() {
+                ui.weak("No presets saved yet.");
+            }
+            for (i, preset) in presets.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.button(format!("▶ {}", preset.name)).on_hover_text(preset.summary()).clicked() {
+                        action = Some(PresetAction::Apply(i));
+                        ui.close_menu();
+                    }
+                    ui.weak(preset.summary());
+                    if ui.small_button("🗑").on_hover_text("Delete preset").clicked() {
+                        action = Some(PresetAction::Delete(i));
+                    }
+                });
+            }
+        });
+
+        match &self.status {
+            Some(Ok(message)) => {
+                ui.colored_label(Color32::from_rgb(0, 200, 0), message);
+            }
+            Some(Err(message)) => {
+                ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", message));
+            }
+            None => {}
+        }
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_round_trip() {
+        let entry = |index, sub_index| PresetSdo { index, sub_index, interval_ms: 100, data_type: SdoDataType::Int16 };
+        let preset = SubscriptionPreset::capture(" Drive ", vec![entry(0x6064, 0), entry(0x6041, 0)], vec![3, 1]);
+        assert_eq!(preset.name, "Drive");
+        assert_eq!(preset.sdo[0].address(), SdoAddress { index: 0x6041, sub_index: 0 });
+        assert_eq!(preset.tpdos, vec![1, 3]);
+
+        #[derive(Serialize, Deserialize)]
+        struct Presets {
+            presets: Vec<SubscriptionPreset>,
+        }
+        let text = toml::to_string_pretty(&Presets { presets: vec![preset.clone()] }).unwrap();
+        let loaded: Presets = toml::from_str(&text).unwrap();
+        assert_eq!(loaded.presets, vec![preset]);
+    }
+}
@@ -0,0 +1,208 @@
+// queue.rs - Bounded queue between the connection manager, the communication thread and the UI
+//
+// When the queue is full the oldest sample is discarded to make room, so a fast PDO stream
+// can never grow memory without limit. Control messages are never discarded. The number of
+// dropped samples is reported to the receiver, either as a synthesized item
+// (`QueueItem::overflow`) or via `Receiver::take_dropped`.
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// Items that can travel through a bounded queue
+pub trait QueueItem: Sized {
+    /// Whether this item may be discarded to make room when the queue is full
+    fn is_sample(&self) -> bool;
+
+    /// Item handed to the receiver after `dropped` samples were discarded
+    ///
+    /// Returning `None` leaves the count for [`Receiver::take_dropped`].
+    fn overflow(_dropped: u64) -> Option<Self> {
+        None
+    }
+}
+
+struct Shared<T> {
+    queue: Mutex<VecDeque<T>>,
+    capacity: usize,
+    dropped: AtomicU64,
+    senders: AtomicUsize,
+    receiver_alive: AtomicBool,
+    notify: Notify,
+}
+
+/// Sending half of a bounded queue
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Receiving half of a bounded queue
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Create a bounded queue holding at most `capacity` items
+pub fn channel<T: QueueItem>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(VecDeque::with_capacity(capacity)),
+        capacity,
+        dropped: AtomicU64::new(0),
+        senders: AtomicUsize::new(1),
+        receiver_alive: AtomicBool::new(true),
+        notify: Notify::new(),
+    });
+
+    (Sender { shared: shared.clone() }, Receiver { shared })
+}
+
+impl<T: QueueItem> Sender<T> {
+    /// Queue an item, discarding the oldest sample if the queue is full
+    ///
+    /// Fails only when the receiver has been dropped.
+    pub fn send(&self, item: T) -> Result<(), T> {
+        if !self.shared.receiver_alive.load(Ordering::Acquire) {
+            return Err(item);
+        }
+
+        {
+            let mut queue = self.shared.queue.lock().unwrap();
+            if queue.len() >= self.shared.capacity {
+                if let Some(position) = queue.iter().position(|queued| queued.is_sample()) {
+                    queue.remove(position);
+                    self.shared.dropped.fetch_add(1, Ordering::AcqRel);
+                } else if item.is_sample() {
+                    // Only control messages are queued - drop the new sample instead
+                    self.shared.dropped.fetch_add(1, Ordering::AcqRel);
+                    return Ok(());
+                }
+            }
+            queue.push_back(item);
+        }
+
+        self.shared.notify.notify_one();
+        Ok(())
+    }
+
+    /// Whether the receiver has been dropped
+    pub fn is_closed(&self) -> bool {
+        !self.shared.receiver_alive.load(Ordering::Acquire)
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::AcqRel);
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            // Wake the receiver so it can observe the disconnect
+            self.shared.notify.notify_one();
+        }
+    }
+}
+
+impl<T: QueueItem> Receiver<T> {
+    /// Take the next item without waiting
+    pub fn try_recv(&self) -> Option<T> {
+        if let Some(report) = self.take_overflow_report() {
+            return Some(report);
+        }
+        self.shared.queue.lock().unwrap().pop_front()
+    }
+
+    /// Wait for the next item; returns `None` once all senders are gone and the queue is drained
+    pub async fn recv(&self) -> Option<T> {
+        loop {
+            if let Some(item) = self.try_recv() {
+                return Some(item);
+            }
+            if self.shared.senders.load(Ordering::Acquire) == 0 {
+                return None;
+            }
+            self.shared.notify.notified().await;
+        }
+    }
+
+    /// Number of samples discarded since the last call (for items without an overflow report)
+    pub fn take_dropped(&self) -> u64 {
+        self.shared.dropped.swap(0, Ordering::AcqRel)
+    }
+
+    fn take_overflow_report(&self) -> Option<T> {
+        let dropped = self.shared.dropped.load(Ordering::Acquire);
+        if dropped == 0 {
+            return None;
+        }
+
+        let report = T::overflow(dropped)?;
+        self.shared.dropped.fetch_sub(dropped, Ordering::AcqRel);
+        Some(report)
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").field("capacity", &self.shared.capacity).finish()
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").field("capacity", &self.shared.capacity).finish()
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.receiver_alive.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Item {
+        Sample(u32),
+        Control,
+        Overflow(u64),
+    }
+
+    impl QueueItem for Item {
+        fn is_sample(&self) -> bool {
+            matches!(self, Item::Sample(_))
+        }
+
+        fn overflow(dropped: u64) -> Option<Self> {
+            Some(Item::Overflow(dropped))
+        }
+    }
+
+    #[test]
+    fn test_full_queue_drops_oldest_sample_and_keeps_control() {
+        let (tx, rx) = channel(3);
+        tx.send(Item::Control).unwrap();
+        tx.send(Item::Sample(1)).unwrap();
+        tx.send(Item::Sample(2)).unwrap();
+        tx.send(Item::Sample(3)).unwrap();
+
+        assert_eq!(rx.try_recv(), Some(Item::Overflow(1)));
+        assert_eq!(rx.try_recv(), Some(Item::Control));
+        assert_eq!(rx.try_recv(), Some(Item::Sample(2)));
+        assert_eq!(rx.try_recv(), Some(Item::Sample(3)));
+        assert_eq!(rx.try_recv(), None);
+    }
+
+    #[test]
+    fn test_send_fails_after_receiver_dropped() {
+        let (tx, rx) = channel(2);
+        drop(rx);
+        assert_eq!(tx.send(Item::Sample(1)), Err(Item::Sample(1)));
+    }
+}
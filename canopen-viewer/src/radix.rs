@@ -0,0 +1,84 @@
+// radix.rs - Hex / decimal / binary display of integer values
+//
+// Values arrive as decimal strings. Negative numbers are shown in hex and binary as
+// the two's complement of the object's size, e.g. -2 as INTEGER16 is 0xFFFE.
+use canopen_common::SdoDataType;
+use eframe::egui;
+
+use crate::sdo_write::integer_size;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Radix {
+    #[default]
+    Decimal,
+    Hex,
+    Binary,
+}
+
+impl Radix {
+    /// Radix selected by the next click on the toggle
+    pub fn next(self) -> Self {
+        match self {
+            Self::Decimal => Self::Hex,
+            Self::Hex => Self::Binary,
+            Self::Binary => Self::Decimal,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Decimal => "Dec",
+            Self::Hex => "Hex",
+            Self::Binary => "Bin",
+        }
+    }
+
+    /// Format a decimal value string; anything that isn't an integer is returned unchanged
+    pub fn format(self, value: &str, data_type: &SdoDataType) -> String {
+        let (Some(size), Ok(number)) = (integer_size(data_type), value.parse::<i64>()) else {
+            return value.to_string();
+        };
+        let bits = size * 8;
+        let raw = (number as u64) & (u64::MAX >> (64 - bits));
+
+        match self {
+            Self::Decimal => value.to_string(),
+            Self::Hex => format!("0x{:0width$X}", raw, width = size * 2),
+            Self::Binary => {
+                let digits = format!("{:0width$b}", raw, width = bits);
+                // Group by nibble so status and control words stay readable
+                let nibbles: Vec<&str> = (0..bits / 4).map(|i| &digits[i * 4..i * 4 + 4]).collect();
+                format!("0b{}", nibbles.join("_"))
+            }
+        }
+    }
+}
+
+/// Button showing the current radix; a click switches to the next one
+///
+/// Shown only for integer types, other values have nothing to toggle.
+pub fn radix_toggle(ui: &mut egui::Ui, radix: &mut Radix, data_type: &SdoDataType) {
+    if integer_size(data_type).is_none() {
+        return;
+    }
+    if ui.small_button(radix.label()).on_hover_text("Switch between decimal, hex and binary").clicked() {
+        *radix = radix.next();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_radix() {
+        assert_eq!(Radix::Hex.format("4660", &SdoDataType::UInt16), "0x1234");
+        assert_eq!(Radix::Hex.format("-2", &SdoDataType::Int16), "0xFFFE");
+        assert_eq!(Radix::Binary.format("6", &SdoDataType::UInt8), "0b0000_0110");
+        assert_eq!(Radix::Decimal.format("-2", &SdoDataType::Int16), "-2");
+
+        // Non-integer values are left alone
+        assert_eq!(Radix::Hex.format("1.5", &SdoDataType::Real32), "1.5");
+        assert_eq!(Radix::Hex.format("abc", &SdoDataType::UInt8), "abc");
+    }
+}
@@ -0,0 +1,486 @@
+// report.rs - PDF test report of a session
+//
+// A test protocol needs a record of which device was measured, what the signals did and
+// which faults came up. The report puts the session details, the chosen plots, the
+// statistics of every channel and the alarms and emergencies into one PDF to attach to it.
+// The file is written directly: A4 pages with the standard Helvetica fonts, and the plots
+// as vector paths laid out like the SVG export, so no PDF library is needed.
+use configparser::ini::Ini;
+use eframe::egui::{self, Color32};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::channel_style::Dash;
+use crate::decimation;
+use crate::svg_export::{self, SvgPlot};
+
+const PAGE_WIDTH: f64 = 595.0;
+const PAGE_HEIGHT: f64 = 842.0;
+const MARGIN: f64 = 50.0;
+/// Height of the axes of a plot, without title, tick labels and legend
+const PLOT_HEIGHT: f64 = 170.0;
+/// Room left of the axes for the Y tick labels and axis label
+const PLOT_LEFT: f64 = 55.0;
+/// Envelope buckets per series, a few per point of the plot width
+const PDF_BUCKETS: usize = 1000;
+const LABEL_WIDTH: f64 = 150.0;
+/// Statistics in the channel table, by the labels `ChannelStats::rows` uses
+const STAT_COLUMNS: [&str; 6] = ["Samples", "Min", "Max", "Mean", "Std dev", "Rate (Hz)"];
+
+pub struct ReportChannel {
+    pub name: String,
+    /// Label and value of each statistic
+    pub stats: Vec<(&'static str, String)>,
+}
+
+pub struct Report {
+    pub title: String,
+    /// Session, connection and device details as label and value
+    pub details: Vec<(String, String)>,
+    pub plots: Vec<SvgPlot>,
+    pub channels: Vec<ReportChannel>,
+    /// Time, kind and text of the alarms and emergencies
+    pub events: Vec<[String; 3]>,
+}
+
+/// Vendor and product from the `[DeviceInfo]` section of an EDS, as label and value
+pub fn device_info(path: &Path) -> Vec<(String, String)> {
+    let mut eds = Ini::new();
+    if eds.load(path).is_err() {
+        return Vec::new();
+    }
+    [
+        ("VendorName", "Vendor"),
+        ("VendorNumber", "Vendor ID"),
+        ("ProductName", "Product"),
+        ("ProductNumber", "Product code"),
+        ("RevisionNumber", "Revision"),
+        ("OrderCode", "Order code"),
+    ]
+    .into_iter()
+    .filter_map(|(key, label)| Some((label.to_string(), eds.get("DeviceInfo", key).filter(|value| !value.is_empty())?)))
+    .collect()
+}
+
+/// PDF string literal of `text` in WinAnsi encoding; characters outside Latin-1 become '?'
+fn pdf_string(text: &str) -> String {
+    let mut literal = String::from("(");
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                literal.push('\\');
+                literal.push(c);
+            }
+            ' '..='~' => literal.push(c),
+            '\u{a0}'..='\u{ff}' => {
+                let _ = write!(literal, "\\{:03o}", c as u32);
+            }
+            _ => literal.push('?'),
+        }
+    }
+    literal.push(')');
+    literal
+}
+
+/// Rough width of Helvetica text, enough to align and wrap it
+fn text_width(text: &str, size: f64) -> f64 {
+    text.chars().count() as f64 * size * 0.52
+}
+
+/// `text` broken into lines of at most `width` at the given size, at spaces where possible
+fn wrap(text: &str, width: f64, size: f64) -> Vec<String> {
+    let max_chars = ((width / (size * 0.52)) as usize).max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split(' ') {
+        let mut word = word.to_string();
+        while word.chars().count() > max_chars {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            let rest = word.chars().skip(max_chars).collect();
+            lines.push(word.chars().take(max_chars).collect());
+            word = rest;
+        }
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_chars {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    lines.push(line);
+    lines
+}
+
+/// Stroke color operator of an RGB color
+fn stroke_color([r, g, b]: [u8; 3]) -> String {
+    format!("{:.3} {:.3} {:.3} RG", r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0)
+}
+
+fn dash_pattern(dash: Dash) -> &'static str {
+    match dash {
+        Dash::Solid => "[] 0 d",
+        Dash::Dashed => "[6 3] 0 d",
+        Dash::Dotted => "[1.5 2] 0 d",
+    }
+}
+
+/// Content streams of the pages, filled from the top down
+struct Pages {
+    done: Vec<String>,
+    current: String,
+    /// Top of the free space on the current page
+    y: f64,
+}
+
+impl Pages {
+    fn new() -> Self {
+        Self { done: Vec::new(), current: String::new(), y: PAGE_HEIGHT - MARGIN }
+    }
+
+    fn new_page(&mut self) {
+        self.done.push(std::mem::take(&mut self.current));
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    /// Continue on a new page unless `height` fits on this one
+    fn reserve(&mut self, height: f64) {
+        if self.y - height < MARGIN && self.y < PAGE_HEIGHT - MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn text_at(&mut self, x: f64, y: f64, size: f64, bold: bool, text: &str) {
+        let font = if bold { "F2" } else { "F1" };
+        let _ = writeln!(self.current, "BT /{} {} Tf {:.2} {:.2} Td {} Tj ET", font, size, x, y, pdf_string(text));
+    }
+
+    /// A line of text below the previous one
+    fn line(&mut self, size: f64, bold: bool, text: &str) {
+        self.reserve(size * 1.4);
+        self.y -= size * 1.4;
+        self.text_at(MARGIN, self.y, size, bold, text);
+    }
+
+    fn heading(&mut self, text: &str) {
+        // Keep a heading together with the start of its section
+        self.reserve(60.0);
+        self.y -= 12.0;
+        self.line(13.0, true, text);
+        self.y -= 4.0;
+    }
+
+    /// A table row; each cell is wrapped to its column, `widths` are the column widths
+    fn row(&mut self, cells: &[&str], widths: &[f64], bold: bool) {
+        let size = 9.0;
+        let wrapped: Vec<Vec<String>> = cells.iter().zip(widths).map(|(cell, width)| wrap(cell, width - 6.0, size)).collect();
+        let lines = wrapped.iter().map(Vec::len).max().unwrap_or(1);
+        self.reserve(lines as f64 * size * 1.3);
+        let mut x = MARGIN;
+        for (cell, width) in wrapped.iter().zip(widths) {
+            for (line_number, line) in cell.iter().enumerate() {
+                self.text_at(x, self.y - (line_number + 1) as f64 * size * 1.3, size, bold, line);
+            }
+            x += width;
+        }
+        self.y -= lines as f64 * size * 1.3;
+    }
+
+    fn plot(&mut self, plot: &SvgPlot) {
+        let legend_height = plot.series.len() as f64 * 11.0;
+        self.reserve(PLOT_HEIGHT + legend_height + 60.0);
+        self.y -= 14.0;
+        self.text_at(MARGIN, self.y, 11.0, true, &plot.title);
+
+        let ((x_min, x_max), (y_min, y_max)) = svg_export::axis_ranges(plot);
+        let left = MARGIN + PLOT_LEFT;
+        let width = PAGE_WIDTH - MARGIN - left;
+        let top = self.y - 8.0;
+        let bottom = top - PLOT_HEIGHT;
+        let to_x = |x: f64| left + (x - x_min) / (x_max - x_min) * width;
+        let to_y = |y: f64| bottom + (y - y_min) / (y_max - y_min) * PLOT_HEIGHT;
+
+        // Grid and tick labels
+        self.current.push_str("0.85 G 0.4 w\n");
+        let x_ticks = svg_export::nice_ticks(x_min, x_max, 6);
+        let y_ticks = svg_export::nice_ticks(y_min, y_max, 5);
+        for x in &x_ticks {
+            let _ = writeln!(self.current, "{:.2} {:.2} m {:.2} {:.2} l S", to_x(*x), bottom, to_x(*x), top);
+        }
+        for y in &y_ticks {
+            let _ = writeln!(self.current, "{:.2} {:.2} m {:.2} {:.2} l S", left, to_y(*y), left + width, to_y(*y));
+        }
+        let _ = writeln!(self.current, "0 G 0.8 w {:.2} {:.2} {:.2} {:.2} re S", left, bottom, width, PLOT_HEIGHT);
+        for x in x_ticks {
+            let label = (plot.x_format)(x);
+            self.text_at(to_x(x) - text_width(&label, 7.0) / 2.0, bottom - 10.0, 7.0, false, &label);
+        }
+        for y in y_ticks {
+            let label = (plot.y_format)(y);
+            self.text_at(left - 4.0 - text_width(&label, 7.0), to_y(y) - 2.5, 7.0, false, &label);
+        }
+        self.text_at(left + width / 2.0 - text_width(&plot.x_label, 8.0) / 2.0, bottom - 22.0, 8.0, false, &plot.x_label);
+        let _ = writeln!(self.current, "BT /F1 8 Tf 0 1 -1 0 {:.2} {:.2} Tm {} Tj ET",
+            MARGIN + 8.0, bottom + PLOT_HEIGHT / 2.0 - text_width(&plot.y_label, 8.0) / 2.0, pdf_string(&plot.y_label));
+
+        // Series and markers, clipped to the axes
+        let _ = writeln!(self.current, "q {:.2} {:.2} {:.2} {:.2} re W n", left, bottom, width, PLOT_HEIGHT);
+        for series in &plot.series {
+            let points = decimation::min_max(series.points.iter(), PDF_BUCKETS);
+            let Some((first, rest)) = points.split_first() else {
+                continue;
+            };
+            let _ = write!(self.current, "{} {} w {} {:.2} {:.2} m", stroke_color(series.style.color), series.style.width, dash_pattern(series.style.dash), to_x(first[0]), to_y(first[1]));
+            for [x, y] in rest {
+                let _ = write!(self.current, " {:.2} {:.2} l", to_x(*x), to_y(*y));
+            }
+            self.current.push_str(" S\n");
+        }
+        for (time, text) in plot.markers.iter().filter(|(time, _)| (x_min..=x_max).contains(time)) {
+            let _ = writeln!(self.current, "{} 0.8 w {} {:.2} {:.2} m {:.2} {:.2} l S", stroke_color([100, 149, 237]), dash_pattern(Dash::Dashed), to_x(*time), bottom, to_x(*time), top);
+            self.text_at(to_x(*time) + 2.0, top - 8.0, 7.0, false, text);
+        }
+        self.current.push_str("Q\n");
+
+        // Legend under the axes
+        self.y = bottom - 30.0;
+        for series in &plot.series {
+            self.y -= 11.0;
+            let _ = writeln!(self.current, "{} 1.5 w {} {:.2} {:.2} m {:.2} {:.2} l S [] 0 d", stroke_color(series.style.color), dash_pattern(series.style.dash), left, self.y + 3.0, left + 20.0, self.y + 3.0);
+            self.text_at(left + 26.0, self.y, 8.0, false, &series.name);
+        }
+        self.y -= 10.0;
+    }
+
+    /// The finished pages, each with a footer
+    fn finish(mut self, title: &str) -> Vec<String> {
+        self.new_page();
+        let count = self.done.len();
+        let mut pages = self.done;
+        for (index, page) in pages.iter_mut().enumerate() {
+            let footer = format!("{} - page {} of {}", title, index + 1, count);
+            let _ = writeln!(page, "BT /F1 8 Tf {:.2} {:.2} Td {} Tj ET", PAGE_WIDTH - MARGIN - text_width(&footer, 8.0), MARGIN / 2.0, pdf_string(&footer));
+        }
+        pages
+    }
+}
+
+/// PDF file of the content streams, one page each
+fn assemble(pages: &[String]) -> Vec<u8> {
+    // 1: catalog, 2: page tree, 3 and 4: fonts, then each page followed by its content stream
+    let kids: Vec<String> = (0..pages.len()).map(|index| format!("{} 0 R", 5 + 2 * index)).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_string(),
+    ];
+    for (index, content) in pages.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH, PAGE_HEIGHT, 6 + 2 * index
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content));
+    }
+
+    // The content is ASCII, so string and byte offsets agree
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        let _ = write!(pdf, "{} 0 obj\n{}\nendobj\n", index + 1, object);
+    }
+    let xref = pdf.len();
+    let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(pdf, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(pdf, "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref);
+    pdf.into_bytes()
+}
+
+pub fn render(report: &Report) -> Vec<u8> {
+    let mut pages = Pages::new();
+    pages.line(18.0, true, &report.title);
+    pages.y -= 6.0;
+
+    pages.heading("Session");
+    for (label, value) in &report.details {
+        pages.row(&[label, value], &[LABEL_WIDTH, PAGE_WIDTH - 2.0 * MARGIN - LABEL_WIDTH], false);
+    }
+
+    if !report.plots.is_empty() {
+        pages.heading("Plots");
+        for plot in &report.plots {
+            pages.plot(plot);
+        }
+    }
+
+    pages.heading("Channel statistics");
+    if report.channels.is_empty() {
+        pages.line(9.0, false, "No channels recorded");
+    } else {
+        let mut widths = vec![175.0];
+        widths.extend([(PAGE_WIDTH - 2.0 * MARGIN - 175.0) / STAT_COLUMNS.len() as f64; STAT_COLUMNS.len()]);
+        let mut header = vec!["Channel"];
+        header.extend(STAT_COLUMNS);
+        pages.row(&header, &widths, true);
+        for channel in &report.channels {
+            let mut cells = vec![channel.name.as_str()];
+            cells.extend(STAT_COLUMNS.iter().map(|column| {
+                channel.stats.iter().find(|(label, _)| label == column).map_or("", |(_, value)| value.as_str())
+            }));
+            pages.row(&cells, &widths, false);
+        }
+    }
+
+    pages.heading("Alarms and emergencies");
+    if report.events.is_empty() {
+        pages.line(9.0, false, "None recorded");
+    } else {
+        let widths = [110.0, 60.0, PAGE_WIDTH - 2.0 * MARGIN - 170.0];
+        pages.row(&["Time", "Type", "Event"], &widths, true);
+        for [time, kind, text] in &report.events {
+            pages.row(&[time, kind, text], &widths, false);
+        }
+    }
+
+    assemble(&pages.finish(&report.title))
+}
+
+pub fn save(path: &Path, report: &Report) -> io::Result<()> {
+    std::fs::write(path, render(report))
+}
+
+/// Window to pick the plots of the report and generate it
+pub struct ReportPanel {
+    pub open: bool,
+    pub title: String,
+    /// Titles of the plots left out; plots added later are included
+    excluded: HashSet<String>,
+    result: Option<Result<PathBuf, String>>,
+}
+
+impl ReportPanel {
+    pub fn new() -> Self {
+        Self { open: false, title: "Test report".to_string(), excluded: HashSet::new(), result: None }
+    }
+
+    pub fn includes(&self, plot: &str) -> bool {
+        !self.excluded.contains(plot)
+    }
+
+    /// Where the report was written, or why it wasn't
+    pub fn finished(&mut self, result: Result<PathBuf, String>) {
+        self.result = Some(result);
+    }
+
+    /// `plots` are the titles of the plots on the dashboard. Returns true when the user
+    /// asked for the report.
+    pub fn show(&mut self, ctx: &egui::Context, plots: &[String]) -> bool {
+        let mut generate = false;
+        let mut open = self.open;
+        egui::Window::new("Test Report")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Title:");
+                    ui.text_edit_singleline(&mut self.title);
+                });
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.strong("Plots");
+                    if ui.small_button("All").clicked() {
+                        self.excluded.clear();
+                    }
+                    if ui.small_button("None").clicked() {
+                        self.excluded.extend(plots.iter().cloned());
+                    }
+                });
+                if plots.is_empty() {
+                    ui.weak("Nothing plotted on this dashboard");
+                }
+                for plot in plots {
+                    let mut included = self.includes(plot);
+                    if ui.checkbox(&mut included, plot).changed() {
+                        if included {
+                            self.excluded.remove(plot);
+                        } else {
+                            self.excluded.insert(plot.clone());
+                        }
+                    }
+                }
+                ui.separator();
+                ui.weak("Session details, device identity, statistics of every channel and the alarms and emergencies are always included.");
+
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!self.title.trim().is_empty(), egui::Button::new("📄 Generate PDF")).clicked() {
+                        generate = true;
+                    }
+                    match &self.result {
+                        Some(Ok(path)) => {
+                            ui.colored_label(Color32::from_rgb(0, 200, 0), format!("Saved {}", path.file_name().unwrap_or_default().to_string_lossy()))
+                                .on_hover_text(path.display().to_string());
+                        }
+                        Some(Err(error)) => {
+                            ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", error));
+                        }
+                        None => {}
+                    }
+                });
+            });
+        self.open = open;
+        generate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel_style::ChannelStyle;
+    use crate::svg_export::SvgSeries;
+
+    #[test]
+    fn test_report_pdf_structure() {
+        assert_eq!(pdf_string("a(b)\\ 25°C → x"), "(a\\(b\\)\\\\ 25\\260C ? x)");
+        assert_eq!(wrap("one two three", 60.0, 10.0), vec!["one two", "three"]);
+
+        let plot = SvgPlot {
+            title: "Velocity".to_string(),
+            x_label: "Time (seconds)".to_string(),
+            y_label: "rpm".to_string(),
+            series: vec![SvgSeries { name: "Velocity".to_string(), style: ChannelStyle::new([1, 2, 3]), points: vec![[0.0, 0.0], [1.0, 5.0]] }],
+            markers: vec![(0.5, "Start".to_string())],
+            y_range: None,
+            x_format: Box::new(|value| format!("{}", value)),
+            y_format: Box::new(|value| format!("{}", value)),
+        };
+        let report = Report {
+            title: "Run 1".to_string(),
+            details: vec![("Node ID".to_string(), "5".to_string())],
+            plots: vec![plot],
+            channels: vec![ReportChannel { name: "Velocity".to_string(), stats: vec![("Samples", "2".to_string()), ("Max", "5.0000".to_string())] }],
+            // Enough rows to need a second page
+            events: (0..80).map(|i| [format!("12:00:{:02}", i % 60), "EMCY".to_string(), format!("Node 5: fault {}", i)]).collect(),
+        };
+        let pdf = String::from_utf8(render(&report)).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("/Count 2 >>"));
+        assert!(pdf.contains("(Run 1 - page 2 of 2) Tj"));
+        assert!(pdf.contains("0.004 0.008 0.012 RG"));
+        assert!(pdf.contains("(Node 5: fault 79) Tj"));
+
+        // The cross-reference table points at the objects
+        let xref: usize = pdf.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(pdf[xref..].starts_with("xref\n0 9\n"));
+        let third: usize = pdf[xref..].lines().nth(5).unwrap()[..10].parse().unwrap();
+        assert!(pdf[third..].starts_with("3 0 obj\n"));
+    }
+}
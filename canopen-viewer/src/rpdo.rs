@@ -0,0 +1,302 @@
+// rpdo.rs - Composing and transmitting the node's RPDOs
+//
+// The RPDO layout is read from the device (0x1400 / 0x1600) and every mapped object
+// gets an input matching its type. The values are packed little-endian at their bit
+// offsets into one frame, which the communication thread sends once or cyclically -
+// so the viewer can drive a device (setpoints, control words), not just watch it.
+use canopen_common::SdoDataType;
+use eframe::egui::{self, Color32};
+
+use crate::communication::TpdoMappedObject;
+use crate::pdo_layout;
+use crate::sdo_write::{self, WriteValue};
+
+/// Layout of one RPDO; mapped objects use the same description as TPDOs
+#[derive(Debug, Clone)]
+pub struct RpdoConfig {
+    pub rpdo_number: u8,
+    pub cob_id: u16,
+    pub mapped_objects: Vec<TpdoMappedObject>,
+}
+
+/// Starting value of a mapped object; single bits are booleans
+pub fn initial_value(object: &TpdoMappedObject) -> WriteValue {
+    match object.data_type {
+        _ if object.bit_length == 1 => WriteValue::Boolean(false),
+        SdoDataType::Real32 => WriteValue::Real(0.0),
+        SdoDataType::VisibleString | SdoDataType::OctetString => WriteValue::Text(String::new()),
+        _ => WriteValue::Integer(0),
+    }
+}
+
+/// Frame data for the values of the mapped objects, each at its bit offset
+pub fn pack(mapped_objects: &[TpdoMappedObject], values: &[WriteValue]) -> Result<Vec<u8>, String> {
+    let mut frame = 0u64;
+    let mut offset = 0usize;
+
+    for (object, value) in mapped_objects.iter().zip(values) {
+        let bits = object.bit_length as usize;
+        if bits == 0 {
+            continue;
+        }
+        if offset + bits > 64 {
+            return Err("Mapping is longer than 8 bytes".to_string());
+        }
+        let bytes = value.encode(&object.data_type).map_err(|err| format!("{}: {}", object.name, err))?;
+        let mut raw = [0u8; 8];
+        let len = bytes.len().min(8);
+        raw[..len].copy_from_slice(&bytes[..len]);
+        // Objects mapped with fewer bits than their type keep the low bits
+        let raw = u64::from_le_bytes(raw) & (u64::MAX >> (64 - bits));
+        frame |= raw << offset;
+        offset += bits;
+    }
+
+    Ok(frame.to_le_bytes()[..offset.div_ceil(8)].to_vec())
+}
+
+pub enum RpdoAction {
+    Discover,
+    /// Transmit one frame
+    Send { rpdo_number: u8, cob_id: u16, data: Vec<u8> },
+    /// Transmit the frame every `interval_ms`, replacing a running transmission of this RPDO
+    Start { rpdo_number: u8, cob_id: u16, data: Vec<u8>, interval_ms: u64 },
+    Stop(u8),
+}
+
+struct RpdoState {
+    config: RpdoConfig,
+    values: Vec<WriteValue>,
+    interval_ms: u64,
+    /// Data of the running cyclic transmission
+    cyclic_data: Option<Vec<u8>>,
+    sent: usize,
+    status: Option<Result<String, String>>,
+}
+
+pub struct RpdoPanel {
+    pub open: bool,
+    rpdos: Vec<RpdoState>,
+    discovering: bool,
+    discovered: bool,
+}
+
+impl RpdoPanel {
+    pub fn new() -> Self {
+        Self { open: false, rpdos: Vec::new(), discovering: false, discovered: false }
+    }
+
+    pub fn discovered(&mut self, configs: Vec<RpdoConfig>) {
+        self.rpdos = configs.into_iter()
+            .map(|config| RpdoState {
+                values: config.mapped_objects.iter().map(initial_value).collect(),
+                config,
+                interval_ms: 100,
+                cyclic_data: None,
+                sent: 0,
+                status: None,
+            })
+            .collect();
+        self.discovering = false;
+        self.discovered = true;
+    }
+
+    pub fn transmitted(&mut self, rpdo_number: u8, result: Result<(), String>) {
+        if let Some(rpdo) = self.rpdos.iter_mut().find(|rpdo| rpdo.config.rpdo_number == rpdo_number) {
+            match result {
+                Ok(()) => {
+                    rpdo.sent += 1;
+                    rpdo.status = Some(Ok(format!("{} frames sent", rpdo.sent)));
+                }
+                Err(err) => rpdo.status = Some(Err(err)),
+            }
+        }
+    }
+
+    /// A cyclic transmission ended because a frame couldn't be sent
+    pub fn stopped(&mut self, rpdo_number: u8, error: String) {
+        if let Some(rpdo) = self.rpdos.iter_mut().find(|rpdo| rpdo.config.rpdo_number == rpdo_number) {
+            rpdo.cyclic_data = None;
+            rpdo.status = Some(Err(error));
+        }
+    }
+
+    /// Forget the node's RPDOs, e.g. after disconnecting
+    pub fn reset(&mut self) {
+        *self = Self { open: self.open, ..Self::new() };
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, connected: bool) -> Vec<RpdoAction> {
+        let mut actions = Vec::new();
+        let mut open = self.open;
+        let running = self.rpdos.iter().any(|rpdo| rpdo.cyclic_data.is_some());
+
+        egui::Window::new("RPDO Transmit")
+            .open(&mut open)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let can_discover = connected && !self.discovering && !running;
+                    if ui.add_enabled(can_discover, egui::Button::new("🔍 Discover RPDOs")).clicked() {
+                        self.discovering = true;
+                        actions.push(RpdoAction::Discover);
+                    }
+                    if self.discovering {
+                        ui.spinner();
+                        ui.label("Reading 0x1400 / 0x1600...");
+                    }
+                });
+                ui.separator();
+
+                if self.rpdos.is_empty() {
+                    ui.label(if self.discovered {
+                        "The node has no enabled RPDOs with mapped objects."
+                    } else {
+                        "Discover the RPDO configuration of the node to compose frames."
+                    });
+                }
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for rpdo in &mut self.rpdos {
+                        draw_rpdo(ui, rpdo, connected, &mut actions);
+                    }
+                });
+            });
+
+        // Don't keep driving the device without the window that stops it
+        if !open {
+            for rpdo in &mut self.rpdos {
+                if rpdo.cyclic_data.take().is_some() {
+                    actions.push(RpdoAction::Stop(rpdo.config.rpdo_number));
+                }
+            }
+        }
+        self.open = open;
+        actions
+    }
+}
+
+fn draw_rpdo(ui: &mut egui::Ui, rpdo: &mut RpdoState, connected: bool, actions: &mut Vec<RpdoAction>) {
+    let config = &rpdo.config;
+    let title = format!("RPDO {} - COB-ID 0x{:03X} ({} objects)", config.rpdo_number, config.cob_id, config.mapped_objects.len());
+
+    egui::CollapsingHeader::new(title)
+        .id_salt(("rpdo", config.rpdo_number))
+        .default_open(true)
+        .show(ui, |ui| {
+            pdo_layout::draw_mapping(ui, &config.mapped_objects);
+            ui.add_space(5.0);
+
+            egui::Grid::new(("rpdo_grid", config.rpdo_number))
+                .num_columns(4)
+                .spacing([10.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Object");
+                    ui.strong("Address");
+                    ui.strong("Type");
+                    ui.strong("Value");
+                    ui.end_row();
+
+                    for (object, value) in config.mapped_objects.iter().zip(&mut rpdo.values) {
+                        ui.label(&object.name);
+                        ui.label(format!("{:#06X}:{:02X}", object.index, object.sub_index));
+                        ui.label(format!("{:?} ({} bit)", object.data_type, object.bit_length));
+                        sdo_write::value_input(ui, value, &object.data_type);
+                        ui.end_row();
+                    }
+                });
+
+            let data = pack(&config.mapped_objects, &rpdo.values);
+            match &data {
+                Ok(bytes) => {
+                    let hex: Vec<String> = bytes.iter().map(|b| format!("{:02X}", b)).collect();
+                    ui.monospace(format!("Data: {}", hex.join(" ")));
+                }
+                Err(err) => {
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+                }
+            }
+
+            let rpdo_number = config.rpdo_number;
+            let cob_id = config.cob_id;
+            ui.horizontal(|ui| {
+                let data = data.as_ref().ok();
+                if ui.add_enabled(connected && data.is_some(), egui::Button::new("📤 Send once")).clicked() {
+                    if let Some(data) = data {
+                        actions.push(RpdoAction::Send { rpdo_number, cob_id, data: data.clone() });
+                    }
+                }
+                ui.separator();
+
+                if rpdo.cyclic_data.is_some() {
+                    ui.spinner();
+                    ui.label(format!("Sending every {} ms", rpdo.interval_ms));
+                    if ui.button("⏹ Stop").clicked() {
+                        rpdo.cyclic_data = None;
+                        actions.push(RpdoAction::Stop(rpdo_number));
+                    }
+                } else {
+                    ui.add(egui::DragValue::new(&mut rpdo.interval_ms).range(1..=60_000).suffix(" ms"));
+                    if ui.add_enabled(connected && data.is_some(), egui::Button::new("▶ Send cyclic")).clicked() {
+                        if let Some(data) = data {
+                            rpdo.cyclic_data = Some(data.clone());
+                            rpdo.status = None;
+                            actions.push(RpdoAction::Start { rpdo_number, cob_id, data: data.clone(), interval_ms: rpdo.interval_ms });
+                        }
+                    }
+                }
+            });
+
+            // Values edited while sending cyclically take effect right away
+            if let (Some(sending), Ok(data)) = (&rpdo.cyclic_data, &data) {
+                if sending != data {
+                    rpdo.cyclic_data = Some(data.clone());
+                    actions.push(RpdoAction::Start { rpdo_number, cob_id, data: data.clone(), interval_ms: rpdo.interval_ms });
+                }
+            }
+
+            match &rpdo.status {
+                Some(Ok(message)) => {
+                    ui.colored_label(Color32::from_rgb(0, 200, 0), message);
+                }
+                Some(Err(err)) => {
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+                }
+                None => {}
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_rpdo() {
+        let object = |bit_length, data_type| TpdoMappedObject {
+            index: 0x6040,
+            sub_index: 0,
+            bit_length,
+            data_type,
+            name: "Object".to_string(),
+        };
+        let mapping = vec![
+            object(16, SdoDataType::UInt16),
+            object(32, SdoDataType::Int32),
+            object(1, SdoDataType::UInt8),
+            object(1, SdoDataType::UInt8),
+        ];
+        let mut values: Vec<WriteValue> = mapping.iter().map(initial_value).collect();
+        assert_eq!(values[2], WriteValue::Boolean(false));
+
+        values[0] = WriteValue::Integer(0x000F);
+        values[1] = WriteValue::Integer(-2);
+        values[3] = WriteValue::Boolean(true);
+        assert_eq!(pack(&mapping, &values), Ok(vec![0x0F, 0x00, 0xFE, 0xFF, 0xFF, 0xFF, 0x02]));
+
+        values[0] = WriteValue::Integer(70_000);
+        assert!(pack(&mapping, &values).is_err());
+        assert!(pack(&vec![object(32, SdoDataType::UInt32); 3], &[WriteValue::Integer(0), WriteValue::Integer(0), WriteValue::Integer(0)]).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+// scaling.rs - Engineering-unit conversion of raw object values
+//
+// value = raw * factor + offset, shown with a unit. Defaults come from custom `Factor`,
+// `Offset` and `Unit` keys in the object's EDS section; a scaling set in the SDO dialog
+// is kept in the config file and takes precedence.
+use serde::{Deserialize, Serialize};
+
+/// Decimal places kept when showing a converted value, hides float noise like 0.30000000000000004
+const DISPLAY_PRECISION: f64 = 1e6;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scaling {
+    pub factor: f64,
+    pub offset: f64,
+    pub unit: String,
+}
+
+impl Default for Scaling {
+    fn default() -> Self {
+        Self {
+            factor: 1.0,
+            offset: 0.0,
+            unit: String::new(),
+        }
+    }
+}
+
+impl Scaling {
+    /// Scaling from the EDS custom keys, `None` if the object has none of them
+    pub fn from_eds(factor: Option<&str>, offset: Option<&str>, unit: Option<&str>) -> Option<Self> {
+        if factor.is_none() && offset.is_none() && unit.is_none() {
+            return None;
+        }
+        let number = |text: Option<&str>, default| text.and_then(|text| text.trim().parse().ok()).unwrap_or(default);
+        Some(Self {
+            factor: number(factor, 1.0),
+            offset: number(offset, 0.0),
+            unit: unit.unwrap_or("").trim().to_string(),
+        })
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn apply(&self, raw: f64) -> f64 {
+        raw * self.factor + self.offset
+    }
+
+    /// Converted value without unit, `None` if `value` isn't a number
+    pub fn convert(&self, value: &str) -> Option<String> {
+        let converted = self.apply(value.trim().parse().ok()?);
+        Some(((converted * DISPLAY_PRECISION).round() / DISPLAY_PRECISION).to_string())
+    }
+
+    /// Raw value a converted one came from, `None` if `converted` isn't a number or the factor is 0
+    pub fn raw(&self, converted: &str) -> Option<String> {
+        let converted: f64 = converted.trim().parse().ok()?;
+        if self.factor == 0.0 {
+            return None;
+        }
+        let raw = (converted - self.offset) / self.factor;
+        Some(((raw * DISPLAY_PRECISION).round() / DISPLAY_PRECISION).to_string())
+    }
+
+    /// Converted value with unit, `None` if `value` isn't a number
+    pub fn format(&self, value: &str) -> Option<String> {
+        let converted = self.convert(value)?;
+        Some(if self.unit.is_empty() { converted } else { format!("{} {}", converted, self.unit) })
+    }
+
+    /// Axis and column label, e.g. "Value [°C]"
+    pub fn value_label(&self) -> String {
+        if self.unit.is_empty() {
+            "Value".to_string()
+        } else {
+            format!("Value [{}]", self.unit)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scaling() {
+        let scaling = Scaling::from_eds(Some("0.1"), Some("-40"), Some("°C")).unwrap();
+        assert_eq!(scaling.format("653"), Some("25.3 °C".to_string()));
+        assert_eq!(scaling.convert("3"), Some("-39.7".to_string()));
+        assert_eq!(scaling.format("abc"), None);
+        assert_eq!(scaling.raw("25.3"), Some("653".to_string()));
+        assert_eq!(scaling.value_label(), "Value [°C]");
+
+        // Missing keys keep their neutral value
+        let scaling = Scaling::from_eds(None, None, Some("rpm")).unwrap();
+        assert_eq!(scaling.format("1500"), Some("1500 rpm".to_string()));
+        assert!(Scaling::from_eds(None, None, None).is_none());
+        assert!(Scaling::default().is_identity());
+    }
+}
@@ -0,0 +1,258 @@
+// scanner.rs - Probe an index/sub-index range for objects the EDS doesn't list
+//
+// Manufacturer objects are often missing from an incomplete EDS. The scanner reads
+// every address of a range and lists the ones the node answers, with the raw bytes.
+// An object that exists but refuses the read (e.g. write-only) is listed with its
+// abort reason; "object does not exist" skips the remaining sub-indices of the index.
+use eframe::egui::{self, Color32};
+use std::collections::BTreeMap;
+
+use crate::communication::{SdoAddress, SdoObject};
+
+/// Abort codes meaning nothing is at the address
+pub const OBJECT_DOES_NOT_EXIST: u32 = 0x0602_0000;
+pub const SUB_INDEX_DOES_NOT_EXIST: u32 = 0x0609_0011;
+
+/// Addresses to probe, bounds included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanRange {
+    pub first_index: u16,
+    pub last_index: u16,
+    pub first_sub: u8,
+    pub last_sub: u8,
+}
+
+impl ScanRange {
+    pub fn indices(&self) -> std::ops::RangeInclusive<u16> {
+        self.first_index..=self.last_index
+    }
+
+    pub fn sub_indices(&self) -> std::ops::RangeInclusive<u8> {
+        self.first_sub..=self.last_sub
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices().count() * self.sub_indices().count()
+    }
+}
+
+/// An address the node answered
+#[derive(Debug, Clone)]
+pub struct ScanHit {
+    pub address: SdoAddress,
+    /// Data bytes of the response, or why the existing object couldn't be read
+    pub data: Result<Vec<u8>, String>,
+}
+
+/// Result of probing one address
+#[derive(Debug, Clone, PartialEq)]
+pub enum Probe {
+    Found(Result<Vec<u8>, String>),
+    /// No sub-index here (or no answer at all)
+    Missing,
+    /// The whole index is absent, its other sub-indices needn't be tried
+    ObjectMissing,
+}
+
+/// Classify the outcome of a read, the error being the message of the failed request
+pub fn probe(result: Result<Vec<u8>, String>) -> Probe {
+    match result {
+        Ok(data) => Probe::Found(Ok(data)),
+        Err(err) => match abort_code(&err) {
+            Some(OBJECT_DOES_NOT_EXIST) => Probe::ObjectMissing,
+            Some(SUB_INDEX_DOES_NOT_EXIST) => Probe::Missing,
+            Some(_) => Probe::Found(Err(err)),
+            None if err.contains("timeout") => Probe::Missing,
+            None => Probe::Found(Err(err)),
+        },
+    }
+}
+
+/// Abort code from an error message like "Request failed: SDO abort 0x06020000: ..."
+pub fn abort_code(error: &str) -> Option<u32> {
+    let start = error.find("SDO abort 0x")? + "SDO abort 0x".len();
+    u32::from_str_radix(error.get(start..start + 8)?, 16).ok()
+}
+
+/// The bytes read as a little-endian unsigned number and, if printable, as text
+pub fn interpret(data: &[u8]) -> String {
+    let mut parts = Vec::new();
+    if (1..=4).contains(&data.len()) {
+        let mut bytes = [0u8; 4];
+        bytes[..data.len()].copy_from_slice(data);
+        parts.push(u32::from_le_bytes(bytes).to_string());
+    }
+    let text = String::from_utf8_lossy(data);
+    let text = text.trim_end_matches('\0');
+    if data.len() > 1 && !text.is_empty() && text.chars().all(|c| c.is_ascii_graphic() || c == ' ') {
+        parts.push(format!("\"{}\"", text));
+    }
+    parts.join("  ")
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(" ")
+}
+
+pub enum ScanAction {
+    Start(ScanRange),
+    Cancel,
+}
+
+pub struct ObjectScanner {
+    pub open: bool,
+    range: ScanRange,
+    /// Addresses probed and total while a scan is running
+    progress: Option<(usize, usize)>,
+    hits: Vec<ScanHit>,
+    unknown_only: bool,
+}
+
+impl ObjectScanner {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            range: ScanRange { first_index: 0x2000, last_index: 0x20FF, first_sub: 0, last_sub: 8 },
+            progress: None,
+            hits: Vec::new(),
+            unknown_only: true,
+        }
+    }
+
+    pub fn progress(&mut self, done: usize, total: usize) {
+        if self.progress.is_some() {
+            self.progress = Some((done, total));
+        }
+    }
+
+    pub fn found(&mut self, hit: ScanHit) {
+        if self.progress.is_some() {
+            self.hits.push(hit);
+        }
+    }
+
+    /// The scan completed, was cancelled or the connection closed
+    pub fn finished(&mut self) {
+        self.progress = None;
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        connected: bool,
+        objects: Option<&BTreeMap<u16, SdoObject>>,
+    ) -> Option<ScanAction> {
+        let mut action = None;
+        let mut open = self.open;
+
+        egui::Window::new("Object Scanner")
+            .open(&mut open)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                let running = self.progress.is_some();
+                ui.add_enabled_ui(!running, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Index:");
+                        ui.add(egui::DragValue::new(&mut self.range.first_index).hexadecimal(4, false, true).prefix("0x"));
+                        ui.label("to");
+                        ui.add(egui::DragValue::new(&mut self.range.last_index).hexadecimal(4, false, true).prefix("0x"));
+                        ui.label("Sub-index:");
+                        ui.add(egui::DragValue::new(&mut self.range.first_sub));
+                        ui.label("to");
+                        ui.add(egui::DragValue::new(&mut self.range.last_sub));
+                    });
+                });
+
+                ui.horizontal(|ui| {
+                    if let Some((done, total)) = self.progress {
+                        let fraction = if total > 0 { done as f32 / total as f32 } else { 0.0 };
+                        ui.add(egui::ProgressBar::new(fraction)
+                            .text(format!("{}/{}", done, total))
+                            .desired_width(200.0));
+                        if ui.button("Cancel").clicked() {
+                            self.progress = None;
+                            action = Some(ScanAction::Cancel);
+                        }
+                    } else {
+                        let valid = self.range.first_index <= self.range.last_index && self.range.first_sub <= self.range.last_sub;
+                        if ui.add_enabled(connected && valid, egui::Button::new("🔎 Scan"))
+                            .on_hover_text(format!("Read {} addresses", if valid { self.range.len() } else { 0 }))
+                            .clicked()
+                        {
+                            self.hits.clear();
+                            self.progress = Some((0, self.range.len()));
+                            action = Some(ScanAction::Start(self.range));
+                        }
+                    }
+                    ui.checkbox(&mut self.unknown_only, "Only objects missing from the EDS");
+                });
+                ui.separator();
+
+                let in_eds = |address: &SdoAddress| objects
+                    .and_then(|objects| objects.get(&address.index))
+                    .and_then(|object| object.sub_objects.get(&address.sub_index))
+                    .map(|sub_object| sub_object.name.as_str());
+                let hits: Vec<&ScanHit> = self.hits.iter()
+                    .filter(|hit| !self.unknown_only || in_eds(&hit.address).is_none())
+                    .collect();
+                ui.label(format!("{} of {} responding objects shown", hits.len(), self.hits.len()));
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("scan_hits")
+                        .num_columns(4)
+                        .spacing([20.0, 4.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("Address");
+                            ui.strong("EDS");
+                            ui.strong("Data");
+                            ui.strong("Value");
+                            ui.end_row();
+
+                            for hit in hits {
+                                ui.monospace(format!("{:04X}:{:02X}", hit.address.index, hit.address.sub_index));
+                                match in_eds(&hit.address) {
+                                    Some(name) => ui.label(name),
+                                    None => ui.colored_label(Color32::from_rgb(255, 165, 0), "not in EDS"),
+                                };
+                                match &hit.data {
+                                    Ok(data) => {
+                                        ui.monospace(hex_bytes(data));
+                                        ui.label(interpret(data));
+                                    }
+                                    Err(err) => {
+                                        ui.label("—");
+                                        ui.colored_label(Color32::from_rgb(255, 100, 100), err);
+                                    }
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+
+        self.open = open;
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_outcomes() {
+        assert_eq!(probe(Ok(vec![0x2A])), Probe::Found(Ok(vec![0x2A])));
+        assert_eq!(probe(Err("Request failed: SDO abort 0x06020000: Object does not exist".to_string())), Probe::ObjectMissing);
+        assert_eq!(probe(Err("Request failed: SDO abort 0x06090011: Sub-index does not exist".to_string())), Probe::Missing);
+        assert_eq!(probe(Err("Request failed: SDO request timeout".to_string())), Probe::Missing);
+
+        // Refusing the read still proves the object exists
+        let write_only = "Request failed: SDO abort 0x06010001: Attempt to read a write only object".to_string();
+        assert_eq!(probe(Err(write_only.clone())), Probe::Found(Err(write_only)));
+
+        assert_eq!(interpret(&[0x34, 0x12]), "4660");
+        assert_eq!(interpret(b"V1.2"), "841888086  \"V1.2\"");
+        assert_eq!(interpret(&[1, 2, 3, 4, 5]), "");
+    }
+}
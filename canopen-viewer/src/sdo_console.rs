@@ -0,0 +1,268 @@
+// sdo_console.rs - Expert panel for one-shot SDO reads and writes by address
+//
+// Works for any index and sub-index, whether or not the EDS lists it, and shows the
+// raw request and response bytes next to the decoded result or abort reason.
+use canopen_common::SdoDataType;
+use eframe::egui::{self, Color32};
+use std::collections::VecDeque;
+use std::sync::mpsc::Sender;
+
+use crate::communication::{Command, RawSdoExchange};
+use crate::sdo_write::WriteValue;
+
+/// Exchanges kept in the console history
+const HISTORY_LENGTH: usize = 50;
+
+pub const DATA_TYPES: [SdoDataType; 9] = [
+    SdoDataType::UInt8,
+    SdoDataType::UInt16,
+    SdoDataType::UInt32,
+    SdoDataType::Int8,
+    SdoDataType::Int16,
+    SdoDataType::Int32,
+    SdoDataType::Real32,
+    SdoDataType::VisibleString,
+    SdoDataType::OctetString,
+];
+
+pub struct SdoConsole {
+    pub open: bool,
+    index: String,
+    sub_index: String,
+    data_type: SdoDataType,
+    value: String,
+    input_error: Option<String>,
+    history: VecDeque<RawSdoExchange>,
+}
+
+impl SdoConsole {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            index: "1000".to_string(),
+            sub_index: "0".to_string(),
+            data_type: SdoDataType::UInt32,
+            value: String::new(),
+            input_error: None,
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Add a finished exchange to the history
+    pub fn record(&mut self, exchange: RawSdoExchange) {
+        if self.history.len() >= HISTORY_LENGTH {
+            self.history.pop_back();
+        }
+        self.history.push_front(exchange);
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, command_tx: Option<&Sender<Command>>) {
+        let mut open = self.open;
+        egui::Window::new("SDO Console")
+            .open(&mut open)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Index (hex):");
+                    ui.add(egui::TextEdit::singleline(&mut self.index).desired_width(50.0));
+                    ui.label("Sub:");
+                    ui.add(egui::TextEdit::singleline(&mut self.sub_index).desired_width(30.0));
+
+                    egui::ComboBox::from_id_salt("sdo_console_type")
+                        .selected_text(format!("{:?}", self.data_type))
+                        .show_ui(ui, |ui| {
+                            for data_type in DATA_TYPES {
+                                let label = format!("{:?}", data_type);
+                                ui.selectable_value(&mut self.data_type, data_type, label);
+                            }
+                        });
+
+                    ui.label("Value:");
+                    ui.add(egui::TextEdit::singleline(&mut self.value)
+                        .hint_text("for writes")
+                        .desired_width(100.0));
+                });
+
+                ui.horizontal(|ui| {
+                    if ui.button("📥 Read").clicked() {
+                        self.send(command_tx, false);
+                    }
+                    if ui.button("📤 Write").clicked() {
+                        self.send(command_tx, true);
+                    }
+                    if ui.button("🗑 Clear").clicked() {
+                        self.history.clear();
+                    }
+                    if let Some(err) = &self.input_error {
+                        ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+                    }
+                });
+
+                ui.separator();
+                self.draw_history(ui);
+            });
+        self.open = open;
+    }
+
+    fn send(&mut self, command_tx: Option<&Sender<Command>>, write: bool) {
+        match self.build_command(write) {
+            Ok(command) => {
+                self.input_error = None;
+                if let Some(tx) = command_tx {
+                    let _ = tx.send(command);
+                }
+            }
+            Err(err) => self.input_error = Some(err),
+        }
+    }
+
+    fn build_command(&self, write: bool) -> Result<Command, String> {
+        let index_text = self.index.trim().trim_start_matches("0x");
+        let index = u16::from_str_radix(index_text, 16)
+            .map_err(|_| format!("Invalid index '{}'", self.index))?;
+
+        let sub_text = self.sub_index.trim();
+        let sub_index = match sub_text.strip_prefix("0x") {
+            Some(hex) => u8::from_str_radix(hex, 16),
+            None => sub_text.parse(),
+        }.map_err(|_| format!("Invalid sub-index '{}'", self.sub_index))?;
+
+        let data = if write {
+            Some(WriteValue::parse(&self.value, &self.data_type)?.encode(&self.data_type)?)
+        } else {
+            None
+        };
+
+        Ok(Command::RawSdo { index, sub_index, data_type: self.data_type.clone(), data })
+    }
+
+    fn draw_history(&self, ui: &mut egui::Ui) {
+        if self.history.is_empty() {
+            ui.label("No requests yet.");
+            return;
+        }
+
+        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            egui::Grid::new("sdo_console_history")
+                .num_columns(6)
+                .spacing([10.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Time");
+                    ui.label("");
+                    ui.label("Address");
+                    ui.label("Request");
+                    ui.label("Response");
+                    ui.label("Result");
+                    ui.end_row();
+
+                    for exchange in &self.history {
+                        ui.label(exchange.timestamp.format("%H:%M:%S%.3f").to_string());
+                        ui.label(if exchange.write { "W" } else { "R" });
+                        ui.label(format!("{:04X}:{:02X}", exchange.index, exchange.sub_index));
+                        ui.monospace(hex_bytes(&exchange.request));
+                        ui.monospace(exchange.response.as_deref().map(hex_bytes).unwrap_or_else(|| "—".to_string()));
+                        match &exchange.result {
+                            Ok(value) => ui.label(value),
+                            Err(err) => ui.colored_label(Color32::from_rgb(255, 100, 100), err),
+                        };
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+
+    fn console(index: &str, sub_index: &str, data_type: SdoDataType, value: &str) -> SdoConsole {
+        SdoConsole {
+            index: index.to_string(),
+            sub_index: sub_index.to_string(),
+            data_type,
+            value: value.to_string(),
+            ..SdoConsole::new()
+        }
+    }
+
+    fn exchange(index: u16) -> RawSdoExchange {
+        RawSdoExchange {
+            timestamp: Local::now(),
+            index,
+            sub_index: 0,
+            write: false,
+            request: vec![0x40, 0x00, 0x10, 0x00],
+            response: None,
+            result: Err("SDO request timeout".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_read_command_addresses() {
+        for (index, sub_index) in [("6041", "0"), ("0x6041", "0x0"), (" 6041 ", " 0 ")] {
+            let command = console(index, sub_index, SdoDataType::UInt16, "").build_command(false).unwrap();
+            assert!(matches!(command, Command::RawSdo { index: 0x6041, sub_index: 0, data_type: SdoDataType::UInt16, data: None }));
+        }
+        let command = console("1018", "0x0A", SdoDataType::UInt32, "").build_command(false).unwrap();
+        assert!(matches!(command, Command::RawSdo { index: 0x1018, sub_index: 10, .. }));
+        let command = console("1018", "10", SdoDataType::UInt32, "").build_command(false).unwrap();
+        assert!(matches!(command, Command::RawSdo { sub_index: 10, .. }));
+    }
+
+    #[test]
+    fn test_invalid_addresses() {
+        assert_eq!(console("G000", "0", SdoDataType::UInt8, "").build_command(false).err(), Some("Invalid index 'G000'".to_string()));
+        assert_eq!(console("10000", "0", SdoDataType::UInt8, "").build_command(false).err(), Some("Invalid index '10000'".to_string()));
+        assert_eq!(console("1000", "256", SdoDataType::UInt8, "").build_command(false).err(), Some("Invalid sub-index '256'".to_string()));
+        assert_eq!(console("1000", "", SdoDataType::UInt8, "").build_command(false).err(), Some("Invalid sub-index ''".to_string()));
+    }
+
+    #[test]
+    fn test_write_command_encodes_the_value() {
+        let command = console("6040", "0", SdoDataType::UInt16, "1000").build_command(true).unwrap();
+        assert!(matches!(command, Command::RawSdo { data: Some(ref data), .. } if data == &[0xE8, 0x03]));
+        let command = console("2000", "1", SdoDataType::Int8, "-2").build_command(true).unwrap();
+        assert!(matches!(command, Command::RawSdo { data: Some(ref data), .. } if data == &[0xFE]));
+        assert!(console("6040", "0", SdoDataType::UInt8, "300").build_command(true).is_err());
+        // The value only matters for writes
+        assert!(console("6040", "0", SdoDataType::UInt8, "300").build_command(false).is_ok());
+    }
+
+    #[test]
+    fn test_send_reports_input_errors() {
+        let (command_tx, command_rx) = std::sync::mpsc::channel();
+        let mut console = console("xyz", "0", SdoDataType::UInt8, "");
+        console.send(Some(&command_tx), false);
+        assert_eq!(console.input_error.as_deref(), Some("Invalid index 'xyz'"));
+        assert!(command_rx.try_recv().is_err());
+
+        console.index = "1000".to_string();
+        console.send(Some(&command_tx), false);
+        assert_eq!(console.input_error, None);
+        assert!(matches!(command_rx.try_recv(), Ok(Command::RawSdo { index: 0x1000, .. })));
+    }
+
+    #[test]
+    fn test_history_keeps_the_newest_exchanges() {
+        let mut console = SdoConsole::new();
+        for index in 0..HISTORY_LENGTH as u16 + 5 {
+            console.record(exchange(index));
+        }
+        assert_eq!(console.history.len(), HISTORY_LENGTH);
+        assert_eq!(console.history.front().map(|exchange| exchange.index), Some(HISTORY_LENGTH as u16 + 4));
+        assert_eq!(console.history.back().map(|exchange| exchange.index), Some(5));
+    }
+
+    #[test]
+    fn test_hex_bytes() {
+        assert_eq!(hex_bytes(&[0x40, 0x00, 0x10, 0x0A]), "40 00 10 0A");
+        assert_eq!(hex_bytes(&[]), "");
+    }
+}
@@ -0,0 +1,134 @@
+// sdo_search.rs - Query language of the SDO list search box
+//
+// Space-separated terms that must all match one sub-object:
+//   `0x2000-0x20FF`  index range
+//   `access:rw`      access type
+//   `type:uint16`    data type, by name or EDS code (`type:0x0006`)
+//   `/^target.*/`    case-insensitive regular expression on the object or sub-object name
+//   anything else    text in the name or index, as before
+use canopen_common::SdoDataType;
+use regex::{Regex, RegexBuilder};
+use std::ops::RangeInclusive;
+
+use crate::communication::{SdoObject, SdoSubObject};
+
+#[derive(Debug, Clone)]
+enum Term {
+    Text(String),
+    Pattern(Regex),
+    IndexRange(RangeInclusive<u16>),
+    Access(String),
+    DataType(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SdoQuery {
+    terms: Vec<Term>,
+}
+
+impl SdoQuery {
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let mut terms = Vec::new();
+        for word in query.split_whitespace() {
+            let lower = word.to_lowercase();
+            let term = if let Some(pattern) = word.strip_prefix('/').and_then(|rest| rest.strip_suffix('/')).filter(|p| !p.is_empty()) {
+                let regex = RegexBuilder::new(pattern).case_insensitive(true).build()
+                    .map_err(|err| format!("Invalid pattern /{}/: {}", pattern, err))?;
+                Term::Pattern(regex)
+            } else if let Some(access) = lower.strip_prefix("access:") {
+                Term::Access(access.to_string())
+            } else if let Some(data_type) = lower.strip_prefix("type:") {
+                Term::DataType(data_type.to_string())
+            } else if let Some(range) = parse_index_range(&lower) {
+                Term::IndexRange(range?)
+            } else {
+                Term::Text(lower)
+            };
+            terms.push(term);
+        }
+        Ok(Self { terms })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Whether a sub-object (with its parent object) satisfies every term
+    pub fn matches(&self, index: u16, object: &SdoObject, sub_object: &SdoSubObject) -> bool {
+        self.terms.iter().all(|term| match term {
+            Term::Text(text) => {
+                object.name.to_lowercase().contains(text)
+                    || sub_object.name.to_lowercase().contains(text)
+                    || format!("{:#06x}", index).contains(text)
+            }
+            Term::Pattern(pattern) => pattern.is_match(&object.name) || pattern.is_match(&sub_object.name),
+            Term::IndexRange(range) => range.contains(&index),
+            Term::Access(access) => sub_object.access_type.eq_ignore_ascii_case(access),
+            Term::DataType(data_type) => {
+                sub_object.data_type.eq_ignore_ascii_case(data_type)
+                    || SdoDataType::from_eds_type(&sub_object.data_type)
+                        .is_some_and(|dt| format!("{:?}", dt).eq_ignore_ascii_case(data_type))
+            }
+        })
+    }
+}
+
+/// `0x2000-0x20ff`; `None` if the word isn't a range at all
+fn parse_index_range(word: &str) -> Option<Result<RangeInclusive<u16>, String>> {
+    let (first, last) = word.split_once('-')?;
+    let first = first.strip_prefix("0x")?;
+    let last = last.strip_prefix("0x").unwrap_or(last);
+    let parse = |hex: &str| u16::from_str_radix(hex, 16).map_err(|_| format!("Invalid index range '{}'", word));
+    Some((|| {
+        let (first, last) = (parse(first)?, parse(last)?);
+        if first > last {
+            return Err(format!("Index range '{}' is reversed", word));
+        }
+        Ok(first..=last)
+    })())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_query_terms() {
+        let sub_object = |name: &str, data_type: &str, access: &str| SdoSubObject {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            access_type: access.to_string(),
+            low_limit: None,
+            high_limit: None,
+            default_value: None,
+            value_descriptions: BTreeMap::new(),
+            scaling: None,
+        };
+        let object = SdoObject { name: "Target velocity".to_string(), sub_objects: BTreeMap::new() };
+        let target = sub_object("Target velocity", "0x0004", "rw");
+        let matches = |query: &str| SdoQuery::parse(query).unwrap().matches(0x60FF, &object, &target);
+
+        assert!(matches(""));
+        assert!(matches("velocity 0x6000-0x60ff"));
+        assert!(!matches("velocity 0x2000-0x20FF"));
+        assert!(matches("0x60ff"));
+        assert!(matches("access:rw type:int32"));
+        assert!(matches("type:0x0004"));
+        assert!(!matches("access:ro"));
+        assert!(!matches("type:int16"));
+        let unsigned = sub_object("Target velocity", "0x0007", "rw");
+        assert!(SdoQuery::parse("type:uint32").unwrap().matches(0x60FF, &object, &unsigned));
+        // The name of a signed type is part of the unsigned one's
+        assert!(!SdoQuery::parse("type:int32").unwrap().matches(0x60FF, &object, &unsigned));
+        assert!(matches("/^target\\s+vel/"));
+        assert!(!matches("/^velocity/"));
+        assert!(matches("/VELOCITY$/"));
+        let numbered = sub_object("Output 12", "0x0007", "rw");
+        assert!(SdoQuery::parse("/^output\\s[\\d]+$/").unwrap().matches(0x60FF, &object, &numbered));
+        assert!(!SdoQuery::parse("/^[\\d]/").unwrap().matches(0x60FF, &object, &sub_object("d", "0x0007", "rw")));
+
+        assert!(SdoQuery::parse("0x20FF-0x2000").is_err());
+        assert!(SdoQuery::parse("/(velocity/").is_err());
+    }
+}
@@ -0,0 +1,250 @@
+// sdo_write.rs - Values entered in the SDO write editor and their encoding
+//
+// Only expedited transfers are supported, so strings are limited to four bytes.
+// Numbers are also checked against the LowLimit / HighLimit of the EDS before sending.
+use canopen_common::SdoDataType;
+use eframe::egui;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Largest payload of an expedited SDO download
+const EXPEDITED_MAX_BYTES: usize = 4;
+
+/// Value being edited; the variant selects the input widget
+#[derive(Debug, Clone, PartialEq)]
+pub enum WriteValue {
+    Boolean(bool),
+    Integer(i64),
+    Real(f64),
+    Text(String),
+}
+
+impl WriteValue {
+    /// Empty value for an object with the given EDS `DataType` code
+    pub fn for_eds_type(eds_type: &str, data_type: &SdoDataType) -> Self {
+        if is_eds_boolean(eds_type) {
+            return Self::Boolean(false);
+        }
+        match data_type {
+            SdoDataType::Real32 => Self::Real(0.0),
+            SdoDataType::VisibleString | SdoDataType::OctetString => Self::Text(String::new()),
+            _ => Self::Integer(0),
+        }
+    }
+
+    /// Parse a value typed as text; integers may also be given as 0x hex
+    pub fn parse(text: &str, data_type: &SdoDataType) -> Result<Self, String> {
+        let text = text.trim();
+        match data_type {
+            SdoDataType::Real32 => text.parse().map(Self::Real)
+                .map_err(|_| format!("'{}' is not a number", text)),
+            SdoDataType::VisibleString | SdoDataType::OctetString => Ok(Self::Text(text.to_string())),
+            _ => {
+                let number = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+                    Some(hex) => i64::from_str_radix(hex, 16),
+                    None => text.parse(),
+                };
+                number.map(Self::Integer).map_err(|_| format!("'{}' is not an integer", text))
+            }
+        }
+    }
+
+    /// Encode the value as the little-endian payload of an SDO download
+    pub fn encode(&self, data_type: &SdoDataType) -> Result<Vec<u8>, String> {
+        let bytes = match (self, data_type) {
+            (Self::Boolean(value), _) => vec![*value as u8],
+            (Self::Integer(value), _) => {
+                if let Some(range) = integer_range(data_type) {
+                    if !range.contains(value) {
+                        return Err(format!("{} is outside {}..={}", value, range.start(), range.end()));
+                    }
+                }
+                let size = integer_size(data_type)
+                    .ok_or_else(|| format!("{:?} is not an integer type", data_type))?;
+                value.to_le_bytes()[..size].to_vec()
+            }
+            (Self::Real(value), _) => (*value as f32).to_le_bytes().to_vec(),
+            (Self::Text(text), SdoDataType::OctetString) => parse_hex_bytes(text)?,
+            (Self::Text(text), _) => text.as_bytes().to_vec(),
+        };
+
+        if bytes.len() > EXPEDITED_MAX_BYTES {
+            return Err(format!("Only values up to {} bytes can be written", EXPEDITED_MAX_BYTES));
+        }
+        Ok(bytes)
+    }
+}
+
+/// Input widget for a value: checkbox, number limited to the type's range, or text
+pub fn value_input(ui: &mut egui::Ui, value: &mut WriteValue, data_type: &SdoDataType) {
+    match value {
+        WriteValue::Boolean(checked) => {
+            ui.checkbox(checked, "");
+        }
+        WriteValue::Integer(number) => {
+            let mut drag = egui::DragValue::new(number);
+            if let Some(range) = integer_range(data_type) {
+                drag = drag.range(range);
+            }
+            ui.add(drag);
+        }
+        WriteValue::Real(number) => {
+            ui.add(egui::DragValue::new(number).speed(0.1));
+        }
+        WriteValue::Text(text) => {
+            let hint = if matches!(data_type, SdoDataType::OctetString) { "hex bytes" } else { "text" };
+            ui.add(egui::TextEdit::singleline(text).hint_text(hint).desired_width(120.0));
+        }
+    }
+}
+
+/// Allowed range of an object from the EDS; either bound may be missing
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValueLimits {
+    pub low: Option<f64>,
+    pub high: Option<f64>,
+}
+
+impl ValueLimits {
+    /// Parse the EDS `LowLimit` / `HighLimit` strings
+    ///
+    /// Hex limits of signed types are two's complement of the object's size, e.g. 0xFF80
+    /// is -128 for an INTEGER16. Limits that depend on $NODEID are ignored.
+    pub fn from_eds(low: Option<&str>, high: Option<&str>, data_type: &SdoDataType) -> Self {
+        Self {
+            low: low.and_then(|text| parse_limit(text, data_type)),
+            high: high.and_then(|text| parse_limit(text, data_type)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.low.is_none() && self.high.is_none()
+    }
+
+    /// Check a number against the limits; other values always pass
+    pub fn check(&self, value: &WriteValue) -> Result<(), String> {
+        let number = match value {
+            WriteValue::Integer(value) => *value as f64,
+            WriteValue::Real(value) => *value,
+            _ => return Ok(()),
+        };
+        let below = self.low.is_some_and(|low| number < low);
+        let above = self.high.is_some_and(|high| number > high);
+        if below || above {
+            return Err(format!("{} is outside the allowed range {}", number, self));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ValueLimits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.low, self.high) {
+            (Some(low), Some(high)) => write!(f, "{}..={}", low, high),
+            (Some(low), None) => write!(f, "≥ {}", low),
+            (None, Some(high)) => write!(f, "≤ {}", high),
+            (None, None) => write!(f, "any"),
+        }
+    }
+}
+
+fn parse_limit(text: &str, data_type: &SdoDataType) -> Option<f64> {
+    if text.to_ascii_uppercase().contains("NODEID") {
+        return None;
+    }
+    parse_eds_number(text, data_type)
+}
+
+/// Decimal or 0x hex number of an EDS value; hex of signed types is two's complement
+pub fn parse_eds_number(text: &str, data_type: &SdoDataType) -> Option<f64> {
+    let text = text.trim();
+    let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) else {
+        return text.parse().ok();
+    };
+    let raw = u64::from_str_radix(hex, 16).ok()?;
+    let signed = matches!(data_type, SdoDataType::Int8 | SdoDataType::Int16 | SdoDataType::Int32);
+    match integer_size(data_type) {
+        Some(size) if signed => {
+            let shift = 64 - size * 8;
+            Some((((raw << shift) as i64) >> shift) as f64)
+        }
+        _ => Some(raw as f64),
+    }
+}
+
+/// Values an integer type can hold
+pub fn integer_range(data_type: &SdoDataType) -> Option<RangeInclusive<i64>> {
+    match data_type {
+        SdoDataType::UInt8 => Some(0..=u8::MAX as i64),
+        SdoDataType::UInt16 => Some(0..=u16::MAX as i64),
+        SdoDataType::UInt32 => Some(0..=u32::MAX as i64),
+        SdoDataType::Int8 => Some(i8::MIN as i64..=i8::MAX as i64),
+        SdoDataType::Int16 => Some(i16::MIN as i64..=i16::MAX as i64),
+        SdoDataType::Int32 => Some(i32::MIN as i64..=i32::MAX as i64),
+        _ => None,
+    }
+}
+
+/// Size in bytes of an integer type
+pub fn integer_size(data_type: &SdoDataType) -> Option<usize> {
+    match data_type {
+        SdoDataType::UInt8 | SdoDataType::Int8 => Some(1),
+        SdoDataType::UInt16 | SdoDataType::Int16 => Some(2),
+        SdoDataType::UInt32 | SdoDataType::Int32 => Some(4),
+        _ => None,
+    }
+}
+
+/// BOOLEAN objects get a checkbox instead of a number
+fn is_eds_boolean(eds_type: &str) -> bool {
+    matches!(eds_type.to_ascii_uppercase().as_str(), "0X0001" | "1")
+}
+
+/// "01 A2" or "01A2"
+fn parse_hex_bytes(text: &str) -> Result<Vec<u8>, String> {
+    let digits: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if !digits.is_ascii() || !digits.len().is_multiple_of(2) {
+        return Err(format!("'{}' is not a sequence of hex bytes", text));
+    }
+    (0..digits.len() / 2)
+        .map(|i| u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16)
+            .map_err(|_| format!("'{}' is not a sequence of hex bytes", text)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_write_values() {
+        assert_eq!(WriteValue::for_eds_type("0x0001", &SdoDataType::UInt8), WriteValue::Boolean(false));
+        assert_eq!(WriteValue::Boolean(true).encode(&SdoDataType::UInt8), Ok(vec![1]));
+
+        assert_eq!(WriteValue::Integer(-2).encode(&SdoDataType::Int16), Ok(vec![0xFE, 0xFF]));
+        assert_eq!(WriteValue::Integer(0x1234_5678).encode(&SdoDataType::UInt32), Ok(vec![0x78, 0x56, 0x34, 0x12]));
+        assert!(WriteValue::Integer(256).encode(&SdoDataType::UInt8).is_err());
+
+        assert_eq!(WriteValue::Real(1.0).encode(&SdoDataType::Real32), Ok(vec![0x00, 0x00, 0x80, 0x3F]));
+        assert_eq!(WriteValue::Text("01 a2".to_string()).encode(&SdoDataType::OctetString), Ok(vec![0x01, 0xA2]));
+        assert!(WriteValue::Text("too long".to_string()).encode(&SdoDataType::VisibleString).is_err());
+
+        assert_eq!(WriteValue::parse("0x1F", &SdoDataType::UInt8), Ok(WriteValue::Integer(31)));
+        assert!(WriteValue::parse("1.5", &SdoDataType::UInt8).is_err());
+    }
+
+    #[test]
+    fn test_eds_limits() {
+        let limits = ValueLimits::from_eds(Some("0xFF80"), Some("100"), &SdoDataType::Int16);
+        assert_eq!(limits, ValueLimits { low: Some(-128.0), high: Some(100.0) });
+        assert_eq!(limits.to_string(), "-128..=100");
+        assert!(limits.check(&WriteValue::Integer(100)).is_ok());
+        assert!(limits.check(&WriteValue::Integer(101)).is_err());
+        assert!(limits.check(&WriteValue::Integer(-129)).is_err());
+
+        let limits = ValueLimits::from_eds(Some("0.5"), Some("$NODEID+0x10"), &SdoDataType::Real32);
+        assert_eq!(limits, ValueLimits { low: Some(0.5), high: None });
+        assert!(limits.check(&WriteValue::Real(0.25)).is_err());
+        assert!(limits.check(&WriteValue::Text("abc".to_string())).is_ok());
+    }
+}
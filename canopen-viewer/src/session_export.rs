@@ -0,0 +1,97 @@
+// session_export.rs - All subscribed channels in one CSV
+//
+// The per-plot CSV files each have their own sample times, which makes them tedious to
+// merge in a spreadsheet. The session export writes one row per moment any channel was
+// sampled, with the absolute time and one column per channel. A channel without a sample
+// at that moment gets its last value (sample-and-hold) or a value interpolated between its
+// neighbours; before its first sample the cell stays empty.
+use chrono::{DateTime, Local};
+use std::path::Path;
+
+use crate::timeline;
+use crate::xy_plot;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    SampleAndHold,
+    Interpolate,
+}
+
+impl Alignment {
+    pub const ALL: [Alignment; 2] = [Alignment::SampleAndHold, Alignment::Interpolate];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Alignment::SampleAndHold => "Sample and hold",
+            Alignment::Interpolate => "Interpolated",
+        }
+    }
+}
+
+pub struct ExportChannel {
+    /// Column header
+    pub name: String,
+    /// `[seconds since the session start, value]`, ordered by time
+    pub points: Vec<[f64; 2]>,
+}
+
+/// Value of a channel at `time`
+fn value_at(points: &[[f64; 2]], time: f64, alignment: Alignment) -> Option<f64> {
+    match alignment {
+        Alignment::SampleAndHold => {
+            let after = points.partition_point(|point| point[0] <= time);
+            points.get(after.checked_sub(1)?).map(|point| point[1])
+        }
+        Alignment::Interpolate => xy_plot::interpolate(points, time),
+    }
+}
+
+/// Every sample time of any channel, each with the value of every channel then
+pub fn align(channels: &[ExportChannel], alignment: Alignment) -> Vec<(f64, Vec<Option<f64>>)> {
+    let mut times: Vec<f64> = channels.iter().flat_map(|channel| channel.points.iter().map(|point| point[0])).collect();
+    times.sort_by(f64::total_cmp);
+    times.dedup();
+    times.into_iter()
+        .map(|time| (time, channels.iter().map(|channel| value_at(&channel.points, time, alignment)).collect()))
+        .collect()
+}
+
+pub fn write(path: &Path, epoch: DateTime<Local>, channels: &[ExportChannel], alignment: Alignment) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = csv::Writer::from_path(path)?;
+    let mut header = vec!["Timestamp".to_string(), "Time (seconds)".to_string()];
+    header.extend(channels.iter().map(|channel| channel.name.clone()));
+    writer.write_record(&header)?;
+
+    for (time, values) in align(channels, alignment) {
+        let mut record = vec![timeline::clock_time(epoch, time, "%Y-%m-%d %H:%M:%S%.3f"), time.to_string()];
+        record.extend(values.into_iter().map(|value| value.map_or_else(String::new, |value| value.to_string())));
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_alignment() {
+        let channels = [
+            ExportChannel { name: "a".to_string(), points: vec![[0.0, 1.0], [2.0, 3.0]] },
+            ExportChannel { name: "b".to_string(), points: vec![[1.0, 10.0], [2.0, 20.0], [3.0, 30.0]] },
+        ];
+
+        let held = align(&channels, Alignment::SampleAndHold);
+        assert_eq!(held, vec![
+            (0.0, vec![Some(1.0), None]),
+            (1.0, vec![Some(1.0), Some(10.0)]),
+            (2.0, vec![Some(3.0), Some(20.0)]),
+            (3.0, vec![Some(3.0), Some(30.0)]),
+        ]);
+
+        let interpolated = align(&channels, Alignment::Interpolate);
+        assert_eq!(interpolated[1], (1.0, vec![Some(2.0), Some(10.0)]));
+        assert_eq!(interpolated[3], (3.0, vec![None, Some(30.0)]));
+    }
+}
@@ -0,0 +1,198 @@
+// snapshot.rs - Values of all readable objects of a node, saved as CSV
+//
+// One row per sub-object: index and sub-index in hex, the EDS name, the type used for
+// the read, the payload bytes and either the decoded value or the read error.
+use canopen_common::SdoDataType;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+
+/// One object read for a snapshot
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotEntry {
+    pub index: u16,
+    pub sub_index: u8,
+    pub name: String,
+    pub data_type: SdoDataType,
+    /// Payload as received, empty if the read failed
+    pub raw: Vec<u8>,
+    /// Decoded value, or the error including the abort code
+    pub value: Result<String, String>,
+}
+
+/// CSV layout of an entry
+#[derive(Serialize, Deserialize)]
+struct SnapshotRow {
+    #[serde(rename = "Index")]
+    index: String,
+    #[serde(rename = "SubIndex")]
+    sub_index: String,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "DataType")]
+    data_type: SdoDataType,
+    #[serde(rename = "Raw")]
+    raw: String,
+    #[serde(rename = "Value")]
+    value: String,
+    #[serde(rename = "Error")]
+    error: String,
+}
+
+/// Write a snapshot to a CSV file
+pub fn save(path: &Path, entries: &[SnapshotEntry]) -> Result<(), Box<dyn Error>> {
+    let mut writer = csv::Writer::from_path(path)?;
+
+    for entry in entries {
+        let (value, error) = match &entry.value {
+            Ok(value) => (value.clone(), String::new()),
+            Err(error) => (String::new(), error.clone()),
+        };
+        writer.serialize(SnapshotRow {
+            index: format!("0x{:04X}", entry.index),
+            sub_index: format!("0x{:02X}", entry.sub_index),
+            name: entry.name.clone(),
+            data_type: entry.data_type.clone(),
+            raw: entry.raw.iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(" "),
+            value,
+            error,
+        })?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read a snapshot written by [`save`]
+pub fn load(path: &Path) -> Result<Vec<SnapshotEntry>, Box<dyn Error>> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let mut entries = Vec::new();
+
+    for row in reader.deserialize() {
+        let row: SnapshotRow = row?;
+        let raw = row.raw.split_whitespace()
+            .map(|byte| u8::from_str_radix(byte, 16))
+            .collect::<Result<Vec<u8>, _>>()?;
+
+        entries.push(SnapshotEntry {
+            index: u16::from_str_radix(row.index.trim_start_matches("0x"), 16)?,
+            sub_index: u8::from_str_radix(row.sub_index.trim_start_matches("0x"), 16)?,
+            name: row.name,
+            data_type: row.data_type,
+            raw,
+            value: if row.error.is_empty() { Ok(row.value) } else { Err(row.error) },
+        });
+    }
+
+    Ok(entries)
+}
+
+/// An object whose value differs between two snapshots
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotChange {
+    pub index: u16,
+    pub sub_index: u8,
+    pub name: String,
+    /// `None` if the object is missing from that snapshot
+    pub before: Option<Result<String, String>>,
+    pub after: Option<Result<String, String>>,
+}
+
+/// The entries of one object in the "before" and "after" snapshot
+type EntryPair<'a> = (Option<&'a SnapshotEntry>, Option<&'a SnapshotEntry>);
+
+/// Objects whose value or read result changed, ordered by index and sub-index
+pub fn diff(before: &[SnapshotEntry], after: &[SnapshotEntry]) -> Vec<SnapshotChange> {
+    let mut objects: BTreeMap<(u16, u8), EntryPair> = BTreeMap::new();
+    for entry in before {
+        objects.entry((entry.index, entry.sub_index)).or_default().0 = Some(entry);
+    }
+    for entry in after {
+        objects.entry((entry.index, entry.sub_index)).or_default().1 = Some(entry);
+    }
+
+    objects.into_iter()
+        .filter(|(_, (before, after))| match (before, after) {
+            (Some(before), Some(after)) => before.raw != after.raw || before.value != after.value,
+            _ => true,
+        })
+        .map(|((index, sub_index), (before, after))| SnapshotChange {
+            index,
+            sub_index,
+            name: before.or(after).map(|entry| entry.name.clone()).unwrap_or_default(),
+            before: before.map(|entry| entry.value.clone()),
+            after: after.map(|entry| entry.value.clone()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let entries = vec![
+            SnapshotEntry {
+                index: 0x1000,
+                sub_index: 0,
+                name: "Device type".to_string(),
+                data_type: SdoDataType::UInt32,
+                raw: vec![0x91, 0x01, 0x00, 0x00],
+                value: Ok("401".to_string()),
+            },
+            SnapshotEntry {
+                index: 0x2000,
+                sub_index: 1,
+                name: "Setpoint, raw".to_string(),
+                data_type: SdoDataType::Int16,
+                raw: Vec::new(),
+                value: Err("SDO abort 0x06020000: Object does not exist".to_string()),
+            },
+        ];
+
+        let directory = TestDir::new("snapshot");
+        let path = directory.join("snapshot.csv");
+        save(&path, &entries).unwrap();
+        let loaded = load(&path).unwrap();
+
+        assert_eq!(loaded, entries);
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("Index,SubIndex,Name,DataType,Raw,Value,Error\n0x1000,0x00,Device type,UInt32,91 01 00 00,401,\n"));
+    }
+
+    #[test]
+    fn test_load_rejects_malformed_rows() {
+        let directory = TestDir::new("snapshot_malformed");
+        let path = directory.join("snapshot.csv");
+        let header = "Index,SubIndex,Name,DataType,Raw,Value,Error\n";
+        for row in ["0x1000,0x00,Device type,UInt32,91 0G,401,", "0xXYZ,0x00,Device type,UInt32,,401,", "0x1000,0x00,Device type,Float128,,401,"] {
+            std::fs::write(&path, format!("{}{}\n", header, row)).unwrap();
+            assert!(load(&path).is_err(), "{}", row);
+        }
+        assert!(load(&directory.join("missing.csv")).is_err());
+    }
+
+    #[test]
+    fn test_diff_lists_changed_and_missing_objects() {
+        let entry = |index: u16, value: &str| SnapshotEntry {
+            index,
+            sub_index: 0,
+            name: format!("Object {:04X}", index),
+            data_type: SdoDataType::UInt8,
+            raw: vec![value.parse().unwrap()],
+            value: Ok(value.to_string()),
+        };
+        let before = vec![entry(0x1000, "1"), entry(0x2000, "5"), entry(0x3000, "7")];
+        let after = vec![entry(0x1000, "1"), entry(0x2000, "6"), entry(0x4000, "9")];
+
+        let changes = diff(&before, &after);
+        let indexes: Vec<u16> = changes.iter().map(|change| change.index).collect();
+        assert_eq!(indexes, vec![0x2000, 0x3000, 0x4000]);
+        assert_eq!(changes[0].after, Some(Ok("6".to_string())));
+        assert_eq!(changes[1].after, None);
+        assert_eq!(changes[2].before, None);
+    }
+}
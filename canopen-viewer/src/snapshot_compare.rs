@@ -0,0 +1,235 @@
+// snapshot_compare.rs - Window comparing two snapshots, or a snapshot with live values
+//
+// "Before" is always loaded from a file. "After" is another file or a fresh snapshot
+// of the connected node, e.g. to see what changed after a firmware update.
+use eframe::egui::{self, Color32};
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use crate::snapshot::{self, SnapshotChange, SnapshotEntry};
+
+/// Entries of one side and where they came from
+struct Side {
+    label: String,
+    entries: Vec<SnapshotEntry>,
+}
+
+pub struct SnapshotCompare {
+    pub open: bool,
+    before: Option<Side>,
+    after: Option<Side>,
+    /// A live snapshot was requested for the "after" side
+    waiting_for_live: bool,
+    range_from: String,
+    range_to: String,
+    error: Option<String>,
+}
+
+impl SnapshotCompare {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            before: None,
+            after: None,
+            waiting_for_live: false,
+            range_from: String::new(),
+            range_to: String::new(),
+            error: None,
+        }
+    }
+
+    pub fn waiting_for_live(&self) -> bool {
+        self.waiting_for_live
+    }
+
+    /// Use a finished live snapshot as the "after" side
+    pub fn set_live(&mut self, entries: Vec<SnapshotEntry>) {
+        self.waiting_for_live = false;
+        let label = format!("Live ({})", chrono::Local::now().format("%H:%M:%S"));
+        self.after = Some(Side { label, entries });
+    }
+
+    pub fn cancel_live(&mut self) {
+        self.waiting_for_live = false;
+    }
+
+    /// Draw the window; returns `true` when a live snapshot should be taken
+    pub fn show(&mut self, ctx: &egui::Context, live_available: bool) -> bool {
+        let mut take_live = false;
+        let mut open = self.open;
+
+        egui::Window::new("Compare Snapshots")
+            .open(&mut open)
+            .default_width(600.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Before:");
+                    ui.label(self.before.as_ref().map_or("—", |side| side.label.as_str()));
+                    if ui.button("📂 Load...").clicked() {
+                        if let Some(side) = self.load_side() {
+                            self.before = Some(side);
+                        }
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("After:");
+                    if self.waiting_for_live {
+                        ui.spinner();
+                        ui.label("Reading live values...");
+                    } else {
+                        ui.label(self.after.as_ref().map_or("—", |side| side.label.as_str()));
+                    }
+                    if ui.button("📂 Load...").clicked() {
+                        if let Some(side) = self.load_side() {
+                            self.after = Some(side);
+                        }
+                    }
+                    if ui.add_enabled(live_available && !self.waiting_for_live, egui::Button::new("📷 Live values"))
+                        .on_hover_text("Take a snapshot of the connected node")
+                        .clicked()
+                    {
+                        self.waiting_for_live = true;
+                        take_live = true;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Index range (hex):");
+                    ui.add(egui::TextEdit::singleline(&mut self.range_from).hint_text("0000").desired_width(50.0));
+                    ui.label("–");
+                    ui.add(egui::TextEdit::singleline(&mut self.range_to).hint_text("FFFF").desired_width(50.0));
+                });
+
+                if let Some(err) = &self.error {
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+                }
+
+                ui.separator();
+                self.draw_changes(ui);
+            });
+
+        self.open = open;
+        take_live
+    }
+
+    fn load_side(&mut self) -> Option<Side> {
+        let path = rfd::FileDialog::new().add_filter("Snapshot", &["csv"]).pick_file()?;
+        match snapshot::load(&path) {
+            Ok(entries) => {
+                self.error = None;
+                Some(Side { label: file_label(&path), entries })
+            }
+            Err(e) => {
+                self.error = Some(format!("Failed to load {}: {}", file_label(&path), e));
+                None
+            }
+        }
+    }
+
+    /// Indexes to show; an empty bound is open
+    fn index_range(&self) -> RangeInclusive<u16> {
+        let parse = |text: &str, default| {
+            u16::from_str_radix(text.trim().trim_start_matches("0x"), 16).unwrap_or(default)
+        };
+        parse(&self.range_from, 0)..=parse(&self.range_to, u16::MAX)
+    }
+
+    fn draw_changes(&self, ui: &mut egui::Ui) {
+        let (Some(before), Some(after)) = (&self.before, &self.after) else {
+            ui.label("Load a snapshot for both sides to compare them.");
+            return;
+        };
+
+        let range = self.index_range();
+        let changes: Vec<SnapshotChange> = snapshot::diff(&before.entries, &after.entries)
+            .into_iter()
+            .filter(|change| range.contains(&change.index))
+            .collect();
+
+        if changes.is_empty() {
+            ui.label("No differences.");
+            return;
+        }
+        ui.label(format!("{} objects differ", changes.len()));
+
+        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            egui::Grid::new("snapshot_diff_grid")
+                .num_columns(4)
+                .spacing([10.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Address");
+                    ui.label("Name");
+                    ui.label("Before");
+                    ui.label("After");
+                    ui.end_row();
+
+                    for change in &changes {
+                        ui.label(format!("{:04X}:{:02X}", change.index, change.sub_index));
+                        ui.label(&change.name);
+                        value_label(ui, &change.before);
+                        value_label(ui, &change.after);
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
+
+fn value_label(ui: &mut egui::Ui, value: &Option<Result<String, String>>) {
+    match value {
+        Some(Ok(value)) => {
+            ui.label(value);
+        }
+        Some(Err(err)) => {
+            ui.colored_label(Color32::from_rgb(255, 100, 100), "error").on_hover_text(err);
+        }
+        None => {
+            ui.weak("missing");
+        }
+    }
+}
+
+fn file_label(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(from: &str, to: &str) -> RangeInclusive<u16> {
+        SnapshotCompare { range_from: from.to_string(), range_to: to.to_string(), ..SnapshotCompare::new() }.index_range()
+    }
+
+    #[test]
+    fn test_index_range() {
+        assert_eq!(range("", ""), 0x0000..=0xFFFF);
+        assert_eq!(range("2000", "20FF"), 0x2000..=0x20FF);
+        assert_eq!(range("0x6000", " 0x6FFF "), 0x6000..=0x6FFF);
+        // An unreadable bound is left open
+        assert_eq!(range("zz", "1FFF"), 0x0000..=0x1FFF);
+        assert_eq!(range("1000", "10000"), 0x1000..=0xFFFF);
+    }
+
+    #[test]
+    fn test_live_snapshot_fills_the_after_side() {
+        let mut compare = SnapshotCompare::new();
+        compare.waiting_for_live = true;
+        compare.cancel_live();
+        assert!(!compare.waiting_for_live());
+        assert!(compare.after.is_none());
+
+        compare.waiting_for_live = true;
+        compare.set_live(Vec::new());
+        assert!(!compare.waiting_for_live());
+        assert!(compare.after.as_ref().is_some_and(|side| side.label.starts_with("Live (")));
+    }
+
+    #[test]
+    fn test_file_label() {
+        assert_eq!(file_label(Path::new("/home/user/snapshots/node5.csv")), "node5.csv");
+        assert_eq!(file_label(Path::new("/")), "");
+    }
+}
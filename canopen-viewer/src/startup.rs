@@ -0,0 +1,294 @@
+// startup.rs - NMT-master style startup of the monitored node (CiA 302 boot-up)
+//
+// In manager mode the viewer brings the node up itself when it connects and after each
+// boot-up, for test benches without another master: the identity object (0x1018) is
+// checked against the expected values, the node's DCF is written, its heartbeat
+// producer is set so the consumer time can supervise it, and NMT Start is sent. The
+// sequence stops at the first failed step, leaving the node pre-operational.
+use canopen_common::SdoDataType;
+use configparser::ini::Ini;
+use eframe::egui::{self, Color32};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::sdo_write::parse_eds_number;
+use crate::snapshot::SnapshotEntry;
+
+/// Identity object
+pub const IDENTITY_INDEX: u16 = 0x1018;
+/// Producer heartbeat time
+pub const HEARTBEAT_PRODUCER_INDEX: u16 = 0x1017;
+
+/// Identity the node must report before it is configured; unset fields aren't checked
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExpectedIdentity {
+    pub vendor_id: Option<u32>,
+    pub product_code: Option<u32>,
+    pub revision: Option<u32>,
+    pub serial_number: Option<u32>,
+}
+
+impl ExpectedIdentity {
+    /// Vendor, product and revision from the `[DeviceInfo]` section of an EDS
+    pub fn from_eds(path: &Path) -> Result<Self, String> {
+        let mut eds = Ini::new();
+        eds.load(path)?;
+        let number = |key: &str| eds.get("DeviceInfo", key)
+            .and_then(|value| parse_eds_number(&value, &SdoDataType::UInt32))
+            .map(|value| value as u32);
+        Ok(Self {
+            vendor_id: number("VendorNumber"),
+            product_code: number("ProductNumber"),
+            revision: number("RevisionNumber"),
+            serial_number: None,
+        })
+    }
+
+    /// Sub-index of 0x1018, name and expected value of the fields to check
+    pub fn fields(&self) -> Vec<(u8, &'static str, u32)> {
+        [
+            (1, "Vendor ID", self.vendor_id),
+            (2, "Product code", self.product_code),
+            (3, "Revision", self.revision),
+            (4, "Serial number", self.serial_number),
+        ]
+        .into_iter()
+        .filter_map(|(sub_index, name, expected)| Some((sub_index, name, expected?)))
+        .collect()
+    }
+}
+
+/// Outcome of the identity check from the values read for `fields()`
+pub fn check_identity(expected: &ExpectedIdentity, actual: &[(u8, u32)]) -> Result<String, String> {
+    let fields = expected.fields();
+    if fields.is_empty() {
+        return Ok("no expected identity set, skipped".to_string());
+    }
+    let mismatches: Vec<String> = fields.iter()
+        .filter_map(|&(sub_index, name, expected)| {
+            let actual = actual.iter().find(|(sub, _)| *sub == sub_index).map(|&(_, value)| value);
+            match actual {
+                Some(actual) if actual == expected => None,
+                Some(actual) => Some(format!("{} is 0x{:08X}, expected 0x{:08X}", name, actual, expected)),
+                None => Some(format!("{} not read", name)),
+            }
+        })
+        .collect();
+    if mismatches.is_empty() {
+        Ok(format!("{} identity fields match", fields.len()))
+    } else {
+        Err(mismatches.join("; "))
+    }
+}
+
+/// Producer heartbeat time to write so heartbeats arrive within the consumer time, if
+/// the current one doesn't already
+pub fn producer_time(current_ms: u16, consumer_time: Duration) -> Option<u16> {
+    let consumer_ms = consumer_time.as_millis().min(u16::MAX as u128) as u16;
+    (current_ms == 0 || current_ms >= consumer_ms).then(|| (consumer_ms / 2).max(1))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartupStep {
+    Identity,
+    Configuration,
+    Heartbeat,
+    Start,
+}
+
+impl StartupStep {
+    pub const ALL: [StartupStep; 4] = [Self::Identity, Self::Configuration, Self::Heartbeat, Self::Start];
+}
+
+impl fmt::Display for StartupStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Identity => write!(f, "Check identity"),
+            Self::Configuration => write!(f, "Write DCF"),
+            Self::Heartbeat => write!(f, "Set heartbeat"),
+            Self::Start => write!(f, "NMT Start"),
+        }
+    }
+}
+
+/// Everything the communication thread needs to run the sequence
+#[derive(Debug, Clone)]
+pub struct StartupPlan {
+    pub identity: ExpectedIdentity,
+    /// DCF values in write order; empty without a DCF
+    pub dcf_entries: Vec<SnapshotEntry>,
+    pub consumer_time: Option<Duration>,
+}
+
+pub enum StartupAction {
+    Run,
+    /// Manager mode or the expected identity were changed
+    Save,
+}
+
+pub struct StartupPanel {
+    pub open: bool,
+    running: bool,
+    results: Vec<(StartupStep, Result<String, String>)>,
+    error: Option<String>,
+}
+
+impl StartupPanel {
+    pub fn new() -> Self {
+        Self { open: false, running: false, results: Vec::new(), error: None }
+    }
+
+    pub fn running(&self) -> bool {
+        self.running
+    }
+
+    pub fn start(&mut self) {
+        self.running = true;
+        self.results.clear();
+    }
+
+    pub fn step_finished(&mut self, step: StartupStep, result: Result<String, String>) {
+        self.results.push((step, result));
+    }
+
+    /// Returns whether every step succeeded
+    pub fn finished(&mut self) -> bool {
+        self.running = false;
+        self.results.len() == StartupStep::ALL.len() && self.results.iter().all(|(_, result)| result.is_ok())
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        connected: bool,
+        manager_mode: &mut bool,
+        identity: &mut ExpectedIdentity,
+        eds_path: Option<&Path>,
+        dcf_path: Option<&Path>,
+    ) -> Option<StartupAction> {
+        let mut action = None;
+        let mut open = self.open;
+
+        egui::Window::new("Startup Sequence")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if ui.checkbox(manager_mode, "Manager mode: run on connect and after boot-up").changed() {
+                    action = Some(StartupAction::Save);
+                }
+                ui.label("Use only when the viewer is the only NMT master on the bus.");
+                ui.separator();
+
+                ui.strong("Expected identity (0x1018)");
+                ui.add_enabled_ui(!self.running, |ui| {
+                    egui::Grid::new("startup_identity_grid").num_columns(2).show(ui, |ui| {
+                        for (name, value) in [
+                            ("Vendor ID", &mut identity.vendor_id),
+                            ("Product code", &mut identity.product_code),
+                            ("Revision", &mut identity.revision),
+                            ("Serial number", &mut identity.serial_number),
+                        ] {
+                            let mut check = value.is_some();
+                            if ui.checkbox(&mut check, name).changed() {
+                                *value = check.then_some(0);
+                                action = Some(StartupAction::Save);
+                            }
+                            if let Some(value) = value {
+                                let response = ui.add(egui::DragValue::new(value).hexadecimal(8, false, true));
+                                if response.drag_stopped() || response.lost_focus() {
+                                    action = Some(StartupAction::Save);
+                                }
+                            } else {
+                                ui.weak("not checked");
+                            }
+                            ui.end_row();
+                        }
+                    });
+                    if ui.add_enabled(eds_path.is_some(), egui::Button::new("📄 Take from EDS")).clicked() {
+                        if let Some(path) = eds_path {
+                            match ExpectedIdentity::from_eds(path) {
+                                Ok(from_eds) => {
+                                    *identity = ExpectedIdentity { serial_number: identity.serial_number, ..from_eds };
+                                    self.error = None;
+                                    action = Some(StartupAction::Save);
+                                }
+                                Err(e) => self.error = Some(format!("Failed to read EDS: {}", e)),
+                            }
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("DCF:");
+                    match dcf_path.and_then(|path| path.file_name()) {
+                        Some(name) => ui.label(name.to_string_lossy().to_string()),
+                        None => ui.weak("none (assign the boot DCF in the NMT menu)"),
+                    };
+                });
+                ui.separator();
+
+                if ui.add_enabled(connected && !self.running, egui::Button::new("▶ Run now")).clicked() {
+                    action = Some(StartupAction::Run);
+                }
+                if let Some(error) = &self.error {
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", error));
+                }
+
+                for step in StartupStep::ALL {
+                    let result = self.results.iter().find(|(done, _)| *done == step).map(|(_, result)| result);
+                    ui.horizontal(|ui| {
+                        match result {
+                            Some(Ok(message)) => {
+                                ui.colored_label(Color32::from_rgb(0, 200, 0), format!("✔ {}", step));
+                                ui.weak(message);
+                            }
+                            Some(Err(err)) => {
+                                ui.colored_label(Color32::from_rgb(255, 100, 100), format!("✖ {}", step));
+                                ui.colored_label(Color32::from_rgb(255, 100, 100), err);
+                            }
+                            None if self.running => {
+                                ui.spinner();
+                                ui.label(step.to_string());
+                            }
+                            None => {
+                                ui.weak(step.to_string());
+                            }
+                        }
+                    });
+                }
+            });
+
+        self.open = open;
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn test_startup_checks() {
+        let directory = TestDir::new("startup");
+        let path = directory.join("device.eds");
+        std::fs::write(&path, "[DeviceInfo]\r\nVendorNumber=0x000001A2\r\nProductNumber=42\r\n").unwrap();
+        let expected = ExpectedIdentity::from_eds(&path).unwrap();
+        assert_eq!(expected, ExpectedIdentity { vendor_id: Some(0x1A2), product_code: Some(42), ..Default::default() });
+
+        assert_eq!(expected.fields(), vec![(1, "Vendor ID", 0x1A2), (2, "Product code", 42)]);
+        assert!(check_identity(&expected, &[(1, 0x1A2), (2, 42)]).is_ok());
+        assert_eq!(
+            check_identity(&expected, &[(1, 0x1A2), (2, 43)]),
+            Err("Product code is 0x0000002B, expected 0x0000002A".to_string())
+        );
+        assert!(check_identity(&ExpectedIdentity::default(), &[]).is_ok());
+
+        let consumer = Duration::from_millis(1000);
+        assert_eq!(producer_time(0, consumer), Some(500));
+        assert_eq!(producer_time(1500, consumer), Some(500));
+        assert_eq!(producer_time(200, consumer), None);
+    }
+}
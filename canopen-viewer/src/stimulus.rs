@@ -0,0 +1,306 @@
+// stimulus.rs - Periodic writes of a value pattern to one object
+//
+// Exercises an actuator setpoint (constant, ramp, square, sine or a list of steps)
+// while the response is watched in the TPDO plots. The communication thread evaluates
+// the waveform at every tick and stops at the first failed write.
+use canopen_common::SdoDataType;
+use eframe::egui::{self, Color32};
+use std::f64::consts::TAU;
+
+use crate::communication::SdoAddress;
+use crate::sdo_console::DATA_TYPES;
+use crate::sdo_write::{integer_range, WriteValue};
+
+/// Value pattern over time; periods are in seconds
+#[derive(Debug, Clone, PartialEq)]
+pub enum Waveform {
+    Constant { value: f64 },
+    /// Rises from `from` to `to` over one period, then starts again
+    Ramp { from: f64, to: f64, period: f64 },
+    Square { low: f64, high: f64, period: f64 },
+    Sine { offset: f64, amplitude: f64, period: f64 },
+    /// Each value is held for `hold` seconds, the list repeats
+    Steps { values: Vec<f64>, hold: f64 },
+}
+
+impl Waveform {
+    const KINDS: [&'static str; 5] = ["Constant", "Ramp", "Square", "Sine", "Steps"];
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Constant { .. } => "Constant",
+            Self::Ramp { .. } => "Ramp",
+            Self::Square { .. } => "Square",
+            Self::Sine { .. } => "Sine",
+            Self::Steps { .. } => "Steps",
+        }
+    }
+
+    fn default_for(kind: &str) -> Self {
+        match kind {
+            "Ramp" => Self::Ramp { from: 0.0, to: 100.0, period: 10.0 },
+            "Square" => Self::Square { low: 0.0, high: 100.0, period: 2.0 },
+            "Sine" => Self::Sine { offset: 50.0, amplitude: 50.0, period: 5.0 },
+            "Steps" => Self::Steps { values: vec![0.0, 50.0, 100.0], hold: 1.0 },
+            _ => Self::Constant { value: 0.0 },
+        }
+    }
+
+    /// Value `t` seconds after the start
+    pub fn value_at(&self, t: f64) -> f64 {
+        let phase = |period: f64| if period > 0.0 { (t / period).fract() } else { 0.0 };
+        match self {
+            Self::Constant { value } => *value,
+            Self::Ramp { from, to, period } => from + (to - from) * phase(*period),
+            Self::Square { low, high, period } => if phase(*period) < 0.5 { *low } else { *high },
+            Self::Sine { offset, amplitude, period } => offset + amplitude * (TAU * phase(*period)).sin(),
+            Self::Steps { values, hold } => {
+                if values.is_empty() || *hold <= 0.0 {
+                    return values.first().copied().unwrap_or(0.0);
+                }
+                values[(t / hold) as usize % values.len()]
+            }
+        }
+    }
+}
+
+/// Payload for a waveform value; integers are rounded and clamped to the type's range
+pub fn encode_value(value: f64, data_type: &SdoDataType) -> Result<Vec<u8>, String> {
+    match integer_range(data_type) {
+        Some(range) => {
+            let number = (value.round() as i64).clamp(*range.start(), *range.end());
+            WriteValue::Integer(number).encode(data_type)
+        }
+        None => WriteValue::Real(value).encode(data_type),
+    }
+}
+
+/// Stimulus settings sent to the communication thread
+#[derive(Debug, Clone)]
+pub struct StimulusConfig {
+    pub address: SdoAddress,
+    pub data_type: SdoDataType,
+    pub waveform: Waveform,
+    pub interval_ms: u64,
+}
+
+pub enum StimulusAction {
+    Start(StimulusConfig),
+    Stop,
+}
+
+pub struct StimulusGenerator {
+    pub open: bool,
+    index: u16,
+    sub_index: u8,
+    data_type: SdoDataType,
+    waveform: Waveform,
+    /// Text of the step list, e.g. "0, 50, 100"
+    steps_text: String,
+    interval_ms: u64,
+    running: bool,
+    writes: usize,
+    last_value: Option<f64>,
+    error: Option<String>,
+}
+
+impl StimulusGenerator {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            index: 0x2000,
+            sub_index: 0,
+            data_type: SdoDataType::Int16,
+            waveform: Waveform::Sine { offset: 50.0, amplitude: 50.0, period: 5.0 },
+            steps_text: "0, 50, 100".to_string(),
+            interval_ms: 100,
+            running: false,
+            writes: 0,
+            last_value: None,
+            error: None,
+        }
+    }
+
+    /// Open the window for an object picked in the SDO list
+    pub fn open_for(&mut self, address: &SdoAddress, data_type: SdoDataType) {
+        if !self.running {
+            self.index = address.index;
+            self.sub_index = address.sub_index;
+            self.data_type = data_type;
+        }
+        self.open = true;
+    }
+
+    pub fn written(&mut self, value: f64, result: Result<(), String>) {
+        match result {
+            Ok(()) => {
+                self.writes += 1;
+                self.last_value = Some(value);
+            }
+            Err(err) => self.error = Some(format!("Write of {} failed: {}", value, err)),
+        }
+    }
+
+    pub fn stopped(&mut self) {
+        self.running = false;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, connected: bool) -> Option<StimulusAction> {
+        let mut action = None;
+        let mut open = self.open;
+
+        egui::Window::new("Stimulus Generator")
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(!self.running, |ui| self.draw_settings(ui));
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if self.running {
+                        ui.spinner();
+                        if ui.button("⏹ Stop").clicked() {
+                            action = Some(StimulusAction::Stop);
+                        }
+                    } else if ui.add_enabled(connected, egui::Button::new("▶ Start")).clicked() {
+                        match self.config() {
+                            Ok(config) => {
+                                self.running = true;
+                                self.writes = 0;
+                                self.last_value = None;
+                                self.error = None;
+                                action = Some(StimulusAction::Start(config));
+                            }
+                            Err(err) => self.error = Some(err),
+                        }
+                    }
+                    ui.label(format!("{} writes", self.writes));
+                    if let Some(value) = self.last_value {
+                        ui.label(format!("last: {:.2}", value));
+                    }
+                });
+
+                if let Some(err) = &self.error {
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+                }
+            });
+
+        // Don't keep writing to the device without the window that stops it
+        if !open && self.running {
+            action = Some(StimulusAction::Stop);
+        }
+        self.open = open;
+        action
+    }
+
+    fn draw_settings(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Object:");
+            ui.add(egui::DragValue::new(&mut self.index).hexadecimal(4, false, true).prefix("0x"));
+            ui.label("sub");
+            ui.add(egui::DragValue::new(&mut self.sub_index));
+            egui::ComboBox::from_id_salt("stimulus_type")
+                .selected_text(format!("{:?}", self.data_type))
+                .show_ui(ui, |ui| {
+                    for data_type in DATA_TYPES.into_iter().filter(|dt| !matches!(dt, SdoDataType::VisibleString | SdoDataType::OctetString)) {
+                        let label = format!("{:?}", data_type);
+                        ui.selectable_value(&mut self.data_type, data_type, label);
+                    }
+                });
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Pattern:");
+            egui::ComboBox::from_id_salt("stimulus_kind")
+                .selected_text(self.waveform.kind())
+                .show_ui(ui, |ui| {
+                    for kind in Waveform::KINDS {
+                        if ui.selectable_label(self.waveform.kind() == kind, kind).clicked() {
+                            self.waveform = Waveform::default_for(kind);
+                        }
+                    }
+                });
+            ui.label("every");
+            ui.add(egui::DragValue::new(&mut self.interval_ms).range(10..=60_000).suffix(" ms"));
+        });
+
+        ui.horizontal(|ui| {
+            let number = |ui: &mut egui::Ui, label: &str, value: &mut f64| {
+                ui.label(label);
+                ui.add(egui::DragValue::new(value).speed(0.1));
+            };
+            let seconds = |ui: &mut egui::Ui, label: &str, value: &mut f64| {
+                ui.label(label);
+                ui.add(egui::DragValue::new(value).speed(0.1).range(0.01..=3600.0).suffix(" s"));
+            };
+            match &mut self.waveform {
+                Waveform::Constant { value } => number(ui, "Value", value),
+                Waveform::Ramp { from, to, period } => {
+                    number(ui, "From", from);
+                    number(ui, "To", to);
+                    seconds(ui, "Period", period);
+                }
+                Waveform::Square { low, high, period } => {
+                    number(ui, "Low", low);
+                    number(ui, "High", high);
+                    seconds(ui, "Period", period);
+                }
+                Waveform::Sine { offset, amplitude, period } => {
+                    number(ui, "Offset", offset);
+                    number(ui, "Amplitude", amplitude);
+                    seconds(ui, "Period", period);
+                }
+                Waveform::Steps { hold, .. } => {
+                    ui.label("Values");
+                    ui.add(egui::TextEdit::singleline(&mut self.steps_text).desired_width(140.0));
+                    seconds(ui, "Hold", hold);
+                }
+            }
+        });
+    }
+
+    fn config(&self) -> Result<StimulusConfig, String> {
+        let mut waveform = self.waveform.clone();
+        if let Waveform::Steps { values, .. } = &mut waveform {
+            *values = self.steps_text.split(',')
+                .map(|value| value.trim().parse::<f64>().map_err(|_| format!("'{}' is not a number", value.trim())))
+                .collect::<Result<_, _>>()?;
+        }
+        // Catch type problems (e.g. strings) before the first tick
+        encode_value(waveform.value_at(0.0), &self.data_type)?;
+
+        Ok(StimulusConfig {
+            address: SdoAddress { index: self.index, sub_index: self.sub_index },
+            data_type: self.data_type.clone(),
+            waveform,
+            interval_ms: self.interval_ms,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waveform_values() {
+        let ramp = Waveform::Ramp { from: 0.0, to: 100.0, period: 10.0 };
+        assert_eq!(ramp.value_at(2.5), 25.0);
+        assert_eq!(ramp.value_at(12.5), 25.0);
+
+        let square = Waveform::Square { low: -1.0, high: 1.0, period: 2.0 };
+        assert_eq!(square.value_at(0.5), -1.0);
+        assert_eq!(square.value_at(1.5), 1.0);
+
+        let sine = Waveform::Sine { offset: 10.0, amplitude: 5.0, period: 4.0 };
+        assert!((sine.value_at(1.0) - 15.0).abs() < 1e-9);
+
+        let steps = Waveform::Steps { values: vec![1.0, 2.0, 3.0], hold: 0.5 };
+        assert_eq!(steps.value_at(0.7), 2.0);
+        assert_eq!(steps.value_at(1.6), 1.0);
+
+        // Integers are rounded and clamped to the type
+        assert_eq!(encode_value(300.4, &SdoDataType::UInt8), Ok(vec![0xFF]));
+        assert_eq!(encode_value(-1.6, &SdoDataType::Int16), Ok(vec![0xFE, 0xFF]));
+    }
+}
@@ -0,0 +1,188 @@
+// svg_export.rs - Plots written as SVG figures
+//
+// A screenshot has the resolution of the screen, which looks poor once printed. The SVG
+// export draws the recorded series again as vector graphics: axes with ticks, grid, the
+// series in their channel styles, the markers and a legend. The axes are laid out from
+// the data (or the fixed Y range), not from the zoom of the plot on screen, and each
+// series is reduced to a min/max envelope fine enough for print.
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
+
+use crate::channel_style::{ChannelStyle, Dash};
+use crate::decimation;
+
+const WIDTH: f64 = 900.0;
+const HEIGHT: f64 = 500.0;
+const LEFT: f64 = 80.0;
+const RIGHT: f64 = 220.0;
+const TOP: f64 = 40.0;
+const BOTTOM: f64 = 60.0;
+/// Envelope buckets per series, several per point of the plot width
+const SVG_BUCKETS: usize = 2000;
+const MARKER_COLOR: &str = "rgb(100,149,237)";
+
+pub struct SvgSeries {
+    pub name: String,
+    pub style: ChannelStyle,
+    pub points: Vec<[f64; 2]>,
+}
+
+pub struct SvgPlot {
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    pub series: Vec<SvgSeries>,
+    /// Time and text of the markers
+    pub markers: Vec<(f64, String)>,
+    /// Value range of the Y axis; None fits the data
+    pub y_range: Option<(f64, f64)>,
+    pub x_format: Box<dyn Fn(f64) -> String>,
+    pub y_format: Box<dyn Fn(f64) -> String>,
+}
+
+/// About `count` evenly spaced round values (1, 2 or 5 times a power of ten) in the range
+pub fn nice_ticks(min: f64, max: f64, count: usize) -> Vec<f64> {
+    let span = max - min;
+    if !span.is_finite() || span <= 0.0 || count == 0 {
+        return vec![min];
+    }
+    let rough = span / count as f64;
+    let magnitude = 10f64.powf(rough.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0].into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| *step >= rough)
+        .unwrap_or(10.0 * magnitude);
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn dash_array(dash: Dash) -> &'static str {
+    match dash {
+        Dash::Solid => "",
+        Dash::Dashed => " stroke-dasharray=\"8,4\"",
+        Dash::Dotted => " stroke-dasharray=\"2,3\"",
+    }
+}
+
+/// Smallest and largest value of one coordinate; a span of one around a single value
+fn extent(values: impl Iterator<Item = f64>) -> Option<(f64, f64)> {
+    let (min, max) = values.fold(None, |range: Option<(f64, f64)>, value| {
+        Some(range.map_or((value, value), |(min, max)| (min.min(value), max.max(value))))
+    })?;
+    Some(if max > min { (min, max) } else { (min - 0.5, max + 0.5) })
+}
+
+/// X and Y range of the axes: all of the data, or the fixed Y range
+pub fn axis_ranges(plot: &SvgPlot) -> ((f64, f64), (f64, f64)) {
+    let points = || plot.series.iter().flat_map(|series| series.points.iter());
+    let x_range = extent(points().map(|point| point[0])).unwrap_or((0.0, 1.0));
+    let y_range = plot.y_range.filter(|(min, max)| max > min).unwrap_or_else(|| {
+        let (min, max) = extent(points().map(|point| point[1])).unwrap_or((0.0, 1.0));
+        let margin = (max - min) * 0.05;
+        (min - margin, max + margin)
+    });
+    (x_range, y_range)
+}
+
+pub fn render(plot: &SvgPlot) -> String {
+    let ((x_min, x_max), (y_min, y_max)) = axis_ranges(plot);
+
+    let plot_width = WIDTH - LEFT - RIGHT;
+    let plot_height = HEIGHT - TOP - BOTTOM;
+    let to_x = |x: f64| LEFT + (x - x_min) / (x_max - x_min) * plot_width;
+    let to_y = |y: f64| TOP + (y_max - y) / (y_max - y_min) * plot_height;
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" font-family=\"sans-serif\" font-size=\"12\">", WIDTH, HEIGHT, WIDTH, HEIGHT);
+    let _ = writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>");
+    let _ = writeln!(svg, "<text x=\"{}\" y=\"24\" font-size=\"16\" text-anchor=\"middle\">{}</text>", LEFT + plot_width / 2.0, escape(&plot.title));
+
+    // Grid and tick labels
+    for x in nice_ticks(x_min, x_max, 8) {
+        let position = to_x(x);
+        let _ = writeln!(svg, "<line x1=\"{:.2}\" y1=\"{}\" x2=\"{:.2}\" y2=\"{}\" stroke=\"#ddd\"/>", position, TOP, position, TOP + plot_height);
+        let _ = writeln!(svg, "<text x=\"{:.2}\" y=\"{}\" text-anchor=\"middle\">{}</text>", position, TOP + plot_height + 18.0, escape(&(plot.x_format)(x)));
+    }
+    for y in nice_ticks(y_min, y_max, 6) {
+        let position = to_y(y);
+        let _ = writeln!(svg, "<line x1=\"{}\" y1=\"{:.2}\" x2=\"{}\" y2=\"{:.2}\" stroke=\"#ddd\"/>", LEFT, position, LEFT + plot_width, position);
+        let _ = writeln!(svg, "<text x=\"{}\" y=\"{:.2}\" text-anchor=\"end\" dominant-baseline=\"middle\">{}</text>", LEFT - 6.0, position, escape(&(plot.y_format)(y)));
+    }
+    let _ = writeln!(svg, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"black\"/>", LEFT, TOP, plot_width, plot_height);
+    let _ = writeln!(svg, "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>", LEFT + plot_width / 2.0, HEIGHT - 16.0, escape(&plot.x_label));
+    let _ = writeln!(svg, "<text transform=\"translate(18 {}) rotate(-90)\" text-anchor=\"middle\">{}</text>", TOP + plot_height / 2.0, escape(&plot.y_label));
+
+    // Series and markers, clipped to the plot area
+    let _ = writeln!(svg, "<clipPath id=\"plot-area\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/></clipPath>", LEFT, TOP, plot_width, plot_height);
+    let _ = writeln!(svg, "<g clip-path=\"url(#plot-area)\">");
+    for series in &plot.series {
+        let coordinates: Vec<String> = decimation::min_max(series.points.iter(), SVG_BUCKETS).into_iter()
+            .map(|[x, y]| format!("{:.2},{:.2}", to_x(x), to_y(y)))
+            .collect();
+        let [r, g, b] = series.style.color;
+        let _ = writeln!(svg, "<polyline fill=\"none\" stroke=\"rgb({},{},{})\" stroke-width=\"{}\"{} points=\"{}\"/>",
+            r, g, b, series.style.width, dash_array(series.style.dash), coordinates.join(" "));
+    }
+    for (time, text) in plot.markers.iter().filter(|(time, _)| (x_min..=x_max).contains(time)) {
+        let position = to_x(*time);
+        let _ = writeln!(svg, "<line x1=\"{:.2}\" y1=\"{}\" x2=\"{:.2}\" y2=\"{}\" stroke=\"{}\" stroke-dasharray=\"6,4\"/>", position, TOP, position, TOP + plot_height, MARKER_COLOR);
+        let _ = writeln!(svg, "<text x=\"{:.2}\" y=\"{}\" fill=\"{}\">{}</text>", position + 3.0, TOP + 12.0, MARKER_COLOR, escape(text));
+    }
+    let _ = writeln!(svg, "</g>");
+
+    // Legend right of the plot
+    for (row, series) in plot.series.iter().enumerate() {
+        let y = TOP + 10.0 + row as f64 * 20.0;
+        let [r, g, b] = series.style.color;
+        let _ = writeln!(svg, "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"rgb({},{},{})\" stroke-width=\"{}\"{}/>",
+            WIDTH - RIGHT + 15.0, y, WIDTH - RIGHT + 45.0, y, r, g, b, series.style.width.max(2.0), dash_array(series.style.dash));
+        let _ = writeln!(svg, "<text x=\"{}\" y=\"{}\" dominant-baseline=\"middle\">{}</text>", WIDTH - RIGHT + 52.0, y, escape(&series.name));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub fn save(path: &Path, plot: &SvgPlot) -> io::Result<()> {
+    std::fs::write(path, render(plot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_svg_ticks_and_rendering() {
+        assert_eq!(nice_ticks(0.0, 10.0, 5), vec![0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+        assert_eq!(nice_ticks(-1.0, 1.0, 4), vec![-1.0, -0.5, 0.0, 0.5, 1.0]);
+        assert_eq!(nice_ticks(5.0, 5.0, 5), vec![5.0]);
+
+        let format = |value: f64| format!("{}", value);
+        let plot = SvgPlot {
+            title: "Speed & <torque>".to_string(),
+            x_label: "Time (seconds)".to_string(),
+            y_label: "Value".to_string(),
+            series: vec![SvgSeries {
+                name: "Velocity".to_string(),
+                style: ChannelStyle { dash: Dash::Dashed, ..ChannelStyle::new([1, 2, 3]) },
+                points: vec![[0.0, 0.0], [1.0, 5.0], [2.0, 10.0]],
+            }],
+            markers: vec![(1.0, "Valve opened".to_string()), (9.0, "Outside".to_string())],
+            y_range: None,
+            x_format: Box::new(format),
+            y_format: Box::new(format),
+        };
+        let svg = render(&plot);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("Speed &amp; &lt;torque&gt;"));
+        assert!(svg.contains("stroke=\"rgb(1,2,3)\" stroke-width=\"1.5\" stroke-dasharray=\"8,4\" points=\""));
+        assert!(svg.contains(">Valve opened<"));
+        assert!(!svg.contains("Outside"));
+        assert!(svg.contains(">Velocity<"));
+    }
+}
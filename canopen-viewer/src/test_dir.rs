@@ -0,0 +1,40 @@
+// test_dir.rs - Scratch directory for tests that write files
+//
+// Tests run in parallel threads of one process, so the process ID alone doesn't keep
+// their files apart. Every directory gets its own number and is removed again when
+// the test is done, also when an assertion fails.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_NUMBER: AtomicU64 = AtomicU64::new(0);
+
+pub struct TestDir {
+    path: PathBuf,
+}
+
+impl TestDir {
+    /// Create an empty directory named after `name`
+    pub fn new(name: &str) -> Self {
+        let number = NEXT_NUMBER.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("canopen_{}_test_{}_{}", name, std::process::id(), number));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path of a file in the directory
+    pub fn join(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
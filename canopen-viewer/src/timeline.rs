@@ -0,0 +1,229 @@
+// timeline.rs - Alarms, emergencies and connection events on one time axis
+//
+// Whether a fault bit followed a voltage dip or the other way round is hard to tell from
+// separate lists. The timeline collects heartbeat alarms, EMCYs, connection losses and
+// markers the user sets, one lane per kind, timed from the same session epoch as the plots.
+// Markers are also drawn into every plot, written to the log and exported with the plot data.
+use chrono::{DateTime, Local};
+use eframe::egui::{self, Color32};
+use egui_plot::{Plot, Points};
+use std::collections::VecDeque;
+
+/// Events kept; the oldest are dropped first
+const MAX_EVENTS: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Alarm,
+    Emergency,
+    Connection,
+    Marker,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 4] = [EventKind::Alarm, EventKind::Emergency, EventKind::Connection, EventKind::Marker];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EventKind::Alarm => "Alarm",
+            EventKind::Emergency => "EMCY",
+            EventKind::Connection => "Connection",
+            EventKind::Marker => "Marker",
+        }
+    }
+
+    fn color(self) -> Color32 {
+        match self {
+            EventKind::Alarm => Color32::from_rgb(255, 165, 0),
+            EventKind::Emergency => Color32::from_rgb(255, 100, 100),
+            EventKind::Connection => Color32::from_rgb(150, 150, 150),
+            EventKind::Marker => Color32::from_rgb(100, 149, 237),
+        }
+    }
+
+    /// Row of the kind in the timeline plot
+    fn lane(self) -> f64 {
+        Self::ALL.iter().position(|kind| *kind == self).unwrap_or(0) as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TimelineEvent {
+    pub at: DateTime<Local>,
+    pub kind: EventKind,
+    pub text: String,
+}
+
+pub struct Timeline {
+    pub open: bool,
+    events: VecDeque<TimelineEvent>,
+    marker_text: String,
+    markers_created: usize,
+}
+
+/// Seconds from `reference` to `at`, the x position on plots timed from `reference`
+pub fn offset_seconds(at: DateTime<Local>, reference: DateTime<Local>) -> f64 {
+    (at - reference).num_milliseconds() as f64 / 1000.0
+}
+
+/// Label the x axis of a plot timed from `epoch` in seconds, or in local time of day
+pub fn time_axis(plot: Plot<'_>, epoch: DateTime<Local>, wall_clock: bool) -> Plot<'_> {
+    if !wall_clock {
+        return plot.x_axis_label("Time (seconds)");
+    }
+    plot.x_axis_label("Local time")
+        .x_axis_formatter(move |mark, _range| clock_time(epoch, mark.value, "%H:%M:%S"))
+}
+
+/// Time of day `seconds` after `epoch`
+pub fn clock_time(epoch: DateTime<Local>, seconds: f64, format: &str) -> String {
+    (epoch + chrono::Duration::milliseconds((seconds * 1000.0).round() as i64)).format(format).to_string()
+}
+
+/// Hover text of a time on a plot timed from `epoch`
+pub fn time_text(epoch: DateTime<Local>, seconds: f64, wall_clock: bool) -> String {
+    if wall_clock { clock_time(epoch, seconds, "%H:%M:%S%.3f") } else { format!("{:.3} s", seconds) }
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Self { open: false, events: VecDeque::new(), marker_text: String::new(), markers_created: 0 }
+    }
+
+    pub fn record_at(&mut self, at: DateTime<Local>, kind: EventKind, text: String) {
+        if self.events.len() >= MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(TimelineEvent { at, kind, text });
+    }
+
+    pub fn record(&mut self, kind: EventKind, text: String) {
+        self.record_at(Local::now(), kind, text);
+    }
+
+    /// Set a marker now, named after the text field or numbered; returns its text
+    pub fn add_marker(&mut self) -> String {
+        self.markers_created += 1;
+        let text = if self.marker_text.trim().is_empty() {
+            format!("Marker {}", self.markers_created)
+        } else {
+            self.marker_text.trim().to_string()
+        };
+        self.marker_text.clear();
+        self.record(EventKind::Marker, text.clone());
+        text
+    }
+
+    /// Text field and button to set a marker; returns the text of a marker just set
+    pub fn marker_input(&mut self, ui: &mut egui::Ui) -> Option<String> {
+        let edit = ui.add(egui::TextEdit::singleline(&mut self.marker_text).hint_text("Marker text").desired_width(200.0));
+        let entered = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        let clicked = ui.button("📍 Add marker").on_hover_text("Mark this moment on the timeline and in the plots").clicked();
+        (entered || clicked).then(|| self.add_marker())
+    }
+
+    /// Every event kept, oldest first
+    pub fn events(&self) -> impl Iterator<Item = &TimelineEvent> {
+        self.events.iter()
+    }
+
+    pub fn markers(&self) -> impl Iterator<Item = &TimelineEvent> {
+        self.events.iter().filter(|event| event.kind == EventKind::Marker)
+    }
+
+    /// `epoch` is the session epoch the plots are timed from. Returns the text of a marker
+    /// set in the window.
+    pub fn show(&mut self, ctx: &egui::Context, epoch: DateTime<Local>, wall_clock: bool) -> Option<String> {
+        let mut open = self.open;
+        let mut marker = None;
+        egui::Window::new("Event Timeline")
+            .open(&mut open)
+            .default_width(700.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    marker = self.marker_input(ui);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.add_enabled(!self.events.is_empty(), egui::Button::new("🗑 Clear")).clicked() {
+                            self.events.clear();
+                        }
+                    });
+                });
+                ui.separator();
+
+                if self.events.is_empty() {
+                    ui.label("No events yet.");
+                    return;
+                }
+
+                let plot = Plot::new("event_timeline")
+                    .height(180.0)
+                    .width(ui.available_width())
+                    .allow_scroll(false)
+                    .include_y(-0.5)
+                    .include_y(EventKind::ALL.len() as f64 - 0.5)
+                    .y_axis_formatter(|mark, _range| {
+                        EventKind::ALL.iter()
+                            .find(|kind| mark.value.fract() == 0.0 && kind.lane() == mark.value)
+                            .map_or_else(String::new, |kind| kind.label().to_string())
+                    });
+                time_axis(plot, epoch, wall_clock)
+                    .label_formatter(move |name, value| {
+                        if name.is_empty() { String::new() } else { format!("{}\n{}", name, time_text(epoch, value.x, wall_clock)) }
+                    })
+                    .show(ui, |plot_ui| {
+                        for event in &self.events {
+                            let position = [offset_seconds(event.at, epoch), event.kind.lane()];
+                            plot_ui.points(
+                                Points::new(vec![position])
+                                    .name(&event.text)
+                                    .radius(4.0)
+                                    .color(event.kind.color())
+                                    .shape(egui_plot::MarkerShape::Diamond),
+                            );
+                        }
+                    });
+
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    egui::Grid::new("timeline_events").num_columns(3).striped(true).show(ui, |ui| {
+                        for event in self.events.iter().rev() {
+                            ui.monospace(event.at.format("%H:%M:%S%.3f").to_string());
+                            ui.colored_label(event.kind.color(), event.kind.label());
+                            ui.label(&event.text);
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        self.open = open;
+        marker
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_timeline_events_and_markers() {
+        let reference = Local.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        let mut timeline = Timeline::new();
+        timeline.record_at(reference + chrono::Duration::milliseconds(1500), EventKind::Emergency, "Node 5: 0x2310".to_string());
+        timeline.add_marker();
+        timeline.marker_text = " Valve opened ".to_string();
+        timeline.add_marker();
+
+        let markers: Vec<&str> = timeline.markers().map(|event| event.text.as_str()).collect();
+        assert_eq!(markers, vec!["Marker 1", "Valve opened"]);
+        assert_eq!(offset_seconds(timeline.events[0].at, reference), 1.5);
+        assert_eq!(clock_time(reference, 1.5, "%S%.3f"), "21.500");
+        assert_eq!(EventKind::Marker.lane(), 3.0);
+
+        for i in 0..MAX_EVENTS {
+            timeline.record_at(reference, EventKind::Connection, i.to_string());
+        }
+        assert_eq!(timeline.events.len(), MAX_EVENTS);
+        assert_eq!(timeline.markers().count(), 0);
+    }
+}
@@ -0,0 +1,589 @@
+// trace.rs - Live raw CAN trace, candump style
+//
+// Every frame the interface receives is listed with its receive time, COB-ID, DLC and
+// data, plus what it means under the CANopen predefined connection set. TPDOs the
+// viewer discovered on the monitored node are named by their number even when they use
+// a custom COB-ID. Frames the viewer sends itself only show up if the interface echoes
+// them back.
+use canopen_common::{
+    get_abort_code_description, parse_emcy_frame, NmtCommand, NmtState, EMCY_COB_ID_BASE,
+    HEARTBEAT_COB_ID_BASE, LSS_MASTER_COB_ID, LSS_SLAVE_COB_ID, SYNC_COB_ID,
+};
+use chrono::{DateTime, Local};
+use eframe::egui::{self, Color32};
+use socketcan::EmbeddedFrame;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::canopen::timestamp::ReceivedFrame;
+use crate::communication::TpdoConfig;
+use crate::trace_export::{self, TraceFormat};
+
+/// Frames kept for scrolling back; older ones are discarded
+const MAX_TRACE_ROWS: usize = 10_000;
+
+/// A frame as listed in the trace and recorded to the logs
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEntry {
+    pub timestamp: DateTime<Local>,
+    /// 11-bit or 29-bit identifier, without flags
+    pub id: u32,
+    pub extended: bool,
+    pub remote: bool,
+    pub dlc: usize,
+    pub data: Vec<u8>,
+    /// Sent by the viewer rather than received
+    pub transmitted: bool,
+}
+
+impl TraceEntry {
+    pub fn from_received(received: &ReceivedFrame) -> Self {
+        let frame = &received.frame;
+        let (id, extended) = match frame.id() {
+            socketcan::Id::Standard(id) => (id.as_raw() as u32, false),
+            socketcan::Id::Extended(id) => (id.as_raw(), true),
+        };
+        Self {
+            timestamp: DateTime::<Local>::from(received.timestamp),
+            id,
+            extended,
+            remote: frame.is_remote_frame(),
+            dlc: frame.dlc(),
+            data: frame.data().to_vec(),
+            transmitted: false,
+        }
+    }
+
+    pub fn from_transmitted(sent: &ReceivedFrame) -> Self {
+        Self { transmitted: true, ..Self::from_received(sent) }
+    }
+}
+
+fn nmt_command(specifier: u8) -> Option<NmtCommand> {
+    match specifier {
+        0x01 => Some(NmtCommand::Start),
+        0x02 => Some(NmtCommand::Stop),
+        0x80 => Some(NmtCommand::EnterPreOperational),
+        0x81 => Some(NmtCommand::ResetNode),
+        0x82 => Some(NmtCommand::ResetCommunication),
+        _ => None,
+    }
+}
+
+/// Multiplexer (index:sub-index) of an SDO frame
+fn sdo_object(data: &[u8]) -> String {
+    match data.get(1..4) {
+        Some(mux) => format!("0x{:04X}:{:02X}", u16::from_le_bytes([mux[0], mux[1]]), mux[2]),
+        None => "?".to_string(),
+    }
+}
+
+fn sdo_abort(data: &[u8]) -> String {
+    let code = data.get(4..8).map_or(0, |code| u32::from_le_bytes([code[0], code[1], code[2], code[3]]));
+    format!("{}: {}", sdo_object(data), get_abort_code_description(code))
+}
+
+/// SDO request from a client (COB-ID 0x600 + node)
+fn sdo_request(node_id: u32, data: &[u8]) -> String {
+    let Some(&command) = data.first() else {
+        return format!("SDO request to node {} (empty)", node_id);
+    };
+    match command >> 5 {
+        0 => format!("SDO download segment to node {}", node_id),
+        1 => format!("SDO download of {} to node {}", sdo_object(data), node_id),
+        2 => format!("SDO upload of {} from node {}", sdo_object(data), node_id),
+        3 => format!("SDO upload segment request to node {}", node_id),
+        4 => format!("SDO abort to node {} {}", node_id, sdo_abort(data)),
+        5 | 6 => format!("SDO block transfer to node {}", node_id),
+        _ => format!("SDO request to node {} (unknown command 0x{:02X})", node_id, command),
+    }
+}
+
+/// SDO response of a server (COB-ID 0x580 + node)
+fn sdo_response(node_id: u32, data: &[u8]) -> String {
+    let Some(&command) = data.first() else {
+        return format!("SDO response from node {} (empty)", node_id);
+    };
+    match command >> 5 {
+        0 => format!("SDO upload segment from node {}", node_id),
+        1 => format!("SDO download segment confirmed by node {}", node_id),
+        2 => format!("SDO upload response {} from node {}", sdo_object(data), node_id),
+        3 => format!("SDO download response {} from node {}", sdo_object(data), node_id),
+        4 => format!("SDO abort from node {} {}", node_id, sdo_abort(data)),
+        5 | 6 => format!("SDO block transfer from node {}", node_id),
+        _ => format!("SDO response from node {} (unknown command 0x{:02X})", node_id, command),
+    }
+}
+
+/// What a frame means on a CANopen bus; empty for frames outside the predefined connection set
+///
+/// `tpdos` are the TPDOs discovered on the monitored node.
+pub fn interpret(entry: &TraceEntry, monitored: u8, tpdos: &[TpdoConfig]) -> String {
+    if entry.extended {
+        return String::new();
+    }
+    let id = entry.id;
+    let data = entry.data.as_slice();
+
+    if let Some(tpdo) = tpdos.iter().find(|tpdo| tpdo.cob_id as u32 == id) {
+        let request = if entry.remote { " remote request" } else { "" };
+        return format!("TPDO{}{} of node {}", tpdo.tpdo_number, request, monitored);
+    }
+
+    match id {
+        0x000 => match (data.first().copied().and_then(nmt_command), data.get(1)) {
+            (Some(command), Some(0)) => format!("NMT {} all nodes", command),
+            (Some(command), Some(node_id)) => format!("NMT {} node {}", command, node_id),
+            _ => "NMT (malformed)".to_string(),
+        },
+        _ if id == SYNC_COB_ID as u32 => match data.first() {
+            Some(counter) => format!("SYNC counter {}", counter),
+            None => "SYNC".to_string(),
+        },
+        0x081..=0x0FF => {
+            let node_id = id - EMCY_COB_ID_BASE as u32;
+            match parse_emcy_frame(data) {
+                Some(message) => format!("EMCY node {}: {}", node_id, message),
+                None => format!("EMCY node {} (malformed)", node_id),
+            }
+        }
+        0x100 => "TIME".to_string(),
+        0x181..=0x57F if id & 0x7F != 0 => {
+            let node_id = id & 0x7F;
+            // 0x180 TPDO1, 0x200 RPDO1, 0x280 TPDO2, ... 0x500 RPDO4
+            let slot = (id - 0x180) >> 7;
+            let kind = if slot & 1 == 0 { "TPDO" } else { "RPDO" };
+            let request = if entry.remote { " remote request" } else { "" };
+            format!("{}{}{} of node {}", kind, slot / 2 + 1, request, node_id)
+        }
+        0x581..=0x5FF => sdo_response(id - 0x580, data),
+        0x601..=0x67F => sdo_request(id - 0x600, data),
+        0x701..=0x77F => {
+            let node_id = id - HEARTBEAT_COB_ID_BASE as u32;
+            if entry.remote {
+                return format!("Node guarding request to node {}", node_id);
+            }
+            match NmtState::from_heartbeat(data) {
+                Some(NmtState::BootUp) => format!("Boot-up of node {}", node_id),
+                Some(state) => format!("Heartbeat node {}: {}", node_id, state),
+                None => format!("Heartbeat node {} (unknown state)", node_id),
+            }
+        }
+        _ if id == LSS_MASTER_COB_ID as u32 => format!("LSS request 0x{:02X}", data.first().copied().unwrap_or(0)),
+        _ if id == LSS_SLAVE_COB_ID as u32 => format!("LSS response 0x{:02X}", data.first().copied().unwrap_or(0)),
+        _ => String::new(),
+    }
+}
+
+/// Kinds of frames in the predefined connection set, for filtering and coloring
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameKind {
+    Nmt,
+    Sync,
+    Emcy,
+    Time,
+    Tpdo,
+    Rpdo,
+    Sdo,
+    Heartbeat,
+    Lss,
+    Other,
+}
+
+impl FrameKind {
+    pub const ALL: [FrameKind; 10] = [
+        Self::Nmt, Self::Sync, Self::Emcy, Self::Time, Self::Tpdo,
+        Self::Rpdo, Self::Sdo, Self::Heartbeat, Self::Lss, Self::Other,
+    ];
+
+    fn default_color(self) -> Color32 {
+        match self {
+            Self::Nmt => Color32::from_rgb(255, 165, 0),
+            Self::Sync | Self::Time => Color32::from_rgb(120, 160, 255),
+            Self::Emcy => Color32::from_rgb(255, 100, 100),
+            Self::Tpdo => Color32::from_rgb(0, 200, 0),
+            Self::Rpdo => Color32::from_rgb(0, 180, 180),
+            Self::Sdo => Color32::GRAY,
+            Self::Heartbeat => Color32::from_rgb(180, 140, 255),
+            Self::Lss => Color32::from_rgb(230, 200, 0),
+            Self::Other => Color32::LIGHT_GRAY,
+        }
+    }
+}
+
+impl fmt::Display for FrameKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Nmt => write!(f, "NMT"),
+            Self::Sync => write!(f, "SYNC"),
+            Self::Emcy => write!(f, "EMCY"),
+            Self::Time => write!(f, "TIME"),
+            Self::Tpdo => write!(f, "TPDO"),
+            Self::Rpdo => write!(f, "RPDO"),
+            Self::Sdo => write!(f, "SDO"),
+            Self::Heartbeat => write!(f, "Heartbeat"),
+            Self::Lss => write!(f, "LSS"),
+            Self::Other => write!(f, "Other"),
+        }
+    }
+}
+
+/// Kind of a frame and the node it belongs to (the addressed node for NMT)
+pub fn classify(entry: &TraceEntry, monitored: u8, tpdos: &[TpdoConfig]) -> (FrameKind, Option<u8>) {
+    if entry.extended {
+        return (FrameKind::Other, None);
+    }
+    if tpdos.iter().any(|tpdo| tpdo.cob_id as u32 == entry.id) {
+        return (FrameKind::Tpdo, Some(monitored));
+    }
+    let node_id = (entry.id & 0x7F) as u8;
+    match entry.id {
+        0x000 => (FrameKind::Nmt, entry.data.get(1).copied().filter(|&node_id| node_id != 0)),
+        _ if entry.id == SYNC_COB_ID as u32 => (FrameKind::Sync, None),
+        0x081..=0x0FF => (FrameKind::Emcy, Some(node_id)),
+        0x100 => (FrameKind::Time, None),
+        0x181..=0x57F if node_id != 0 => {
+            let kind = if (entry.id - 0x180) >> 7 & 1 == 0 { FrameKind::Tpdo } else { FrameKind::Rpdo };
+            (kind, Some(node_id))
+        }
+        0x581..=0x5FF | 0x601..=0x67F => (FrameKind::Sdo, Some(node_id)),
+        0x701..=0x77F => (FrameKind::Heartbeat, Some(node_id)),
+        _ if entry.id == LSS_MASTER_COB_ID as u32 || entry.id == LSS_SLAVE_COB_ID as u32 => (FrameKind::Lss, None),
+        _ => (FrameKind::Other, None),
+    }
+}
+
+fn parse_id(text: &str, hex: bool) -> Option<u32> {
+    let text = text.trim();
+    if hex {
+        let digits = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")).unwrap_or(text);
+        u32::from_str_radix(digits, 16).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+/// Parse a list of IDs and ranges like "0x181, 0x200-0x27F" (hex) or "1-4, 7" (decimal)
+pub fn parse_id_list(text: &str, hex: bool) -> Result<Vec<RangeInclusive<u32>>, String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let (low, high) = part.split_once('-').unwrap_or((part, part));
+            match (parse_id(low, hex), parse_id(high, hex)) {
+                (Some(low), Some(high)) if low <= high => Ok(low..=high),
+                _ => Err(format!("Invalid ID or range '{}'", part)),
+            }
+        })
+        .collect()
+}
+
+/// A list of IDs whose frames are the only ones shown, or are hidden
+struct IdFilter {
+    text: String,
+    exclude: bool,
+    hex: bool,
+    ranges: Result<Vec<RangeInclusive<u32>>, String>,
+}
+
+impl IdFilter {
+    fn new(hex: bool) -> Self {
+        Self { text: String::new(), exclude: false, hex, ranges: Ok(Vec::new()) }
+    }
+
+    /// An empty or invalid list lets everything through
+    fn allows(&self, id: Option<u32>) -> bool {
+        match &self.ranges {
+            Ok(ranges) if !ranges.is_empty() => {
+                let listed = id.is_some_and(|id| ranges.iter().any(|range| range.contains(&id)));
+                listed != self.exclude
+            }
+            _ => true,
+        }
+    }
+
+    fn show(&mut self, ui: &mut egui::Ui, label: &str, hint: &str) {
+        ui.horizontal(|ui| {
+            ui.label(label);
+            egui::ComboBox::from_id_salt(label)
+                .width(80.0)
+                .selected_text(if self.exclude { "Exclude" } else { "Include" })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.exclude, false, "Include");
+                    ui.selectable_value(&mut self.exclude, true, "Exclude");
+                });
+            if ui.add(egui::TextEdit::singleline(&mut self.text).hint_text(hint).desired_width(200.0)).changed() {
+                self.ranges = parse_id_list(&self.text, self.hex);
+            }
+            if let Err(err) = &self.ranges {
+                ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", err));
+            }
+        });
+    }
+}
+
+struct TraceRow {
+    entry: TraceEntry,
+    kind: FrameKind,
+    node_id: Option<u8>,
+    interpretation: String,
+}
+
+pub struct TracePanel {
+    pub open: bool,
+    paused: bool,
+    /// Frames received while paused, which aren't in the trace
+    skipped: usize,
+    rows: VecDeque<TraceRow>,
+    cob_id_filter: IdFilter,
+    node_filter: IdFilter,
+    hidden_kinds: HashSet<FrameKind>,
+    color_rows: bool,
+    colors: HashMap<FrameKind, Color32>,
+    error: Option<String>,
+}
+
+impl TracePanel {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            paused: false,
+            skipped: 0,
+            rows: VecDeque::new(),
+            cob_id_filter: IdFilter::new(true),
+            node_filter: IdFilter::new(false),
+            hidden_kinds: HashSet::new(),
+            color_rows: true,
+            colors: FrameKind::ALL.iter().map(|&kind| (kind, kind.default_color())).collect(),
+            error: None,
+        }
+    }
+
+    /// Append received frames unless the trace is paused
+    pub fn push(&mut self, entries: Vec<TraceEntry>, monitored: u8, tpdos: &[TpdoConfig]) {
+        if self.paused {
+            self.skipped += entries.len();
+            return;
+        }
+        for entry in entries {
+            let (kind, node_id) = classify(&entry, monitored, tpdos);
+            let interpretation = interpret(&entry, monitored, tpdos);
+            self.rows.push_back(TraceRow { entry, kind, node_id, interpretation });
+        }
+        let excess = self.rows.len().saturating_sub(MAX_TRACE_ROWS);
+        self.rows.drain(..excess);
+    }
+
+    fn shows(&self, row: &TraceRow) -> bool {
+        !self.hidden_kinds.contains(&row.kind)
+            && self.cob_id_filter.allows(Some(row.entry.id))
+            && self.node_filter.allows(row.node_id.map(u32::from))
+    }
+
+    fn show_filters(&mut self, ui: &mut egui::Ui) {
+        self.cob_id_filter.show(ui, "COB-ID:", "hex, e.g. 0x181, 0x200-0x27F");
+        self.node_filter.show(ui, "Node:", "e.g. 1-4, 7");
+        ui.horizontal_wrapped(|ui| {
+            for kind in FrameKind::ALL {
+                let mut shown = !self.hidden_kinds.contains(&kind);
+                if ui.checkbox(&mut shown, kind.to_string()).changed() {
+                    if shown {
+                        self.hidden_kinds.remove(&kind);
+                    } else {
+                        self.hidden_kinds.insert(kind);
+                    }
+                }
+                if self.color_rows {
+                    if let Some(color) = self.colors.get_mut(&kind) {
+                        ui.color_edit_button_srgba(color);
+                    }
+                }
+                ui.add_space(6.0);
+            }
+        });
+        ui.checkbox(&mut self.color_rows, "Color rows by kind");
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, connected: bool) {
+        let mut open = self.open;
+
+        egui::Window::new("CAN Trace")
+            .open(&mut open)
+            .default_size([760.0, 420.0])
+            .show(ctx, |ui| {
+                let visible: Vec<usize> = (0..self.rows.len()).filter(|&i| self.shows(&self.rows[i])).collect();
+                let mut export = None;
+
+                ui.horizontal(|ui| {
+                    let pause_label = if self.paused { "▶ Resume" } else { "⏸ Pause" };
+                    if ui.button(pause_label).clicked() {
+                        self.paused = !self.paused;
+                        self.skipped = 0;
+                    }
+                    if ui.button("🗑 Clear").clicked() {
+                        self.rows.clear();
+                    }
+                    ui.add_enabled_ui(!visible.is_empty(), |ui| {
+                        ui.menu_button("💾 Export", |ui| {
+                            for format in TraceFormat::ALL {
+                                if ui.button(format.label()).clicked() {
+                                    export = Some(format);
+                                    ui.close_menu();
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("Save the frames shown");
+                    });
+                    if visible.len() == self.rows.len() {
+                        ui.label(format!("{} frames", self.rows.len()));
+                    } else {
+                        ui.label(format!("{} of {} frames shown", visible.len(), self.rows.len()));
+                    }
+                    if self.paused && self.skipped > 0 {
+                        ui.colored_label(Color32::from_rgb(255, 165, 0), format!("{} frames not recorded while paused", self.skipped));
+                    }
+                    if !connected {
+                        ui.weak("Not connected");
+                    }
+                });
+                if let Some(error) = &self.error {
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), format!("⚠ {}", error));
+                }
+                egui::CollapsingHeader::new("Filter").show(ui, |ui| self.show_filters(ui));
+                ui.separator();
+
+                if let Some(format) = export {
+                    self.export(format, &visible);
+                }
+
+                let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                egui::ScrollArea::both()
+                    .auto_shrink([false, false])
+                    .stick_to_bottom(!self.paused)
+                    .show_rows(ui, row_height, visible.len(), |ui, range| {
+                        for &index in &visible[range] {
+                            let Some(row) = self.rows.get(index) else {
+                                continue;
+                            };
+                            let entry = &row.entry;
+                            let id = if entry.extended { format!("{:08X}", entry.id) } else { format!("{:03X}", entry.id) };
+                            let data = if entry.remote {
+                                "remote request".to_string()
+                            } else {
+                                entry.data.iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(" ")
+                            };
+                            let line = format!("{}  {:>8}  [{}]  {:<23}", entry.timestamp.format("%H:%M:%S%.6f"), id, entry.dlc, data);
+                            let color = self.colors.get(&row.kind).copied().filter(|_| self.color_rows);
+                            ui.horizontal(|ui| match color {
+                                Some(color) => {
+                                    ui.label(egui::RichText::new(line).monospace().color(color));
+                                    ui.colored_label(color, &row.interpretation);
+                                }
+                                None => {
+                                    ui.monospace(line);
+                                    ui.label(&row.interpretation);
+                                }
+                            });
+                        }
+                    });
+            });
+
+        self.open = open;
+    }
+
+    fn export(&mut self, format: TraceFormat, visible: &[usize]) {
+        let entries: Vec<&TraceEntry> = visible.iter().filter_map(|&index| self.rows.get(index)).map(|row| &row.entry).collect();
+        let file_name = format!("trace_{}.{}", Local::now().format("%Y-%m-%d_%H-%M-%S"), format.extension());
+        if let Some(path) = rfd::FileDialog::new().set_file_name(&file_name).save_file() {
+            match trace_export::save(&path, format, &entries) {
+                Ok(()) => {
+                    println!("✓ Saved {} frames to {:?}", entries.len(), path);
+                    self.error = None;
+                }
+                Err(e) => self.error = Some(format!("Failed to save trace: {}", e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: u32, data: &[u8]) -> TraceEntry {
+        TraceEntry {
+            timestamp: Local::now(),
+            id,
+            extended: false,
+            remote: false,
+            dlc: data.len(),
+            data: data.to_vec(),
+            transmitted: false,
+        }
+    }
+
+    #[test]
+    fn test_trace_interpretation() {
+        let tpdos = vec![TpdoConfig {
+            tpdo_number: 2,
+            cob_id: 0x284,
+            mapped_objects: Vec::new(),
+            transmission_type: None,
+            rtr_allowed: false,
+            event_timer_ms: None,
+        }];
+        let interpret = |entry: &TraceEntry| interpret(entry, 4, &tpdos);
+
+        assert_eq!(interpret(&entry(0x604, &[0x40, 0x00, 0x20, 0x01, 0, 0, 0, 0])), "SDO upload of 0x2000:01 from node 4");
+        assert_eq!(interpret(&entry(0x584, &[0x43, 0x00, 0x20, 0x01, 1, 2, 3, 4])), "SDO upload response 0x2000:01 from node 4");
+        assert_eq!(
+            interpret(&entry(0x584, &[0x80, 0x00, 0x20, 0x01, 0x00, 0x00, 0x02, 0x06])),
+            "SDO abort from node 4 0x2000:01: Object does not exist in the object dictionary"
+        );
+        assert_eq!(interpret(&entry(0x284, &[1, 2])), "TPDO2 of node 4");
+        assert_eq!(interpret(&entry(0x183, &[1])), "TPDO1 of node 3");
+        assert_eq!(interpret(&entry(0x505, &[1])), "RPDO4 of node 5");
+        assert_eq!(interpret(&entry(0x704, &[0x05])), "Heartbeat node 4: Operational");
+        assert_eq!(interpret(&entry(0x704, &[0x00])), "Boot-up of node 4");
+        assert_eq!(interpret(&entry(0x000, &[0x01, 0])), "NMT Start all nodes");
+        assert_eq!(interpret(&entry(0x080, &[])), "SYNC");
+        assert_eq!(interpret(&TraceEntry { extended: true, ..entry(0x704, &[]) }), "");
+
+        let mut panel = TracePanel::new();
+        panel.push(vec![entry(0x080, &[]); MAX_TRACE_ROWS + 5], 4, &tpdos);
+        assert_eq!(panel.rows.len(), MAX_TRACE_ROWS);
+        panel.paused = true;
+        panel.push(vec![entry(0x080, &[]); 3], 4, &tpdos);
+        assert_eq!(panel.skipped, 3);
+    }
+
+    #[test]
+    fn test_trace_filters() {
+        assert_eq!(parse_id_list("0x181, 200-27f", true), Ok(vec![0x181..=0x181, 0x200..=0x27F]));
+        assert_eq!(parse_id_list(" 1-4,7 ", false), Ok(vec![1..=4, 7..=7]));
+        assert!(parse_id_list("4-1", false).is_err());
+        assert!(parse_id_list("0x18g", true).is_err());
+        assert_eq!(parse_id_list("", true), Ok(Vec::new()));
+
+        let tpdos = Vec::new();
+        assert_eq!(classify(&entry(0x284, &[1]), 4, &tpdos), (FrameKind::Tpdo, Some(4)));
+        assert_eq!(classify(&entry(0x305, &[1]), 4, &tpdos), (FrameKind::Rpdo, Some(5)));
+        assert_eq!(classify(&entry(0x603, &[0x40]), 4, &tpdos), (FrameKind::Sdo, Some(3)));
+        assert_eq!(classify(&entry(0x000, &[0x01, 0]), 4, &tpdos), (FrameKind::Nmt, None));
+        assert_eq!(classify(&entry(0x7E4, &[0x5E]), 4, &tpdos), (FrameKind::Lss, None));
+
+        let mut panel = TracePanel::new();
+        panel.push(vec![entry(0x604, &[0x40]), entry(0x184, &[1]), entry(0x185, &[1]), entry(0x080, &[])], 4, &tpdos);
+        let shown = |panel: &TracePanel| panel.rows.iter().filter(|row| panel.shows(row)).map(|row| row.entry.id).collect::<Vec<_>>();
+        panel.hidden_kinds.insert(FrameKind::Sdo);
+        assert_eq!(shown(&panel), vec![0x184, 0x185, 0x080]);
+        panel.node_filter.ranges = parse_id_list("4", false);
+        assert_eq!(shown(&panel), vec![0x184]);
+        panel.node_filter.exclude = true;
+        assert_eq!(shown(&panel), vec![0x185, 0x080]);
+        panel.cob_id_filter.ranges = parse_id_list("0x180-0x1FF", true);
+        assert_eq!(shown(&panel), vec![0x185]);
+    }
+}
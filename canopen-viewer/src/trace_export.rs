@@ -0,0 +1,184 @@
+// trace_export.rs - Write the raw CAN trace in common interchange formats
+//
+// candump log files can be replayed with can-utils (and by the viewer itself), Vector
+// ASC is read by CANalyzer/CANoe and most analysis scripts, and PEAK TRC (version 1.1)
+// by PCAN-View and PCAN-Explorer. All frames are written as received on the first
+// channel; ASC and TRC times are relative to the first exported frame.
+use chrono::{DateTime, Local, NaiveDate};
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
+
+use crate::trace::TraceEntry;
+
+/// Interface name written into candump logs
+const CANDUMP_INTERFACE: &str = "can0";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    Candump,
+    VectorAsc,
+    PeakTrc,
+}
+
+impl TraceFormat {
+    pub const ALL: [TraceFormat; 3] = [Self::Candump, Self::VectorAsc, Self::PeakTrc];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Candump => "candump log (.log)",
+            Self::VectorAsc => "Vector ASC (.asc)",
+            Self::PeakTrc => "PEAK TRC (.trc)",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Candump => "log",
+            Self::VectorAsc => "asc",
+            Self::PeakTrc => "trc",
+        }
+    }
+}
+
+fn hex_bytes(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(" ")
+}
+
+/// Seconds from the first frame
+fn offset(entry: &TraceEntry, start: DateTime<Local>) -> f64 {
+    (entry.timestamp - start).num_microseconds().unwrap_or(0) as f64 / 1e6
+}
+
+fn candump(entries: &[&TraceEntry]) -> String {
+    let mut text = String::new();
+    for entry in entries {
+        let id = if entry.extended { format!("{:08X}", entry.id) } else { format!("{:03X}", entry.id) };
+        let payload = if entry.remote {
+            "R".to_string()
+        } else {
+            entry.data.iter().map(|byte| format!("{:02X}", byte)).collect()
+        };
+        let seconds = entry.timestamp.timestamp_micros() as f64 / 1e6;
+        let _ = writeln!(text, "({:.6}) {} {}#{}", seconds, CANDUMP_INTERFACE, id, payload);
+    }
+    text
+}
+
+fn vector_asc(entries: &[&TraceEntry]) -> String {
+    let start = entries.first().map_or_else(Local::now, |entry| entry.timestamp);
+    let date = start.format("%a %b %d %I:%M:%S%.3f %P %Y");
+    let mut text = format!("date {}\nbase hex  timestamps absolute\nno internal events logged\n", date);
+    let _ = writeln!(text, "Begin Triggerblock {}", date);
+    for entry in entries {
+        let id = if entry.extended { format!("{:X}x", entry.id) } else { format!("{:X}", entry.id) };
+        let frame = if entry.remote {
+            format!("r {:X}", entry.dlc)
+        } else {
+            format!("d {:X} {}", entry.dlc, hex_bytes(&entry.data))
+        };
+        let _ = writeln!(text, "{:>11.6} 1  {:<15} Rx   {}", offset(entry, start), id, frame.trim_end());
+    }
+    text.push_str("End TriggerBlock\n");
+    text
+}
+
+fn peak_trc(entries: &[&TraceEntry]) -> String {
+    let start = entries.first().map_or_else(Local::now, |entry| entry.timestamp);
+    // Days since 1899-12-30, as in OLE automation dates
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30).unwrap_or_default().and_hms_opt(0, 0, 0).unwrap_or_default();
+    let days = (start.naive_local() - epoch).num_milliseconds() as f64 / 86_400_000.0;
+
+    let mut text = format!(";$FILEVERSION=1.1\n;$STARTTIME={:.10}\n;\n", days);
+    let _ = writeln!(text, ";   Start time: {}", start.format("%d.%m.%Y %H:%M:%S%.3f"));
+    text.push_str(";   Generated by CANopen Data Viewer\n");
+    text.push_str(";\n;   Message Number\n;   |         Time Offset (ms)\n;   |         |        Type\n");
+    text.push_str(";   |         |        |        ID (hex)\n;   |         |        |        |     Data Length\n");
+    text.push_str(";   |         |        |        |     |   Data Bytes (hex) ...\n;   |         |        |        |     |   |\n");
+    text.push_str(";---+--   ----+----  --+--  ----+---  +  -+ -- -- -- -- -- -- --\n");
+    for (number, entry) in entries.iter().enumerate() {
+        let id = if entry.extended { format!("{:08X}", entry.id) } else { format!("{:04X}", entry.id) };
+        let data = if entry.remote { "RTR".to_string() } else { hex_bytes(&entry.data) };
+        let line = format!(
+            "{:>6})  {:>11.1}  Rx  {:>8}  {}  {}",
+            number + 1, offset(entry, start) * 1000.0, id, entry.dlc, data
+        );
+        let _ = writeln!(text, "{}", line.trim_end());
+    }
+    text
+}
+
+/// Trace text in `format`
+pub fn format_trace(format: TraceFormat, entries: &[&TraceEntry]) -> String {
+    match format {
+        TraceFormat::Candump => candump(entries),
+        TraceFormat::VectorAsc => vector_asc(entries),
+        TraceFormat::PeakTrc => peak_trc(entries),
+    }
+}
+
+pub fn save(path: &Path, format: TraceFormat, entries: &[&TraceEntry]) -> io::Result<()> {
+    std::fs::write(path, format_trace(format, entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// A standard data frame, an extended one 1.5 ms later and a remote frame at 10 ms
+    fn entries() -> Vec<TraceEntry> {
+        let start = Local.timestamp_micros(1_700_000_000_250_000).unwrap();
+        let frame = |micros: i64, id: u32, extended: bool, remote: bool, data: &[u8]| TraceEntry {
+            timestamp: start + chrono::Duration::microseconds(micros),
+            id,
+            extended,
+            remote,
+            dlc: if remote { 0 } else { data.len() },
+            data: data.to_vec(),
+            transmitted: false,
+        };
+        vec![
+            frame(0, 0x181, false, false, &[0x11, 0x22]),
+            frame(1500, 0x1234_5678, true, false, &[0xAA]),
+            frame(10_000, 0x701, false, true, &[]),
+        ]
+    }
+
+    fn export(format: TraceFormat) -> String {
+        let entries = entries();
+        let entries: Vec<&TraceEntry> = entries.iter().collect();
+        format_trace(format, &entries)
+    }
+
+    #[test]
+    fn test_candump_export() {
+        assert_eq!(
+            export(TraceFormat::Candump),
+            "(1700000000.250000) can0 181#1122\n(1700000000.251500) can0 12345678#AA\n(1700000000.260000) can0 701#R\n"
+        );
+    }
+
+    #[test]
+    fn test_vector_asc_export() {
+        let asc = export(TraceFormat::VectorAsc);
+        let lines: Vec<&str> = asc.lines().collect();
+        assert!(lines[0].starts_with("date "));
+        assert_eq!(lines[4], "   0.000000 1  181             Rx   d 2 11 22");
+        assert_eq!(lines[5], "   0.001500 1  12345678x       Rx   d 1 AA");
+        assert_eq!(lines[6], "   0.010000 1  701             Rx   r 0");
+        assert_eq!(lines[7], "End TriggerBlock");
+    }
+
+    #[test]
+    fn test_peak_trc_export() {
+        let trc = export(TraceFormat::PeakTrc);
+        assert!(trc.starts_with(";$FILEVERSION=1.1\n;$STARTTIME="));
+        let frames: Vec<&str> = trc.lines().filter(|line| !line.starts_with(';')).collect();
+        assert_eq!(frames, vec![
+            "     1)          0.0  Rx      0181  2  11 22",
+            "     2)          1.5  Rx  12345678  1  AA",
+            "     3)         10.0  Rx      0701  0  RTR",
+        ]);
+    }
+}
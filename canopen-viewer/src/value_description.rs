@@ -0,0 +1,114 @@
+// value_description.rs - Names for the values of enumerated objects
+//
+// Descriptions come from `[<index>sub<n>ValueDescription]` sections of the EDS (lines
+// like `3=Profile Velocity`) and from the `[value_descriptions]` table of the config
+// file, which is keyed the same way ("6060sub0") and takes precedence.
+use std::collections::{BTreeMap, HashMap};
+
+use crate::communication::{SdoAddress, SdoObject};
+
+/// Value names per object, from the EDS and the user's config
+pub struct ValueDescriptions {
+    names: HashMap<SdoAddress, BTreeMap<i64, String>>,
+}
+
+impl ValueDescriptions {
+    pub fn new() -> Self {
+        Self { names: HashMap::new() }
+    }
+
+    /// Collect the descriptions of the object dictionary and overlay the config table
+    pub fn load(objects: &BTreeMap<u16, SdoObject>, config: &BTreeMap<String, BTreeMap<String, String>>) -> Self {
+        let mut names: HashMap<SdoAddress, BTreeMap<i64, String>> = HashMap::new();
+
+        for (index, object) in objects {
+            for (sub_index, sub_object) in &object.sub_objects {
+                if !sub_object.value_descriptions.is_empty() {
+                    let address = SdoAddress { index: *index, sub_index: *sub_index };
+                    names.insert(address, sub_object.value_descriptions.clone());
+                }
+            }
+        }
+
+        for (key, values) in config {
+            let Some(address) = SdoAddress::from_eds_key(key) else {
+                eprintln!("Ignoring value descriptions for '{}': expected e.g. \"6060sub0\"", key);
+                continue;
+            };
+            let object_names = names.entry(address).or_default();
+            for (value, name) in values {
+                match parse_value(value) {
+                    Some(value) => {
+                        object_names.insert(value, name.clone());
+                    }
+                    None => eprintln!("Ignoring value description '{}' of {}: not an integer", value, key),
+                }
+            }
+        }
+
+        Self { names }
+    }
+
+    /// Name of a value given as a decimal string, if the object has one for it
+    pub fn describe(&self, address: &SdoAddress, value: &str) -> Option<&str> {
+        let value = value.parse::<i64>().ok()?;
+        self.names.get(address)?.get(&value).map(String::as_str)
+    }
+
+    /// "Profile Velocity (3)" for described values, `shown` (the formatted number) otherwise
+    pub fn label(&self, address: &SdoAddress, value: &str, shown: String) -> String {
+        match self.describe(address, value) {
+            Some(name) => format!("{} ({})", name, shown),
+            None => shown,
+        }
+    }
+}
+
+/// Parse the lines of an EDS `ValueDescription` section
+pub fn parse_eds_section(entries: &HashMap<String, Option<String>>) -> BTreeMap<i64, String> {
+    entries.iter()
+        .filter_map(|(value, name)| Some((parse_value(value)?, name.as_ref()?.trim().to_string())))
+        .collect()
+}
+
+/// Decimal or 0x hex
+fn parse_value(text: &str) -> Option<i64> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => i64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::communication::SdoSubObject;
+
+    #[test]
+    fn test_config_overrides_eds_descriptions() {
+        let mut sub_objects = BTreeMap::new();
+        sub_objects.insert(0, SdoSubObject {
+            name: "Modes of operation".to_string(),
+            data_type: "0x0002".to_string(),
+            access_type: "rw".to_string(),
+            low_limit: None,
+            high_limit: None,
+            default_value: None,
+            value_descriptions: BTreeMap::from([(1, "Profile Position".to_string()), (3, "Velocity".to_string())]),
+            scaling: None,
+        });
+        let objects = BTreeMap::from([(0x6060, SdoObject { name: "Modes of operation".to_string(), sub_objects })]);
+        let config = BTreeMap::from([(
+            "6060sub0".to_string(),
+            BTreeMap::from([("3".to_string(), "Profile Velocity".to_string()), ("0xFF".to_string(), "Vendor".to_string())]),
+        )]);
+
+        let descriptions = ValueDescriptions::load(&objects, &config);
+        let address = SdoAddress { index: 0x6060, sub_index: 0 };
+        assert_eq!(descriptions.label(&address, "3", "3".to_string()), "Profile Velocity (3)");
+        assert_eq!(descriptions.describe(&address, "1"), Some("Profile Position"));
+        assert_eq!(descriptions.describe(&address, "255"), Some("Vendor"));
+        assert_eq!(descriptions.label(&address, "7", "0x07".to_string()), "0x07");
+    }
+}
@@ -0,0 +1,212 @@
+// watch.rs - Table of all subscribed SDO objects and TPDO fields
+//
+// A compact alternative to the plots: one row per signal with its latest value, how
+// old that value is and how often it arrives. Clicking a column header sorts by it.
+use chrono::{DateTime, Local};
+use eframe::egui;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+use crate::overlay::PlotSeries;
+
+/// Time span the receive rate is averaged over
+const RATE_WINDOW_SECONDS: f64 = 5.0;
+
+/// Receive rate of a signal from its recent arrival times
+#[derive(Debug, Clone, Default)]
+pub struct RateMeter {
+    arrivals: VecDeque<DateTime<Local>>,
+}
+
+impl RateMeter {
+    pub fn record(&mut self, at: DateTime<Local>) {
+        self.arrivals.push_back(at);
+        while self.arrivals.front().is_some_and(|first| seconds_between(*first, at) > RATE_WINDOW_SECONDS) {
+            self.arrivals.pop_front();
+        }
+    }
+
+    /// Samples per second; drops to 0 once nothing arrived within the window
+    pub fn rate(&self, now: DateTime<Local>) -> f64 {
+        let recent: Vec<&DateTime<Local>> = self.arrivals.iter()
+            .filter(|at| seconds_between(**at, now) <= RATE_WINDOW_SECONDS)
+            .collect();
+        match (recent.first(), recent.last()) {
+            (Some(first), Some(last)) if recent.len() > 1 => {
+                let span = seconds_between(**first, **last);
+                if span > 0.0 { (recent.len() - 1) as f64 / span } else { 0.0 }
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+fn seconds_between(from: DateTime<Local>, to: DateTime<Local>) -> f64 {
+    (to - from).num_milliseconds() as f64 / 1000.0
+}
+
+/// One signal in the watch table
+#[derive(Debug, Clone)]
+pub struct WatchRow {
+    pub name: String,
+    /// "2000:01" for SDO objects, "TPDO1" for TPDO fields, "Derived" for derived channels
+    pub address: String,
+    /// Dragged onto the plots from the name
+    pub series: PlotSeries,
+    pub value: Option<String>,
+    pub unit: String,
+    /// Seconds since the last value
+    pub age: Option<f64>,
+    /// Values per second
+    pub rate: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchColumn {
+    Name,
+    Address,
+    Value,
+    Unit,
+    Age,
+    Rate,
+}
+
+impl WatchColumn {
+    const ALL: [WatchColumn; 6] = [Self::Name, Self::Address, Self::Value, Self::Unit, Self::Age, Self::Rate];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Name => "Name",
+            Self::Address => "Address",
+            Self::Value => "Value",
+            Self::Unit => "Unit",
+            Self::Age => "Age",
+            Self::Rate => "Rate",
+        }
+    }
+}
+
+/// Sort order of the table
+pub struct WatchTable {
+    sort_by: WatchColumn,
+    descending: bool,
+}
+
+impl WatchTable {
+    pub fn new() -> Self {
+        Self { sort_by: WatchColumn::Address, descending: false }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, mut rows: Vec<WatchRow>) {
+        if rows.is_empty() {
+            ui.label("No active subscriptions. Select an SDO to start reading or enable TPDO plotting.");
+            return;
+        }
+        self.sort(&mut rows);
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("watch_table")
+                .num_columns(WatchColumn::ALL.len())
+                .spacing([20.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    for column in WatchColumn::ALL {
+                        let arrow = match (self.sort_by == column, self.descending) {
+                            (true, false) => " ⏶",
+                            (true, true) => " ⏷",
+                            (false, _) => "",
+                        };
+                        if ui.selectable_label(self.sort_by == column, format!("{}{}", column.label(), arrow)).clicked() {
+                            if self.sort_by == column {
+                                self.descending = !self.descending;
+                            } else {
+                                self.sort_by = column;
+                                self.descending = false;
+                            }
+                        }
+                    }
+                    ui.end_row();
+
+                    for row in &rows {
+                        ui.add(egui::Label::new(&row.name).sense(egui::Sense::drag()))
+                            .on_hover_text("Drag onto the plots")
+                            .dnd_set_drag_payload(row.series.clone());
+                        ui.monospace(&row.address);
+                        ui.monospace(row.value.as_deref().unwrap_or("—"));
+                        ui.label(&row.unit);
+                        ui.label(row.age.map_or_else(|| "—".to_string(), |age| format!("{:.1} s", age)));
+                        ui.label(format!("{:.1}/s", row.rate));
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+
+    fn sort(&self, rows: &mut [WatchRow]) {
+        rows.sort_by(|a, b| {
+            let ordering = match self.sort_by {
+                WatchColumn::Name => a.name.cmp(&b.name),
+                WatchColumn::Address => a.address.cmp(&b.address),
+                WatchColumn::Value => compare_values(a.value.as_deref(), b.value.as_deref()),
+                WatchColumn::Unit => a.unit.cmp(&b.unit),
+                WatchColumn::Age => a.age.unwrap_or(f64::INFINITY).total_cmp(&b.age.unwrap_or(f64::INFINITY)),
+                WatchColumn::Rate => a.rate.total_cmp(&b.rate),
+            };
+            if self.descending { ordering.reverse() } else { ordering }
+        });
+    }
+}
+
+/// Numbers compare numerically and before text; missing values go last
+fn compare_values(a: Option<&str>, b: Option<&str>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => match (a.parse::<f64>(), b.parse::<f64>()) {
+            (Ok(a), Ok(b)) => a.total_cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        },
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_rate_meter() {
+        let start = Local::now();
+        let mut meter = RateMeter::default();
+        for i in 0..11 {
+            meter.record(start + Duration::milliseconds(i * 100));
+        }
+        let last = start + Duration::milliseconds(1000);
+        assert!((meter.rate(last) - 10.0).abs() < 1e-9);
+
+        // Nothing recent: the signal stopped
+        assert_eq!(meter.rate(last + Duration::seconds(10)), 0.0);
+    }
+
+    #[test]
+    fn test_sort_by_value() {
+        let row = |name: &str, value: Option<&str>| WatchRow {
+            name: name.to_string(),
+            address: String::new(),
+            series: PlotSeries::Sdo(crate::communication::SdoAddress { index: 0x2000, sub_index: 0 }),
+            value: value.map(str::to_string),
+            unit: String::new(),
+            age: None,
+            rate: 0.0,
+        };
+        let mut rows = vec![row("a", Some("10")), row("b", None), row("c", Some("9.5")), row("d", Some("Idle (0)"))];
+        let table = WatchTable { sort_by: WatchColumn::Value, descending: false };
+        table.sort(&mut rows);
+
+        let names: Vec<&str> = rows.iter().map(|row| row.name.as_str()).collect();
+        assert_eq!(names, vec!["c", "a", "d", "b"]);
+    }
+}
@@ -0,0 +1,172 @@
+// xy_plot.rs - One subscription plotted against another
+//
+// Torque over speed or pressure over flow shows an operating curve that two time plots
+// hide. The channels are sampled at different moments, so both are interpolated onto a
+// common time grid over the span they overlap, as fine as the denser of the two. The trace
+// fades with age so the current operating point stands out from its history.
+use eframe::egui::{self, Color32};
+use egui_plot::{Line, Plot, PlotPoints, Points};
+use serde::{Deserialize, Serialize};
+
+use crate::overlay::PlotSeries;
+
+/// Upper bound of grid points, so long recordings stay cheap to draw
+const MAX_GRID_POINTS: usize = 5000;
+/// Age steps of the fading trace
+const FADE_STEPS: usize = 8;
+const TRACE_COLOR: Color32 = Color32::from_rgb(100, 149, 237);
+
+fn default_fade_seconds() -> f64 {
+    10.0
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XyPlotSettings {
+    pub x: PlotSeries,
+    pub y: PlotSeries,
+    /// Age in seconds after which the trace has faded out
+    #[serde(default = "default_fade_seconds")]
+    pub fade_seconds: f64,
+}
+
+impl XyPlotSettings {
+    pub fn new(x: PlotSeries, y: PlotSeries) -> Self {
+        Self { x, y, fade_seconds: default_fade_seconds() }
+    }
+
+    pub fn uses(&self, series: &PlotSeries) -> bool {
+        self.x == *series || self.y == *series
+    }
+}
+
+/// Value of a series at `time` by linear interpolation; None outside the recorded span
+pub fn interpolate(points: &[[f64; 2]], time: f64) -> Option<f64> {
+    let after = points.partition_point(|point| point[0] < time);
+    let next = points.get(after)?;
+    if next[0] == time {
+        return Some(next[1]);
+    }
+    let previous = points.get(after.checked_sub(1)?)?;
+    let fraction = (time - previous[0]) / (next[0] - previous[0]);
+    Some(previous[1] + fraction * (next[1] - previous[1]))
+}
+
+fn mean_interval(points: &[[f64; 2]]) -> Option<f64> {
+    let (first, last) = (points.first()?, points.last()?);
+    (points.len() > 1).then(|| (last[0] - first[0]) / (points.len() - 1) as f64)
+}
+
+/// `[time, x, y]` samples of two series on a common time axis
+pub fn pair(x: &[[f64; 2]], y: &[[f64; 2]]) -> Vec<[f64; 3]> {
+    let (Some(x_step), Some(y_step)) = (mean_interval(x), mean_interval(y)) else {
+        return Vec::new();
+    };
+    let start = x[0][0].max(y[0][0]);
+    let end = x[x.len() - 1][0].min(y[y.len() - 1][0]);
+    if end < start {
+        return Vec::new();
+    }
+    let step = x_step.min(y_step).max((end - start) / MAX_GRID_POINTS as f64);
+    if step <= 0.0 {
+        return Vec::new();
+    }
+
+    let steps = ((end - start) / step).floor() as usize;
+    (0..=steps)
+        .filter_map(|i| {
+            let time = start + i as f64 * step;
+            Some([time, interpolate(x, time)?, interpolate(y, time)?])
+        })
+        .collect()
+}
+
+pub enum XyPlotAction {
+    /// Fading or axes changed
+    Changed,
+    Remove,
+}
+
+/// `x` and `y` are the names, axis labels and points of the two series on a shared time axis
+pub fn show(
+    ui: &mut egui::Ui,
+    id: usize,
+    settings: &mut XyPlotSettings,
+    x: (&str, &str, &[[f64; 2]]),
+    y: (&str, &str, &[[f64; 2]]),
+) -> Option<XyPlotAction> {
+    let mut action = None;
+    let samples = pair(x.2, y.2);
+
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.label(format!("XY - {} over {}", y.0, x.0));
+        ui.separator();
+
+        Plot::new(format!("xy_plot_{}", id))
+            .view_aspect(1.5)
+            .height(350.0)
+            .width(ui.available_width())
+            .allow_scroll(false)
+            .x_axis_label(format!("{} - {}", x.0, x.1))
+            .y_axis_label(format!("{} - {}", y.0, y.1))
+            .show(ui, |plot_ui| {
+                let Some(newest) = samples.last().map(|sample| sample[0]) else {
+                    return;
+                };
+                // Older parts of the trace are drawn fainter, the oldest not at all
+                let step_seconds = settings.fade_seconds / FADE_STEPS as f64;
+                for age_step in (0..FADE_STEPS).rev() {
+                    let newer = newest - age_step as f64 * step_seconds;
+                    let older = newer - step_seconds;
+                    let segment: Vec<[f64; 2]> = samples.iter()
+                        .filter(|sample| sample[0] >= older && sample[0] <= newer)
+                        .map(|sample| [sample[1], sample[2]])
+                        .collect();
+                    let alpha = 1.0 - age_step as f32 / FADE_STEPS as f32;
+                    plot_ui.line(Line::new(PlotPoints::from(segment)).color(TRACE_COLOR.gamma_multiply(alpha)));
+                }
+                let last = samples[samples.len() - 1];
+                plot_ui.points(Points::new(vec![[last[1], last[2]]]).radius(5.0).color(Color32::from_rgb(255, 165, 0)));
+            });
+
+        ui.horizontal(|ui| {
+            ui.label("Fade after:");
+            if ui.add(egui::DragValue::new(&mut settings.fade_seconds).range(1.0..=600.0).suffix(" s")).changed() {
+                action = Some(XyPlotAction::Changed);
+            }
+            if ui.button("⇄ Swap axes").clicked() {
+                std::mem::swap(&mut settings.x, &mut settings.y);
+                action = Some(XyPlotAction::Changed);
+            }
+            if ui.button("🗑 Remove").clicked() {
+                action = Some(XyPlotAction::Remove);
+            }
+        });
+    });
+
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xy_pairing_on_common_grid() {
+        let x = [[0.0, 0.0], [1.0, 10.0], [2.0, 20.0]];
+        assert_eq!(interpolate(&x, 0.5), Some(5.0));
+        assert_eq!(interpolate(&x, 2.0), Some(20.0));
+        assert_eq!(interpolate(&x, 2.5), None);
+        assert_eq!(interpolate(&x, -0.1), None);
+
+        // Only the overlapping span, as fine as the denser series
+        let y = [[0.5, 1.0], [1.0, 2.0], [1.5, 3.0], [2.0, 4.0], [2.5, 5.0]];
+        let samples = pair(&x, &y);
+        assert_eq!(samples.first(), Some(&[0.5, 5.0, 1.0]));
+        assert_eq!(samples.last(), Some(&[2.0, 20.0, 4.0]));
+        assert_eq!(samples.len(), 4);
+        assert_eq!(samples[1], [1.0, 10.0, 2.0]);
+
+        assert!(pair(&x, &[[3.0, 1.0], [4.0, 2.0]]).is_empty());
+        assert!(pair(&x, &[[1.0, 1.0]]).is_empty());
+    }
+}
@@ -0,0 +1,291 @@
+// y_axis.rs - Value axis range and scale of a plot
+//
+// Autoscaling follows every spike, which hides small deviations around a set point, and
+// signals spanning several decades flatten to a line near zero. Each plot can instead keep
+// a fixed value range, autoscale with a margin of its choice, or draw the logarithm of its
+// values; ticks and hover labels still show the values themselves. A second axis at the
+// right can label the same curve in another unit, °F next to °C or rpm next to rad/s, so
+// nobody converts in their head. The settings are kept with the dashboard layout.
+use eframe::egui;
+use egui_plot::{AxisHints, HPlacement, Plot};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Margin egui_plot uses by default, as a fraction of the span
+const DEFAULT_MARGIN: f64 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum YRange {
+    /// Fit the data, leaving `margin` of its span free above and below
+    Auto { margin: f64 },
+    Fixed { min: f64, max: f64 },
+}
+
+impl Default for YRange {
+    fn default() -> Self {
+        YRange::Auto { margin: DEFAULT_MARGIN }
+    }
+}
+
+/// Unit of the second value axis, converted from the unit of the plot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitConversion {
+    CelsiusToFahrenheit,
+    FahrenheitToCelsius,
+    CelsiusToKelvin,
+    RadPerSecondToRpm,
+    RpmToRadPerSecond,
+    MetresPerSecondToKmh,
+    BarToPsi,
+    PsiToBar,
+}
+
+impl UnitConversion {
+    pub const ALL: [UnitConversion; 8] = [
+        UnitConversion::CelsiusToFahrenheit,
+        UnitConversion::FahrenheitToCelsius,
+        UnitConversion::CelsiusToKelvin,
+        UnitConversion::RadPerSecondToRpm,
+        UnitConversion::RpmToRadPerSecond,
+        UnitConversion::MetresPerSecondToKmh,
+        UnitConversion::BarToPsi,
+        UnitConversion::PsiToBar,
+    ];
+
+    /// Unit converted from and to, factor and offset
+    fn parameters(self) -> (&'static str, &'static str, f64, f64) {
+        match self {
+            UnitConversion::CelsiusToFahrenheit => ("°C", "°F", 1.8, 32.0),
+            UnitConversion::FahrenheitToCelsius => ("°F", "°C", 5.0 / 9.0, -160.0 / 9.0),
+            UnitConversion::CelsiusToKelvin => ("°C", "K", 1.0, 273.15),
+            UnitConversion::RadPerSecondToRpm => ("rad/s", "rpm", 30.0 / PI, 0.0),
+            UnitConversion::RpmToRadPerSecond => ("rpm", "rad/s", PI / 30.0, 0.0),
+            UnitConversion::MetresPerSecondToKmh => ("m/s", "km/h", 3.6, 0.0),
+            UnitConversion::BarToPsi => ("bar", "psi", 14.503_773_8, 0.0),
+            UnitConversion::PsiToBar => ("psi", "bar", 1.0 / 14.503_773_8, 0.0),
+        }
+    }
+
+    pub fn unit(self) -> &'static str {
+        self.parameters().1
+    }
+
+    pub fn apply(self, value: f64) -> f64 {
+        let (_, _, factor, offset) = self.parameters();
+        value * factor + offset
+    }
+
+    pub fn label(self) -> String {
+        let (from, to, _, _) = self.parameters();
+        format!("{} → {}", from, to)
+    }
+
+    /// Whether the conversion takes values in `unit`
+    pub fn converts(self, unit: &str) -> bool {
+        self.parameters().0.eq_ignore_ascii_case(unit.trim())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct YAxisSettings {
+    pub range: YRange,
+    /// Draw log10 of the values; values of zero or below are left out
+    pub log: bool,
+    /// Second axis at the right with the values in another unit
+    pub secondary: Option<UnitConversion>,
+}
+
+impl YAxisSettings {
+    /// Where a value is drawn on the axis; None for values a log axis cannot show
+    pub fn position(&self, value: f64) -> Option<f64> {
+        if !self.log {
+            Some(value)
+        } else if value > 0.0 {
+            Some(value.log10())
+        } else {
+            None
+        }
+    }
+
+    /// The value drawn at a position on the axis
+    pub fn value(&self, position: f64) -> f64 {
+        if self.log { 10f64.powf(position) } else { position }
+    }
+
+    /// Points moved onto the axis
+    pub fn transform(&self, points: Vec<[f64; 2]>) -> Vec<[f64; 2]> {
+        if !self.log {
+            return points;
+        }
+        points.into_iter()
+            .filter_map(|[time, value]| Some([time, self.position(value)?]))
+            .collect()
+    }
+
+    /// Hover text of a position, with the converted value when there is a second axis
+    pub fn hover_text(&self, position: f64) -> String {
+        let value = self.value(position);
+        match self.secondary {
+            Some(conversion) => format!("{} ({} {})", format_value(value), format_value(conversion.apply(value)), conversion.unit()),
+            None => format_value(value),
+        }
+    }
+
+    /// Range, margin and tick labels of the value axes
+    pub fn apply<'a>(&self, plot: Plot<'a>) -> Plot<'a> {
+        let plot = match self.range {
+            YRange::Auto { margin } => plot.set_margin_fraction(egui::vec2(DEFAULT_MARGIN as f32, margin as f32)),
+            // The time axis keeps following the data; double-click returns to the range
+            YRange::Fixed { min, max } => [min, max].into_iter()
+                .filter_map(|limit| self.position(limit))
+                .fold(plot.auto_bounds([true, false]), |plot, limit| plot.include_y(limit)),
+        };
+        let plot = if self.log {
+            plot.y_axis_formatter(|mark, _range| format_value(10f64.powf(mark.value)))
+        } else {
+            plot
+        };
+        let Some(conversion) = self.secondary else {
+            return plot;
+        };
+        // The second axis labels the grid lines of the first with converted values
+        let settings = *self;
+        let mut main = AxisHints::new_y();
+        if self.log {
+            main = main.formatter(|mark, _range| format_value(10f64.powf(mark.value)));
+        }
+        let secondary = AxisHints::new_y()
+            .placement(HPlacement::Right)
+            .label(format!("Value [{}]", conversion.unit()))
+            .formatter(move |mark, _range| format_value(conversion.apply(settings.value(mark.value))));
+        plot.custom_y_axes(vec![main, secondary])
+    }
+}
+
+/// A value for a tick or hover label, in scientific notation when very large or small
+pub fn format_value(value: f64) -> String {
+    if value == 0.0 || (1e-3..1e5).contains(&value.abs()) {
+        let text = format!("{:.4}", value);
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        format!("{:.2e}", value)
+    }
+}
+
+/// "Y axis" menu under a plot; `data_range` seeds the limits when switching to a fixed range
+/// and `unit` picks the conversions offered for a second axis. Returns whether the settings changed.
+pub fn menu(ui: &mut egui::Ui, settings: &mut YAxisSettings, data_range: Option<(f64, f64)>, unit: &str) -> bool {
+    let mut changed = false;
+    ui.menu_button("↕ Y axis", |ui| {
+        let fixed = matches!(settings.range, YRange::Fixed { .. });
+        ui.horizontal(|ui| {
+            if ui.radio(!fixed, "Autoscale").clicked() && fixed {
+                settings.range = YRange::default();
+                changed = true;
+            }
+            if ui.radio(fixed, "Fixed range").clicked() && !fixed {
+                let (min, max) = data_range.filter(|(min, max)| max > min).unwrap_or((0.0, 1.0));
+                settings.range = YRange::Fixed { min, max };
+                changed = true;
+            }
+        });
+
+        match &mut settings.range {
+            YRange::Auto { margin } => {
+                ui.horizontal(|ui| {
+                    ui.label("Margin:");
+                    let mut percent = *margin * 100.0;
+                    if ui.add(egui::DragValue::new(&mut percent).range(0.0..=100.0).suffix(" %")).changed() {
+                        *margin = percent / 100.0;
+                        changed = true;
+                    }
+                });
+            }
+            YRange::Fixed { min, max } => {
+                egui::Grid::new("y_axis_range").num_columns(2).show(ui, |ui| {
+                    ui.label("Max:");
+                    changed |= ui.add(egui::DragValue::new(max).speed(0.1)).changed();
+                    ui.end_row();
+                    ui.label("Min:");
+                    changed |= ui.add(egui::DragValue::new(min).speed(0.1)).changed();
+                    ui.end_row();
+                });
+                if *max <= *min {
+                    ui.colored_label(egui::Color32::from_rgb(255, 100, 100), "⚠ Max must be above min");
+                }
+            }
+        }
+
+        changed |= ui.checkbox(&mut settings.log, "Logarithmic").changed();
+        if settings.log {
+            ui.weak("Values of zero or below are not drawn");
+            if let YRange::Fixed { min, .. } = settings.range {
+                if min <= 0.0 {
+                    ui.colored_label(egui::Color32::from_rgb(255, 165, 0), "Min must be above zero on a log axis");
+                }
+            }
+        }
+
+        ui.separator();
+        ui.label("Second axis:");
+        // Conversions from the unit of the plot; all of them when it has no known unit
+        let matching: Vec<UnitConversion> = UnitConversion::ALL.into_iter().filter(|conversion| conversion.converts(unit)).collect();
+        let offered = if matching.is_empty() { UnitConversion::ALL.to_vec() } else { matching };
+        changed |= ui.selectable_value(&mut settings.secondary, None, "None").changed();
+        for conversion in offered {
+            changed |= ui.selectable_value(&mut settings.secondary, Some(conversion), conversion.label()).changed();
+        }
+    });
+    changed
+}
+
+/// Smallest and largest value of the points
+pub fn value_range(points: &[[f64; 2]]) -> Option<(f64, f64)> {
+    let min = points.iter().map(|point| point[1]).reduce(f64::min)?;
+    let max = points.iter().map(|point| point[1]).reduce(f64::max)?;
+    Some((min, max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_y_axis_log_scale_and_settings() {
+        let linear = YAxisSettings::default();
+        assert_eq!(linear.transform(vec![[0.0, -2.0]]), vec![[0.0, -2.0]]);
+
+        let log = YAxisSettings { range: YRange::Fixed { min: 1.0, max: 1000.0 }, log: true, secondary: None };
+        assert_eq!(log.transform(vec![[0.0, 100.0], [1.0, 0.0], [2.0, -5.0], [3.0, 1.0]]), vec![[0.0, 2.0], [3.0, 0.0]]);
+        assert_eq!(log.position(0.0), None);
+        assert!((log.value(3.0) - 1000.0).abs() < 1e-9);
+
+        assert_eq!(format_value(1000.0), "1000");
+        assert_eq!(format_value(0.25), "0.25");
+        assert_eq!(format_value(1e6), "1.00e6");
+        assert_eq!(format_value(2e-5), "2.00e-5");
+
+        assert_eq!(value_range(&[[0.0, 3.0], [1.0, -1.0], [2.0, 2.0]]), Some((-1.0, 3.0)));
+        assert_eq!(value_range(&[]), None);
+
+        let fahrenheit = YAxisSettings { secondary: Some(UnitConversion::CelsiusToFahrenheit), ..linear };
+        assert_eq!(fahrenheit.hover_text(100.0), "100 (212 °F)");
+        assert!((UnitConversion::FahrenheitToCelsius.apply(212.0) - 100.0).abs() < 1e-9);
+        assert!((UnitConversion::RadPerSecondToRpm.apply(PI) - 30.0).abs() < 1e-9);
+        assert!(UnitConversion::RpmToRadPerSecond.converts("RPM"));
+        assert!(!UnitConversion::BarToPsi.converts("°C"));
+
+        #[derive(Serialize, Deserialize)]
+        struct Wrapper {
+            axis: YAxisSettings,
+        }
+        let text = toml::to_string(&Wrapper { axis: log }).unwrap();
+        assert_eq!(toml::from_str::<Wrapper>(&text).unwrap().axis, log);
+        let text = toml::to_string(&Wrapper { axis: fahrenheit }).unwrap();
+        assert_eq!(toml::from_str::<Wrapper>(&text).unwrap().axis, fahrenheit);
+        assert_eq!(toml::from_str::<Wrapper>("[axis]\n").unwrap().axis, linear);
+    }
+}
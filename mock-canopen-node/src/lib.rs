@@ -0,0 +1,13 @@
+//! # Mock CANopen Node
+//!
+//! The simulated node behind the `mock-node` binary, usable without a CAN socket.
+//!
+//! [`MockNode`] answers frames handed to it and produces its TPDO on request, so the
+//! same node can sit on a SocketCAN interface (the binary) or run in-process (the
+//! viewer's simulation backend).
+
+pub mod node;
+pub mod object_dictionary;
+pub mod sdo_server;
+
+pub use node::{MockNode, TPDO_INTERVAL};
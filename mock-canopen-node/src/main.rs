@@ -15,13 +15,9 @@
 //! cargo run -p mock-canopen-node -- --interface vcan0 --node-id 4
 //! ```
 
-mod object_dictionary;
-mod sdo_server;
-
-use socketcan::{CanSocket, Socket, CanFrame, StandardId, EmbeddedFrame};
+use socketcan::{CanSocket, Socket, EmbeddedFrame};
 use std::time::{Duration, Instant};
-use object_dictionary::ObjectDictionary;
-use sdo_server::SdoServer;
+use mock_canopen_node::{MockNode, TPDO_INTERVAL};
 
 fn main() {
     // Parse command line arguments (simplified for now)
@@ -64,18 +60,14 @@ fn main() {
     socket.set_read_timeout(Duration::from_millis(10))
         .expect("Failed to set socket timeout");
 
-    // Create object dictionary with test data
-    let mut object_dict = ObjectDictionary::new();
-    object_dict.add_test_objects_for_node(node_id);
+    // Create the node (SDO server over an object dictionary with test data)
+    let mut node = MockNode::new(node_id);
 
-    println!("✓ Object dictionary loaded with {} objects", object_dict.len());
+    println!("✓ Object dictionary loaded with {} objects", node.object_dict().len());
     println!("\n📋 Available SDO Objects:");
-    object_dict.print_summary();
+    node.object_dict().print_summary();
     println!();
 
-    // Create SDO server
-    let mut sdo_server = SdoServer::new(node_id, object_dict);
-
     println!("🚀 Mock node is running!");
     println!("   Waiting for SDO requests on COB-ID 0x{:03X}...", 0x600 + node_id as u16);
     println!("   Broadcasting TPDO1 on COB-ID 0x{:03X} every 100ms", 0x180 + node_id as u16);
@@ -84,15 +76,14 @@ fn main() {
 
     // TPDO broadcasting state
     let mut last_tpdo_time = Instant::now();
-    let tpdo_interval = Duration::from_millis(100);
 
     // Main loop: listen for CAN frames and respond to SDO requests
     loop {
         // Handle incoming SDO requests
         match socket.read_frame() {
             Ok(frame) => {
-                // Let the SDO server handle the frame
-                if let Some(response_frame) = sdo_server.handle_frame(&frame) {
+                // Let the node handle the frame
+                if let Some(response_frame) = node.handle_frame(&frame) {
                     // Send the response
                     if let Err(e) = socket.write_frame(&response_frame) {
                         eprintln!("⚠ Failed to send response: {}", e);
@@ -109,38 +100,18 @@ fn main() {
         }
 
         // Broadcast TPDO periodically
-        if last_tpdo_time.elapsed() >= tpdo_interval {
-            // Read current values from Object Dictionary
-            // TPDO1 mapping: 0x2000:01 (CabinTemperature, Real32), 0x2000:02 (OutsideTemperature, Real32)
-            let cabin_temp = sdo_server.object_dict().get(0x2000, 0x01);
-            let outside_temp = sdo_server.object_dict().get(0x2000, 0x02);
-
-            if let (Some((cabin_data, _)), Some((outside_data, _))) = (cabin_temp, outside_temp) {
-                // Create TPDO frame
-                // TPDO1 COB-ID = 0x180 + node_id
-                let tpdo_cob_id = 0x180 + node_id as u16;
-
-                if let Some(std_id) = StandardId::new(tpdo_cob_id) {
-                    let mut data = [0u8; 8];
-
-                    // Pack data according to TPDO mapping
-                    // Bytes 0-3: CabinTemperature (Real32, little-endian)
-                    data[0..4].copy_from_slice(&cabin_data[..4]);
-                    // Bytes 4-7: OutsideTemperature (Real32, little-endian)
-                    data[4..8].copy_from_slice(&outside_data[..4]);
-
-                    if let Some(frame) = CanFrame::new(std_id, &data) {
-                        if let Err(e) = socket.write_frame(&frame) {
-                            eprintln!("⚠ Failed to send TPDO: {}", e);
-                        } else {
-                            // Decode for display
-                            let cabin_f32 = f32::from_le_bytes([cabin_data[0], cabin_data[1], cabin_data[2], cabin_data[3]]);
-                            let outside_f32 = f32::from_le_bytes([outside_data[0], outside_data[1], outside_data[2], outside_data[3]]);
-                            print!("📤 TPDO1: CabinTemp={:.2}°C, OutsideTemp={:.2}°C\r", cabin_f32, outside_f32);
-                            use std::io::Write;
-                            std::io::stdout().flush().ok();
-                        }
-                    }
+        if last_tpdo_time.elapsed() >= TPDO_INTERVAL {
+            if let Some(frame) = node.tpdo1_frame() {
+                if let Err(e) = socket.write_frame(&frame) {
+                    eprintln!("⚠ Failed to send TPDO: {}", e);
+                } else {
+                    // Decode for display
+                    let data = frame.data();
+                    let cabin_f32 = f32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+                    let outside_f32 = f32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+                    print!("📤 TPDO1: CabinTemp={:.2}°C, OutsideTemp={:.2}°C\r", cabin_f32, outside_f32);
+                    use std::io::Write;
+                    std::io::stdout().flush().ok();
                 }
             }
 
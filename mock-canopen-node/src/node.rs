@@ -0,0 +1,64 @@
+//! A complete simulated node: SDO server plus TPDO1 generation
+
+use std::time::Duration;
+use socketcan::{CanFrame, StandardId, EmbeddedFrame};
+use crate::object_dictionary::ObjectDictionary;
+use crate::sdo_server::SdoServer;
+
+/// How often TPDO1 is broadcast
+pub const TPDO_INTERVAL: Duration = Duration::from_millis(100);
+
+pub struct MockNode {
+    node_id: u8,
+    sdo_server: SdoServer,
+}
+
+impl MockNode {
+    /// Create a node with the standard test object dictionary
+    pub fn new(node_id: u8) -> Self {
+        let mut object_dict = ObjectDictionary::new();
+        object_dict.add_test_objects_for_node(node_id);
+
+        Self {
+            node_id,
+            sdo_server: SdoServer::new(node_id, object_dict),
+        }
+    }
+
+    pub fn node_id(&self) -> u8 {
+        self.node_id
+    }
+
+    pub fn object_dict(&self) -> &ObjectDictionary {
+        self.sdo_server.object_dict()
+    }
+
+    /// Print every SDO request and response to stdout (on by default)
+    pub fn set_verbose(&mut self, verbose: bool) {
+        self.sdo_server.set_verbose(verbose);
+    }
+
+    /// Handle an incoming CAN frame, returning the response if it was addressed to us
+    pub fn handle_frame(&mut self, frame: &CanFrame) -> Option<CanFrame> {
+        self.sdo_server.handle_frame(frame)
+    }
+
+    /// Build the next TPDO1 frame
+    ///
+    /// TPDO1 mapping: 0x2000:01 (CabinTemperature, Real32), 0x2000:02 (OutsideTemperature, Real32)
+    pub fn tpdo1_frame(&self) -> Option<CanFrame> {
+        let (cabin_data, _) = self.object_dict().get(0x2000, 0x01)?;
+        let (outside_data, _) = self.object_dict().get(0x2000, 0x02)?;
+
+        // TPDO1 COB-ID = 0x180 + node_id
+        let std_id = StandardId::new(0x180 + self.node_id as u16)?;
+        let mut data = [0u8; 8];
+
+        // Bytes 0-3: CabinTemperature (Real32, little-endian)
+        data[0..4].copy_from_slice(&cabin_data[..4]);
+        // Bytes 4-7: OutsideTemperature (Real32, little-endian)
+        data[4..8].copy_from_slice(&outside_data[..4]);
+
+        CanFrame::new(std_id, &data)
+    }
+}
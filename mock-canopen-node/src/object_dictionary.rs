@@ -15,6 +15,7 @@ pub enum ObjectEntry {
 }
 
 /// Object dictionary mapping (index, subindex) to values
+#[derive(Default)]
 pub struct ObjectDictionary {
     entries: HashMap<(u16, u8), ObjectEntry>,
 }
@@ -57,6 +58,10 @@ impl ObjectDictionary {
         self.entries.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
     /// Print a summary of all objects
     pub fn print_summary(&self) {
         let mut indices: Vec<_> = self.entries.keys().collect();
@@ -72,6 +77,7 @@ impl ObjectDictionary {
     }
 
     /// Add standard test objects for demonstration
+    #[allow(dead_code)]  // Kept for callers that don't care about the node ID
     pub fn add_test_objects(&mut self) {
         self.add_test_objects_for_node(4); // Default node ID 4
     }
@@ -9,6 +9,7 @@ pub struct SdoServer {
     object_dict: ObjectDictionary,
     request_cob_id: u16,  // 0x600 + node_id
     response_cob_id: u16, // 0x580 + node_id
+    verbose: bool,        // Print requests and responses
 }
 
 impl SdoServer {
@@ -18,9 +19,15 @@ impl SdoServer {
             object_dict,
             request_cob_id: 0x600 + node_id as u16,
             response_cob_id: 0x580 + node_id as u16,
+            verbose: true,
         }
     }
 
+    /// Enable or disable printing of requests and responses
+    pub fn set_verbose(&mut self, verbose: bool) {
+        self.verbose = verbose;
+    }
+
     /// Get a reference to the object dictionary for TPDO data reads
     pub fn object_dict(&self) -> &ObjectDictionary {
         &self.object_dict
@@ -51,7 +58,9 @@ impl SdoServer {
 
         // Check if this is an SDO upload request (0x40)
         if command == 0x40 {
-            println!("📥 SDO Upload Request: Index=0x{:04X}, SubIndex=0x{:02X}", index, subindex);
+            if self.verbose {
+                println!("📥 SDO Upload Request: Index=0x{:04X}, SubIndex=0x{:02X}", index, subindex);
+            }
             return self.create_sdo_response(index, subindex);
         }
 
@@ -66,14 +75,18 @@ impl SdoServer {
                 let response_frame = self.create_expedited_response(index, subindex, &data)?;
 
                 // Log the response
-                let value_str = format_data(&data, &data_type);
-                println!("📤 SDO Response: Value={} (type={:?})", value_str, data_type);
+                if self.verbose {
+                    let value_str = format_data(&data, &data_type);
+                    println!("📤 SDO Response: Value={} (type={:?})", value_str, data_type);
+                }
 
                 Some(response_frame)
             }
             None => {
                 // Object doesn't exist - send abort
-                println!("⚠  Object not found: 0x{:04X}:0x{:02X}", index, subindex);
+                if self.verbose {
+                    println!("⚠  Object not found: 0x{:04X}:0x{:02X}", index, subindex);
+                }
                 self.create_abort_response(index, subindex, 0x06020000) // Object does not exist
             }
         }
@@ -133,7 +146,7 @@ impl SdoServer {
 /// Format data for display based on its type
 fn format_data(data: &[u8], data_type: &SdoDataType) -> String {
     match data_type {
-        SdoDataType::UInt8 if data.len() >= 1 => {
+        SdoDataType::UInt8 if !data.is_empty() => {
             format!("{}", data[0])
         }
         SdoDataType::UInt16 if data.len() >= 2 => {
@@ -142,7 +155,7 @@ fn format_data(data: &[u8], data_type: &SdoDataType) -> String {
         SdoDataType::UInt32 if data.len() >= 4 => {
             format!("{}", u32::from_le_bytes([data[0], data[1], data[2], data[3]]))
         }
-        SdoDataType::Int8 if data.len() >= 1 => {
+        SdoDataType::Int8 if !data.is_empty() => {
             format!("{}", data[0] as i8)
         }
         SdoDataType::Int16 if data.len() >= 2 => {